@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use validator::Validate;
 
+use hopr_chain_rpc::client::validate_rpc_url;
 use hopr_chain_types::ContractAddresses;
 use hopr_primitive_types::primitives::Address;
 
@@ -156,6 +157,8 @@ pub struct ChainNetworkConfig {
     pub max_block_range: u64,
     /// maximum number of RPC requests per second
     pub max_requests_per_sec: Option<u32>,
+    /// whether concurrent identical RPC requests share a single request to the RPC provider
+    pub rpc_request_deduplication: bool,
 }
 
 /// Check whether the version is allowed
@@ -172,11 +175,13 @@ fn satisfies(version: &str, allowed_versions: &str) -> crate::errors::Result<boo
 
 impl ChainNetworkConfig {
     /// Returns the network details, returns an error if network is not supported
+    /// or its RPC provider URL is not valid (see [validate_rpc_url]).
     pub fn new(
         id: &str,
         version: &str,
         maybe_custom_provider: Option<&str>,
         max_rpc_requests_per_sec: Option<u32>,
+        rpc_request_deduplication: bool,
         protocol_config: &mut ProtocolsConfig,
     ) -> Result<Self, String> {
         let network = protocol_config
@@ -193,6 +198,8 @@ impl ChainNetworkConfig {
             chain.default_provider = custom_provider.into();
         }
 
+        validate_rpc_url(&chain.default_provider).map_err(|e| e.to_string())?;
+
         match satisfies(version, network.version_range.as_str()) {
             Ok(true) => Ok(ChainNetworkConfig {
                 announcements: network.addresses.announcements.to_owned(),
@@ -213,6 +220,7 @@ impl ChainNetworkConfig {
                 tx_polling_interval: network.tx_polling_interval,
                 max_block_range: network.max_block_range,
                 max_requests_per_sec: max_rpc_requests_per_sec.or(chain.max_rpc_requests_per_sec),
+                rpc_request_deduplication,
             }),
             Ok(false) => Err(format!(
                 "network {id} is not supported, supported networks {:?}",
@@ -298,6 +306,27 @@ mod test {
         let _ = ProtocolsConfig::default();
     }
 
+    #[test]
+    fn test_chain_network_config_should_reject_an_invalid_rpc_provider_url() {
+        let mut protocol_config = ProtocolsConfig::default();
+        let network = protocol_config
+            .networks
+            .keys()
+            .next()
+            .expect("default protocol config should contain a network")
+            .clone();
+
+        let res = ChainNetworkConfig::new(
+            &network,
+            "1.0.0",
+            Some("ftp://localhost:8545"),
+            None,
+            false,
+            &mut protocol_config,
+        );
+        assert!(res.is_err(), "unsupported RPC url scheme must be rejected");
+    }
+
     #[test]
     fn test_version_is_satisfied_should_work_on_ranges() {
         let actual = satisfies("1.90.0", ">=1.89, <1.93");
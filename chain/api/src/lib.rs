@@ -201,11 +201,14 @@ impl<T: HoprDbAllOperations + Send + Sync + Clone + std::fmt::Debug + 'static> H
         let requestor = DefaultHttpRequestor::new(rpc_http_config);
 
         // Build JSON RPC client
-        let rpc_client = JsonRpcClient::new(
+        let rpc_client = JsonRpcClient::try_new(
             &chain_config.chain.default_provider,
             requestor.clone(),
             rpc_http_retry_policy,
-        );
+        )
+        .expect("RPC provider url must have been validated by the ChainNetworkConfig")
+        .with_unique_id_namespace()
+        .with_request_deduplication(chain_config.rpc_request_deduplication);
 
         // Build RPC operations
         let rpc_operations =
@@ -0,0 +1,87 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hopr_chain_rpc::buffer::BufferPool;
+use serde::Serialize;
+
+const SAMPLE_SIZE: usize = 50;
+
+/// Same shape as the JSON RPC request sent by the client.
+#[derive(Serialize)]
+struct Request<'a, T> {
+    id: u64,
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogFilter {
+    from_block: String,
+    to_block: String,
+    address: String,
+    topics: Vec<String>,
+}
+
+fn log_filters(count: usize) -> Vec<LogFilter> {
+    (0..count)
+        .map(|i| LogFilter {
+            from_block: format!("0x{:x}", 1000 * i),
+            to_block: format!("0x{:x}", 1000 * (i + 1)),
+            address: format!("0x{i:040x}"),
+            topics: (0..4).map(|t| format!("0x{:064x}", i * 4 + t)).collect(),
+        })
+        .collect()
+}
+
+fn request<T>(params: T) -> Request<'static, T> {
+    Request {
+        id: 1,
+        jsonrpc: "2.0",
+        method: "eth_getLogs",
+        params,
+    }
+}
+
+pub fn request_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("request_serialization");
+    group.sample_size(SAMPLE_SIZE);
+
+    let pool = BufferPool::default();
+
+    for filter_count in [1, 100] {
+        let params = log_filters(filter_count);
+        let raw_params = serde_json::value::to_raw_value(&params).expect("params must be serializable");
+
+        // Previously the params were converted into a `serde_json::Value`, which was then serialized
+        // into a fresh string on each attempt.
+        let value_params = serde_json::to_value(&params).expect("params must be serializable");
+        let fresh_string = || {
+            black_box(serde_json::to_string(&request(&value_params)).expect("request must be serializable"));
+        };
+        // Now the params are serialized once and each attempt copies them into a pooled buffer,
+        // without any allocation (see the `buffer_allocations` test).
+        let pooled_buffer = || {
+            black_box(
+                pool.serialize(&request(&raw_params))
+                    .expect("request must be serializable"),
+            );
+        };
+
+        let size = serde_json::to_vec(&request(&raw_params))
+            .expect("request must be serializable")
+            .len();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        for (name, mut serialize) in [
+            ("value_into_fresh_string", Box::new(fresh_string) as Box<dyn FnMut()>),
+            ("raw_into_pooled_buffer", Box::new(pooled_buffer)),
+        ] {
+            group.bench_function(BenchmarkId::new(name, filter_count), |b| b.iter(&mut serialize));
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, request_serialization);
+criterion_main!(benches);
@@ -0,0 +1,165 @@
+//! Pool of reusable buffers the request bodies are serialized into.
+//!
+//! Serializing each request into a fresh buffer allocates it anew and grows it several times
+//! until the whole body fits. The [BufferPool] keeps the buffers of the already sent requests,
+//! so that the following requests of a similar size are serialized without any allocation.
+use serde::Serialize;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Default maximum number of idle buffers kept by the [BufferPool].
+pub const DEFAULT_POOLED_BUFFERS: usize = 16;
+
+/// Buffers with a larger capacity are not returned to the [BufferPool],
+/// so that a single huge request does not pin its memory for good.
+pub const MAX_POOLED_BUFFER_CAPACITY: usize = 4 * 1024 * 1024;
+
+type Buffers = Mutex<Vec<Vec<u8>>>;
+
+/// Bounded pool of reusable byte buffers.
+///
+/// The clones of the pool share the same buffers.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Arc<Buffers>,
+    max_buffers: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_POOLED_BUFFERS)
+    }
+}
+
+impl BufferPool {
+    /// Creates a pool keeping at most `max_buffers` idle buffers.
+    pub fn new(max_buffers: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(max_buffers))),
+            max_buffers,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or allocates a new one if the pool is empty.
+    ///
+    /// The buffer is returned to the pool once the [PooledBuffer] is dropped.
+    pub fn take(&self) -> PooledBuffer {
+        let buffer = self.buffers.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        PooledBuffer {
+            buffer: buffer.unwrap_or_default(),
+            pool: Arc::downgrade(&self.buffers),
+            max_buffers: self.max_buffers,
+        }
+    }
+
+    /// Serializes the `data` as JSON into a buffer taken from the pool.
+    pub fn serialize<T: Serialize + ?Sized>(&self, data: &T) -> serde_json::Result<PooledBuffer> {
+        let mut buffer = self.take();
+        serde_json::to_writer(&mut *buffer, data)?;
+        Ok(buffer)
+    }
+
+    /// Number of idle buffers in the pool.
+    pub fn available(&self) -> usize {
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+/// Buffer taken from a [BufferPool], which is returned into it when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: Vec<u8>,
+    pool: Weak<Buffers>,
+    max_buffers: usize,
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if self.buffer.capacity() == 0 || self.buffer.capacity() > MAX_POOLED_BUFFER_CAPACITY {
+            return;
+        }
+
+        if let Some(pool) = self.pool.upgrade() {
+            let mut buffers = pool.lock().unwrap_or_else(|e| e.into_inner());
+            if buffers.len() < self.max_buffers {
+                let mut buffer = std::mem::take(&mut self.buffer);
+                buffer.clear();
+                buffers.push(buffer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn buffer_pool_should_reuse_the_returned_buffers() -> anyhow::Result<()> {
+        let pool = BufferPool::new(2);
+        assert_eq!(0, pool.available());
+
+        let data = json!({"jsonrpc": "2.0", "id": 1, "method": "eth_getLogs", "params": ["0x1", "0x2"]});
+        let buffer = pool.serialize(&data)?;
+        assert_eq!(serde_json::to_vec(&data)?, *buffer);
+
+        let ptr = buffer.as_ptr();
+        let capacity = buffer.capacity();
+        drop(buffer);
+        assert_eq!(1, pool.available());
+
+        let buffer = pool.serialize(&json!([1, 2, 3]))?;
+        assert_eq!(b"[1,2,3]", buffer.as_ref());
+        assert_eq!(ptr, buffer.as_ptr(), "the returned buffer must be reused");
+        assert_eq!(capacity, buffer.capacity());
+        assert_eq!(0, pool.available());
+
+        Ok(())
+    }
+
+    #[test]
+    fn buffer_pool_should_be_bounded() {
+        let pool = BufferPool::new(2);
+
+        let buffers = (0..3)
+            .map(|_| {
+                let mut buffer = pool.take();
+                buffer.push(1);
+                buffer
+            })
+            .collect::<Vec<_>>();
+        drop(buffers);
+        assert_eq!(2, pool.available());
+
+        let mut huge = pool.take();
+        huge.reserve(MAX_POOLED_BUFFER_CAPACITY + 1);
+        drop(huge);
+        assert_eq!(1, pool.available(), "huge buffer must not be returned");
+
+        // Buffers outliving the pool are just freed
+        let buffer = pool.take();
+        drop(pool);
+        drop(buffer);
+    }
+}
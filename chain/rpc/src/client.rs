@@ -14,7 +14,8 @@
 
 use async_trait::async_trait;
 use ethers::providers::{JsonRpcClient, JsonRpcError};
-use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::{FutureExt, SinkExt, StreamExt};
 use http_types::Method;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -22,19 +23,24 @@ use std::fmt::{Debug, Formatter};
 use std::io::{BufWriter, Write};
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tracing::{debug, error, trace, warn};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, trace, warn};
 use validator::Validate;
 
-use hopr_async_runtime::prelude::sleep;
+use hopr_async_runtime::clock::{Clock, RealClock, Sleep};
 
+use crate::audit::{CallOutcome, CallRecord};
 use crate::client::RetryAction::{NoRetry, RetryAfter};
-use crate::errors::{HttpRequestError, JsonRpcProviderClientError};
-use crate::helper::{Request, Response};
-use crate::{HttpRequestor, RetryAction, RetryPolicy};
+use crate::errors::{HttpRequestError, JsonRpcProviderClientError, RetryReason, RpcErrorKind};
+use crate::helper::{Request, Response, ResultArraySplitter};
+use crate::quirks::QuirksMode;
+use crate::retry::{retry_with_hooks, RetryBudget, RetryError, RetryHooks};
+use crate::stats::ClientStats;
+use crate::usage::CallUsage;
+use crate::{HttpRequestor, RetryAction, RetryPolicy, StreamingHttpRequestor};
 
 #[cfg(all(feature = "prometheus", not(test)))]
-use hopr_metrics::metrics::{MultiCounter, MultiHistogram};
+use hopr_metrics::metrics::{MultiCounter, MultiHistogram, SimpleGauge};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 lazy_static::lazy_static! {
@@ -58,6 +64,23 @@ lazy_static::lazy_static! {
         &["call"]
     )
     .unwrap();
+    static ref METRIC_RPC_RETRY_REASONS: MultiCounter = MultiCounter::new(
+        "hopr_rpc_retry_reasons",
+        "Number of retries of RPC calls by the reason of the retry",
+        &["method", "reason"]
+    )
+    .unwrap();
+    static ref METRIC_RPC_TERMINAL_FAILURES: MultiCounter = MultiCounter::new(
+        "hopr_rpc_terminal_failures",
+        "Number of RPC calls that failed without any further retries by the reason of the last failure",
+        &["reason"]
+    )
+    .unwrap();
+    static ref METRIC_RPC_INFLIGHT_REQUESTS: SimpleGauge = SimpleGauge::new(
+        "hopr_rpc_inflight_requests",
+        "Number of RPC requests over HTTP currently in flight"
+    )
+    .unwrap();
 }
 
 /// Defines a retry policy suitable for `JsonRpcProviderClient`.
@@ -80,8 +103,14 @@ lazy_static::lazy_static! {
 /// Transport and connection errors (such as connection timeouts) are retried without backoff
 /// at a constant delay of `initial_backoff` if `backoff_on_transport_errors` is not set.
 ///
+/// If `min_backoff` is set, none of the delays is shorter than it.
+///
 /// No more additional retries are allowed on new requests, if the maximum number of concurrent
 /// requests being retried has reached `max_retry_queue_size`.
+///
+/// If `max_retries_per_sec` is set, the retries of all the requests of a [JsonRpcProviderClient] and its clones
+/// together are limited by a token bucket refilled at this rate and holding up to `retry_burst` retries.
+/// Once the bucket is exhausted, the failed requests are not retried.
 #[derive(Clone, Debug, PartialEq, smart_default::SmartDefault, Serialize, Deserialize, Validate)]
 pub struct SimpleJsonRpcRetryPolicy {
     /// Minimum number of retries of any error, regardless the error code.
@@ -123,11 +152,21 @@ pub struct SimpleJsonRpcRetryPolicy {
     /// Default is 30 seconds.
     #[default(Duration::from_secs(30))]
     pub max_backoff: Duration,
+    /// Minimum backoff value.
+    ///
+    /// If set, every delay between retries is at least this long, regardless of the `initial_backoff`
+    /// and `backoff_coefficient`. Takes precedence over `max_backoff` if greater.
+    ///
+    /// Default is `None`.
+    pub min_backoff: Option<Duration>,
     /// Indicates whether to also apply backoff to transport and connection errors (such as connection timeouts).
     ///
     /// Default is false.
     pub backoff_on_transport_errors: bool,
-    /// List of JSON RPC errors that should be retried with backoff
+    /// List of JSON RPC error codes that should be retried with backoff.
+    ///
+    /// Errors classified as [RpcErrorKind::RateLimited] are always retried, this list
+    /// serves as an override for additional (provider-specific) error codes.
     ///
     /// Default is \[429, -32005, -32016\]
     #[default(_code = "vec![-32005, -32016, 429]")]
@@ -135,6 +174,9 @@ pub struct SimpleJsonRpcRetryPolicy {
 
     /// List of HTTP errors that should be retried with backoff.
     ///
+    /// If the provider announces when to retry (via `Retry-After` or `x-ratelimit-reset` headers),
+    /// the announced delay (capped at `max_backoff`) is used instead of the computed backoff.
+    ///
     /// Default is \[429, 504, 503\]
     #[default(
         _code = "vec![http_types::StatusCode::TooManyRequests,http_types::StatusCode::GatewayTimeout,http_types::StatusCode::ServiceUnavailable]"
@@ -148,16 +190,35 @@ pub struct SimpleJsonRpcRetryPolicy {
     #[validate(range(min = 5))]
     #[default = 100]
     pub max_retry_queue_size: u32,
+    /// Maximum average number of retries per second made by all the requests together.
+    ///
+    /// This puts a hard ceiling on the load caused by the retries, regardless of how many
+    /// different requests are failing.
+    ///
+    /// Default is `None` (no limit).
+    #[validate(range(min = 0.0))]
+    pub max_retries_per_sec: Option<f64>,
+    /// Maximum number of retries that can be made at once within `max_retries_per_sec`.
+    ///
+    /// Default is 10
+    #[validate(range(min = 1))]
+    #[default = 10]
+    pub retry_burst: u32,
 }
 
 impl SimpleJsonRpcRetryPolicy {
     fn is_retryable_json_rpc_error(&self, err: &JsonRpcError) -> bool {
-        self.retryable_json_rpc_errors.contains(&err.code) || err.message.contains("rate limit")
+        self.retryable_json_rpc_errors.contains(&err.code)
+            || RpcErrorKind::from_json_rpc_error(err) == RpcErrorKind::RateLimited
     }
 
     fn is_retryable_http_error(&self, status: &http_types::StatusCode) -> bool {
         self.retryable_http_errors.contains(status)
     }
+
+    fn clamp_backoff(&self, backoff: Duration) -> Duration {
+        self.min_backoff.map_or(backoff, |min| backoff.max(min))
+    }
 }
 
 impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
@@ -167,6 +228,11 @@ impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
         num_retries: u32,
         retry_queue_size: u32,
     ) -> RetryAction {
+        // The request has already waited too long for a free slot, so it must fail fast
+        if matches!(err, JsonRpcProviderClientError::ConcurrencyLimitReached { .. }) {
+            return NoRetry;
+        }
+
         if self.max_retries.is_some_and(|max| num_retries > max) {
             warn!(
                 count = self.max_retries.expect("max_retries must be set"),
@@ -193,6 +259,7 @@ impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
             .initial_backoff
             .mul_f64(f64::powi(1.0 + self.backoff_coefficient, (num_retries - 1) as i32))
             .min(self.max_backoff);
+        let backoff = self.clamp_backoff(backoff);
 
         // Retry if a global minimum of number of retries was given and wasn't yet attained
         if self.min_retries.is_some_and(|min| num_retries <= min) {
@@ -207,12 +274,16 @@ impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
                 RetryAfter(backoff)
             }
 
-            // Retryable HTTP errors are retries with backoff
-            JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(e))
+            // Retryable HTTP errors are retries with backoff, unless the provider says when to retry
+            JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(e, retry_after))
                 if self.is_retryable_http_error(e) =>
             {
-                debug!(error = ?e, "encountered retryable HTTP error code");
-                RetryAfter(backoff)
+                debug!(error = ?e, ?retry_after, "encountered retryable HTTP error code");
+                RetryAfter(
+                    retry_after
+                        .map(|d| self.clamp_backoff(d.min(self.max_backoff)))
+                        .unwrap_or(backoff),
+                )
             }
 
             // Transport error and timeouts are retried at a constant rate if specified
@@ -223,10 +294,17 @@ impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
                 RetryAfter(if self.backoff_on_transport_errors {
                     backoff
                 } else {
-                    self.initial_backoff
+                    self.clamp_backoff(self.initial_backoff)
                 })
             }
 
+            // Permanent errors would fail again the same way
+            JsonRpcProviderClientError::BackendError(e @ HttpRequestError::PermanentError(_))
+            | JsonRpcProviderClientError::BackendError(e @ HttpRequestError::UnsupportedMethod(_)) => {
+                debug!(error = %e, "encountered non-retryable transport error");
+                NoRetry
+            }
+
             // Some providers send invalid JSON RPC in the error case (no `id:u64`), but the text is a `JsonRpcError`
             JsonRpcProviderClientError::SerdeJson { text, .. } => {
                 #[derive(Deserialize)]
@@ -250,861 +328,4456 @@ impl RetryPolicy<JsonRpcProviderClientError> for SimpleJsonRpcRetryPolicy {
             _ => NoRetry,
         }
     }
+
+    fn retry_rate(&self) -> Option<(f64, u32)> {
+        self.max_retries_per_sec.map(|rate| (rate, self.retry_burst))
+    }
+}
+
+/// [RetryPolicy] of the requests of a [JsonRpcProviderClient], which limits the retries allowed by
+/// the client's retry policy by the [RetryBudget] shared by all the clones of the client.
+struct BudgetedRetryPolicy<'a, R> {
+    policy: &'a R,
+    budget: &'a RetryBudget,
+}
+
+impl<R: RetryPolicy<JsonRpcProviderClientError>> RetryPolicy<JsonRpcProviderClientError>
+    for BudgetedRetryPolicy<'_, R>
+{
+    fn is_retryable_error(
+        &self,
+        err: &JsonRpcProviderClientError,
+        num_retries: u32,
+        retry_queue_size: u32,
+    ) -> RetryAction {
+        match self.policy.is_retryable_error(err, num_retries, retry_queue_size) {
+            RetryAfter(_)
+                if self
+                    .retry_rate()
+                    .is_some_and(|(rate, burst)| !self.budget.try_acquire(rate, burst)) =>
+            {
+                warn!(rate = ?self.retry_rate(), "retry budget has been exhausted");
+                NoRetry
+            }
+            action => action,
+        }
+    }
+
+    fn retry_rate(&self) -> Option<(f64, u32)> {
+        self.policy.retry_rate()
+    }
 }
 
 /// Modified implementation of `ethers::providers::Http` so that it can
 /// operate with any `HttpPostRequestor`.
 /// Also contains possible retry actions to be taken on various failures, therefore it
 /// implements also `ethers::providers::RetryClient` functionality.
-pub struct JsonRpcProviderClient<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>> {
-    id: AtomicU64,
+///
+/// The waits between the retries are timed by the [Clock] `C` (see [JsonRpcProviderClient::with_clock]).
+pub struct JsonRpcProviderClient<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>, C: Clock = RealClock> {
+    id: Arc<AtomicU64>,
+    id_nonce: u64,
+    id_high_water_mark: Option<Arc<IdHighWaterMark>>,
+    in_flight: Option<moka::future::Cache<String, Arc<str>>>,
+    archive: Option<Arc<ArchiveEndpoint>>,
+    retry_cancellation: RetryCancellation,
+    retry_budget: RetryBudget,
+    stats: Arc<ClientStats>,
+    clock: C,
+    concurrency_limit: Option<Arc<ConcurrencyLimit>>,
+    quirks: Option<Arc<QuirksMode>>,
     requests_enqueued: AtomicU32,
-    url: String,
+    url: Arc<std::sync::RwLock<Arc<str>>>,
     requestor: Req,
     retry_policy: R,
 }
 
-impl<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>> JsonRpcProviderClient<Req, R> {
-    /// Creates the client given the `HttpPostRequestor`
-    pub fn new(base_url: &str, requestor: Req, retry_policy: R) -> Self {
+/// Token that cancels the pending retries of the requests of all the [JsonRpcProviderClient]s sharing it.
+///
+/// Once cancelled, the requests waiting for their next retry fail immediately with
+/// [JsonRpcProviderClientError::Cancelled], and the failed requests are no longer retried.
+/// The requests currently in progress are not interrupted.
+#[derive(Clone)]
+pub struct RetryCancellation {
+    cancelled: Arc<AtomicBool>,
+    trigger: Arc<std::sync::Mutex<Option<futures::channel::oneshot::Sender<()>>>>,
+    signal: futures::future::Shared<futures::channel::oneshot::Receiver<()>>,
+}
+
+impl Default for RetryCancellation {
+    fn default() -> Self {
+        let (trigger, signal) = futures::channel::oneshot::channel();
         Self {
-            id: AtomicU64::new(1),
-            requests_enqueued: AtomicU32::new(0),
-            url: base_url.to_owned(),
-            requestor,
-            retry_policy,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            trigger: Arc::new(std::sync::Mutex::new(Some(trigger))),
+            signal: signal.shared(),
         }
     }
+}
 
-    async fn send_request_internal<T, A>(&self, method: &str, params: T) -> Result<A, JsonRpcProviderClientError>
-    where
-        T: Serialize + Send + Sync,
-        A: DeserializeOwned,
-    {
-        // Create the Request object
-        let next_id = self.id.fetch_add(1, Ordering::SeqCst);
-        let payload = Request::new(next_id, method, params);
+impl Debug for RetryCancellation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryCancellation")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
 
-        debug!(method, "sending rpc request");
-        trace!(
-            method,
-            request = serde_json::to_string(&payload).expect("request must be serializable"),
-            "sending rpc request",
-        );
+impl RetryCancellation {
+    /// Cancels all the pending and future retries.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        // Dropping the sender resolves the signal for all the waiting requests
+        self.trigger.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
 
-        // Perform the actual request
-        let start = std::time::Instant::now();
-        let body = self.requestor.http_post(self.url.as_ref(), payload).await?;
-        let req_duration = start.elapsed();
+    /// Indicates whether the retries have been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
 
-        trace!(method, duration_in_ms = req_duration.as_millis(), "rpc request took");
+    /// Waits for the given backoff using the `clock`, unless the retries get cancelled in the meantime.
+    ///
+    /// Returns `false` if the retries have been cancelled.
+    async fn backoff(&self, clock: &impl Clock, backoff: Duration) -> bool {
+        if self.is_cancelled() {
+            return false;
+        }
 
-        #[cfg(all(feature = "prometheus", not(test)))]
-        METRIC_RPC_CALLS_TIMING.observe(&[method], req_duration.as_secs_f64());
+        let delay = clock.sleep(backoff);
+        let signal = self.signal.clone();
+        futures::pin_mut!(delay);
+        matches!(
+            futures::future::select(delay, signal).await,
+            futures::future::Either::Left(_)
+        )
+    }
+}
 
-        // First deserialize the Response object
-        let raw = match serde_json::from_slice(&body) {
-            Ok(Response::Success { result, .. }) => result.to_owned(),
-            Ok(Response::Error { error, .. }) => {
-                #[cfg(all(feature = "prometheus", not(test)))]
-                METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+/// [Clock] that records all the requested sleeps and completes them immediately,
+/// so that the retries can be tested deterministically without any real waiting.
+///
+/// The time of the clock advances by each requested sleep. The clones of the clock share
+/// the recorded sleeps and the time.
+///
+/// This is useful for testing only and should **NOT** be used in production.
+#[derive(Debug, Clone)]
+pub struct RecordingClock {
+    start: Instant,
+    delays: Arc<std::sync::Mutex<Vec<Duration>>>,
+}
 
-                return Err(error.into());
-            }
-            Ok(_) => {
-                let err = JsonRpcProviderClientError::SerdeJson {
-                    err: serde::de::Error::custom("unexpected notification over HTTP transport"),
-                    text: String::from_utf8_lossy(&body).to_string(),
-                };
-                #[cfg(all(feature = "prometheus", not(test)))]
-                METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+impl Default for RecordingClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            delays: Default::default(),
+        }
+    }
+}
 
-                return Err(err);
-            }
-            Err(err) => {
-                #[cfg(all(feature = "prometheus", not(test)))]
-                METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+impl RecordingClock {
+    /// All the sleeps requested so far, in the order of the requests.
+    pub fn delays(&self) -> Vec<Duration> {
+        self.delays.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
 
-                return Err(JsonRpcProviderClientError::SerdeJson {
-                    err,
-                    text: String::from_utf8_lossy(&body).to_string(),
-                });
-            }
+impl Clock for RecordingClock {
+    fn now(&self) -> Instant {
+        self.start
+            + self
+                .delays
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .sum::<Duration>()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.delays.lock().unwrap_or_else(|e| e.into_inner()).push(duration);
+        futures::future::ready(()).boxed()
+    }
+}
+
+/// Secondary endpoint of an archive node, which serves the queries against old blocks
+/// (see [JsonRpcProviderClient::with_archive_endpoint]).
+#[derive(Debug)]
+struct ArchiveEndpoint {
+    url: Arc<str>,
+    depth: u64,
+    latest_block: AtomicU64,
+}
+
+impl ArchiveEndpoint {
+    /// Indicates whether the given block is older than the depth, given the latest block observed so far.
+    fn serves(&self, block: u64) -> bool {
+        let latest_block = self.latest_block.load(Ordering::Relaxed);
+        latest_block > 0 && latest_block.saturating_sub(block) > self.depth
+    }
+}
+
+/// Limit of the number of requests in flight at the same time
+/// (see [JsonRpcProviderClient::with_max_concurrent_requests]).
+#[derive(Debug)]
+struct ConcurrencyLimit {
+    permits: Arc<async_lock::Semaphore>,
+    max_concurrent_requests: usize,
+    max_wait: Option<Duration>,
+}
+
+impl ConcurrencyLimit {
+    /// Waits for a free slot, but at most for `max_wait` if set, as timed by the `clock`.
+    async fn acquire(&self, clock: &impl Clock) -> Result<async_lock::SemaphoreGuardArc, JsonRpcProviderClientError> {
+        if let Some(permit) = self.permits.try_acquire_arc() {
+            return Ok(permit);
+        }
+
+        let Some(max_wait) = self.max_wait else {
+            return Ok(self.permits.acquire_arc().await);
         };
 
-        // Next, deserialize the data out of the Response object
-        let json_str = raw.get();
-        trace!(method, response = &json_str, "rpc request response received");
+        let acquire = std::pin::pin!(self.permits.acquire_arc());
+        let timeout = clock.sleep(max_wait);
+        match futures::future::select(acquire, timeout).await {
+            futures::future::Either::Left((permit, _)) => Ok(permit),
+            futures::future::Either::Right(_) => Err(JsonRpcProviderClientError::ConcurrencyLimitReached {
+                max_concurrent_requests: self.max_concurrent_requests,
+                waited: max_wait,
+            }),
+        }
+    }
+}
 
-        let res = serde_json::from_str(json_str).map_err(|err| JsonRpcProviderClientError::SerdeJson {
-            err,
-            text: raw.to_string(),
-        })?;
+/// Request counted as in flight until dropped, holding its slot of the [ConcurrencyLimit] if there is one.
+struct InflightRequest {
+    _permit: Option<async_lock::SemaphoreGuardArc>,
+}
 
+impl InflightRequest {
+    fn new(permit: Option<async_lock::SemaphoreGuardArc>) -> Self {
         #[cfg(all(feature = "prometheus", not(test)))]
-        METRIC_COUNT_RPC_CALLS.increment(&[method, "success"]);
+        METRIC_RPC_INFLIGHT_REQUESTS.increment(1.0);
 
-        Ok(res)
+        Self { _permit: permit }
     }
 }
 
-impl<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>> Debug for JsonRpcProviderClient<Req, R> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("JsonRpcProviderClient")
-            .field("id", &self.id)
-            .field("url", &self.url)
-            .field("requests_enqueued", &self.requests_enqueued)
-            .finish_non_exhaustive()
+impl Drop for InflightRequest {
+    fn drop(&mut self) {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_RPC_INFLIGHT_REQUESTS.decrement(1.0);
     }
 }
 
-impl<Req: HttpRequestor + Clone, R: RetryPolicy<JsonRpcProviderClientError> + Clone> Clone
-    for JsonRpcProviderClient<Req, R>
-{
-    fn clone(&self) -> Self {
-        Self {
-            id: AtomicU64::new(1),
-            url: self.url.clone(),
-            requests_enqueued: AtomicU32::new(0),
-            requestor: self.requestor.clone(),
-            retry_policy: self.retry_policy.clone(),
-        }
+/// Position of the block parameter of the historical JSON RPC queries, or `None` for the other methods.
+fn block_param_position(method: &str) -> Option<usize> {
+    match method {
+        "eth_getLogs" | "eth_getBlockByNumber" | "eth_getBlockTransactionCountByNumber" => Some(0),
+        "eth_call" | "eth_getBalance" | "eth_getCode" | "eth_getTransactionCount" => Some(1),
+        "eth_getStorageAt" => Some(2),
+        _ => None,
     }
 }
 
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl<Req, R> JsonRpcClient for JsonRpcProviderClient<Req, R>
-where
-    Req: HttpRequestor,
-    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
-{
-    type Error = JsonRpcProviderClientError;
+/// Extracts the block number a historical JSON RPC query is made against.
+///
+/// Returns `None` for methods that do not query a particular block, and for queries against
+/// a block tag other than `earliest` (e.g. `latest`) or against a block hash.
+fn requested_block(method: &str, params: &serde_json::Value) -> Option<u64> {
+    let block = params.get(block_param_position(method)?)?;
+    let block = match method {
+        "eth_getLogs" => block.get("fromBlock")?,
+        _ => block,
+    };
 
-    async fn request<T, A>(&self, method: &str, params: T) -> Result<A, Self::Error>
-    where
-        T: Serialize + Send + Sync,
-        A: DeserializeOwned + Send,
-    {
-        // Helper type that caches the `params` value across several retries
-        // This is necessary because the wrapper provider is supposed to skip he `params` if it's of
-        // size 0, see `crate::transports::common::Request`
-        enum RetryParams<Params> {
-            Value(Params),
-            Zst(()),
-        }
+    // EIP-1898 style block parameter
+    let block = block.get("blockNumber").unwrap_or(block).as_str()?;
+    match block {
+        "earliest" => Some(0),
+        _ => u64::from_str_radix(block.strip_prefix("0x")?, 16).ok(),
+    }
+}
 
-        let params = if std::mem::size_of::<A>() == 0 {
-            RetryParams::Zst(())
-        } else {
-            let params = serde_json::to_value(params)
-                .map_err(|err| JsonRpcProviderClientError::SerdeJson { err, text: "".into() })?;
-            RetryParams::Value(params)
-        };
+/// Number of bits of the JSON RPC request id used for the sequence number of the request.
+///
+/// The bits above are used by the client instance nonce (see [JsonRpcProviderClient::with_unique_id_namespace]).
+/// The whole id always fits into 53 bits to remain a safe integer for JavaScript-based providers.
+const RPC_ID_SEQUENCE_BITS: u32 = 40;
+const RPC_ID_SEQUENCE_MASK: u64 = (1 << RPC_ID_SEQUENCE_BITS) - 1;
+const RPC_ID_NONCE_MASK: u64 = (1 << 13) - 1;
 
-        self.requests_enqueued.fetch_add(1, Ordering::SeqCst);
-        let start = std::time::Instant::now();
+/// Instance nonce to be assigned to the next [JsonRpcProviderClient] with a unique id namespace.
+/// The nonce 0 is the default namespace.
+static NEXT_RPC_CLIENT_NONCE: AtomicU64 = AtomicU64::new(1);
 
-        let mut num_retries = 0;
-        loop {
-            let err;
+/// Number of request ids reserved at once by the [IdHighWaterMark].
+const RPC_ID_RESERVATION_BLOCK: u64 = 1000;
 
-            // hack to not hold `A` across an await in the sleep future and prevent requiring
-            // A: Send + Sync
-            {
-                let resp = match params {
-                    RetryParams::Value(ref params) => self.send_request_internal(method, params).await,
-                    RetryParams::Zst(unit) => self.send_request_internal(method, unit).await,
-                };
+/// Persists the upper bound of the request ids that have been used by a [JsonRpcProviderClient].
+///
+/// The ids are reserved in blocks of [RPC_ID_RESERVATION_BLOCK], so the file is not written on every request.
+/// The next block is reserved once half of the current block has been used, so the requests normally
+/// do not wait for the file to be written.
+#[derive(Debug)]
+struct IdHighWaterMark {
+    path: Arc<str>,
+    reserved: AtomicU64,
+    reserving: async_lock::Mutex<()>,
+}
+
+impl IdHighWaterMark {
+    fn load(path: &str) -> std::io::Result<Self> {
+        let reserved = match std::fs::read_to_string(path) {
+            Ok(data) if data.trim().is_empty() => 0,
+            Ok(data) => data.trim().parse::<u64>().map_err(std::io::Error::other)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
 
-                match resp {
-                    Ok(ret) => {
-                        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+        Ok(Self {
+            path: Arc::from(path),
+            reserved: AtomicU64::new(reserved),
+            reserving: async_lock::Mutex::new(()),
+        })
+    }
 
-                        #[cfg(all(feature = "prometheus", not(test)))]
-                        METRIC_RETRIES_PER_RPC_CALL.observe(&[method], num_retries as f64);
+    fn reserved(&self) -> u64 {
+        self.reserved.load(Ordering::SeqCst)
+    }
 
-                        debug!(method, elapsed_in_ms = start.elapsed().as_millis(), "request succeeded",);
-                        return Ok(ret);
-                    }
-                    Err(req_err) => {
-                        err = req_err;
-                        error!(
-                            method,
-                            elapsed_in_ms = start.elapsed().as_millis(),
-                            error = %err,
-                            "request failed",
-                        );
-                        num_retries += 1;
-                    }
-                }
+    /// Makes sure the given id is below the persisted high-water mark.
+    ///
+    /// Waits for the reservation only if the id is not reserved yet, otherwise the next block
+    /// is reserved in advance by at most one of the callers.
+    async fn ensure_reserved(&self, id: u64) {
+        let reserved = self.reserved();
+        if id.saturating_add(RPC_ID_RESERVATION_BLOCK / 2) < reserved {
+            return;
+        }
+
+        if id < reserved {
+            if let Some(_reserving) = self.reserving.try_lock() {
+                self.reserve(id).await;
+            }
+        } else {
+            let _reserving = self.reserving.lock().await;
+            if id >= self.reserved() {
+                self.reserve(id).await;
             }
+        }
+    }
 
-            match self
-                .retry_policy
-                .is_retryable_error(&err, num_retries, self.requests_enqueued.load(Ordering::SeqCst))
-            {
-                NoRetry => {
-                    self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
-                    warn!(method, "no more retries for RPC call");
+    async fn reserve(&self, id: u64) {
+        let new_reserved = id.saturating_add(RPC_ID_RESERVATION_BLOCK).max(self.reserved());
+        let path = self.path.clone();
 
-                    #[cfg(all(feature = "prometheus", not(test)))]
-                    METRIC_RETRIES_PER_RPC_CALL.observe(&[method], num_retries as f64);
+        let (tx, rx) = futures::channel::oneshot::channel();
+        drop(hopr_async_runtime::prelude::spawn_blocking(move || {
+            let _ = tx.send(Self::persist(&path, new_reserved));
+        }));
 
-                    debug!(
-                        method,
-                        duration_in_ms = start.elapsed().as_millis(),
-                        "failed request duration in the retry queue",
-                    );
-                    return Err(err);
-                }
-                RetryAfter(backoff) => {
-                    warn!(method, backoff_in_ms = backoff.as_millis(), "request will retry",);
-                    sleep(backoff).await
-                }
+        match rx.await {
+            Ok(Ok(())) => {
+                self.reserved.fetch_max(new_reserved, Ordering::SeqCst);
+            }
+            Ok(Err(error)) => {
+                error!(%error, path = %self.path, "failed to persist the rpc request id high-water mark")
             }
+            Err(_) => error!(path = %self.path, "rpc request id high-water mark persistence was cancelled"),
         }
     }
+
+    /// Writes the high-water mark into a temporary file which then replaces the persisted one,
+    /// so the persisted high-water mark is never left partially written.
+    fn persist(path: &str, reserved: u64) -> std::io::Result<()> {
+        let tmp_path = format!("{path}.tmp");
+        std::fs::write(&tmp_path, reserved.to_string())?;
+        std::fs::rename(&tmp_path, path)
+    }
 }
 
-#[cfg(any(test, feature = "runtime-async-std"))]
-pub mod surf_client {
-    use async_std::prelude::FutureExt;
-    use async_trait::async_trait;
-    use serde::Serialize;
-    use tracing::info;
+/// Standard HTTP header with the delay after which a rate-limited request can be retried.
+const RETRY_AFTER_HEADER: &str = "retry-after";
+/// Non-standard HTTP header used by some RPC providers to announce the reset of the rate-limit window.
+const RATELIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
 
-    use crate::errors::HttpRequestError;
-    use crate::{HttpPostRequestorConfig, HttpRequestor};
+/// Values of `x-ratelimit-reset` above this threshold are considered UNIX timestamps instead of
+/// the number of seconds until the reset.
+const RATELIMIT_RESET_TIMESTAMP_THRESHOLD: f64 = 1_000_000_000.0;
 
-    /// HTTP client that uses a non-Tokio runtime based HTTP client library, such as `surf`.
-    /// `surf` works also for Browsers in WASM environments.
-    #[derive(Clone, Debug, Default)]
-    pub struct SurfRequestor {
-        client: surf::Client,
-        cfg: HttpPostRequestorConfig,
+/// Determines the delay after which a request can be retried from the values of the rate-limit headers.
+///
+/// `Retry-After` takes precedence and only its delay-seconds form is supported.
+/// The `x-ratelimit-reset` can be either the number of seconds until the reset or a UNIX timestamp.
+pub(crate) fn parse_rate_limit_headers(retry_after: Option<&str>, ratelimit_reset: Option<&str>) -> Option<Duration> {
+    if let Some(delay) = retry_after.and_then(|v| v.trim().parse::<u64>().ok()) {
+        return Some(Duration::from_secs(delay));
     }
 
-    impl SurfRequestor {
-        pub fn new(cfg: HttpPostRequestorConfig) -> Self {
-            info!(?cfg, "creating surf client");
-
-            let mut client = surf::client().with(surf::middleware::Redirect::new(cfg.max_redirects));
+    let reset = ratelimit_reset
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)?;
+
+    if reset > RATELIMIT_RESET_TIMESTAMP_THRESHOLD {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs_f64();
+        Some(Duration::from_secs_f64((reset - now).max(0.0)))
+    } else {
+        Some(Duration::from_secs_f64(reset))
+    }
+}
 
-            // Rate limit of 0 also means unlimited as if None was given
-            if let Some(max) = cfg.max_requests_per_sec.and_then(|r| (r > 0).then_some(r)) {
-                client = client.with(
-                    surf_governor::GovernorMiddleware::per_second(max)
-                        .expect("cannot setup http rate limiter middleware"),
-                );
-            }
+/// Checks the `method` against the [allowlist](crate::HttpPostRequestorConfig::allowed_methods) and serializes
+/// the `data` into the body of the request, unless the method is one without a body (`GET`, `HEAD` or `OPTIONS`).
+pub(crate) fn request_body<T: Serialize>(
+    allowed_methods: &[Method],
+    method: Method,
+    data: Option<T>,
+) -> Result<Option<Vec<u8>>, HttpRequestError> {
+    request_body_with(allowed_methods, method, data, |data| serde_json::to_vec(data))
+}
 
-            Self { client, cfg }
-        }
+/// Same as [request_body], but the `data` is serialized using the given `serialize` function.
+pub(crate) fn request_body_with<T: Serialize, B>(
+    allowed_methods: &[Method],
+    method: Method,
+    data: Option<T>,
+    serialize: impl FnOnce(&T) -> serde_json::Result<B>,
+) -> Result<Option<B>, HttpRequestError> {
+    if !allowed_methods.contains(&method) {
+        return Err(HttpRequestError::UnsupportedMethod(method));
     }
 
-    #[async_trait]
-    impl HttpRequestor for SurfRequestor {
-        async fn http_query<T>(
-            &self,
-            method: http_types::Method,
-            url: &str,
-            data: Option<T>,
-        ) -> Result<Box<[u8]>, HttpRequestError>
-        where
-            T: Serialize + Send + Sync,
-        {
-            let request = match method {
-                http_types::Method::Post => self
-                    .client
-                    .post(url)
-                    .body_json(&data.ok_or(HttpRequestError::UnknownError("missing data".to_string()))?)
-                    .map_err(|e| HttpRequestError::UnknownError(e.to_string()))?,
-                http_types::Method::Get => self.client.get(url),
-                _ => return Err(HttpRequestError::UnknownError("unsupported method".to_string())),
-            };
+    match method {
+        Method::Get | Method::Head | Method::Options => Ok(None),
+        _ => serialize(&data.ok_or(HttpRequestError::UnknownError("missing data".to_string()))?)
+            .map(Some)
+            .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}"))),
+    }
+}
 
-            async move {
-                match request.await {
-                    Ok(mut response) if response.status().is_success() => match response.body_bytes().await {
-                        Ok(data) => Ok(data.into_boxed_slice()),
-                        Err(e) => Err(HttpRequestError::TransportError(e.to_string())),
-                    },
-                    Ok(response) => Err(HttpRequestError::HttpError(response.status())),
-                    Err(e) => Err(HttpRequestError::TransportError(e.to_string())),
-                }
-            }
-            .timeout(self.cfg.http_request_timeout)
-            .await
-            .map_err(|_| HttpRequestError::Timeout)?
-        }
+/// Makes an owned copy of an error shared between deduplicated requests.
+///
+/// Deserialization errors cannot be cloned, so only their description is retained.
+fn shared_error_to_owned(err: &JsonRpcProviderClientError) -> JsonRpcProviderClientError {
+    match err {
+        JsonRpcProviderClientError::SerdeJson { err, text } => JsonRpcProviderClientError::SerdeJson {
+            err: serde::de::Error::custom(err.to_string()),
+            text: text.clone(),
+        },
+        JsonRpcProviderClientError::JsonRpcError(err) => JsonRpcProviderClientError::JsonRpcError(err.clone()),
+        JsonRpcProviderClientError::BackendError(err) => JsonRpcProviderClientError::BackendError(err.clone()),
+        JsonRpcProviderClientError::InvalidUrl { url, reason } => JsonRpcProviderClientError::InvalidUrl {
+            url: url.clone(),
+            reason: reason.clone(),
+        },
+        JsonRpcProviderClientError::Cancelled => JsonRpcProviderClientError::Cancelled,
+        JsonRpcProviderClientError::ConcurrencyLimitReached {
+            max_concurrent_requests,
+            waited,
+        } => JsonRpcProviderClientError::ConcurrencyLimitReached {
+            max_concurrent_requests: *max_concurrent_requests,
+            waited: *waited,
+        },
     }
 }
 
-#[cfg(any(test, feature = "runtime-tokio"))]
-pub mod reqwest_client {
-    use async_trait::async_trait;
-    use http_types::StatusCode;
-    use serde::Serialize;
-    use std::sync::Arc;
-    use std::time::Duration;
-    use tracing::info;
+/// URL schemes supported by the [JsonRpcProviderClient].
+pub const SUPPORTED_RPC_URL_SCHEMES: [&str; 2] = ["http", "https"];
 
-    use crate::errors::HttpRequestError;
-    use crate::{HttpPostRequestorConfig, HttpRequestor};
+/// Validates the given RPC endpoint URL.
+///
+/// The URL must be well-formed, use one of the [SUPPORTED_RPC_URL_SCHEMES] and contain a host.
+pub fn validate_rpc_url(url: &str) -> Result<url::Url, JsonRpcProviderClientError> {
+    let invalid = |reason: String| JsonRpcProviderClientError::InvalidUrl {
+        url: url.to_owned(),
+        reason,
+    };
 
-    /// HTTP client that uses a Tokio runtime-based HTTP client library, such as `reqwest`.
-    #[derive(Clone, Debug, Default)]
-    pub struct ReqwestRequestor {
-        client: reqwest::Client,
-        limiter: Option<Arc<governor::DefaultKeyedRateLimiter<String>>>,
-    }
+    let parsed = url::Url::parse(url).map_err(|e| invalid(e.to_string()))?;
 
-    impl ReqwestRequestor {
-        pub fn new(cfg: HttpPostRequestorConfig) -> Self {
-            info!(?cfg, "creating reqwest client");
-            Self {
-                client: reqwest::Client::builder()
-                    .timeout(cfg.http_request_timeout)
-                    .redirect(reqwest::redirect::Policy::limited(cfg.max_redirects as usize))
-                    // 30 seconds is longer than the normal interval between RPC requests, thus the
-                    // connection should remain available
-                    .tcp_keepalive(Some(Duration::from_secs(30)))
-                    // Enable all supported encodings to reduce the amount of data transferred
-                    // in responses. This is relevant for large eth_getLogs responses.
-                    .zstd(true)
-                    .brotli(true)
-                    .build()
-                    .expect("could not build reqwest client"),
-                limiter: cfg
-                    .max_requests_per_sec
-                    .filter(|reqs| *reqs > 0) // Ensures the following unwrapping won't fail
-                    .map(|reqs| {
-                        Arc::new(governor::DefaultKeyedRateLimiter::keyed(governor::Quota::per_second(
-                            reqs.try_into().unwrap(),
-                        )))
-                    }),
-            }
-        }
+    if !SUPPORTED_RPC_URL_SCHEMES.contains(&parsed.scheme()) {
+        return Err(invalid(format!("unsupported scheme '{}'", parsed.scheme())));
     }
 
-    #[async_trait]
-    impl HttpRequestor for ReqwestRequestor {
-        async fn http_query<T>(
-            &self,
-            method: http_types::Method,
-            url: &str,
-            data: Option<T>,
-        ) -> Result<Box<[u8]>, HttpRequestError>
-        where
-            T: Serialize + Send + Sync,
-        {
-            let url = reqwest::Url::parse(url)
-                .map_err(|e| HttpRequestError::UnknownError(format!("url parse error: {e}")))?;
-
-            let builder = match method {
-                http_types::Method::Get => self.client.get(url.clone()),
-                http_types::Method::Post => self.client.post(url.clone()).body(
-                    serde_json::to_string(&data.ok_or(HttpRequestError::UnknownError("missing data".to_string()))?)
-                        .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}")))?,
-                ),
-                _ => return Err(HttpRequestError::UnknownError("unsupported method".to_string())),
-            };
+    if parsed.host_str().is_none_or(|host| host.is_empty()) {
+        return Err(invalid("missing host".into()));
+    }
 
-            if self
-                .limiter
-                .clone()
-                .map(|limiter| limiter.check_key(&url.host_str().unwrap_or(".").to_string()).is_ok())
-                .unwrap_or(true)
-            {
-                let resp = builder
-                    .header("content-type", "application/json")
-                    .send()
-                    .await
-                    .map_err(|e| {
-                        if e.is_status() {
-                            HttpRequestError::HttpError(
-                                StatusCode::try_from(e.status().map(|s| s.as_u16()).unwrap_or(500))
-                                    .expect("status code must be compatible"), // cannot happen
-                            )
-                        } else if e.is_timeout() {
-                            HttpRequestError::Timeout
-                        } else {
-                            HttpRequestError::UnknownError(e.to_string())
-                        }
-                    })?;
+    Ok(parsed)
+}
 
-                resp.bytes()
-                    .await
-                    .map(|b| Box::from(b.as_ref()))
-                    .map_err(|e| HttpRequestError::UnknownError(format!("error retrieving body: {e}")))
-            } else {
-                Err(HttpRequestError::HttpError(StatusCode::TooManyRequests))
-            }
+impl<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>> JsonRpcProviderClient<Req, R> {
+    /// Creates the client given the `HttpPostRequestor`
+    pub fn new(base_url: &str, requestor: Req, retry_policy: R) -> Self {
+        Self {
+            id: Arc::new(AtomicU64::new(1)),
+            id_nonce: 0,
+            id_high_water_mark: None,
+            in_flight: None,
+            archive: None,
+            retry_cancellation: RetryCancellation::default(),
+            retry_budget: RetryBudget::default(),
+            stats: Arc::new(ClientStats::default()),
+            clock: RealClock,
+            concurrency_limit: None,
+            quirks: None,
+            requests_enqueued: AtomicU32::new(0),
+            url: Arc::new(std::sync::RwLock::new(Arc::from(base_url))),
+            requestor,
+            retry_policy,
         }
     }
-}
-
-/// Snapshot of a response cached by the [`SnapshotRequestor`].
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct RequestorResponseSnapshot {
-    id: usize,
-    request: String,
-    response: String,
-}
 
-/// Replays an RPC response to a request if it is found in the snapshot YAML file.
-/// If no such request has been seen before,
-/// it captures the new request/response pair obtained from the inner [`HttpRequestor`]
-/// and stores it into the snapshot file.
-///
-/// This is useful for snapshot testing only and should **NOT** be used in production.
-#[derive(Debug, Clone)]
-pub struct SnapshotRequestor<T> {
-    inner: T,
-    next_id: Arc<AtomicUsize>,
-    entries: moka::future::Cache<String, RequestorResponseSnapshot>,
-    file: String,
-    aggressive_save: bool,
-    fail_on_miss: bool,
-    ignore_snapshot: bool,
+    /// Same as [JsonRpcProviderClient::new], but validates the `base_url` first
+    /// (see [validate_rpc_url]).
+    pub fn try_new(base_url: &str, requestor: Req, retry_policy: R) -> Result<Self, JsonRpcProviderClientError> {
+        validate_rpc_url(base_url)?;
+        Ok(Self::new(base_url, requestor, retry_policy))
+    }
 }
 
-impl<T> SnapshotRequestor<T> {
-    /// Creates a new instance by wrapping an existing [`HttpRequestor`] and capturing
-    /// the request/response pairs.
-    ///
-    /// The constructor does not load any [snapshot entries](SnapshotRequestor) from
-    /// the `snapshot_file`.
-    /// The [`SnapshotRequestor::load`] method must be used after construction to do that.
-    pub fn new(inner: T, snapshot_file: &str) -> Self {
-        Self {
-            inner,
-            next_id: Arc::new(AtomicUsize::new(1)),
-            entries: moka::future::Cache::builder().build(),
-            file: snapshot_file.to_owned(),
-            aggressive_save: false,
-            fail_on_miss: false,
-            ignore_snapshot: false,
+impl<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>, C: Clock> JsonRpcProviderClient<Req, R, C> {
+    /// Uses the given [Clock] to time the waits between the retries (the default is [RealClock]).
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> JsonRpcProviderClient<Req, R, C2> {
+        JsonRpcProviderClient {
+            id: self.id,
+            id_nonce: self.id_nonce,
+            id_high_water_mark: self.id_high_water_mark,
+            in_flight: self.in_flight,
+            archive: self.archive,
+            retry_cancellation: self.retry_cancellation,
+            retry_budget: self.retry_budget,
+            stats: self.stats,
+            clock,
+            concurrency_limit: self.concurrency_limit,
+            quirks: self.quirks,
+            requests_enqueued: self.requests_enqueued,
+            url: self.url,
+            requestor: self.requestor,
+            retry_policy: self.retry_policy,
         }
     }
 
-    /// Gets the path to the snapshot disk file.
-    pub fn snapshot_path(&self) -> &str {
-        &self.file
+    /// Assigns a process-unique nonce to this client, which is put into the high bits of all request ids,
+    /// so that the ids do not overlap with any other client instance in this process.
+    ///
+    /// This is not done by default, because the snapshot-based tests (see [SnapshotRequestor])
+    /// rely on deterministic request ids.
+    pub fn with_unique_id_namespace(mut self) -> Self {
+        let nonce = NEXT_RPC_CLIENT_NONCE
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |nonce| {
+                Some(if nonce >= RPC_ID_NONCE_MASK { 1 } else { nonce + 1 })
+            })
+            .expect("nonce update closure never fails");
+        self.id_nonce = nonce << RPC_ID_SEQUENCE_BITS;
+        self
     }
 
-    /// Clears all entries from the snapshot in memory.
-    /// The snapshot file is not changed.
-    pub fn clear(&self) {
-        self.entries.invalidate_all();
-        self.next_id.store(1, Ordering::Relaxed);
+    /// Sets the sequence number of the next request id (the default is 1).
+    ///
+    /// Since the sequence number is shared with all the clones of this client, this should be done
+    /// before the client is cloned.
+    pub fn with_initial_id(self, initial_id: u64) -> Self {
+        self.id
+            .store((initial_id & RPC_ID_SEQUENCE_MASK).max(1), Ordering::SeqCst);
+        self
     }
 
-    /// Clears all entries and loads them from the snapshot file.
-    /// If `fail_on_miss` is set and the data is successfully loaded, all later
-    /// requests that miss the loaded snapshot will result in HTTP error 404.
-    pub async fn try_load(&mut self, fail_on_miss: bool) -> Result<(), std::io::Error> {
-        if self.ignore_snapshot {
-            return Ok(());
-        }
+    /// Persists the high-water mark of the used request ids into the given file and makes the client
+    /// continue from the persisted high-water mark, so that the ids remain monotonic across restarts.
+    ///
+    /// The high-water mark is persisted in advance for every [RPC_ID_RESERVATION_BLOCK] requests.
+    pub fn with_persisted_id(mut self, path: &str) -> std::io::Result<Self> {
+        let high_water_mark = IdHighWaterMark::load(path)?;
+        let initial_id = self.id.load(Ordering::SeqCst).max(high_water_mark.reserved());
+
+        self.id_high_water_mark = Some(Arc::new(high_water_mark));
+        Ok(self.with_initial_id(initial_id))
+    }
 
-        let loaded = serde_yaml::from_reader::<_, Vec<RequestorResponseSnapshot>>(std::fs::File::open(&self.file)?)
-            .map_err(std::io::Error::other)?;
+    /// If enabled, concurrent identical requests (same method and parameters) share a single
+    /// in-flight request to the RPC provider.
+    ///
+    /// The result is shared only with the requests issued while the request is in-flight, and is not
+    /// cached afterward. Errors are also shared only with the requests waiting for the same resolution.
+    /// The deduplication is shared with all the clones of this client.
+    pub fn with_request_deduplication(mut self, enabled: bool) -> Self {
+        self.in_flight = enabled.then(|| moka::future::Cache::builder().build());
+        self
+    }
 
-        self.clear();
+    /// Directs the historical queries (such as `eth_getLogs` or `eth_call`) against blocks older than `depth`
+    /// to the given archive endpoint. All the other requests are sent to the primary endpoint.
+    ///
+    /// The age of a block is determined from the latest block number returned by an `eth_blockNumber`
+    /// request of this client (or any of its clones). Until such request succeeds, all the requests
+    /// are sent to the primary endpoint.
+    ///
+    /// Fails if the `archive_url` is not valid (see [validate_rpc_url]).
+    pub fn with_archive_endpoint(mut self, archive_url: &str, depth: u64) -> Result<Self, JsonRpcProviderClientError> {
+        validate_rpc_url(archive_url)?;
+        self.archive = Some(Arc::new(ArchiveEndpoint {
+            url: Arc::from(archive_url),
+            depth,
+            latest_block: AtomicU64::new(0),
+        }));
+        Ok(self)
+    }
 
-        let loaded_len = futures::stream::iter(loaded)
-            .then(|entry| {
-                self.next_id.fetch_max(entry.id, Ordering::Relaxed);
-                self.entries.insert(entry.request.clone(), entry)
-            })
-            .collect::<Vec<_>>()
-            .await
-            .len();
+    /// Makes the retries of this client (and all its clones) cancellable using the given token,
+    /// which can be shared with other clients.
+    pub fn with_retry_cancellation(mut self, cancellation: RetryCancellation) -> Self {
+        self.retry_cancellation = cancellation;
+        self
+    }
 
-        if loaded_len > 0 {
-            self.fail_on_miss = fail_on_miss;
-        }
+    /// Token cancelling the pending retries of this client and all its clones,
+    /// e.g. on a graceful shutdown.
+    pub fn retry_cancellation(&self) -> RetryCancellation {
+        self.retry_cancellation.clone()
+    }
 
-        tracing::debug!("snapshot with {loaded_len} entries has been loaded from {}", &self.file);
-        Ok(())
+    /// Keeps the records of the last `capacity` calls made by this client and all its clones
+    /// in the [audit log](ClientStats::audit_snapshot) of its [stats](JsonRpcProviderClient::stats),
+    /// with their parameters truncated to `max_params_len` bytes.
+    pub fn with_audit_log(mut self, capacity: usize, max_params_len: usize) -> Self {
+        self.stats = Arc::new(self.take_stats().with_audit_log(capacity, max_params_len));
+        self
     }
 
-    /// Similar as [`SnapshotRequestor::try_load`], except that no entries are cleared if the load fails.
+    /// Tracks the usage of each JSON RPC method by this client and all its clones over a sliding `window`
+    /// in the [usage report](ClientStats::usage_report) of its [stats](JsonRpcProviderClient::stats).
     ///
-    /// This method consumes and returns self for easier call chaining.
-    pub async fn load(mut self, fail_on_miss: bool) -> Self {
-        let _ = self.try_load(fail_on_miss).await;
+    /// Only the calls made via [JsonRpcClient::request] are tracked.
+    pub fn with_usage_report(mut self, window: Duration) -> Self {
+        self.stats = Arc::new(self.take_stats().with_usage_report(window));
         self
     }
 
-    /// Forces saving to disk on each newly inserted entry.
+    /// Takes the stats out of this client to be reconfigured.
     ///
-    /// Use this only when the expected number of entries in the snapshot is small.
-    pub fn with_aggresive_save(mut self) -> Self {
-        self.aggressive_save = true;
-        self
+    /// If they are already shared with a clone, an empty copy is taken instead.
+    fn take_stats(&mut self) -> ClientStats {
+        Arc::try_unwrap(std::mem::take(&mut self.stats)).unwrap_or_else(|stats| stats.empty_copy())
     }
 
-    /// If set, the snapshot data will be ignored and resolution
-    /// will always be done with the inner requestor.
+    /// Records the finished call, including all its retries, in the [stats](JsonRpcProviderClient::stats).
+    fn record_call(
+        &self,
+        method: &str,
+        params: Option<&serde_json::value::RawValue>,
+        call: CallUsage,
+        outcome: impl FnOnce() -> CallOutcome,
+    ) {
+        self.stats.record_call(method, call, || {
+            let url = self.endpoint_for(method, &params);
+            let endpoint = crate::audit::redact_url(&url);
+            let outcome = match outcome() {
+                CallOutcome::Failure(error) => CallOutcome::Failure(error.replace(&*url, &endpoint)),
+                outcome => outcome,
+            };
+
+            CallRecord {
+                method: method.into(),
+                endpoint,
+                params: params.map(|p| p.to_string()).unwrap_or_default(),
+                params_truncated: false,
+                started_at: std::time::SystemTime::now()
+                    .checked_sub(call.latency)
+                    .unwrap_or(std::time::UNIX_EPOCH),
+                duration: call.latency,
+                retries: call.retries,
+                outcome,
+            }
+        });
+    }
+
+    /// Action logging the current [usage report](ClientStats::usage_report), to be run periodically by the caller
+    /// (e.g. using `execute_on_tick`).
     ///
-    /// This will inhibit any attempts to [`load`](SnapshotRequestor::try_load) or
-    /// [`save`](SnapshotRequestor::save) snapshot data.
-    pub fn with_ignore_snapshot(mut self, ignore_snapshot: bool) -> Self {
-        self.ignore_snapshot = ignore_snapshot;
+    /// Nothing is logged if the [usage report](JsonRpcProviderClient::with_usage_report) is not enabled.
+    pub fn usage_report_logger(&self) -> impl Fn() -> futures::future::Ready<()> + Send + Sync + 'static {
+        let stats = self.stats.clone();
+        move || {
+            let report = stats.usage_report();
+            for (method, usage) in &report.methods {
+                info!(
+                    method,
+                    calls = usage.calls,
+                    failures = usage.failures,
+                    retries = usage.retries,
+                    average_latency_in_ms = usage.average_latency.as_millis(),
+                    bytes = usage.bytes,
+                    calls_per_second = usage.calls_per_second,
+                    window_in_s = report.window.as_secs(),
+                    "rpc usage report",
+                );
+            }
+            futures::future::ready(())
+        }
+    }
+
+    /// Repairs the known deviations of the RPC provider from the JSON RPC specification
+    /// (see [QuirksMode]) in each response before it is deserialized.
+    ///
+    /// This is disabled by default. The [streamed requests](JsonRpcProviderClient::request_streamed)
+    /// are never repaired.
+    pub fn with_quirks_mode(mut self, quirks: QuirksMode) -> Self {
+        self.quirks = Some(Arc::new(quirks));
         self
     }
 
-    /// Save the currently cached entries to the snapshot file on disk.
+    /// Limits the number of requests of this client and all its clones in flight at the same time
+    /// to `max_concurrent_requests` (at least 1), to protect RPC providers with limited resources.
+    /// `None` removes the limit, which is the default.
     ///
-    /// Note that this method is automatically called on Drop, so usually it is unnecessary
-    /// to call it explicitly.
-    pub fn save(&self) -> Result<(), std::io::Error> {
-        if self.ignore_snapshot {
-            return Ok(());
-        }
+    /// The slot is taken for each attempt of a call separately and released before the backoff
+    /// of its retry, so that the calls waiting for a retry do not hold back the other calls.
+    /// A streamed request holds its slot until its stream is dropped.
+    ///
+    /// If `max_wait` is set, the request waiting longer for a free slot fails with
+    /// [JsonRpcProviderClientError::ConcurrencyLimitReached], which is not retried by the
+    /// [SimpleJsonRpcRetryPolicy]. Otherwise, the request waits until a slot is available.
+    pub fn with_max_concurrent_requests(
+        mut self,
+        max_concurrent_requests: Option<usize>,
+        max_wait: Option<Duration>,
+    ) -> Self {
+        self.concurrency_limit = max_concurrent_requests.map(|max| {
+            let max = max.max(1);
+            Arc::new(ConcurrencyLimit {
+                permits: Arc::new(async_lock::Semaphore::new(max)),
+                max_concurrent_requests: max,
+                max_wait,
+            })
+        });
+        self
+    }
 
-        let mut values: Vec<RequestorResponseSnapshot> = self.entries.iter().map(|(_, r)| r).collect();
-        values.sort_unstable_by_key(|a| a.id);
+    /// Waits for a slot of the [concurrency limit](JsonRpcProviderClient::with_max_concurrent_requests)
+    /// and counts the request as in flight.
+    async fn start_request(&self, method: &str) -> Result<InflightRequest, JsonRpcProviderClientError> {
+        let permit = match &self.concurrency_limit {
+            Some(limit) => Some(limit.acquire(&self.clock).await.inspect_err(|error| {
+                warn!(method, %error, "rpc request could not get a slot to be sent");
+            })?),
+            None => None,
+        };
 
-        let mut writer = BufWriter::new(std::fs::File::create(&self.file)?);
+        Ok(InflightRequest::new(permit))
+    }
 
-        serde_yaml::to_writer(&mut writer, &values).map_err(std::io::Error::other)?;
+    /// Statistics of the calls and requests made by this client and all its clones.
+    ///
+    /// Each attempt of a retried call counts as a separate request in the per-endpoint statistics.
+    pub fn stats(&self) -> Arc<ClientStats> {
+        self.stats.clone()
+    }
 
-        writer.flush()?;
+    /// Current URL of the primary endpoint.
+    pub fn url(&self) -> Arc<str> {
+        self.url.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
 
-        tracing::debug!("snapshot with {} entries saved to file {}", values.len(), self.file);
+    /// Switches the primary endpoint of this client and all its clones to the given URL
+    /// (see [validate_rpc_url]), without recreating the client.
+    ///
+    /// The requests already in progress (but not their retries) finish against the previous URL,
+    /// all the subsequent requests are sent to the new URL.
+    pub fn set_url(&self, new_url: &str) -> Result<(), JsonRpcProviderClientError> {
+        validate_rpc_url(new_url)?;
+
+        let previous = std::mem::replace(
+            &mut *self.url.write().unwrap_or_else(|e| e.into_inner()),
+            Arc::from(new_url),
+        );
+        info!(
+            from = %crate::audit::redact_url(&previous),
+            to = %crate::audit::redact_url(new_url),
+            "rpc endpoint url changed"
+        );
         Ok(())
     }
-}
 
-impl<R: HttpRequestor> SnapshotRequestor<R> {
-    async fn http_post_with_snapshot<In>(&self, url: &str, data: In) -> Result<Box<[u8]>, HttpRequestError>
-    where
-        In: Serialize + Send + Sync,
-    {
-        let request = serde_json::to_string(&data)
-            .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}")))?;
+    /// Selects the endpoint the request should be sent to.
+    fn endpoint_for<T: Serialize>(&self, method: &str, params: &T) -> Arc<str> {
+        // Only the parameters of the historical queries are worth inspecting
+        let Some(archive) = self.archive.as_ref().filter(|_| block_param_position(method).is_some()) else {
+            return self.url();
+        };
 
-        let inserted = AtomicBool::new(false);
-        let result = self
-            .entries
-            .entry(request.clone())
-            .or_try_insert_with(async {
-                if self.fail_on_miss {
-                    tracing::error!("{request} is missing in {}", &self.file);
-                    return Err(HttpRequestError::HttpError(http_types::StatusCode::NotFound));
-                }
+        match serde_json::to_value(params)
+            .ok()
+            .and_then(|params| requested_block(method, &params))
+        {
+            Some(block) if archive.serves(block) => {
+                debug!(method, block, "routing rpc request to the archive endpoint");
+                archive.url.clone()
+            }
+            _ => self.url(),
+        }
+    }
 
-                let response = self.inner.http_post(url, data).await?;
-                let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-                inserted.store(true, Ordering::Relaxed);
+    /// Retry policy of the requests, whose retries are limited by the retry budget of this client.
+    fn budgeted_retry_policy(&self) -> BudgetedRetryPolicy<'_, R> {
+        BudgetedRetryPolicy {
+            policy: &self.retry_policy,
+            budget: &self.retry_budget,
+        }
+    }
 
-                tracing::debug!("saved new snapshot entry #{id}");
-                Ok(RequestorResponseSnapshot {
-                    id,
-                    request: request.clone(),
-                    response: String::from_utf8(response.into_vec())
-                        .map_err(|e| HttpRequestError::UnknownError(format!("unparseable data: {e}")))?,
-                })
+    /// Generates the next JSON RPC request id.
+    ///
+    /// The sequence number is shared by all the clones of this client and wraps around to 1
+    /// once it exhausts its [RPC_ID_SEQUENCE_BITS].
+    async fn next_id(&self) -> u64 {
+        let seq = self
+            .id
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |seq| {
+                Some(if seq >= RPC_ID_SEQUENCE_MASK { 1 } else { seq + 1 })
             })
-            .await
-            .map(|e| e.into_value().response.into_bytes().into_boxed_slice())
-            .map_err(|e: Arc<HttpRequestError>| e.as_ref().clone())?;
+            .expect("id update closure never fails");
 
-        if inserted.load(Ordering::Relaxed) && self.aggressive_save {
-            tracing::debug!("{request} was NOT found and was resolved");
-            self.save().map_err(|e| HttpRequestError::UnknownError(e.to_string()))?;
-        } else {
-            tracing::debug!("{request} was found");
+        if let Some(high_water_mark) = &self.id_high_water_mark {
+            high_water_mark.ensure_reserved(seq).await;
         }
 
-        Ok(result)
-    }
-}
-
-impl<T> Drop for SnapshotRequestor<T> {
-    fn drop(&mut self) {
-        if let Err(e) = self.save() {
-            tracing::error!("failed to save snapshot: {e}");
-        }
+        self.id_nonce | (seq & RPC_ID_SEQUENCE_MASK)
     }
-}
 
-#[async_trait::async_trait]
-impl<R: HttpRequestor> HttpRequestor for SnapshotRequestor<R> {
-    async fn http_query<T>(&self, _: Method, _: &str, _: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    /// Sends the request, possibly sharing an identical in-flight request if
+    /// [deduplication](JsonRpcProviderClient::with_request_deduplication) is enabled.
+    async fn send_request_deduplicated<T>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<Arc<str>, JsonRpcProviderClientError>
     where
         T: Serialize + Send + Sync,
     {
-        todo!()
+        let Some(in_flight) = &self.in_flight else {
+            return self.send_request_internal(method, params).await;
+        };
+
+        let key = serde_json::to_string(&(method, &params))
+            .map_err(|err| JsonRpcProviderClientError::SerdeJson { err, text: "".into() })?;
+
+        match in_flight
+            .entry(key.clone())
+            .or_try_insert_with(self.send_request_internal(method, params))
+            .await
+        {
+            Ok(entry) => {
+                // The result must not be cached beyond the single in-flight resolution
+                if entry.is_fresh() {
+                    in_flight.invalidate(&key).await;
+                } else {
+                    debug!(method, "rpc request deduplicated with an in-flight request");
+                }
+                Ok(entry.into_value())
+            }
+            Err(err) => Err(Arc::try_unwrap(err).unwrap_or_else(|shared| shared_error_to_owned(&shared))),
+        }
     }
 
-    async fn http_post<T>(&self, url: &str, data: T) -> Result<Box<[u8]>, HttpRequestError>
+    async fn send_request_internal<T>(&self, method: &str, params: T) -> Result<Arc<str>, JsonRpcProviderClientError>
     where
         T: Serialize + Send + Sync,
     {
-        self.http_post_with_snapshot(url, data).await
-    }
+        let _inflight = self.start_request(method).await?;
 
-    async fn http_get(&self, _url: &str) -> Result<Box<[u8]>, HttpRequestError> {
-        todo!()
+        // Create the Request object
+        let next_id = self.next_id().await;
+        let url = self.endpoint_for(method, &params);
+        let payload = Request::new(next_id, method, params);
+
+        debug!(method, "sending rpc request");
+        trace!(
+            method,
+            request = serde_json::to_string(&payload).expect("request must be serializable"),
+            "sending rpc request",
+        );
+
+        // Perform the actual request
+        let start = std::time::Instant::now();
+        let result = async {
+            let body = self.requestor.http_post(&url, payload).await?;
+            let req_duration = start.elapsed();
+
+            let body = match &self.quirks {
+                Some(quirks) => quirks.normalize(method, next_id, body),
+                None => body,
+            };
+
+            trace!(method, duration_in_ms = req_duration.as_millis(), "rpc request took");
+
+            #[cfg(all(feature = "prometheus", not(test)))]
+            METRIC_RPC_CALLS_TIMING.observe(&[method], req_duration.as_secs_f64());
+
+            // First deserialize the Response object
+            let raw = match serde_json::from_slice(&body) {
+                Ok(Response::Success { result, .. }) => result.to_owned(),
+                Ok(Response::Error { error, .. }) => {
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+
+                    return Err(error.into());
+                }
+                Ok(_) => {
+                    let err = JsonRpcProviderClientError::SerdeJson {
+                        err: serde::de::Error::custom("unexpected notification over HTTP transport"),
+                        text: String::from_utf8_lossy(&body).to_string(),
+                    };
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+
+                    return Err(err);
+                }
+                Err(err) => {
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    METRIC_COUNT_RPC_CALLS.increment(&[method, "failure"]);
+
+                    return Err(JsonRpcProviderClientError::SerdeJson {
+                        err,
+                        text: String::from_utf8_lossy(&body).to_string(),
+                    });
+                }
+            };
+
+            let json_str = raw.get();
+            trace!(method, response = &json_str, "rpc request response received");
+
+            if let (Some(archive), "eth_blockNumber") = (&self.archive, method) {
+                if let Ok(block) = serde_json::from_str::<ethers::types::U64>(json_str) {
+                    archive.latest_block.fetch_max(block.as_u64(), Ordering::Relaxed);
+                }
+            }
+
+            #[cfg(all(feature = "prometheus", not(test)))]
+            METRIC_COUNT_RPC_CALLS.increment(&[method, "success"]);
+
+            Ok::<_, JsonRpcProviderClientError>(Arc::from(json_str))
+        }
+        .await;
+
+        self.stats.record_request(&url, start.elapsed(), result.is_ok());
+        result
     }
 }
 
-#[async_trait]
-impl<R: HttpRequestor> HttpRequestor for &SnapshotRequestor<R> {
-    async fn http_query<T>(&self, _: Method, _: &str, _: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+impl<Req: StreamingHttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>, C: Clock>
+    JsonRpcProviderClient<Req, R, C>
+{
+    /// Performs a JSON RPC request with an array result and deserializes the elements of the result
+    /// one by one as the response arrives, so that large results (such as of `eth_getLogs`)
+    /// do not have to be buffered as a whole.
+    ///
+    /// Unlike [JsonRpcClient::request], the request is not retried, because the elements already
+    /// yielded by the stream cannot be taken back. A result which is not an array is deserialized as a whole
+    /// and yielded element by element, a `null` result yields no elements.
+    pub async fn request_streamed<T, E>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<BoxStream<'static, Result<E, JsonRpcProviderClientError>>, JsonRpcProviderClientError>
     where
         T: Serialize + Send + Sync,
+        E: DeserializeOwned + Send + 'static,
     {
-        todo!()
+        let inflight = self.start_request(method).await?;
+        let next_id = self.next_id().await;
+        let url = self.endpoint_for(method, &params);
+        let payload = Request::new(next_id, method, params);
+
+        debug!(method, "sending streamed rpc request");
+        let mut body = self.requestor.http_post_streamed(&url, payload).await?;
+        let method = method.to_owned();
+
+        Ok(async_stream::stream! {
+            let _inflight = inflight;
+            let mut splitter = ResultArraySplitter::default();
+            let mut count = 0_usize;
+
+            while let Some(chunk) = body.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        for element in splitter.feed(&chunk) {
+                            count += 1;
+                            yield serde_json::from_slice::<E>(&element).map_err(|err| JsonRpcProviderClientError::SerdeJson {
+                                err,
+                                text: String::from_utf8_lossy(&element).into_owned(),
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        #[cfg(all(feature = "prometheus", not(test)))]
+                        METRIC_COUNT_RPC_CALLS.increment(&[&method, "failure"]);
+
+                        yield Err(err.into());
+                        return;
+                    }
+                }
+            }
+
+            let (rest, result_found) = splitter.finish();
+            let failure = match serde_json::from_slice::<Response>(&rest) {
+                Ok(Response::Success { .. }) if result_found => None,
+                Ok(Response::Success { result, .. }) => {
+                    match serde_json::from_str::<Option<Vec<E>>>(result.get()) {
+                        Ok(elements) => {
+                            for element in elements.into_iter().flatten() {
+                                count += 1;
+                                yield Ok(element);
+                            }
+                            None
+                        }
+                        Err(err) => Some(JsonRpcProviderClientError::SerdeJson {
+                            err,
+                            text: result.get().to_owned(),
+                        }),
+                    }
+                }
+                Ok(Response::Error { error, .. }) => Some(error.into()),
+                Ok(Response::Notification { .. }) => Some(JsonRpcProviderClientError::SerdeJson {
+                    err: serde::de::Error::custom("unexpected notification over HTTP transport"),
+                    text: String::from_utf8_lossy(&rest).into_owned(),
+                }),
+                Err(err) => Some(JsonRpcProviderClientError::SerdeJson {
+                    err,
+                    text: String::from_utf8_lossy(&rest).into_owned(),
+                }),
+            };
+
+            match failure {
+                Some(err) => {
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    METRIC_COUNT_RPC_CALLS.increment(&[&method, "failure"]);
+
+                    yield Err(err);
+                }
+                None => {
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    METRIC_COUNT_RPC_CALLS.increment(&[&method, "success"]);
+
+                    debug!(method, count, "streamed rpc request finished");
+                }
+            }
+        }
+        .boxed())
     }
 
-    async fn http_post<T>(&self, url: &str, data: T) -> Result<Box<[u8]>, HttpRequestError>
-    where
-        T: Serialize + Send + Sync,
+    /// Streams the logs matching the given filter (see [JsonRpcProviderClient::request_streamed]).
+    pub async fn get_logs_streamed(
+        &self,
+        filter: &ethers::types::Filter,
+    ) -> Result<BoxStream<'static, Result<ethers::types::Log, JsonRpcProviderClientError>>, JsonRpcProviderClientError>
     {
-        self.http_post_with_snapshot(url, data).await
+        self.request_streamed("eth_getLogs", [filter]).await
     }
+}
 
-    async fn http_get(&self, _url: &str) -> Result<Box<[u8]>, HttpRequestError> {
-        todo!()
+impl<Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>, C: Clock> Debug
+    for JsonRpcProviderClient<Req, R, C>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonRpcProviderClient")
+            .field("id", &self.id)
+            .field("id_nonce", &(self.id_nonce >> RPC_ID_SEQUENCE_BITS))
+            .field("url", &self.url())
+            .field("requests_enqueued", &self.requests_enqueued)
+            .finish_non_exhaustive()
     }
 }
 
-type AnvilRpcClient<R> = ethers::middleware::SignerMiddleware<
-    ethers::providers::Provider<JsonRpcProviderClient<R, SimpleJsonRpcRetryPolicy>>,
-    ethers::signers::Wallet<ethers::core::k256::ecdsa::SigningKey>,
->;
+impl<Req: HttpRequestor + Clone, R: RetryPolicy<JsonRpcProviderClientError> + Clone, C: Clock> Clone
+    for JsonRpcProviderClient<Req, R, C>
+{
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            id_nonce: self.id_nonce,
+            id_high_water_mark: self.id_high_water_mark.clone(),
+            in_flight: self.in_flight.clone(),
+            archive: self.archive.clone(),
+            retry_cancellation: self.retry_cancellation.clone(),
+            retry_budget: self.retry_budget.clone(),
+            stats: self.stats.clone(),
+            clock: self.clock.clone(),
+            concurrency_limit: self.concurrency_limit.clone(),
+            quirks: self.quirks.clone(),
+            url: self.url.clone(),
+            requests_enqueued: AtomicU32::new(0),
+            requestor: self.requestor.clone(),
+            retry_policy: self.retry_policy.clone(),
+        }
+    }
+}
 
-/// Used for testing. Creates Ethers RPC client to the local Anvil instance.
-#[cfg(not(target_arch = "wasm32"))]
-pub fn create_rpc_client_to_anvil<R: HttpRequestor>(
-    backend: R,
-    anvil: &ethers::utils::AnvilInstance,
-    signer: &hopr_crypto_types::keypairs::ChainKeypair,
-) -> Arc<AnvilRpcClient<R>> {
-    use ethers::signers::Signer;
-    use hopr_crypto_types::keypairs::Keypair;
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<Req, R, C> JsonRpcClient for JsonRpcProviderClient<Req, R, C>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+    C: Clock,
+{
+    type Error = JsonRpcProviderClientError;
 
-    let wallet =
-        ethers::signers::LocalWallet::from_bytes(signer.secret().as_ref()).expect("failed to construct wallet");
-    let json_client = JsonRpcProviderClient::new(&anvil.endpoint(), backend, SimpleJsonRpcRetryPolicy::default());
-    let provider = ethers::providers::Provider::new(json_client).interval(Duration::from_millis(10_u64));
+    async fn request<T, A>(&self, method: &str, params: T) -> Result<A, Self::Error>
+    where
+        T: Serialize + Send + Sync,
+        A: DeserializeOwned + Send,
+    {
+        // Helper type that caches the `params` value across several retries
+        // This is necessary because the wrapper provider is supposed to skip he `params` if it's of
+        // size 0, see `crate::transports::common::Request`
+        // The `params` are serialized only once, each retry copies the already serialized JSON as-is.
+        enum RetryParams<Params> {
+            Value(Params),
+            Zst(()),
+        }
 
-    Arc::new(ethers::middleware::SignerMiddleware::new(
-        provider,
-        wallet.with_chain_id(anvil.chain_id()),
-    ))
-}
+        let params = if std::mem::size_of::<A>() == 0 {
+            RetryParams::Zst(())
+        } else {
+            let params = serde_json::value::to_raw_value(&params)
+                .map_err(|err| JsonRpcProviderClientError::SerdeJson { err, text: "".into() })?;
+            RetryParams::Value(params)
+        };
+        let audited_params = match &params {
+            RetryParams::Value(params) => Some(params.as_ref()),
+            RetryParams::Zst(_) => None,
+        };
 
-#[cfg(test)]
-mod tests {
-    use async_trait::async_trait;
-    use ethers::providers::JsonRpcClient;
-    use hopr_async_runtime::prelude::sleep;
-    use hopr_chain_types::utils::create_anvil;
-    use hopr_chain_types::{ContractAddresses, ContractInstances};
-    use hopr_crypto_types::keypairs::{ChainKeypair, Keypair};
-    use hopr_primitive_types::primitives::Address;
-    use http_types::Method;
-    use serde::Serialize;
-    use serde_json::json;
-    use std::fmt::Debug;
-    use std::sync::atomic::Ordering;
-    use std::time::Duration;
-    use tempfile::NamedTempFile;
+        self.requests_enqueued.fetch_add(1, Ordering::SeqCst);
+        let start = std::time::Instant::now();
 
-    use crate::client::reqwest_client::ReqwestRequestor;
-    use crate::client::surf_client::SurfRequestor;
-    use crate::client::{
-        create_rpc_client_to_anvil, JsonRpcProviderClient, SimpleJsonRpcRetryPolicy, SnapshotRequestor,
-    };
-    use crate::errors::{HttpRequestError, JsonRpcProviderClientError};
-    use crate::{HttpRequestor, ZeroRetryPolicy};
+        let response_bytes = AtomicU64::new(0);
+        let response_bytes = &response_bytes;
+        let params = &params;
+        let hooks = RequestRetryHooks {
+            client: self,
+            method,
+            start,
+        };
+        let result = retry_with_hooks(
+            move || async move {
+                match params {
+                    RetryParams::Value(params) => self.send_request_deduplicated(method, params).await,
+                    RetryParams::Zst(unit) => self.send_request_deduplicated(method, unit).await,
+                }
+                // Next, deserialize the data out of the Response object
+                .and_then(|raw| {
+                    response_bytes.store(raw.len() as u64, Ordering::Relaxed);
+                    serde_json::from_str::<A>(&raw).map_err(|err| JsonRpcProviderClientError::SerdeJson {
+                        err,
+                        text: raw.to_string(),
+                    })
+                })
+            },
+            &self.budgeted_retry_policy(),
+            &hooks,
+        )
+        .await;
+
+        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+        match result {
+            Ok((ret, num_retries)) => {
+                #[cfg(all(feature = "prometheus", not(test)))]
+                METRIC_RETRIES_PER_RPC_CALL.observe(&[method], num_retries as f64);
 
-    async fn deploy_contracts<R: HttpRequestor + Debug>(req: R) -> anyhow::Result<ContractAddresses> {
-        let anvil = create_anvil(None);
-        let chain_key_0 = ChainKeypair::from_secret(anvil.keys()[0].to_bytes().as_ref())?;
+                let call = CallUsage {
+                    latency: start.elapsed(),
+                    retries: num_retries,
+                    failed: false,
+                    bytes: response_bytes.load(Ordering::Relaxed),
+                };
+                self.record_call(method, audited_params, call, || CallOutcome::Success);
 
-        let client = create_rpc_client_to_anvil(req, &anvil, &chain_key_0);
+                debug!(method, elapsed_in_ms = start.elapsed().as_millis(), "request succeeded",);
+                Ok(ret)
+            }
+            Err(RetryError::Exhausted { error, failures }) => {
+                let reason = RetryReason::from(&error);
+                self.stats.record_terminal_failure(reason);
+                warn!(method, %reason, "no more retries for RPC call");
 
-        let contracts = ContractInstances::deploy_for_testing(client.clone(), &chain_key_0)
-            .await
-            .expect("deploy failed");
+                #[cfg(all(feature = "prometheus", not(test)))]
+                METRIC_RPC_TERMINAL_FAILURES.increment(&[reason.as_str()]);
 
-        Ok(ContractAddresses::from(&contracts))
+                #[cfg(all(feature = "prometheus", not(test)))]
+                METRIC_RETRIES_PER_RPC_CALL.observe(&[method], failures as f64);
+
+                let call = CallUsage {
+                    latency: start.elapsed(),
+                    retries: failures.saturating_sub(1),
+                    failed: true,
+                    bytes: 0,
+                };
+                self.record_call(method, audited_params, call, || CallOutcome::Failure(error.to_string()));
+
+                debug!(
+                    method,
+                    duration_in_ms = start.elapsed().as_millis(),
+                    "failed request duration in the retry queue",
+                );
+                Err(error)
+            }
+            Err(RetryError::Abandoned { failures, .. }) => {
+                warn!(method, "retries of the RPC call have been cancelled");
+                let call = CallUsage {
+                    latency: start.elapsed(),
+                    retries: failures.saturating_sub(1),
+                    failed: true,
+                    bytes: 0,
+                };
+                self.record_call(method, audited_params, call, || CallOutcome::Cancelled);
+                Err(JsonRpcProviderClientError::Cancelled)
+            }
+        }
     }
+}
 
-    #[async_std::test]
-    async fn test_client_should_deploy_contracts_via_surf() -> anyhow::Result<()> {
-        let contract_addrs = deploy_contracts(SurfRequestor::default()).await?;
+/// Hooks of the retries of a single [JsonRpcProviderClient] request,
+/// which log the failures, collect the retry statistics and honor the [RetryCancellation].
+struct RequestRetryHooks<'a, Req: HttpRequestor, R: RetryPolicy<JsonRpcProviderClientError>, C: Clock> {
+    client: &'a JsonRpcProviderClient<Req, R, C>,
+    method: &'a str,
+    start: std::time::Instant,
+}
 
-        assert_ne!(contract_addrs.token, Address::default());
-        assert_ne!(contract_addrs.channels, Address::default());
-        assert_ne!(contract_addrs.announcements, Address::default());
-        assert_ne!(contract_addrs.network_registry, Address::default());
-        assert_ne!(contract_addrs.safe_registry, Address::default());
-        assert_ne!(contract_addrs.price_oracle, Address::default());
+impl<Req, R, C> RetryHooks<JsonRpcProviderClientError> for RequestRetryHooks<'_, Req, R, C>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError>,
+    C: Clock,
+{
+    fn retry_queue_size(&self) -> u32 {
+        self.client.requests_enqueued.load(Ordering::SeqCst)
+    }
 
-        Ok(())
+    fn on_failure(&self, err: &JsonRpcProviderClientError, _failures: u32) {
+        error!(
+            method = self.method,
+            elapsed_in_ms = self.start.elapsed().as_millis(),
+            error = %err,
+            "request failed",
+        );
     }
 
-    #[tokio::test]
-    async fn test_client_should_deploy_contracts_via_reqwest() -> anyhow::Result<()> {
-        let contract_addrs = deploy_contracts(ReqwestRequestor::default()).await?;
+    fn on_retry(&self, err: &JsonRpcProviderClientError, _failures: u32, backoff: Duration) {
+        let reason = RetryReason::from(err);
+        self.client.stats.record_retry(reason);
+        warn!(method = self.method, %reason, backoff_in_ms = backoff.as_millis(), "request will retry",);
 
-        assert_ne!(contract_addrs.token, Address::default());
-        assert_ne!(contract_addrs.channels, Address::default());
-        assert_ne!(contract_addrs.announcements, Address::default());
-        assert_ne!(contract_addrs.network_registry, Address::default());
-        assert_ne!(contract_addrs.safe_registry, Address::default());
-        assert_ne!(contract_addrs.price_oracle, Address::default());
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_RPC_RETRY_REASONS.increment(&[self.method, reason.as_str()]);
+    }
 
-        Ok(())
+    fn backoff(&self, backoff: Duration) -> BoxFuture<'_, bool> {
+        self.client
+            .retry_cancellation
+            .backoff(&self.client.clock, backoff)
+            .boxed()
     }
+}
 
-    #[async_std::test]
-    async fn test_client_should_get_block_number() -> anyhow::Result<()> {
-        let block_time = Duration::from_secs(1);
+#[cfg(any(test, feature = "runtime-async-std"))]
+pub mod surf_client {
+    use async_std::prelude::FutureExt;
+    use async_trait::async_trait;
+    use isahc::config::Configurable;
+    use serde::Serialize;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tracing::{debug, info};
 
-        let anvil = create_anvil(Some(block_time));
-        let client = JsonRpcProviderClient::new(
-            &anvil.endpoint(),
-            SurfRequestor::default(),
-            SimpleJsonRpcRetryPolicy::default(),
-        );
+    use crate::errors::HttpRequestError;
+    use crate::signer::{signed_headers, RequestSigner};
+    use crate::{HttpPostRequestorConfig, HttpRequestor};
 
-        let mut last_number = 0;
+    /// Time for which the resolved address of a host is reused by the [ServerNameOverride].
+    const SERVER_NAME_OVERRIDE_RESOLUTION_TTL: Duration = Duration::from_secs(60);
+
+    /// Maximum number of resolved addresses the [ServerNameOverride] keeps a client for.
+    const SERVER_NAME_OVERRIDE_MAX_CLIENTS: u64 = 16;
+
+    /// Maps the `surf` error to the [`HttpRequestError`], so that the retry policy can tell
+    /// transient failures from the permanent ones.
+    fn classify_error(e: surf::Error) -> HttpRequestError {
+        match e.downcast_ref::<isahc::Error>() {
+            Some(isahc::Error::Timeout) => HttpRequestError::Timeout,
+            Some(
+                isahc::Error::BadClientCertificate(_)
+                | isahc::Error::BadServerCertificate(_)
+                | isahc::Error::SSLConnectFailed(_)
+                | isahc::Error::SSLEngineError(_),
+            ) => HttpRequestError::PermanentError(e.to_string()),
+            _ => HttpRequestError::TransportError(e.to_string()),
+        }
+    }
+
+    /// Creates the `surf` client, which optionally connects to the given address regardless the request URL.
+    fn build_client(cfg: &HttpPostRequestorConfig, dial: Option<SocketAddr>) -> surf::Client {
+        // The timeouts must be set on the underlying client, because `surf` cannot time the connection
+        // establishment separately. The client also aborts the timed-out requests.
+        let mut builder = isahc::HttpClient::builder()
+            .connect_timeout(cfg.connect_timeout.min(cfg.http_request_timeout))
+            .timeout(cfg.http_request_timeout);
+
+        if let Some(path) = &cfg.tls_root_certificates {
+            builder = builder.ssl_ca_certificate(isahc::config::CaCertificate::file(path));
+        }
+
+        if let Some(addr) = dial {
+            builder = builder.dial(isahc::config::Dialer::ip_socket(addr));
+        }
+
+        let http_client = builder
+            .build()
+            .map(http_client::isahc::IsahcClient::from_client)
+            .expect("cannot setup http client");
+
+        let mut client =
+            surf::Client::with_http_client(http_client).with(surf::middleware::Redirect::new(cfg.max_redirects));
+
+        // Rate limit of 0 also means unlimited as if None was given
+        if let Some(max) = cfg.max_requests_per_sec.and_then(|r| (r > 0).then_some(r)) {
+            client = client.with(
+                surf_governor::GovernorMiddleware::per_second(max).expect("cannot setup http rate limiter middleware"),
+            );
+        }
+
+        client
+    }
+
+    /// Addresses the requests to the overridden TLS server name (see [HttpPostRequestorConfig::tls_server_name]).
+    ///
+    /// The underlying client cannot set the SNI independently of the request URL, so the URL host
+    /// is replaced by the server name and the connection is made to the resolved address of the original host
+    /// instead. The resolved addresses are reused for [SERVER_NAME_OVERRIDE_RESOLUTION_TTL]. There is one client
+    /// per resolved address, each with its own rate limit, and the clients of the least recently used addresses
+    /// are dropped once there are more than [SERVER_NAME_OVERRIDE_MAX_CLIENTS].
+    #[derive(Debug)]
+    struct ServerNameOverride {
+        server_name: String,
+        resolved: moka::future::Cache<(String, u16), SocketAddr>,
+        clients: moka::future::Cache<SocketAddr, surf::Client>,
+    }
+
+    impl ServerNameOverride {
+        fn new(server_name: String) -> Self {
+            Self {
+                server_name,
+                resolved: moka::future::Cache::builder()
+                    .time_to_live(SERVER_NAME_OVERRIDE_RESOLUTION_TTL)
+                    .max_capacity(SERVER_NAME_OVERRIDE_MAX_CLIENTS)
+                    .build(),
+                clients: moka::future::Cache::builder()
+                    .max_capacity(SERVER_NAME_OVERRIDE_MAX_CLIENTS)
+                    .eviction_policy(moka::policy::EvictionPolicy::lru())
+                    .build(),
+            }
+        }
+
+        /// Resolves the address of the `domain`, reusing the previous resolution if it is recent enough.
+        async fn resolve(&self, domain: &str, port: u16) -> Result<SocketAddr, HttpRequestError> {
+            self.resolved
+                .try_get_with((domain.to_owned(), port), async {
+                    debug!(domain, port, "resolving host of the request with server name override");
+                    async_std::net::ToSocketAddrs::to_socket_addrs(&(domain, port))
+                        .await
+                        .map_err(|e| HttpRequestError::TransportError(e.to_string()))?
+                        .next()
+                        .ok_or_else(|| HttpRequestError::TransportError(format!("cannot resolve '{domain}'")))
+                })
+                .await
+                .map_err(|e: Arc<HttpRequestError>| e.as_ref().clone())
+        }
+
+        /// Resolves the client and the URL the request to the given URL should be sent with.
+        async fn route(
+            &self,
+            cfg: &HttpPostRequestorConfig,
+            url: &str,
+        ) -> Result<(surf::Client, String), HttpRequestError> {
+            let mut url = url::Url::parse(url).map_err(|e| HttpRequestError::PermanentError(e.to_string()))?;
+            let port = url
+                .port_or_known_default()
+                .ok_or_else(|| HttpRequestError::PermanentError(format!("missing port in '{url}'")))?;
+
+            let addr = match url.host() {
+                Some(url::Host::Ipv4(ip)) => SocketAddr::from((ip, port)),
+                Some(url::Host::Ipv6(ip)) => SocketAddr::from((ip, port)),
+                Some(url::Host::Domain(domain)) => self.resolve(domain, port).await?,
+                None => return Err(HttpRequestError::PermanentError(format!("missing host in '{url}'"))),
+            };
+
+            url.set_host(Some(&self.server_name))
+                .map_err(|e| HttpRequestError::PermanentError(e.to_string()))?;
+
+            let client = self
+                .clients
+                .get_with(addr, async {
+                    debug!(%addr, server_name = self.server_name, "creating surf client with server name override");
+                    build_client(cfg, Some(addr))
+                })
+                .await;
+
+            Ok((client, url.into()))
+        }
+    }
+
+    /// HTTP client that uses a non-Tokio runtime based HTTP client library, such as `surf`.
+    /// `surf` works also for Browsers in WASM environments.
+    #[derive(Clone, Debug, Default)]
+    pub struct SurfRequestor {
+        client: surf::Client,
+        server_name_override: Option<Arc<ServerNameOverride>>,
+        signer: Option<Arc<dyn RequestSigner>>,
+        cfg: HttpPostRequestorConfig,
+    }
+
+    impl SurfRequestor {
+        pub fn new(cfg: HttpPostRequestorConfig) -> Self {
+            info!(?cfg, "creating surf client");
+
+            let server_name_override = cfg
+                .tls_server_name
+                .clone()
+                .map(|server_name| Arc::new(ServerNameOverride::new(server_name)));
+
+            Self {
+                client: build_client(&cfg, None),
+                server_name_override,
+                signer: None,
+                cfg,
+            }
+        }
+
+        /// Signs each sent request with the given `signer`.
+        pub fn with_request_signer<S: RequestSigner + 'static>(mut self, signer: S) -> Self {
+            self.signer = Some(Arc::new(signer));
+            self
+        }
+    }
+
+    #[async_trait]
+    impl HttpRequestor for SurfRequestor {
+        async fn http_query<T>(
+            &self,
+            method: http_types::Method,
+            url: &str,
+            data: Option<T>,
+        ) -> Result<Box<[u8]>, HttpRequestError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            // The whole operation (resolution, connection, sending and reading of the body) is raced
+            // against the deadline. On timeout, the in-flight request is dropped, which aborts it.
+            async move {
+                let (client, url) = match &self.server_name_override {
+                    Some(server_name_override) => server_name_override.route(&self.cfg, url).await?,
+                    None => (self.client.clone(), url.to_owned()),
+                };
+
+                let body = super::request_body(&self.cfg.allowed_methods, method, data)?;
+                let mut request = client.request(method, url);
+
+                for (name, value) in signed_headers(self.signer.as_deref(), body.as_deref().unwrap_or_default()) {
+                    request = request.header(name.as_str(), value);
+                }
+
+                if let Some(body) = body {
+                    let mut body = surf::Body::from_bytes(body);
+                    body.set_mime(surf::http::mime::JSON);
+                    request = request.body(body);
+                }
+
+                match request.await {
+                    Ok(mut response) if response.status().is_success() => match response.body_bytes().await {
+                        Ok(data) => Ok(data.into_boxed_slice()),
+                        Err(e) => Err(classify_error(e)),
+                    },
+                    Ok(response) => Err(HttpRequestError::HttpError(
+                        response.status(),
+                        super::parse_rate_limit_headers(
+                            response.header(super::RETRY_AFTER_HEADER).map(|v| v.last().as_str()),
+                            response
+                                .header(super::RATELIMIT_RESET_HEADER)
+                                .map(|v| v.last().as_str()),
+                        ),
+                    )),
+                    Err(e) => Err(classify_error(e)),
+                }
+            }
+            .timeout(self.cfg.http_request_timeout)
+            .await
+            .map_err(|_| HttpRequestError::Timeout)?
+        }
+    }
+}
+
+#[cfg(any(test, feature = "runtime-tokio"))]
+pub mod reqwest_client {
+    use async_trait::async_trait;
+    use futures::{StreamExt, TryStreamExt};
+    use http_types::StatusCode;
+    use serde::Serialize;
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tracing::info;
+
+    use crate::buffer::BufferPool;
+    use crate::errors::HttpRequestError;
+    use crate::signer::{signed_headers, RequestSigner};
+    use crate::{HttpBodyStream, HttpPostRequestorConfig, HttpRequestor, StreamingHttpRequestor};
+
+    /// Maps the `reqwest` error to the [`HttpRequestError`], so that the retry policy can tell
+    /// transient failures from the permanent ones.
+    pub(crate) fn classify_error(e: reqwest::Error) -> HttpRequestError {
+        if e.is_status() {
+            HttpRequestError::HttpError(
+                StatusCode::try_from(e.status().map(|s| s.as_u16()).unwrap_or(500))
+                    .expect("status code must be compatible"), // cannot happen
+                None,
+            )
+        } else if e.is_timeout() {
+            HttpRequestError::Timeout
+        } else if e.is_builder() || e.is_redirect() {
+            HttpRequestError::PermanentError(e.to_string())
+        } else if e.is_connect() {
+            // TLS failures surface as connection errors, so the whole chain of causes must be inspected
+            let tls_error = std::iter::successors(std::error::Error::source(&e), |cause| cause.source())
+                .find_map(|cause| cause.downcast_ref::<native_tls::Error>());
+
+            match tls_error {
+                Some(tls_error) => HttpRequestError::PermanentError(format!("{e}: {tls_error}")),
+                None => HttpRequestError::TransportError(e.to_string()),
+            }
+        } else if e.is_request() || e.is_body() || e.is_decode() {
+            HttpRequestError::TransportError(e.to_string())
+        } else {
+            HttpRequestError::UnknownError(e.to_string())
+        }
+    }
+
+    /// HTTP client that uses a Tokio runtime-based HTTP client library, such as `reqwest`.
+    #[derive(Clone, Debug)]
+    pub struct ReqwestRequestor {
+        client: reqwest::Client,
+        limiter: Option<Arc<governor::DefaultKeyedRateLimiter<String>>>,
+        signer: Option<Arc<dyn RequestSigner>>,
+        allowed_methods: Vec<http_types::Method>,
+        body_chunk_size: Option<usize>,
+        buffers: BufferPool,
+    }
+
+    impl Default for ReqwestRequestor {
+        fn default() -> Self {
+            Self {
+                client: reqwest::Client::default(),
+                limiter: None,
+                signer: None,
+                allowed_methods: HttpPostRequestorConfig::default().allowed_methods,
+                body_chunk_size: None,
+                buffers: BufferPool::default(),
+            }
+        }
+    }
+
+    /// Writer serializing the request body into chunks of a fixed size.
+    ///
+    /// Unlike a single growing buffer, the body is never reallocated and copied during the serialization,
+    /// and each chunk is released as soon as it has been sent. A batch request is serialized incrementally,
+    /// one element at a time as the body is being sent, so that the whole body is never held in memory.
+    #[derive(Debug)]
+    struct ChunkedBody {
+        chunk_size: usize,
+        chunks: Vec<Vec<u8>>,
+    }
+
+    impl ChunkedBody {
+        fn new(chunk_size: usize) -> Self {
+            Self {
+                chunk_size: chunk_size.max(1),
+                chunks: Vec::new(),
+            }
+        }
+
+        /// Stream of the body chunks, serializing the next element of a batch each time it is polled.
+        fn stream<T: Serialize>(
+            data: &T,
+            chunk_size: usize,
+        ) -> serde_json::Result<impl futures::Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static> {
+            let (open, elements, close): (&[u8], _, &[u8]) = match serde_json::to_value(data)? {
+                serde_json::Value::Array(batch) => (b"[", batch, b"]"),
+                single => (b"", vec![single], b""),
+            };
+
+            // The opening bracket is sent with the first element, or with the closing one when the batch is empty
+            let close = if elements.is_empty() {
+                [open, close].concat()
+            } else {
+                close.to_vec()
+            };
+
+            Ok(futures::stream::iter(elements.into_iter().enumerate())
+                .map(move |(i, element)| {
+                    let mut body = Self::new(chunk_size);
+                    body.write_all(if i == 0 { open } else { b"," })?;
+                    serde_json::to_writer(&mut body, &element)?;
+                    Ok::<_, std::io::Error>(body.chunks)
+                })
+                .chain(futures::stream::once(futures::future::ready(Ok(vec![close]))))
+                .map_ok(|chunks| futures::stream::iter(chunks.into_iter().filter(|chunk| !chunk.is_empty()).map(Ok)))
+                .try_flatten())
+        }
+    }
+
+    impl std::io::Write for ChunkedBody {
+        fn write(&mut self, mut buf: &[u8]) -> std::io::Result<usize> {
+            let written = buf.len();
+            while !buf.is_empty() {
+                let chunk = match self.chunks.last_mut() {
+                    Some(chunk) if chunk.len() < self.chunk_size => chunk,
+                    _ => {
+                        self.chunks.push(Vec::with_capacity(self.chunk_size));
+                        self.chunks.last_mut().expect("chunk has just been added")
+                    }
+                };
+
+                let len = buf.len().min(self.chunk_size - chunk.len());
+                chunk.extend_from_slice(&buf[..len]);
+                buf = &buf[len..];
+            }
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl ReqwestRequestor {
+        pub fn new(cfg: HttpPostRequestorConfig) -> Self {
+            info!(?cfg, "creating reqwest client");
+            Self {
+                client: reqwest::Client::builder()
+                    .timeout(cfg.http_request_timeout)
+                    .redirect(reqwest::redirect::Policy::limited(cfg.max_redirects as usize))
+                    // 30 seconds is longer than the normal interval between RPC requests, thus the
+                    // connection should remain available
+                    .tcp_keepalive(Some(Duration::from_secs(30)))
+                    // Enable all supported encodings to reduce the amount of data transferred
+                    // in responses. This is relevant for large eth_getLogs responses.
+                    .zstd(true)
+                    .brotli(true)
+                    .build()
+                    .expect("could not build reqwest client"),
+                limiter: cfg
+                    .max_requests_per_sec
+                    .filter(|reqs| *reqs > 0) // Ensures the following unwrapping won't fail
+                    .map(|reqs| {
+                        Arc::new(governor::DefaultKeyedRateLimiter::keyed(governor::Quota::per_second(
+                            reqs.try_into().unwrap(),
+                        )))
+                    }),
+                signer: None,
+                allowed_methods: cfg.allowed_methods,
+                body_chunk_size: cfg.body_chunk_size.filter(|size| *size > 0),
+                buffers: BufferPool::default(),
+            }
+        }
+
+        /// Signs each sent request with the given `signer`.
+        pub fn with_request_signer<S: RequestSigner + 'static>(mut self, signer: S) -> Self {
+            self.signer = Some(Arc::new(signer));
+            self
+        }
+    }
+
+    impl ReqwestRequestor {
+        /// Sends the request and checks the status of the response, without reading its body.
+        async fn send<T>(
+            &self,
+            method: http_types::Method,
+            url: &str,
+            data: Option<T>,
+        ) -> Result<reqwest::Response, HttpRequestError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            let url = reqwest::Url::parse(url)
+                .map_err(|e| HttpRequestError::PermanentError(format!("url parse error: {e}")))?;
+
+            let reqwest_method = reqwest::Method::from_bytes(method.to_string().as_bytes())
+                .map_err(|_| HttpRequestError::UnsupportedMethod(method))?;
+            let mut builder = self.client.request(reqwest_method, url.clone());
+
+            // The signature is calculated over the whole body, so signed requests cannot be streamed
+            match self.body_chunk_size.filter(|_| self.signer.is_none()) {
+                Some(chunk_size) => {
+                    let chunks = super::request_body_with(&self.allowed_methods, method, data, |data| {
+                        ChunkedBody::stream(data, chunk_size)
+                    })?;
+
+                    if let Some(chunks) = chunks {
+                        builder = builder.body(reqwest::Body::wrap_stream(chunks));
+                    }
+                }
+                None => {
+                    let body = super::request_body_with(&self.allowed_methods, method, data, |data| {
+                        self.buffers.serialize(data)
+                    })?;
+                    let signed_body = body.as_deref().map(Vec::as_slice).unwrap_or_default();
+                    for (name, value) in signed_headers(self.signer.as_deref(), signed_body) {
+                        builder = builder.header(name, value);
+                    }
+
+                    // The buffer is returned to the pool once the body has been sent
+                    if let Some(body) = body {
+                        builder = builder.body(bytes::Bytes::from_owner(body));
+                    }
+                }
+            }
+
+            if self
+                .limiter
+                .clone()
+                .map(|limiter| limiter.check_key(&url.host_str().unwrap_or(".").to_string()).is_ok())
+                .unwrap_or(true)
+            {
+                let resp = builder
+                    .header("content-type", "application/json")
+                    .send()
+                    .await
+                    .map_err(classify_error)?;
+
+                if !resp.status().is_success() {
+                    let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+                    return Err(HttpRequestError::HttpError(
+                        StatusCode::try_from(resp.status().as_u16()).expect("status code must be compatible"), // cannot happen
+                        super::parse_rate_limit_headers(
+                            header(super::RETRY_AFTER_HEADER),
+                            header(super::RATELIMIT_RESET_HEADER),
+                        ),
+                    ));
+                }
+
+                Ok(resp)
+            } else {
+                Err(HttpRequestError::HttpError(StatusCode::TooManyRequests, None))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl HttpRequestor for ReqwestRequestor {
+        async fn http_query<T>(
+            &self,
+            method: http_types::Method,
+            url: &str,
+            data: Option<T>,
+        ) -> Result<Box<[u8]>, HttpRequestError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            self.send(method, url, data)
+                .await?
+                .bytes()
+                .await
+                .map(|b| Box::from(b.as_ref()))
+                .map_err(classify_error)
+        }
+    }
+
+    #[async_trait]
+    impl StreamingHttpRequestor for ReqwestRequestor {
+        async fn http_post_streamed<T>(&self, url: &str, data: T) -> Result<HttpBodyStream, HttpRequestError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            let resp = self.send(http_types::Method::Post, url, Some(data)).await?;
+            Ok(resp
+                .bytes_stream()
+                .map(|chunk| chunk.map(|b| Box::from(b.as_ref())).map_err(classify_error))
+                .boxed())
+        }
+    }
+}
+
+/// Builds the [HttpRequestor] of the HTTP client library matching the enabled runtime.
+///
+/// The `surf`-based [SurfRequestor](surf_client::SurfRequestor) is used with the `runtime-async-std` feature,
+/// the `reqwest`-based [ReqwestRequestor](reqwest_client::ReqwestRequestor) is used when only
+/// the `runtime-tokio` feature is enabled.
+#[cfg(any(feature = "runtime-async-std", feature = "runtime-tokio"))]
+pub fn build_http_requestor(cfg: crate::HttpPostRequestorConfig) -> Arc<dyn crate::ObjectSafeHttpRequestor> {
+    #[cfg(feature = "runtime-async-std")]
+    let requestor = surf_client::SurfRequestor::new(cfg);
+
+    // Both features could be enabled during testing, therefore reqwest is only used when tokio is exclusively enabled
+    #[cfg(all(feature = "runtime-tokio", not(feature = "runtime-async-std")))]
+    let requestor = reqwest_client::ReqwestRequestor::new(cfg);
+
+    Arc::new(requestor)
+}
+
+/// Async runtime whose HTTP client library a [`BoxedRequestor`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestorRuntime {
+    /// The `surf`-based [SurfRequestor](surf_client::SurfRequestor), requires the `runtime-async-std` feature.
+    AsyncStd,
+    /// The `reqwest`-based [ReqwestRequestor](reqwest_client::ReqwestRequestor), requires the `runtime-tokio` feature.
+    Tokio,
+}
+
+/// [HttpRequestor] holding the implementation of either of the runtimes, selected at construction.
+///
+/// Unlike [build_http_requestor], which picks the implementation at compile time, this allows a single
+/// build with both the `runtime-async-std` and `runtime-tokio` features to use the HTTP client library
+/// matching the runtime it actually runs on.
+#[cfg(any(test, feature = "runtime-async-std", feature = "runtime-tokio"))]
+#[derive(Debug, Clone)]
+pub enum BoxedRequestor {
+    #[cfg(any(test, feature = "runtime-async-std"))]
+    Surf(surf_client::SurfRequestor),
+    #[cfg(any(test, feature = "runtime-tokio"))]
+    Reqwest(reqwest_client::ReqwestRequestor),
+}
+
+#[cfg(any(test, feature = "runtime-async-std", feature = "runtime-tokio"))]
+impl BoxedRequestor {
+    /// Creates the requestor of the given `runtime`.
+    ///
+    /// Returns `None` if the support of the `runtime` has not been compiled in.
+    pub fn new(runtime: RequestorRuntime, cfg: crate::HttpPostRequestorConfig) -> Option<Self> {
+        match runtime {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            RequestorRuntime::AsyncStd => Some(Self::Surf(surf_client::SurfRequestor::new(cfg))),
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            RequestorRuntime::Tokio => Some(Self::Reqwest(reqwest_client::ReqwestRequestor::new(cfg))),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Runtime of the held requestor.
+    pub fn runtime(&self) -> RequestorRuntime {
+        match self {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            Self::Surf(_) => RequestorRuntime::AsyncStd,
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            Self::Reqwest(_) => RequestorRuntime::Tokio,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "runtime-async-std"))]
+impl From<surf_client::SurfRequestor> for BoxedRequestor {
+    fn from(value: surf_client::SurfRequestor) -> Self {
+        Self::Surf(value)
+    }
+}
+
+#[cfg(any(test, feature = "runtime-tokio"))]
+impl From<reqwest_client::ReqwestRequestor> for BoxedRequestor {
+    fn from(value: reqwest_client::ReqwestRequestor) -> Self {
+        Self::Reqwest(value)
+    }
+}
+
+#[cfg(any(test, feature = "runtime-async-std", feature = "runtime-tokio"))]
+#[async_trait]
+impl HttpRequestor for BoxedRequestor {
+    async fn http_query<T>(&self, method: Method, url: &str, data: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        match self {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            Self::Surf(requestor) => requestor.http_query(method, url, data).await,
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            Self::Reqwest(requestor) => requestor.http_query(method, url, data).await,
+        }
+    }
+
+    async fn http_post<T>(&self, url: &str, data: T) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        match self {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            Self::Surf(requestor) => requestor.http_post(url, data).await,
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            Self::Reqwest(requestor) => requestor.http_post(url, data).await,
+        }
+    }
+
+    async fn http_get(&self, url: &str) -> Result<Box<[u8]>, HttpRequestError> {
+        match self {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            Self::Surf(requestor) => requestor.http_get(url).await,
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            Self::Reqwest(requestor) => requestor.http_get(url).await,
+        }
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        match self {
+            #[cfg(any(test, feature = "runtime-async-std"))]
+            Self::Surf(requestor) => requestor.health_check(url).await,
+            #[cfg(any(test, feature = "runtime-tokio"))]
+            Self::Reqwest(requestor) => requestor.health_check(url).await,
+        }
+    }
+}
+
+/// Snapshot of a response cached by the [`SnapshotRequestor`].
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct RequestorResponseSnapshot {
+    pub(crate) id: usize,
+    pub(crate) request: String,
+    pub(crate) response: String,
+    /// Time offset of the request since the first captured request,
+    /// if [recorded](SnapshotRequestor::with_recorded_timing).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) offset_ms: Option<u64>,
+}
+
+/// Handling of the snapshot files exceeding the [maximum size](SnapshotRequestor::with_max_file_size).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OversizedSnapshotPolicy {
+    /// The load fails with an [`InvalidData`](std::io::ErrorKind::InvalidData) error.
+    #[default]
+    Fail,
+    /// The file is loaded entry by entry, so that only a single parsed entry is held in memory
+    /// besides the already loaded ones.
+    Stream,
+}
+
+/// Hands over each entry of a snapshot sequence to the callback as soon as it is deserialized.
+///
+/// Stops once the callback returns `false`.
+struct SnapshotEntryVisitor<F>(F);
+
+impl<'de, F: FnMut(RequestorResponseSnapshot) -> bool> serde::de::Visitor<'de> for SnapshotEntryVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of snapshot entries")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(mut self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(entry) = seq.next_element::<RequestorResponseSnapshot>()? {
+            if !(self.0)(entry) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replays an RPC response to a request if it is found in the snapshot YAML file.
+/// If no such request has been seen before,
+/// it captures the new request/response pair obtained from the inner [`HttpRequestor`]
+/// and stores it into the snapshot file.
+///
+/// By default, the responses are replayed by the content of the requests, regardless of their order.
+/// In the [strict order](SnapshotRequestor::with_strict_order) mode, the requests must also arrive
+/// in the same order as they were captured.
+///
+/// This is useful for snapshot testing only and should **NOT** be used in production.
+#[derive(Debug, Clone)]
+pub struct SnapshotRequestor<T> {
+    inner: T,
+    next_id: Arc<AtomicUsize>,
+    entries: moka::future::Cache<String, RequestorResponseSnapshot>,
+    used_requests: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    replay_order: Arc<std::sync::Mutex<std::collections::VecDeque<usize>>>,
+    file: String,
+    aggressive_save: bool,
+    fail_on_miss: bool,
+    ignore_snapshot: bool,
+    strict_order: bool,
+    max_file_size: Option<u64>,
+    oversized_policy: OversizedSnapshotPolicy,
+    load_failed: Arc<AtomicBool>,
+    started: Option<Arc<std::sync::OnceLock<std::time::Instant>>>,
+}
+
+impl<T> SnapshotRequestor<T> {
+    /// Creates a new instance by wrapping an existing [`HttpRequestor`] and capturing
+    /// the request/response pairs.
+    ///
+    /// The constructor does not load any [snapshot entries](SnapshotRequestor) from
+    /// the `snapshot_file`.
+    /// The [`SnapshotRequestor::load`] method must be used after construction to do that.
+    pub fn new(inner: T, snapshot_file: &str) -> Self {
+        Self {
+            inner,
+            next_id: Arc::new(AtomicUsize::new(1)),
+            entries: moka::future::Cache::builder().build(),
+            used_requests: Default::default(),
+            replay_order: Default::default(),
+            file: snapshot_file.to_owned(),
+            aggressive_save: false,
+            fail_on_miss: false,
+            ignore_snapshot: false,
+            strict_order: false,
+            max_file_size: None,
+            oversized_policy: OversizedSnapshotPolicy::default(),
+            load_failed: Arc::new(AtomicBool::new(false)),
+            started: None,
+        }
+    }
+
+    /// Gets the path to the snapshot disk file.
+    pub fn snapshot_path(&self) -> &str {
+        &self.file
+    }
+
+    /// Clears all entries from the snapshot in memory.
+    /// The snapshot file is not changed.
+    pub fn clear(&self) {
+        self.entries.invalidate_all();
+        self.used_requests.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.replay_order.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.next_id.store(1, Ordering::Relaxed);
+    }
+
+    /// Clears all entries and loads them from the snapshot file.
+    /// If `fail_on_miss` is set and the data is successfully loaded, all later
+    /// requests that miss the loaded snapshot will result in HTTP error 404.
+    ///
+    /// A file exceeding the [maximum size](SnapshotRequestor::with_max_file_size) is handled
+    /// according to the [`OversizedSnapshotPolicy`]. When [streamed](OversizedSnapshotPolicy::Stream),
+    /// the entries are cleared before the load and a failed load leaves no entries at all.
+    ///
+    /// If an existing snapshot file fails to load, the snapshot is no longer [saved](SnapshotRequestor::save),
+    /// so that the file is not overwritten with incomplete entries. A missing file is not a failure.
+    pub async fn try_load(&mut self, fail_on_miss: bool) -> Result<(), std::io::Error> {
+        if self.ignore_snapshot {
+            return Ok(());
+        }
+
+        let result = self.load_entries(fail_on_miss).await;
+        self.load_failed.store(
+            result.as_ref().is_err_and(|e| e.kind() != std::io::ErrorKind::NotFound),
+            Ordering::SeqCst,
+        );
+        result
+    }
+
+    async fn load_entries(&mut self, fail_on_miss: bool) -> Result<(), std::io::Error> {
+        let file = std::fs::File::open(&self.file)?;
+        let size = file.metadata()?.len();
+        if let Some(max_file_size) = self.max_file_size.filter(|max| size > *max) {
+            match self.oversized_policy {
+                OversizedSnapshotPolicy::Fail => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "snapshot file {} has {size} bytes, exceeding the maximum of {max_file_size} bytes",
+                            &self.file
+                        ),
+                    ));
+                }
+                OversizedSnapshotPolicy::Stream => {
+                    tracing::debug!(size, max_file_size, "streaming oversized snapshot file {}", &self.file);
+                    return self.try_load_streaming(file, fail_on_miss).await;
+                }
+            }
+        }
+
+        let loaded =
+            serde_yaml::from_reader::<_, Vec<RequestorResponseSnapshot>>(file).map_err(std::io::Error::other)?;
+
+        self.clear();
+
+        let mut replay_order = loaded.iter().map(|entry| entry.id).collect::<Vec<_>>();
+        replay_order.sort_unstable();
+        *self.replay_order.lock().unwrap_or_else(|e| e.into_inner()) = replay_order.into();
+
+        let loaded_len = futures::stream::iter(loaded)
+            .then(|entry| {
+                self.next_id.fetch_max(entry.id, Ordering::Relaxed);
+                self.entries.insert(entry.request.clone(), entry)
+            })
+            .collect::<Vec<_>>()
+            .await
+            .len();
+
+        if loaded_len > 0 {
+            self.fail_on_miss = fail_on_miss;
+        }
+
+        tracing::debug!("snapshot with {loaded_len} entries has been loaded from {}", &self.file);
+        Ok(())
+    }
+
+    /// Loads the snapshot `file` entry by entry, inserting each entry as soon as it is parsed.
+    ///
+    /// The file is parsed on a blocking thread, which hands over the parsed entries one by one.
+    async fn try_load_streaming(&mut self, file: std::fs::File, fail_on_miss: bool) -> Result<(), std::io::Error> {
+        use serde::Deserializer;
+
+        self.clear();
+
+        let (tx, mut parsed) = futures::channel::mpsc::channel(0);
+        drop(hopr_async_runtime::prelude::spawn_blocking(move || {
+            let mut tx = tx;
+            let result = serde_yaml::Deserializer::from_reader(file).deserialize_seq(SnapshotEntryVisitor(|entry| {
+                futures::executor::block_on(tx.send(Ok(entry))).is_ok()
+            }));
+            if let Err(e) = result {
+                let _ = futures::executor::block_on(tx.send(Err(std::io::Error::other(e))));
+            }
+        }));
+
+        let mut replay_order = Vec::new();
+        while let Some(entry) = parsed.next().await {
+            let entry: RequestorResponseSnapshot = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.clear();
+                    return Err(e);
+                }
+            };
+
+            self.next_id.fetch_max(entry.id, Ordering::Relaxed);
+            replay_order.push(entry.id);
+            self.entries.insert(entry.request.clone(), entry).await;
+        }
+
+        replay_order.sort_unstable();
+        let loaded_len = replay_order.len();
+        *self.replay_order.lock().unwrap_or_else(|e| e.into_inner()) = replay_order.into();
+
+        if loaded_len > 0 {
+            self.fail_on_miss = fail_on_miss;
+        }
+
+        tracing::debug!(
+            "snapshot with {loaded_len} entries has been streamed from {}",
+            &self.file
+        );
+        Ok(())
+    }
+
+    /// Similar as [`SnapshotRequestor::try_load`], except that no entries are cleared if the load fails.
+    ///
+    /// This method consumes and returns self for easier call chaining.
+    pub async fn load(mut self, fail_on_miss: bool) -> Self {
+        let _ = self.try_load(fail_on_miss).await;
+        self
+    }
+
+    /// Forces saving to disk on each newly inserted entry.
+    ///
+    /// Use this only when the expected number of entries in the snapshot is small.
+    pub fn with_aggresive_save(mut self) -> Self {
+        self.aggressive_save = true;
+        self
+    }
+
+    /// If set, the snapshot data will be ignored and resolution
+    /// will always be done with the inner requestor.
+    ///
+    /// This will inhibit any attempts to [`load`](SnapshotRequestor::try_load) or
+    /// [`save`](SnapshotRequestor::save) snapshot data.
+    pub fn with_ignore_snapshot(mut self, ignore_snapshot: bool) -> Self {
+        self.ignore_snapshot = ignore_snapshot;
+        self
+    }
+
+    /// Requires the requests replayed from a loaded snapshot to arrive in the order of their `id`s.
+    ///
+    /// Any request arriving out of the order results in an error, which catches accidental reordering
+    /// of the requests. The order is only enforced when replaying a snapshot loaded with `fail_on_miss`.
+    pub fn with_strict_order(mut self) -> Self {
+        self.strict_order = true;
+        self
+    }
+
+    /// Records the time offset of each newly captured request since the first captured request.
+    pub fn with_recorded_timing(mut self) -> Self {
+        self.started = Some(Default::default());
+        self
+    }
+
+    /// Limits the size of the snapshot file to [load](SnapshotRequestor::try_load) at once to `max_bytes`.
+    ///
+    /// Larger files are handled according to the `policy`. There is no limit by default.
+    pub fn with_max_file_size(mut self, max_bytes: u64, policy: OversizedSnapshotPolicy) -> Self {
+        self.max_file_size = Some(max_bytes);
+        self.oversized_policy = policy;
+        self
+    }
+
+    /// Number of the loaded snapshot entries not yet replayed
+    /// in the [strict order](SnapshotRequestor::with_strict_order).
+    pub fn pending_replay_count(&self) -> usize {
+        self.replay_order.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Drops the entries whose request has not been made since the snapshot was
+    /// [loaded](SnapshotRequestor::try_load), so that the next [save](SnapshotRequestor::save)
+    /// does not keep the dead fixtures.
+    ///
+    /// The dropped entries are no longer awaited in the [strict order](SnapshotRequestor::with_strict_order)
+    /// either. Entries captured after the load are kept. Returns the number of dropped entries.
+    pub async fn prune_unused(&self) -> usize {
+        let unused = {
+            let used_requests = self.used_requests.lock().unwrap_or_else(|e| e.into_inner());
+            self.entries
+                .iter()
+                .filter(|(request, _)| !used_requests.contains(request.as_str()))
+                .collect::<Vec<_>>()
+        };
+
+        for (request, _) in &unused {
+            self.entries.invalidate(request.as_str()).await;
+        }
+        self.entries.run_pending_tasks().await;
+
+        let unused_ids = unused
+            .iter()
+            .map(|(_, entry)| entry.id)
+            .collect::<std::collections::HashSet<_>>();
+        self.replay_order
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|id| !unused_ids.contains(id));
+
+        tracing::debug!("pruned {} unused entries of snapshot {}", unused.len(), &self.file);
+        unused.len()
+    }
+
+    /// Save the currently cached entries to the snapshot file on disk.
+    ///
+    /// Note that this method is automatically called on Drop, so usually it is unnecessary
+    /// to call it explicitly.
+    ///
+    /// Fails if the snapshot file has failed to [load](SnapshotRequestor::try_load).
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        if self.ignore_snapshot {
+            return Ok(());
+        }
+
+        if self.load_failed.load(Ordering::SeqCst) {
+            return Err(std::io::Error::other(format!(
+                "refusing to overwrite snapshot file {} which has failed to load",
+                self.file
+            )));
+        }
+
+        let values = self.sorted_entries();
+        write_snapshot_file(&self.file, &values)?;
+
+        tracing::debug!("snapshot with {} entries saved to file {}", values.len(), self.file);
+        Ok(())
+    }
+
+    /// Currently cached entries in the order of their `id`s.
+    pub(crate) fn sorted_entries(&self) -> Vec<RequestorResponseSnapshot> {
+        let mut values: Vec<RequestorResponseSnapshot> = self.entries.iter().map(|(_, r)| r).collect();
+        values.sort_unstable_by_key(|a| a.id);
+        values
+    }
+}
+
+/// Writes the snapshot `entries` into the snapshot file at `path`.
+pub(crate) fn write_snapshot_file(
+    path: impl AsRef<std::path::Path>,
+    entries: &[RequestorResponseSnapshot],
+) -> Result<(), std::io::Error> {
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+
+    serde_yaml::to_writer(&mut writer, entries).map_err(std::io::Error::other)?;
+
+    writer.flush()
+}
+
+impl<R: HttpRequestor> SnapshotRequestor<R> {
+    async fn http_post_with_snapshot<In>(&self, url: &str, data: In) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        In: Serialize + Send + Sync,
+    {
+        let request = serde_json::to_string(&data)
+            .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}")))?;
+
+        if self.strict_order && self.fail_on_miss {
+            self.check_replay_order(&request).await?;
+        }
+
+        let inserted = AtomicBool::new(false);
+        let result = self
+            .entries
+            .entry(request.clone())
+            .or_try_insert_with(async {
+                if self.fail_on_miss {
+                    tracing::error!("{request} is missing in {}", &self.file);
+                    return Err(HttpRequestError::HttpError(http_types::StatusCode::NotFound, None));
+                }
+
+                let offset_ms = self
+                    .started
+                    .as_ref()
+                    .map(|started| started.get_or_init(std::time::Instant::now).elapsed().as_millis() as u64);
+
+                let response = self.inner.http_post(url, data).await?;
+                let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+                inserted.store(true, Ordering::Relaxed);
+
+                tracing::debug!("saved new snapshot entry #{id}");
+                Ok(RequestorResponseSnapshot {
+                    id,
+                    request: request.clone(),
+                    response: String::from_utf8(response.into_vec())
+                        .map_err(|e| HttpRequestError::UnknownError(format!("unparseable data: {e}")))?,
+                    offset_ms,
+                })
+            })
+            .await
+            .map(|e| e.into_value().response.into_bytes().into_boxed_slice())
+            .map_err(|e: Arc<HttpRequestError>| e.as_ref().clone())?;
+
+        self.used_requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request.clone());
+
+        if inserted.load(Ordering::Relaxed) && self.aggressive_save {
+            tracing::debug!("{request} was NOT found and was resolved");
+            self.save().map_err(|e| HttpRequestError::UnknownError(e.to_string()))?;
+        } else {
+            tracing::debug!("{request} was found");
+        }
+
+        Ok(result)
+    }
+
+    /// Verifies that the `request` is the next one in the order of the loaded snapshot.
+    async fn check_replay_order(&self, request: &str) -> Result<(), HttpRequestError> {
+        let actual = self.entries.get(request).await.map(|entry| entry.id);
+
+        let mut replay_order = self.replay_order.lock().unwrap_or_else(|e| e.into_inner());
+        let expected = replay_order.front().copied();
+
+        if actual.is_some() && actual == expected {
+            replay_order.pop_front();
+            Ok(())
+        } else {
+            tracing::error!(
+                ?expected,
+                ?actual,
+                "{request} arrived out of the order of {}",
+                &self.file
+            );
+            Err(HttpRequestError::UnknownError(format!(
+                "request out of the snapshot order: expected entry #{expected:?}, got #{actual:?}"
+            )))
+        }
+    }
+
+    /// Health checks are not captured into the snapshot.
+    /// When replaying a loaded snapshot, the endpoint is always considered healthy.
+    async fn health_check_with_snapshot(&self, url: &str) -> bool {
+        self.fail_on_miss || self.inner.health_check(url).await
+    }
+}
+
+impl<T> Drop for SnapshotRequestor<T> {
+    fn drop(&mut self) {
+        if self.load_failed.load(Ordering::SeqCst) {
+            tracing::warn!("snapshot file {} has failed to load and is not saved", self.file);
+            return;
+        }
+
+        if let Err(e) = self.save() {
+            tracing::error!("failed to save snapshot: {e}");
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: HttpRequestor> HttpRequestor for SnapshotRequestor<R> {
+    async fn http_query<T>(&self, _: Method, _: &str, _: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        todo!()
+    }
+
+    async fn http_post<T>(&self, url: &str, data: T) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_post_with_snapshot(url, data).await
+    }
+
+    async fn http_get(&self, _url: &str) -> Result<Box<[u8]>, HttpRequestError> {
+        todo!()
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        self.health_check_with_snapshot(url).await
+    }
+}
+
+#[async_trait]
+impl<R: HttpRequestor> HttpRequestor for &SnapshotRequestor<R> {
+    async fn http_query<T>(&self, _: Method, _: &str, _: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        todo!()
+    }
+
+    async fn http_post<T>(&self, url: &str, data: T) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_post_with_snapshot(url, data).await
+    }
+
+    async fn http_get(&self, _url: &str) -> Result<Box<[u8]>, HttpRequestError> {
+        todo!()
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        self.health_check_with_snapshot(url).await
+    }
+}
+
+type AnvilRpcClient<R> = ethers::middleware::SignerMiddleware<
+    ethers::providers::Provider<JsonRpcProviderClient<R, SimpleJsonRpcRetryPolicy>>,
+    ethers::signers::Wallet<ethers::core::k256::ecdsa::SigningKey>,
+>;
+
+/// Used for testing. Creates Ethers RPC client to the local Anvil instance.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_rpc_client_to_anvil<R: HttpRequestor>(
+    backend: R,
+    anvil: &ethers::utils::AnvilInstance,
+    signer: &hopr_crypto_types::keypairs::ChainKeypair,
+) -> Arc<AnvilRpcClient<R>> {
+    use ethers::signers::Signer;
+    use hopr_crypto_types::keypairs::Keypair;
+
+    let wallet =
+        ethers::signers::LocalWallet::from_bytes(signer.secret().as_ref()).expect("failed to construct wallet");
+    let json_client = JsonRpcProviderClient::new(&anvil.endpoint(), backend, SimpleJsonRpcRetryPolicy::default());
+    let provider = ethers::providers::Provider::new(json_client).interval(Duration::from_millis(10_u64));
+
+    Arc::new(ethers::middleware::SignerMiddleware::new(
+        provider,
+        wallet.with_chain_id(anvil.chain_id()),
+    ))
+}
+
+/// Used for testing. Creates Ethers RPC clients to the local Anvil instance, one for each of the given signers.
+///
+/// All the clients share the same provider configuration and HTTP backend.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_rpc_clients_to_anvil<R: HttpRequestor + Clone>(
+    backend: R,
+    anvil: &ethers::utils::AnvilInstance,
+    signers: &[hopr_crypto_types::keypairs::ChainKeypair],
+) -> Vec<Arc<AnvilRpcClient<R>>> {
+    use ethers::signers::Signer;
+    use hopr_crypto_types::keypairs::Keypair;
+
+    let json_client = JsonRpcProviderClient::new(&anvil.endpoint(), backend, SimpleJsonRpcRetryPolicy::default());
+    let provider = ethers::providers::Provider::new(json_client).interval(Duration::from_millis(10_u64));
+
+    signers
+        .iter()
+        .map(|signer| {
+            let wallet =
+                ethers::signers::LocalWallet::from_bytes(signer.secret().as_ref()).expect("failed to construct wallet");
+            Arc::new(ethers::middleware::SignerMiddleware::new(
+                provider.clone(),
+                wallet.with_chain_id(anvil.chain_id()),
+            ))
+        })
+        .collect()
+}
+
+/// Used for testing. Enables or disables automatic mining of a block for each transaction on Anvil.
+pub async fn set_auto_mine<Req, R>(
+    client: &JsonRpcProviderClient<Req, R>,
+    enabled: bool,
+) -> Result<(), JsonRpcProviderClientError>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+{
+    client
+        .request::<_, serde_json::Value>("evm_setAutomine", [enabled])
+        .await
+        .map(|_| ())
+}
+
+/// Used for testing. Mines the given number of blocks on Anvil.
+pub async fn mine_blocks<Req, R>(
+    client: &JsonRpcProviderClient<Req, R>,
+    count: u64,
+) -> Result<(), JsonRpcProviderClientError>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+{
+    client
+        .request::<_, serde_json::Value>("anvil_mine", [ethers::types::U64::from(count)])
+        .await
+        .map(|_| ())
+}
+
+/// Used for testing. Sets the timestamp (in seconds) of the next block mined on Anvil.
+pub async fn set_next_block_timestamp<Req, R>(
+    client: &JsonRpcProviderClient<Req, R>,
+    timestamp: u64,
+) -> Result<(), JsonRpcProviderClientError>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+{
+    client
+        .request::<_, serde_json::Value>("evm_setNextBlockTimestamp", [timestamp])
+        .await
+        .map(|_| ())
+}
+
+/// Used for testing. Creates local Anvil instance forking the chain at `fork_url` at the pinned `fork_block`.
+///
+/// When block time is given, new blocks are mined periodically.
+/// Otherwise, a new block is mined per transaction.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_anvil_forked(
+    fork_url: &str,
+    fork_block: u64,
+    block_time: Option<Duration>,
+) -> ethers::utils::AnvilInstance {
+    // The anvil binary must be in the PATH.
+    let mut anvil = ethers::utils::Anvil::new().fork(fork_url).fork_block_number(fork_block);
+
+    if let Some(bt) = block_time {
+        anvil = anvil.block_time(bt.as_secs());
+    }
+
+    anvil.spawn()
+}
+
+/// Endpoint used by the clients replaying a snapshot of a forked chain, which is never actually contacted.
+const REPLAYED_ANVIL_ENDPOINT: &str = "http://127.0.0.1:8545";
+
+/// Used for testing. Creates Ethers RPC client to a local Anvil fork of the chain at `fork_url`
+/// pinned at `fork_block` (see [`create_anvil_forked`]), capturing the responses with the given `requestor`.
+///
+/// If the snapshot has been [loaded](SnapshotRequestor::try_load) with `fail_on_miss`, all the responses
+/// are replayed from it, so neither Anvil nor the network is needed and no Anvil instance is returned.
+/// Otherwise, the forked Anvil instance is spawned and returned, and must be kept alive while the client is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn create_rpc_client_to_forked_anvil<R: HttpRequestor>(
+    requestor: SnapshotRequestor<R>,
+    fork_url: &str,
+    fork_block: u64,
+    signer: &hopr_crypto_types::keypairs::ChainKeypair,
+) -> (
+    Arc<AnvilRpcClient<SnapshotRequestor<R>>>,
+    Option<ethers::utils::AnvilInstance>,
+) {
+    use hopr_crypto_types::keypairs::Keypair;
+
+    let anvil = (!requestor.fail_on_miss).then(|| create_anvil_forked(fork_url, fork_block, None));
+    let endpoint = anvil
+        .as_ref()
+        .map(|anvil| anvil.endpoint())
+        .unwrap_or_else(|| REPLAYED_ANVIL_ENDPOINT.into());
+
+    let wallet =
+        ethers::signers::LocalWallet::from_bytes(signer.secret().as_ref()).expect("failed to construct wallet");
+    let json_client = JsonRpcProviderClient::new(&endpoint, requestor, SimpleJsonRpcRetryPolicy::default());
+    let provider = ethers::providers::Provider::new(json_client).interval(Duration::from_millis(10_u64));
+
+    // The chain id is also obtained through the snapshot
+    let client = ethers::middleware::SignerMiddleware::new_with_provider_chain(provider, wallet)
+        .await
+        .expect("failed to obtain the chain id of the forked chain");
+
+    (Arc::new(client), anvil)
+}
+
+/// Used for testing. Allows sending transactions from the given `account` on Anvil without its private key.
+pub async fn impersonate_account<Req, R>(
+    client: &JsonRpcProviderClient<Req, R>,
+    account: ethers::types::Address,
+) -> Result<(), JsonRpcProviderClientError>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+{
+    client
+        .request::<_, serde_json::Value>("anvil_impersonateAccount", [account])
+        .await
+        .map(|_| ())
+}
+
+/// Used for testing. Stops the impersonation of the given `account` on Anvil (see [`impersonate_account`]).
+pub async fn stop_impersonating_account<Req, R>(
+    client: &JsonRpcProviderClient<Req, R>,
+    account: ethers::types::Address,
+) -> Result<(), JsonRpcProviderClientError>
+where
+    Req: HttpRequestor,
+    R: RetryPolicy<JsonRpcProviderClientError> + Send + Sync,
+{
+    client
+        .request::<_, serde_json::Value>("anvil_stopImpersonatingAccount", [account])
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use ethers::providers::JsonRpcClient;
+    use hopr_async_runtime::prelude::sleep;
+    use hopr_chain_types::utils::create_anvil;
+    use hopr_chain_types::{ContractAddresses, ContractInstances};
+    use hopr_crypto_types::keypairs::{ChainKeypair, Keypair};
+    use hopr_primitive_types::primitives::Address;
+    use http_types::Method;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::fmt::Debug;
+    use std::io::Write;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    use crate::client::reqwest_client::ReqwestRequestor;
+    use crate::client::surf_client::SurfRequestor;
+    use crate::client::{
+        create_rpc_client_to_anvil, create_rpc_client_to_forked_anvil, create_rpc_clients_to_anvil,
+        impersonate_account, mine_blocks, parse_rate_limit_headers, set_auto_mine, set_next_block_timestamp,
+        validate_rpc_url, BoxedRequestor, JsonRpcProviderClient, OversizedSnapshotPolicy, RecordingClock,
+        RequestorResponseSnapshot, RequestorRuntime, SimpleJsonRpcRetryPolicy, SnapshotRequestor,
+    };
+    use crate::errors::{HttpRequestError, JsonRpcProviderClientError, RetryReason};
+    use crate::{HttpRequestor, ObjectSafeHttpRequestor, RetryAction, RetryPolicy, ZeroRetryPolicy};
+
+    async fn deploy_contracts<R: HttpRequestor + Debug>(req: R) -> anyhow::Result<ContractAddresses> {
+        let anvil = create_anvil(None);
+        let chain_key_0 = ChainKeypair::from_secret(anvil.keys()[0].to_bytes().as_ref())?;
+
+        let client = create_rpc_client_to_anvil(req, &anvil, &chain_key_0);
+
+        let contracts = ContractInstances::deploy_for_testing(client.clone(), &chain_key_0)
+            .await
+            .expect("deploy failed");
+
+        Ok(ContractAddresses::from(&contracts))
+    }
+
+    #[async_std::test]
+    async fn test_clients_to_anvil_should_deploy_from_multiple_accounts_with_manual_mining() -> anyhow::Result<()> {
+        use anyhow::Context;
+        use ethers::providers::Middleware;
+
+        let anvil = create_anvil(None);
+        let keys = anvil.keys()[0..2]
+            .iter()
+            .map(|key| ChainKeypair::from_secret(key.to_bytes().as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let clients = create_rpc_clients_to_anvil(SurfRequestor::default(), &anvil, &keys);
+        assert_eq!(2, clients.len());
+        assert_ne!(clients[0].address(), clients[1].address());
+
+        ContractInstances::deploy_for_testing(clients[0].clone(), &keys[0])
+            .await
+            .expect("deploy from the first account failed");
+
+        let json_client = clients[1].provider().as_ref();
+        set_auto_mine(json_client, false).await?;
+
+        let block_before = clients[1].get_block_number().await?;
+        let timestamp = clients[1]
+            .get_block(block_before)
+            .await?
+            .context("block must exist")?
+            .timestamp
+            .as_u64()
+            + 1000;
+
+        let second = clients[1].clone();
+        let deployment =
+            async_std::task::spawn(
+                async move { hopr_bindings::hopr_token::HoprToken::deploy(second, ())?.send().await },
+            );
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            block_before,
+            clients[1].get_block_number().await?,
+            "no block must be mined automatically"
+        );
+
+        set_next_block_timestamp(json_client, timestamp).await?;
+        mine_blocks(json_client, 1).await?;
+
+        let token = deployment.await.context("deploy from the second account failed")?;
+        assert!(!clients[1].get_code(token.address(), None).await?.is_empty());
+
+        let block_after = clients[1]
+            .get_block(block_before + 1)
+            .await?
+            .context("block must be mined")?;
+        assert_eq!(timestamp, block_after.timestamp.as_u64());
+
+        mine_blocks(json_client, 3).await?;
+        assert_eq!(block_before + 4, clients[1].get_block_number().await?);
+
+        set_auto_mine(json_client, true).await?;
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_clones_should_generate_disjoint_monotonic_ids_concurrently() -> anyhow::Result<()> {
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+
+        let ids = futures::future::join_all((0..4).map(|_| {
+            let clone = client.clone();
+            async_std::task::spawn(async move {
+                let mut ids = Vec::with_capacity(1000);
+                for _ in 0..1000 {
+                    ids.push(clone.next_id().await);
+                    async_std::task::yield_now().await;
+                }
+                ids
+            })
+        }))
+        .await;
+
+        for clone_ids in &ids {
+            assert!(
+                clone_ids.windows(2).all(|w| w[0] < w[1]),
+                "ids of a clone must be increasing"
+            );
+        }
+
+        let all_ids = ids.iter().flatten().collect::<std::collections::HashSet<_>>();
+        assert_eq!(4000, all_ids.len(), "ids of clones must be disjoint");
+        assert!(all_ids.iter().all(|id| **id < (1 << 53)), "ids must be safe integers");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_instances_should_use_distinct_id_namespaces() {
+        let client_1 = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_unique_id_namespace();
+        let client_2 = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_unique_id_namespace();
+
+        let (id_1, id_2) = (client_1.next_id().await, client_2.next_id().await);
+        assert_ne!(id_1, id_2);
+        assert_eq!(1, id_1 & super::RPC_ID_SEQUENCE_MASK);
+        assert_eq!(1, id_2 & super::RPC_ID_SEQUENCE_MASK);
+        assert_ne!(0, id_1 >> super::RPC_ID_SEQUENCE_BITS);
+    }
+
+    #[async_std::test]
+    async fn test_client_should_start_from_the_initial_id() {
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_initial_id(100);
+
+        assert_eq!(100, client.next_id().await);
+        assert_eq!(101, client.clone().next_id().await);
+    }
+
+    #[async_std::test]
+    async fn test_client_ids_should_remain_monotonic_across_restarts_when_persisted() -> anyhow::Result<()> {
+        use anyhow::Context;
+
+        let id_file = NamedTempFile::new()?;
+        let path = id_file.path().to_str().context("invalid path")?;
+
+        let last_id = {
+            let client = JsonRpcProviderClient::new(
+                "http://localhost:8545",
+                SurfRequestor::default(),
+                SimpleJsonRpcRetryPolicy::default(),
+            )
+            .with_persisted_id(path)?;
+
+            let mut ids = Vec::new();
+            for _ in 0..super::RPC_ID_RESERVATION_BLOCK + 10 {
+                ids.push(client.next_id().await);
+            }
+            assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            assert!(
+                !std::path::Path::new(&format!("{path}.tmp")).exists(),
+                "temporary file must replace the high-water mark"
+            );
+            *ids.last().context("must have ids")?
+        };
+
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_persisted_id(path)?;
+
+        assert!(client.next_id().await > last_id, "id must continue after the restart");
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_id_should_wrap_around() {
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+        client.id.store(super::RPC_ID_SEQUENCE_MASK, Ordering::SeqCst);
+
+        let last = client.next_id().await;
+        let wrapped = client.next_id().await;
+        assert_eq!(super::RPC_ID_SEQUENCE_MASK, last & super::RPC_ID_SEQUENCE_MASK);
+        assert_eq!(1, wrapped & super::RPC_ID_SEQUENCE_MASK);
+        assert_eq!(
+            last >> super::RPC_ID_SEQUENCE_BITS,
+            wrapped >> super::RPC_ID_SEQUENCE_BITS
+        );
+    }
+
+    #[test]
+    fn test_validate_rpc_url_should_accept_http_and_https_urls() -> anyhow::Result<()> {
+        assert_eq!(
+            "localhost",
+            validate_rpc_url("http://localhost:8545")?
+                .host_str()
+                .unwrap_or_default()
+        );
+        assert_eq!(
+            "rpc.example.com",
+            validate_rpc_url("https://rpc.example.com/v1/key")?
+                .host_str()
+                .unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_rpc_url_should_reject_invalid_urls() {
+        for url in [
+            "",
+            "localhost:8545",
+            "http://",
+            "https://:8545",
+            "ws://localhost:8545",
+            "ftp://example.com",
+        ] {
+            assert!(
+                matches!(
+                    validate_rpc_url(url),
+                    Err(JsonRpcProviderClientError::InvalidUrl { .. })
+                ),
+                "url '{url}' must be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_client_try_new_should_fail_on_invalid_url() {
+        let res = JsonRpcProviderClient::try_new(
+            "htp:/localhost",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+        assert!(matches!(res, Err(JsonRpcProviderClientError::InvalidUrl { .. })));
+    }
+
+    #[async_std::test]
+    async fn test_client_should_deploy_contracts_via_surf() -> anyhow::Result<()> {
+        let contract_addrs = deploy_contracts(SurfRequestor::default()).await?;
+
+        assert_ne!(contract_addrs.token, Address::default());
+        assert_ne!(contract_addrs.channels, Address::default());
+        assert_ne!(contract_addrs.announcements, Address::default());
+        assert_ne!(contract_addrs.network_registry, Address::default());
+        assert_ne!(contract_addrs.safe_registry, Address::default());
+        assert_ne!(contract_addrs.price_oracle, Address::default());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_should_deploy_contracts_via_reqwest() -> anyhow::Result<()> {
+        let contract_addrs = deploy_contracts(ReqwestRequestor::default()).await?;
+
+        assert_ne!(contract_addrs.token, Address::default());
+        assert_ne!(contract_addrs.channels, Address::default());
+        assert_ne!(contract_addrs.announcements, Address::default());
+        assert_ne!(contract_addrs.network_registry, Address::default());
+        assert_ne!(contract_addrs.safe_registry, Address::default());
+        assert_ne!(contract_addrs.price_oracle, Address::default());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_get_block_number() -> anyhow::Result<()> {
+        let block_time = Duration::from_secs(1);
+
+        let anvil = create_anvil(Some(block_time));
+        let client = JsonRpcProviderClient::new(
+            &anvil.endpoint(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+
+        let mut last_number = 0;
 
         for _ in 0..3 {
             sleep(block_time).await;
 
-            let number: ethers::types::U64 = client.request("eth_blockNumber", ()).await?;
+            let number: ethers::types::U64 = client.request("eth_blockNumber", ()).await?;
+
+            assert!(number.as_u64() > last_number, "next block number must be greater");
+            last_number = number.as_u64();
+        }
+
+        assert_eq!(
+            0,
+            client.requests_enqueued.load(Ordering::SeqCst),
+            "retry queue should be zero on successful requests"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_fail_on_malformed_request() {
+        let anvil = create_anvil(None);
+        let client = JsonRpcProviderClient::new(
+            &anvil.endpoint(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+
+        let err = client
+            .request::<_, ethers::types::U64>("eth_blockNumber_bla", ())
+            .await
+            .expect_err("expected error");
+
+        assert!(matches!(err, JsonRpcProviderClientError::JsonRpcError(..)));
+    }
+
+    #[async_std::test]
+    async fn test_client_should_deduplicate_identical_in_flight_requests() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(200));
+                w.write_all(br#"{"jsonrpc": "2.0", "id": 1, "result": "0x10"}"#)
+            })
+            .expect(2)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_request_deduplication(true);
+
+        let results = futures::future::try_join_all(
+            (0..5).map(|_| client.request::<_, ethers::types::U64>("eth_blockNumber", ())),
+        )
+        .await?;
+        assert!(results.iter().all(|r| r.as_u64() == 16));
+
+        // The result must not be cached after the in-flight request resolved
+        assert_eq!(
+            16,
+            client
+                .request::<_, ethers::types::U64>("eth_blockNumber", ())
+                .await?
+                .as_u64()
+        );
+
+        m.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_limit_the_number_of_concurrent_requests() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let in_flight = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let max_in_flight = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (in_flight_clone, max_in_flight_clone) = (in_flight.clone(), max_in_flight.clone());
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_chunked_body(move |w| {
+                let current = in_flight_clone.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight_clone.fetch_max(current, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(100));
+                in_flight_clone.fetch_sub(1, Ordering::SeqCst);
+                w.write_all(br#"{"jsonrpc": "2.0", "id": 1, "result": "0x10"}"#)
+            })
+            .expect(6)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_max_concurrent_requests(Some(2), None);
+
+        let results = futures::future::try_join_all(
+            (0..6).map(|_| client.request::<_, ethers::types::U64>("eth_blockNumber", ())),
+        )
+        .await?;
+        assert!(results.iter().all(|r| r.as_u64() == 16));
+
+        m.assert();
+        let max_in_flight = max_in_flight.load(Ordering::SeqCst);
+        assert!(
+            (1..=2).contains(&max_in_flight),
+            "at most 2 requests must be in flight, but there were {max_in_flight}"
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_fail_fast_when_no_request_slot_becomes_available() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(300));
+                w.write_all(br#"{"jsonrpc": "2.0", "id": 1, "result": "0x10"}"#)
+            })
+            .expect(2)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                min_retries: Some(2),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_max_concurrent_requests(Some(1), Some(Duration::from_millis(50)));
+
+        // The first request takes the only slot, so the second one gives up waiting without any retries
+        let (first, second) = futures::join!(
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ()),
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ())
+        );
+        assert_eq!(16, first?.as_u64());
+        assert!(
+            matches!(
+                second,
+                Err(JsonRpcProviderClientError::ConcurrencyLimitReached {
+                    max_concurrent_requests: 1,
+                    ..
+                })
+            ),
+            "{second:?}"
+        );
+
+        // The slot is released once the first request finished
+        assert_eq!(
+            16,
+            client
+                .request::<_, ethers::types::U64>("eth_blockNumber", ())
+                .await?
+                .as_u64()
+        );
+
+        m.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_time_the_wait_for_a_request_slot_using_its_clock() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc": "2.0", "id": 1, "result": "0x10"}"#)
+            .expect(1)
+            .create();
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_clock(clock.clone())
+        .with_max_concurrent_requests(Some(1), Some(Duration::from_secs(600)));
+
+        // The wait of the second request elapses on the clock right away, instead of after the `max_wait`
+        let (first, second) = futures::join!(
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ()),
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ())
+        );
+        assert_eq!(16, first?.as_u64());
+        assert!(
+            matches!(second, Err(JsonRpcProviderClientError::ConcurrencyLimitReached { .. })),
+            "{second:?}"
+        );
+        assert_eq!(vec![Duration::from_secs(600)], clock.delays());
+
+        m.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_route_old_block_queries_to_archive_endpoint() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m_block_number = server
+            .mock("POST", "/primary")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc": "2.0", "id": 1, "result": "0x3e8"}"#)
+            .expect(1)
+            .create();
+
+        let m_primary = server
+            .mock("POST", "/primary")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_getBalance"})))
+            .with_body(r#"{"jsonrpc": "2.0", "id": 1, "result": "0x1"}"#)
+            .expect(3)
+            .create();
+
+        let m_archive = server
+            .mock("POST", "/archive")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_getBalance"})))
+            .with_body(r#"{"jsonrpc": "2.0", "id": 1, "result": "0x2"}"#)
+            .expect(2)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &format!("{}/primary", server.url()),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        )
+        .with_archive_endpoint(&format!("{}/archive", server.url()), 100)?;
+
+        let address = "0x0000000000000000000000000000000000000001";
+        let balance_at = |block: serde_json::Value| {
+            let client = client.clone();
+            async move {
+                client
+                    .request::<_, ethers::types::U256>("eth_getBalance", json!([address, block]))
+                    .await
+            }
+        };
+
+        // The latest block is not known yet
+        assert_eq!(1, balance_at(json!("0x1")).await?.as_u64());
+
+        assert_eq!(
+            1000,
+            client
+                .request::<_, ethers::types::U64>("eth_blockNumber", ())
+                .await?
+                .as_u64()
+        );
+
+        assert_eq!(1, balance_at(json!("latest")).await?.as_u64());
+        assert_eq!(1, balance_at(json!("0x384")).await?.as_u64(), "block within depth");
+        assert_eq!(2, balance_at(json!("0x383")).await?.as_u64(), "block beyond depth");
+        assert_eq!(2, balance_at(json!({"blockNumber": "earliest"})).await?.as_u64());
+
+        m_block_number.assert();
+        m_primary.assert();
+        m_archive.assert();
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_should_reject_invalid_archive_endpoint() {
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+
+        for url in ["localhost:8546", "ws://localhost:8546", "http://"] {
+            assert!(
+                matches!(
+                    client.clone().with_archive_endpoint(url, 100),
+                    Err(JsonRpcProviderClientError::InvalidUrl { .. })
+                ),
+                "url: {url}"
+            );
+        }
+    }
+
+    #[async_std::test]
+    async fn test_client_should_fail_on_malformed_response() {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("}malformed{")
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy::default(),
+        );
+
+        let err = client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+        assert!(matches!(err, JsonRpcProviderClientError::SerdeJson { .. }));
+    }
+
+    #[async_std::test]
+    async fn test_client_should_repair_quirky_responses_in_quirks_mode() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_chainId"})))
+            .with_body(r#"{"id":"1","result":"100"}"#)
+            .expect(2)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default());
+        let err = client
+            .request::<_, ethers::types::U64>("eth_chainId", ())
+            .await
+            .expect_err("quirky response must fail without the quirks mode");
+        assert!(matches!(err, JsonRpcProviderClientError::SerdeJson { .. }));
+
+        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default())
+            .with_quirks_mode(QuirksMode::default());
+        let chain_id = client.request::<_, ethers::types::U64>("eth_chainId", ()).await?;
+        assert_eq!(100, chain_id.as_u64());
+
+        m.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_requestor_health_check_should_succeed_on_healthy_endpoint() {
+        let anvil = create_anvil(None);
+        assert!(SurfRequestor::default().health_check(&anvil.endpoint()).await);
+    }
+
+    #[async_std::test]
+    async fn test_requestor_health_check_should_fail_on_unhealthy_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+
+        let m_err = server
+            .mock("POST", "/err")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_chainId"})))
+            .with_body(r#"{"jsonrpc": "2.0", "id": 0, "error": {"message": "some message", "code": -32000}}"#)
+            .expect(1)
+            .create();
+
+        let m_http = server
+            .mock("POST", "/http")
+            .with_status(http_types::StatusCode::ServiceUnavailable as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_chainId"})))
+            .with_body("{}")
+            .expect(1)
+            .create();
+
+        let requestor = SurfRequestor::default();
+        assert!(!requestor.health_check(&format!("{}/err", server.url())).await);
+        assert!(!requestor.health_check(&format!("{}/http", server.url())).await);
+
+        m_err.assert();
+        m_http.assert();
+    }
+
+    #[async_std::test]
+    async fn test_client_should_retry_on_http_error() {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::TooManyRequests as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(3)
+            .create();
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(2),
+                retryable_http_errors: vec![http_types::StatusCode::TooManyRequests],
+                initial_backoff: Duration::from_millis(100),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        let err = client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+        assert_eq!(
+            vec![Duration::from_millis(100), Duration::from_millis(130)],
+            clock.delays()
+        );
+        assert!(matches!(err, JsonRpcProviderClientError::BackendError(_)));
+        assert_eq!(
+            0,
+            client.requests_enqueued.load(Ordering::SeqCst),
+            "retry queue should be zero when policy says no more retries"
+        );
+        assert_eq!(2, client.stats().retries(RetryReason::Http429));
+        assert_eq!(1, client.stats().terminal_failures(RetryReason::Http429));
+    }
+
+    #[async_std::test]
+    async fn test_client_should_count_retries_by_reason() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        // Mocks which have not yet been hit the expected number of times take precedence
+        let rate_limited = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::TooManyRequests as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(1)
+            .create();
+
+        let succeeded = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(1)
+            .create();
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(2),
+                retryable_http_errors: vec![http_types::StatusCode::TooManyRequests],
+                initial_backoff: Duration::from_millis(100),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        let block_number = client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+        assert_eq!(42, block_number.as_u64());
+
+        rate_limited.assert();
+        succeeded.assert();
+        assert_eq!(vec![Duration::from_millis(100)], clock.delays());
+
+        let stats = client.stats();
+        let retried = RetryReason::ALL
+            .iter()
+            .filter(|reason| stats.retries(**reason) > 0)
+            .map(|reason| (reason.as_str(), stats.retries(*reason)))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![("http_429", 1)], retried);
+        assert!(
+            RetryReason::ALL
+                .iter()
+                .all(|reason| stats.terminal_failures(*reason) == 0),
+            "successful request must not count as a terminal failure"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_abort_pending_retries_when_cancelled() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::TooManyRequests as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(10),
+                retryable_http_errors: vec![http_types::StatusCode::TooManyRequests],
+                initial_backoff: Duration::from_secs(30),
+                max_backoff: Duration::from_secs(30),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        );
+
+        let cancellation = client.retry_cancellation();
+        let (res, _) = futures::join!(
+            async_std::future::timeout(
+                Duration::from_secs(5),
+                client.request::<_, ethers::types::U64>("eth_blockNumber", ())
+            ),
+            async {
+                sleep(Duration::from_millis(500)).await;
+                cancellation.cancel();
+            }
+        );
+
+        m.assert();
+        assert!(
+            matches!(res?, Err(JsonRpcProviderClientError::Cancelled)),
+            "request must not wait for the backoff after the cancellation"
+        );
+        assert_eq!(0, client.requests_enqueued.load(Ordering::SeqCst));
+
+        // Requests failing after the cancellation are not retried at all
+        assert!(matches!(
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await,
+            Err(JsonRpcProviderClientError::Cancelled)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rate_limit_headers() {
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            parse_rate_limit_headers(Some("3"), Some("10"))
+        );
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            parse_rate_limit_headers(None, Some("1.5"))
+        );
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            parse_rate_limit_headers(Some("Wed, 21 Oct 2015 07:28:00 GMT"), Some("10"))
+        );
+        assert_eq!(None, parse_rate_limit_headers(Some("invalid"), None));
+        assert_eq!(None, parse_rate_limit_headers(None, None));
+
+        let in_future = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 20;
+        let delay = parse_rate_limit_headers(None, Some(&in_future.to_string())).unwrap();
+        assert!(delay > Duration::from_secs(18) && delay <= Duration::from_secs(20));
+
+        assert_eq!(Some(Duration::ZERO), parse_rate_limit_headers(None, Some("1000000001")));
+    }
+
+    #[async_std::test]
+    async fn test_client_should_honor_retry_after_on_http_error() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::TooManyRequests as usize)
+            .with_header("Retry-After", "1")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(2)
+            .create();
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(1),
+                retryable_http_errors: vec![http_types::StatusCode::TooManyRequests],
+                initial_backoff: Duration::from_secs(30),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        let err = client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+        assert_eq!(
+            vec![Duration::from_secs(1)],
+            clock.delays(),
+            "retry must be done after the announced delay instead of the computed backoff"
+        );
+        assert!(matches!(
+            err,
+            JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(
+                http_types::StatusCode::TooManyRequests,
+                Some(d)
+            )) if d == Duration::from_secs(1)
+        ));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_keep_audit_log_of_recent_calls() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/v3/secret-api-key")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_getBalance"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(3)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &format!("{}/v3/secret-api-key", server.url()),
+            SurfRequestor::default(),
+            ZeroRetryPolicy::default(),
+        )
+        .with_audit_log(2, 8);
+
+        for account in [
+            "0x1000000000000000000000000000000000000000",
+            "0x2000000000000000000000000000000000000000",
+            "0x3000000000000000000000000000000000000000",
+        ] {
+            let balance = client
+                .request::<_, ethers::types::U256>("eth_getBalance", (account, "latest"))
+                .await?;
+            assert_eq!(42, balance.as_u64());
+        }
+
+        m.assert();
+
+        let records = client.stats().audit_snapshot();
+        assert_eq!(
+            vec![r#"["0x2000..."#, r#"["0x3000..."#],
+            records.iter().map(|r| r.params.as_str()).collect::<Vec<_>>(),
+            "oldest call must be evicted and the params truncated"
+        );
+        for record in records {
+            assert_eq!("eth_getBalance", record.method);
+            assert_eq!(format!("{}/***", server.url()), record.endpoint);
+            assert!(record.params_truncated);
+            assert_eq!(0, record.retries);
+            assert_eq!(crate::audit::CallOutcome::Success, record.outcome);
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_report_usage_of_the_rpc_methods() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
 
-            assert!(number.as_u64() > last_number, "next block number must be greater");
-            last_number = number.as_u64();
+        let body = r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#;
+        let m_ok = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(body)
+            .expect(2)
+            .create();
+        let m_err = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::ServiceUnavailable as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_chainId"})))
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default())
+            .with_usage_report(Duration::from_secs(60));
+
+        assert!(client.stats().usage_report().methods.is_empty());
+
+        for _ in 0..2 {
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+        }
+        client
+            .request::<_, ethers::types::U64>("eth_chainId", ())
+            .await
+            .expect_err("request should fail");
+
+        m_ok.assert();
+        m_err.assert();
+
+        let report = client.stats().usage_report();
+        assert_eq!(Duration::from_secs(60), report.window);
+
+        let block_number = &report.methods["eth_blockNumber"];
+        assert_eq!(2, block_number.calls);
+        assert_eq!(0, block_number.failures);
+        assert_eq!(2 * body.len() as u64, block_number.bytes);
+
+        let chain_id = &report.methods["eth_chainId"];
+        assert_eq!(1, chain_id.calls);
+        assert_eq!(1, chain_id.failures);
+        assert_eq!(0, chain_id.bytes);
+
+        // Logging the report must not fail
+        client.usage_report_logger()().await;
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_switch_the_url_of_all_clones() -> anyhow::Result<()> {
+        let mut old_server = mockito::Server::new_async().await;
+        let mut new_server = mockito::Server::new_async().await;
+
+        let m_old = old_server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x1"}"#)
+            .expect(2)
+            .create();
+        let m_new = new_server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2"}"#)
+            .expect(3)
+            .create();
+
+        let client =
+            JsonRpcProviderClient::new(&old_server.url(), SurfRequestor::default(), ZeroRetryPolicy::default());
+        let clone = client.clone();
+
+        for _ in 0..2 {
+            let res = client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+            assert_eq!(1, res.as_u64());
+        }
+
+        clone.set_url(&new_server.url())?;
+        assert_eq!(new_server.url(), client.url().as_ref());
+
+        for c in [&client, &clone, &client.clone()] {
+            let res = c.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+            assert_eq!(2, res.as_u64());
+        }
+
+        m_old.assert();
+        m_new.assert();
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_reject_invalid_url_updates() -> anyhow::Result<()> {
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            SurfRequestor::default(),
+            ZeroRetryPolicy::default(),
+        );
+
+        for url in ["ftp://localhost:8545", "http://", "not a url"] {
+            assert!(
+                matches!(client.set_url(url), Err(JsonRpcProviderClientError::InvalidUrl { .. })),
+                "{url} must be rejected"
+            );
+        }
+        assert_eq!("http://localhost:8545", client.url().as_ref());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_stats_should_be_collected_per_endpoint_until_reset() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m_ok = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(3)
+            .create();
+        let m_err = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::ServiceUnavailable as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_chainId"})))
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default());
+        let stats = client.stats();
+        let endpoint = crate::audit::redact_url(&server.url());
+
+        for _ in 0..2 {
+            client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+        }
+        // Clones share the statistics
+        client
+            .clone()
+            .request::<_, ethers::types::U64>("eth_chainId", ())
+            .await
+            .expect_err("request should fail");
+
+        let snapshot = stats.snapshot_and_reset();
+        let endpoint_stats = &snapshot.endpoints[&endpoint];
+        assert_eq!(2, endpoint_stats.successes);
+        assert_eq!(1, endpoint_stats.failures);
+        assert!(endpoint_stats.latency_p50 <= endpoint_stats.latency_max);
+        assert!(snapshot.period > Duration::ZERO);
+
+        client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+
+        let snapshot = client.stats().snapshot_and_reset();
+        assert_eq!(1, snapshot.endpoints[&endpoint].successes);
+        assert_eq!(0, snapshot.endpoints[&endpoint].failures);
+
+        m_ok.assert();
+        m_err.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_back_off_exponentially_up_to_max_backoff() {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(http_types::StatusCode::ServiceUnavailable as usize)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(6)
+            .create();
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default(),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(5),
+                initial_backoff: Duration::from_secs(1),
+                backoff_coefficient: 1.0,
+                max_backoff: Duration::from_secs(5),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+        assert_eq!(
+            [1, 2, 4, 5, 5].map(Duration::from_secs).to_vec(),
+            clock.delays(),
+            "backoff must double on each retry and be clamped at max_backoff"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_client_should_sign_each_request_attempt() -> anyhow::Result<()> {
+        use crate::signer::{HmacSha256Signer, DEFAULT_SIGNATURE_HEADER, DEFAULT_TIMESTAMP_HEADER};
+
+        let signer = HmacSha256Signer::new(b"gateway-secret");
+        let attempts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut server = mockito::Server::new_async().await;
+        let captured = attempts.clone();
+        let m = server
+            .mock("POST", "/")
+            .match_request(move |req| {
+                let header = |name| {
+                    req.header(name)
+                        .first()
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.to_string())
+                };
+                captured.lock().unwrap().push((
+                    header(DEFAULT_TIMESTAMP_HEADER),
+                    header(DEFAULT_SIGNATURE_HEADER),
+                    req.body().cloned().unwrap_or_default(),
+                ));
+                true
+            })
+            .with_status(http_types::StatusCode::ServiceUnavailable as usize)
+            .with_body("{}")
+            .expect(2)
+            .create();
+
+        let client = JsonRpcProviderClient::new(
+            &server.url(),
+            SurfRequestor::default().with_request_signer(signer.clone()),
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(1),
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(RecordingClock::default());
+
+        client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+
+        let attempts = attempts.lock().unwrap().clone();
+        assert_eq!(2, attempts.len(), "both attempts must be captured");
+        for (timestamp, signature, body) in attempts {
+            let timestamp = timestamp.expect("timestamp header must be present").parse::<u64>()?;
+            let signature = signature.expect("signature header must be present");
+            assert_eq!(
+                signer.signature(&body, timestamp),
+                signature,
+                "signature must match the body and the timestamp of the attempt"
+            );
         }
 
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_retry_transport_errors_at_constant_rate() {
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:1",
+            NullHttpPostRequestor,
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(3),
+                initial_backoff: Duration::from_secs(1),
+                backoff_coefficient: 1.0,
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        assert_eq!([1, 1, 1].map(Duration::from_secs).to_vec(), clock.delays());
+
+        let clock = RecordingClock::default();
+        let client = JsonRpcProviderClient::new(
+            "http://localhost:1",
+            NullHttpPostRequestor,
+            SimpleJsonRpcRetryPolicy {
+                max_retries: Some(3),
+                initial_backoff: Duration::from_secs(1),
+                backoff_coefficient: 1.0,
+                backoff_on_transport_errors: true,
+                ..SimpleJsonRpcRetryPolicy::default()
+            },
+        )
+        .with_clock(clock.clone());
+
+        client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        assert_eq!([1, 2, 4].map(Duration::from_secs).to_vec(), clock.delays());
+    }
+
+    async fn request_block_number_through_dyn_requestor(
+        requestor: std::sync::Arc<dyn ObjectSafeHttpRequestor>,
+    ) -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), requestor, ZeroRetryPolicy::default());
+        let block_number = client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+
+        m.assert();
+        assert_eq!(42, block_number.as_u64());
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_client_should_request_through_dyn_surf_requestor() -> anyhow::Result<()> {
+        request_block_number_through_dyn_requestor(std::sync::Arc::new(SurfRequestor::default())).await
+    }
+
+    #[async_std::test]
+    async fn test_dyn_requestor_should_pass_the_serialized_body_through() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        // Keys out of the alphabetical order and a big number, which would not survive a round-trip via a Value
+        let body = r#"{"method":"eth_call","jsonrpc":"2.0","params":[1.00000000000000000001],"id":1}"#;
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::Exact(body.into()))
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":"0x2a"}"#)
+            .expect(1)
+            .create();
+
+        let requestor: std::sync::Arc<dyn ObjectSafeHttpRequestor> = std::sync::Arc::new(SurfRequestor::default());
+        requestor.http_post_raw(&server.url(), body.as_bytes()).await?;
+
+        m.assert();
+        Ok(())
+    }
+
+    #[cfg(feature = "runtime-async-std")]
+    #[async_std::test]
+    async fn test_client_should_request_through_default_built_requestor() -> anyhow::Result<()> {
+        request_block_number_through_dyn_requestor(crate::client::build_http_requestor(Default::default())).await
+    }
+
+    #[tokio::test]
+    async fn test_client_should_request_through_dyn_reqwest_requestor() -> anyhow::Result<()> {
+        request_block_number_through_dyn_requestor(std::sync::Arc::new(ReqwestRequestor::default())).await
+    }
+
+    #[async_std::test]
+    async fn test_client_should_request_through_boxed_requestor_selected_for_async_std() -> anyhow::Result<()> {
+        let requestor = BoxedRequestor::new(RequestorRuntime::AsyncStd, Default::default())
+            .ok_or_else(|| anyhow::anyhow!("async-std requestor must be available"))?;
+        assert_eq!(RequestorRuntime::AsyncStd, requestor.runtime());
+
+        request_block_number_through_dyn_requestor(std::sync::Arc::new(requestor)).await
+    }
+
+    #[tokio::test]
+    async fn test_client_should_request_through_boxed_requestor_selected_for_tokio() -> anyhow::Result<()> {
+        let requestor = BoxedRequestor::new(RequestorRuntime::Tokio, Default::default())
+            .ok_or_else(|| anyhow::anyhow!("tokio requestor must be available"))?;
+        assert_eq!(RequestorRuntime::Tokio, requestor.runtime());
+
+        request_block_number_through_dyn_requestor(std::sync::Arc::new(requestor)).await
+    }
+
+    #[async_std::test]
+    async fn test_client_should_not_retry_with_zero_retry_policy() {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = server
+            .mock("POST", "/")
+            .with_status(404)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body("{}")
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default());
+
+        let err = client
+            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+            .await
+            .expect_err("expected error");
+
+        m.assert();
+        assert!(matches!(err, JsonRpcProviderClientError::BackendError(_)));
         assert_eq!(
             0,
             client.requests_enqueued.load(Ordering::SeqCst),
-            "retry queue should be zero on successful requests"
+            "retry queue should be zero when policy says no more retries"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_surf_requestor_should_address_overridden_tls_server_name() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let port = server.socket_address().port();
+
+        // The server name does not resolve, so the request can only succeed by connecting to the original host
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_header("host", format!("rpc.hoprnet.invalid:{port}").as_str())
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":0,"result":"0x2a"}"#)
+            .expect(2)
+            .create();
+
+        let requestor = SurfRequestor::new(crate::HttpPostRequestorConfig {
+            tls_server_name: Some("rpc.hoprnet.invalid".into()),
+            ..Default::default()
+        });
+        let client = JsonRpcProviderClient::new(&server.url(), requestor, ZeroRetryPolicy::default());
+
+        for _ in 0..2 {
+            let block_number = client.request::<_, ethers::types::U64>("eth_blockNumber", ()).await?;
+            assert_eq!(42, block_number.as_u64());
+        }
+
+        m.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_surf_requestor_should_time_out_when_connecting_to_blackholed_address() {
+        let cfg = crate::HttpPostRequestorConfig {
+            http_request_timeout: Duration::from_secs(2),
+            connect_timeout: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        // Non-routable address, where the connection attempt never completes
+        let start = std::time::Instant::now();
+        let err = SurfRequestor::new(cfg.clone())
+            .http_post("http://10.255.255.1:8545/", json!({}))
+            .await
+            .expect_err("request to a blackholed address must fail");
+
+        assert!(matches!(err, HttpRequestError::Timeout), "unexpected error: {err:?}");
+        assert!(
+            start.elapsed() < cfg.http_request_timeout + Duration::from_millis(500),
+            "request must not outlive the configured timeout"
         );
+    }
+
+    #[async_std::test]
+    async fn test_surf_requestor_should_classify_tls_mismatch_as_permanent_error() -> anyhow::Result<()> {
+        // The server speaks plain HTTP, so the TLS handshake can never succeed
+        let server = mockito::Server::new_async().await;
+        let url = format!("https://{}/", server.host_with_port());
+
+        let err = SurfRequestor::new(Default::default())
+            .http_post(&url, json!({}))
+            .await
+            .expect_err("TLS handshake must fail");
 
+        assert!(
+            matches!(err, HttpRequestError::PermanentError(_)),
+            "unexpected error: {err:?}"
+        );
         Ok(())
     }
 
     #[async_std::test]
-    async fn test_client_should_fail_on_malformed_request() {
-        let anvil = create_anvil(None);
-        let client = JsonRpcProviderClient::new(
-            &anvil.endpoint(),
-            SurfRequestor::default(),
-            SimpleJsonRpcRetryPolicy::default(),
-        );
+    async fn test_surf_requestor_should_only_use_allowed_methods() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("PUT", "/")
+            .match_body(mockito::Matcher::Json(json!({"key": "value"})))
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
+            .create();
 
-        let err = client
-            .request::<_, ethers::types::U64>("eth_blockNumber_bla", ())
+        let err = SurfRequestor::default()
+            .http_query(Method::Put, &server.url(), Some(json!({"key": "value"})))
             .await
-            .expect_err("expected error");
+            .expect_err("put must not be allowed by default");
+        assert_eq!(HttpRequestError::UnsupportedMethod(Method::Put), err);
 
-        assert!(matches!(err, JsonRpcProviderClientError::JsonRpcError(..)));
+        let requestor = SurfRequestor::new(crate::HttpPostRequestorConfig {
+            allowed_methods: vec![Method::Get, Method::Put],
+            ..Default::default()
+        });
+
+        let resp = requestor
+            .http_query(Method::Put, &server.url(), Some(json!({"key": "value"})))
+            .await?;
+        assert_eq!(b"ok", resp.as_ref());
+
+        let err = requestor
+            .http_post(&server.url(), json!({}))
+            .await
+            .expect_err("post must not be allowed");
+        assert_eq!(HttpRequestError::UnsupportedMethod(Method::Post), err);
+
+        m.assert();
+        Ok(())
     }
 
-    #[async_std::test]
-    async fn test_client_should_fail_on_malformed_response() {
+    #[tokio::test]
+    async fn test_reqwest_requestor_should_stream_chunked_request_bodies() -> anyhow::Result<()> {
         let mut server = mockito::Server::new_async().await;
 
+        let params = (0..100).map(|i| format!("0x{i:064x}")).collect::<Vec<_>>();
         let m = server
             .mock("POST", "/")
+            .match_header("transfer-encoding", "chunked")
+            .match_body(mockito::Matcher::Json(json!({"params": params})))
             .with_status(200)
-            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
-            .with_body("}malformed{")
+            .with_body("ok")
             .expect(1)
             .create();
 
-        let client = JsonRpcProviderClient::new(
-            &server.url(),
-            SurfRequestor::default(),
-            SimpleJsonRpcRetryPolicy::default(),
-        );
+        let requestor = ReqwestRequestor::new(crate::HttpPostRequestorConfig {
+            body_chunk_size: Some(100),
+            ..Default::default()
+        });
 
-        let err = client
-            .request::<_, ethers::types::U64>("eth_blockNumber", ())
-            .await
-            .expect_err("expected error");
+        let resp = requestor.http_post(&server.url(), json!({"params": params})).await?;
+        assert_eq!(b"ok", resp.as_ref());
 
         m.assert();
-        assert!(matches!(err, JsonRpcProviderClientError::SerdeJson { .. }));
+        Ok(())
     }
 
-    #[async_std::test]
-    async fn test_client_should_retry_on_http_error() {
+    #[tokio::test]
+    async fn test_reqwest_requestor_should_stream_chunked_batch_request_bodies() -> anyhow::Result<()> {
         let mut server = mockito::Server::new_async().await;
 
+        let batch = (0..50)
+            .map(|i| json!({"jsonrpc": "2.0", "id": i, "method": "eth_getBalance", "params": [format!("0x{i:040x}")]}))
+            .collect::<Vec<_>>();
         let m = server
             .mock("POST", "/")
-            .with_status(http_types::StatusCode::TooManyRequests as usize)
-            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
-            .with_body("{}")
-            .expect(3)
+            .match_header("transfer-encoding", "chunked")
+            .match_body(mockito::Matcher::Json(json!(batch)))
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
+            .create();
+        let m_empty = server
+            .mock("POST", "/")
+            .match_body("[]")
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
             .create();
 
-        let client = JsonRpcProviderClient::new(
-            &server.url(),
-            SurfRequestor::default(),
-            SimpleJsonRpcRetryPolicy {
-                max_retries: Some(2),
-                retryable_http_errors: vec![http_types::StatusCode::TooManyRequests],
-                initial_backoff: Duration::from_millis(100),
-                ..SimpleJsonRpcRetryPolicy::default()
-            },
+        let requestor = ReqwestRequestor::new(crate::HttpPostRequestorConfig {
+            body_chunk_size: Some(16),
+            ..Default::default()
+        });
+
+        assert_eq!(b"ok", requestor.http_post(&server.url(), &batch).await?.as_ref());
+        assert_eq!(
+            b"ok",
+            requestor
+                .http_post(&server.url(), Vec::<serde_json::Value>::new())
+                .await?
+                .as_ref()
         );
 
-        let err = client
-            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+        m.assert();
+        m_empty.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_requestor_should_only_use_allowed_methods() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let m = server
+            .mock("PATCH", "/")
+            .match_body(mockito::Matcher::Json(json!({"key": "value"})))
+            .with_status(200)
+            .with_body("ok")
+            .expect(1)
+            .create();
+
+        let requestor = ReqwestRequestor::new(crate::HttpPostRequestorConfig {
+            allowed_methods: vec![Method::Patch],
+            ..Default::default()
+        });
+
+        let resp = requestor
+            .http_query(Method::Patch, &server.url(), Some(json!({"key": "value"})))
+            .await?;
+        assert_eq!(b"ok", resp.as_ref());
+
+        let err = requestor
+            .http_get(&server.url())
             .await
-            .expect_err("expected error");
+            .expect_err("get must not be allowed");
+        assert_eq!(HttpRequestError::UnsupportedMethod(Method::Get), err);
+
+        m.assert();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_should_stream_logs_via_reqwest() -> anyhow::Result<()> {
+        use futures::TryStreamExt;
+
+        let mut server = mockito::Server::new_async().await;
+
+        let log = |i: u8| json!({"address": format!("0x{:040x}", i), "topics": [], "data": "0x"});
+        let m = server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_getLogs"})))
+            .with_body(json!({"jsonrpc": "2.0", "id": 1, "result": [log(1), log(2), log(3)]}).to_string())
+            .expect(1)
+            .create();
+
+        let client = JsonRpcProviderClient::new(&server.url(), ReqwestRequestor::default(), ZeroRetryPolicy::default());
+        let logs = client
+            .get_logs_streamed(&ethers::types::Filter::new().from_block(1))
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
 
         m.assert();
-        assert!(matches!(err, JsonRpcProviderClientError::BackendError(_)));
         assert_eq!(
-            0,
-            client.requests_enqueued.load(Ordering::SeqCst),
-            "retry queue should be zero when policy says no more retries"
+            vec![1, 2, 3],
+            logs.iter().map(|log| log.address.to_low_u64_be()).collect::<Vec<_>>()
         );
+
+        Ok(())
     }
 
-    #[async_std::test]
-    async fn test_client_should_not_retry_with_zero_retry_policy() {
+    #[tokio::test]
+    async fn test_client_should_fail_streamed_request_on_json_rpc_error() -> anyhow::Result<()> {
+        use futures::StreamExt;
+
         let mut server = mockito::Server::new_async().await;
 
         let m = server
             .mock("POST", "/")
-            .with_status(404)
-            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_blockNumber"})))
-            .with_body("{}")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "eth_getLogs"})))
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"query returned more than 10000 results"}}"#)
             .expect(1)
             .create();
 
-        let client = JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default());
+        let client = JsonRpcProviderClient::new(&server.url(), ReqwestRequestor::default(), ZeroRetryPolicy::default());
+        let results = client
+            .get_logs_streamed(&ethers::types::Filter::new())
+            .await?
+            .collect::<Vec<_>>()
+            .await;
 
-        let err = client
-            .request::<_, ethers::types::U64>("eth_blockNumber", ())
+        m.assert();
+        assert_eq!(1, results.len());
+        assert!(matches!(
+            &results[0],
+            Err(JsonRpcProviderClientError::JsonRpcError(err)) if err.code == -32005
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_requestor_should_classify_tls_mismatch_as_permanent_error() -> anyhow::Result<()> {
+        // The server speaks plain HTTP, so the TLS handshake can never succeed
+        let server = mockito::Server::new_async().await;
+        let url = format!("https://{}/", server.host_with_port());
+
+        let err = ReqwestRequestor::default()
+            .http_post(&url, json!({}))
             .await
-            .expect_err("expected error");
+            .expect_err("TLS handshake must fail");
 
-        m.assert();
-        assert!(matches!(err, JsonRpcProviderClientError::BackendError(_)));
-        assert_eq!(
-            0,
-            client.requests_enqueued.load(Ordering::SeqCst),
-            "retry queue should be zero when policy says no more retries"
+        assert!(
+            matches!(err, HttpRequestError::PermanentError(_)),
+            "unexpected error: {err:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_requestor_should_classify_refused_connection_as_transport_error() -> anyhow::Result<()> {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+
+        let err = ReqwestRequestor::default()
+            .http_post(&format!("http://127.0.0.1:{port}/"), json!({}))
+            .await
+            .expect_err("connection must be refused");
+
+        assert!(
+            matches!(err, HttpRequestError::TransportError(_)),
+            "unexpected error: {err:?}"
         );
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_policy_should_respect_min_backoff() {
+        let timeout = JsonRpcProviderClientError::BackendError(HttpRequestError::Timeout);
+        let rate_limited = JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(
+            http_types::StatusCode::TooManyRequests,
+            None,
+        ));
+
+        let policy = SimpleJsonRpcRetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            backoff_coefficient: 0.0,
+            ..SimpleJsonRpcRetryPolicy::default()
+        };
+        assert!(matches!(
+            policy.is_retryable_error(&rate_limited, 1, 0),
+            RetryAction::RetryAfter(d) if d == Duration::from_millis(1)
+        ));
+
+        let policy = SimpleJsonRpcRetryPolicy {
+            min_backoff: Some(Duration::from_millis(100)),
+            ..policy
+        };
+        for num_retries in 1..=3 {
+            assert!(matches!(
+                policy.is_retryable_error(&rate_limited, num_retries, 0),
+                RetryAction::RetryAfter(d) if d == Duration::from_millis(100)
+            ));
+            assert!(matches!(
+                policy.is_retryable_error(&timeout, num_retries, 0),
+                RetryAction::RetryAfter(d) if d == Duration::from_millis(100)
+            ));
+        }
+
+        // The floor does not shorten longer delays
+        let policy = SimpleJsonRpcRetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            ..policy
+        };
+        assert!(matches!(
+            policy.is_retryable_error(&rate_limited, 1, 0),
+            RetryAction::RetryAfter(d) if d == Duration::from_secs(1)
+        ));
+    }
+
+    #[test]
+    fn test_client_clones_should_share_the_retry_budget() {
+        let timeout = JsonRpcProviderClientError::BackendError(HttpRequestError::Timeout);
+        let budgeted_client = |max_retries_per_sec, retry_burst| {
+            JsonRpcProviderClient::new(
+                "http://localhost:8545",
+                SurfRequestor::default(),
+                SimpleJsonRpcRetryPolicy {
+                    max_retries_per_sec,
+                    retry_burst,
+                    ..SimpleJsonRpcRetryPolicy::default()
+                },
+            )
+        };
+
+        let client = budgeted_client(Some(0.0), 2);
+        let clone = client.clone();
+        assert_eq!(client.retry_policy, clone.retry_policy);
+
+        assert!(matches!(
+            client.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+            RetryAction::RetryAfter(_)
+        ));
+        assert!(matches!(
+            clone.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+            RetryAction::RetryAfter(_)
+        ));
+        assert!(matches!(
+            client.budgeted_retry_policy().is_retryable_error(&timeout, 2, 0),
+            RetryAction::NoRetry
+        ));
+        assert!(matches!(
+            clone.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+            RetryAction::NoRetry
+        ));
+
+        // The policy itself holds no state, so a new client with the same policy has a budget of its own
+        let other = budgeted_client(Some(0.0), 2);
+        assert!(matches!(
+            other.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+            RetryAction::RetryAfter(_)
+        ));
+
+        // Errors that are not retried anyway do not consume the budget
+        let client = budgeted_client(Some(0.0), 1);
+        let permanent = JsonRpcProviderClientError::BackendError(HttpRequestError::PermanentError("tls".into()));
+        assert!(matches!(
+            client.budgeted_retry_policy().is_retryable_error(&permanent, 1, 0),
+            RetryAction::NoRetry
+        ));
+        assert!(matches!(
+            client.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+            RetryAction::RetryAfter(_)
+        ));
+
+        // No limit by default
+        let client = budgeted_client(None, 10);
+        for _ in 0..100 {
+            assert!(matches!(
+                client.budgeted_retry_policy().is_retryable_error(&timeout, 1, 0),
+                RetryAction::RetryAfter(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_should_not_retry_permanent_errors() {
+        let policy = SimpleJsonRpcRetryPolicy::default();
+
+        assert!(matches!(
+            policy.is_retryable_error(
+                &JsonRpcProviderClientError::BackendError(HttpRequestError::PermanentError("tls".into())),
+                1,
+                0
+            ),
+            RetryAction::NoRetry
+        ));
+        assert!(matches!(
+            policy.is_retryable_error(
+                &JsonRpcProviderClientError::BackendError(HttpRequestError::TransportError("reset".into())),
+                1,
+                0
+            ),
+            RetryAction::RetryAfter(_)
+        ));
     }
 
     #[async_std::test]
@@ -1128,6 +4801,7 @@ mod tests {
             .expect(3)
             .create();
 
+        let clock = RecordingClock::default();
         let client = JsonRpcProviderClient::new(
             &server.url(),
             SurfRequestor::default(),
@@ -1137,7 +4811,8 @@ mod tests {
                 initial_backoff: Duration::from_millis(100),
                 ..SimpleJsonRpcRetryPolicy::default()
             },
-        );
+        )
+        .with_clock(clock.clone());
 
         let err = client
             .request::<_, ethers::types::U64>("eth_blockNumber", ())
@@ -1145,6 +4820,10 @@ mod tests {
             .expect_err("expected error");
 
         m.assert();
+        assert_eq!(
+            vec![Duration::from_millis(100), Duration::from_millis(130)],
+            clock.delays()
+        );
         assert!(matches!(err, JsonRpcProviderClientError::JsonRpcError(_)));
         assert_eq!(
             0,
@@ -1174,6 +4853,7 @@ mod tests {
             .expect(1)
             .create();
 
+        let clock = RecordingClock::default();
         let client = JsonRpcProviderClient::new(
             &server.url(),
             SurfRequestor::default(),
@@ -1183,7 +4863,8 @@ mod tests {
                 initial_backoff: Duration::from_millis(100),
                 ..SimpleJsonRpcRetryPolicy::default()
             },
-        );
+        )
+        .with_clock(clock.clone());
 
         let err = client
             .request::<_, ethers::types::U64>("eth_blockNumber", ())
@@ -1191,6 +4872,7 @@ mod tests {
             .expect_err("expected error");
 
         m.assert();
+        assert_eq!(Vec::<Duration>::new(), clock.delays());
         assert!(matches!(err, JsonRpcProviderClientError::JsonRpcError(_)));
         assert_eq!(
             0,
@@ -1220,6 +4902,7 @@ mod tests {
             .expect(2)
             .create();
 
+        let clock = RecordingClock::default();
         let client = JsonRpcProviderClient::new(
             &server.url(),
             SurfRequestor::default(),
@@ -1230,7 +4913,8 @@ mod tests {
                 initial_backoff: Duration::from_millis(100),
                 ..SimpleJsonRpcRetryPolicy::default()
             },
-        );
+        )
+        .with_clock(clock.clone());
 
         let err = client
             .request::<_, ethers::types::U64>("eth_blockNumber", ())
@@ -1238,6 +4922,7 @@ mod tests {
             .expect_err("expected error");
 
         m.assert();
+        assert_eq!(vec![Duration::from_millis(100)], clock.delays());
         assert!(matches!(err, JsonRpcProviderClientError::JsonRpcError(_)));
         assert_eq!(
             0,
@@ -1266,6 +4951,7 @@ mod tests {
             .expect(3)
             .create();
 
+        let clock = RecordingClock::default();
         let client = JsonRpcProviderClient::new(
             &server.url(),
             SurfRequestor::default(),
@@ -1275,7 +4961,8 @@ mod tests {
                 initial_backoff: Duration::from_millis(100),
                 ..SimpleJsonRpcRetryPolicy::default()
             },
-        );
+        )
+        .with_clock(clock.clone());
 
         let err = client
             .request::<_, ethers::types::U64>("eth_blockNumber", ())
@@ -1283,6 +4970,10 @@ mod tests {
             .expect_err("expected error");
 
         m.assert();
+        assert_eq!(
+            vec![Duration::from_millis(100), Duration::from_millis(130)],
+            clock.delays()
+        );
         assert!(matches!(err, JsonRpcProviderClientError::SerdeJson { .. }));
         assert_eq!(
             0,
@@ -1353,4 +5044,340 @@ mod tests {
 
         Ok(())
     }
+
+    #[test_log::test(async_std::test)]
+    async fn test_forked_client_should_replay_the_contract_storage_at_the_pinned_block() -> anyhow::Result<()> {
+        use ethers::providers::Middleware;
+        use ethers::types::{BlockId, BlockNumber, H256};
+
+        const SLOTS: u64 = 16;
+
+        // The origin chain stands in for the live chain being forked
+        let origin = create_anvil(None);
+        let chain_key_0 = ChainKeypair::from_secret(origin.keys()[0].to_bytes().as_ref())?;
+        let origin_client = create_rpc_client_to_anvil(SurfRequestor::default(), &origin, &chain_key_0);
+        let contracts = ContractInstances::deploy_for_testing(origin_client.clone(), &chain_key_0)
+            .await
+            .expect("deploy failed");
+
+        let token = contracts.token.address();
+        let fork_block = origin_client.get_block_number().await?.as_u64();
+        let at_fork_block = Some(BlockId::Number(BlockNumber::Number(fork_block.into())));
+
+        async fn read_storage<M: Middleware>(
+            client: &M,
+            token: ethers::types::Address,
+            at: Option<BlockId>,
+        ) -> anyhow::Result<Vec<H256>> {
+            let mut values = Vec::new();
+            for slot in 0..SLOTS {
+                values.push(
+                    client
+                        .get_storage_at(token, H256::from_low_u64_be(slot), at)
+                        .await
+                        .map_err(|e| anyhow::anyhow!("{e}"))?,
+                );
+            }
+            Ok(values)
+        }
+
+        let expected = read_storage(origin_client.as_ref(), token, at_fork_block).await?;
+        assert!(expected.iter().any(|v| !v.is_zero()), "token storage must not be empty");
+
+        let snapshot_file = NamedTempFile::new()?;
+        let snapshot_path = snapshot_file.path().to_str().unwrap();
+
+        // Capture the responses of the fork
+        {
+            let (client, fork) = create_rpc_client_to_forked_anvil(
+                SnapshotRequestor::new(SurfRequestor::default(), snapshot_path),
+                &origin.endpoint(),
+                fork_block,
+                &chain_key_0,
+            )
+            .await;
+            assert!(fork.is_some(), "the fork must be spawned when capturing");
+
+            assert_eq!(expected, read_storage(client.as_ref(), token, at_fork_block).await?);
+        }
+        drop(origin);
+
+        // Replay them with neither the fork nor the origin chain being available
+        let (client, fork) = create_rpc_client_to_forked_anvil(
+            SnapshotRequestor::new(NullHttpPostRequestor, snapshot_path)
+                .load(true)
+                .await,
+            "http://unreachable.invalid",
+            fork_block,
+            &chain_key_0,
+        )
+        .await;
+        assert!(fork.is_none(), "no fork must be spawned when replaying");
+
+        assert_eq!(expected, read_storage(client.as_ref(), token, at_fork_block).await?);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_impersonated_account_should_send_transactions_without_its_key() -> anyhow::Result<()> {
+        use ethers::providers::Middleware;
+
+        let anvil = create_anvil(None);
+        let chain_key_0 = ChainKeypair::from_secret(anvil.keys()[0].to_bytes().as_ref())?;
+        let client = create_rpc_client_to_anvil(SurfRequestor::default(), &anvil, &chain_key_0);
+
+        // The impersonated account is funded, but its key is never used
+        let impersonated = ethers::types::Address::from_low_u64_be(0x1234);
+        client
+            .send_transaction(
+                ethers::types::TransactionRequest::pay(impersonated, 10_u64.pow(18)),
+                None,
+            )
+            .await?
+            .await?;
+
+        impersonate_account(client.provider().as_ref(), impersonated).await?;
+
+        let receipt = client
+            .provider()
+            .send_transaction(
+                ethers::types::TransactionRequest::pay(client.address(), 1000_u64).from(impersonated),
+                None,
+            )
+            .await?
+            .await?;
+        assert!(receipt.is_some_and(|r| r.from == impersonated));
+
+        Ok(())
+    }
+
+    fn write_snapshot(requests: &[serde_json::Value]) -> anyhow::Result<NamedTempFile> {
+        let entries = requests
+            .iter()
+            .enumerate()
+            .map(|(i, request)| {
+                Ok(RequestorResponseSnapshot {
+                    id: i + 1,
+                    request: serde_json::to_string(request)?,
+                    response: format!("{{\"result\":{i}}}"),
+                    offset_ms: None,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let snapshot_file = NamedTempFile::new()?;
+        serde_yaml::to_writer(snapshot_file.as_file(), &entries)?;
+        Ok(snapshot_file)
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_requestor_should_replay_in_strict_order() -> anyhow::Result<()> {
+        let requests = [
+            json!({"id": 1, "method": "a"}),
+            json!({"id": 2, "method": "b"}),
+            json!({"id": 3, "method": "a"}),
+        ];
+        let snapshot_file = write_snapshot(&requests)?;
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_strict_order()
+            .load(true)
+            .await;
+        assert_eq!(3, requestor.pending_replay_count());
+
+        for (i, request) in requests.iter().enumerate() {
+            let response = requestor.http_post("http://localhost", request).await?;
+            assert_eq!(format!("{{\"result\":{i}}}").as_bytes(), response.as_ref());
+        }
+        assert_eq!(0, requestor.pending_replay_count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_requestor_should_fail_on_requests_out_of_strict_order() -> anyhow::Result<()> {
+        let requests = [
+            json!({"id": 1, "method": "a"}),
+            json!({"id": 2, "method": "b"}),
+            json!({"id": 3, "method": "c"}),
+        ];
+        let snapshot_file = write_snapshot(&requests)?;
+
+        // The content-keyed mode does not care about the order
+        {
+            let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+                .load(true)
+                .await;
+
+            for request in requests.iter().rev() {
+                requestor.http_post("http://localhost", request).await?;
+            }
+        }
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_strict_order()
+            .load(true)
+            .await;
+
+        requestor.http_post("http://localhost", &requests[0]).await?;
+
+        let err = requestor
+            .http_post("http://localhost", &requests[2])
+            .await
+            .expect_err("request out of order must fail");
+        assert!(matches!(err, HttpRequestError::UnknownError(_)));
+        assert_eq!(
+            2,
+            requestor.pending_replay_count(),
+            "failed request must not advance the replay"
+        );
+
+        requestor.http_post("http://localhost", &requests[1]).await?;
+        requestor.http_post("http://localhost", &requests[2]).await?;
+        assert_eq!(0, requestor.pending_replay_count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_requestor_should_stream_multiline_entries_and_keep_malformed_files() -> anyhow::Result<()> {
+        let entries = (1..=3)
+            .map(|id| RequestorResponseSnapshot {
+                id,
+                request: format!("{{\"id\":{id}}}"),
+                response: format!("{{\"result\":\n- {id}\n}}"),
+                offset_ms: None,
+            })
+            .collect::<Vec<_>>();
+        let snapshot_file = NamedTempFile::new()?;
+        serde_yaml::to_writer(snapshot_file.as_file(), &entries)?;
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_max_file_size(1, OversizedSnapshotPolicy::Stream)
+            .load(true)
+            .await;
+        for entry in &entries {
+            let response = requestor
+                .http_post(
+                    "http://localhost",
+                    serde_json::from_str::<serde_json::Value>(&entry.request)?,
+                )
+                .await?;
+            assert_eq!(entry.response.as_bytes(), response.as_ref());
+        }
+        drop(requestor);
+
+        let malformed = format!("{}- id: [\n", std::fs::read_to_string(snapshot_file.path())?);
+        std::fs::write(snapshot_file.path(), &malformed)?;
+
+        let mut requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_max_file_size(1, OversizedSnapshotPolicy::Stream);
+        assert!(
+            requestor.try_load(true).await.is_err(),
+            "malformed file must fail to load"
+        );
+        assert_eq!(0, requestor.pending_replay_count());
+        drop(requestor);
+        assert_eq!(
+            malformed,
+            std::fs::read_to_string(snapshot_file.path())?,
+            "snapshot file must not be overwritten on drop after a failed load"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_requestor_should_handle_oversized_snapshot_files() -> anyhow::Result<()> {
+        let requests = (0..50)
+            .map(|i| json!({"id": i, "method": format!("method_{i}")}))
+            .collect::<Vec<_>>();
+        let snapshot_file = write_snapshot(&requests)?;
+        let size = snapshot_file.as_file().metadata()?.len();
+
+        let mut requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_max_file_size(size - 1, OversizedSnapshotPolicy::Fail);
+        let err = requestor
+            .try_load(true)
+            .await
+            .expect_err("oversized file must be refused");
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(0, requestor.pending_replay_count());
+        assert!(
+            requestor.save().is_err(),
+            "snapshot which failed to load must not be saved"
+        );
+        drop(requestor);
+        assert_eq!(
+            size,
+            snapshot_file.as_file().metadata()?.len(),
+            "snapshot file must not be overwritten on drop after a failed load"
+        );
+
+        // Within the limit, the file is loaded as usual
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_max_file_size(size, OversizedSnapshotPolicy::Fail)
+            .load(true)
+            .await;
+        assert_eq!(requests.len(), requestor.pending_replay_count());
+        drop(requestor);
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .with_max_file_size(size - 1, OversizedSnapshotPolicy::Stream)
+            .with_strict_order()
+            .load(true)
+            .await;
+        assert_eq!(requests.len(), requestor.pending_replay_count());
+
+        for (i, request) in requests.iter().enumerate() {
+            let response = requestor.http_post("http://localhost", request).await?;
+            assert_eq!(format!("{{\"result\":{i}}}").as_bytes(), response.as_ref());
+        }
+        assert_eq!(0, requestor.pending_replay_count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_snapshot_requestor_should_prune_entries_unused_since_load() -> anyhow::Result<()> {
+        let requests = (0..4)
+            .map(|i| json!({"id": i, "method": format!("method_{i}")}))
+            .collect::<Vec<_>>();
+        let snapshot_file = write_snapshot(&requests)?;
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .load(false)
+            .await;
+        for i in [1, 3] {
+            let response = requestor.http_post("http://localhost", &requests[i]).await?;
+            assert_eq!(format!("{{\"result\":{i}}}").as_bytes(), response.as_ref());
+        }
+
+        assert_eq!(4, requestor.pending_replay_count());
+        assert_eq!(2, requestor.prune_unused().await);
+        assert_eq!(
+            2,
+            requestor.pending_replay_count(),
+            "pruned entries must not be awaited in the replay order"
+        );
+        assert_eq!(0, requestor.prune_unused().await, "used entries must be kept");
+        drop(requestor);
+
+        let requestor = SnapshotRequestor::new(NullHttpPostRequestor, snapshot_file.path().to_str().unwrap())
+            .load(true)
+            .await;
+        for i in [1, 3] {
+            let response = requestor.http_post("http://localhost", &requests[i]).await?;
+            assert_eq!(format!("{{\"result\":{i}}}").as_bytes(), response.as_ref());
+        }
+        for i in [0, 2] {
+            assert!(
+                requestor.http_post("http://localhost", &requests[i]).await.is_err(),
+                "pruned entry {i} must not be saved"
+            );
+        }
+
+        Ok(())
+    }
 }
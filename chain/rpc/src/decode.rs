@@ -0,0 +1,339 @@
+//! Decoding of contract logs into typed events, in parallel where it helps.
+//!
+//! Fetching logs is I/O-bound, but decoding thousands of them is CPU-bound. The [DecodedLogStream]
+//! takes a stream of logs (e.g. the chunked `eth_getLogs` stream used by the indexer). It decodes each log
+//! using the [LogDecoders] registered for its event signature. At most a fixed number of logs is decoded
+//! at once, and the decoded logs come out in the same order the logs went in. The logs from the chain are
+//! ordered by `(block, log_index)`, so the decoded stream is too.
+//!
+//! With the `rayon` feature enabled, the decoding is offloaded onto the CPU thread pool.
+//! Otherwise, it is offloaded onto the blocking thread pool of the async runtime.
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use hopr_crypto_types::types::Hash;
+
+use crate::errors::{LogDecodeError, Result, RpcError};
+use crate::Log;
+
+/// Default maximum number of logs decoded at once by the [DecodedLogStream].
+pub const DEFAULT_MAX_PARALLEL_LOG_DECODES: usize = 16;
+
+/// Decodes a raw log of a single event type.
+pub type LogDecoder<T> = Arc<dyn Fn(&Log) -> std::result::Result<T, String> + Send + Sync>;
+
+/// Decoders of the contract events keyed by their signature (the first topic of the log).
+pub struct LogDecoders<T> {
+    decoders: HashMap<Hash, LogDecoder<T>>,
+}
+
+impl<T> Default for LogDecoders<T> {
+    fn default() -> Self {
+        Self {
+            decoders: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Clone for LogDecoders<T> {
+    fn clone(&self) -> Self {
+        Self {
+            decoders: self.decoders.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for LogDecoders<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogDecoders")
+            .field("signatures", &self.decoders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T> LogDecoders<T> {
+    /// Registers the `decoder` of the logs with the given event `signature`.
+    ///
+    /// Replaces any decoder previously registered for the same signature.
+    pub fn with_event<F>(mut self, signature: Hash, decoder: F) -> Self
+    where
+        F: Fn(&Log) -> std::result::Result<T, String> + Send + Sync + 'static,
+    {
+        self.decoders.insert(signature, Arc::new(decoder));
+        self
+    }
+
+    /// Decodes the given `log` using the decoder registered for its event signature.
+    pub fn decode(&self, log: &Log) -> std::result::Result<T, LogDecodeError> {
+        let signature = log.topics.first().ok_or(LogDecodeError::MissingSignature)?;
+        let decoder = self
+            .decoders
+            .get(signature)
+            .ok_or(LogDecodeError::UnknownEvent(*signature))?;
+
+        decoder(log).map_err(LogDecodeError::Decoder)
+    }
+}
+
+/// Log yielded by the [DecodedLogStream].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedLog<T> {
+    /// The `log` was decoded into the `event`.
+    Event { log: Log, event: T },
+    /// The `log` could not be decoded.
+    Failed { log: Log, error: LogDecodeError },
+}
+
+impl<T> DecodedLog<T> {
+    fn new(log: Log, decoders: &LogDecoders<T>) -> Self {
+        match decoders.decode(&log) {
+            Ok(event) => Self::Event { log, event },
+            Err(error) => Self::Failed { log, error },
+        }
+    }
+
+    /// The raw log.
+    pub fn log(&self) -> &Log {
+        match self {
+            Self::Event { log, .. } | Self::Failed { log, .. } => log,
+        }
+    }
+
+    /// The decoded event, if the decoding succeeded.
+    pub fn event(&self) -> Option<&T> {
+        match self {
+            Self::Event { event, .. } => Some(event),
+            Self::Failed { .. } => None,
+        }
+    }
+}
+
+async fn decode_log<T: Send + 'static>(log: Log, decoders: Arc<LogDecoders<T>>) -> DecodedLog<T> {
+    #[cfg(feature = "rayon")]
+    {
+        hopr_parallelize::cpu::spawn_blocking(move || DecodedLog::new(log, &decoders)).await
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        // The output of the join handle differs between the runtimes, so the result is sent back instead
+        let (tx, rx) = futures::channel::oneshot::channel();
+        drop(hopr_async_runtime::prelude::spawn_blocking(move || {
+            let _ = tx.send(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                DecodedLog::new(log, &decoders)
+            })));
+        }));
+
+        rx.await
+            .expect("spawned log decoding should be awaitable")
+            .unwrap_or_else(|caught_panic| std::panic::resume_unwind(caught_panic))
+    }
+}
+
+/// Stream decoding the logs of the underlying log stream into typed events.
+///
+/// Errors of the underlying stream are passed through, while the failures to decode a log
+/// are yielded as [DecodedLog::Failed] and do not end the stream.
+pub struct DecodedLogStream<'a, T> {
+    inner: BoxStream<'a, Result<DecodedLog<T>>>,
+}
+
+impl<'a, T: Send + 'static> DecodedLogStream<'a, T> {
+    /// Decodes the given `logs` using the `decoders`, at most `max_parallel_decodes` at once.
+    pub fn new<S>(logs: S, decoders: LogDecoders<T>, max_parallel_decodes: usize) -> Self
+    where
+        S: Stream<Item = Result<Log>> + Send + 'a,
+    {
+        let decoders = Arc::new(decoders);
+        let inner = logs
+            .map(move |log| {
+                let decoders = decoders.clone();
+                async move { Ok::<_, RpcError>(decode_log(log?, decoders).await) }
+            })
+            // Keeps the order of the input, regardless of which decoding finishes first
+            .buffered(max_parallel_decodes.max(1))
+            .boxed();
+
+        Self { inner }
+    }
+}
+
+impl<T> Stream for DecodedLogStream<'_, T> {
+    type Item = Result<DecodedLog<T>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_primitive_types::prelude::*;
+
+    lazy_static::lazy_static! {
+        static ref TRANSFER: Hash = Hash::create(&[b"Transfer(uint256)"]);
+        static ref APPROVAL: Hash = Hash::create(&[b"Approval(uint256)"]);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestEvent {
+        Transfer(U256),
+        Approval(U256),
+    }
+
+    fn decode_amount(log: &Log) -> std::result::Result<U256, String> {
+        if log.data.len() == 32 {
+            Ok(U256::from_big_endian(&log.data))
+        } else {
+            Err(format!("invalid data length {}", log.data.len()))
+        }
+    }
+
+    fn decoders() -> LogDecoders<TestEvent> {
+        LogDecoders::default()
+            .with_event(*TRANSFER, |log| decode_amount(log).map(TestEvent::Transfer))
+            .with_event(*APPROVAL, |log| decode_amount(log).map(TestEvent::Approval))
+    }
+
+    fn raw_log(block_number: u64, log_index: u64, topics: Vec<Hash>, data: &[u8]) -> Log {
+        Log {
+            address: Address::default(),
+            topics,
+            data: data.into(),
+            tx_index: 0,
+            block_number,
+            block_hash: Hash::default(),
+            tx_hash: Hash::default(),
+            log_index: log_index.into(),
+            removed: false,
+        }
+    }
+
+    fn amount(value: u64) -> [u8; 32] {
+        U256::from(value).to_be_bytes()
+    }
+
+    fn fixture() -> Vec<Log> {
+        vec![
+            raw_log(1, 0, vec![*TRANSFER], &amount(10)),
+            raw_log(1, 1, vec![*APPROVAL], &amount(20)),
+            // malformed: data too short
+            raw_log(2, 0, vec![*TRANSFER], &[1, 2, 3]),
+            raw_log(2, 1, vec![Hash::create(&[b"Unknown()"])], &[]),
+            raw_log(3, 0, vec![], &[]),
+            raw_log(3, 1, vec![*TRANSFER], &amount(30)),
+        ]
+    }
+
+    #[async_std::test]
+    async fn decoded_log_stream_should_decode_in_order_and_surface_failures() -> anyhow::Result<()> {
+        let logs = fixture();
+
+        let decoded = DecodedLogStream::new(futures::stream::iter(logs.clone()).map(Ok), decoders(), 4)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            logs,
+            decoded.iter().map(|d| d.log().clone()).collect::<Vec<_>>(),
+            "order of the logs must be preserved"
+        );
+        assert_eq!(
+            vec![
+                DecodedLog::Event {
+                    log: logs[0].clone(),
+                    event: TestEvent::Transfer(U256::from(10_u64))
+                },
+                DecodedLog::Event {
+                    log: logs[1].clone(),
+                    event: TestEvent::Approval(U256::from(20_u64))
+                },
+                DecodedLog::Failed {
+                    log: logs[2].clone(),
+                    error: LogDecodeError::Decoder("invalid data length 3".into())
+                },
+                DecodedLog::Failed {
+                    log: logs[3].clone(),
+                    error: LogDecodeError::UnknownEvent(Hash::create(&[b"Unknown()"]))
+                },
+                DecodedLog::Failed {
+                    log: logs[4].clone(),
+                    error: LogDecodeError::MissingSignature
+                },
+                DecodedLog::Event {
+                    log: logs[5].clone(),
+                    event: TestEvent::Transfer(U256::from(30_u64))
+                },
+            ],
+            decoded
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn decoded_log_stream_should_decode_the_logs_concurrently() -> anyhow::Result<()> {
+        use async_std::prelude::FutureExt;
+
+        // Neither decoding can finish, unless both logs are decoded at the same time
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let decoders = LogDecoders::default().with_event(*TRANSFER, move |log| {
+            barrier.wait();
+            decode_amount(log).map(TestEvent::Transfer)
+        });
+        let logs = vec![
+            raw_log(1, 0, vec![*TRANSFER], &amount(10)),
+            raw_log(1, 1, vec![*TRANSFER], &amount(20)),
+        ];
+
+        let decoded = DecodedLogStream::new(futures::stream::iter(logs).map(Ok), decoders, 2)
+            .collect::<Vec<_>>()
+            .timeout(std::time::Duration::from_secs(5))
+            .await?
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            vec![
+                Some(&TestEvent::Transfer(U256::from(10_u64))),
+                Some(&TestEvent::Transfer(U256::from(20_u64)))
+            ],
+            decoded.iter().map(DecodedLog::event).collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn decoded_log_stream_should_pass_through_stream_errors() -> anyhow::Result<()> {
+        let logs = vec![
+            Ok(raw_log(1, 0, vec![*TRANSFER], &amount(10))),
+            Err(RpcError::NoSuchBlock),
+            Ok(raw_log(2, 0, vec![*TRANSFER], &[])),
+        ];
+
+        let decoded = DecodedLogStream::new(futures::stream::iter(logs), decoders(), 1)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(3, decoded.len());
+        assert!(matches!(
+            &decoded[0],
+            Ok(DecodedLog::Event {
+                event: TestEvent::Transfer(_),
+                ..
+            })
+        ));
+        assert!(matches!(decoded[1], Err(RpcError::NoSuchBlock)));
+        assert!(matches!(decoded[2], Ok(DecodedLog::Failed { .. })));
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 use ethers::prelude::nonce_manager::NonceManagerError;
 use ethers::prelude::signer::SignerMiddlewareError;
 use ethers::providers::{JsonRpcError, ProviderError};
+use ethers::types::Bytes;
 use thiserror::Error;
 
 /// Enumerates different errors produced by this crate.
@@ -72,16 +73,40 @@ pub enum HttpRequestError {
     #[error("connection timed out")]
     Timeout,
 
+    /// HTTP error status, optionally with the delay after which the request
+    /// can be retried, as announced by the provider (e.g. via `Retry-After`).
     #[error("http error - status {0}")]
-    HttpError(http_types::StatusCode),
+    HttpError(http_types::StatusCode, Option<std::time::Duration>),
 
     #[error("io error when performing http request: {0}")]
     TransportError(String),
 
+    /// Error that will not go away by repeating the request (e.g. TLS certificate or URL errors).
+    #[error("permanent error when performing http request: {0}")]
+    PermanentError(String),
+
+    /// The HTTP method is not supported by the requestor or not in its allowlist
+    /// (see [HttpPostRequestorConfig::allowed_methods](crate::HttpPostRequestorConfig::allowed_methods)).
+    #[error("unsupported http method: {0}")]
+    UnsupportedMethod(http_types::Method),
+
     #[error("unrecognized error: {0}")]
     UnknownError(String),
 }
 
+/// Reasons a contract log could not be decoded (see [DecodedLogStream](crate::decode::DecodedLogStream)).
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum LogDecodeError {
+    #[error("log does not contain the event signature")]
+    MissingSignature,
+
+    #[error("no decoder for the event with signature {0}")]
+    UnknownEvent(hopr_crypto_types::types::Hash),
+
+    #[error("failed to decode the log: {0}")]
+    Decoder(String),
+}
+
 /// Errors for `JsonRpcProviderClient`
 #[derive(Error, Debug)]
 pub enum JsonRpcProviderClientError {
@@ -99,6 +124,116 @@ pub enum JsonRpcProviderClientError {
 
     #[error(transparent)]
     BackendError(#[from] HttpRequestError),
+
+    #[error("invalid RPC endpoint url '{url}': {reason}")]
+    /// The RPC endpoint URL is malformed or unsupported
+    InvalidUrl {
+        /// The rejected URL
+        url: String,
+        /// Reason of the rejection
+        reason: String,
+    },
+
+    #[error("request retries have been cancelled")]
+    /// The request was not retried, because the retries were cancelled (e.g. on shutdown)
+    Cancelled,
+
+    #[error("no slot for the RPC request became available within {waited:?} (limit of {max_concurrent_requests} concurrent requests)")]
+    /// The request was not sent, because the maximum number of concurrent requests was reached
+    /// for longer than the allowed wait
+    ConcurrencyLimitReached {
+        /// Maximum number of concurrent requests
+        max_concurrent_requests: usize,
+        /// How long the request waited for a free slot
+        waited: std::time::Duration,
+    },
+}
+
+impl JsonRpcProviderClientError {
+    /// Classifies this error into an [RpcErrorKind], if it carries a JSON RPC error response
+    /// or an HTTP status with an equivalent meaning.
+    ///
+    /// Some providers send invalid JSON RPC in the error case (e.g. no `id`), so the error
+    /// object is also extracted from the text of responses that failed to deserialize.
+    pub fn kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            JsonRpcProviderClientError::JsonRpcError(err) => Some(RpcErrorKind::from_json_rpc_error(err)),
+            JsonRpcProviderClientError::SerdeJson { text, .. } => {
+                #[derive(serde::Deserialize)]
+                struct Resp {
+                    error: JsonRpcError,
+                }
+
+                serde_json::from_str::<Resp>(text)
+                    .ok()
+                    .map(|resp| RpcErrorKind::from_json_rpc_error(&resp.error))
+            }
+            JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(
+                http_types::StatusCode::TooManyRequests,
+                _,
+            )) => Some(RpcErrorKind::RateLimited),
+            _ => None,
+        }
+    }
+
+    /// Coarse classification of this error, which tells whether the failed request is worth retrying
+    /// at a higher layer (see [ErrorCategory]).
+    ///
+    /// The classification follows the one of the
+    /// [SimpleJsonRpcRetryPolicy](crate::client::SimpleJsonRpcRetryPolicy) with its defaults.
+    pub fn category(&self) -> ErrorCategory {
+        match self.kind() {
+            Some(RpcErrorKind::RateLimited) => return ErrorCategory::RateLimited,
+            Some(RpcErrorKind::Internal) => return ErrorCategory::Transient,
+            Some(
+                RpcErrorKind::Revert { .. }
+                | RpcErrorKind::MethodNotFound
+                | RpcErrorKind::InvalidParams
+                | RpcErrorKind::NonceTooLow
+                | RpcErrorKind::AlreadyKnown,
+            ) => return ErrorCategory::ClientError,
+            Some(RpcErrorKind::Other(_)) | None => {}
+        }
+
+        match RetryReason::from(self) {
+            RetryReason::JsonRpcRateLimited | RetryReason::Http429 => ErrorCategory::RateLimited,
+            // Generic server errors (e.g. the node not having the requested block yet)
+            RetryReason::JsonRpcServerError | RetryReason::Http5xx | RetryReason::Timeout | RetryReason::Transport => {
+                ErrorCategory::Transient
+            }
+            RetryReason::Http4xx => ErrorCategory::ClientError,
+            RetryReason::JsonRpcStandardError
+            | RetryReason::JsonRpcOtherError
+            | RetryReason::HttpOther
+            | RetryReason::Permanent
+            | RetryReason::MalformedResponse
+            | RetryReason::Other => ErrorCategory::Permanent,
+        }
+    }
+}
+
+/// Retry advisory for a failed RPC request (see [JsonRpcProviderClientError::category]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The failure is likely temporary (e.g. timeouts, connection errors, server errors),
+    /// so the request can be retried.
+    Transient,
+    /// The request was rejected due to rate limiting of the provider, so it can be retried
+    /// after a delay.
+    RateLimited,
+    /// The request itself is invalid (e.g. reverted call, invalid parameters, nonce too low)
+    /// and would fail again the same way.
+    ClientError,
+    /// The request fails for reasons that will not go away by repeating it
+    /// (e.g. TLS errors, invalid RPC endpoint URL, unparseable responses).
+    Permanent,
+}
+
+impl ErrorCategory {
+    /// Indicates whether the failed request is worth retrying.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ErrorCategory::Transient | ErrorCategory::RateLimited)
+    }
 }
 
 impl From<JsonRpcProviderClientError> for ProviderError {
@@ -128,3 +263,496 @@ impl ethers::providers::RpcError for JsonRpcProviderClientError {
         }
     }
 }
+
+/// Classification of a JSON RPC error returned by an RPC provider.
+///
+/// Different client implementations (geth, Nethermind, Erigon, OpenEthereum, Anvil, ...) and hosted
+/// providers use different codes and messages for the same condition, so the classification
+/// is based on both the error code and the error message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    /// The call or transaction was reverted by the EVM, possibly with the revert data.
+    Revert { data: Option<Bytes> },
+    /// The method does not exist or is not available.
+    MethodNotFound,
+    /// Invalid method parameters.
+    InvalidParams,
+    /// The request was rejected due to rate limiting of the provider.
+    RateLimited,
+    /// The transaction nonce is lower than the current nonce of the sender.
+    NonceTooLow,
+    /// The transaction is already present in the transaction pool.
+    AlreadyKnown,
+    /// Internal JSON RPC error.
+    Internal,
+    /// Any other error with the given JSON RPC error code.
+    Other(i64),
+}
+
+/// Parts of the error messages of the providers rejecting a query because of the size of its result.
+const RESULT_SIZE_LIMIT_MARKERS: [&str; 4] = ["query returned more than", "response size", "block range", "results"];
+
+impl RpcErrorKind {
+    /// Classifies the given JSON RPC error.
+    pub fn from_json_rpc_error(err: &JsonRpcError) -> Self {
+        let msg = err.message.to_lowercase();
+
+        // Message-based checks come first, because clients report these using generic codes (-32000, -32010)
+        if msg.contains("nonce too low") || msg.contains("nonce is too low") || msg.contains("oldnonce") {
+            return Self::NonceTooLow;
+        }
+
+        if msg.contains("already known")
+            || msg.contains("alreadyknown")
+            || msg.contains("known transaction")
+            || msg.contains("already imported")
+        {
+            return Self::AlreadyKnown;
+        }
+
+        let reverted_data = err
+            .data
+            .as_ref()
+            .and_then(|d| d.as_str())
+            .is_some_and(|d| d.starts_with("Reverted"));
+        if err.code == 3 || msg.contains("execution reverted") || reverted_data {
+            return Self::Revert {
+                data: Self::revert_data(err),
+            };
+        }
+
+        // Providers also report the queries with a too large result (e.g. `eth_getLogs` over too many blocks)
+        // as an exceeded limit, sometimes even with the rate limiting code -32005, but these fail again the same way
+        let result_too_large = RESULT_SIZE_LIMIT_MARKERS.iter().any(|marker| msg.contains(marker));
+        if !result_too_large
+            && (matches!(err.code, 429 | -32005 | -32016)
+                || msg.contains("rate limit")
+                || msg.contains("too many requests")
+                || msg.contains("request limit exceeded")
+                || msg.contains("capacity limit exceeded"))
+        {
+            return Self::RateLimited;
+        }
+
+        match err.code {
+            -32601 => Self::MethodNotFound,
+            -32602 => Self::InvalidParams,
+            -32603 => Self::Internal,
+            _ if msg.contains("method not found") || (msg.contains("method") && msg.contains("does not exist")) => {
+                Self::MethodNotFound
+            }
+            code => Self::Other(code),
+        }
+    }
+
+    /// Extracts the revert data, which are either directly the hex string in `data`
+    /// or prefixed with `Reverted ` (OpenEthereum).
+    fn revert_data(err: &JsonRpcError) -> Option<Bytes> {
+        let data = err.data.as_ref()?.as_str()?;
+        let hex = data.strip_prefix("Reverted ").unwrap_or(data).trim();
+        hex.starts_with("0x").then(|| hex.parse::<Bytes>().ok()).flatten()
+    }
+}
+
+/// Category of a failed RPC request, used to label the retries and terminal failures.
+///
+/// JSON RPC errors are bucketed by their error code and HTTP errors by their status,
+/// so that e.g. rate limiting can be told apart from the provider flakiness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RetryReason {
+    /// JSON RPC error classified as [RpcErrorKind::RateLimited].
+    JsonRpcRateLimited,
+    /// JSON RPC error with a server error code (-32099 to -32000).
+    JsonRpcServerError,
+    /// JSON RPC error with one of the remaining codes reserved by the JSON RPC specification.
+    JsonRpcStandardError,
+    /// JSON RPC error with any other (provider-specific) code.
+    JsonRpcOtherError,
+    /// HTTP status 429 (Too Many Requests).
+    Http429,
+    /// Any other HTTP 4xx status, except 408 (Request Timeout).
+    Http4xx,
+    /// HTTP 5xx status.
+    Http5xx,
+    /// Any other unsuccessful HTTP status.
+    HttpOther,
+    /// The request timed out, either locally or as reported by the HTTP status 408.
+    Timeout,
+    /// Transport or connection error.
+    Transport,
+    /// Transport error that will not go away by repeating the request.
+    Permanent,
+    /// The response could not be deserialized.
+    MalformedResponse,
+    /// Any other error.
+    Other,
+}
+
+impl RetryReason {
+    /// All the reasons, in the order of their [index](RetryReason::index).
+    pub const ALL: [RetryReason; 13] = [
+        RetryReason::JsonRpcRateLimited,
+        RetryReason::JsonRpcServerError,
+        RetryReason::JsonRpcStandardError,
+        RetryReason::JsonRpcOtherError,
+        RetryReason::Http429,
+        RetryReason::Http4xx,
+        RetryReason::Http5xx,
+        RetryReason::HttpOther,
+        RetryReason::Timeout,
+        RetryReason::Transport,
+        RetryReason::Permanent,
+        RetryReason::MalformedResponse,
+        RetryReason::Other,
+    ];
+
+    /// Label of the reason used in the metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryReason::JsonRpcRateLimited => "json_rpc_rate_limited",
+            RetryReason::JsonRpcServerError => "json_rpc_server_error",
+            RetryReason::JsonRpcStandardError => "json_rpc_standard_error",
+            RetryReason::JsonRpcOtherError => "json_rpc_other_error",
+            RetryReason::Http429 => "http_429",
+            RetryReason::Http4xx => "http_4xx",
+            RetryReason::Http5xx => "http_5xx",
+            RetryReason::HttpOther => "http_other",
+            RetryReason::Timeout => "timeout",
+            RetryReason::Transport => "transport",
+            RetryReason::Permanent => "permanent",
+            RetryReason::MalformedResponse => "malformed_response",
+            RetryReason::Other => "other",
+        }
+    }
+
+    /// Position of the reason in [RetryReason::ALL].
+    pub(crate) fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn from_json_rpc_error(err: &JsonRpcError) -> Self {
+        match (RpcErrorKind::from_json_rpc_error(err), err.code) {
+            (RpcErrorKind::RateLimited, _) => RetryReason::JsonRpcRateLimited,
+            (_, -32099..=-32000) => RetryReason::JsonRpcServerError,
+            (_, -32768..=-32100) => RetryReason::JsonRpcStandardError,
+            _ => RetryReason::JsonRpcOtherError,
+        }
+    }
+}
+
+impl std::fmt::Display for RetryReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&JsonRpcProviderClientError> for RetryReason {
+    fn from(value: &JsonRpcProviderClientError) -> Self {
+        match value {
+            JsonRpcProviderClientError::JsonRpcError(err) => Self::from_json_rpc_error(err),
+            JsonRpcProviderClientError::SerdeJson { text, .. } => {
+                // Some providers send invalid JSON RPC in the error case, see [JsonRpcProviderClientError::kind]
+                #[derive(serde::Deserialize)]
+                struct Resp {
+                    error: JsonRpcError,
+                }
+
+                serde_json::from_str::<Resp>(text)
+                    .map(|resp| Self::from_json_rpc_error(&resp.error))
+                    .unwrap_or(RetryReason::MalformedResponse)
+            }
+            JsonRpcProviderClientError::BackendError(err) => match err {
+                HttpRequestError::HttpError(status, _) => match *status as u16 {
+                    429 => RetryReason::Http429,
+                    408 => RetryReason::Timeout,
+                    400..=499 => RetryReason::Http4xx,
+                    500..=599 => RetryReason::Http5xx,
+                    _ => RetryReason::HttpOther,
+                },
+                HttpRequestError::Timeout => RetryReason::Timeout,
+                HttpRequestError::TransportError(_) | HttpRequestError::UnknownError(_) => RetryReason::Transport,
+                HttpRequestError::PermanentError(_) | HttpRequestError::UnsupportedMethod(_) => RetryReason::Permanent,
+            },
+            JsonRpcProviderClientError::InvalidUrl { .. }
+            | JsonRpcProviderClientError::Cancelled
+            | JsonRpcProviderClientError::ConcurrencyLimitReached { .. } => RetryReason::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn json_rpc_error(payload: serde_json::Value) -> JsonRpcError {
+        serde_json::from_value(payload).expect("must be a valid JSON RPC error")
+    }
+
+    #[test]
+    fn test_rpc_error_kind_should_classify_errors_of_different_clients() {
+        let revert_data: Bytes = "0x08c379a00000000000000000000000000000000000000000000000000000000000000020"
+            .parse()
+            .unwrap();
+
+        let table = [
+            // geth
+            (
+                json!({"code": -32000, "message": "nonce too low: next nonce 5, tx nonce 4"}),
+                RpcErrorKind::NonceTooLow,
+            ),
+            (
+                json!({"code": -32000, "message": "already known"}),
+                RpcErrorKind::AlreadyKnown,
+            ),
+            (
+                json!({"code": 3, "message": "execution reverted: not enough balance", "data": "0x08c379a00000000000000000000000000000000000000000000000000000000000000020"}),
+                RpcErrorKind::Revert {
+                    data: Some(revert_data.clone()),
+                },
+            ),
+            (
+                json!({"code": -32601, "message": "the method eth_foo does not exist/is not available"}),
+                RpcErrorKind::MethodNotFound,
+            ),
+            (
+                json!({"code": -32602, "message": "invalid argument 0: hex string without 0x prefix"}),
+                RpcErrorKind::InvalidParams,
+            ),
+            (
+                json!({"code": -32000, "message": "insufficient funds for gas * price + value"}),
+                RpcErrorKind::Other(-32000),
+            ),
+            // Nethermind
+            (
+                json!({"code": -32010, "message": "OldNonce, Current nonce: 5, nonce of rejected tx: 4"}),
+                RpcErrorKind::NonceTooLow,
+            ),
+            (
+                json!({"code": -32010, "message": "AlreadyKnown"}),
+                RpcErrorKind::AlreadyKnown,
+            ),
+            (
+                json!({"code": -32015, "message": "VM execution error.", "data": "Reverted 0x08c379a00000000000000000000000000000000000000000000000000000000000000020"}),
+                RpcErrorKind::Revert {
+                    data: Some(revert_data.clone()),
+                },
+            ),
+            (
+                json!({"code": -32603, "message": "Internal error"}),
+                RpcErrorKind::Internal,
+            ),
+            // OpenEthereum / Parity
+            (
+                json!({"code": -32010, "message": "Transaction nonce is too low. Try incrementing the nonce."}),
+                RpcErrorKind::NonceTooLow,
+            ),
+            (
+                json!({"code": -32010, "message": "Transaction with the same hash was already imported."}),
+                RpcErrorKind::AlreadyKnown,
+            ),
+            (
+                json!({"code": -32016, "message": "Your request was rate limited"}),
+                RpcErrorKind::RateLimited,
+            ),
+            // Anvil
+            (
+                json!({"code": 3, "message": "execution reverted"}),
+                RpcErrorKind::Revert { data: None },
+            ),
+            (
+                json!({"code": -32601, "message": "Method not found"}),
+                RpcErrorKind::MethodNotFound,
+            ),
+            // Hosted providers
+            (
+                json!({"code": 429, "message": "Too Many Requests"}),
+                RpcErrorKind::RateLimited,
+            ),
+            (
+                json!({"code": -32005, "message": "daily request count exceeded, request rate limited"}),
+                RpcErrorKind::RateLimited,
+            ),
+            (
+                json!({"code": -32000, "message": "compute units per second capacity limit exceeded"}),
+                RpcErrorKind::RateLimited,
+            ),
+            (
+                json!({"code": -32000, "message": "request limit exceeded"}),
+                RpcErrorKind::RateLimited,
+            ),
+            // Result size limits
+            (
+                json!({"code": -32005, "message": "query returned more than 10000 results"}),
+                RpcErrorKind::Other(-32005),
+            ),
+            (
+                json!({"code": -32000, "message": "block range limit exceeded"}),
+                RpcErrorKind::Other(-32000),
+            ),
+            (
+                json!({"code": -32600, "message": "eth_getLogs response size limit exceeded"}),
+                RpcErrorKind::Other(-32600),
+            ),
+            (
+                json!({"code": -32000, "message": "limit exceeded"}),
+                RpcErrorKind::Other(-32000),
+            ),
+        ];
+
+        for (payload, expected) in table {
+            let err = json_rpc_error(payload.clone());
+            assert_eq!(expected, RpcErrorKind::from_json_rpc_error(&err), "payload: {payload}");
+        }
+    }
+
+    #[test]
+    fn test_client_error_kind_should_be_extracted_from_malformed_response() {
+        let err = JsonRpcProviderClientError::SerdeJson {
+            err: serde_json::from_str::<u64>("x").unwrap_err(),
+            text: r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"limit exceeded"}}"#.into(),
+        };
+        assert_eq!(Some(RpcErrorKind::RateLimited), err.kind());
+
+        let err = JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(
+            http_types::StatusCode::TooManyRequests,
+            None,
+        ));
+        assert_eq!(Some(RpcErrorKind::RateLimited), err.kind());
+
+        let err = JsonRpcProviderClientError::BackendError(HttpRequestError::Timeout);
+        assert_eq!(None, err.kind());
+    }
+
+    #[test]
+    fn test_retry_reason_should_bucket_errors() {
+        let malformed = |text: &str| JsonRpcProviderClientError::SerdeJson {
+            err: serde_json::from_str::<u64>("x").unwrap_err(),
+            text: text.into(),
+        };
+        let http = |status| JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(status, None));
+
+        let table = [
+            (
+                JsonRpcProviderClientError::JsonRpcError(json_rpc_error(
+                    json!({"code": -32005, "message": "limit exceeded"}),
+                )),
+                "json_rpc_rate_limited",
+            ),
+            (
+                JsonRpcProviderClientError::JsonRpcError(json_rpc_error(
+                    json!({"code": -32000, "message": "header not found"}),
+                )),
+                "json_rpc_server_error",
+            ),
+            (
+                JsonRpcProviderClientError::JsonRpcError(json_rpc_error(
+                    json!({"code": -32603, "message": "internal error"}),
+                )),
+                "json_rpc_standard_error",
+            ),
+            (
+                malformed(r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"header not found"}}"#),
+                "json_rpc_server_error",
+            ),
+            (malformed("<html>bad gateway</html>"), "malformed_response"),
+            (http(http_types::StatusCode::TooManyRequests), "http_429"),
+            (http(http_types::StatusCode::Forbidden), "http_4xx"),
+            (http(http_types::StatusCode::RequestTimeout), "timeout"),
+            (http(http_types::StatusCode::BadGateway), "http_5xx"),
+            (
+                JsonRpcProviderClientError::BackendError(HttpRequestError::Timeout),
+                "timeout",
+            ),
+            (
+                JsonRpcProviderClientError::BackendError(HttpRequestError::TransportError("reset".into())),
+                "transport",
+            ),
+        ];
+
+        for (err, expected) in table {
+            assert_eq!(expected, RetryReason::from(&err).as_str(), "error: {err}");
+        }
+
+        for (i, reason) in RetryReason::ALL.iter().enumerate() {
+            assert_eq!(i, reason.index());
+        }
+    }
+
+    #[test]
+    fn test_client_error_category_should_advise_on_retries() {
+        let json_rpc = |payload| JsonRpcProviderClientError::JsonRpcError(json_rpc_error(payload));
+        let http = |status| JsonRpcProviderClientError::BackendError(HttpRequestError::HttpError(status, None));
+
+        let table = [
+            (
+                json_rpc(json!({"code": 429, "message": "too many requests"})),
+                ErrorCategory::RateLimited,
+            ),
+            (
+                http(http_types::StatusCode::TooManyRequests),
+                ErrorCategory::RateLimited,
+            ),
+            (
+                json_rpc(json!({"code": -32000, "message": "header not found"})),
+                ErrorCategory::Transient,
+            ),
+            (
+                http(http_types::StatusCode::ServiceUnavailable),
+                ErrorCategory::Transient,
+            ),
+            (
+                JsonRpcProviderClientError::BackendError(HttpRequestError::Timeout),
+                ErrorCategory::Transient,
+            ),
+            (http(http_types::StatusCode::RequestTimeout), ErrorCategory::Transient),
+            (
+                json_rpc(json!({"code": -32603, "message": "internal error"})),
+                ErrorCategory::Transient,
+            ),
+            (
+                json_rpc(json!({"code": -32099, "message": "unknown block"})),
+                ErrorCategory::Transient,
+            ),
+            (
+                json_rpc(json!({"code": -32700, "message": "parse error"})),
+                ErrorCategory::Permanent,
+            ),
+            (
+                json_rpc(json!({"code": 3, "message": "execution reverted"})),
+                ErrorCategory::ClientError,
+            ),
+            (
+                json_rpc(json!({"code": -32000, "message": "nonce too low"})),
+                ErrorCategory::ClientError,
+            ),
+            (http(http_types::StatusCode::Forbidden), ErrorCategory::ClientError),
+            (
+                JsonRpcProviderClientError::BackendError(HttpRequestError::PermanentError("bad certificate".into())),
+                ErrorCategory::Permanent,
+            ),
+            (
+                JsonRpcProviderClientError::SerdeJson {
+                    err: serde_json::from_str::<u64>("x").unwrap_err(),
+                    text: "<html>bad gateway</html>".into(),
+                },
+                ErrorCategory::Permanent,
+            ),
+            (JsonRpcProviderClientError::Cancelled, ErrorCategory::Permanent),
+            (
+                JsonRpcProviderClientError::ConcurrencyLimitReached {
+                    max_concurrent_requests: 1,
+                    waited: std::time::Duration::from_millis(10),
+                },
+                ErrorCategory::Permanent,
+            ),
+        ];
+
+        for (err, expected) in table {
+            assert_eq!(expected, err.category(), "error: {err}");
+        }
+
+        assert!(ErrorCategory::RateLimited.is_retryable());
+        assert!(!ErrorCategory::ClientError.is_retryable());
+    }
+}
@@ -0,0 +1,263 @@
+//! Typed helpers for the commonly used Ethereum JSON RPC calls.
+//!
+//! The [EthRpcExt] extension trait is implemented for every [JsonRpcClient] (such as the
+//! [JsonRpcProviderClient](crate::client::JsonRpcProviderClient)), so that the calls do not have to
+//! spell out the method names and the parameter and result types. Since the helpers only wrap
+//! [JsonRpcClient::request], they go through the same retry and metrics path as any other request.
+use async_trait::async_trait;
+use ethers::providers::JsonRpcClient;
+use ethers::types::{Address, BlockId, BlockNumber, FeeHistory, Filter, Log, TransactionReceipt, H256, U256, U64};
+
+/// Typed helpers for the commonly used Ethereum JSON RPC calls over any [JsonRpcClient].
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait EthRpcExt: JsonRpcClient {
+    /// Number of the latest block (`eth_blockNumber`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// let latest_block = client.block_number().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn block_number(&self) -> Result<U64, Self::Error> {
+        self.request("eth_blockNumber", ()).await
+    }
+
+    /// Chain ID of the network (`eth_chainId`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// let chain_id = client.chain_id().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn chain_id(&self) -> Result<U256, Self::Error> {
+        self.request("eth_chainId", ()).await
+    }
+
+    /// Balance of the given address in wei at the given block, or at the latest block if `None` (`eth_getBalance`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// use ethers::types::{Address, BlockNumber};
+    ///
+    /// let balance = client.get_balance(Address::zero(), Some(BlockNumber::Finalized.into())).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn get_balance(&self, address: Address, block: Option<BlockId>) -> Result<U256, Self::Error> {
+        let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+        self.request("eth_getBalance", (address, block)).await
+    }
+
+    /// Logs matching the given filter (`eth_getLogs`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// use ethers::types::{Address, Filter};
+    ///
+    /// let logs = client
+    ///     .get_logs(&Filter::new().address(Address::zero()).from_block(100).to_block(200))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        self.request("eth_getLogs", [filter]).await
+    }
+
+    /// Base fees and priority fee percentiles of `block_count` blocks up to the `newest_block` (`eth_feeHistory`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// use ethers::types::BlockNumber;
+    ///
+    /// let history = client.fee_history(10, BlockNumber::Latest, &[25.0, 75.0]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumber,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory, Self::Error> {
+        self.request(
+            "eth_feeHistory",
+            (U256::from(block_count), newest_block, reward_percentiles),
+        )
+        .await
+    }
+
+    /// Receipt of the transaction with the given hash, if it has been already mined (`eth_getTransactionReceipt`).
+    ///
+    /// ```no_run
+    /// # use hopr_chain_rpc::eth_rpc::EthRpcExt;
+    /// # async fn example<C: EthRpcExt>(client: C) -> Result<(), C::Error> {
+    /// use ethers::types::H256;
+    ///
+    /// if let Some(receipt) = client.get_transaction_receipt(H256::zero()).await? {
+    ///     println!("mined in block {:?}", receipt.block_number);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn get_transaction_receipt(&self, tx_hash: H256) -> Result<Option<TransactionReceipt>, Self::Error> {
+        self.request("eth_getTransactionReceipt", [tx_hash]).await
+    }
+}
+
+impl<C: JsonRpcClient> EthRpcExt for C {}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, BlockNumber, Filter, H256};
+    use serde_json::json;
+
+    use super::EthRpcExt;
+    use crate::client::surf_client::SurfRequestor;
+    use crate::client::JsonRpcProviderClient;
+    use crate::ZeroRetryPolicy;
+
+    async fn mock_call(
+        server: &mut mockito::ServerGuard,
+        request: serde_json::Value,
+        result: serde_json::Value,
+    ) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .with_status(200)
+            .match_body(mockito::Matcher::PartialJson(request))
+            .with_body(json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string())
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    fn client(
+        server: &mockito::ServerGuard,
+    ) -> JsonRpcProviderClient<SurfRequestor, ZeroRetryPolicy<crate::errors::JsonRpcProviderClientError>> {
+        JsonRpcProviderClient::new(&server.url(), SurfRequestor::default(), ZeroRetryPolicy::default())
+    }
+
+    #[async_std::test]
+    async fn test_block_number_and_chain_id_should_be_requested_without_params() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let block_number = mock_call(&mut server, json!({"method": "eth_blockNumber"}), json!("0x2a")).await;
+        let chain_id = mock_call(&mut server, json!({"method": "eth_chainId"}), json!("0x64")).await;
+
+        let client = client(&server);
+        assert_eq!(42, client.block_number().await?.as_u64());
+        assert_eq!(100, client.chain_id().await?.as_u64());
+
+        block_number.assert();
+        chain_id.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_balance_should_default_to_latest_block() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let address: Address = "0x1000000000000000000000000000000000000001".parse()?;
+
+        let latest = mock_call(
+            &mut server,
+            json!({"method": "eth_getBalance", "params": [address, "latest"]}),
+            json!("0x1"),
+        )
+        .await;
+        let at_block = mock_call(
+            &mut server,
+            json!({"method": "eth_getBalance", "params": [address, "0x64"]}),
+            json!("0x2"),
+        )
+        .await;
+
+        let client = client(&server);
+        assert_eq!(1, client.get_balance(address, None).await?.as_u64());
+        assert_eq!(
+            2,
+            client
+                .get_balance(address, Some(BlockNumber::Number(100.into()).into()))
+                .await?
+                .as_u64()
+        );
+
+        latest.assert();
+        at_block.assert();
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_logs_should_send_filter_as_single_param() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let address: Address = "0x1000000000000000000000000000000000000001".parse()?;
+
+        let m = mock_call(
+            &mut server,
+            json!({"method": "eth_getLogs", "params": [{"address": address, "fromBlock": "0x1", "toBlock": "0x2"}]}),
+            json!([{"address": address, "topics": [], "data": "0x"}]),
+        )
+        .await;
+
+        let logs = client(&server)
+            .get_logs(&Filter::new().address(address).from_block(1).to_block(2))
+            .await?;
+
+        m.assert();
+        assert_eq!(1, logs.len());
+        assert_eq!(address, logs[0].address);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_fee_history_should_send_block_count_as_quantity() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+
+        let m = mock_call(
+            &mut server,
+            json!({"method": "eth_feeHistory", "params": ["0x2", "latest", [25.0, 75.0]]}),
+            json!({
+                "oldestBlock": "0x63",
+                "baseFeePerGas": ["0x1", "0x2", "0x3"],
+                "gasUsedRatio": [0.5, 0.25],
+                "reward": [["0x1", "0x2"], ["0x3", "0x4"]]
+            }),
+        )
+        .await;
+
+        let history = client(&server)
+            .fee_history(2, BlockNumber::Latest, &[25.0, 75.0])
+            .await?;
+
+        m.assert();
+        assert_eq!(99, history.oldest_block.as_u64());
+        assert_eq!(3, history.base_fee_per_gas.len());
+        assert_eq!(2, history.reward.len());
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_transaction_receipt_should_return_none_for_pending_transaction() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let tx_hash = H256::from_low_u64_be(1);
+
+        let m = mock_call(
+            &mut server,
+            json!({"method": "eth_getTransactionReceipt", "params": [tx_hash]}),
+            json!(null),
+        )
+        .await;
+
+        assert!(client(&server).get_transaction_receipt(tx_hash).await?.is_none());
+
+        m.assert();
+        Ok(())
+    }
+}
@@ -166,3 +166,210 @@ impl<'de: 'a, 'a> Deserialize<'de> for Response<'a> {
         deserializer.deserialize_map(ResponseVisitor(&()))
     }
 }
+
+/// Incrementally splits a JSON RPC response body into the raw elements of its `result` array,
+/// so that a large result (e.g. of `eth_getLogs`) does not have to be buffered as a whole.
+///
+/// All the other parts of the response are buffered with the result array left empty,
+/// so that the response can still be deserialized as a whole (e.g. when it carries an error instead).
+///
+/// Each value is parsed as a [RawValue] by the `serde_json` [Deserializer](serde_json::Deserializer)
+/// once it has been received completely, only the separators of the top-level object and of the result
+/// array are consumed here.
+#[derive(Debug, Default)]
+pub(crate) struct ResultArraySplitter {
+    state: SplitterState,
+    pending: Vec<u8>,
+    outside: Vec<u8>,
+    result_found: bool,
+}
+
+/// Next expected part of the response body.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SplitterState {
+    /// Start of the top-level object.
+    #[default]
+    Start,
+    /// Key of a top-level field or the end of the object.
+    Key,
+    /// Separator of the key of a top-level field.
+    Colon { result: bool },
+    /// Value of a top-level field, which is split if it is the result array.
+    Value { result: bool },
+    /// Separator of the top-level fields or the end of the object.
+    AfterValue,
+    /// Element of the result array or its end.
+    Element,
+    /// Separator of the elements of the result array or its end.
+    AfterElement,
+    /// Anything after the top-level object, or after a part of the body which is not a valid response.
+    Rest,
+}
+
+/// Result of parsing the next value of the body.
+enum NextValue<'a> {
+    /// The complete value and the number of bytes it took.
+    Complete(&'a RawValue, usize),
+    /// The value has not been received completely yet.
+    Incomplete,
+    /// The body is not valid JSON.
+    Invalid,
+}
+
+impl ResultArraySplitter {
+    /// Processes the next chunk of the response body and returns the raw elements of the result array
+    /// completed within this chunk.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        self.pending.extend_from_slice(chunk);
+        let pending = std::mem::take(&mut self.pending);
+
+        let mut elements = Vec::new();
+        let mut pos = 0;
+        while pos < pending.len() {
+            let input = &pending[pos..];
+            if self.state == SplitterState::Rest {
+                self.outside.extend_from_slice(input);
+                pos = pending.len();
+                break;
+            }
+
+            if input[0].is_ascii_whitespace() {
+                pos += 1;
+                continue;
+            }
+
+            // Next state and the number of bytes to be buffered in the response
+            let (next, len) = match (self.state, input[0]) {
+                (SplitterState::Start, b'{') => (SplitterState::Key, 1),
+                (SplitterState::Key | SplitterState::AfterValue, b'}') => (SplitterState::Rest, 1),
+                (SplitterState::AfterValue, b',') => (SplitterState::Key, 1),
+                (SplitterState::Colon { result }, b':') => (SplitterState::Value { result }, 1),
+                (SplitterState::Value { result: true }, b'[') => {
+                    self.result_found = true;
+                    (SplitterState::Element, 1)
+                }
+                (SplitterState::Element | SplitterState::AfterElement, b']') => (SplitterState::AfterValue, 1),
+                (SplitterState::AfterElement, b',') => {
+                    pos += 1;
+                    self.state = SplitterState::Element;
+                    continue;
+                }
+                (SplitterState::Key | SplitterState::Value { .. } | SplitterState::Element, _) => {
+                    match Self::next_value(input) {
+                        NextValue::Complete(element, len) if self.state == SplitterState::Element => {
+                            elements.push(element.get().as_bytes().to_vec());
+                            pos += len;
+                            self.state = SplitterState::AfterElement;
+                            continue;
+                        }
+                        NextValue::Complete(key, len) if self.state == SplitterState::Key => {
+                            match serde_json::from_str::<String>(key.get()) {
+                                Ok(key) => (
+                                    SplitterState::Colon {
+                                        result: key == "result" && !self.result_found,
+                                    },
+                                    len,
+                                ),
+                                Err(_) => (SplitterState::Rest, 0),
+                            }
+                        }
+                        NextValue::Complete(_, len) => (SplitterState::AfterValue, len),
+                        NextValue::Incomplete => break,
+                        NextValue::Invalid => (SplitterState::Rest, 0),
+                    }
+                }
+                _ => (SplitterState::Rest, 0),
+            };
+
+            self.outside.extend_from_slice(&input[..len]);
+            pos += len;
+            self.state = next;
+        }
+
+        self.pending = pending[pos..].to_vec();
+        elements
+    }
+
+    /// Finishes the splitting and returns the buffered response without the elements of the result array,
+    /// and whether the result array has been found.
+    pub fn finish(mut self) -> (Vec<u8>, bool) {
+        // An incomplete body is left for the deserialization of the response to fail on
+        self.outside.append(&mut self.pending);
+        (self.outside, self.result_found)
+    }
+
+    /// Parses the value at the start of the `input`.
+    ///
+    /// A value is complete only once it is followed by another byte, because e.g. a number
+    /// at the end of the input could continue in the next chunk.
+    fn next_value(input: &[u8]) -> NextValue<'_> {
+        let mut values = serde_json::Deserializer::from_slice(input).into_iter::<&RawValue>();
+        match values.next() {
+            Some(Ok(value)) if values.byte_offset() < input.len() => NextValue::Complete(value, values.byte_offset()),
+            Some(Ok(_)) | None => NextValue::Incomplete,
+            Some(Err(err)) if err.is_eof() => NextValue::Incomplete,
+            Some(Err(_)) => NextValue::Invalid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(body: &str, chunk_size: usize) -> (Vec<String>, String, bool) {
+        let mut splitter = ResultArraySplitter::default();
+        let elements = body
+            .as_bytes()
+            .chunks(chunk_size)
+            .flat_map(|chunk| splitter.feed(chunk))
+            .map(|element| String::from_utf8(element).expect("element must be valid utf-8"))
+            .collect::<Vec<_>>();
+
+        let (rest, result_found) = splitter.finish();
+        (
+            elements,
+            String::from_utf8(rest).expect("rest must be valid utf-8"),
+            result_found,
+        )
+    }
+
+    #[test]
+    fn test_result_array_splitter_should_split_elements_regardless_of_chunking() -> anyhow::Result<()> {
+        let body = r#"{"jsonrpc":"2.0", "id":1, "result": [ {"a":"x,]\"}", "b":[1,2]} , {"c":{}} , "s]" , 5 ] }"#;
+
+        for chunk_size in [1, 2, 3, 7, body.len()] {
+            let (elements, rest, result_found) = split(body, chunk_size);
+            assert_eq!(
+                vec![r#"{"a":"x,]\"}", "b":[1,2]}"#, r#"{"c":{}}"#, r#""s]""#, "5"],
+                elements,
+                "chunk size {chunk_size}"
+            );
+            assert!(result_found);
+
+            let rest: serde_json::Value = serde_json::from_str(&rest)?;
+            assert_eq!(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": []}), rest);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_result_array_splitter_should_buffer_responses_without_result_array() -> anyhow::Result<()> {
+        let error = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"result [x]","data":["result"]}}"#;
+        let (elements, rest, result_found) = split(error, 3);
+        assert!(elements.is_empty());
+        assert!(!result_found);
+        assert_eq!(error, rest);
+
+        let (elements, _, result_found) = split(r#"{"id":1,"nested":{"result":[1]},"result":null}"#, 1);
+        assert!(elements.is_empty(), "only the top-level result must be split");
+        assert!(!result_found);
+
+        let (elements, _, result_found) = split(r#"{"id":1,"x":"result","result":[1]}"#, 1);
+        assert_eq!(vec!["1"], elements);
+        assert!(result_found);
+
+        Ok(())
+    }
+}
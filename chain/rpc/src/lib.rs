@@ -20,7 +20,6 @@ use std::cmp::Ordering;
 use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 use std::future::{Future, IntoFuture};
-use std::marker::PhantomData;
 use std::pin::Pin;
 use std::time::Duration;
 
@@ -29,14 +28,26 @@ use hopr_primitive_types::prelude::*;
 
 use crate::errors::RpcError::{ProviderError, TransactionDropped};
 use crate::errors::{HttpRequestError, Result};
-use crate::RetryAction::NoRetry;
 
+pub use hopr_async_runtime::retry::{RetryAction, RetryPolicy, ZeroRetryPolicy};
+
+pub mod audit;
+pub mod buffer;
 pub mod client;
+pub mod decode;
 pub mod errors;
+pub mod eth_rpc;
 mod helper;
 pub mod indexer;
 pub mod middleware;
+pub mod quirks;
+pub mod retry;
 pub mod rpc;
+#[cfg(any(test, feature = "testing"))]
+pub mod scenario;
+pub mod signer;
+pub mod stats;
+pub mod usage;
 
 /// A type containing selected fields from  the `eth_getLogs` RPC calls.
 ///
@@ -208,35 +219,6 @@ impl From<LogFilter> for ethers::types::Filter {
     }
 }
 
-/// Indicates what retry action should be taken, as result of a `RetryPolicy` implementation.
-pub enum RetryAction {
-    /// Request should not be retried
-    NoRetry,
-    /// Request should be retried after the given duration has elapsed.
-    RetryAfter(Duration),
-}
-
-/// Simple retry policy trait
-pub trait RetryPolicy<E> {
-    /// Indicates whether a client should retry the request given the last error, current number of retries
-    /// of this request and the number of other requests being retried by the client at this time.
-    fn is_retryable_error(&self, _err: &E, _retry_number: u32, _retry_queue_size: u32) -> RetryAction {
-        NoRetry
-    }
-}
-
-/// Performs no retries.
-#[derive(Clone, Debug)]
-pub struct ZeroRetryPolicy<E>(PhantomData<E>);
-
-impl<E> Default for ZeroRetryPolicy<E> {
-    fn default() -> Self {
-        Self(PhantomData)
-    }
-}
-
-impl<E> RetryPolicy<E> for ZeroRetryPolicy<E> {}
-
 /// Abstraction for an HTTP client that performs HTTP POST with serializable request data.
 #[async_trait]
 pub trait HttpRequestor: std::fmt::Debug + Send + Sync {
@@ -265,6 +247,125 @@ pub trait HttpRequestor: std::fmt::Debug + Send + Sync {
     async fn http_get(&self, url: &str) -> std::result::Result<Box<[u8]>, HttpRequestError> {
         self.http_query(http_types::Method::Get, url, Option::<()>::None).await
     }
+
+    /// Checks whether the RPC endpoint at the given URL is healthy.
+    ///
+    /// The default implementation issues a lightweight `eth_chainId` JSON RPC request and
+    /// considers the endpoint healthy if it responds with a result.
+    /// Implementations can override this with a more efficient check.
+    async fn health_check(&self, url: &str) -> bool {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "eth_chainId",
+            "params": []
+        });
+
+        match self.http_post(url, request).await {
+            Ok(response) => serde_json::from_slice::<serde_json::Value>(&response)
+                .is_ok_and(|value| value.get("result").is_some_and(|result| !result.is_null())),
+            Err(error) => {
+                tracing::debug!(%error, url, "rpc endpoint health check failed");
+                false
+            }
+        }
+    }
+}
+
+/// Stream of the chunks of an HTTP response body.
+pub type HttpBodyStream = futures::stream::BoxStream<'static, std::result::Result<Box<[u8]>, HttpRequestError>>;
+
+/// Extension of the [HttpRequestor] which can stream the response body instead of buffering it whole,
+/// e.g. for large `eth_getLogs` responses (see [JsonRpcProviderClient::request_streamed](client::JsonRpcProviderClient::request_streamed)).
+#[async_trait]
+pub trait StreamingHttpRequestor: HttpRequestor {
+    /// Performs HTTP POST of JSON data to the given URL
+    /// and streams the chunks of the JSON response as they arrive.
+    async fn http_post_streamed<T>(&self, url: &str, data: T) -> std::result::Result<HttpBodyStream, HttpRequestError>
+    where
+        T: Serialize + Send + Sync;
+}
+
+/// Object-safe counterpart of the [HttpRequestor], operating on already serialized JSON request bodies.
+///
+/// It is implemented for every [HttpRequestor], so that the requestors can be used as trait objects
+/// (see [client::build_http_requestor]). Conversely, `Arc<dyn ObjectSafeHttpRequestor>`
+/// implements [HttpRequestor] and can be used with the [JsonRpcProviderClient](client::JsonRpcProviderClient).
+#[async_trait]
+pub trait ObjectSafeHttpRequestor: std::fmt::Debug + Send + Sync {
+    /// Performs HTTP request with optional serialized JSON body to the given URL
+    /// and gets the JSON response.
+    async fn http_query_raw(
+        &self,
+        method: http_types::Method,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> std::result::Result<Box<[u8]>, HttpRequestError>;
+
+    /// Performs HTTP POST of the serialized JSON body to the given URL
+    /// and gets the JSON response.
+    async fn http_post_raw(&self, url: &str, body: &[u8]) -> std::result::Result<Box<[u8]>, HttpRequestError> {
+        self.http_query_raw(http_types::Method::Post, url, Some(body)).await
+    }
+
+    /// Checks whether the RPC endpoint at the given URL is healthy.
+    ///
+    /// See [HttpRequestor::health_check].
+    async fn health_check_raw(&self, url: &str) -> bool;
+}
+
+#[async_trait]
+impl<R: HttpRequestor> ObjectSafeHttpRequestor for R {
+    async fn http_query_raw(
+        &self,
+        method: http_types::Method,
+        url: &str,
+        body: Option<&[u8]>,
+    ) -> std::result::Result<Box<[u8]>, HttpRequestError> {
+        // The body is only validated and borrowed, so that it is passed through as it is
+        let data = body
+            .map(serde_json::from_slice::<&serde_json::value::RawValue>)
+            .transpose()
+            .map_err(|e| HttpRequestError::UnknownError(format!("request body is not valid json: {e}")))?;
+
+        self.http_query(method, url, data).await
+    }
+
+    async fn health_check_raw(&self, url: &str) -> bool {
+        self.health_check(url).await
+    }
+}
+
+#[async_trait]
+impl HttpRequestor for std::sync::Arc<dyn ObjectSafeHttpRequestor> {
+    async fn http_query<T>(
+        &self,
+        method: http_types::Method,
+        url: &str,
+        data: Option<T>,
+    ) -> std::result::Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        let body = data
+            .map(|data| serde_json::to_vec(&data))
+            .transpose()
+            .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}")))?;
+
+        self.as_ref().http_query_raw(method, url, body.as_deref()).await
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        self.as_ref().health_check_raw(url).await
+    }
+}
+
+fn default_connect_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_allowed_methods() -> Vec<http_types::Method> {
+    vec![http_types::Method::Get, http_types::Method::Post]
 }
 
 /// Common configuration for all native `HttpPostRequestor`s
@@ -276,6 +377,16 @@ pub struct HttpPostRequestorConfig {
     #[default(Duration::from_secs(30))]
     pub http_request_timeout: Duration,
 
+    /// Timeout for establishing the connection to the RPC endpoint,
+    /// including the DNS resolution and the TLS handshake.
+    ///
+    /// It is always additionally bounded by `http_request_timeout`.
+    ///
+    /// Defaults to 10 seconds.
+    #[serde(default = "default_connect_timeout")]
+    #[default(default_connect_timeout())]
+    pub connect_timeout: Duration,
+
     /// Maximum number of HTTP redirects to follow
     ///
     /// Defaults to 3
@@ -288,6 +399,47 @@ pub struct HttpPostRequestorConfig {
     /// Defaults to 10
     #[default(Some(10))]
     pub max_requests_per_sec: Option<u32>,
+
+    /// Path to a PEM file with one or more root certificates used to verify the RPC endpoint,
+    /// e.g. for endpoints with certificates issued by a private CA.
+    ///
+    /// If set, the certificates replace the system trust store.
+    /// Currently applied only by the `SurfRequestor`.
+    ///
+    /// Defaults to `None` (system trust store).
+    #[serde(default)]
+    pub tls_root_certificates: Option<std::path::PathBuf>,
+
+    /// Server name used for TLS SNI and certificate verification instead of the host in the RPC endpoint URL.
+    ///
+    /// The connection is still made to the host given in the URL, but the request (including its `Host` header)
+    /// is addressed to this server name. Currently applied only by the `SurfRequestor`.
+    ///
+    /// Defaults to `None` (the host in the URL).
+    #[serde(default)]
+    pub tls_server_name: Option<String>,
+
+    /// HTTP methods the requestor is allowed to use.
+    ///
+    /// Requests with all methods except `GET`, `HEAD` and `OPTIONS` carry the JSON-serialized data as their body.
+    /// Requests with a method outside this list fail with [HttpRequestError::UnsupportedMethod].
+    ///
+    /// Defaults to `GET` and `POST`.
+    #[serde(default = "default_allowed_methods")]
+    #[default(default_allowed_methods())]
+    pub allowed_methods: Vec<http_types::Method>,
+
+    /// If set, the request bodies are serialized into chunks of this many bytes and streamed
+    /// using the chunked transfer encoding, instead of being serialized into a single buffer.
+    ///
+    /// This lowers the peak memory usage of large (e.g. batch) requests, but some RPC providers
+    /// do not accept requests without a `Content-Length`. Requests signed by a
+    /// [RequestSigner](crate::signer::RequestSigner) are never streamed.
+    /// Currently applied only by the `ReqwestRequestor`.
+    ///
+    /// Defaults to `None` (no streaming). `Some(0)` also disables the streaming.
+    #[serde(default)]
+    pub body_chunk_size: Option<usize>,
 }
 
 /// Shorthand for creating a new EIP1559 transaction object.
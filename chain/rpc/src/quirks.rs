@@ -0,0 +1,499 @@
+//! Normalization of the JSON RPC responses of the providers deviating from the specification.
+//!
+//! Some RPC providers return responses that are almost, but not quite, valid JSON RPC 2.0 responses.
+//! When the [QuirksMode] is enabled on the [JsonRpcProviderClient](crate::client::JsonRpcProviderClient),
+//! each response body is passed through a list of [ResponseQuirk]s, which detect and repair such
+//! deviations before the response is deserialized.
+//!
+//! The default list contains:
+//! - [HtmlErrorPage]: HTTP 200 with an HTML error page instead of a JSON body,
+//! - [MissingJsonRpcVersion]: the `jsonrpc` field is missing,
+//! - [StringResponseId]: the `id` is a string instead of a number,
+//! - [NullErrorAlongsideResult]: a successful response with an additional `"error": null`,
+//! - [ErrorNestedInResult]: the error object nested under the `result` field,
+//! - [StringEncodedChainId]: the `eth_chainId` result encoded as a decimal string or a number.
+//!
+//! Each response of a batch response is repaired on its own.
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::MultiCounter;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_QUIRK_FIXES: MultiCounter = MultiCounter::new(
+        "hopr_rpc_quirk_fixes",
+        "Number of the RPC responses repaired by the quirks mode",
+        &["quirk"]
+    )
+    .unwrap();
+}
+
+/// JSON RPC error code used for the responses that are not JSON at all.
+pub const NON_JSON_RESPONSE_ERROR_CODE: i64 = -32603;
+
+/// Maximum length of the title of an HTML page put into the error message.
+const MAX_HTML_TITLE_LEN: usize = 128;
+
+/// A single known deviation of an RPC provider from the JSON RPC 2.0 specification.
+pub trait ResponseQuirk: Debug + Send + Sync {
+    /// Name of the quirk, which labels the counted fixes.
+    fn name(&self) -> &'static str;
+
+    /// Repairs a response `body` of the request with the given `id` that is not valid JSON.
+    ///
+    /// Returns the repaired response if the quirk has been detected. The default does not repair anything.
+    fn repair_body(&self, _method: &str, _id: u64, _body: &[u8]) -> Option<Value> {
+        None
+    }
+
+    /// Repairs the parsed `response` object of a `method` call in place.
+    ///
+    /// Returns `true` if the quirk has been detected and repaired. The default does not repair anything.
+    fn repair_response(&self, _method: &str, _response: &mut Map<String, Value>) -> bool {
+        false
+    }
+}
+
+/// Responds with an HTML error page (e.g. of a gateway or a rate limiter) with HTTP status 200.
+///
+/// The page is turned into a JSON RPC error with the [NON_JSON_RESPONSE_ERROR_CODE] carrying the page title.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlErrorPage;
+
+impl ResponseQuirk for HtmlErrorPage {
+    fn name(&self) -> &'static str {
+        "html_error_page"
+    }
+
+    fn repair_body(&self, _method: &str, id: u64, body: &[u8]) -> Option<Value> {
+        let page = String::from_utf8_lossy(body);
+        let page = page.trim_start();
+        if !page.starts_with('<') {
+            return None;
+        }
+
+        let lowercase = page.to_ascii_lowercase();
+        let title = lowercase
+            .find("<title>")
+            .map(|start| start + "<title>".len())
+            .and_then(|start| lowercase[start..].find("</title>").map(|len| &page[start..start + len]))
+            .map(|title| title.trim().chars().take(MAX_HTML_TITLE_LEN).collect::<String>())
+            .unwrap_or_default();
+
+        Some(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": NON_JSON_RESPONSE_ERROR_CODE,
+                "message": format!("provider returned an HTML page: {title}"),
+            }
+        }))
+    }
+}
+
+/// Omits the mandatory `jsonrpc` field of the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MissingJsonRpcVersion;
+
+impl ResponseQuirk for MissingJsonRpcVersion {
+    fn name(&self) -> &'static str {
+        "missing_jsonrpc"
+    }
+
+    fn repair_response(&self, _method: &str, response: &mut Map<String, Value>) -> bool {
+        if response.contains_key("jsonrpc") {
+            return false;
+        }
+
+        response.insert("jsonrpc".into(), Value::from("2.0"));
+        true
+    }
+}
+
+/// Echoes the numeric `id` of the request as a string.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringResponseId;
+
+impl ResponseQuirk for StringResponseId {
+    fn name(&self) -> &'static str {
+        "string_id"
+    }
+
+    fn repair_response(&self, _method: &str, response: &mut Map<String, Value>) -> bool {
+        let Some(id) = response
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            return false;
+        };
+
+        response.insert("id".into(), Value::from(id));
+        true
+    }
+}
+
+/// Adds `"error": null` to the successful responses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullErrorAlongsideResult;
+
+impl ResponseQuirk for NullErrorAlongsideResult {
+    fn name(&self) -> &'static str {
+        "null_error"
+    }
+
+    fn repair_response(&self, _method: &str, response: &mut Map<String, Value>) -> bool {
+        if !response.get("error").is_some_and(Value::is_null) {
+            return false;
+        }
+
+        response.remove("error");
+        // A response with no result at all is a `null` result
+        response.entry("result").or_insert(Value::Null);
+        true
+    }
+}
+
+/// Returns the error object as a successful response, nested under the `result` field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ErrorNestedInResult;
+
+impl ResponseQuirk for ErrorNestedInResult {
+    fn name(&self) -> &'static str {
+        "nested_error"
+    }
+
+    fn repair_response(&self, _method: &str, response: &mut Map<String, Value>) -> bool {
+        let is_error =
+            |error: &Map<String, Value>| error.get("code").is_some_and(Value::is_i64) && error.contains_key("message");
+
+        let error = match response.get("result") {
+            Some(Value::Object(result)) => match result.get("error") {
+                Some(Value::Object(nested)) if is_error(nested) => nested.clone(),
+                _ if result.len() <= 3 && is_error(result) => result.clone(),
+                _ => return false,
+            },
+            _ => return false,
+        };
+
+        response.remove("result");
+        response.insert("error".into(), Value::Object(error));
+        true
+    }
+}
+
+/// Returns the result of `eth_chainId` as a decimal string or a number instead of a hex-encoded quantity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringEncodedChainId;
+
+impl ResponseQuirk for StringEncodedChainId {
+    fn name(&self) -> &'static str {
+        "decimal_chain_id"
+    }
+
+    fn repair_response(&self, method: &str, response: &mut Map<String, Value>) -> bool {
+        if method != "eth_chainId" {
+            return false;
+        }
+
+        let chain_id = match response.get("result") {
+            Some(Value::Number(number)) => number.as_u64(),
+            Some(Value::String(s)) if !s.starts_with("0x") => s.trim().parse::<u64>().ok(),
+            _ => None,
+        };
+
+        match chain_id {
+            Some(chain_id) => {
+                response.insert("result".into(), Value::from(format!("{chain_id:#x}")));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Normalization step repairing the known deviations of the RPC providers from the JSON RPC specification.
+///
+/// The quirks are tried in order, each applied fix is counted under the `hopr_rpc_quirk_fixes` metric.
+/// Further quirks can be added via [QuirksMode::with_quirk].
+#[derive(Debug)]
+pub struct QuirksMode {
+    quirks: Vec<Box<dyn ResponseQuirk>>,
+}
+
+impl Default for QuirksMode {
+    /// Quirks mode repairing all the quirks documented in the [module](crate::quirks).
+    fn default() -> Self {
+        Self::empty()
+            .with_quirk(HtmlErrorPage)
+            .with_quirk(MissingJsonRpcVersion)
+            .with_quirk(StringResponseId)
+            .with_quirk(NullErrorAlongsideResult)
+            .with_quirk(ErrorNestedInResult)
+            .with_quirk(StringEncodedChainId)
+    }
+}
+
+impl QuirksMode {
+    /// Quirks mode with no quirks.
+    pub fn empty() -> Self {
+        Self { quirks: Vec::new() }
+    }
+
+    /// Adds the `quirk` at the end of the list.
+    pub fn with_quirk<Q: ResponseQuirk + 'static>(mut self, quirk: Q) -> Self {
+        self.quirks.push(Box::new(quirk));
+        self
+    }
+
+    /// Names of the quirks in the order they are tried.
+    pub fn quirk_names(&self) -> Vec<&'static str> {
+        self.quirks.iter().map(|quirk| quirk.name()).collect()
+    }
+
+    /// Repairs the response `body` of a `method` call with the given request `id`.
+    ///
+    /// If the body is a batch response, each of its responses is repaired as a response of the `method` call.
+    /// The body is returned unchanged if no quirk has been detected.
+    pub fn normalize(&self, method: &str, id: u64, body: Box<[u8]>) -> Box<[u8]> {
+        let mut repaired = false;
+
+        let mut response = match serde_json::from_slice::<Value>(&body) {
+            Ok(response) => response,
+            Err(_) => match self.quirks.iter().find_map(|quirk| {
+                quirk
+                    .repair_body(method, id, &body)
+                    .map(|response| (quirk.name(), response))
+            }) {
+                Some((name, response)) => {
+                    Self::record_fix(method, name);
+                    repaired = true;
+                    response
+                }
+                None => return body,
+            },
+        };
+
+        match &mut response {
+            Value::Object(object) => repaired |= self.repair_response(method, object),
+            Value::Array(batch) => {
+                for object in batch.iter_mut().filter_map(Value::as_object_mut) {
+                    repaired |= self.repair_response(method, object);
+                }
+            }
+            _ => {}
+        }
+
+        if !repaired {
+            return body;
+        }
+
+        match serde_json::to_vec(&response) {
+            Ok(normalized) => normalized.into_boxed_slice(),
+            Err(_) => body,
+        }
+    }
+
+    fn repair_response(&self, method: &str, response: &mut Map<String, Value>) -> bool {
+        let mut repaired = false;
+        for quirk in &self.quirks {
+            if quirk.repair_response(method, response) {
+                Self::record_fix(method, quirk.name());
+                repaired = true;
+            }
+        }
+        repaired
+    }
+
+    fn record_fix(method: &str, quirk: &'static str) {
+        tracing::debug!(method, quirk, "repaired a non-conforming rpc response");
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_QUIRK_FIXES.increment(&[quirk]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn normalize(quirks: &QuirksMode, method: &str, body: &str) -> Value {
+        let normalized = quirks.normalize(method, 7, body.as_bytes().into());
+        serde_json::from_slice(&normalized).expect("normalized response must be valid JSON")
+    }
+
+    #[test]
+    fn quirks_mode_should_repair_the_documented_quirks() {
+        // (quirk, method, captured response from data/quirks, expected normalized response)
+        let cases = [
+            (
+                "html_error_page",
+                "eth_blockNumber",
+                include_str!("../data/quirks/html_error_page_cloudflare_502.html"),
+                json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32603, "message": "provider returned an HTML page: 502 Bad Gateway"}}),
+            ),
+            (
+                "html_error_page",
+                "eth_getBalance",
+                include_str!("../data/quirks/html_error_page_nginx_429.html"),
+                json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32603, "message": "provider returned an HTML page: 429 Too Many Requests"}}),
+            ),
+            (
+                "missing_jsonrpc",
+                "eth_blockNumber",
+                include_str!("../data/quirks/missing_jsonrpc_eth_blockNumber.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": "0x1f3a2c"}),
+            ),
+            (
+                "string_id",
+                "eth_gasPrice",
+                include_str!("../data/quirks/string_id_eth_gasPrice.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": "0x3b9aca00"}),
+            ),
+            (
+                "null_error",
+                "eth_getTransactionCount",
+                include_str!("../data/quirks/null_error_eth_getTransactionCount.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": "0x2a"}),
+            ),
+            (
+                "null_error",
+                "eth_getTransactionReceipt",
+                include_str!("../data/quirks/null_error_eth_getTransactionReceipt.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": null}),
+            ),
+            (
+                "nested_error",
+                "eth_call",
+                include_str!("../data/quirks/nested_error_eth_call.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32000, "message": "execution reverted"}}),
+            ),
+            (
+                "nested_error",
+                "eth_sendRawTransaction",
+                include_str!("../data/quirks/nested_error_eth_sendRawTransaction.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32000, "message": "nonce too low"}}),
+            ),
+            (
+                "decimal_chain_id",
+                "eth_chainId",
+                include_str!("../data/quirks/decimal_chain_id_string.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": "0x64"}),
+            ),
+            (
+                "decimal_chain_id",
+                "eth_chainId",
+                include_str!("../data/quirks/decimal_chain_id_number.json"),
+                json!({"jsonrpc": "2.0", "id": 7, "result": "0x7a69"}),
+            ),
+        ];
+
+        for (quirk, method, captured, expected) in cases {
+            let only_quirk = QuirksMode::default()
+                .quirks
+                .into_iter()
+                .find(|q| q.name() == quirk)
+                .map(|q| QuirksMode { quirks: vec![q] })
+                .expect("quirk must be in the default list");
+
+            assert_eq!(
+                expected,
+                normalize(&only_quirk, method, captured),
+                "{quirk} must be repaired"
+            );
+            assert_eq!(
+                expected,
+                normalize(&QuirksMode::default(), method, captured),
+                "{quirk} must be repaired by the default list"
+            );
+        }
+    }
+
+    #[test]
+    fn quirks_mode_should_repair_multiple_quirks_of_a_response() {
+        assert_eq!(
+            json!({"jsonrpc": "2.0", "id": 3, "result": "0x64"}),
+            normalize(&QuirksMode::default(), "eth_chainId", r#"{"id":"3","result":"100"}"#)
+        );
+    }
+
+    #[test]
+    fn quirks_mode_should_repair_each_response_of_a_batch() {
+        assert_eq!(
+            json!([
+                {"jsonrpc": "2.0", "id": 7, "result": "0x1f3a2c"},
+                {"jsonrpc": "2.0", "id": 8, "result": "0x2a"},
+                {"jsonrpc": "2.0", "id": 9, "error": {"code": -32000, "message": "nonce too low"}}
+            ]),
+            normalize(
+                &QuirksMode::default(),
+                "eth_getTransactionCount",
+                include_str!("../data/quirks/batch_eth_getTransactionCount.json")
+            )
+        );
+    }
+
+    #[test]
+    fn quirks_mode_should_keep_conforming_responses_intact() {
+        let quirks = QuirksMode::default();
+
+        let cases = [
+            ("eth_chainId", r#"{"jsonrpc":"2.0","id":7,"result":"0x64"}"#),
+            ("eth_blockNumber", r#"{"jsonrpc":"2.0","id":7,"result":"100"}"#),
+            (
+                "eth_call",
+                r#"{"jsonrpc":"2.0","id":7,"result":{"code":"0x1","message":"not an error"}}"#,
+            ),
+            (
+                "eth_call",
+                r#"{"jsonrpc":"2.0","id":7,"error":{"code":3,"message":"reverted"}}"#,
+            ),
+            (
+                "eth_blockNumber",
+                r#"[{"jsonrpc":"2.0","id":7,"result":"0x1"},{"jsonrpc":"2.0","id":8,"result":"0x2"}]"#,
+            ),
+            ("eth_blockNumber", "}malformed{"),
+        ];
+
+        for (method, body) in cases {
+            assert_eq!(
+                body.as_bytes(),
+                quirks.normalize(method, 7, body.as_bytes().into()).as_ref(),
+                "{body} must not be changed"
+            );
+        }
+    }
+
+    #[derive(Debug)]
+    struct UppercaseHexResult;
+
+    impl ResponseQuirk for UppercaseHexResult {
+        fn name(&self) -> &'static str {
+            "uppercase_hex"
+        }
+
+        fn repair_response(&self, _method: &str, response: &mut Map<String, Value>) -> bool {
+            match response.get_mut("result") {
+                Some(Value::String(result)) if result.starts_with("0X") => {
+                    *result = result.to_lowercase();
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    #[test]
+    fn quirks_mode_should_be_extensible_with_custom_quirks() {
+        let quirks = QuirksMode::default().with_quirk(UppercaseHexResult);
+        assert_eq!(Some(&"uppercase_hex"), quirks.quirk_names().last());
+
+        assert_eq!(
+            json!({"jsonrpc": "2.0", "id": 7, "result": "0xff"}),
+            normalize(&quirks, "eth_blockNumber", r#"{"id":7,"result":"0XFF"}"#)
+        );
+    }
+}
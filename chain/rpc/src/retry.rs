@@ -0,0 +1,5 @@
+//! Generic retrying of fallible asynchronous operations driven by a [RetryPolicy](crate::RetryPolicy).
+//!
+//! The [JsonRpcProviderClient](crate::client::JsonRpcProviderClient) uses this to retry the RPC requests.
+//! The retry helpers are shared with the other crates and re-exported from [hopr_async_runtime::retry].
+pub use hopr_async_runtime::retry::*;
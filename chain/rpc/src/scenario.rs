@@ -0,0 +1,596 @@
+//! Recording and replaying of whole RPC scenarios for reproducible integration tests.
+//!
+//! A scenario is a [snapshot](SnapshotRequestor) captured against a live RPC endpoint (such as Anvil)
+//! by the [`ScenarioRecorder`], which additionally records the time each request was made at.
+//! Unlike the [`SnapshotRequestor`], which resolves each request by its content, the [`ScenarioPlayer`]
+//! replays the captured entries as an ordered sequence of request/response pairs without the endpoint.
+//!
+//! The JSON RPC request ids are not part of the request matching, because they are not stable
+//! across different client instances. The replayed responses always carry the id of the request.
+//!
+//! This is useful for testing only and should **NOT** be used in production.
+
+use async_trait::async_trait;
+use http_types::Method;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use hopr_async_runtime::prelude::sleep;
+
+use crate::client::{write_snapshot_file, RequestorResponseSnapshot, SnapshotRequestor};
+use crate::errors::HttpRequestError;
+use crate::HttpRequestor;
+
+/// Path of the file of the scenario with the given `name` in the scenario directory `dir`.
+pub fn scenario_path(dir: impl AsRef<Path>, name: &str) -> PathBuf {
+    dir.as_ref().join(format!("{name}.yaml"))
+}
+
+/// Strips the JSON RPC ids from the (possibly batched) `request` and returns it along with its method.
+fn normalize_request(request: &str) -> (String, String) {
+    match serde_json::from_str::<serde_json::Value>(request) {
+        Ok(serde_json::Value::Object(mut object)) => {
+            object.remove("id");
+            let method = object
+                .get("method")
+                .and_then(|method| method.as_str())
+                .unwrap_or_default()
+                .to_owned();
+            (serde_json::Value::Object(object).to_string(), method)
+        }
+        Ok(serde_json::Value::Array(mut batch)) => {
+            batch
+                .iter_mut()
+                .filter_map(|request| request.as_object_mut())
+                .for_each(|request| {
+                    request.remove("id");
+                });
+            (serde_json::Value::Array(batch).to_string(), "batch".into())
+        }
+        _ => (request.to_owned(), String::new()),
+    }
+}
+
+/// JSON RPC ids of the (possibly batched) request or response.
+fn json_rpc_ids(value: &serde_json::Value) -> Vec<Option<serde_json::Value>> {
+    match value {
+        serde_json::Value::Array(batch) => batch.iter().map(|item| item.get("id").cloned()).collect(),
+        single => vec![single.get("id").cloned()],
+    }
+}
+
+/// Replaces the JSON RPC ids in the `recorded` response by the ids of the actual `request`.
+fn rewrite_response_ids(request: &str, recorded: &str) -> Box<[u8]> {
+    let (Ok(request), Ok(mut response)) = (
+        serde_json::from_str::<serde_json::Value>(request),
+        serde_json::from_str::<serde_json::Value>(recorded),
+    ) else {
+        return recorded.as_bytes().into();
+    };
+
+    let items = match &mut response {
+        serde_json::Value::Array(batch) => batch.iter_mut().collect::<Vec<_>>(),
+        single => vec![single],
+    };
+    for (item, id) in items.into_iter().zip(json_rpc_ids(&request)) {
+        if let (Some(item), Some(id)) = (item.as_object_mut(), id) {
+            item.insert("id".into(), id);
+        }
+    }
+
+    response.to_string().into_bytes().into_boxed_slice()
+}
+
+/// Records the requests made via the inner [`HttpRequestor`] as a named scenario.
+///
+/// The requests are captured by a [`SnapshotRequestor`] that also records the time offset
+/// of each request relative to the first recorded request.
+/// Only the successful requests are recorded, and health checks are never recorded.
+///
+/// The scenario is saved into the scenario directory only on [`ScenarioRecorder::save`],
+/// nothing is saved on Drop.
+#[derive(Debug)]
+pub struct ScenarioRecorder<R> {
+    snapshot: SnapshotRequestor<R>,
+    path: PathBuf,
+}
+
+impl<R> ScenarioRecorder<R> {
+    /// Creates a recorder of the scenario with the given `name` into the scenario directory `dir`,
+    /// wrapping the live `inner` requestor.
+    pub fn new(inner: R, dir: impl AsRef<Path>, name: &str) -> Self {
+        let path = scenario_path(dir, name);
+        Self {
+            snapshot: SnapshotRequestor::new(inner, &path.to_string_lossy())
+                .with_ignore_snapshot(true)
+                .with_recorded_timing(),
+            path,
+        }
+    }
+
+    /// Path of the scenario file.
+    pub fn scenario_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of the request/response pairs recorded so far.
+    pub fn recorded_count(&self) -> usize {
+        self.snapshot.sorted_entries().len()
+    }
+
+    /// Saves the recorded scenario into the scenario directory, creating the directory if needed.
+    ///
+    /// The file is written on a blocking thread.
+    pub async fn save(&self) -> std::io::Result<PathBuf> {
+        let entries = self.snapshot.sorted_entries();
+        let path = self.path.clone();
+
+        let (tx, rx) = futures::channel::oneshot::channel();
+        drop(hopr_async_runtime::prelude::spawn_blocking(move || {
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| write_snapshot_file(&path, &entries))
+                .map(|_| (path, entries.len()));
+            let _ = tx.send(result);
+        }));
+
+        let (path, len) = rx
+            .await
+            .map_err(|_| std::io::Error::other("saving of the scenario was cancelled"))??;
+
+        tracing::debug!("scenario with {len} entries saved to file {}", path.display());
+        Ok(path)
+    }
+}
+
+impl<R: HttpRequestor> ScenarioRecorder<R> {
+    async fn http_query_recorded<In>(
+        &self,
+        method: Method,
+        url: &str,
+        data: Option<In>,
+    ) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        In: Serialize + Send + Sync,
+    {
+        match (method, data) {
+            (Method::Post, Some(data)) => self.snapshot.http_post(url, data).await,
+            (method, _) => Err(HttpRequestError::UnknownError(format!(
+                "{method} requests are not part of scenarios"
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: HttpRequestor> HttpRequestor for ScenarioRecorder<R> {
+    async fn http_query<T>(&self, method: Method, url: &str, data: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_query_recorded(method, url, data).await
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        self.snapshot.health_check(url).await
+    }
+}
+
+#[async_trait]
+impl<R: HttpRequestor> HttpRequestor for &ScenarioRecorder<R> {
+    async fn http_query<T>(&self, method: Method, url: &str, data: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_query_recorded(method, url, data).await
+    }
+
+    async fn health_check(&self, url: &str) -> bool {
+        self.snapshot.health_check(url).await
+    }
+}
+
+/// Tolerance of the [`ScenarioPlayer`] to the order of the replayed requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplayOrder {
+    /// Each request must be the next one not yet replayed.
+    #[default]
+    Exact,
+    /// Each request must be the next one not yet replayed among the requests of the same method,
+    /// while the requests of different methods can be interleaved arbitrarily.
+    PerMethod,
+    /// Requests can be replayed in any order.
+    Unordered,
+}
+
+/// Scenario entry not yet replayed by the [`ScenarioPlayer`].
+#[derive(Debug)]
+struct PendingEntry {
+    key: String,
+    method: String,
+    entry: RequestorResponseSnapshot,
+}
+
+impl From<RequestorResponseSnapshot> for PendingEntry {
+    fn from(entry: RequestorResponseSnapshot) -> Self {
+        let (key, method) = normalize_request(&entry.request);
+        Self { key, method, entry }
+    }
+}
+
+/// Replays a scenario recorded by the [`ScenarioRecorder`] without the live RPC endpoint.
+///
+/// Each recorded entry is replayed exactly once, and the requests must arrive in the
+/// [order](ScenarioPlayer::with_order) they were recorded in. Requests missing in the scenario
+/// result in HTTP error 404, requests out of the order result in an error.
+///
+/// By default, the responses are replayed immediately. With the
+/// [time compression](ScenarioPlayer::with_time_compression), each response is held back until
+/// the (compressed) time offset of its request since the first replayed request.
+#[derive(Debug)]
+pub struct ScenarioPlayer {
+    name: String,
+    order: ReplayOrder,
+    time_compression: Option<f64>,
+    started: OnceLock<Instant>,
+    remaining: Mutex<Vec<PendingEntry>>,
+}
+
+impl ScenarioPlayer {
+    /// Loads the scenario with the given `name` from the scenario directory `dir`.
+    pub fn load(dir: impl AsRef<Path>, name: &str) -> std::io::Result<Self> {
+        let mut entries = serde_yaml::from_reader::<_, Vec<RequestorResponseSnapshot>>(std::fs::File::open(
+            scenario_path(dir, name),
+        )?)
+        .map_err(std::io::Error::other)?;
+        entries.sort_unstable_by_key(|entry| entry.id);
+
+        tracing::debug!("scenario {name} with {} entries has been loaded", entries.len());
+        Ok(Self {
+            name: name.to_owned(),
+            order: ReplayOrder::default(),
+            time_compression: None,
+            started: OnceLock::new(),
+            remaining: Mutex::new(entries.into_iter().map(PendingEntry::from).collect()),
+        })
+    }
+
+    /// Sets the tolerance to the order of the replayed requests.
+    pub fn with_order(mut self, order: ReplayOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Replays the responses at the recorded pace sped up by the given `factor`.
+    ///
+    /// The factor of `1.0` replays in real time, the factor of `10.0` replays ten times faster.
+    pub fn with_time_compression(mut self, factor: f64) -> Self {
+        self.time_compression = Some(factor.max(f64::EPSILON));
+        self
+    }
+
+    /// Number of the scenario entries not yet replayed.
+    pub fn remaining_count(&self) -> usize {
+        self.remaining.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Takes the entry matching the `request` out of the remaining entries, respecting the replay order.
+    fn take_entry(&self, request: &str) -> Result<PendingEntry, HttpRequestError> {
+        let (key, method) = normalize_request(request);
+
+        let mut remaining = self.remaining.lock().unwrap_or_else(|e| e.into_inner());
+        let candidate = match self.order {
+            ReplayOrder::Exact => remaining.first().map(|_| 0),
+            ReplayOrder::PerMethod => remaining.iter().position(|pending| pending.method == method),
+            ReplayOrder::Unordered => remaining.iter().position(|pending| pending.key == key),
+        };
+
+        match candidate {
+            Some(index) if remaining[index].key == key => Ok(remaining.remove(index)),
+            Some(index) => {
+                let expected = remaining[index].entry.id;
+                tracing::error!(
+                    expected,
+                    order = ?self.order,
+                    "{request} arrived out of the order of scenario {}",
+                    self.name
+                );
+                Err(HttpRequestError::UnknownError(format!(
+                    "request out of the scenario order: expected entry #{expected}"
+                )))
+            }
+            None => {
+                tracing::error!("{request} is missing in scenario {}", self.name);
+                Err(HttpRequestError::HttpError(http_types::StatusCode::NotFound, None))
+            }
+        }
+    }
+
+    async fn http_post_replayed<In>(&self, data: In) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        In: Serialize + Send + Sync,
+    {
+        let request = serde_json::to_string(&data)
+            .map_err(|e| HttpRequestError::UnknownError(format!("serialize error: {e}")))?;
+
+        let started = *self.started.get_or_init(Instant::now);
+        let PendingEntry { method, entry, .. } = self.take_entry(&request)?;
+
+        if let Some(factor) = self.time_compression {
+            let due = Duration::from_millis(entry.offset_ms.unwrap_or_default()).div_f64(factor);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                sleep(wait).await;
+            }
+        }
+
+        tracing::debug!(id = entry.id, %method, "replayed scenario entry");
+        Ok(rewrite_response_ids(&request, &entry.response))
+    }
+
+    async fn http_query_replayed<In>(&self, method: Method, data: Option<In>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        In: Serialize + Send + Sync,
+    {
+        match (method, data) {
+            (Method::Post, Some(data)) => self.http_post_replayed(data).await,
+            (method, _) => Err(HttpRequestError::UnknownError(format!(
+                "{method} requests are not part of scenarios"
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl HttpRequestor for ScenarioPlayer {
+    async fn http_query<T>(&self, method: Method, _: &str, data: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_query_replayed(method, data).await
+    }
+
+    /// When replaying a scenario, the endpoint is always considered healthy.
+    async fn health_check(&self, _: &str) -> bool {
+        true
+    }
+}
+
+#[async_trait]
+impl HttpRequestor for &ScenarioPlayer {
+    async fn http_query<T>(&self, method: Method, _: &str, data: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+    where
+        T: Serialize + Send + Sync,
+    {
+        self.http_query_replayed(method, data).await
+    }
+
+    async fn health_check(&self, _: &str) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::middleware::SignerMiddleware;
+    use ethers::providers::Provider;
+    use ethers::signers::{LocalWallet, Signer};
+    use hopr_chain_types::utils::create_anvil;
+    use hopr_chain_types::{ContractAddresses, ContractInstances};
+    use hopr_crypto_types::keypairs::{ChainKeypair, Keypair};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::client::surf_client::SurfRequestor;
+    use crate::client::{create_rpc_client_to_anvil, JsonRpcProviderClient, SimpleJsonRpcRetryPolicy};
+
+    fn write_scenario(dir: &Path, requests: &[serde_json::Value]) -> anyhow::Result<()> {
+        let entries = requests
+            .iter()
+            .enumerate()
+            .map(|(i, request)| RequestorResponseSnapshot {
+                id: i + 1,
+                request: request.to_string(),
+                response: json!({"jsonrpc": "2.0", "id": 0, "result": i}).to_string(),
+                offset_ms: Some(100 * i as u64),
+            })
+            .collect::<Vec<_>>();
+        write_snapshot_file(scenario_path(dir, "test"), &entries)?;
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct FixedRequestor;
+
+    #[async_trait]
+    impl HttpRequestor for FixedRequestor {
+        async fn http_query<T>(&self, _: Method, _: &str, _: Option<T>) -> Result<Box<[u8]>, HttpRequestError>
+        where
+            T: Serialize + Send + Sync,
+        {
+            Ok(json!({"jsonrpc": "2.0", "id": 0, "result": 1})
+                .to_string()
+                .into_bytes()
+                .into_boxed_slice())
+        }
+    }
+
+    async fn record_two_requests(recorder: &ScenarioRecorder<FixedRequestor>) -> anyhow::Result<()> {
+        recorder
+            .http_post("http://localhost", json!({"id": 1, "method": "a"}))
+            .await?;
+        sleep(Duration::from_millis(50)).await;
+        recorder
+            .http_post("http://localhost", json!({"id": 2, "method": "a"}))
+            .await?;
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_scenario_recorder_should_save_the_timed_entries_only_explicitly() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let recorder = ScenarioRecorder::new(FixedRequestor, dir.path(), "test");
+        record_two_requests(&recorder).await?;
+        assert_eq!(2, recorder.recorded_count());
+        drop(recorder);
+        assert!(
+            !scenario_path(dir.path(), "test").exists(),
+            "the scenario must not be saved on drop"
+        );
+
+        let recorder = ScenarioRecorder::new(FixedRequestor, dir.path().join("nested"), "test");
+        record_two_requests(&recorder).await?;
+        let path = recorder.save().await?;
+
+        let entries: Vec<RequestorResponseSnapshot> = serde_yaml::from_reader(std::fs::File::open(path)?)?;
+        assert_eq!(vec![1, 2], entries.iter().map(|entry| entry.id).collect::<Vec<_>>());
+        assert_eq!(Some(0), entries[0].offset_ms);
+        assert!(entries[1].offset_ms.is_some_and(|offset| offset >= 50));
+
+        Ok(())
+    }
+
+    async fn replay(player: &ScenarioPlayer, requests: &[serde_json::Value]) -> Vec<Result<usize, HttpRequestError>> {
+        let mut results = Vec::new();
+        for request in requests {
+            results.push(player.http_post("http://localhost", request).await.map(|response| {
+                let response: serde_json::Value = serde_json::from_slice(&response).expect("must be json");
+                assert_eq!(request["id"], response["id"], "response must carry the request id");
+                response["result"].as_u64().expect("must have result") as usize
+            }));
+        }
+        results
+    }
+
+    #[async_std::test]
+    async fn test_scenario_player_should_enforce_the_replay_order() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_scenario(
+            dir.path(),
+            &[
+                json!({"id": 1, "method": "a", "params": [1]}),
+                json!({"id": 2, "method": "b", "params": [1]}),
+                json!({"id": 3, "method": "a", "params": [2]}),
+            ],
+        )?;
+
+        // Different request ids than recorded
+        let swapped_methods = [
+            json!({"id": 11, "method": "b", "params": [1]}),
+            json!({"id": 12, "method": "a", "params": [1]}),
+            json!({"id": 13, "method": "a", "params": [2]}),
+        ];
+        let swapped_within_method = [
+            json!({"id": 21, "method": "a", "params": [2]}),
+            json!({"id": 22, "method": "b", "params": [1]}),
+            json!({"id": 23, "method": "a", "params": [1]}),
+        ];
+
+        let player = ScenarioPlayer::load(dir.path(), "test")?;
+        let results = replay(&player, &swapped_methods).await;
+        assert!(
+            results[0].is_err(),
+            "exact order must not allow different methods to be swapped"
+        );
+
+        let player = ScenarioPlayer::load(dir.path(), "test")?.with_order(ReplayOrder::PerMethod);
+        let results = replay(&player, &swapped_methods).await;
+        assert_eq!(vec![1, 0, 2], results.into_iter().collect::<Result<Vec<_>, _>>()?);
+        assert_eq!(0, player.remaining_count());
+
+        let results = replay(&player, &swapped_within_method).await;
+        assert!(
+            matches!(
+                results[0],
+                Err(HttpRequestError::HttpError(http_types::StatusCode::NotFound, _))
+            ),
+            "replayed entries must not be replayed again"
+        );
+
+        let player = ScenarioPlayer::load(dir.path(), "test")?.with_order(ReplayOrder::PerMethod);
+        let results = replay(&player, &swapped_within_method).await;
+        assert!(
+            results[0].is_err(),
+            "per-method order must not allow same methods to be swapped"
+        );
+
+        let player = ScenarioPlayer::load(dir.path(), "test")?.with_order(ReplayOrder::Unordered);
+        let results = replay(&player, &swapped_within_method).await;
+        assert_eq!(vec![2, 1, 0], results.into_iter().collect::<Result<Vec<_>, _>>()?);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_scenario_player_should_replay_with_time_compression() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let requests = [
+            json!({"id": 1, "method": "a"}),
+            json!({"id": 2, "method": "a"}),
+            json!({"id": 3, "method": "a"}),
+        ];
+        write_scenario(dir.path(), &requests)?;
+
+        let started = Instant::now();
+        let player = ScenarioPlayer::load(dir.path(), "test")?.with_time_compression(2.0);
+        let results = replay(&player, &requests).await;
+
+        assert_eq!(vec![0, 1, 2], results.into_iter().collect::<Result<Vec<_>, _>>()?);
+        assert!(
+            started.elapsed() >= Duration::from_millis(100),
+            "the 200 ms must be compressed to 100 ms"
+        );
+        assert!(
+            started.elapsed() < Duration::from_millis(200),
+            "the 200 ms must be compressed"
+        );
+
+        Ok(())
+    }
+
+    async fn deploy<R: HttpRequestor>(
+        client: Arc<SignerMiddleware<Provider<JsonRpcProviderClient<R, SimpleJsonRpcRetryPolicy>>, LocalWallet>>,
+        deployer: &ChainKeypair,
+    ) -> anyhow::Result<ContractAddresses> {
+        let contracts = ContractInstances::deploy_for_testing(client, deployer)
+            .await
+            .map_err(|e| anyhow::anyhow!("deploy failed: {e}"))?;
+        Ok(ContractAddresses::from(&contracts))
+    }
+
+    #[async_std::test]
+    async fn test_scenario_should_replay_contract_deployment_without_anvil() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let anvil = create_anvil(None);
+        let chain_id = anvil.chain_id();
+        let deployer = ChainKeypair::from_secret(anvil.keys()[0].to_bytes().as_ref())?;
+
+        let recorder = ScenarioRecorder::new(SurfRequestor::default(), dir.path(), "deploy");
+        let recorded = deploy(create_rpc_client_to_anvil(&recorder, &anvil, &deployer), &deployer).await?;
+        recorder.save().await?;
+        assert!(recorder.recorded_count() > 0);
+
+        drop(anvil);
+
+        let player = ScenarioPlayer::load(dir.path(), "deploy")?.with_order(ReplayOrder::PerMethod);
+        let wallet = LocalWallet::from_bytes(deployer.secret().as_ref())?.with_chain_id(chain_id);
+        let provider = Provider::new(JsonRpcProviderClient::new(
+            "http://localhost:8545",
+            &player,
+            SimpleJsonRpcRetryPolicy::default(),
+        ))
+        .interval(Duration::from_millis(10));
+
+        let replayed = deploy(Arc::new(SignerMiddleware::new(provider, wallet)), &deployer).await?;
+
+        assert_eq!(recorded, replayed);
+        assert_eq!(0, player.remaining_count(), "the whole scenario must be replayed");
+
+        Ok(())
+    }
+}
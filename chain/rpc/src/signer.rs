@@ -0,0 +1,145 @@
+//! Signing of the RPC requests for the authenticated private endpoints.
+//!
+//! Some self-hosted RPC gateways require each request to carry a timestamp and a signature of its body,
+//! so that the endpoint cannot be abused by anyone who learns its URL. The [RequestSigner] can be set
+//! on the HTTP requestors (see e.g. [SurfRequestor::with_request_signer](crate::client::surf_client::SurfRequestor::with_request_signer)),
+//! which then sign each sent request, including each retry, at its current time.
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::{Debug, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default name of the header carrying the timestamp of a request signed by the [HmacSha256Signer].
+pub const DEFAULT_TIMESTAMP_HEADER: &str = "x-request-timestamp";
+
+/// Default name of the header carrying the signature of a request signed by the [HmacSha256Signer].
+pub const DEFAULT_SIGNATURE_HEADER: &str = "x-request-signature";
+
+/// Authenticates the HTTP requests sent to an RPC endpoint.
+///
+/// The implementations must not log the signature material.
+pub trait RequestSigner: Debug + Send + Sync {
+    /// Creates the headers authenticating the request with the given serialized `body`,
+    /// which is sent at the given `timestamp` (UNIX time in seconds).
+    fn sign(&self, body: &[u8], timestamp: u64) -> Vec<(String, String)>;
+}
+
+/// [RequestSigner] authenticating the requests with an HMAC-SHA256 over the timestamp and the body.
+///
+/// The signature is the hex-encoded HMAC-SHA256 of `<timestamp>.<body>` using the shared secret key.
+/// Both the timestamp and the signature are sent in separate headers.
+#[derive(Clone)]
+pub struct HmacSha256Signer {
+    key: Box<[u8]>,
+    timestamp_header: String,
+    signature_header: String,
+}
+
+impl Debug for HmacSha256Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacSha256Signer")
+            .field("key", &"<redacted>")
+            .field("timestamp_header", &self.timestamp_header)
+            .field("signature_header", &self.signature_header)
+            .finish()
+    }
+}
+
+impl HmacSha256Signer {
+    /// Signs the requests using the given shared secret `key`
+    /// and sends the signatures in the [default headers](DEFAULT_SIGNATURE_HEADER).
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: key.as_ref().into(),
+            timestamp_header: DEFAULT_TIMESTAMP_HEADER.into(),
+            signature_header: DEFAULT_SIGNATURE_HEADER.into(),
+        }
+    }
+
+    /// Sends the timestamp and the signature in the headers with the given names.
+    pub fn with_header_names(mut self, timestamp_header: &str, signature_header: &str) -> Self {
+        self.timestamp_header = timestamp_header.into();
+        self.signature_header = signature_header.into();
+        self
+    }
+
+    /// Computes the hex-encoded signature of the `body` sent at the given `timestamp`.
+    pub fn signature(&self, body: &[u8], timestamp: u64) -> String {
+        // HMAC accepts keys of any length
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("hmac key of any length must be valid");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl RequestSigner for HmacSha256Signer {
+    fn sign(&self, body: &[u8], timestamp: u64) -> Vec<(String, String)> {
+        vec![
+            (self.timestamp_header.clone(), timestamp.to_string()),
+            (self.signature_header.clone(), self.signature(body, timestamp)),
+        ]
+    }
+}
+
+/// Creates the headers of a request with the given `body` signed by the `signer` (if any) at the current time.
+pub(crate) fn signed_headers(signer: Option<&dyn RequestSigner>, body: &[u8]) -> Vec<(String, String)> {
+    signer
+        .map(|signer| {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            signer.sign(body, timestamp)
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_signer_should_sign_timestamp_and_body() {
+        let signer = HmacSha256Signer::new(b"secret").with_header_names("x-ts", "x-sig");
+        let body = br#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId","params":[]}"#;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(b"1700000000.");
+        mac.update(body);
+        let expected = hex::encode(mac.finalize().into_bytes());
+
+        assert_eq!(
+            vec![
+                ("x-ts".to_string(), "1700000000".to_string()),
+                ("x-sig".to_string(), expected)
+            ],
+            signer.sign(body, 1700000000)
+        );
+        assert_ne!(
+            signer.signature(body, 1700000000),
+            signer.signature(body, 1700000001),
+            "signature must depend on the timestamp"
+        );
+    }
+
+    #[test]
+    fn hmac_signer_should_not_reveal_the_key() {
+        let signer = HmacSha256Signer::new(b"very-secret-key");
+
+        assert!(!format!("{signer:?}").contains("very-secret-key"));
+        assert!(format!("{signer:?}").contains("<redacted>"));
+    }
+
+    #[test]
+    fn no_headers_should_be_created_without_signer() {
+        assert!(signed_headers(None, b"{}").is_empty());
+
+        let headers = signed_headers(Some(&HmacSha256Signer::new(b"key")), b"{}");
+        assert_eq!(
+            vec![DEFAULT_TIMESTAMP_HEADER, DEFAULT_SIGNATURE_HEADER],
+            headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Statistics of the [JsonRpcProviderClient](crate::client::JsonRpcProviderClient).
+//!
+//! The [ClientStats] are the single place where the client records its activity:
+//! - the retries and terminal failures of the calls, by their [RetryReason],
+//! - the number of successful and failed HTTP requests made to each RPC endpoint together with their latencies,
+//!   until they are taken and reset by [ClientStats::snapshot_and_reset], which allows reporting the service level
+//!   of the RPC providers over arbitrary periods,
+//! - optionally, the [CallAuditLog] of the most recent calls,
+//! - optionally, the [UsageTracker] of the JSON RPC methods over a sliding window.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::audit::{redact_url, CallAuditLog, CallRecord};
+use crate::errors::RetryReason;
+use crate::usage::{CallUsage, UsageReport, UsageTracker};
+
+/// Maximum number of latency samples kept per endpoint between two resets.
+///
+/// Once exceeded, the oldest samples are overwritten, so the percentiles reflect the most recent requests.
+pub const MAX_LATENCY_SAMPLES: usize = 10_000;
+
+/// Statistics of the requests to a single endpoint since the last reset.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStats {
+    /// Number of requests that returned a successful JSON RPC response.
+    pub successes: u64,
+    /// Number of requests that failed with a transport, HTTP or JSON RPC error.
+    pub failures: u64,
+    /// Median latency of the requests.
+    pub latency_p50: Duration,
+    /// 95th percentile of the latency of the requests.
+    pub latency_p95: Duration,
+    /// 99th percentile of the latency of the requests.
+    pub latency_p99: Duration,
+    /// Highest latency of a request.
+    pub latency_max: Duration,
+}
+
+impl EndpointStats {
+    /// Ratio of the successful requests, or `None` if there were no requests.
+    pub fn success_rate(&self) -> Option<f64> {
+        let total = self.successes + self.failures;
+        (total > 0).then(|| self.successes as f64 / total as f64)
+    }
+}
+
+/// Statistics of all the endpoints taken by [ClientStats::snapshot_and_reset].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClientStatsSnapshot {
+    /// Duration since the previous reset (or since the client was created).
+    pub period: Duration,
+    /// Statistics of each endpoint with the possible secrets in its URL redacted.
+    pub endpoints: BTreeMap<String, EndpointStats>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointCounters {
+    successes: u64,
+    failures: u64,
+    latencies: Vec<Duration>,
+    next_sample: usize,
+}
+
+impl EndpointCounters {
+    fn record(&mut self, latency: Duration, success: bool) {
+        if success {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+
+        if self.latencies.len() < MAX_LATENCY_SAMPLES {
+            self.latencies.push(latency);
+        } else {
+            self.latencies[self.next_sample] = latency;
+            self.next_sample = (self.next_sample + 1) % MAX_LATENCY_SAMPLES;
+        }
+    }
+
+    fn into_stats(mut self) -> EndpointStats {
+        self.latencies.sort_unstable();
+        let percentile = |p: usize| {
+            // Nearest-rank method
+            let rank = (p * self.latencies.len()).div_ceil(100);
+            self.latencies.get(rank.saturating_sub(1)).copied().unwrap_or_default()
+        };
+
+        EndpointStats {
+            successes: self.successes,
+            failures: self.failures,
+            latency_p50: percentile(50),
+            latency_p95: percentile(95),
+            latency_p99: percentile(99),
+            latency_max: self.latencies.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Period {
+    started: Instant,
+    endpoints: HashMap<String, EndpointCounters>,
+}
+
+/// Statistics of the calls and requests of the RPC client.
+///
+/// The statistics are shared by all the clones of the client (see
+/// [JsonRpcProviderClient::stats](crate::client::JsonRpcProviderClient::stats)).
+/// The retry counters and the per-endpoint statistics are always collected, while the audit log and the usage
+/// report are collected only if enabled.
+#[derive(Debug)]
+pub struct ClientStats {
+    retries: [AtomicU64; RetryReason::ALL.len()],
+    terminal_failures: [AtomicU64; RetryReason::ALL.len()],
+    period: Mutex<Period>,
+    audit_log: Option<CallAuditLog>,
+    usage: Option<UsageTracker>,
+}
+
+impl Default for ClientStats {
+    fn default() -> Self {
+        Self {
+            retries: Default::default(),
+            terminal_failures: Default::default(),
+            period: Mutex::new(Period {
+                started: Instant::now(),
+                endpoints: HashMap::new(),
+            }),
+            audit_log: None,
+            usage: None,
+        }
+    }
+}
+
+impl ClientStats {
+    /// Keeps the records of the last `capacity` calls in a [CallAuditLog],
+    /// with their parameters truncated to `max_params_len` bytes.
+    pub fn with_audit_log(mut self, capacity: usize, max_params_len: usize) -> Self {
+        self.audit_log = Some(CallAuditLog::new(capacity, max_params_len));
+        self
+    }
+
+    /// Tracks the usage of each JSON RPC method over a sliding `window` (see [UsageTracker]).
+    pub fn with_usage_report(mut self, window: Duration) -> Self {
+        self.usage = Some(UsageTracker::new(window));
+        self
+    }
+
+    /// Empty statistics with the same audit log and usage report settings.
+    pub(crate) fn empty_copy(&self) -> Self {
+        Self {
+            audit_log: self
+                .audit_log
+                .as_ref()
+                .map(|log| CallAuditLog::new(log.capacity(), log.max_params_len())),
+            usage: self.usage.as_ref().map(|usage| UsageTracker::new(usage.window())),
+            ..Default::default()
+        }
+    }
+
+    /// Number of retries made due to the given reason.
+    pub fn retries(&self, reason: RetryReason) -> u64 {
+        self.retries[reason.index()].load(Ordering::Relaxed)
+    }
+
+    /// Number of calls that failed without any further retries due to the given reason.
+    pub fn terminal_failures(&self, reason: RetryReason) -> u64 {
+        self.terminal_failures[reason.index()].load(Ordering::Relaxed)
+    }
+
+    /// Records of the most recent calls, from the oldest to the newest.
+    ///
+    /// Empty if the [audit log](ClientStats::with_audit_log) is not enabled.
+    pub fn audit_snapshot(&self) -> Vec<CallRecord> {
+        self.audit_log.as_ref().map(|log| log.snapshot()).unwrap_or_default()
+    }
+
+    /// Usage of the JSON RPC methods within the window.
+    ///
+    /// Empty if the [usage report](ClientStats::with_usage_report) is not enabled.
+    pub fn usage_report(&self) -> UsageReport {
+        self.usage.as_ref().map(|usage| usage.report()).unwrap_or_default()
+    }
+
+    pub(crate) fn record_retry(&self, reason: RetryReason) {
+        self.retries[reason.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_terminal_failure(&self, reason: RetryReason) {
+        self.terminal_failures[reason.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a finished call of the `method`, including all its retries, in the usage report and the audit log.
+    ///
+    /// The `record` is created only if the audit log is enabled.
+    pub(crate) fn record_call(&self, method: &str, call: CallUsage, record: impl FnOnce() -> CallRecord) {
+        if let Some(usage) = &self.usage {
+            usage.record(method, call);
+        }
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(record());
+        }
+    }
+
+    /// Records a finished HTTP request to the endpoint at `url`.
+    pub(crate) fn record_request(&self, url: &str, latency: Duration, success: bool) {
+        let mut period = self.period.lock().unwrap_or_else(|e| e.into_inner());
+        match period.endpoints.get_mut(url) {
+            Some(counters) => counters.record(latency, success),
+            None => {
+                let mut counters = EndpointCounters::default();
+                counters.record(latency, success);
+                period.endpoints.insert(url.to_owned(), counters);
+            }
+        }
+    }
+
+    /// Takes the statistics collected since the last reset and starts collecting anew.
+    pub fn snapshot_and_reset(&self) -> ClientStatsSnapshot {
+        let previous = {
+            let mut period = self.period.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(
+                &mut *period,
+                Period {
+                    started: Instant::now(),
+                    endpoints: HashMap::new(),
+                },
+            )
+        };
+
+        let mut endpoints = BTreeMap::new();
+        for (url, counters) in previous.endpoints {
+            // Distinct URLs may share the redacted form, so their statistics must be merged
+            match endpoints.entry(redact_url(&url)) {
+                std::collections::btree_map::Entry::Vacant(e) => {
+                    e.insert(counters);
+                }
+                std::collections::btree_map::Entry::Occupied(mut e) => {
+                    let merged: &mut EndpointCounters = e.get_mut();
+                    merged.successes += counters.successes;
+                    merged.failures += counters.failures;
+                    merged.latencies.extend(counters.latencies);
+                }
+            }
+        }
+
+        ClientStatsSnapshot {
+            period: previous.started.elapsed(),
+            endpoints: endpoints
+                .into_iter()
+                .map(|(endpoint, counters)| (endpoint, counters.into_stats()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_stats_should_compute_latency_percentiles_per_endpoint() {
+        let stats = ClientStats::default();
+        for ms in 1..=100 {
+            stats.record_request("http://primary:8545", Duration::from_millis(ms), ms % 10 != 0);
+        }
+        stats.record_request("http://archive:8545", Duration::from_millis(7), true);
+
+        let snapshot = stats.snapshot_and_reset();
+        assert_eq!(2, snapshot.endpoints.len());
+        assert_eq!(
+            EndpointStats {
+                successes: 90,
+                failures: 10,
+                latency_p50: Duration::from_millis(50),
+                latency_p95: Duration::from_millis(95),
+                latency_p99: Duration::from_millis(99),
+                latency_max: Duration::from_millis(100),
+            },
+            snapshot.endpoints["http://primary:8545"]
+        );
+        assert_eq!(Some(0.9), snapshot.endpoints["http://primary:8545"].success_rate());
+
+        let archive = &snapshot.endpoints["http://archive:8545"];
+        assert_eq!(1, archive.successes);
+        assert_eq!(Duration::from_millis(7), archive.latency_p50);
+        assert_eq!(Duration::from_millis(7), archive.latency_p99);
+    }
+
+    #[test]
+    fn test_client_stats_should_reset_after_snapshot() {
+        let stats = ClientStats::default();
+        stats.record_request("http://primary:8545", Duration::from_millis(10), true);
+        assert_eq!(1, stats.snapshot_and_reset().endpoints["http://primary:8545"].successes);
+
+        let snapshot = stats.snapshot_and_reset();
+        assert!(snapshot.endpoints.is_empty());
+
+        stats.record_request("http://primary:8545", Duration::from_millis(10), false);
+        let endpoint = &stats.snapshot_and_reset().endpoints["http://primary:8545"];
+        assert_eq!((0, 1), (endpoint.successes, endpoint.failures));
+        assert_eq!(Some(0.0), endpoint.success_rate());
+    }
+
+    #[test]
+    fn test_client_stats_should_redact_and_merge_endpoints() {
+        let stats = ClientStats::default();
+        stats.record_request("https://rpc.provider.io/v1/key1", Duration::from_millis(10), true);
+        stats.record_request("https://rpc.provider.io/v1/key2", Duration::from_millis(20), false);
+
+        let snapshot = stats.snapshot_and_reset();
+        assert_eq!(1, snapshot.endpoints.len());
+        let endpoint = &snapshot.endpoints["https://rpc.provider.io/***"];
+        assert_eq!((1, 1), (endpoint.successes, endpoint.failures));
+        assert_eq!(Duration::from_millis(20), endpoint.latency_max);
+    }
+
+    #[test]
+    fn test_client_stats_should_record_calls_only_in_the_enabled_parts() {
+        let call = CallUsage {
+            latency: Duration::from_millis(10),
+            retries: 1,
+            failed: false,
+            bytes: 42,
+        };
+        let record = || CallRecord {
+            method: "eth_blockNumber".into(),
+            endpoint: "http://primary:8545".into(),
+            params: String::new(),
+            params_truncated: false,
+            started_at: std::time::UNIX_EPOCH,
+            duration: call.latency,
+            retries: call.retries,
+            outcome: crate::audit::CallOutcome::Success,
+        };
+
+        let stats = ClientStats::default();
+        stats.record_call("eth_blockNumber", call, || panic!("the audit log is not enabled"));
+        assert!(stats.audit_snapshot().is_empty());
+        assert!(stats.usage_report().methods.is_empty());
+
+        let stats = ClientStats::default()
+            .with_audit_log(10, 100)
+            .with_usage_report(Duration::from_secs(60))
+            .empty_copy();
+        stats.record_call("eth_blockNumber", call, record);
+        stats.record_retry(RetryReason::Http429);
+        assert_eq!(vec![record()], stats.audit_snapshot());
+        assert_eq!(1, stats.usage_report().methods["eth_blockNumber"].calls);
+        assert_eq!(1, stats.retries(RetryReason::Http429));
+        assert_eq!(0, stats.terminal_failures(RetryReason::Http429));
+    }
+
+    #[test]
+    fn test_client_stats_should_bound_the_latency_samples() {
+        let stats = ClientStats::default();
+        for _ in 0..MAX_LATENCY_SAMPLES {
+            stats.record_request("http://primary:8545", Duration::from_secs(10), true);
+        }
+        for _ in 0..MAX_LATENCY_SAMPLES {
+            stats.record_request("http://primary:8545", Duration::from_millis(1), true);
+        }
+
+        let endpoint = &stats.snapshot_and_reset().endpoints["http://primary:8545"];
+        assert_eq!(2 * MAX_LATENCY_SAMPLES as u64, endpoint.successes);
+        assert_eq!(Duration::from_millis(1), endpoint.latency_max);
+    }
+}
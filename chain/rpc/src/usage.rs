@@ -0,0 +1,275 @@
+//! Usage statistics of the RPC calls made by the [JsonRpcProviderClient](crate::client::JsonRpcProviderClient).
+//!
+//! The [UsageTracker] counts the calls, failures, retries, latency and received bytes of each JSON RPC method
+//! over a sliding window. The window is a ring of 1-second buckets, so recording a call only touches
+//! the bucket of the current second, and the buckets are only summed up when a [UsageReport] is requested.
+//! The tracker is kept as a part of the [ClientStats](crate::stats::ClientStats) of the client.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default length of the sliding window of the [UsageTracker].
+pub const DEFAULT_USAGE_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Usage of a single JSON RPC method over the window of a [UsageReport].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MethodUsage {
+    /// Number of finished calls.
+    pub calls: u64,
+    /// Number of calls that failed after all their retries (or whose retries were cancelled).
+    pub failures: u64,
+    /// Number of retries made by the calls.
+    pub retries: u64,
+    /// Average duration of a call, including all its retries.
+    pub average_latency: Duration,
+    /// Number of bytes of the successful responses.
+    pub bytes: u64,
+    /// Average number of calls per second over the window.
+    pub calls_per_second: f64,
+}
+
+/// Usage of the JSON RPC methods over the last `window`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// Length of the window the usage was collected over.
+    pub window: Duration,
+    /// Usage of each method called within the window.
+    pub methods: BTreeMap<String, MethodUsage>,
+}
+
+/// Single finished call recorded by the [UsageTracker].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallUsage {
+    /// Duration of the call, including all its retries.
+    pub latency: Duration,
+    /// Number of retries made.
+    pub retries: u32,
+    /// Indicates whether the call failed.
+    pub failed: bool,
+    /// Number of bytes of the response, if the call succeeded.
+    pub bytes: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Counters {
+    calls: u64,
+    failures: u64,
+    retries: u64,
+    latency: Duration,
+    bytes: u64,
+}
+
+impl Counters {
+    fn add(&mut self, call: &CallUsage) {
+        self.calls += 1;
+        self.failures += call.failed as u64;
+        self.retries += call.retries as u64;
+        self.latency += call.latency;
+        self.bytes += call.bytes;
+    }
+
+    fn merge(&mut self, other: &Counters) {
+        self.calls += other.calls;
+        self.failures += other.failures;
+        self.retries += other.retries;
+        self.latency += other.latency;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Counters of the calls made within a single second.
+#[derive(Debug, Default)]
+struct Bucket {
+    second: u64,
+    methods: HashMap<String, Counters>,
+}
+
+/// Sliding window of the usage of the JSON RPC methods.
+#[derive(Debug)]
+pub struct UsageTracker {
+    buckets: Mutex<Vec<Bucket>>,
+    started: Instant,
+}
+
+impl UsageTracker {
+    /// Creates a tracker over the given `window`, rounded up to whole seconds.
+    pub fn new(window: Duration) -> Self {
+        let seconds = window.as_secs() + (window.subsec_nanos() > 0) as u64;
+        Self {
+            buckets: Mutex::new((0..seconds.max(1)).map(|_| Bucket::default()).collect()),
+            started: Instant::now(),
+        }
+    }
+
+    /// Length of the window.
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.buckets.lock().unwrap_or_else(|e| e.into_inner()).len() as u64)
+    }
+
+    /// Records the finished call of the `method`.
+    pub fn record(&self, method: &str, call: CallUsage) {
+        self.record_at(Instant::now(), method, call)
+    }
+
+    /// Usage of the methods called within the window.
+    pub fn report(&self) -> UsageReport {
+        self.report_at(Instant::now())
+    }
+
+    fn second(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.started).as_secs()
+    }
+
+    fn record_at(&self, now: Instant, method: &str, call: CallUsage) {
+        let second = self.second(now);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let len = buckets.len() as u64;
+        let bucket = &mut buckets[(second % len) as usize];
+
+        // The bucket still holds the counters of a second that has fallen out of the window
+        if bucket.second != second {
+            bucket.second = second;
+            bucket.methods.clear();
+        }
+
+        match bucket.methods.get_mut(method) {
+            Some(counters) => counters.add(&call),
+            None => {
+                let mut counters = Counters::default();
+                counters.add(&call);
+                bucket.methods.insert(method.to_owned(), counters);
+            }
+        }
+    }
+
+    fn report_at(&self, now: Instant) -> UsageReport {
+        let second = self.second(now);
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let window = buckets.len() as u64;
+
+        let mut totals = BTreeMap::<String, Counters>::new();
+        for bucket in buckets
+            .iter()
+            .filter(|b| b.second <= second && second - b.second < window)
+        {
+            for (method, counters) in &bucket.methods {
+                totals.entry(method.clone()).or_default().merge(counters);
+            }
+        }
+        drop(buckets);
+
+        UsageReport {
+            window: Duration::from_secs(window),
+            methods: totals
+                .into_iter()
+                .map(|(method, c)| {
+                    let usage = MethodUsage {
+                        calls: c.calls,
+                        failures: c.failures,
+                        retries: c.retries,
+                        average_latency: c.latency / c.calls.max(1) as u32,
+                        bytes: c.bytes,
+                        calls_per_second: c.calls as f64 / window as f64,
+                    };
+                    (method, usage)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_USAGE_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(latency_ms: u64, retries: u32, failed: bool, bytes: u64) -> CallUsage {
+        CallUsage {
+            latency: Duration::from_millis(latency_ms),
+            retries,
+            failed,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_usage_tracker_should_aggregate_calls_within_the_window() {
+        let tracker = UsageTracker::new(Duration::from_secs(10));
+        let start = tracker.started;
+
+        tracker.record_at(start, "eth_blockNumber", call(10, 0, false, 100));
+        tracker.record_at(
+            start + Duration::from_millis(999),
+            "eth_blockNumber",
+            call(30, 1, false, 100),
+        );
+        // next bucket
+        tracker.record_at(start + Duration::from_secs(1), "eth_blockNumber", call(20, 2, true, 0));
+        tracker.record_at(start + Duration::from_secs(5), "eth_getLogs", call(100, 0, false, 5000));
+
+        let report = tracker.report_at(start + Duration::from_secs(9));
+        assert_eq!(Duration::from_secs(10), report.window);
+        assert_eq!(
+            MethodUsage {
+                calls: 3,
+                failures: 1,
+                retries: 3,
+                average_latency: Duration::from_millis(20),
+                bytes: 200,
+                calls_per_second: 0.3,
+            },
+            report.methods["eth_blockNumber"]
+        );
+        assert_eq!(1, report.methods["eth_getLogs"].calls);
+        assert_eq!(5000, report.methods["eth_getLogs"].bytes);
+    }
+
+    #[test]
+    fn test_usage_tracker_should_drop_calls_falling_out_of_the_window() {
+        let tracker = UsageTracker::new(Duration::from_secs(3));
+        let start = tracker.started;
+
+        tracker.record_at(start, "eth_call", call(10, 0, false, 10));
+        tracker.record_at(start + Duration::from_millis(1500), "eth_call", call(10, 0, false, 10));
+        tracker.record_at(start + Duration::from_millis(2500), "eth_call", call(10, 0, false, 10));
+
+        assert_eq!(
+            3,
+            tracker.report_at(start + Duration::from_millis(2999)).methods["eth_call"].calls
+        );
+        assert_eq!(
+            2,
+            tracker.report_at(start + Duration::from_secs(3)).methods["eth_call"].calls
+        );
+        assert_eq!(
+            1,
+            tracker.report_at(start + Duration::from_secs(4)).methods["eth_call"].calls
+        );
+        assert!(tracker.report_at(start + Duration::from_secs(5)).methods.is_empty());
+
+        // The bucket of the second 0 is reused by the second 3
+        tracker.record_at(start + Duration::from_secs(3), "eth_getBalance", call(10, 0, false, 10));
+        let report = tracker.report_at(start + Duration::from_secs(3));
+        assert_eq!(2, report.methods["eth_call"].calls);
+        assert_eq!(1, report.methods["eth_getBalance"].calls);
+    }
+
+    #[test]
+    fn test_usage_report_should_serialize() -> anyhow::Result<()> {
+        let tracker = UsageTracker::new(Duration::from_secs(60));
+        tracker.record("eth_chainId", call(5, 0, false, 42));
+
+        let report = tracker.report();
+        let json = serde_json::to_string(&report)?;
+        assert_eq!(report, serde_json::from_str::<UsageReport>(&json)?);
+        assert_eq!(Duration::from_secs(60), tracker.window());
+
+        Ok(())
+    }
+}
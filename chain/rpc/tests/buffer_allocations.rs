@@ -0,0 +1,100 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use hopr_chain_rpc::buffer::BufferPool;
+use serde::Serialize;
+
+/// Counts the allocations made by the current thread, so that tests running in parallel do not interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> u64 {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    id: u64,
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: T,
+}
+
+fn request<T>(params: T) -> Request<'static, T> {
+    Request {
+        id: 1,
+        jsonrpc: "2.0",
+        method: "eth_getLogs",
+        params,
+    }
+}
+
+fn params() -> Box<serde_json::value::RawValue> {
+    let filters = (0..100)
+        .map(|i| {
+            serde_json::json!({
+                "fromBlock": format!("0x{:x}", 1000 * i),
+                "toBlock": format!("0x{:x}", 1000 * (i + 1)),
+                "address": format!("0x{i:040x}"),
+                "topics": (0..4).map(|t| format!("0x{:064x}", i * 4 + t)).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::value::to_raw_value(&filters).expect("params must be serializable")
+}
+
+#[test]
+fn serializing_a_request_into_a_fresh_string_should_allocate() {
+    let params = params();
+
+    let count = allocations(|| {
+        let _ = serde_json::to_string(&request(&params)).expect("request must be serializable");
+    });
+
+    assert!(count > 0, "allocations must be counted");
+}
+
+#[test]
+fn serializing_a_request_into_a_warm_pool_should_not_allocate() {
+    let pool = BufferPool::new(1);
+    let params = params();
+
+    let expected = pool
+        .serialize(&request(&params))
+        .expect("request must be serializable")
+        .to_vec();
+    assert_eq!(1, pool.available(), "buffer must be returned into the pool");
+
+    let count = allocations(|| {
+        let buffer = pool.serialize(&request(&params)).expect("request must be serializable");
+        assert_eq!(expected.as_slice(), buffer.as_slice());
+    });
+
+    assert_eq!(0, count, "serialization into a pooled buffer must not allocate");
+}
@@ -0,0 +1,167 @@
+//! Source of time for the timers, timeouts and periodic processes.
+//!
+//! The [`RealClock`] is backed by the runtime selected by the feature flags, while the [`MockClock`]
+//! advances only virtually, which allows deterministic testing of the time-dependent logic.
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Future completing once the [`Clock`] has advanced by the requested duration.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Abstraction over the passage of time.
+pub trait Clock: Clone + Send + Sync + 'static {
+    /// Current instant of this clock.
+    fn now(&self) -> Instant;
+
+    /// Creates a future completing once this clock has advanced by the given `duration`.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// Wall clock backed by the sleep of the selected runtime.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(crate::prelude::sleep(duration))
+    }
+}
+
+#[derive(Debug)]
+struct MockClockState {
+    now: Instant,
+    next_id: u64,
+    sleepers: HashMap<u64, (Instant, Option<Waker>)>,
+}
+
+/// Virtual clock which advances only when explicitly told so using [`MockClock::advance`].
+///
+/// The clones of the clock share the same virtual time.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    state: Arc<Mutex<MockClockState>>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockClockState {
+                now: Instant::now(),
+                next_id: 0,
+                sleepers: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl MockClock {
+    /// Advances the virtual time, waking up all the sleeps that have elapsed.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.now += duration;
+
+        let now = state.now;
+        state
+            .sleepers
+            .values_mut()
+            .filter(|(deadline, _)| *deadline <= now)
+            .filter_map(|(_, waker)| waker.take())
+            .for_each(Waker::wake);
+    }
+
+    /// Number of sleeps created by this clock that have not been dropped yet.
+    pub fn pending_sleeps(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).sleepers.len()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).now
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let id = state.next_id;
+        state.next_id += 1;
+        let deadline = state.now + duration;
+        state.sleepers.insert(id, (deadline, None));
+
+        Box::pin(MockSleep {
+            id,
+            deadline,
+            state: self.state.clone(),
+        })
+    }
+}
+
+struct MockSleep {
+    id: u64,
+    deadline: Instant,
+    state: Arc<Mutex<MockClockState>>,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.now >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        if let Some((_, waker)) = state.sleepers.get_mut(&self.id) {
+            *waker = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for MockSleep {
+    fn drop(&mut self) {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .sleepers
+            .remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll_once(sleep: &mut Sleep) -> Poll<()> {
+        sleep.as_mut().poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn mock_clock_should_complete_sleeps_only_after_advancing() {
+        let clock = MockClock::default();
+        let start = clock.now();
+
+        let mut short = clock.sleep(Duration::from_secs(1));
+        let mut long = clock.sleep(Duration::from_secs(10));
+        assert_eq!(2, clock.pending_sleeps());
+        assert!(poll_once(&mut short).is_pending());
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(Duration::from_secs(5), clock.now() - start);
+        assert!(poll_once(&mut short).is_ready());
+        assert!(poll_once(&mut long).is_pending());
+
+        drop(short);
+        assert_eq!(1, clock.pending_sleeps());
+
+        clock.advance(Duration::from_secs(5));
+        assert!(poll_once(&mut long).is_ready());
+    }
+}
@@ -2,6 +2,9 @@
 //! runtime.
 //!
 //!
+pub mod clock;
+pub mod retry;
+
 #[cfg(feature = "runtime-async-std")]
 pub mod prelude {
     pub use async_std::future::timeout as timeout_fut;
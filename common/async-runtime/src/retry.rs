@@ -0,0 +1,343 @@
+//! Generic retrying of fallible asynchronous operations driven by a [RetryPolicy].
+//!
+//! The JSON RPC client uses this to retry the RPC requests,
+//! but any other operation (e.g. a DB or transport call) can be retried the same way.
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::prelude::sleep;
+
+use RetryAction::{NoRetry, RetryAfter};
+
+/// Indicates what retry action should be taken, as result of a `RetryPolicy` implementation.
+pub enum RetryAction {
+    /// Request should not be retried
+    NoRetry,
+    /// Request should be retried after the given duration has elapsed.
+    RetryAfter(Duration),
+}
+
+/// Simple retry policy trait
+pub trait RetryPolicy<E> {
+    /// Indicates whether a client should retry the request given the last error, current number of retries
+    /// of this request and the number of other requests being retried by the client at this time.
+    fn is_retryable_error(&self, _err: &E, _retry_number: u32, _retry_queue_size: u32) -> RetryAction {
+        NoRetry
+    }
+
+    /// Maximum average number of retries per second and the maximum number of retries at once, which are
+    /// allowed to all the operations sharing the same [RetryBudget] together.
+    ///
+    /// By default, the retries are not limited.
+    fn retry_rate(&self) -> Option<(f64, u32)> {
+        None
+    }
+}
+
+/// Performs no retries.
+#[derive(Clone, Debug)]
+pub struct ZeroRetryPolicy<E>(PhantomData<E>);
+
+impl<E> Default for ZeroRetryPolicy<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E> RetryPolicy<E> for ZeroRetryPolicy<E> {}
+
+/// Error of an operation driven by [retry_with_hooks].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetryError<E> {
+    /// The policy did not allow any more retries after the last `error`.
+    Exhausted {
+        /// Error of the last attempt.
+        error: E,
+        /// Total number of failed attempts.
+        failures: u32,
+    },
+    /// The retries were abandoned by [RetryHooks::backoff] after the last `error`.
+    Abandoned {
+        /// Error of the last attempt.
+        error: E,
+        /// Total number of failed attempts.
+        failures: u32,
+    },
+}
+
+impl<E> RetryError<E> {
+    /// Error of the last attempt.
+    pub fn into_error(self) -> E {
+        match self {
+            RetryError::Exhausted { error, .. } | RetryError::Abandoned { error, .. } => error,
+        }
+    }
+
+    /// Total number of failed attempts.
+    pub fn failures(&self) -> u32 {
+        match self {
+            RetryError::Exhausted { failures, .. } | RetryError::Abandoned { failures, .. } => *failures,
+        }
+    }
+}
+
+/// Hooks observing and customizing the retries in [retry_with_hooks].
+///
+/// All the methods have default implementations, which do nothing and wait using the sleep
+/// of the selected async runtime.
+pub trait RetryHooks<E> {
+    /// Number of other operations currently being retried, which is passed to the [RetryPolicy].
+    fn retry_queue_size(&self) -> u32 {
+        0
+    }
+
+    /// Called after each failed attempt with its error and the total number of failed attempts so far.
+    fn on_failure(&self, _err: &E, _failures: u32) {}
+
+    /// Called once the [RetryPolicy] decides to retry after the given `backoff`.
+    fn on_retry(&self, _err: &E, _failures: u32, _backoff: Duration) {}
+
+    /// Waits for the `backoff` before the next attempt.
+    ///
+    /// Returns `false` if the retries should be abandoned instead.
+    fn backoff(&self, backoff: Duration) -> BoxFuture<'_, bool> {
+        sleep(backoff).map(|_| true).boxed()
+    }
+}
+
+/// [RetryHooks] with all the default implementations.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DefaultRetryHooks;
+
+impl<E> RetryHooks<E> for DefaultRetryHooks {}
+
+/// Performs the operation `f` until it succeeds or the `policy` no longer allows retrying it.
+///
+/// Returns the result of the first successful attempt or the error of the last attempt.
+pub async fn retry_with_policy<F, Fut, T, E, P>(f: F, policy: &P) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: RetryPolicy<E> + ?Sized,
+{
+    retry_with_hooks(f, policy, &DefaultRetryHooks)
+        .await
+        .map(|(value, _)| value)
+        .map_err(RetryError::into_error)
+}
+
+/// Same as [retry_with_policy], but the retries are observed and customized by the given `hooks`.
+///
+/// On success, returns the result together with the number of failed attempts that preceded it.
+pub async fn retry_with_hooks<F, Fut, T, E, P, H>(mut f: F, policy: &P, hooks: &H) -> Result<(T, u32), RetryError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: RetryPolicy<E> + ?Sized,
+    H: RetryHooks<E> + ?Sized,
+{
+    let mut failures = 0;
+    loop {
+        let error = match f().await {
+            Ok(value) => return Ok((value, failures)),
+            Err(error) => error,
+        };
+
+        failures += 1;
+        hooks.on_failure(&error, failures);
+
+        match policy.is_retryable_error(&error, failures, hooks.retry_queue_size()) {
+            NoRetry => return Err(RetryError::Exhausted { error, failures }),
+            RetryAfter(backoff) => {
+                hooks.on_retry(&error, failures, backoff);
+                if !hooks.backoff(backoff).await {
+                    return Err(RetryError::Abandoned { error, failures });
+                }
+            }
+        }
+    }
+}
+
+/// Token bucket limiting the rate of the retries made by all the operations sharing it.
+///
+/// The bucket holds up to `burst` tokens and is refilled at `per_second` tokens per second,
+/// each retry takes a single token. All the clones of the budget share the same bucket.
+#[derive(Debug, Clone, Default)]
+pub struct RetryBudget {
+    // Remaining tokens and the time of the last refill, initialized on the first use
+    bucket: Arc<Mutex<Option<(f64, Instant)>>>,
+}
+
+impl RetryBudget {
+    /// Takes a single token from the bucket if there is one.
+    ///
+    /// Returns `false` if the budget is exhausted.
+    pub fn try_acquire(&self, per_second: f64, burst: u32) -> bool {
+        self.try_acquire_at(Instant::now(), per_second, burst)
+    }
+
+    fn try_acquire_at(&self, now: Instant, per_second: f64, burst: u32) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last_refill) = bucket.get_or_insert((burst as f64, now));
+
+        let elapsed = now.saturating_duration_since(*last_refill).as_secs_f64();
+        *tokens = (*tokens + elapsed * per_second.max(0.0)).min(burst as f64);
+        *last_refill = now.max(*last_refill);
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    /// Retries up to `max_retries` times with a fixed backoff.
+    struct FixedRetryPolicy {
+        max_retries: u32,
+        backoff: Duration,
+    }
+
+    impl RetryPolicy<String> for FixedRetryPolicy {
+        fn is_retryable_error(&self, _err: &String, retry_number: u32, _retry_queue_size: u32) -> RetryAction {
+            if retry_number > self.max_retries {
+                NoRetry
+            } else {
+                RetryAfter(self.backoff)
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        abandon: bool,
+        failures: Mutex<Vec<(String, u32)>>,
+        backoffs: Mutex<Vec<Duration>>,
+    }
+
+    impl RetryHooks<String> for RecordingHooks {
+        fn on_failure(&self, err: &String, failures: u32) {
+            self.failures.lock().unwrap().push((err.clone(), failures));
+        }
+
+        fn backoff(&self, backoff: Duration) -> BoxFuture<'_, bool> {
+            self.backoffs.lock().unwrap().push(backoff);
+            futures::future::ready(!self.abandon).boxed()
+        }
+    }
+
+    fn fail_times(attempts: &AtomicU32, times: u32) -> Result<u32, String> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempt <= times {
+            Err(format!("attempt {attempt} failed"))
+        } else {
+            Ok(attempt)
+        }
+    }
+
+    #[async_std::test]
+    async fn retry_with_policy_should_retry_until_success() {
+        let attempts = AtomicU32::new(0);
+        let policy = FixedRetryPolicy {
+            max_retries: 5,
+            backoff: Duration::from_millis(1),
+        };
+
+        let result = retry_with_policy(|| async { fail_times(&attempts, 2) }, &policy).await;
+
+        assert_eq!(Ok(3), result);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn retry_with_policy_should_return_last_error_when_retries_are_exhausted() {
+        let attempts = AtomicU32::new(0);
+        let policy = FixedRetryPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+
+        let result = retry_with_policy(|| async { fail_times(&attempts, 10) }, &policy).await;
+
+        assert_eq!(Err("attempt 3 failed".to_string()), result);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn retry_with_hooks_should_report_failures_and_backoffs() {
+        let attempts = AtomicU32::new(0);
+        let hooks = RecordingHooks::default();
+        let policy = FixedRetryPolicy {
+            max_retries: 5,
+            backoff: Duration::from_secs(1),
+        };
+
+        let result = retry_with_hooks(|| async { fail_times(&attempts, 2) }, &policy, &hooks).await;
+
+        assert_eq!(Ok((3, 2)), result);
+        assert_eq!(
+            vec![("attempt 1 failed".to_string(), 1), ("attempt 2 failed".to_string(), 2)],
+            *hooks.failures.lock().unwrap()
+        );
+        assert_eq!(vec![Duration::from_secs(1); 2], *hooks.backoffs.lock().unwrap());
+    }
+
+    #[async_std::test]
+    async fn retry_with_hooks_should_stop_when_backoff_is_abandoned() {
+        let attempts = AtomicU32::new(0);
+        let hooks = RecordingHooks {
+            abandon: true,
+            ..Default::default()
+        };
+        let policy = FixedRetryPolicy {
+            max_retries: 5,
+            backoff: Duration::from_secs(1),
+        };
+
+        let result = retry_with_hooks(|| async { fail_times(&attempts, 10) }, &policy, &hooks).await;
+
+        assert_eq!(
+            Err(RetryError::Abandoned {
+                error: "attempt 1 failed".to_string(),
+                failures: 1
+            }),
+            result
+        );
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn retry_budget_should_allow_bursts_and_refill_over_time() {
+        let budget = RetryBudget::default();
+        let shared = budget.clone();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(budget.try_acquire_at(start, 2.0, 3));
+        }
+        assert!(!shared.try_acquire_at(start, 2.0, 3), "clones must share the budget");
+
+        // 2 tokens per second
+        assert!(!budget.try_acquire_at(start + Duration::from_millis(250), 2.0, 3));
+        assert!(shared.try_acquire_at(start + Duration::from_millis(500), 2.0, 3));
+        assert!(!budget.try_acquire_at(start + Duration::from_millis(500), 2.0, 3));
+
+        // The refill never exceeds the burst
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(budget.try_acquire_at(later, 2.0, 3));
+        }
+        assert!(!budget.try_acquire_at(later, 2.0, 3));
+    }
+}
@@ -249,6 +249,125 @@ impl TagBloomFilter {
     }
 }
 
+fn encode_zero_run(out: &mut Vec<u8>, mut len: u64) {
+    out.push(0);
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn decode_zero_run<'a>(bytes: &mut impl Iterator<Item = &'a u8>) -> Option<u64> {
+    let mut len = 0_u64;
+    for shift in (0..u64::BITS).step_by(7) {
+        let byte = *bytes.next()?;
+        len |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(len);
+        }
+    }
+    None
+}
+
+impl TagBloomFilter {
+    /// Magic bytes at the start of the compact encoding.
+    pub const COMPACT_MAGIC: [u8; 4] = *b"HTBF";
+    /// Current version of the compact encoding.
+    pub const COMPACT_VERSION: u8 = 1;
+
+    const COMPACT_HEADER_LEN: usize = Self::COMPACT_MAGIC.len() + 1 + 2 * size_of::<u64>();
+
+    // Upper bound of the decoded bitmap size, so that corrupted data cannot exhaust the memory.
+    const MAX_COMPACT_BITMAP_LEN: usize = 1 << 30;
+
+    /// Indicates whether the given data start with the header of the compact encoding.
+    pub fn is_compact_encoding(data: &[u8]) -> bool {
+        data.starts_with(&Self::COMPACT_MAGIC)
+    }
+
+    /// Encodes the filter using the compact binary encoding.
+    ///
+    /// The encoding starts with a header of [`TagBloomFilter::COMPACT_MAGIC`], a version byte and
+    /// the item count and capacity (both as little-endian `u64`). The header is followed by the
+    /// serialized bitmap of the filter, in which each run of zero bytes is replaced by a single zero byte
+    /// followed by the LEB128-encoded length of the run. The filter is sparse most of its lifetime,
+    /// so this is much smaller than the bitmap itself.
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let bitmap = self.bloom.0.to_bytes();
+
+        let mut ret = Vec::with_capacity(Self::COMPACT_HEADER_LEN + bitmap.len() / 8);
+        ret.extend_from_slice(&Self::COMPACT_MAGIC);
+        ret.push(Self::COMPACT_VERSION);
+        ret.extend_from_slice(&(self.count as u64).to_le_bytes());
+        ret.extend_from_slice(&(self.capacity as u64).to_le_bytes());
+
+        let mut zeros = 0_u64;
+        for byte in bitmap {
+            if byte == 0 {
+                zeros += 1;
+                continue;
+            }
+            if zeros > 0 {
+                encode_zero_run(&mut ret, zeros);
+                zeros = 0;
+            }
+            ret.push(byte);
+        }
+        if zeros > 0 {
+            encode_zero_run(&mut ret, zeros);
+        }
+
+        ret
+    }
+
+    /// Decodes the filter from the compact binary encoding.
+    pub fn from_compact_bytes(data: &[u8]) -> Result<Self> {
+        let parse_err = |what: &str| CoreTypesError::ParseError(format!("TagBloomFilter: {what}"));
+
+        if data.len() < Self::COMPACT_HEADER_LEN || !Self::is_compact_encoding(data) {
+            return Err(parse_err("invalid header"));
+        }
+
+        let version = data[Self::COMPACT_MAGIC.len()];
+        if version != Self::COMPACT_VERSION {
+            return Err(parse_err(&format!("unsupported version {version}")));
+        }
+
+        let (count, rest) = data[Self::COMPACT_MAGIC.len() + 1..].split_at(size_of::<u64>());
+        let (capacity, rest) = rest.split_at(size_of::<u64>());
+        let count = u64::from_le_bytes(count.try_into().map_err(|_| parse_err("count"))?) as usize;
+        let capacity = u64::from_le_bytes(capacity.try_into().map_err(|_| parse_err("capacity"))?) as usize;
+
+        let mut bitmap = Vec::new();
+        let mut bytes = rest.iter();
+        while let Some(byte) = bytes.next() {
+            if *byte != 0 {
+                bitmap.push(*byte);
+                continue;
+            }
+
+            let run = decode_zero_run(&mut bytes).ok_or_else(|| parse_err("truncated zero run"))?;
+            let new_len = usize::try_from(run)
+                .ok()
+                .and_then(|run| bitmap.len().checked_add(run))
+                .filter(|len| *len <= Self::MAX_COMPACT_BITMAP_LEN)
+                .ok_or_else(|| parse_err("zero run too long"))?;
+            bitmap.resize(new_len, 0);
+        }
+
+        Ok(Self {
+            bloom: SerializableBloomWrapper(Bloom::from_bytes(bitmap).map_err(|e| parse_err(&e.to_string()))?),
+            count,
+            capacity,
+        })
+    }
+}
+
 impl Default for TagBloomFilter {
     fn default() -> Self {
         Self::with_capacity(Self::DEFAULT_MAX_ITEMS)
@@ -389,6 +508,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn tag_bloom_filter_compact_encoding_should_preserve_membership() -> anyhow::Result<()> {
+        let mut filter1 = TagBloomFilter::default();
+        let items = (0..10_000)
+            .map(|_| {
+                let mut ret = random_bytes::<PACKET_TAG_LENGTH>();
+                ret[0] = 0xaa; // ensure it is not the zero tag
+                ret
+            })
+            .collect::<Vec<_>>();
+        items.iter().for_each(|item| filter1.set(item));
+
+        let encoded = filter1.to_compact_bytes();
+        assert!(TagBloomFilter::is_compact_encoding(&encoded));
+        assert!(
+            encoded.len() < filter1.bloom.0.to_bytes().len() / 4,
+            "compact encoding of a sparse filter must be much smaller than its bitmap"
+        );
+
+        let filter2 = TagBloomFilter::from_compact_bytes(&encoded)?;
+        assert_eq!(filter1.count(), filter2.count());
+        assert_eq!(filter1.capacity(), filter2.capacity());
+        assert!(
+            items.iter().all(|item| filter2.check(item)),
+            "all items must be present"
+        );
+        assert!(!filter2.check(&ZEROS_TAG), "decoded bf must not contain zero tag");
+        assert_eq!(filter1.bloom.0.to_bytes(), filter2.bloom.0.to_bytes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tag_bloom_filter_compact_encoding_should_reject_invalid_data() {
+        let encoded = TagBloomFilter::with_capacity(1000).to_compact_bytes();
+
+        assert!(TagBloomFilter::from_compact_bytes(&encoded[..encoded.len() - 1]).is_err());
+        assert!(TagBloomFilter::from_compact_bytes(&encoded[1..]).is_err());
+
+        let mut unsupported = encoded.clone();
+        unsupported[TagBloomFilter::COMPACT_MAGIC.len()] = TagBloomFilter::COMPACT_VERSION + 1;
+        assert!(TagBloomFilter::from_compact_bytes(&unsupported).is_err());
+    }
+
     #[test]
     fn tag_bloom_filter_count() {
         let mut filter = TagBloomFilter::default();
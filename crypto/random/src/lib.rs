@@ -13,14 +13,9 @@ pub use rand::{Rng, RngCore};
 /// This is the last positive 64-bit value in the two's complement representation.
 pub const MAX_RANDOM_INTEGER: u64 = 9007199254740991;
 
-/// Gets the default cryptographically secure random number generator.
-///
-/// **WARNING** On debug builds with the ` fixed_rng ` feature enabled during
-/// compilation, this function will return an RNG with a fixed seed, which is *NOT SECURE*!
-/// This is reserved for deterministic testing.
 #[cfg(all(debug_assertions, feature = "fixed_rng"))]
 #[inline]
-pub fn rng() -> impl RngCore + CryptoRng {
+fn system_rng() -> impl RngCore + CryptoRng {
     use rand::SeedableRng;
     rand::rngs::StdRng::from_seed([
         0x5f, 0x57, 0xce, 0x2a, 0x84, 0x14, 0x7e, 0x88, 0x43, 0x56, 0x44, 0x56, 0x7f, 0x90, 0x4f, 0xb2, 0x04, 0x6b,
@@ -28,13 +23,100 @@ pub fn rng() -> impl RngCore + CryptoRng {
     ])
 }
 
-/// Gets the default cryptographically secure random number generator.
 #[cfg(any(not(debug_assertions), not(feature = "fixed_rng")))]
 #[inline]
-pub fn rng() -> impl RngCore + CryptoRng {
+fn system_rng() -> impl RngCore + CryptoRng {
     rand::rngs::OsRng
 }
 
+/// Gets the default cryptographically secure random number generator.
+///
+/// **WARNING** On debug builds with the ` fixed_rng ` feature enabled during
+/// compilation, this function will return an RNG with a fixed seed, which is *NOT SECURE*!
+/// This is reserved for deterministic testing.
+#[cfg(not(feature = "seedable_rng"))]
+#[inline]
+pub fn rng() -> impl RngCore + CryptoRng {
+    system_rng()
+}
+
+/// Gets the default cryptographically secure random number generator.
+///
+/// **WARNING** On debug builds with the ` fixed_rng ` feature enabled during
+/// compilation, this function will return an RNG with a fixed seed, which is *NOT SECURE*!
+/// Inside [`with_seeded_rng`], it returns an RNG with the given seed, which is *NOT SECURE* either.
+/// This is reserved for deterministic testing.
+#[cfg(feature = "seedable_rng")]
+#[inline]
+pub fn rng() -> impl RngCore + CryptoRng {
+    seeded::ScopedRng
+}
+
+#[cfg(feature = "seedable_rng")]
+pub use seeded::with_seeded_rng;
+
+#[cfg(feature = "seedable_rng")]
+mod seeded {
+    use rand::rngs::StdRng;
+    use rand::{CryptoRng, RngCore, SeedableRng};
+    use std::cell::RefCell;
+
+    thread_local! {
+        static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+    }
+
+    /// Runs the `f` with all the randomness of the [`rng`](super::rng) on the current thread
+    /// drawn from a single RNG with the given `seed`.
+    ///
+    /// **WARNING** The randomness is fully determined by the `seed`, which is *NOT SECURE*!
+    /// This is reserved for deterministic testing, such as producing test vectors.
+    pub fn with_seeded_rng<T>(seed: [u8; 32], f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<StdRng>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                SEEDED_RNG.with(|rng| *rng.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(SEEDED_RNG.with(|rng| rng.replace(Some(StdRng::from_seed(seed)))));
+        f()
+    }
+
+    /// Draws from the seeded RNG of the current thread if there is any, otherwise from the system RNG.
+    pub(super) struct ScopedRng;
+
+    impl ScopedRng {
+        fn draw<T>(&mut self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+            SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+                Some(seeded) => f(seeded),
+                None => f(&mut super::system_rng()),
+            })
+        }
+    }
+
+    impl RngCore for ScopedRng {
+        fn next_u32(&mut self) -> u32 {
+            self.draw(|rng| rng.next_u32())
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.draw(|rng| rng.next_u64())
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.draw(|rng| rng.fill_bytes(dest))
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.draw(|rng| rng.try_fill_bytes(dest))
+        }
+    }
+
+    impl CryptoRng for ScopedRng {}
+}
+
 /// Returns `true` if the build is using an **insecure** RNG with a fixed seed.
 ///
 /// See also [`rng`].
@@ -123,4 +205,19 @@ mod tests {
         assert_eq!(0, buffer[1]);
         assert_eq!(0, buffer[9]);
     }
+
+    #[cfg(feature = "seedable_rng")]
+    #[test]
+    fn test_seeded_rng_should_be_deterministic_only_within_its_scope() {
+        let seeded = || with_seeded_rng([7u8; 32], || (random_bytes::<32>(), random_bytes::<32>()));
+
+        let (first, second) = seeded();
+        assert_eq!((first, second), seeded(), "same seed must produce the same randomness");
+        assert_ne!(first, second, "randomness must not repeat within the scope");
+        assert_ne!(
+            first,
+            random_bytes::<32>(),
+            "seed must not be used outside of the scope"
+        );
+    }
 }
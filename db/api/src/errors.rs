@@ -28,8 +28,22 @@ pub enum DbError {
     #[error("ticket validation error for {:?}: {}", 0.0, 0.1)]
     TicketValidationError(Box<(Ticket, String)>),
 
+    #[error("unexpected acknowledgement: {0}")]
+    UnexpectedAcknowledgement(String),
+
     #[error("logical error: {0}")]
     LogicalError(String),
+
+    #[error("transient DB error: {0}")]
+    Transient(String),
+}
+
+impl DbError {
+    /// Indicates whether the error is likely to go away when the operation is retried,
+    /// such as when the database is temporarily locked or no connection is available.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DbError::Transient(_))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, DbError>;
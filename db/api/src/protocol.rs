@@ -56,7 +56,10 @@ pub trait HoprDbProtocolOperations {
 pub enum AckResult {
     Sender(Acknowledgement),
     RelayerWinning(AcknowledgedTicket),
-    RelayerLosing,
+    /// Losing ticket in the channel with the given id.
+    RelayerLosing(Hash),
+    /// Acknowledgement that has already been processed recently (e.g. due to a retransmission).
+    Duplicate,
 }
 
 impl Debug for AckResult {
@@ -64,7 +67,8 @@ impl Debug for AckResult {
         match self {
             Self::Sender(_) => f.debug_tuple("Sender").finish(),
             Self::RelayerWinning(_) => f.debug_tuple("RelayerWinning").finish(),
-            Self::RelayerLosing => write!(f, "RelayerLosing"),
+            Self::RelayerLosing(channel_id) => f.debug_tuple("RelayerLosing").field(channel_id).finish(),
+            Self::Duplicate => f.debug_tuple("Duplicate").finish(),
         }
     }
 }
@@ -86,13 +90,18 @@ pub enum TransportPacketWithChainData {
         previous_hop: OffchainPublicKey,
         next_hop: OffchainPublicKey,
         data: Box<[u8]>,
-        ack: Acknowledgement,
+        /// Key share to be acknowledged to the previous hop, the acknowledgement is signed by the caller.
+        ack_key: HalfKey,
+        /// Challenge solved by the acknowledgement of the next hop.
+        ack_challenge: HalfKeyChallenge,
     },
     /// Packet that is being sent out by us
     Outgoing {
         next_hop: OffchainPublicKey,
         ack_challenge: HalfKeyChallenge,
         data: Box<[u8]>,
+        /// Value of the ticket attached to the packet for the next hop.
+        ticket_value: Balance,
     },
 }
 
@@ -108,7 +117,7 @@ impl From<ResolvedAcknowledgement> for AckResult {
         match value {
             ResolvedAcknowledgement::Sending(ack) => AckResult::Sender(ack),
             ResolvedAcknowledgement::RelayingWin(ack_ticket) => AckResult::RelayerWinning(ack_ticket),
-            ResolvedAcknowledgement::RelayingLoss(_) => AckResult::RelayerLosing,
+            ResolvedAcknowledgement::RelayingLoss(channel_id) => AckResult::RelayerLosing(channel_id),
         }
     }
 }
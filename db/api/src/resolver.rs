@@ -12,4 +12,7 @@ pub trait HoprDbResolverOperations {
 
     /// Tries to resolve on-chain public key given the off-chain public key
     async fn resolve_chain_key(&self, offchain_key: &OffchainPublicKey) -> Result<Option<Address>>;
+
+    /// Indicates whether the account with the given off-chain public key has announced itself on-chain
+    async fn is_announced(&self, offchain_key: &OffchainPublicKey) -> Result<bool>;
 }
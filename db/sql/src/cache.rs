@@ -104,11 +104,20 @@ pub struct HoprDbCaches {
 
 impl Default for HoprDbCaches {
     fn default() -> Self {
+        Self::new(Duration::from_secs(30), 100_000)
+    }
+}
+
+impl HoprDbCaches {
+    /// Creates the caches, keeping at most `max_unacked_tickets` tickets waiting for their acknowledgement
+    /// for at most the `unacked_ticket_ttl`.
+    pub fn new(unacked_ticket_ttl: Duration, max_unacked_tickets: u64) -> Self {
         let single_values = Cache::builder().time_to_idle(Duration::from_secs(1800)).build();
 
         let unacked_tickets = Cache::builder()
-            .time_to_live(Duration::from_secs(30))
-            .max_capacity(1_000_000_000)
+            .time_to_live(unacked_ticket_ttl)
+            .max_capacity(max_unacked_tickets)
+            .eviction_policy(moka::policy::EvictionPolicy::lru())
             .build();
 
         let ticket_index = Cache::builder().expire_after(ExpiryNever).max_capacity(10_000).build();
@@ -153,9 +162,7 @@ impl Default for HoprDbCaches {
             key_id_mapper: CacheKeyMapper::with_capacity(10_000),
         }
     }
-}
 
-impl HoprDbCaches {
     /// Invalidates all caches.
     pub fn invalidate_all(&self) {
         self.single_values.invalidate_all();
@@ -33,6 +33,16 @@ pub struct HoprDbConfig {
     pub force_create: bool,
     #[default(Duration::from_secs(5))]
     pub log_slow_queries: Duration,
+    /// Maximum time a ticket waits for the acknowledgement of its packet.
+    ///
+    /// Should match the expectation window of the acknowledgement protocol.
+    #[default(Duration::from_secs(30))]
+    pub unacked_ticket_ttl: Duration,
+    /// Maximum number of tickets waiting for the acknowledgement of their packet, the oldest are dropped first.
+    ///
+    /// Should match the maximum number of pending acknowledgements of the acknowledgement protocol.
+    #[default(100_000)]
+    pub max_unacked_tickets: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -191,7 +201,7 @@ impl HoprDb {
             .exec(&tickets_db)
             .await?;
 
-        let caches = Arc::new(HoprDbCaches::default());
+        let caches = Arc::new(HoprDbCaches::new(cfg.unacked_ticket_ttl, cfg.max_unacked_tickets));
         caches.invalidate_all();
 
         // Initialize KeyId mapping for accounts
@@ -263,6 +273,31 @@ mod tests {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn unacked_tickets_should_be_bounded_by_the_configured_ttl_and_capacity() -> anyhow::Result<()> {
+        use hopr_crypto_types::prelude::HalfKey;
+        use hopr_internal_types::prelude::PendingAcknowledgement;
+
+        let caches = crate::cache::HoprDbCaches::new(std::time::Duration::from_millis(50), 3);
+        for _ in 0..10 {
+            caches
+                .unacked_tickets
+                .insert(
+                    HalfKey::random().to_challenge(),
+                    PendingAcknowledgement::WaitingAsSender,
+                )
+                .await;
+        }
+        caches.unacked_tickets.run_pending_tasks().await;
+        assert!(caches.unacked_tickets.entry_count() <= 3);
+
+        async_std::task::sleep(std::time::Duration::from_millis(60)).await;
+        caches.unacked_tickets.run_pending_tasks().await;
+        assert_eq!(0, caches.unacked_tickets.entry_count());
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn peers_without_any_recent_updates_should_be_discarded_on_restarts() -> anyhow::Result<()> {
         let random_filename: String = rand::thread_rng()
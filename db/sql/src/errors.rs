@@ -54,6 +54,9 @@ pub enum DbSqlError {
     #[error("ack validation error: {0}")]
     AcknowledgementValidationError(String),
 
+    #[error("unexpected acknowledgement: {0}")]
+    UnexpectedAcknowledgement(String),
+
     #[error(transparent)]
     BackendError(#[from] sea_orm::DbErr),
 
@@ -82,9 +85,57 @@ impl From<TicketValidationError> for DbSqlError {
     }
 }
 
+impl DbSqlError {
+    /// Indicates whether the error is caused by a temporary condition of the database backend,
+    /// such as a locked database or an exhausted connection pool.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DbSqlError::BackendError(e) => is_transient_backend_error(e),
+            DbSqlError::TransactionError(e) => e
+                .downcast_ref::<DbSqlError>()
+                .map(DbSqlError::is_transient)
+                .or_else(|| e.downcast_ref::<sea_orm::DbErr>().map(is_transient_backend_error))
+                .unwrap_or(false),
+            DbSqlError::CacheError(e) => e.is_transient(),
+            DbSqlError::ApiError(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+/// Primary SQLite result codes of a busy or locked database.
+///
+/// The extended result codes (e.g. `SQLITE_BUSY_SNAPSHOT`) carry the primary code in their lowest byte.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+fn is_transient_backend_error(error: &sea_orm::DbErr) -> bool {
+    match error {
+        sea_orm::DbErr::ConnectionAcquire(_) | sea_orm::DbErr::Conn(_) => true,
+        sea_orm::DbErr::Exec(sea_orm::RuntimeErr::SqlxError(e))
+        | sea_orm::DbErr::Query(sea_orm::RuntimeErr::SqlxError(e)) => is_transient_sqlx_error(e),
+        _ => false,
+    }
+}
+
+fn is_transient_sqlx_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(e) => e
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)),
+        _ => false,
+    }
+}
+
 impl From<DbSqlError> for hopr_db_api::errors::DbError {
     fn from(value: DbSqlError) -> Self {
-        hopr_db_api::errors::DbError::General(value.to_string())
+        match value {
+            DbSqlError::UnexpectedAcknowledgement(e) => hopr_db_api::errors::DbError::UnexpectedAcknowledgement(e),
+            e if e.is_transient() => hopr_db_api::errors::DbError::Transient(e.to_string()),
+            e => hopr_db_api::errors::DbError::General(e.to_string()),
+        }
     }
 }
 
@@ -165,7 +165,8 @@ impl HoprDb {
             .remove(&ack.ack_challenge()?)
             .await
             .ok_or_else(|| {
-                DbSqlError::AcknowledgementValidationError(format!(
+                // The challenge has either never been expected, it has expired or it was already acknowledged
+                DbSqlError::UnexpectedAcknowledgement(format!(
                     "received unexpected acknowledgement for half key challenge {}",
                     ack.to_hex()
                 ))
@@ -320,6 +321,7 @@ impl HoprDbProtocolOperations for HoprDb {
 
         if let Some(out) = packet.try_as_outgoing() {
             let mut transport_payload = Vec::with_capacity(HoprPacket::SIZE);
+            let ticket_value = out.ticket.amount;
             transport_payload.extend_from_slice(out.packet.as_ref());
             transport_payload.extend_from_slice(&out.ticket.into_encoded());
 
@@ -327,6 +329,7 @@ impl HoprDbProtocolOperations for HoprDb {
                 next_hop: out.next_hop,
                 ack_challenge: out.ack_challenge,
                 data: transport_payload.into_boxed_slice(),
+                ticket_value,
             })
         } else {
             Err(DbSqlError::LogicalError("must be an outgoing packet".into()).into())
@@ -443,6 +446,7 @@ impl HoprDbProtocolOperations for HoprDb {
                 .await;
 
             let mut transport_payload = Vec::with_capacity(HoprPacket::SIZE);
+            let ticket_value = out.ticket.amount;
             transport_payload.extend_from_slice(out.packet.as_ref());
             transport_payload.extend_from_slice(&out.ticket.into_encoded());
 
@@ -450,6 +454,7 @@ impl HoprDbProtocolOperations for HoprDb {
                 next_hop: out.next_hop,
                 ack_challenge: out.ack_challenge,
                 data: transport_payload.into_boxed_slice(),
+                ticket_value,
             })
         } else {
             Err(DbSqlError::LogicalError("must be an outgoing packet".into()).into())
@@ -516,7 +521,8 @@ impl HoprDbProtocolOperations for HoprDb {
                             previous_hop: fwd.previous_hop,
                             next_hop: fwd.outgoing.next_hop,
                             data: payload.into_boxed_slice(),
-                            ack: Acknowledgement::new(fwd.ack_key, pkt_keypair),
+                            ack_key: fwd.ack_key,
+                            ack_challenge: fwd.outgoing.ack_challenge,
                         })
                     }
                     Err(DbSqlError::TicketValidationError(boxed_error)) => {
@@ -26,6 +26,13 @@ impl HoprDbResolverOperations for HoprDb {
             .transpose()
             .map_err(|_e| DbError::LogicalError("failed to transpose the translated key".into()))?)
     }
+
+    async fn is_announced(&self, offchain_key: &OffchainPublicKey) -> Result<bool> {
+        Ok(self
+            .get_account(None, *offchain_key)
+            .await?
+            .is_some_and(|account| account.has_announced()))
+    }
 }
 
 #[cfg(test)]
@@ -197,4 +204,37 @@ mod tests {
         assert_eq!(actual_pk, Some(packet_2), "packet keys must match");
         Ok(())
     }
+
+    #[async_std::test]
+    async fn test_is_announced_should_be_true_only_for_announced_accounts() -> anyhow::Result<()> {
+        let db = HoprDb::new_in_memory(ChainKeypair::random()).await?;
+
+        let (announced, not_announced) = (*OffchainKeypair::random().public(), *OffchainKeypair::random().public());
+        for (public_key, entry_type) in [
+            (
+                announced,
+                AccountType::Announced {
+                    multiaddr: "/ip4/127.0.0.1/tcp/4444".parse()?,
+                    updated_block: 1,
+                },
+            ),
+            (not_announced, AccountType::NotAnnounced),
+        ] {
+            db.insert_account(
+                None,
+                AccountEntry {
+                    public_key,
+                    chain_addr: ChainKeypair::random().public().to_address(),
+                    entry_type,
+                    published_at: 1,
+                },
+            )
+            .await?;
+        }
+
+        assert!(db.is_announced(&announced).await?);
+        assert!(!db.is_announced(&not_announced).await?);
+        assert!(!db.is_announced(OffchainKeypair::random().public()).await?);
+        Ok(())
+    }
 }
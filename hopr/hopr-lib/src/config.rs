@@ -59,6 +59,10 @@ pub struct Chain {
     #[serde(default = "just_true")]
     #[default = true]
     pub fast_sync: bool,
+    /// Indicates whether concurrent identical RPC requests share a single request to the RPC provider.
+    #[serde(default = "just_true")]
+    #[default = true]
+    pub rpc_request_deduplication: bool,
 }
 
 #[inline]
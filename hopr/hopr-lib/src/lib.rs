@@ -403,6 +403,8 @@ impl Hopr {
             create_if_missing: cfg.db.initialize,
             force_create: cfg.db.force_initialize,
             log_slow_queries: std::time::Duration::from_millis(150),
+            unacked_ticket_ttl: cfg.protocol.ack.expectation_window,
+            max_unacked_tickets: cfg.protocol.ack.max_pending_acks as u64,
         };
         let db = futures::executor::block_on(HoprDb::new(db_path.as_path(), me_onchain.clone(), db_cfg))?;
 
@@ -416,6 +418,7 @@ impl Hopr {
             crate::constants::APP_VERSION_COERCED,
             cfg.chain.provider.as_deref(),
             cfg.chain.max_rpc_requests_per_sec,
+            cfg.chain.rpc_request_deduplication,
             &mut cfg.chain.protocols,
         )
         .map_err(|e| HoprLibError::GeneralError(format!("Failed to resolve blockchain environment: {e}")))?;
@@ -1169,6 +1172,11 @@ impl Hopr {
         Ok(self.transport_api.network_peer_info(peer).await?)
     }
 
+    /// Get the connectivity direction of a PeerId classified from the recent heartbeat interactions
+    pub fn network_peer_connectivity(&self, peer: &PeerId) -> hopr_transport::ConnectivityDirection {
+        self.transport_api.network_peer_connectivity(peer)
+    }
+
     /// Get peers connected peers with quality higher than some value
     pub async fn all_network_peers(
         &self,
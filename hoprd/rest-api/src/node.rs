@@ -108,6 +108,7 @@ pub(crate) struct PeerInfo {
     backoff: f64,
     is_new: bool,
     reported_version: String,
+    connectivity: String,
 }
 
 #[serde_as]
@@ -212,6 +213,7 @@ pub(super) async fn peers(
             backoff: info.backoff,
             is_new: info.heartbeats_sent == 0u64,
             reported_version: info.peer_version.unwrap_or("UNKNOWN".to_string()),
+            connectivity: hopr.network_peer_connectivity(&peer_id).to_string(),
         })
         .collect::<Vec<_>>()
         .await;
@@ -460,8 +460,8 @@ mod tests {
         AwaitingAggregator<(), (), HoprDb>,
         futures::channel::oneshot::Receiver<()>,
     )> {
-        let mut alice = TicketAggregationInteraction::<(), ()>::new(db_alice, key_alice);
-        let mut bob = TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), key_bob);
+        let mut alice = TicketAggregationInteraction::<(), ()>::new(db_alice, key_alice, Default::default());
+        let mut bob = TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), key_bob, Default::default());
 
         let (tx, awaiter) = futures::channel::oneshot::channel::<()>();
         let bob_aggregator = bob.writer();
@@ -36,6 +36,9 @@ pub enum HoprTransportError {
 
     #[error("Network monitoring error: {0}")]
     NetworkError(#[from] hopr_transport_network::errors::NetworkingError),
+
+    #[error("Mixer error: {0}")]
+    Mixer(#[from] hopr_transport_mixer::errors::MixerError),
 }
 
 /// Result produced by the crate, uses the [HoprTransportError] as the error type.
@@ -51,15 +51,17 @@ use hopr_platform::time::native::current_time;
 use hopr_primitive_types::prelude::*;
 use hopr_transport_network::{
     heartbeat::Heartbeat,
+    messaging::ControlMessage,
     ping::{PingConfig, PingQueryReplier, Pinger, Pinging},
 };
 use hopr_transport_p2p::{
-    swarm::{TicketAggregationRequestType, TicketAggregationResponseType},
-    HoprSwarm,
+    swarm::{HeartbeatPing, HeartbeatPong, TicketAggregationRequestType, TicketAggregationResponseType},
+    HoprSwarm, Ping, Pong,
 };
 use hopr_transport_protocol::{
+    config::{Profile, ProtocolConfig},
     errors::ProtocolError,
-    msg::processor::{MsgSender, PacketInteractionConfig, PacketSendFinalizer, SendMsgInput},
+    msg::processor::{MsgSender, PacketInteractionConfig, SendMsgInput},
     ticket_aggregation::processor::{
         AwaitingAggregator, TicketAggregationActions, TicketAggregationInteraction, TicketAggregatorTrait,
     },
@@ -89,7 +91,7 @@ use crate::{
     errors::HoprTransportError,
 };
 use hopr_crypto_packet::prelude::HoprPacket;
-use hopr_network_types::prelude::{DestinationRouting, ResolvedTransportRouting};
+use hopr_network_types::prelude::DestinationRouting;
 pub use {
     hopr_crypto_types::{
         keypairs::{ChainKeypair, Keypair, OffchainKeypair},
@@ -98,8 +100,18 @@ pub use {
     hopr_internal_types::prelude::HoprPseudonym,
     hopr_network_types::prelude::RoutingOptions,
     hopr_transport_identity::{Multiaddr, PeerId},
-    hopr_transport_network::network::{Health, Network, NetworkTriggeredEvent, PeerOrigin, PeerStatus},
-    hopr_transport_protocol::{execute_on_tick, PeerDiscovery},
+    hopr_transport_network::network::{
+        ConnectivityDirection, Health, Network, NetworkTriggeredEvent, PeerOrigin, PeerStatus,
+    },
+    hopr_transport_protocol::{
+        ack::stats::{ChannelTicketStats, TicketStats},
+        control::PipelineControl,
+        execute_on_tick,
+        health::{ProcessStatus, ProtocolHealth},
+        msg::accounting::{TagTraffic, TrafficAccounting},
+        msg::budget::{BufferAccounting, BufferedBytes, MIXER_QUEUE},
+        PeerDiscovery,
+    },
     hopr_transport_session::{
         errors::TransportSessionError, traits::SendMsg, Capability as SessionCapability, IncomingSession, Session,
         SessionClientConfig, SessionId, SESSION_USABLE_MTU_SIZE,
@@ -184,6 +196,9 @@ where
 /// Currently used implementation of [`PathSelector`](hopr_path::selectors::PathSelector).
 type CurrentPathSelector = DfsPathSelector<RandomizedEdgeWeighting>;
 
+/// Packet held by the mixer along with its bytes accounted in the buffer budget.
+type MixedPacket = ((PeerId, Box<[u8]>), BufferedBytes);
+
 /// Interface into the physical transport mechanism allowing all off-chain HOPR-related tasks on
 /// the transport, as well as off-chain ticket manipulation.
 pub struct HoprTransport<T>
@@ -202,6 +217,10 @@ where
     process_ticket_aggregate:
         Arc<OnceLock<TicketAggregationActions<TicketAggregationResponseType, TicketAggregationRequestType>>>,
     smgr: SessionManager<helpers::MessageSender<T, CurrentPathSelector>>,
+    ticket_stats: TicketStats,
+    traffic_accounting: TrafficAccounting,
+    protocol_health: ProtocolHealth,
+    protocol_control: Arc<OnceLock<PipelineControl>>,
 }
 
 impl<T> HoprTransport<T>
@@ -221,6 +240,11 @@ where
         let me_peerid: PeerId = me.into();
         let me_chain_addr = me_onchain.public().to_address();
 
+        let deviations = cfg.protocol.diff(&ProtocolConfig::profile(Profile::Balanced));
+        if !deviations.is_empty() {
+            info!(?deviations, "Protocol configuration deviates from the balanced profile");
+        }
+
         Self {
             me: me.clone(),
             me_peerid,
@@ -256,6 +280,10 @@ where
                     idle_timeout: cfg.session.idle_timeout,
                 },
             ),
+            ticket_stats: TicketStats::default(),
+            traffic_accounting: TrafficAccounting::default(),
+            protocol_health: ProtocolHealth::default(),
+            protocol_control: Arc::new(OnceLock::new()),
             cfg,
         }
     }
@@ -392,7 +420,7 @@ where
         let (ping_tx, ping_rx) = mpsc::unbounded::<(PeerId, PingQueryReplier)>();
 
         let ping_cfg = PingConfig {
-            timeout: self.cfg.protocol.heartbeat.timeout,
+            timeout: self.cfg.protocol.heartbeat.probe_timeout,
             max_parallel_pings: self.cfg.heartbeat.max_parallel_probes,
         };
 
@@ -412,17 +440,21 @@ where
             .set(ping)
             .expect("must set the ping executor only once");
 
-        let ticket_agg_proc = TicketAggregationInteraction::new(self.db.clone(), me_onchain);
+        let ticket_agg_proc =
+            TicketAggregationInteraction::new(self.db.clone(), me_onchain, self.cfg.protocol.ticket_aggregation);
         let tkt_agg_writer = ticket_agg_proc.writer();
 
-        let (external_msg_send, external_msg_rx) =
-            mpsc::channel::<(ApplicationData, ResolvedTransportRouting, PacketSendFinalizer)>(
-                MAXIMUM_MSG_OUTGOING_BUFFER_SIZE,
-            );
+        let (external_msg_send, external_msg_rx) = mpsc::channel::<SendMsgInput>(MAXIMUM_MSG_OUTGOING_BUFFER_SIZE);
+
+        let msg_sender = MsgSender::new(external_msg_send);
+        let msg_sender = match self.cfg.protocol.msg.send_finalizer_timeout {
+            Some(timeout) => msg_sender.with_finalizer_timeout(timeout),
+            None => msg_sender,
+        };
 
         self.process_packet_send
             .clone()
-            .set(MsgSender::new(external_msg_send))
+            .set(msg_sender)
             .expect("must set the packet processing writer only once");
 
         self.process_ticket_aggregate
@@ -438,7 +470,6 @@ where
                 .expect("Ping should be initialized at this point")
                 .clone(),
             hopr_transport_network::heartbeat::HeartbeatExternalInteractions::new(self.network.clone()),
-            Box::new(|dur| Box::pin(sleep(dur))),
         );
 
         // initiate the transport layer
@@ -468,14 +499,22 @@ where
                         .unwrap_or(hopr_transport_mixer::config::HOPR_MIXER_CAPACITY)
                 })
                 .unwrap_or(hopr_transport_mixer::config::HOPR_MIXER_CAPACITY),
+            max_packet_age: std::env::var("HOPR_INTERNAL_MIXER_MAX_PACKET_AGE_IN_MS")
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(std::time::Duration::from_millis),
             ..MixerConfig::default()
         };
+        // A maximum age within the mixing delay would silently drop the delayed packets
+        mixer_cfg.validate().inspect_err(
+            |error| error!(%error, "Invalid HOPR_INTERNAL_MIXER_MAX_PACKET_AGE_IN_MS for the mixer delay"),
+        )?;
         #[cfg(feature = "mixer-channel")]
-        let (mixing_channel_tx, mixing_channel_rx) = hopr_transport_mixer::channel::<(PeerId, Box<[u8]>)>(mixer_cfg);
+        let (mixing_channel_tx, mixing_channel_rx) = hopr_transport_mixer::channel::<MixedPacket>(mixer_cfg);
 
         #[cfg(feature = "mixer-stream")]
         let (mixing_channel_tx, mixing_channel_rx) = {
-            let (tx, rx) = futures::channel::mpsc::channel::<(PeerId, Box<[u8]>)>(MAXIMUM_MSG_OUTGOING_BUFFER_SIZE);
+            let (tx, rx) = futures::channel::mpsc::channel::<MixedPacket>(MAXIMUM_MSG_OUTGOING_BUFFER_SIZE);
             let rx = rx.then_concurrent(move |v| {
                 let cfg = mixer_cfg;
 
@@ -523,8 +562,14 @@ where
         let (wire_msg_tx, wire_msg_rx) =
             hopr_transport_protocol::stream::process_stream_protocol(msg_codec, msg_proto_control).await?;
 
-        let _mixing_process_before_sending_out =
-            hopr_async_runtime::prelude::spawn(mixing_channel_rx.map(Ok).forward(wire_msg_tx));
+        // The packets held by the mixer take their share of the buffer budget of the protocol pipeline
+        let buffer_accounting = BufferAccounting::new(self.cfg.protocol.msg.buffer_budget);
+        let mixing_channel_tx = buffer_accounting.tracked_sink(MIXER_QUEUE, mixing_channel_tx);
+        let _mixing_process_before_sending_out = hopr_async_runtime::prelude::spawn(
+            mixing_channel_rx
+                .map(|(packet, _buffered): MixedPacket| Ok(packet))
+                .forward(wire_msg_tx),
+        );
 
         let ack_proto_control =
             transport_layer.build_protocol_control(hopr_transport_protocol::ack::CURRENT_HOPR_ACK_PROTOCOL);
@@ -532,7 +577,34 @@ where
         let (wire_ack_tx, wire_ack_rx) =
             hopr_transport_protocol::stream::process_stream_protocol(ack_codec, ack_proto_control).await?;
 
-        let transport_layer = transport_layer.with_processors(tkt_agg_writer);
+        // Pings are answered by a dedicated responder, so that they do not wait behind the packet processing
+        // Each direction is queued separately, only the pings the responder cannot keep up with are dropped
+        let (heartbeat_ping_tx, heartbeat_ping_rx) =
+            mpsc::channel::<HeartbeatPing>(self.cfg.protocol.heartbeat.responder_queue_size);
+        let (heartbeat_pong_tx, heartbeat_pong_rx) =
+            mpsc::channel::<HeartbeatPong>(self.cfg.protocol.heartbeat.responder_queue_size);
+
+        let transport_layer = transport_layer
+            .with_processors(tkt_agg_writer)
+            .with_heartbeat_responder(heartbeat_ping_tx, heartbeat_pong_rx);
+
+        let pong_version = version.clone();
+        let network = self.network.clone();
+        for (k, v) in hopr_transport_protocol::heartbeat::responder::run_heartbeat_responder(
+            self.cfg.protocol.heartbeat,
+            (heartbeat_pong_tx, heartbeat_ping_rx),
+            move |peer, ping: Ping| {
+                // The pings initiated by the peer classify its connectivity direction
+                network.record_inbound_ping(peer);
+
+                ControlMessage::generate_pong_response(&ping.0)
+                    .ok()
+                    .map(|response| Pong(response, pong_version.clone()))
+            },
+            Some(self.protocol_health.clone()),
+        ) {
+            processes.insert(HoprTransportProcess::Protocol(k), v);
+        }
 
         processes.insert(HoprTransportProcess::Medium, spawn(transport_layer.run(version)));
 
@@ -545,17 +617,38 @@ where
         );
 
         let (tx_from_protocol, rx_from_protocol) = mpsc::unbounded::<ApplicationData>();
-        for (k, v) in hopr_transport_protocol::run_msg_ack_protocol(
+        let mut protocol_options = hopr_transport_protocol::options::ProtocolOptions::default()
+            .with_bloom_filter_persistence(tbf_path)
+            .with_ban_events(internal_discovery_update_tx.clone())
+            .with_ticket_stats(self.ticket_stats.clone())
+            .with_traffic_accounting(self.traffic_accounting.clone())
+            .with_health(self.protocol_health.clone())
+            .with_buffer_accounting(buffer_accounting);
+        // The sender is notified about an unreachable first hop only along with the handoff to the wire
+        if self.cfg.protocol.msg.finalize_after_wire_send {
+            protocol_options = protocol_options.with_reachability(Arc::new(
+                network_notifier::NetworkReachability::new(self.network.clone()),
+            ));
+        }
+
+        let (protocol_processes, protocol_control) = hopr_transport_protocol::run_msg_ack_protocol(
             packet_cfg,
+            self.cfg.protocol.msg,
+            self.cfg.protocol.ack,
             self.db.clone(),
-            Some(tbf_path),
             (wire_ack_tx, wire_ack_rx),
             (mixing_channel_tx, wire_msg_rx),
             (tx_from_protocol, external_msg_rx),
+            protocol_options,
         )
-        .await
-        .into_iter()
-        {
+        .await?;
+
+        self.protocol_control
+            .clone()
+            .set(protocol_control)
+            .expect("must set the protocol control only once");
+
+        for (k, v) in protocol_processes {
             processes.insert(HoprTransportProcess::Protocol(k), v);
         }
 
@@ -879,6 +972,33 @@ where
         Ok(self.network.get(peer).await?)
     }
 
+    /// Connectivity direction of the peer classified from the recent heartbeat interactions, kept in memory.
+    pub fn network_peer_connectivity(&self, peer: &PeerId) -> ConnectivityDirection {
+        self.network.connectivity(peer)
+    }
+
+    /// Near-real-time per-channel statistics of the tickets received by this node, kept in memory.
+    pub fn channel_ticket_statistics(&self) -> TicketStats {
+        self.ticket_stats.clone()
+    }
+
+    /// Messages and payload bytes sent and received by this node per application tag, kept in memory.
+    pub fn traffic_accounting(&self) -> TrafficAccounting {
+        self.traffic_accounting.clone()
+    }
+
+    /// Status of the processes of the `msg`/`ack` protocol pipeline, updated as the processes run.
+    pub fn protocol_health(&self) -> ProtocolHealth {
+        self.protocol_health.clone()
+    }
+
+    /// Control to pause and resume the `msg`/`ack` protocol pipeline, e.g. during maintenance.
+    ///
+    /// The control is available once the protocol pipeline has been started.
+    pub fn protocol_control(&self) -> Option<PipelineControl> {
+        self.protocol_control.get().cloned()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub async fn ticket_statistics(&self) -> errors::Result<TicketStatistics> {
         let ticket_stats = self.db.get_ticket_statistics(None).await?;
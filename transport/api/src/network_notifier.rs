@@ -12,6 +12,7 @@ use hopr_transport_network::{
     ping::PingExternalAPI,
     HoprDbPeersOperations, PeerId,
 };
+use hopr_transport_protocol::msg::reachability::PeerReachability;
 
 /// Implementor of the ping external API.
 ///
@@ -107,3 +108,33 @@ where
         }
     }
 }
+
+/// Reachability of the first hops of the sent packets, as observed by the [Network].
+///
+/// A peer is reachable if the [Network] considers it connected.
+#[derive(Debug)]
+pub struct NetworkReachability<T>
+where
+    T: HoprDbPeersOperations + Sync + Send + std::fmt::Debug,
+{
+    network: Arc<Network<T>>,
+}
+
+impl<T> NetworkReachability<T>
+where
+    T: HoprDbPeersOperations + Sync + Send + std::fmt::Debug,
+{
+    pub fn new(network: Arc<Network<T>>) -> Self {
+        Self { network }
+    }
+}
+
+#[async_trait]
+impl<T> PeerReachability for NetworkReachability<T>
+where
+    T: HoprDbPeersOperations + Sync + Send + std::fmt::Debug,
+{
+    async fn is_reachable(&self, peer: &PeerId) -> bool {
+        self.network.is_connected(peer).await
+    }
+}
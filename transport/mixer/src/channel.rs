@@ -11,12 +11,12 @@ use std::{
     task::Poll,
     time::Duration,
 };
-use tracing::{error, trace};
+use tracing::{debug, error, trace};
 
-use crate::{config::MixerConfig, data::DelayedData};
+use crate::{config::MixerConfig, data::DelayedData, delay::DelayFunction};
 
 #[cfg(all(feature = "prometheus", not(test)))]
-use hopr_metrics::metrics::SimpleGauge;
+use hopr_metrics::metrics::{SimpleCounter, SimpleGauge};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 lazy_static::lazy_static! {
@@ -27,11 +27,16 @@ lazy_static::lazy_static! {
         "Average mixer packet delay averaged over a packet window"
     )
     .unwrap();
+    pub static ref METRIC_MIXER_EXPIRED_PACKETS: SimpleCounter = SimpleCounter::new(
+        "hopr_mixer_expired_packets_count",
+        "Number of packets dropped for exceeding their maximum age in the mixer"
+    )
+    .unwrap();
 }
 
 /// Mixing and delaying channel using random delay function.
 ///
-/// Mixing is performed by assigning random delays to the ingress timestamp of data,
+/// Mixing is performed by assigning random delays (see [`DelayFunction`]) to the ingress timestamp of data,
 /// then storing the values inside a binary heap with reversed ordering (max heap).
 /// This effectively creates a min heap behavior, which is required to ensure that
 /// data is released in order of their delay expiration.
@@ -49,9 +54,39 @@ struct Channel<T> {
     buffer: BinaryHeap<Reverse<DelayedData<T>>>,
     timer: futures_timer::Delay,
     waker: Option<std::task::Waker>,
+    delay: Box<dyn DelayFunction<T>>,
     cfg: MixerConfig,
 }
 
+impl<T> Channel<T> {
+    /// Pops the first item from the buffer, unless it exceeded its maximum age in the mixer.
+    fn pop_unexpired(&mut self, now: std::time::Instant) -> Option<T> {
+        let data = self
+            .buffer
+            .pop()
+            .expect("The value should be present within the same locked access")
+            .0;
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_QUEUE_SIZE.decrement(1.0f64);
+
+        let age = data.age(now);
+        if self.cfg.max_packet_age.is_some_and(|max_age| age > max_age) {
+            debug!(
+                age_in_ms = age.as_millis(),
+                "dropping an item exceeding its maximum age in the mixer"
+            );
+
+            #[cfg(all(feature = "prometheus", not(test)))]
+            METRIC_MIXER_EXPIRED_PACKETS.increment();
+
+            None
+        } else {
+            Some(data.item)
+        }
+    }
+}
+
 /// Channel with sender and receiver counters allowing closure tracking.
 struct TrackedChannel<T> {
     channel: Arc<Mutex<Channel<T>>>,
@@ -137,11 +172,11 @@ impl<T> futures::sink::Sink<T> for Sender<T> {
         if is_active {
             let mut channel = self.channel.channel.lock().map_err(|_| SenderError::Lock)?;
 
-            let random_delay = channel.cfg.random_delay();
+            let random_delay = channel.delay.delay(&item);
 
             trace!(delay_in_ms = random_delay.as_millis(), "generated mixer delay",);
 
-            let delayed_data: DelayedData<T> = (std::time::Instant::now() + random_delay, item).into();
+            let delayed_data = DelayedData::new(std::time::Instant::now(), random_delay, item);
             channel.buffer.push(Reverse(delayed_data));
 
             if let Some(waker) = channel.waker.as_ref() {
@@ -199,61 +234,48 @@ impl<T> Stream for Receiver<T> {
 
     #[tracing::instrument(level = "trace", skip(self, cx))]
     fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Option<Self::Item>> {
-        let now = std::time::Instant::now();
         if self.channel.sender_count.load(Ordering::Relaxed) > 0 {
             let Ok(mut channel) = self.channel.channel.lock() else {
                 error!("mutex is poisoned, terminating stream");
                 return Poll::Ready(None);
             };
 
-            if channel.buffer.peek().map(|x| x.0.release_at < now).unwrap_or(false) {
-                let data = channel
-                    .buffer
-                    .pop()
-                    .expect("The value should be present within the same locked access")
-                    .0
-                    .item;
-
-                trace!(from = "direct", "yield item");
-
-                #[cfg(all(feature = "prometheus", not(test)))]
-                METRIC_QUEUE_SIZE.decrement(1.0f64);
-
-                return Poll::Ready(Some(data));
-            }
-
-            if let Some(waker) = channel.waker.as_mut() {
-                waker.clone_from(cx.waker());
-            } else {
-                let waker = cx.waker().clone();
-                channel.waker = Some(waker);
-            }
-
-            if let Some(next) = channel.buffer.peek() {
-                let remaining = next.0.release_at.duration_since(now);
-
-                trace!("reseting the timer");
-                channel.timer.reset(remaining);
-
-                futures::ready!(channel.timer.poll_unpin(cx));
-
-                trace!(from = "timer", "yield item");
-
-                #[cfg(all(feature = "prometheus", not(test)))]
-                METRIC_QUEUE_SIZE.decrement(1.0f64);
-
-                return Poll::Ready(Some(
-                    channel
-                        .buffer
-                        .pop()
-                        .expect("The value should be present within the locked access")
-                        .0
-                        .item,
-                ));
+            // The items exceeding their maximum age are skipped
+            loop {
+                let now = std::time::Instant::now();
+                if channel.buffer.peek().map(|x| x.0.release_at < now).unwrap_or(false) {
+                    if let Some(data) = channel.pop_unexpired(now) {
+                        trace!(from = "direct", "yield item");
+                        return Poll::Ready(Some(data));
+                    }
+                    continue;
+                }
+
+                if let Some(waker) = channel.waker.as_mut() {
+                    waker.clone_from(cx.waker());
+                } else {
+                    let waker = cx.waker().clone();
+                    channel.waker = Some(waker);
+                }
+
+                if let Some(next) = channel.buffer.peek() {
+                    let remaining = next.0.release_at.duration_since(now);
+
+                    trace!("reseting the timer");
+                    channel.timer.reset(remaining);
+
+                    futures::ready!(channel.timer.poll_unpin(cx));
+
+                    if let Some(data) = channel.pop_unexpired(std::time::Instant::now()) {
+                        trace!(from = "timer", "yield item");
+                        return Poll::Ready(Some(data));
+                    }
+                    continue;
+                }
+
+                trace!(from = "direct", "pending");
+                return Poll::Pending;
             }
-
-            trace!(from = "direct", "pending");
-            Poll::Pending
         } else {
             self.channel.receiver_active.store(false, Ordering::Relaxed);
             Poll::Ready(None)
@@ -261,6 +283,12 @@ impl<T> Stream for Receiver<T> {
     }
 }
 
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.channel.receiver_active.store(false, Ordering::Relaxed);
+    }
+}
+
 impl<T> Receiver<T> {
     /// Receive a single delayed mixed item.
     pub async fn recv(&mut self) -> Option<T> {
@@ -269,12 +297,27 @@ impl<T> Receiver<T> {
 }
 
 /// Instantiate a mixing channel and return the sender and receiver end of the channel.
+///
+/// All items are delayed uniformly by a random delay from the range given by the `cfg`.
 pub fn channel<T>(cfg: crate::config::MixerConfig) -> (Sender<T>, Receiver<T>) {
+    channel_with_delay(cfg, cfg)
+}
+
+/// Instantiate a mixing channel delaying each item by the given `delay` function
+/// and return the sender and receiver end of the channel.
+///
+/// This allows e.g. delaying the control traffic less than the cover traffic using the
+/// [`ClassifiedDelay`](crate::delay::ClassifiedDelay).
+pub fn channel_with_delay<T>(
+    cfg: crate::config::MixerConfig,
+    delay: impl DelayFunction<T> + 'static,
+) -> (Sender<T>, Receiver<T>) {
     #[cfg(all(feature = "prometheus", not(test)))]
     {
         // Initialize the lazy statics here
         lazy_static::initialize(&METRIC_QUEUE_SIZE);
         lazy_static::initialize(&METRIC_MIXER_AVERAGE_DELAY);
+        lazy_static::initialize(&METRIC_MIXER_EXPIRED_PACKETS);
     }
 
     let mut buffer = BinaryHeap::new();
@@ -285,6 +328,7 @@ pub fn channel<T>(cfg: crate::config::MixerConfig) -> (Sender<T>, Receiver<T>) {
             buffer,
             timer: Delay::new(Duration::from_secs(0)),
             waker: None,
+            delay: Box::new(delay),
             cfg,
         })),
         sender_count: Arc::new(AtomicUsize::new(1)),
@@ -319,6 +363,17 @@ mod tests {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn mixer_channel_should_refuse_elements_once_the_receiver_is_dropped() -> anyhow::Result<()> {
+        let (tx, rx) = channel(MixerConfig::default());
+        tx.send(1)?;
+
+        drop(rx);
+        assert!(matches!(tx.send(2), Err(SenderError::Closed)));
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn mixer_channel_should_introduce_random_delay() -> anyhow::Result<()> {
         let start = std::time::SystemTime::now();
@@ -487,4 +542,99 @@ mod tests {
         tracing::info!(?input, ?mixed_output, "asserted data");
         Ok(assert_eq!(input, mixed_output))
     }
+
+    #[async_std::test]
+    async fn mixer_channel_should_drop_the_items_exceeding_their_maximum_age() -> anyhow::Result<()> {
+        let (tx, mut rx) = channel(MixerConfig {
+            min_delay: Duration::from_millis(0),
+            delay_range: Duration::from_millis(0),
+            max_packet_age: Some(Duration::from_millis(50)),
+            ..MixerConfig::default()
+        });
+
+        // The receiver is not polled, so that the items become stale
+        for i in 0..10 {
+            tx.send(i)?;
+        }
+        async_std::task::sleep(Duration::from_millis(100)).await;
+
+        tx.send(10)?;
+        assert_eq!(Some(10), rx.next().timeout(MAXIMUM_SINGLE_DELAY_DURATION).await?);
+        assert!(
+            rx.next().timeout(MAXIMUM_SINGLE_DELAY_DURATION).await.is_err(),
+            "stale items must not be released"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn mixer_channel_should_release_the_items_within_their_maximum_age() -> anyhow::Result<()> {
+        const ITERATIONS: usize = 10;
+
+        let (tx, rx) = channel(MixerConfig {
+            max_packet_age: Some(MAXIMUM_SINGLE_DELAY_DURATION + PROCESSING_LEEWAY),
+            ..MixerConfig::default()
+        });
+
+        for i in 0..ITERATIONS {
+            tx.send(i)?;
+        }
+
+        let mut output = rx
+            .take(ITERATIONS)
+            .collect::<Vec<_>>()
+            .timeout(2 * MAXIMUM_SINGLE_DELAY_DURATION)
+            .await?;
+        output.sort();
+
+        assert_eq!((0..ITERATIONS).collect::<Vec<_>>(), output);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn mixer_channel_should_delay_the_items_using_the_classified_delay() -> anyhow::Result<()> {
+        use crate::delay::{ClassifiedDelay, PacketClass};
+
+        const ITERATIONS: usize = 10;
+
+        let cfg = MixerConfig::default();
+        let delay = ClassifiedDelay::new(
+            |item: &(usize, Box<[u8]>)| {
+                if item.1.is_empty() {
+                    PacketClass::Cover
+                } else {
+                    PacketClass::Control
+                }
+            },
+            &cfg,
+        )
+        .with_class_delay(PacketClass::Control, Duration::from_millis(0), Duration::from_millis(0))
+        .with_class_delay(PacketClass::Cover, Duration::from_millis(50), Duration::from_millis(50));
+
+        let (tx, rx) = channel_with_delay(cfg, delay);
+
+        // Cover packets are sent first, but must be released only after all the control packets
+        for i in 0..ITERATIONS {
+            tx.send((i, Box::default()))?;
+        }
+        for i in ITERATIONS..2 * ITERATIONS {
+            tx.send((i, vec![1u8].into_boxed_slice()))?;
+        }
+
+        let output = rx
+            .take(2 * ITERATIONS)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>()
+            .timeout(2 * MAXIMUM_SINGLE_DELAY_DURATION)
+            .await?;
+
+        assert_eq!((ITERATIONS..2 * ITERATIONS).collect::<Vec<_>>(), output[..ITERATIONS]);
+
+        let mut cover = output[ITERATIONS..].to_vec();
+        cover.sort();
+        assert_eq!((0..ITERATIONS).collect::<Vec<_>>(), cover);
+
+        Ok(())
+    }
 }
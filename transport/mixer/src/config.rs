@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use crate::errors::{MixerError, Result};
+
 pub const HOPR_MIXER_MINIMUM_DEFAULT_DELAY_IN_MS: u64 = 0;
 pub const HOPR_MIXER_DEFAULT_DELAY_RANGE_IN_MS: u64 = 200;
 pub const HOPR_MIXER_DELAY_METRIC_WINDOW: u64 = 100;
@@ -22,20 +24,90 @@ pub struct MixerConfig {
     pub capacity: usize,
     #[default(HOPR_MIXER_DELAY_METRIC_WINDOW)]
     pub metric_delay_window: u64,
+    /// The maximum time a packet may spend in the mixer.
+    ///
+    /// A packet held longer, e.g. because the receiver was not polled in time,
+    /// has become stale and is dropped instead of being released.
+    /// If not set, the packets are released regardless of their age.
+    /// Otherwise, it must exceed the [maximum delay](MixerConfig::max_delay), see [MixerConfig::validate].
+    pub max_packet_age: Option<Duration>,
 }
 
 impl MixerConfig {
+    /// The maximum delay introduced during mixing.
+    pub fn max_delay(&self) -> Duration {
+        self.min_delay.saturating_add(self.delay_range)
+    }
+
+    /// Checks that the packets are not dropped for their age before they could be released.
+    ///
+    /// Fails if the `max_packet_age` does not exceed the [maximum delay](MixerConfig::max_delay),
+    /// since the mixer would then drop all the packets delayed by more than the maximum age.
+    pub fn validate(&self) -> Result<()> {
+        match self.max_packet_age {
+            Some(max_packet_age) if max_packet_age <= self.max_delay() => Err(MixerError::MaxPacketAgeTooShort {
+                max_packet_age,
+                max_delay: self.max_delay(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Get a random delay duration from the specified minimum and maximum delay available
     /// inside the configuration.
     pub fn random_delay(&self) -> Duration {
-        let max_delay = self.min_delay.saturating_add(self.delay_range);
+        random_delay(self.min_delay, self.delay_range)
+    }
+}
+
+/// Get a random delay duration between `min_delay` and `min_delay + delay_range`.
+pub(crate) fn random_delay(min_delay: Duration, delay_range: Duration) -> Duration {
+    // The random integer range must not be empty
+    if delay_range.as_millis() == 0 {
+        return min_delay;
+    }
 
-        let random_delay = if max_delay.as_millis() == 0 {
-            max_delay.as_millis() as u64
-        } else {
-            hopr_crypto_random::random_integer(self.min_delay.as_millis() as u64, Some(max_delay.as_millis() as u64))
+    let max_delay = min_delay.saturating_add(delay_range);
+    Duration::from_millis(hopr_crypto_random::random_integer(
+        min_delay.as_millis() as u64,
+        Some(max_delay.as_millis() as u64),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixer_config_should_reject_a_max_packet_age_not_exceeding_the_max_delay() {
+        let cfg = MixerConfig {
+            min_delay: Duration::from_millis(50),
+            delay_range: Duration::from_millis(100),
+            ..MixerConfig::default()
         };
+        assert_eq!(Ok(()), cfg.validate(), "no maximum age must be valid");
+
+        for max_packet_age in [0, 100, 150] {
+            assert_eq!(
+                Err(MixerError::MaxPacketAgeTooShort {
+                    max_packet_age: Duration::from_millis(max_packet_age),
+                    max_delay: Duration::from_millis(150),
+                }),
+                MixerConfig {
+                    max_packet_age: Some(Duration::from_millis(max_packet_age)),
+                    ..cfg
+                }
+                .validate()
+            );
+        }
 
-        Duration::from_millis(random_delay)
+        assert_eq!(
+            Ok(()),
+            MixerConfig {
+                max_packet_age: Some(Duration::from_millis(151)),
+                ..cfg
+            }
+            .validate()
+        );
     }
 }
@@ -6,9 +6,26 @@ use std::cmp::Ordering;
 /// to ensure proper mixing.
 pub struct DelayedData<T> {
     pub release_at: std::time::Instant,
+    pub enqueued_at: std::time::Instant,
     pub item: T,
 }
 
+impl<T> DelayedData<T> {
+    /// Creates the data enqueued at `enqueued_at` to be released after the given `delay`.
+    pub fn new(enqueued_at: std::time::Instant, delay: std::time::Duration, item: T) -> Self {
+        Self {
+            release_at: enqueued_at + delay,
+            enqueued_at,
+            item,
+        }
+    }
+
+    /// Time the data has spent in the mixer until `now`.
+    pub fn age(&self, now: std::time::Instant) -> std::time::Duration {
+        now.saturating_duration_since(self.enqueued_at)
+    }
+}
+
 impl<T> PartialEq for DelayedData<T> {
     fn eq(&self, other: &Self) -> bool {
         self.release_at == other.release_at
@@ -29,10 +46,12 @@ impl<T> Ord for DelayedData<T> {
     }
 }
 
+/// The data is considered enqueued at its release timestamp.
 impl<T> From<(std::time::Instant, T)> for DelayedData<T> {
     fn from(value: (std::time::Instant, T)) -> Self {
         Self {
             release_at: value.0,
+            enqueued_at: value.0,
             item: value.1,
         }
     }
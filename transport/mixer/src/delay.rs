@@ -0,0 +1,144 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::config::{random_delay, MixerConfig};
+
+/// Function assigning the mixing delay to each item entering the mixer.
+///
+/// The [`MixerConfig`] itself is the uniform delay function, delaying all items
+/// by a random delay from its configured range.
+pub trait DelayFunction<T>: Send + Sync {
+    /// Get the delay of the `item`.
+    fn delay(&self, item: &T) -> Duration;
+}
+
+impl<T> DelayFunction<T> for MixerConfig {
+    fn delay(&self, _item: &T) -> Duration {
+        self.random_delay()
+    }
+}
+
+impl<T, F> DelayFunction<T> for F
+where
+    F: Fn(&T) -> Duration + Send + Sync,
+{
+    fn delay(&self, item: &T) -> Duration {
+        self(item)
+    }
+}
+
+/// Class of a packet passing through the mixer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketClass {
+    /// Protocol control traffic (e.g. acknowledgements), which should be delayed as little as possible.
+    Control,
+    /// Regular data traffic.
+    Data,
+    /// Cover traffic, which can be delayed the most.
+    Cover,
+}
+
+/// Hook classifying the items entering the mixer, e.g. the `(PeerId, Box<[u8]>)` frames.
+pub trait PacketClassifier<T>: Send + Sync {
+    /// Get the class of the `item`.
+    fn classify(&self, item: &T) -> PacketClass;
+}
+
+impl<T, F> PacketClassifier<T> for F
+where
+    F: Fn(&T) -> PacketClass + Send + Sync,
+{
+    fn classify(&self, item: &T) -> PacketClass {
+        self(item)
+    }
+}
+
+/// Delay function choosing the delay range by the [`PacketClass`] of each item.
+///
+/// The classes without a specific delay range use the range of the [`MixerConfig`]
+/// the function has been created with.
+#[derive(Debug, Clone)]
+pub struct ClassifiedDelay<C> {
+    classifier: C,
+    default_delay: (Duration, Duration),
+    class_delays: HashMap<PacketClass, (Duration, Duration)>,
+}
+
+impl<C> ClassifiedDelay<C> {
+    /// Create a delay function using the `classifier` and the delay range of the `cfg` for all classes.
+    pub fn new(classifier: C, cfg: &MixerConfig) -> Self {
+        Self {
+            classifier,
+            default_delay: (cfg.min_delay, cfg.delay_range),
+            class_delays: HashMap::new(),
+        }
+    }
+
+    /// Set the delay range of the given packet `class`.
+    pub fn with_class_delay(mut self, class: PacketClass, min_delay: Duration, delay_range: Duration) -> Self {
+        self.class_delays.insert(class, (min_delay, delay_range));
+        self
+    }
+}
+
+impl<T, C: PacketClassifier<T>> DelayFunction<T> for ClassifiedDelay<C> {
+    fn delay(&self, item: &T) -> Duration {
+        let class = self.classifier.classify(item);
+        let (min_delay, delay_range) = self.class_delays.get(&class).copied().unwrap_or(self.default_delay);
+        random_delay(min_delay, delay_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(item: &(u8, Box<[u8]>)) -> PacketClass {
+        match item.1.first() {
+            Some(0) => PacketClass::Control,
+            Some(1) => PacketClass::Cover,
+            _ => PacketClass::Data,
+        }
+    }
+
+    #[test]
+    fn classified_delay_should_use_the_delay_range_of_the_packet_class() {
+        let cfg = MixerConfig {
+            min_delay: Duration::from_millis(50),
+            delay_range: Duration::from_millis(0),
+            ..MixerConfig::default()
+        };
+
+        let delay = ClassifiedDelay::new(classify, &cfg)
+            .with_class_delay(PacketClass::Control, Duration::from_millis(0), Duration::from_millis(0))
+            .with_class_delay(
+                PacketClass::Cover,
+                Duration::from_millis(100),
+                Duration::from_millis(10),
+            );
+
+        assert_eq!(
+            Duration::from_millis(0),
+            delay.delay(&(1u8, vec![0].into_boxed_slice()))
+        );
+        assert_eq!(
+            Duration::from_millis(50),
+            delay.delay(&(1u8, vec![2].into_boxed_slice()))
+        );
+
+        let cover_delay = delay.delay(&(1u8, vec![1].into_boxed_slice()));
+        assert!(cover_delay >= Duration::from_millis(100) && cover_delay <= Duration::from_millis(110));
+    }
+
+    #[test]
+    fn closures_should_be_usable_as_delay_functions() {
+        let delay = |item: &u64| Duration::from_millis(*item);
+        assert_eq!(Duration::from_millis(7), delay.delay(&7));
+
+        let cfg = MixerConfig {
+            min_delay: Duration::from_millis(3),
+            delay_range: Duration::from_millis(0),
+            ..MixerConfig::default()
+        };
+        assert_eq!(Duration::from_millis(3), DelayFunction::<u64>::delay(&cfg, &7));
+    }
+}
@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Errors that can be generated by the crate.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MixerError {
+    #[error("maximum packet age of {max_packet_age:?} does not exceed the maximum mixing delay of {max_delay:?}")]
+    MaxPacketAgeTooShort {
+        max_packet_age: Duration,
+        max_delay: Duration,
+    },
+}
+
+/// Result produced by the crate, uses the [MixerError] as the error type.
+pub type Result<T> = core::result::Result<T, MixerError>;
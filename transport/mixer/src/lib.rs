@@ -1,6 +1,9 @@
 pub mod channel;
 pub mod config;
 pub mod data;
+pub mod delay;
+pub mod errors;
 
-pub use channel::channel;
+pub use channel::{channel, channel_with_delay};
 pub use config::MixerConfig;
+pub use delay::{ClassifiedDelay, DelayFunction, PacketClass, PacketClassifier};
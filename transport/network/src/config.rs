@@ -17,6 +17,11 @@ pub const DEFAULT_AUTO_PATH_QUALITY_THRESHOLD: f64 = 0.95;
 
 pub const DEFAULT_MAX_FIRST_HOP_LATENCY_THRESHOLD: Duration = Duration::from_millis(100);
 
+pub const DEFAULT_CONNECTIVITY_WINDOW_SIZE: usize = 20;
+pub const DEFAULT_CONNECTIVITY_MIN_INTERACTIONS: usize = 5;
+pub const DEFAULT_CONNECTIVITY_ENTER_THRESHOLD: f64 = 0.3;
+pub const DEFAULT_CONNECTIVITY_EXIT_THRESHOLD: f64 = 0.1;
+
 /// Configuration for the [`crate::network::Network`] object
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, SmartDefault, PartialEq)]
@@ -74,6 +79,31 @@ pub struct NetworkConfig {
     #[serde(default = "backoff_max")]
     #[default(backoff_max())]
     pub backoff_max: f64,
+
+    /// Number of the most recent heartbeat interactions with a peer (probes initiated by us and pings
+    /// initiated by the peer) its connectivity direction is classified from
+    #[serde(default = "connectivity_window_size")]
+    #[default(connectivity_window_size())]
+    pub connectivity_window_size: usize,
+
+    /// Minimum number of interactions with a peer before its connectivity direction is classified
+    #[serde(default = "connectivity_min_interactions")]
+    #[default(connectivity_min_interactions())]
+    pub connectivity_min_interactions: usize,
+
+    /// Ratio of the answered probes (or of the pings initiated by the peer) from which the peer
+    /// starts to be considered reachable (or initiating) in that direction
+    #[serde(default = "connectivity_enter_threshold")]
+    #[default(connectivity_enter_threshold())]
+    pub connectivity_enter_threshold: f64,
+
+    /// Ratio below which a peer already considered reachable (or initiating) in a direction stops to be so
+    ///
+    /// Must not be greater than `connectivity_enter_threshold`, the gap between the two prevents
+    /// the classification from flapping.
+    #[serde(default = "connectivity_exit_threshold")]
+    #[default(connectivity_exit_threshold())]
+    pub connectivity_exit_threshold: f64,
 }
 
 impl Validate for NetworkConfig {
@@ -140,6 +170,27 @@ impl Validate for NetworkConfig {
             );
         }
 
+        if self.connectivity_min_interactions < 1 || self.connectivity_min_interactions > self.connectivity_window_size
+        {
+            errors.add(
+                "connectivity_min_interactions",
+                validator::ValidationError::new(
+                    "connectivity_min_interactions must be between 1 and connectivity_window_size",
+                ),
+            );
+        }
+
+        if !(0.0..=1.0).contains(&self.connectivity_enter_threshold)
+            || !(0.0..=self.connectivity_enter_threshold).contains(&self.connectivity_exit_threshold)
+        {
+            errors.add(
+                "connectivity_enter_threshold and connectivity_exit_threshold",
+                validator::ValidationError::new(
+                    "connectivity_exit_threshold must be between 0 and connectivity_enter_threshold, which must be at most 1",
+                ),
+            );
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -207,3 +258,23 @@ fn backoff_min() -> f64 {
 fn backoff_max() -> f64 {
     duration_5_min().as_millis() as f64 / duration_1_s().as_millis() as f64
 }
+
+#[inline]
+fn connectivity_window_size() -> usize {
+    DEFAULT_CONNECTIVITY_WINDOW_SIZE
+}
+
+#[inline]
+fn connectivity_min_interactions() -> usize {
+    DEFAULT_CONNECTIVITY_MIN_INTERACTIONS
+}
+
+#[inline]
+fn connectivity_enter_threshold() -> f64 {
+    DEFAULT_CONNECTIVITY_ENTER_THRESHOLD
+}
+
+#[inline]
+fn connectivity_exit_threshold() -> f64 {
+    DEFAULT_CONNECTIVITY_EXIT_THRESHOLD
+}
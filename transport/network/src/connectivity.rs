@@ -0,0 +1,280 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use libp2p_identity::PeerId;
+use tracing::debug;
+
+use crate::config::NetworkConfig;
+
+/// Direction in which the connectivity with a peer has been observed.
+///
+/// Derived from the recent heartbeat interactions with the peer: the probes initiated by us
+/// and the pings initiated by the peer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, strum::Display, strum::EnumString)]
+pub enum ConnectivityDirection {
+    /// Not enough interactions observed yet.
+    #[default]
+    Unknown,
+    /// The peer answers our probes and also pings us.
+    Bidirectional,
+    /// The peer only answers when we initiate, e.g. because it is behind NAT or has no public address.
+    OutboundOnly,
+    /// The peer pings us, but does not answer our probes.
+    InboundOnly,
+}
+
+/// Single heartbeat interaction with a peer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interaction {
+    /// Our probe has been answered by the peer.
+    ProbeAnswered,
+    /// Our probe has not been answered by the peer.
+    ProbeFailed,
+    /// The peer has pinged us.
+    PingReceived,
+}
+
+#[derive(Debug, Default)]
+struct PeerConnectivity {
+    interactions: VecDeque<Interaction>,
+    answers: bool,
+    initiates: bool,
+}
+
+/// Applies the hysteresis between the `enter` and `exit` thresholds to the current `state`.
+fn hysteresis(state: bool, ratio: f64, enter: f64, exit: f64) -> bool {
+    if state {
+        ratio >= exit
+    } else {
+        ratio >= enter
+    }
+}
+
+impl PeerConnectivity {
+    fn record(&mut self, interaction: Interaction, cfg: &NetworkConfig) -> ConnectivityDirection {
+        self.interactions.push_back(interaction);
+        while self.interactions.len() > cfg.connectivity_window_size.max(1) {
+            self.interactions.pop_front();
+        }
+
+        let total = self.interactions.len();
+        if total < cfg.connectivity_min_interactions {
+            return ConnectivityDirection::Unknown;
+        }
+
+        let count = |kind: Interaction| self.interactions.iter().filter(|i| **i == kind).count();
+        let (answered, failed, pings) = (
+            count(Interaction::ProbeAnswered),
+            count(Interaction::ProbeFailed),
+            count(Interaction::PingReceived),
+        );
+
+        // Without any probes in the window, whether the peer answers remains as it was
+        if answered + failed > 0 {
+            let ratio = answered as f64 / (answered + failed) as f64;
+            self.answers = hysteresis(
+                self.answers,
+                ratio,
+                cfg.connectivity_enter_threshold,
+                cfg.connectivity_exit_threshold,
+            );
+        }
+
+        self.initiates = hysteresis(
+            self.initiates,
+            pings as f64 / total as f64,
+            cfg.connectivity_enter_threshold,
+            cfg.connectivity_exit_threshold,
+        );
+
+        self.direction(cfg)
+    }
+
+    fn record_logged(&mut self, peer: &PeerId, interaction: Interaction, cfg: &NetworkConfig) -> ConnectivityDirection {
+        let previous = self.direction(cfg);
+        let current = self.record(interaction, cfg);
+        if previous != current {
+            debug!(%peer, %previous, %current, "peer connectivity direction changed");
+        }
+        current
+    }
+
+    fn direction(&self, cfg: &NetworkConfig) -> ConnectivityDirection {
+        if self.interactions.len() < cfg.connectivity_min_interactions {
+            return ConnectivityDirection::Unknown;
+        }
+
+        match (self.answers, self.initiates) {
+            (true, true) => ConnectivityDirection::Bidirectional,
+            (true, false) => ConnectivityDirection::OutboundOnly,
+            (false, true) => ConnectivityDirection::InboundOnly,
+            (false, false) => ConnectivityDirection::Unknown,
+        }
+    }
+}
+
+/// Per-peer bookkeeping of the heartbeat interactions classifying the [`ConnectivityDirection`] of each peer.
+///
+/// A peer answering our probes, but never pinging us is likely behind NAT or has no public address.
+/// The classification is done over a sliding window of the most recent interactions and uses
+/// separate thresholds to enter and exit each direction, so that it does not flap on the boundary.
+///
+/// Only the peers first [recorded](ConnectivityTracker::record) by our own probes are tracked, i.e. the peers
+/// of the network, until they are [removed](ConnectivityTracker::remove) from it. The interactions initiated
+/// by any other peer are [ignored](ConnectivityTracker::record_if_tracked), so that they cannot grow the tracker.
+#[derive(Debug, Default)]
+pub struct ConnectivityTracker {
+    peers: Mutex<HashMap<PeerId, PeerConnectivity>>,
+}
+
+impl ConnectivityTracker {
+    /// Records an interaction with the `peer` and returns its updated classification.
+    pub fn record(&self, peer: &PeerId, interaction: Interaction, cfg: &NetworkConfig) -> ConnectivityDirection {
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        peers.entry(*peer).or_default().record_logged(peer, interaction, cfg)
+    }
+
+    /// Same as [`ConnectivityTracker::record`], but only if the `peer` is already tracked.
+    ///
+    /// Returns `None` if the `peer` is not tracked.
+    pub fn record_if_tracked(
+        &self,
+        peer: &PeerId,
+        interaction: Interaction,
+        cfg: &NetworkConfig,
+    ) -> Option<ConnectivityDirection> {
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        Some(peers.get_mut(peer)?.record_logged(peer, interaction, cfg))
+    }
+
+    /// Current classification of the `peer`.
+    pub fn direction(&self, peer: &PeerId, cfg: &NetworkConfig) -> ConnectivityDirection {
+        self.peers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(peer)
+            .map(|connectivity| connectivity.direction(cfg))
+            .unwrap_or_default()
+    }
+
+    /// Forgets all the interactions with the `peer`.
+    pub fn remove(&self, peer: &PeerId) {
+        self.peers.lock().unwrap_or_else(|e| e.into_inner()).remove(peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConnectivityDirection::*;
+    use Interaction::*;
+
+    fn cfg() -> NetworkConfig {
+        NetworkConfig {
+            connectivity_window_size: 10,
+            connectivity_min_interactions: 4,
+            connectivity_enter_threshold: 0.3,
+            connectivity_exit_threshold: 0.1,
+            ..NetworkConfig::default()
+        }
+    }
+
+    fn feed(tracker: &ConnectivityTracker, peer: &PeerId, script: &[Interaction]) -> Vec<ConnectivityDirection> {
+        script.iter().map(|i| tracker.record(peer, *i, &cfg())).collect()
+    }
+
+    #[test]
+    fn connectivity_should_be_unknown_until_enough_interactions() {
+        let tracker = ConnectivityTracker::default();
+        let peer = PeerId::random();
+
+        assert_eq!(Unknown, tracker.direction(&peer, &cfg()));
+        assert_eq!(
+            vec![Unknown, Unknown, Unknown, OutboundOnly],
+            feed(&tracker, &peer, &[ProbeAnswered; 4])
+        );
+        assert_eq!(OutboundOnly, tracker.direction(&peer, &cfg()));
+    }
+
+    #[test]
+    fn connectivity_should_classify_the_interaction_patterns() {
+        let tracker = ConnectivityTracker::default();
+
+        let bidirectional = PeerId::random();
+        let history = feed(
+            &tracker,
+            &bidirectional,
+            &[ProbeAnswered, PingReceived, ProbeAnswered, PingReceived],
+        );
+        assert_eq!(Some(&Bidirectional), history.last());
+
+        let inbound = PeerId::random();
+        let history = feed(
+            &tracker,
+            &inbound,
+            &[ProbeFailed, PingReceived, ProbeFailed, PingReceived],
+        );
+        assert_eq!(Some(&InboundOnly), history.last());
+
+        let unreachable = PeerId::random();
+        let history = feed(&tracker, &unreachable, &[ProbeFailed; 5]);
+        assert_eq!(Some(&Unknown), history.last());
+
+        tracker.remove(&bidirectional);
+        assert_eq!(Unknown, tracker.direction(&bidirectional, &cfg()));
+    }
+
+    #[test]
+    fn connectivity_should_only_track_the_peers_recorded_by_our_probes() {
+        let tracker = ConnectivityTracker::default();
+        let (known, stranger) = (PeerId::random(), PeerId::random());
+
+        assert_eq!(None, tracker.record_if_tracked(&stranger, PingReceived, &cfg()));
+        assert!(
+            tracker.peers.lock().unwrap().is_empty(),
+            "unknown peer must not be tracked"
+        );
+
+        tracker.record(&known, ProbeAnswered, &cfg());
+        assert_eq!(Some(Unknown), tracker.record_if_tracked(&known, PingReceived, &cfg()));
+        assert_eq!(1, tracker.peers.lock().unwrap().len());
+
+        tracker.remove(&known);
+        assert_eq!(None, tracker.record_if_tracked(&known, PingReceived, &cfg()));
+        assert!(tracker.peers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn connectivity_should_transition_with_hysteresis() {
+        let tracker = ConnectivityTracker::default();
+        let peer = PeerId::random();
+
+        // Only answering our probes
+        feed(&tracker, &peer, &[ProbeAnswered; 10]);
+        assert_eq!(OutboundOnly, tracker.direction(&peer, &cfg()));
+
+        // 2 pings out of 10 interactions are below the enter threshold
+        let history = feed(&tracker, &peer, &[PingReceived, ProbeAnswered, PingReceived]);
+        assert_eq!(vec![OutboundOnly, OutboundOnly, OutboundOnly], history);
+
+        // The 3rd ping enters the bidirectional classification
+        assert_eq!(vec![Bidirectional], feed(&tracker, &peer, &[PingReceived]));
+
+        // Dropping to a single ping in the window (below the enter threshold, but at the exit one) does not exit it
+        let history = feed(&tracker, &peer, &[ProbeAnswered; 9]);
+        assert!(history.iter().all(|d| *d == Bidirectional), "{history:?}");
+
+        // No ping in the window anymore exits it
+        assert_eq!(vec![OutboundOnly], feed(&tracker, &peer, &[ProbeAnswered]));
+
+        // Failing probes exit the outbound direction only once below the exit threshold
+        let history = feed(&tracker, &peer, &[ProbeFailed; 9]);
+        assert_eq!(Some(&OutboundOnly), history.last());
+        assert_eq!(vec![Unknown], feed(&tracker, &peer, &[ProbeFailed]));
+
+        // And need to get above the enter threshold to enter it again
+        let history = feed(&tracker, &peer, &[ProbeAnswered; 2]);
+        assert_eq!(vec![Unknown, Unknown], history);
+        assert_eq!(vec![OutboundOnly], feed(&tracker, &peer, &[ProbeAnswered]));
+    }
+}
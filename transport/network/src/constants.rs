@@ -9,5 +9,8 @@ pub const DEFAULT_HEARTBEAT_THRESHOLD: std::time::Duration = std::time::Duration
 /// all the nodes start their interval at the same time
 pub const DEFAULT_HEARTBEAT_INTERVAL_VARIANCE: std::time::Duration = std::time::Duration::from_secs(2);
 
+/// Time since the start of the heartbeat during which the failed probes are not penalized
+pub const DEFAULT_HEARTBEAT_WARMUP: std::time::Duration = std::time::Duration::from_secs(0);
+
 /// The maximum number of parallel probes the heartbeat performs
 pub const DEFAULT_MAX_PARALLEL_PINGS: usize = 25;
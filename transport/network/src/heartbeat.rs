@@ -1,17 +1,18 @@
 use async_trait::async_trait;
 use futures::{
+    channel::mpsc::Sender,
     future::{select, Either, FutureExt},
     pin_mut, StreamExt,
 };
+use hopr_async_runtime::clock::{Clock, RealClock};
 use hopr_db_api::peers::HoprDbPeersOperations;
-use hopr_primitive_types::traits::SaturatingSub;
 use libp2p_identity::PeerId;
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
 use validator::Validate;
 
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 use hopr_metrics::{histogram_start_measure, metrics::SimpleHistogram};
@@ -24,13 +25,19 @@ lazy_static::lazy_static! {
             "Measures total time in seconds it takes to probe all other nodes",
             vec![0.5, 1.0, 2.5, 5.0, 10.0, 15.0, 30.0, 60.0],
         ).unwrap();
+    static ref METRIC_HEARTBEAT_ROUND_DURATION: SimpleHistogram =
+        SimpleHistogram::new(
+            "hopr_heartbeat_round_duration_sec",
+            "Measures time in seconds it takes to probe the peers of a heartbeat round, excluding the wait for the next round",
+            vec![0.5, 1.0, 2.5, 5.0, 10.0, 15.0, 30.0, 60.0],
+        ).unwrap();
 }
 
 use hopr_platform::time::native::current_time;
 
 use crate::constants::{
     DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_HEARTBEAT_INTERVAL_VARIANCE, DEFAULT_HEARTBEAT_THRESHOLD,
-    DEFAULT_MAX_PARALLEL_PINGS,
+    DEFAULT_HEARTBEAT_WARMUP, DEFAULT_MAX_PARALLEL_PINGS,
 };
 use crate::network::Network;
 use crate::ping::Pinging;
@@ -61,6 +68,12 @@ pub struct HeartbeatConfig {
     #[serde(default = "default_heartbeat_threshold")]
     #[default(default_heartbeat_threshold())]
     pub threshold: std::time::Duration,
+    /// Time since the start of the heartbeat in seconds during which the probe timeouts are recorded,
+    /// but not penalized, because the peers have not been probed yet
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "default_heartbeat_warmup")]
+    #[default(default_heartbeat_warmup())]
+    pub warmup: std::time::Duration,
 }
 
 #[inline]
@@ -83,6 +96,12 @@ fn default_heartbeat_variance() -> std::time::Duration {
     DEFAULT_HEARTBEAT_INTERVAL_VARIANCE
 }
 
+#[inline]
+fn default_heartbeat_warmup() -> std::time::Duration {
+    DEFAULT_HEARTBEAT_WARMUP
+}
+
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use tracing::error;
@@ -94,6 +113,38 @@ pub trait HeartbeatExternalApi {
     ///
     /// After a duration of non-pinging based specified by the configurable threshold.
     async fn get_peers(&self, from_timestamp: std::time::SystemTime) -> Vec<PeerId>;
+
+    /// Get all peers the `Network` currently considers reachable.
+    async fn get_reachable_peers(&self) -> HashSet<PeerId>;
+
+    /// Enables or disables penalizing the peers for the failed probes in the `Network`.
+    fn set_failure_penalties(&self, enabled: bool);
+}
+
+/// Change of the reachability of a peer observed during a heartbeat round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerTransition {
+    /// The peer was unreachable before the round and is reachable after it.
+    BecameReachable(PeerId),
+    /// The peer was reachable before the round and is unreachable after it.
+    BecameUnreachable(PeerId),
+}
+
+/// Aggregated results of a single heartbeat round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepSummary {
+    /// Number of peers selected for probing in the round.
+    pub peers_probed: usize,
+    /// Number of probes answered during the round.
+    pub reachable: usize,
+    /// Number of peers which became unreachable during the round.
+    pub newly_failed: usize,
+    /// Average latency of the answered probes, if any probe was answered.
+    pub average_rtt: Option<std::time::Duration>,
+    /// Time it took to probe the peers, excluding the wait for the next round.
+    pub round_duration: std::time::Duration,
+    /// Peers whose reachability changed during the round.
+    pub transitions: Vec<PeerTransition>,
 }
 
 /// Implementor of the heartbeat external API.
@@ -137,41 +188,109 @@ where
                 vec![]
             })
     }
+
+    async fn get_reachable_peers(&self) -> HashSet<PeerId> {
+        self.network
+            .connected_peers()
+            .await
+            .map(HashSet::from_iter)
+            .unwrap_or_else(|e| {
+                error!(error = %e, "Failed to get the reachable peers for the heartbeat procedure");
+                HashSet::new()
+            })
+    }
+
+    fn set_failure_penalties(&self, enabled: bool) {
+        self.network.set_failure_penalties(enabled)
+    }
 }
 
-pub type AsyncSleepFn =
-    Box<dyn Fn(std::time::Duration) -> std::pin::Pin<Box<dyn futures::Future<Output = ()> + Send>> + Send>;
+/// Warmup window of the [`Heartbeat`], during which the failed probes are not penalized.
+///
+/// The penalties are enabled again once the guard is dropped, which happens when the warmup
+/// has elapsed or when the heartbeat loop is dropped before that.
+struct WarmupGuard<API: HeartbeatExternalApi> {
+    external_api: Arc<API>,
+    until: std::time::Instant,
+}
+
+impl<API: HeartbeatExternalApi> WarmupGuard<API> {
+    fn start(external_api: Arc<API>, until: std::time::Instant) -> Self {
+        external_api.set_failure_penalties(false);
+        Self { external_api, until }
+    }
+}
+
+impl<API: HeartbeatExternalApi> Drop for WarmupGuard<API> {
+    fn drop(&mut self) {
+        info!("Heartbeat warmup ended, failed probes are penalized");
+        self.external_api.set_failure_penalties(true);
+    }
+}
 
 /// Heartbeat mechanism providing the regular trigger and processing for the heartbeat protocol.
 ///
 /// This object provides a single public method that can be polled. Once triggered, it will never
 /// return and will only terminate with an unresolvable error or a panic.
-pub struct Heartbeat<T: Pinging, API: HeartbeatExternalApi> {
+pub struct Heartbeat<T: Pinging, API: HeartbeatExternalApi, C: Clock = RealClock> {
     config: HeartbeatConfig,
     pinger: T,
-    external_api: API,
-    sleep_fn: AsyncSleepFn,
+    external_api: Arc<API>,
+    clock: C,
+    sweep_summaries: Option<Sender<SweepSummary>>,
 }
 
-impl<T: Pinging, API: HeartbeatExternalApi> std::fmt::Debug for Heartbeat<T, API> {
+impl<T: Pinging, API: HeartbeatExternalApi, C: Clock> std::fmt::Debug for Heartbeat<T, API, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Heartbeat").field("config", &self.config).finish()
     }
 }
 
 impl<T: Pinging, API: HeartbeatExternalApi> Heartbeat<T, API> {
-    pub fn new(config: HeartbeatConfig, pinger: T, external_api: API, sleep_fn: AsyncSleepFn) -> Self {
+    pub fn new(config: HeartbeatConfig, pinger: T, external_api: API) -> Self {
+        Self::with_clock(config, pinger, external_api, RealClock)
+    }
+}
+
+impl<T: Pinging, API: HeartbeatExternalApi, C: Clock> Heartbeat<T, API, C> {
+    /// Creates the heartbeat scheduling the rounds using the given `clock`.
+    pub fn with_clock(config: HeartbeatConfig, pinger: T, external_api: API, clock: C) -> Self {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        {
+            // Initialize the lazy statics here
+            lazy_static::initialize(&METRIC_TIME_TO_HEARTBEAT);
+            lazy_static::initialize(&METRIC_HEARTBEAT_ROUND_DURATION);
+        }
+
         Self {
             config,
             pinger,
-            external_api,
-            sleep_fn,
+            external_api: Arc::new(external_api),
+            clock,
+            sweep_summaries: None,
         }
     }
 
+    /// Emits a [`SweepSummary`] into the given channel at the end of each heartbeat round.
+    ///
+    /// The summary is dropped if the channel is full.
+    pub fn with_sweep_summaries(mut self, summaries: Sender<SweepSummary>) -> Self {
+        self.sweep_summaries = Some(summaries);
+        self
+    }
+
+    /// Starts the warmup window, during which the failed probes are not penalized.
+    fn start_warmup(&self) -> Option<WarmupGuard<API>> {
+        (!self.config.warmup.is_zero()).then(|| {
+            info!(warmup = ?self.config.warmup, "Heartbeat warmup started, failed probes are not penalized");
+            WarmupGuard::start(self.external_api.clone(), self.clock.now() + self.config.warmup)
+        })
+    }
+
     #[tracing::instrument(level = "info", skip(self), fields(from_timestamp = tracing::field::debug(current_time())))]
     async fn perform_heartbeat_round(&mut self) {
         let start = current_time();
+        let round_start = self.clock.now();
         let from_timestamp = start.checked_sub(self.config.threshold).unwrap_or(start);
 
         let mut peers = self.external_api.get_peers(from_timestamp).await;
@@ -196,37 +315,101 @@ impl<T: Pinging, API: HeartbeatExternalApi> Heartbeat<T, API> {
 
         let peers_contacted = peers.len();
         debug!(peers = tracing::field::debug(&peers), "Heartbeat round start");
-        let timeout = (self.sleep_fn)(this_round_planned_duration).fuse();
-        let ping_stream = self.pinger.ping(peers);
-
-        pin_mut!(timeout, ping_stream);
-
-        match select(timeout, ping_stream.collect::<Vec<_>>().fuse()).await {
-            Either::Left(_) => debug!("Heartbeat round interrupted by timeout"),
-            Either::Right((v, _)) => {
-                // We intentionally ignore any ping errors here
-                let this_round_actual_duration = current_time().saturating_sub(start);
-                let time_to_wait_for_next_round =
-                    this_round_planned_duration.saturating_sub(this_round_actual_duration);
-
-                let ping_ok = v.iter().filter(|v| v.is_ok()).count();
-                info!(
-                    round_duration_ms = tracing::field::debug(this_round_actual_duration.as_millis()),
-                    time_til_next_round_ms = tracing::field::debug(time_to_wait_for_next_round.as_millis()),
-                    peers_contacted,
-                    ping_ok,
-                    ping_fail = peers_contacted - ping_ok,
-                    "Heartbeat round finished"
-                );
-
-                (self.sleep_fn)(time_to_wait_for_next_round).await
-            }
+
+        // the reachability is only sampled for the summary, the per-peer state stays in the network
+        let reachable_before = match self.sweep_summaries {
+            Some(_) => Some(self.reachability(&peers).await),
+            None => None,
+        };
+        let probed_peers = reachable_before.as_ref().map(|_| peers.clone());
+
+        let mut ping_ok = 0_usize;
+        let mut total_rtt = std::time::Duration::ZERO;
+        let finished = {
+            let timeout = self.clock.sleep(this_round_planned_duration).fuse();
+            // We intentionally ignore any ping errors here
+            let ping_stream = self.pinger.ping(peers).for_each(|result| {
+                if let Ok(rtt) = result {
+                    ping_ok += 1;
+                    total_rtt = total_rtt.saturating_add(rtt);
+                }
+                futures::future::ready(())
+            });
+
+            pin_mut!(timeout, ping_stream);
+
+            matches!(select(timeout, ping_stream.fuse()).await, Either::Right(_))
         };
 
+        let this_round_actual_duration = self.clock.now().saturating_duration_since(round_start);
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_HEARTBEAT_ROUND_DURATION.observe(this_round_actual_duration.as_secs_f64());
+
+        if let (Some(probed_peers), Some(reachable_before)) = (probed_peers, reachable_before) {
+            let reachable_after = self.reachability(&probed_peers).await;
+            let transitions = probed_peers
+                .into_iter()
+                .zip(reachable_before.into_iter().zip(reachable_after))
+                .filter_map(|(peer, states)| match states {
+                    (false, true) => Some(PeerTransition::BecameReachable(peer)),
+                    (true, false) => Some(PeerTransition::BecameUnreachable(peer)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            self.emit_sweep_summary(SweepSummary {
+                peers_probed: peers_contacted,
+                reachable: ping_ok,
+                newly_failed: transitions
+                    .iter()
+                    .filter(|t| matches!(t, PeerTransition::BecameUnreachable(_)))
+                    .count(),
+                average_rtt: (ping_ok > 0).then(|| total_rtt / ping_ok as u32),
+                round_duration: this_round_actual_duration,
+                transitions,
+            });
+        }
+
+        if finished {
+            let time_to_wait_for_next_round = this_round_planned_duration.saturating_sub(this_round_actual_duration);
+
+            info!(
+                round_duration_ms = tracing::field::debug(this_round_actual_duration.as_millis()),
+                time_til_next_round_ms = tracing::field::debug(time_to_wait_for_next_round.as_millis()),
+                peers_contacted,
+                ping_ok,
+                ping_fail = peers_contacted - ping_ok,
+                "Heartbeat round finished"
+            );
+
+            self.clock.sleep(time_to_wait_for_next_round).await
+        } else {
+            debug!("Heartbeat round interrupted by timeout");
+        }
+
         #[cfg(all(feature = "prometheus", not(test)))]
         METRIC_TIME_TO_HEARTBEAT.record_measure(heartbeat_round_timer);
     }
 
+    async fn reachability(&self, peers: &[PeerId]) -> Vec<bool> {
+        let reachable = self.external_api.get_reachable_peers().await;
+        peers.iter().map(|peer| reachable.contains(peer)).collect()
+    }
+
+    fn emit_sweep_summary(&mut self, summary: SweepSummary) {
+        if let Some(summaries) = self.sweep_summaries.as_mut() {
+            if let Err(error) = summaries.try_send(summary) {
+                if error.is_disconnected() {
+                    debug!("Heartbeat sweep summary receiver is gone, stopping the summaries");
+                    self.sweep_summaries = None;
+                } else {
+                    warn!("Heartbeat sweep summary channel is full, dropping the summary");
+                }
+            }
+        }
+    }
+
     /// Heartbeat loop responsible for periodically requesting peers to ping around from the
     /// external API interface.
     ///
@@ -234,8 +417,17 @@ impl<T: Pinging, API: HeartbeatExternalApi> Heartbeat<T, API> {
     ///
     /// This feature should be joined with other internal loops and awaited after all
     /// components have been initialized.
+    /// The failed probes are not penalized during the configured warmup, even if the loop
+    /// is dropped before the warmup has elapsed.
     pub async fn heartbeat_loop(&mut self) {
+        let mut warmup = self.start_warmup();
+
         loop {
+            // The warmup ends once it has elapsed
+            if warmup.as_ref().is_some_and(|warmup| self.clock.now() >= warmup.until) {
+                warmup = None;
+            }
+
             self.perform_heartbeat_round().await
         }
     }
@@ -246,6 +438,7 @@ mod tests {
     use super::*;
     use async_std::task::sleep;
     use futures::Stream;
+    use hopr_async_runtime::clock::MockClock;
     use std::time::Duration;
 
     fn simple_heartbeat_config() -> HeartbeatConfig {
@@ -254,6 +447,7 @@ mod tests {
             interval: std::time::Duration::from_millis(5u64),
             threshold: std::time::Duration::from_millis(0u64),
             max_parallel_probes: 14,
+            warmup: std::time::Duration::ZERO,
         }
     }
 
@@ -270,6 +464,261 @@ mod tests {
         }
     }
 
+    /// Estimator state shared by the scripted pinger and the scripted external API.
+    type Reachability = Arc<std::sync::Mutex<std::collections::HashMap<PeerId, bool>>>;
+
+    /// Pinger replaying the scripted outcome of each round, advancing the clock by the RTTs.
+    struct ScriptedPinger {
+        rounds: std::sync::Mutex<std::collections::VecDeque<Vec<(PeerId, Option<Duration>)>>>,
+        reachability: Reachability,
+        clock: MockClock,
+    }
+
+    impl Pinging for ScriptedPinger {
+        fn ping(&self, _peers: Vec<PeerId>) -> impl Stream<Item = crate::errors::Result<Duration>> {
+            let round = self
+                .rounds
+                .lock()
+                .expect("lock must not be poisoned")
+                .pop_front()
+                .unwrap_or_default();
+
+            let results = round
+                .into_iter()
+                .map(|(peer, rtt)| {
+                    self.reachability
+                        .lock()
+                        .expect("lock must not be poisoned")
+                        .insert(peer, rtt.is_some());
+                    self.clock.advance(rtt.unwrap_or(Duration::from_millis(100)));
+                    rtt.ok_or(crate::errors::NetworkingError::Timeout(1))
+                })
+                .collect::<Vec<_>>();
+
+            futures::stream::iter(results)
+        }
+    }
+
+    struct ScriptedExternalApi {
+        peers: Vec<PeerId>,
+        reachability: Reachability,
+        failure_penalties: Arc<std::sync::Mutex<Vec<bool>>>,
+    }
+
+    #[async_trait]
+    impl HeartbeatExternalApi for ScriptedExternalApi {
+        async fn get_peers(&self, _from_timestamp: std::time::SystemTime) -> Vec<PeerId> {
+            self.peers.clone()
+        }
+
+        async fn get_reachable_peers(&self) -> HashSet<PeerId> {
+            self.reachability
+                .lock()
+                .expect("lock must not be poisoned")
+                .iter()
+                .filter_map(|(peer, reachable)| reachable.then_some(*peer))
+                .collect()
+        }
+
+        fn set_failure_penalties(&self, enabled: bool) {
+            self.failure_penalties
+                .lock()
+                .expect("lock must not be poisoned")
+                .push(enabled);
+        }
+    }
+
+    #[async_std::test]
+    async fn test_heartbeat_should_emit_a_sweep_summary_after_each_round() -> anyhow::Result<()> {
+        let config = HeartbeatConfig {
+            interval: Duration::from_secs(10),
+            ..simple_heartbeat_config()
+        };
+        let clock = MockClock::default();
+        let (a, b, c) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+        let reachability: Reachability = Arc::new(std::sync::Mutex::new([(b, true)].into_iter().collect()));
+        let pinger = ScriptedPinger {
+            rounds: std::sync::Mutex::new(
+                vec![
+                    vec![
+                        (a, Some(Duration::from_millis(10))),
+                        (b, None),
+                        (c, Some(Duration::from_millis(30))),
+                    ],
+                    vec![(a, None), (b, Some(Duration::from_millis(20))), (c, None)],
+                ]
+                .into(),
+            ),
+            reachability: reachability.clone(),
+            clock: clock.clone(),
+        };
+        let api = ScriptedExternalApi {
+            peers: vec![a, b, c],
+            reachability,
+            failure_penalties: Default::default(),
+        };
+
+        let (summary_tx, mut summary_rx) = futures::channel::mpsc::channel(10);
+        let mut heartbeat = Heartbeat::with_clock(config, pinger, api, clock.clone()).with_sweep_summaries(summary_tx);
+
+        let mut summaries = Vec::new();
+        for _ in 0..2 {
+            let (_, summary) = futures::join!(heartbeat.perform_heartbeat_round(), async {
+                let summary = summary_rx.next().await;
+                // let the round finish its wait for the next round
+                clock.advance(config.interval * 2);
+                summary
+            });
+            summaries.push(summary.ok_or_else(|| anyhow::anyhow!("summary must be emitted"))?);
+        }
+
+        let sorted = |mut transitions: Vec<PeerTransition>| {
+            transitions.sort_by_key(|t| match t {
+                PeerTransition::BecameReachable(p) | PeerTransition::BecameUnreachable(p) => *p,
+            });
+            transitions
+        };
+
+        assert_eq!(
+            SweepSummary {
+                peers_probed: 3,
+                reachable: 2,
+                newly_failed: 1,
+                average_rtt: Some(Duration::from_millis(20)),
+                round_duration: Duration::from_millis(140),
+                transitions: sorted(vec![
+                    PeerTransition::BecameReachable(a),
+                    PeerTransition::BecameUnreachable(b),
+                    PeerTransition::BecameReachable(c),
+                ]),
+            },
+            SweepSummary {
+                transitions: sorted(summaries[0].transitions.clone()),
+                ..summaries[0].clone()
+            }
+        );
+
+        assert_eq!(
+            SweepSummary {
+                peers_probed: 3,
+                reachable: 1,
+                newly_failed: 2,
+                average_rtt: Some(Duration::from_millis(20)),
+                round_duration: Duration::from_millis(220),
+                transitions: sorted(vec![
+                    PeerTransition::BecameUnreachable(a),
+                    PeerTransition::BecameReachable(b),
+                    PeerTransition::BecameUnreachable(c),
+                ]),
+            },
+            SweepSummary {
+                transitions: sorted(summaries[1].transitions.clone()),
+                ..summaries[1].clone()
+            }
+        );
+
+        Ok(())
+    }
+
+    fn warmup_heartbeat(
+        warmup: Duration,
+        clock: &MockClock,
+    ) -> (
+        Heartbeat<ScriptedPinger, ScriptedExternalApi, MockClock>,
+        Arc<std::sync::Mutex<Vec<bool>>>,
+    ) {
+        let config = HeartbeatConfig {
+            warmup,
+            ..simple_heartbeat_config()
+        };
+        let failure_penalties = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let api = ScriptedExternalApi {
+            peers: vec![],
+            reachability: Default::default(),
+            failure_penalties: failure_penalties.clone(),
+        };
+        let pinger = ScriptedPinger {
+            rounds: Default::default(),
+            reachability: Default::default(),
+            clock: clock.clone(),
+        };
+
+        (
+            Heartbeat::with_clock(config, pinger, api, clock.clone()),
+            failure_penalties,
+        )
+    }
+
+    #[async_std::test]
+    async fn test_heartbeat_should_not_penalize_failures_during_the_warmup() -> anyhow::Result<()> {
+        let warmup = Duration::from_secs(30);
+        let clock = MockClock::default();
+        let (mut heartbeat, failure_penalties) = warmup_heartbeat(warmup, &clock);
+        let penalties = || failure_penalties.lock().expect("lock must not be poisoned").clone();
+
+        let mut heartbeat_loop = std::pin::pin!(heartbeat.heartbeat_loop());
+        assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+        assert_eq!(
+            vec![false],
+            penalties(),
+            "failures must not be penalized during the warmup"
+        );
+
+        clock.advance(warmup / 2);
+        assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+        assert_eq!(vec![false], penalties(), "warmup must not be over yet");
+
+        clock.advance(warmup / 2);
+        assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+        clock.advance(warmup);
+        assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+        assert_eq!(
+            vec![false, true],
+            penalties(),
+            "failures must be penalized after the warmup"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_heartbeat_should_penalize_failures_when_dropped_during_the_warmup() {
+        let clock = MockClock::default();
+        let (mut heartbeat, failure_penalties) = warmup_heartbeat(Duration::from_secs(30), &clock);
+        let penalties = || failure_penalties.lock().expect("lock must not be poisoned").clone();
+
+        {
+            let mut heartbeat_loop = std::pin::pin!(heartbeat.heartbeat_loop());
+            assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+            assert_eq!(vec![false], penalties());
+        }
+
+        assert_eq!(
+            vec![false, true],
+            penalties(),
+            "failures must be penalized once the loop is dropped"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_heartbeat_should_penalize_failures_right_away_without_a_warmup() {
+        let clock = MockClock::default();
+        let (mut heartbeat, failure_penalties) = warmup_heartbeat(Duration::ZERO, &clock);
+
+        {
+            let mut heartbeat_loop = std::pin::pin!(heartbeat.heartbeat_loop());
+            assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+            clock.advance(Duration::from_secs(1));
+            assert!(futures::poll!(heartbeat_loop.as_mut()).is_pending());
+        }
+
+        assert!(
+            failure_penalties.lock().expect("lock must not be poisoned").is_empty(),
+            "failure penalties must never be disabled"
+        );
+    }
+
     #[async_std::test]
     async fn test_heartbeat_should_loop_multiple_times() {
         let config = simple_heartbeat_config();
@@ -282,12 +731,7 @@ mod tests {
             .times(expected_loop_count as usize..)
             .return_const(vec![PeerId::random(), PeerId::random()]);
 
-        let mut heartbeat = Heartbeat::new(
-            config,
-            DelayingPinger { delay: ping_delay },
-            mock,
-            Box::new(|dur| Box::pin(sleep(dur))),
-        );
+        let mut heartbeat = Heartbeat::new(config, DelayingPinger { delay: ping_delay }, mock);
 
         futures::select!(
             _ = heartbeat.heartbeat_loop().fuse() => {},
@@ -310,12 +754,7 @@ mod tests {
             .times(expected_loop_count..)
             .return_const(vec![PeerId::random(), PeerId::random()]);
 
-        let mut heartbeat = Heartbeat::new(
-            config,
-            DelayingPinger { delay: ping_delay },
-            mock,
-            Box::new(|dur| Box::pin(sleep(dur))),
-        );
+        let mut heartbeat = Heartbeat::new(config, DelayingPinger { delay: ping_delay }, mock);
 
         let tolerance = std::time::Duration::from_millis(2);
         futures::select!(
@@ -20,6 +20,8 @@
 /// Configuration of the network module.
 pub mod config;
 
+/// Classification of the direction in which the peers are reachable.
+pub mod connectivity;
 /// Global constants published from this crate.
 pub mod constants;
 /// Errors that can be generated by the crate.
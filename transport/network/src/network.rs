@@ -1,4 +1,5 @@
 use std::collections::hash_set::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, SystemTime};
 
 use futures::StreamExt;
@@ -11,6 +12,8 @@ pub use hopr_db_api::peers::{HoprDbPeersOperations, PeerOrigin, PeerSelector, Pe
 use hopr_platform::time::native::current_time;
 
 use crate::config::NetworkConfig;
+pub use crate::connectivity::ConnectivityDirection;
+use crate::connectivity::{ConnectivityTracker, Interaction};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 use {
@@ -90,6 +93,8 @@ where
     am_i_public: bool,
     cfg: NetworkConfig,
     db: T,
+    connectivity: ConnectivityTracker,
+    penalize_failures: AtomicBool,
     #[cfg(all(feature = "prometheus", not(test)))]
     started_at: Duration,
 }
@@ -115,11 +120,21 @@ where
             am_i_public: true,
             cfg: cfg.clone(),
             db,
+            connectivity: ConnectivityTracker::default(),
+            penalize_failures: AtomicBool::new(true),
             #[cfg(all(feature = "prometheus", not(test)))]
             started_at: current_time().as_unix_timestamp(),
         }
     }
 
+    /// Enables or disables penalizing the peers for the failed probes.
+    ///
+    /// While disabled, a failed probe is still recorded, but it neither lowers the quality of the peer,
+    /// nor increases its backoff, nor closes the connection to it.
+    pub fn set_failure_penalties(&self, enabled: bool) {
+        self.penalize_failures.store(enabled, Ordering::Relaxed);
+    }
+
     /// Check whether the PeerId is present in the network
     pub async fn has(&self, peer: &PeerId) -> bool {
         peer == &self.me || self.db.get_network_peer(peer).await.is_ok_and(|p| p.is_some())
@@ -134,6 +149,16 @@ where
                 .is_ok_and(|ps| ps.is_some_and(|p| p.is_ignored(current_time(), self.cfg.ignore_timeframe)))
     }
 
+    /// Checks if the peer is present in the network and its quality is above the offline threshold.
+    pub async fn is_connected(&self, peer: &PeerId) -> bool {
+        let minimum_quality = self.cfg.quality_offline_threshold;
+        peer != &self.me
+            && self
+                .get(peer)
+                .await
+                .is_ok_and(|ps| ps.is_some_and(|p| p.get_quality() > minimum_quality))
+    }
+
     /// Add a new peer into the network
     ///
     /// Each peer must have an origin specification.
@@ -189,6 +214,22 @@ where
         }
     }
 
+    /// Records a heartbeat ping initiated by the peer.
+    ///
+    /// Together with the results of our own probes passed to [`Network::update`], this classifies
+    /// the [`ConnectivityDirection`] of the peer, which is then reported by [`Network::connectivity`].
+    /// The pings of the peers not probed by us yet are ignored.
+    pub fn record_inbound_ping(&self, peer: &PeerId) -> ConnectivityDirection {
+        self.connectivity
+            .record_if_tracked(peer, Interaction::PingReceived, &self.cfg)
+            .unwrap_or_default()
+    }
+
+    /// Connectivity direction of the peer classified from the recent heartbeat interactions.
+    pub fn connectivity(&self, peer: &PeerId) -> ConnectivityDirection {
+        self.connectivity.direction(peer, &self.cfg)
+    }
+
     /// Remove peer from the network
     pub async fn remove(&self, peer: &PeerId) -> crate::errors::Result<()> {
         if peer == &self.me {
@@ -196,6 +237,7 @@ where
         }
 
         self.db.remove_network_peer(peer).await?;
+        self.connectivity.remove(peer);
 
         #[cfg(all(feature = "prometheus", not(test)))]
         {
@@ -225,13 +267,21 @@ where
             entry.heartbeats_sent += 1;
             entry.peer_version = version;
 
+            let penalized = ping_result.is_err() && self.penalize_failures.load(Ordering::Relaxed);
+            let interaction = if ping_result.is_ok() {
+                Interaction::ProbeAnswered
+            } else {
+                Interaction::ProbeFailed
+            };
+            self.connectivity.record(peer, interaction, &self.cfg);
+
             if let Ok(latency) = ping_result {
                 entry.last_seen = current_time();
                 entry.last_seen_latency = latency;
                 entry.heartbeats_succeeded += 1;
                 entry.backoff = self.cfg.backoff_min;
                 entry.update_quality(1.0_f64.min(entry.get_quality() + self.cfg.quality_step));
-            } else {
+            } else if penalized {
                 entry.backoff = self.cfg.backoff_max.max(entry.backoff.powf(self.cfg.backoff_exponent));
                 entry.update_quality(0.0_f64.max(entry.get_quality() - self.cfg.quality_step));
 
@@ -240,6 +290,8 @@ where
                 if q < self.cfg.quality_bad_threshold {
                     entry.ignored = Some(current_time());
                 }
+            } else {
+                debug!(%peer, "Not penalizing the failed probe");
             }
 
             let (peer_id, quality) = (entry.id.1, entry.get_quality());
@@ -251,7 +303,7 @@ where
                 self.refresh_metrics(&stats)
             }
 
-            if quality <= self.cfg.quality_offline_threshold {
+            if quality <= self.cfg.quality_offline_threshold && (ping_result.is_ok() || penalized) {
                 Ok(Some(NetworkTriggeredEvent::CloseConnection(peer_id)))
             } else {
                 Ok(Some(NetworkTriggeredEvent::UpdateQuality(peer_id, quality)))
@@ -354,7 +406,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::network::{Health, Network, NetworkConfig, NetworkTriggeredEvent, PeerOrigin};
+    use crate::network::{ConnectivityDirection, Health, Network, NetworkConfig, NetworkTriggeredEvent, PeerOrigin};
     use anyhow::Context;
     use hopr_crypto_types::keypairs::{ChainKeypair, Keypair, OffchainKeypair};
     use hopr_platform::time::native::current_time;
@@ -509,6 +561,34 @@ mod tests {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn test_network_should_report_the_connectivity_direction_of_a_peer() -> anyhow::Result<()> {
+        let peer: PeerId = OffchainKeypair::random().public().into();
+        let me: PeerId = OffchainKeypair::random().public().into();
+
+        let peers = basic_network(&me).await?;
+
+        peers.add(&peer, PeerOrigin::IncomingConnection, vec![]).await?;
+
+        for _ in 0..5 {
+            peers
+                .update(&peer, Ok(std::time::Duration::from_millis(10)), None)
+                .await?;
+        }
+        assert_eq!(ConnectivityDirection::OutboundOnly, peers.connectivity(&peer));
+
+        for _ in 0..5 {
+            peers.record_inbound_ping(&peer);
+        }
+
+        assert_eq!(ConnectivityDirection::Bidirectional, peers.connectivity(&peer));
+
+        peers.remove(&peer).await?;
+        assert_eq!(ConnectivityDirection::Unknown, peers.connectivity(&peer));
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn test_network_update_should_merge_metadata() -> anyhow::Result<()> {
         let peer: PeerId = OffchainKeypair::random().public().into();
@@ -605,6 +685,48 @@ mod tests {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn test_network_should_record_but_not_penalize_failed_heartbeats_when_penalties_are_disabled(
+    ) -> anyhow::Result<()> {
+        let peer: PeerId = OffchainKeypair::random().public().into();
+        let me: PeerId = OffchainKeypair::random().public().into();
+
+        let peers = basic_network(&me).await?;
+
+        peers.add(&peer, PeerOrigin::IncomingConnection, vec![]).await?;
+        peers
+            .update(&peer, Ok(std::time::Duration::from_millis(123_u64)), None)
+            .await?;
+        let before = peers.get(&peer).await?.expect("the peer record should be present");
+
+        peers.set_failure_penalties(false);
+        for _ in 0..5 {
+            assert!(
+                !matches!(
+                    peers.update(&peer, Err(()), None).await?,
+                    Some(NetworkTriggeredEvent::CloseConnection(_))
+                ),
+                "connection must not be closed for a failure that is not penalized"
+            );
+        }
+
+        let actual = peers.get(&peer).await?.expect("the peer record should be present");
+        assert_eq!(actual.heartbeats_sent, before.heartbeats_sent + 5);
+        assert_eq!(actual.heartbeats_succeeded, before.heartbeats_succeeded);
+        assert_eq!(actual.get_quality(), before.get_quality());
+        assert_eq!(actual.backoff, before.backoff);
+        assert!(!peers.is_ignored(&peer).await);
+
+        peers.set_failure_penalties(true);
+        peers.update(&peer, Err(()), None).await?;
+
+        let actual = peers.get(&peer).await?.expect("the peer record should be present");
+        assert!(actual.get_quality() < before.get_quality());
+        assert!(actual.backoff > before.backoff);
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn test_network_peer_should_be_listed_for_the_ping_if_last_recorded_later_than_reference(
     ) -> anyhow::Result<()> {
@@ -716,6 +838,33 @@ mod tests {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn test_network_should_consider_a_peer_connected_only_above_the_offline_threshold() -> anyhow::Result<()> {
+        let peer: PeerId = OffchainKeypair::random().public().into();
+        let me: PeerId = OffchainKeypair::random().public().into();
+
+        let peers = basic_network(&me).await?;
+        assert!(!peers.is_connected(&peer).await);
+
+        peers.add(&peer, PeerOrigin::IncomingConnection, vec![]).await?;
+        assert!(!peers.is_connected(&peer).await);
+
+        for _ in 0..10 {
+            peers
+                .update(&peer, Ok(std::time::Duration::from_millis(10)), None)
+                .await?;
+        }
+        assert!(peers.is_connected(&peer).await);
+
+        for _ in 0..10 {
+            peers.update(&peer, Err(()), None).await?;
+        }
+        assert!(!peers.is_connected(&peer).await);
+        assert!(!peers.is_connected(&me).await);
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn test_network_should_close_connection_to_peer_once_it_reaches_the_lowest_possible_quality(
     ) -> anyhow::Result<()> {
@@ -4,6 +4,7 @@ pub const HOPR_SWARM_IDLE_CONNECTION_TIMEOUT: std::time::Duration = std::time::D
 /// P2P protocol identifiers
 pub(crate) const HOPR_HEARTBEAT_PROTOCOL_V_0_1_0: &str = "/hopr/heartbeat/0.1.0";
 pub(crate) const HOPR_TICKET_AGGREGATION_PROTOCOL_V_0_1_0: &str = "/hopr/ticket-aggregation/0.1.0";
+pub(crate) const HOPR_TICKET_AGGREGATION_CANCEL_PROTOCOL_V_0_1_0: &str = "/hopr/ticket-aggregation-cancel/0.1.0";
 
 // Swarm configuration
 /// The maximum number of concurrently dialed (outbound) peers.
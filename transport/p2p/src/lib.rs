@@ -41,7 +41,10 @@ use hopr_transport_network::network::NetworkTriggeredEvent;
 use hopr_transport_network::ping::PingQueryReplier;
 use hopr_transport_protocol::PeerDiscovery;
 
-use crate::constants::{HOPR_HEARTBEAT_PROTOCOL_V_0_1_0, HOPR_TICKET_AGGREGATION_PROTOCOL_V_0_1_0};
+use crate::constants::{
+    HOPR_HEARTBEAT_PROTOCOL_V_0_1_0, HOPR_TICKET_AGGREGATION_CANCEL_PROTOCOL_V_0_1_0,
+    HOPR_TICKET_AGGREGATION_PROTOCOL_V_0_1_0,
+};
 
 pub const MSG_ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
@@ -53,6 +56,10 @@ pub struct Ping(pub ControlMessage);
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pong(pub ControlMessage, pub String);
 
+/// `Ticket aggregation cancel` protocol notice aborting the aggregation requested from the counterparty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregationCancel;
+
 // Control object for the streams over the HOPR protocols
 #[derive(Clone)]
 pub struct HoprStreamProtocolControl {
@@ -101,9 +108,14 @@ pub struct HoprNetworkBehavior {
     streams: libp2p_stream::Behaviour,
     heartbeat_generator: behavior::heartbeat::Behaviour,
     ticket_aggregation_behavior: behavior::ticket_aggregation::Behaviour,
+    /// Outgoing heartbeat probes
     pub heartbeat: libp2p::request_response::cbor::Behaviour<Ping, Pong>,
+    /// Responses to the incoming heartbeat probes
+    pub heartbeat_responder: libp2p::request_response::cbor::Behaviour<Ping, Pong>,
     pub ticket_aggregation:
         libp2p::request_response::cbor::Behaviour<Vec<TransferableWinningTicket>, std::result::Result<Ticket, String>>,
+    /// Notices canceling the sent ticket aggregation requests
+    pub ticket_aggregation_cancel: libp2p::request_response::cbor::Behaviour<AggregationCancel, ()>,
     // WARNING: the order of struct members is important, `discovery` must be the last member,
     // because the request_response components remove the peer from its peer store after a failed
     // dial operation and the discovery mechanism is responsible for populating all peer stores.
@@ -124,7 +136,8 @@ impl HoprNetworkBehavior {
         onchain_events: U,
         heartbeat_requests: V,
         ticket_aggregation_processed_events: W,
-        hb_timeout: std::time::Duration,
+        hb_probe_timeout: std::time::Duration,
+        hb_responder_timeout: std::time::Duration,
         ticket_aggregation_timeout: std::time::Duration,
     ) -> Self
     where
@@ -140,12 +153,20 @@ impl HoprNetworkBehavior {
             ticket_aggregation_behavior: behavior::ticket_aggregation::Behaviour::new(
                 ticket_aggregation_processed_events,
             ),
+            // The probing and responding sides are separate behaviors, so that each can have its own timeout
             heartbeat: libp2p::request_response::cbor::Behaviour::<Ping, Pong>::new(
                 [(
                     StreamProtocol::new(HOPR_HEARTBEAT_PROTOCOL_V_0_1_0),
-                    libp2p::request_response::ProtocolSupport::Full,
+                    libp2p::request_response::ProtocolSupport::Outbound,
                 )],
-                libp2p::request_response::Config::default().with_request_timeout(hb_timeout),
+                libp2p::request_response::Config::default().with_request_timeout(hb_probe_timeout),
+            ),
+            heartbeat_responder: libp2p::request_response::cbor::Behaviour::<Ping, Pong>::new(
+                [(
+                    StreamProtocol::new(HOPR_HEARTBEAT_PROTOCOL_V_0_1_0),
+                    libp2p::request_response::ProtocolSupport::Inbound,
+                )],
+                libp2p::request_response::Config::default().with_request_timeout(hb_responder_timeout),
             ),
             ticket_aggregation: libp2p::request_response::cbor::Behaviour::<
                 Vec<TransferableWinningTicket>,
@@ -157,6 +178,13 @@ impl HoprNetworkBehavior {
                 )],
                 libp2p::request_response::Config::default().with_request_timeout(ticket_aggregation_timeout),
             ),
+            ticket_aggregation_cancel: libp2p::request_response::cbor::Behaviour::<AggregationCancel, ()>::new(
+                [(
+                    StreamProtocol::new(HOPR_TICKET_AGGREGATION_CANCEL_PROTOCOL_V_0_1_0),
+                    libp2p::request_response::ProtocolSupport::Full,
+                )],
+                libp2p::request_response::Config::default().with_request_timeout(ticket_aggregation_timeout),
+            ),
         }
     }
 }
@@ -174,6 +202,7 @@ pub enum HoprNetworkBehaviorEvent {
     TicketAggregation(
         libp2p::request_response::Event<Vec<TransferableWinningTicket>, std::result::Result<Ticket, String>>,
     ),
+    TicketAggregationCancel(libp2p::request_response::Event<AggregationCancel, ()>),
     KeepAlive(void::Void),
 }
 
@@ -224,4 +253,10 @@ impl From<libp2p::request_response::Event<Vec<TransferableWinningTicket>, std::r
     }
 }
 
+impl From<libp2p::request_response::Event<AggregationCancel, ()>> for HoprNetworkBehaviorEvent {
+    fn from(event: libp2p::request_response::Event<AggregationCancel, ()>) -> Self {
+        Self::TicketAggregationCancel(event)
+    }
+}
+
 pub use swarm::HoprSwarm;
@@ -16,10 +16,10 @@ use hopr_transport_protocol::{
     PeerDiscovery,
 };
 
-use crate::{constants, errors::Result, HoprNetworkBehavior, HoprNetworkBehaviorEvent, Ping, Pong};
+use crate::{constants, errors::Result, AggregationCancel, HoprNetworkBehavior, HoprNetworkBehaviorEvent, Ping, Pong};
 
 #[cfg(all(feature = "prometheus", not(test)))]
-use hopr_metrics::metrics::SimpleGauge;
+use hopr_metrics::metrics::{SimpleCounter, SimpleGauge};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 lazy_static::lazy_static! {
@@ -27,6 +27,10 @@ lazy_static::lazy_static! {
         "hopr_transport_p2p_opened_connection_count",
         "Number of currently open connections"
     ).unwrap();
+    static ref METRIC_DROPPED_HEARTBEAT_PINGS: SimpleCounter = SimpleCounter::new(
+        "hopr_dropped_heartbeat_pings_count",
+        "Number of incoming heartbeat pings dropped because the responder queue was full",
+    ).unwrap();
 }
 
 /// Build objects comprising the p2p network.
@@ -85,7 +89,8 @@ where
                 indexer_update_input,
                 heartbeat_requests,
                 ticket_aggregation_interactions,
-                protocol_cfg.heartbeat.timeout,
+                protocol_cfg.heartbeat.probe_timeout,
+                protocol_cfg.heartbeat.responder_timeout,
                 protocol_cfg.ticket_aggregation.timeout,
             )
         })
@@ -202,6 +207,7 @@ impl HoprSwarm {
         HoprSwarmWithProcessors {
             swarm: self,
             ticket_aggregation_writer,
+            heartbeat_responder: None,
         }
     }
 }
@@ -246,9 +252,19 @@ impl From<(PeerId, Box<[u8]>)> for Inputs {
 pub type TicketAggregationRequestType = OutboundRequestId;
 pub type TicketAggregationResponseType = ResponseChannel<std::result::Result<Ticket, String>>;
 
+pub type HeartbeatResponseChannel = ResponseChannel<Pong>;
+/// Incoming ping passed to the heartbeat responder along with the channel to answer it on.
+pub type HeartbeatPing = (PeerId, Ping, HeartbeatResponseChannel);
+/// Answer of the heartbeat responder to the ping received on the channel.
+pub type HeartbeatPong = (PeerId, Pong, HeartbeatResponseChannel);
+
 pub struct HoprSwarmWithProcessors {
     swarm: HoprSwarm,
     ticket_aggregation_writer: TicketAggregationActions<TicketAggregationResponseType, TicketAggregationRequestType>,
+    heartbeat_responder: Option<(
+        futures::channel::mpsc::Sender<HeartbeatPing>,
+        futures::channel::mpsc::Receiver<HeartbeatPong>,
+    )>,
 }
 
 impl std::fmt::Debug for HoprSwarmWithProcessors {
@@ -258,6 +274,20 @@ impl std::fmt::Debug for HoprSwarmWithProcessors {
 }
 
 impl HoprSwarmWithProcessors {
+    /// Hands the incoming heartbeat pings over to a dedicated responder (see
+    /// [`hopr_transport_protocol::heartbeat::responder`]) instead of answering them in the swarm loop.
+    ///
+    /// Each direction has a channel of its own: the pings that do not fit into the `pings` channel are dropped,
+    /// while the `pongs` are sent as they come.
+    pub fn with_heartbeat_responder(
+        mut self,
+        pings: futures::channel::mpsc::Sender<HeartbeatPing>,
+        pongs: futures::channel::mpsc::Receiver<HeartbeatPong>,
+    ) -> Self {
+        self.heartbeat_responder = Some((pings, pongs));
+        self
+    }
+
     /// Main p2p loop that instantiates a new libp2p::Swarm instance and sets up listening and reacting pipelines
     /// running in a neverending loop future.
     ///
@@ -267,6 +297,9 @@ impl HoprSwarmWithProcessors {
     pub async fn run(self, version: String) {
         let mut swarm: libp2p::Swarm<HoprNetworkBehavior> = self.swarm.into();
 
+        #[cfg(all(feature = "prometheus", not(test)))]
+        lazy_static::initialize(&METRIC_DROPPED_HEARTBEAT_PINGS);
+
         // NOTE: an improvement would be a forgetting cache for the active requests
         let active_pings: moka::future::Cache<libp2p::request_response::OutboundRequestId, PingQueryReplier> =
             moka::future::CacheBuilder::new(1000)
@@ -279,8 +312,17 @@ impl HoprSwarmWithProcessors {
             .time_to_live(std::time::Duration::from_secs(40))
             .build();
 
+        let (aggregation_cancel_tx, mut aggregation_cancel_rx) =
+            futures::channel::mpsc::unbounded::<(PeerId, OutboundRequestId)>();
+
         let mut aggregation_writer = self.ticket_aggregation_writer;
 
+        // Without a responder, the pings are answered directly in the loop
+        let (mut heartbeat_pings, mut heartbeat_pongs) = match self.heartbeat_responder {
+            Some((pings, pongs)) => (Some(pings), pongs),
+            None => (None, futures::channel::mpsc::channel(0).1),
+        };
+
         loop {
             select! {
                 event = swarm.select_next_some() => match event {
@@ -305,7 +347,10 @@ impl HoprSwarmWithProcessors {
                                     libp2p::request_response::Message::<Vec<TransferableWinningTicket>, std::result::Result<Ticket, String>>::Response {
                                         request_id, response
                                     } => {
-                                        if let Err(e) = aggregation_writer.receive_ticket(peer, response, request_id) {
+                                        if active_aggregation_requests.get(&request_id).await.is_some_and(|finalizer| finalizer.is_canceled()) {
+                                            active_aggregation_requests.invalidate(&request_id).await;
+                                            debug!(%peer, %request_id, %connection_id, "Discarding the response to a canceled aggregation request");
+                                        } else if let Err(e) = aggregation_writer.receive_ticket(peer, response, request_id) {
                                             error!(%peer, %request_id, %connection_id, error = %e,  "Failed to receive aggregated ticket");
                                         }
                                     }
@@ -326,6 +371,47 @@ impl HoprSwarmWithProcessors {
                             },
                         }
                     }
+                    SwarmEvent::Behaviour(HoprNetworkBehaviorEvent::TicketAggregationCancel(event)) => {
+                        let _span = tracing::span!(tracing::Level::DEBUG, "swarm protocol", protocol = "/hopr/ticket-aggregation-cancel/0.1.0");
+                        match event {
+                            libp2p::request_response::Event::<AggregationCancel, ()>::Message {
+                                peer,
+                                message,
+                                connection_id
+                            } => {
+                                match message {
+                                    libp2p::request_response::Message::<AggregationCancel, ()>::Request {
+                                        request_id, channel, ..
+                                    } => {
+                                        trace!(%peer, %request_id, %connection_id, "Received a ticket aggregation cancel notice");
+
+                                        if let Err(e) = aggregation_writer.receive_aggregation_cancel(peer) {
+                                            error!(%peer, %request_id, %connection_id, error = %e, "Failed to process a ticket aggregation cancel notice");
+                                        }
+                                        if swarm.behaviour_mut().ticket_aggregation_cancel.send_response(channel, ()).is_err() {
+                                            debug!(%peer, %request_id, %connection_id, "Failed to acknowledge a ticket aggregation cancel notice");
+                                        }
+                                    },
+                                    libp2p::request_response::Message::<AggregationCancel, ()>::Response {
+                                        request_id, ..
+                                    } => {
+                                        trace!(%peer, %request_id, %connection_id, "Ticket aggregation cancel notice acknowledged");
+                                    }
+                                }
+                            },
+                            libp2p::request_response::Event::<AggregationCancel, ()>::OutboundFailure {
+                                peer, request_id, error, connection_id
+                            } => {
+                                debug!(%peer, %request_id, %connection_id, %error, "Failed to send an aggregation cancel notice");
+                            },
+                            libp2p::request_response::Event::<AggregationCancel, ()>::InboundFailure {
+                                peer, request_id, error, connection_id
+                            } => {
+                                debug!(%peer, %request_id, %connection_id, %error, "Failed to receive an aggregation cancel notice");
+                            },
+                            libp2p::request_response::Event::<AggregationCancel, ()>::ResponseSent {..} => {},
+                        }
+                    }
                     SwarmEvent::Behaviour(HoprNetworkBehaviorEvent::Heartbeat(event)) => {
                         let _span = tracing::span!(tracing::Level::DEBUG, "swarm protocol", protocol = "/hopr/heartbeat/0.1.0");
                         match event {
@@ -340,9 +426,19 @@ impl HoprSwarmWithProcessors {
                                     } => {
                                         trace!(%peer, %request_id, %connection_id, "Received a heartbeat Ping");
 
-                                        if let Ok(challenge_response) = ControlMessage::generate_pong_response(&request.0)
+                                        if let Some(pings) = heartbeat_pings.as_mut() {
+                                            // The only place where the pings are dropped, when the responder falls behind
+                                            if let Err(e) = pings.try_send((peer, request, channel)) {
+                                                #[cfg(all(feature = "prometheus", not(test)))]
+                                                if e.is_full() {
+                                                    METRIC_DROPPED_HEARTBEAT_PINGS.increment();
+                                                }
+
+                                                warn!(%peer, %request_id, %connection_id, full = e.is_full(), "Failed to pass a Ping request to the heartbeat responder");
+                                            }
+                                        } else if let Ok(challenge_response) = ControlMessage::generate_pong_response(&request.0)
                                         {
-                                            if swarm.behaviour_mut().heartbeat.send_response(channel, Pong(challenge_response, version.clone())).is_err() {
+                                            if swarm.behaviour_mut().heartbeat_responder.send_response(channel, Pong(challenge_response, version.clone())).is_err() {
                                                 error!(%peer, %request_id, %connection_id, "Failed to reply to a Ping request");
                                             };
                                         }
@@ -386,10 +482,29 @@ impl HoprSwarmWithProcessors {
                         let _span = tracing::span!(tracing::Level::DEBUG, "swarm behavior", behavior="ticket aggregation");
 
                         match event {
+                            TicketAggregationProcessed::Send(peer, acked_tickets, finalizer) if finalizer.is_canceled() => {
+                                debug!(%peer, "Not sending canceled request to aggregate {} tickets", acked_tickets.len());
+                                if let Some(channel) = acked_tickets.first().map(|t| t.ticket.channel_id) {
+                                    if let Err(e) = aggregation_writer.rollback_canceled(&channel) {
+                                        error!(%peer, %channel, error = %e, "Failed to roll back the canceled aggregation");
+                                    }
+                                }
+                            },
                             TicketAggregationProcessed::Send(peer, acked_tickets, finalizer) => {
                                 let ack_tkt_count = acked_tickets.len();
+                                let channel = acked_tickets.first().map(|t| t.ticket.channel_id);
                                 let request_id = swarm.behaviour_mut().ticket_aggregation.send_request(&peer, acked_tickets);
                                 debug!(%peer, %request_id, "Sending request to aggregate {ack_tkt_count} tickets");
+                                if let Some(channel) = channel {
+                                    let mut writer = aggregation_writer.clone();
+                                    let cancel_tx = aggregation_cancel_tx.clone();
+                                    finalizer.on_cancel(move || {
+                                        if let Err(e) = writer.rollback_canceled(&channel) {
+                                            error!(%peer, %channel, error = %e, "Failed to roll back the canceled aggregation");
+                                        }
+                                        let _ = cancel_tx.unbounded_send((peer, request_id));
+                                    });
+                                }
                                 active_aggregation_requests.insert(request_id, finalizer).await;
                             },
                             TicketAggregationProcessed::Reply(peer, ticket, response) => {
@@ -409,6 +524,22 @@ impl HoprSwarmWithProcessors {
                                     }
                                 }
                             }
+                            TicketAggregationProcessed::Refused(peer, error, request) => {
+                                match active_aggregation_requests.remove(&request).await {
+                                    Some(finalizer) => {
+                                        active_aggregation_requests.run_pending_tasks().await;
+                                        if let Some(channel) = finalizer.channel() {
+                                            if let Err(e) = aggregation_writer.rollback_refused(&channel) {
+                                                error!(%peer, %channel, error = %e, "Failed to roll back the refused aggregation");
+                                            }
+                                        }
+                                        finalizer.fail(error);
+                                    },
+                                    None => {
+                                        warn!(%peer, request_id = %request, "Response already handled")
+                                    }
+                                }
+                            }
                         }
                     }
                     SwarmEvent::Behaviour(HoprNetworkBehaviorEvent::HeartbeatGenerator(event)) => {
@@ -529,6 +660,18 @@ impl HoprSwarmWithProcessors {
                         trace!(transport="libp2p", peer = %peer_id, multiaddress = %address, "New peer stored in swarm")
                     },
                     _ => trace!(transport="libp2p", "Unsupported enum option detected")
+                },
+                (peer, request_id) = aggregation_cancel_rx.select_next_some() => {
+                    // The finalizer is kept, so that the response to the canceled request is discarded
+                    debug!(%peer, %request_id, "Aggregation request canceled after being sent");
+
+                    // The counterparty is told to abort the aggregation over the dedicated protocol
+                    swarm.behaviour_mut().ticket_aggregation_cancel.send_request(&peer, AggregationCancel);
+                },
+                (peer, pong, channel) = heartbeat_pongs.select_next_some() => {
+                    if swarm.behaviour_mut().heartbeat_responder.send_response(channel, pong).is_err() {
+                        error!(%peer, "Failed to reply to a Ping request");
+                    }
                 }
             }
         }
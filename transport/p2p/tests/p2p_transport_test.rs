@@ -218,3 +218,46 @@ async fn p2p_only_communication_quic() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg_attr(feature = "runtime-async-std", async_std::test)]
+async fn heartbeat_responder_should_not_wait_for_a_stalled_ping_beyond_its_timeout() -> anyhow::Result<()> {
+    use futures::AsyncReadExt;
+    use hopr_transport_protocol::stream::BidirectionalStreamControl;
+
+    let (api1, swarm1) = build_p2p_swarm(Announcement::QUIC).await?;
+    let (api2, swarm2) = build_p2p_swarm(Announcement::QUIC).await?;
+
+    let responder_timeout = ProtocolConfig::default().heartbeat.responder_timeout;
+    assert!(responder_timeout < ProtocolConfig::default().heartbeat.probe_timeout);
+
+    // The stalling peer opens raw streams over the heartbeat protocol instead of using its heartbeat behavior
+    let stalling_control = swarm2.build_protocol_control("/hopr/heartbeat/0.1.0");
+
+    let _sjh1 = SelfClosingJoinHandle::new(swarm1.run("1.0.0".into()));
+    let _sjh2 = SelfClosingJoinHandle::new(swarm2.run("1.0.0".into()));
+
+    api2.update_from_announcements
+        .unbounded_send(PeerDiscovery::Announce(api1.me, vec![api1.address.clone()]))
+        .context("failed to send announcement")?;
+    api2.update_from_announcements
+        .unbounded_send(PeerDiscovery::Allow(api1.me))
+        .context("failed to send announcement")?;
+
+    // Wait for node listen_on and announcements
+    sleep(std::time::Duration::from_secs(3)).await;
+
+    let mut stream = stalling_control
+        .open(api1.me)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to open stream: {e}"))?;
+
+    // Never send the Ping, the responder must give up on the stream after its timeout
+    let mut buf = [0u8; 64];
+    let read = timeout(4 * responder_timeout, stream.read(&mut buf))
+        .await
+        .context("responder must close the stalled stream")?;
+
+    assert!(matches!(read, Ok(0) | Err(_)), "responder must not send any data");
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use criterion::async_executor::AsyncStdExecutor;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::StreamExt;
+use hopr_crypto_types::prelude::*;
+use hopr_transport_protocol::ack::signer::{default_ack_signing_workers, AckSigner};
+use libp2p::PeerId;
+
+const SAMPLE_SIZE: usize = 20;
+const ACK_COUNT: usize = 2000;
+const PEER_COUNT: usize = 32;
+
+pub fn ack_signing_pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ack_signing_pool");
+    group.sample_size(SAMPLE_SIZE);
+    group.throughput(Throughput::Elements(ACK_COUNT as u64));
+
+    let keypair = OffchainKeypair::random();
+    let peers = (0..PEER_COUNT)
+        .map(|_| PeerId::from(*OffchainKeypair::random().public()))
+        .collect::<Vec<_>>();
+
+    // Single signing worker compared to the default pool of one worker per CPU
+    let mut worker_counts = vec![1, default_ack_signing_workers()];
+    worker_counts.dedup();
+
+    for workers in worker_counts {
+        group.bench_with_input(BenchmarkId::new("workers", workers), &workers, |b, &workers| {
+            b.to_async(AsyncStdExecutor).iter(|| {
+                let (signer, signed) = AckSigner::new(keypair.clone(), workers);
+                let peers = peers.clone();
+
+                async move {
+                    let submit = async {
+                        for i in 0..ACK_COUNT {
+                            signer
+                                .sign(peers[i % PEER_COUNT], HalfKey::random())
+                                .await
+                                .expect("signing pool must be running");
+                        }
+                    };
+
+                    let (_, signed) = futures::join!(submit, signed.take(ACK_COUNT).count());
+                    assert_eq!(ACK_COUNT, signed);
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, ack_signing_pool);
+criterion_main!(benches);
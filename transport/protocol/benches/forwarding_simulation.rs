@@ -0,0 +1,46 @@
+use criterion::async_executor::AsyncStdExecutor;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use hopr_crypto_packet::prelude::HoprPacket;
+use hopr_transport_protocol::config::ProtocolConfig;
+use hopr_transport_protocol::simulation::{simulate_forwarding, CountingAllocator};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const SAMPLE_SIZE: usize = 10;
+const PACKET_COUNT: usize = 1000;
+
+pub fn forwarding_simulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("forwarding_simulation");
+    group.sample_size(SAMPLE_SIZE);
+    group.throughput(Throughput::Elements(PACKET_COUNT as u64));
+
+    let cfg = ProtocolConfig::default();
+
+    for payload_size in [100, HoprPacket::PAYLOAD_SIZE - 2] {
+        group.bench_with_input(
+            BenchmarkId::new("relay", format!("{PACKET_COUNT} packets of {payload_size} bytes")),
+            &payload_size,
+            |b, payload_size| {
+                b.to_async(AsyncStdExecutor).iter_custom(|iters| async move {
+                    let mut elapsed = std::time::Duration::ZERO;
+                    for _ in 0..iters {
+                        let report = simulate_forwarding(&cfg, PACKET_COUNT, *payload_size)
+                            .await
+                            .expect("forwarding simulation must succeed");
+                        assert_eq!(report.forwarded, PACKET_COUNT, "all packets must be forwarded");
+
+                        tracing::debug!(?report, "forwarding simulation finished");
+                        elapsed += report.elapsed;
+                    }
+                    elapsed
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, forwarding_simulation);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+#[path = "../tests/common/mod.rs"]
+mod common;
+use common::{create_dbs, create_minimal_topology, random_packets_of_count, resolve_mock_path, PEERS, PEERS_CHAIN};
+
+use criterion::{async_executor::AsyncExecutor, criterion_group, criterion_main, BatchSize, Criterion};
+use hopr_crypto_types::keypairs::Keypair;
+use hopr_crypto_types::prelude::Randomizable;
+use hopr_internal_types::prelude::*;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_primitive_types::prelude::BalanceType;
+use hopr_transport_protocol::bloom::WrappedTagBloomFilter;
+use hopr_transport_protocol::msg::processor::{PacketInteractionConfig, PacketProcessor, RecvOperation};
+use libp2p::PeerId;
+
+const SAMPLE_SIZE: usize = 100;
+const PEER_COUNT: usize = 3;
+
+pub fn packet_processing(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("temporary directory must be constructible");
+    let runtime = criterion::async_executor::AsyncStdExecutor {};
+
+    let (dbs, routing) = runtime.block_on(async {
+        let mut dbs = create_dbs(PEER_COUNT).await.expect("DBs must be constructible");
+        create_minimal_topology(&mut dbs)
+            .await
+            .expect("topology must be constructible");
+
+        let path = resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            PEERS[1..PEER_COUNT].iter().map(|p| p.public().into()).collect(),
+            PEERS_CHAIN[1..PEER_COUNT]
+                .iter()
+                .map(|key| key.public().to_address())
+                .collect(),
+        )
+        .await
+        .expect("path must be constructible");
+
+        let routing = ResolvedTransportRouting::Forward {
+            pseudonym: HoprPseudonym::random(),
+            forward_path: path,
+            return_paths: vec![],
+        };
+
+        (dbs, routing)
+    });
+
+    let processors = dbs
+        .into_iter()
+        .enumerate()
+        .map(|(i, db)| {
+            PacketProcessor::new(
+                db,
+                WrappedTagBloomFilter::new(dir.path().join(format!("tbf_{i}")).to_string_lossy().into_owned()),
+                PacketInteractionConfig::new(
+                    &PEERS[i],
+                    &PEERS_CHAIN[i],
+                    Some(1.0),
+                    Some(BalanceType::HOPR.balance(100)),
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let sender_peer: PeerId = PEERS[0].public().into();
+
+    let mut group = c.benchmark_group("packet_processing");
+    group.sample_size(SAMPLE_SIZE);
+
+    group.bench_function("wrap outgoing packet", |b| {
+        b.iter_batched(
+            || random_packets_of_count(1).remove(0),
+            |data| {
+                processors[0]
+                    .send_blocking(data, routing.clone())
+                    .expect("packet must be wrapped")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Each packet can be received only once, so a fresh packet is wrapped for every iteration
+    group.bench_function("forward incoming packet", |b| {
+        b.iter_batched(
+            || {
+                processors[0]
+                    .send_blocking(random_packets_of_count(1).remove(0), routing.clone())
+                    .expect("packet must be wrapped")
+                    .1
+            },
+            |packet| {
+                assert!(matches!(
+                    processors[1].recv_blocking(&sender_peer, packet),
+                    Ok(RecvOperation::Forward { .. })
+                ))
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, packet_processing);
+criterion_main!(benches);
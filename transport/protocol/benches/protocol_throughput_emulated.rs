@@ -10,7 +10,7 @@ use hopr_crypto_types::keypairs::Keypair;
 use hopr_internal_types::prelude::*;
 use hopr_network_types::prelude::ResolvedTransportRouting;
 use hopr_primitive_types::prelude::{Balance, BalanceType};
-use hopr_transport_protocol::msg::processor::{MsgSender, PacketInteractionConfig, PacketSendFinalizer};
+use hopr_transport_protocol::msg::processor::{MsgSender, PacketInteractionConfig, SendMsgInput};
 use libp2p::PeerId;
 
 const SAMPLE_SIZE: usize = 20;
@@ -58,11 +58,7 @@ pub fn protocol_throughput_sender(c: &mut Criterion) {
                         let (_wire_msg_recv_tx, wire_msg_recv_rx) =
                             futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
 
-                        let (api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<(
-                            ApplicationData,
-                            ResolvedTransportRouting,
-                            PacketSendFinalizer,
-                        )>();
+                        let (api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
                         let (api_recv_tx, _api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
 
                         let cfg = PacketInteractionConfig {
@@ -70,17 +66,23 @@ pub fn protocol_throughput_sender(c: &mut Criterion) {
                             chain_keypair: (&PEERS_CHAIN[TESTED_PEER_ID]).clone(),
                             outgoing_ticket_win_prob: Some(1.0),
                             outgoing_ticket_price: Some(Balance::new(1, BalanceType::HOPR)),
+                            resend_unacked_after: None,
+                            max_resends: 0,
+                            pricing: Default::default(),
                         };
 
-                        let processes = hopr_transport_protocol::run_msg_ack_protocol(
+                        let (processes, _control) = hopr_transport_protocol::run_msg_ack_protocol(
                             cfg,
+                            Default::default(),
+                            Default::default(),
                             dbs[TESTED_PEER_ID].clone(),
-                            None,
                             (wire_ack_send_tx, wire_ack_recv_rx),
                             (wire_msg_send_tx, wire_msg_recv_rx),
                             (api_recv_tx, api_send_rx),
+                            Default::default(),
                         )
-                        .await;
+                        .await
+                        .expect("the protocol must start");
 
                         let path = resolve_mock_path(
                             PEERS_CHAIN[TESTED_PEER_ID].public().to_address(),
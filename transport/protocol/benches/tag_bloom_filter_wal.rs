@@ -0,0 +1,80 @@
+use criterion::async_executor::AsyncStdExecutor;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use hopr_crypto_random::random_bytes;
+use hopr_crypto_types::types::PACKET_TAG_LENGTH;
+use hopr_transport_protocol::bloom::{WrappedTagBloomFilter, DEFAULT_TAG_WAL_MAX_BATCH_LATENCY};
+
+const SAMPLE_SIZE: usize = 100;
+const BATCH_SIZES: [u64; 3] = [1, 16, 256];
+
+pub fn tag_bloom_filter_check_and_set(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("temporary directory must be constructible");
+
+    let mut group = c.benchmark_group("tag_bloom_filter_check_and_set");
+    group.sample_size(SAMPLE_SIZE);
+
+    let tbf = WrappedTagBloomFilter::new(dir.path().join("no_wal").to_string_lossy().into_owned());
+    group.bench_function("without write-ahead log", |b| {
+        b.to_async(AsyncStdExecutor).iter(|| {
+            let tbf = tbf.clone();
+            async move { tbf.check_and_set(&random_bytes::<PACKET_TAG_LENGTH>()).await }
+        })
+    });
+
+    let tbf = WrappedTagBloomFilter::new_with_wal(
+        dir.path().join("wal").to_string_lossy().into_owned(),
+        DEFAULT_TAG_WAL_MAX_BATCH_LATENCY,
+    );
+    group.bench_function("with write-ahead log", |b| {
+        b.to_async(AsyncStdExecutor).iter(|| {
+            let tbf = tbf.clone();
+            async move { tbf.check_and_set(&random_bytes::<PACKET_TAG_LENGTH>()).await }
+        })
+    });
+
+    group.finish();
+}
+
+/// Measures the per-packet latency of the durable check, including the fsync of the tag's batch.
+pub fn tag_bloom_filter_check_and_set_durably(c: &mut Criterion) {
+    let dir = tempfile::tempdir().expect("temporary directory must be constructible");
+
+    let mut group = c.benchmark_group("tag_bloom_filter_check_and_set_durably");
+    group.sample_size(SAMPLE_SIZE);
+
+    let tbf = WrappedTagBloomFilter::new_with_wal(
+        dir.path().join("wal").to_string_lossy().into_owned(),
+        DEFAULT_TAG_WAL_MAX_BATCH_LATENCY,
+    );
+    for batch_size in BATCH_SIZES {
+        group.throughput(Throughput::Elements(batch_size));
+        group.bench_function(format!("batch of {batch_size}"), |b| {
+            b.to_async(AsyncStdExecutor).iter(|| {
+                let tbf = tbf.clone();
+                async move {
+                    let tags = (0..batch_size)
+                        .map(|_| random_bytes::<PACKET_TAG_LENGTH>())
+                        .collect::<Vec<_>>();
+                    let checks = futures::future::join_all(tags.iter().map(|tag| tbf.check_and_set_durably(tag)));
+
+                    // Syncs once all the checks of the batch have been queued
+                    let sync = async {
+                        async_std::task::yield_now().await;
+                        tbf.sync_wal().await
+                    };
+
+                    futures::join!(checks, sync).0
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    tag_bloom_filter_check_and_set,
+    tag_bloom_filter_check_and_set_durably
+);
+criterion_main!(benches);
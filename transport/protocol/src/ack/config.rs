@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSeconds};
+use validator::Validate;
+
+use crate::ack::signer::default_ack_signing_workers;
+use crate::stream::SinkFailurePolicy;
+
+/// Handling of the received acknowledgements that cannot be validated.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MalformedAckPolicy {
+    /// The acknowledgement is dropped and counted.
+    #[default]
+    Drop,
+    /// The acknowledgement is dropped and counted, and the peer is banned
+    /// once it has sent `threshold` malformed acknowledgements.
+    ///
+    /// A `threshold` of 0 is treated as 1.
+    Ban { threshold: u32 },
+}
+
+/// Configuration for the `ack` protocol.
+#[serde_as]
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AckProtocolConfig {
+    /// Behavior when sending an acknowledgement to the wire fails
+    #[serde(default)]
+    pub sink_failure_policy: SinkFailurePolicy,
+    /// Time window within which an acknowledgement is expected from a peer a packet was sent to,
+    /// otherwise the acknowledgement is reported as missing.
+    ///
+    /// This is also the maximum age of a pending acknowledgement: older ones are removed by a periodic sweep
+    /// and the acknowledgements arriving for them afterward are rejected as stale. The tickets waiting
+    /// for the acknowledgement in the database are kept for the same time.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "default_ack_expectation_window")]
+    #[default(default_ack_expectation_window())]
+    pub expectation_window: Duration,
+    /// Maximum number of acknowledgements expected from all the peers at the same time.
+    ///
+    /// Once reached, the oldest pending acknowledgement is dropped for each newly sent packet.
+    /// The tickets waiting for the acknowledgement in the database are bounded the same way.
+    #[serde(default = "default_max_pending_acks")]
+    #[default(default_max_pending_acks())]
+    #[validate(range(min = 1))]
+    pub max_pending_acks: usize,
+    /// Time window within which a repeatedly received acknowledgement is recognized as a duplicate
+    /// and not processed again.
+    ///
+    /// Zero disables the duplicate detection.
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "default_duplicate_ack_window")]
+    #[default(default_duplicate_ack_window())]
+    pub duplicate_window: Duration,
+    /// Maximum number of recently received acknowledgements remembered for the duplicate detection.
+    #[serde(default = "default_duplicate_ack_capacity")]
+    #[default(default_duplicate_ack_capacity())]
+    pub duplicate_capacity: u64,
+    /// Number of workers signing the outgoing acknowledgements.
+    ///
+    /// Defaults to the number of available CPUs.
+    #[serde(default = "default_ack_signing_workers")]
+    #[default(default_ack_signing_workers())]
+    #[validate(range(min = 1))]
+    pub signing_workers: usize,
+    /// Handling of the received acknowledgements that cannot be validated
+    #[serde(default)]
+    pub malformed_ack_policy: MalformedAckPolicy,
+}
+
+fn default_ack_expectation_window() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_max_pending_acks() -> usize {
+    crate::ack::processor::DEFAULT_MAX_PENDING_ACKS
+}
+
+fn default_duplicate_ack_window() -> Duration {
+    Duration::from_secs(120)
+}
+
+fn default_duplicate_ack_capacity() -> u64 {
+    100_000
+}
@@ -1,4 +1,7 @@
+pub mod config;
 pub mod processor;
+pub mod signer;
+pub mod stats;
 
 pub mod codec;
 
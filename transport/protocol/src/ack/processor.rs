@@ -1,22 +1,192 @@
-use tracing::trace;
+use futures::channel::mpsc::UnboundedSender;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, trace, warn};
 
+use hopr_async_runtime::clock::{Clock, RealClock};
 use hopr_crypto_types::prelude::*;
+use hopr_db_api::errors::DbError;
 pub use hopr_db_api::protocol::AckResult;
 use hopr_db_api::protocol::HoprDbProtocolOperations;
 use hopr_internal_types::prelude::*;
 use hopr_transport_identity::PeerId;
 
+use crate::ack::config::{AckProtocolConfig, MalformedAckPolicy};
 use crate::errors::{ProtocolError, Result};
+use crate::PeerDiscovery;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::{SimpleCounter, SimpleHistogram};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_STALE_ACKS: SimpleCounter = SimpleCounter::new(
+        "hopr_stale_ack_count",
+        "Number of received acknowledgements rejected as stale or replayed"
+    )
+    .unwrap();
+    static ref METRIC_MALFORMED_ACKS: SimpleCounter = SimpleCounter::new(
+        "hopr_malformed_ack_count",
+        "Number of received acknowledgements rejected as malformed"
+    )
+    .unwrap();
+    static ref METRIC_EXPIRED_PENDING_ACKS: SimpleCounter = SimpleCounter::new(
+        "hopr_expired_pending_acks",
+        "Number of pending acknowledgements dropped for being too old or over the capacity"
+    )
+    .unwrap();
+    static ref METRIC_ACK_LATENCY: SimpleHistogram = SimpleHistogram::new(
+        "hopr_ack_latency_sec",
+        "Time between forwarding a packet and receiving its acknowledgement (seconds)",
+        vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0],
+    )
+    .unwrap();
+}
+
+/// Maximum number of peers whose malformed acknowledgements are counted towards a ban.
+const MALFORMED_ACK_TRACKED_PEERS: u64 = 10_000;
 
 /// Implements protocol acknowledgement logic for acknowledgements
+///
+/// Acknowledgements received repeatedly within the configured
+/// [duplicate window](AckProtocolConfig::duplicate_window) are not processed again
+/// and result in [`AckResult::Duplicate`].
+///
+/// Acknowledgements of challenges that are not awaited (anymore), i.e. acknowledgements that arrived
+/// after the pending acknowledgement expired or that replay an already processed acknowledgement
+/// outside the duplicate window, are rejected with [`ProtocolError::StaleAcknowledgement`].
+/// When the processor [tracks the expectations](AcknowledgementProcessor::with_timeout_tracker), the
+/// acknowledgements from peers no acknowledgement is expected from anymore are rejected the same way,
+/// without being processed. When the processor [checks the freshness](AcknowledgementProcessor::with_freshness_tracker)
+/// of the acknowledgements, so are those solving a challenge not issued within the freshness window,
+/// or already solved before.
+///
+/// Acknowledgements that cannot be validated are rejected with [`ProtocolError::MalformedAcknowledgement`].
+/// Depending on the [malformed acknowledgement policy](AckProtocolConfig::malformed_ack_policy), the peer
+/// repeatedly sending them is banned by a [`PeerDiscovery::Ban`] event sent to the
+/// [ban events](AcknowledgementProcessor::with_ban_events) channel.
 #[derive(Clone)]
-pub struct AcknowledgementProcessor<Db: HoprDbProtocolOperations> {
+pub struct AcknowledgementProcessor<Db: HoprDbProtocolOperations, C: Clock = RealClock> {
     db: Db,
+    recent_acks: Option<moka::future::Cache<HalfKeyChallenge, ()>>,
+    stale_acks: Arc<AtomicU64>,
+    malformed_acks: Arc<AtomicU64>,
+    malformed_acks_per_peer: Option<(u32, moka::future::Cache<PeerId, Arc<AtomicU32>>)>,
+    ban_events: Option<UnboundedSender<PeerDiscovery>>,
+    latencies: Option<AckLatencyTracker<C>>,
+    expectations: Option<AckTimeoutTracker<C>>,
+    freshness: Option<AckFreshnessTracker<C>>,
+    clock: PhantomData<C>,
 }
 
 impl<Db: HoprDbProtocolOperations> AcknowledgementProcessor<Db> {
-    pub fn new(db: Db) -> Self {
-        Self { db }
+    pub fn new(db: Db, cfg: AckProtocolConfig) -> Self {
+        Self::with_clock(db, cfg)
+    }
+}
+
+impl<Db: HoprDbProtocolOperations, C: Clock> AcknowledgementProcessor<Db, C> {
+    /// Same as [`AcknowledgementProcessor::new`], but works with the trackers measuring the time using the clock `C`.
+    pub fn with_clock(db: Db, cfg: AckProtocolConfig) -> Self {
+        Self {
+            db,
+            recent_acks: (!cfg.duplicate_window.is_zero() && cfg.duplicate_capacity > 0).then(|| {
+                moka::future::Cache::builder()
+                    .time_to_live(cfg.duplicate_window)
+                    .max_capacity(cfg.duplicate_capacity)
+                    .build()
+            }),
+            stale_acks: Arc::new(AtomicU64::new(0)),
+            malformed_acks: Arc::new(AtomicU64::new(0)),
+            malformed_acks_per_peer: match cfg.malformed_ack_policy {
+                MalformedAckPolicy::Drop => None,
+                MalformedAckPolicy::Ban { threshold } => Some((
+                    threshold.max(1),
+                    moka::future::Cache::builder()
+                        .max_capacity(MALFORMED_ACK_TRACKED_PEERS)
+                        .build(),
+                )),
+            },
+            ban_events: None,
+            latencies: None,
+            expectations: None,
+            freshness: None,
+            clock: PhantomData,
+        }
+    }
+
+    /// Sends the bans of the peers repeatedly sending malformed acknowledgements into the given channel.
+    pub fn with_ban_events(mut self, ban_events: UnboundedSender<PeerDiscovery>) -> Self {
+        self.ban_events = Some(ban_events);
+        self
+    }
+
+    /// Measures the acknowledgement latency of the packets [forwarded](AckLatencyTracker::forwarded)
+    /// into the given tracker.
+    pub fn with_latency_tracker(mut self, latencies: AckLatencyTracker<C>) -> Self {
+        self.latencies = Some(latencies);
+        self
+    }
+
+    /// Fulfills the expectations of the given tracker by the received acknowledgements, and rejects those
+    /// not matching any expectation, e.g. because it has already [expired](AckTimeoutTracker::expire).
+    pub fn with_timeout_tracker(mut self, expectations: AckTimeoutTracker<C>) -> Self {
+        self.expectations = Some(expectations);
+        self
+    }
+
+    /// Accepts only the acknowledgements solving a challenge [issued](AckFreshnessTracker::issued)
+    /// into the given tracker within its window, each at most once.
+    pub fn with_freshness_tracker(mut self, freshness: AckFreshnessTracker<C>) -> Self {
+        self.freshness = Some(freshness);
+        self
+    }
+
+    fn record_stale(&self, peer: &PeerId) -> ProtocolError {
+        self.stale_acks.fetch_add(1, Ordering::Relaxed);
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_STALE_ACKS.increment();
+
+        debug!(%peer, "Received a stale or replayed acknowledgement");
+        ProtocolError::StaleAcknowledgement
+    }
+
+    /// Number of acknowledgements rejected as stale or replayed.
+    pub fn stale_acks(&self) -> u64 {
+        self.stale_acks.load(Ordering::Relaxed)
+    }
+
+    /// Number of acknowledgements rejected as malformed.
+    pub fn malformed_acks(&self) -> u64 {
+        self.malformed_acks.load(Ordering::Relaxed)
+    }
+
+    async fn record_malformed(&self, peer: &PeerId) {
+        self.malformed_acks.fetch_add(1, Ordering::Relaxed);
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_MALFORMED_ACKS.increment();
+
+        if let Some((threshold, per_peer)) = &self.malformed_acks_per_peer {
+            let count = per_peer
+                .get_with_by_ref(peer, async { Arc::new(AtomicU32::new(0)) })
+                .await
+                .fetch_add(1, Ordering::Relaxed)
+                + 1;
+
+            if count == *threshold {
+                per_peer.invalidate(peer).await;
+                warn!(%peer, count, "Banning peer repeatedly sending malformed acknowledgements");
+
+                if let Some(ban_events) = &self.ban_events {
+                    if let Err(e) = ban_events.unbounded_send(PeerDiscovery::Ban(*peer)) {
+                        error!(%peer, error = %e, "Failed to send the ban of a peer");
+                    }
+                }
+            }
+        }
     }
 
     /// Processes the outgoing acknowledgement.
@@ -30,13 +200,1135 @@ impl<Db: HoprDbProtocolOperations> AcknowledgementProcessor<Db> {
     #[tracing::instrument(level = "debug", skip(self, ack))]
     pub async fn recv(&self, peer: &PeerId, ack: Acknowledgement) -> Result<AckResult> {
         let remote_pk = OffchainPublicKey::try_from(peer)?;
-        self.db
-            .handle_acknowledgement(ack.validate(&remote_pk)?)
-            .await
-            .map_err(|e| {
+        let ack = match ack.validate(&remote_pk) {
+            Ok(ack) => ack,
+            Err(e) => {
+                self.record_malformed(peer).await;
+                debug!(%peer, error = %e, "Received a malformed acknowledgement");
+                return Err(ProtocolError::MalformedAcknowledgement(e.to_string()));
+            }
+        };
+
+        let challenge = if self.recent_acks.is_some()
+            || self.latencies.is_some()
+            || self.freshness.is_some()
+            || self.expectations.is_some()
+        {
+            Some(ack.ack_challenge()?)
+        } else {
+            None
+        };
+
+        // The challenge is marked as seen before processing, so that concurrently received duplicates
+        // cannot be processed both
+        if let Some((recent_acks, challenge)) = self.recent_acks.as_ref().zip(challenge) {
+            if !recent_acks.entry(challenge).or_insert(()).await.is_fresh() {
+                debug!("Received a duplicate acknowledgement");
+                return Ok(AckResult::Duplicate);
+            }
+        }
+
+        // The challenge is consumed, so that the acknowledgement replayed later is not fresh anymore
+        let issued_at = match self.freshness.as_ref().zip(challenge) {
+            Some((freshness, challenge)) => match freshness.consume(&challenge).await {
+                Some(issued_at) => Some(issued_at),
+                None => return Err(self.record_stale(peer)),
+            },
+            None => None,
+        };
+
+        // Acknowledgements of the expired expectations are rejected as if their challenge was unknown
+        if self
+            .expectations
+            .as_ref()
+            .zip(challenge)
+            .is_some_and(|(expectations, challenge)| !expectations.acknowledged(peer, &challenge))
+        {
+            return Err(self.record_stale(peer));
+        }
+
+        // Not retried, since the operation is not idempotent
+        match self.db.handle_acknowledgement(ack).await {
+            Ok(result) => {
+                if let (Some(latencies), Some(challenge), AckResult::RelayerWinning(_) | AckResult::RelayerLosing(_)) =
+                    (&self.latencies, challenge, &result)
+                {
+                    latencies.acknowledged(&challenge).await;
+                }
+                Ok(result)
+            }
+            Err(e) => {
                 trace!(error = %e, "Failed to process a received acknowledgement");
-                let error: ProtocolError = e.into();
-                error
-            })
+
+                // Failed acknowledgement can be processed again when re-sent
+                if let Some((recent_acks, challenge)) = self.recent_acks.as_ref().zip(challenge) {
+                    recent_acks.invalidate(&challenge).await;
+                }
+
+                if matches!(e, DbError::UnexpectedAcknowledgement(_)) {
+                    return Err(self.record_stale(peer));
+                }
+
+                if let Some((expectations, challenge)) = self.expectations.as_ref().zip(challenge) {
+                    expectations.expect(peer, challenge);
+                }
+                if let Some(((freshness, challenge), issued_at)) = self.freshness.as_ref().zip(challenge).zip(issued_at)
+                {
+                    freshness.restore(challenge, issued_at).await;
+                }
+                Err(e.into())
+            }
+        }
+    }
+}
+
+/// Emitted when acknowledgements expected from a peer did not arrive within the expectation window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AckTimeoutEvent {
+    /// Peer that has not acknowledged the packets sent to it.
+    pub peer: PeerId,
+    /// Number of acknowledgements that did not arrive in time.
+    pub missing: usize,
+}
+
+/// Emitted when an acknowledgement of a relayed packet reveals that its ticket is not winning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LosingTicketEvent {
+    /// Peer that acknowledged the relayed packet.
+    pub peer: PeerId,
+    /// Incoming channel the losing ticket was issued in.
+    pub channel_id: Hash,
+}
+
+/// Maximum number of acknowledgements expected at the same time by default.
+pub const DEFAULT_MAX_PENDING_ACKS: usize = 100_000;
+
+/// Maximum number of forwarded packets tracked for the acknowledgement latency at the same time.
+pub const DEFAULT_MAX_TRACKED_ACK_LATENCIES: u64 = 10_000;
+
+/// Correlates the acknowledgements with the forwarded packets to measure the acknowledgement latency.
+///
+/// The forwarded packets are keyed by the challenge their acknowledgement solves. At most `max_tracked`
+/// packets are tracked, and a packet is tracked for at most the `window`, so the packets that
+/// are never acknowledged do not accumulate.
+#[derive(Debug, Clone)]
+pub struct AckLatencyTracker<C: Clock = RealClock> {
+    window: Duration,
+    clock: C,
+    forwarded: moka::future::Cache<HalfKeyChallenge, Instant>,
+}
+
+impl AckLatencyTracker {
+    pub fn new(window: Duration) -> Self {
+        Self::with_capacity(window, DEFAULT_MAX_TRACKED_ACK_LATENCIES)
+    }
+
+    /// Same as [`AckLatencyTracker::new`], but tracks at most `max_tracked` packets.
+    pub fn with_capacity(window: Duration, max_tracked: u64) -> Self {
+        Self::with_clock(window, max_tracked, RealClock)
+    }
+}
+
+impl<C: Clock> AckLatencyTracker<C> {
+    /// Same as [`AckLatencyTracker::with_capacity`], but the latencies are measured using the given `clock`.
+    pub fn with_clock(window: Duration, max_tracked: u64, clock: C) -> Self {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        lazy_static::initialize(&METRIC_ACK_LATENCY);
+
+        Self {
+            window,
+            clock,
+            forwarded: moka::future::Cache::builder()
+                .time_to_live(window)
+                .max_capacity(max_tracked)
+                .build(),
+        }
+    }
+
+    /// Records that a packet acknowledged by solving the `challenge` has been forwarded.
+    pub async fn forwarded(&self, challenge: HalfKeyChallenge) {
+        self.forwarded.insert(challenge, self.clock.now()).await;
+    }
+
+    /// Records the acknowledgement solving the `challenge` and returns the latency since the packet was forwarded.
+    ///
+    /// Returns `None` if the packet is not tracked, e.g. because it has been tracked for too long.
+    pub async fn acknowledged(&self, challenge: &HalfKeyChallenge) -> Option<Duration> {
+        // The removed packet might have expired, but not have been evicted yet
+        let latency = self
+            .forwarded
+            .remove(challenge)
+            .await
+            .map(|forwarded_at| self.clock.now().saturating_duration_since(forwarded_at))
+            .filter(|latency| *latency < self.window)?;
+        trace!(?latency, "Forwarded packet acknowledged");
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_ACK_LATENCY.observe(latency.as_secs_f64());
+
+        Some(latency)
+    }
+}
+
+/// Remembers when the challenges solved by the acknowledgements of the sent packets were issued.
+///
+/// An acknowledgement is fresh only if it solves a challenge issued at most the `window` ago, which has not
+/// been solved before. At most `max_tracked` challenges are remembered, the least recently issued ones
+/// are forgotten first.
+#[derive(Debug, Clone)]
+pub struct AckFreshnessTracker<C: Clock = RealClock> {
+    window: Duration,
+    clock: C,
+    issued: moka::future::Cache<HalfKeyChallenge, Instant>,
+}
+
+impl AckFreshnessTracker {
+    pub fn new(window: Duration) -> Self {
+        Self::with_capacity(window, DEFAULT_MAX_PENDING_ACKS as u64)
+    }
+
+    /// Same as [`AckFreshnessTracker::new`], but remembers at most `max_tracked` challenges.
+    pub fn with_capacity(window: Duration, max_tracked: u64) -> Self {
+        Self::with_clock(window, max_tracked, RealClock)
+    }
+}
+
+impl<C: Clock> AckFreshnessTracker<C> {
+    /// Same as [`AckFreshnessTracker::with_capacity`], but the issue times are measured using the given `clock`.
+    pub fn with_clock(window: Duration, max_tracked: u64, clock: C) -> Self {
+        Self {
+            window,
+            clock,
+            issued: moka::future::Cache::builder()
+                .time_to_live(window)
+                .max_capacity(max_tracked)
+                .eviction_policy(moka::policy::EvictionPolicy::lru())
+                .build(),
+        }
+    }
+
+    /// Records that a packet acknowledged by solving the `challenge` has been sent.
+    pub async fn issued(&self, challenge: HalfKeyChallenge) {
+        self.issued.insert(challenge, self.clock.now()).await;
+    }
+
+    /// Consumes the `challenge` and returns when it was issued, if it is fresh.
+    pub async fn consume(&self, challenge: &HalfKeyChallenge) -> Option<Instant> {
+        // The removed challenge might have expired, but not have been evicted yet
+        self.issued
+            .remove(challenge)
+            .await
+            .filter(|issued_at| self.clock.now().saturating_duration_since(*issued_at) < self.window)
+    }
+
+    /// Makes the consumed `challenge` fresh again, e.g. when its acknowledgement failed to be processed.
+    pub async fn restore(&self, challenge: HalfKeyChallenge, issued_at: Instant) {
+        self.issued.insert(challenge, issued_at).await;
+    }
+}
+
+/// Acknowledgement expected from a peer.
+#[derive(Debug, Clone, Copy)]
+struct PendingAck {
+    seq: u64,
+    peer: PeerId,
+    sent: Instant,
+}
+
+/// Acknowledgements expected from all the peers, keyed by the challenge they solve.
+#[derive(Debug, Default)]
+struct PendingAcks {
+    next_seq: u64,
+    expected: HashMap<HalfKeyChallenge, PendingAck>,
+    /// Number of the expectations of each peer.
+    per_peer: HashMap<PeerId, usize>,
+    /// Expectations of all the peers, oldest first, possibly including the already fulfilled ones.
+    order: VecDeque<(u64, HalfKeyChallenge)>,
+}
+
+impl PendingAcks {
+    fn insert(&mut self, peer: &PeerId, challenge: HalfKeyChallenge, sent: Instant) {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        let pending = PendingAck { seq, peer: *peer, sent };
+        // The challenges are random, a repeated one replaces the older expectation
+        if let Some(replaced) = self.expected.insert(challenge, pending) {
+            self.decrement(&replaced.peer);
+        }
+        *self.per_peer.entry(*peer).or_default() += 1;
+        self.order.push_back((seq, challenge));
+    }
+
+    fn decrement(&mut self, peer: &PeerId) {
+        if let Some(count) = self.per_peer.get_mut(peer) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_peer.remove(peer);
+            }
+        }
+    }
+
+    fn remove(&mut self, challenge: &HalfKeyChallenge) -> Option<PendingAck> {
+        let removed = self.expected.remove(challenge)?;
+        self.decrement(&removed.peer);
+        Some(removed)
+    }
+
+    /// Fulfills the expectation of the `challenge` if it is pending from the `peer`,
+    /// dropping it from the global order once at its front.
+    fn fulfill(&mut self, peer: &PeerId, challenge: &HalfKeyChallenge) -> bool {
+        if self.expected.get(challenge).map(|pending| pending.peer) != Some(*peer) {
+            return false;
+        }
+
+        self.remove(challenge);
+        self.trim_order();
+        true
+    }
+
+    /// Indicates whether the expectation is still pending.
+    fn is_pending(&self, seq: u64, challenge: &HalfKeyChallenge) -> bool {
+        self.expected.get(challenge).is_some_and(|pending| pending.seq == seq)
+    }
+
+    /// Drops all the already fulfilled or expired expectations from the global order.
+    fn compact_order(&mut self) {
+        let expected = &self.expected;
+        self.order
+            .retain(|(seq, challenge)| expected.get(challenge).is_some_and(|pending| pending.seq == *seq));
+    }
+
+    fn evict_oldest(&mut self) -> bool {
+        while let Some((seq, challenge)) = self.order.pop_front() {
+            if self.is_pending(seq, &challenge) {
+                return self.remove(&challenge).is_some();
+            }
+        }
+        false
+    }
+
+    /// Removes the pending expectation at the front of the global order if the `predicate` holds for it.
+    fn pop_oldest_if(&mut self, predicate: impl Fn(&PendingAck) -> bool) -> Option<PendingAck> {
+        self.trim_order();
+        let (_, challenge) = self.order.front().copied()?;
+        if !self.expected.get(&challenge).is_some_and(predicate) {
+            return None;
+        }
+
+        self.order.pop_front();
+        self.remove(&challenge)
+    }
+
+    /// Drops the already fulfilled or expired expectations from the front of the global order.
+    fn trim_order(&mut self) {
+        while let Some((seq, challenge)) = self.order.front().copied() {
+            if self.is_pending(seq, &challenge) {
+                break;
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+/// Tracks acknowledgements expected from peers we have sent packets to.
+///
+/// Every packet sent to a peer creates an expectation keyed by the challenge solved by its acknowledgement,
+/// which is fulfilled only by the acknowledgement of that challenge received from that peer.
+/// Expectations older than the window are reported by [`AckTimeoutTracker::expire`].
+///
+/// At most `max_pending` expectations are kept, once reached, the oldest expectation of all the peers
+/// is evicted to make room for the new one. The evicted expectations and those removed by
+/// [`AckTimeoutTracker::expire`] are counted as [expired](AckTimeoutTracker::expired), and an acknowledgement
+/// arriving for them is not matched to any expectation.
+#[derive(Debug, Clone)]
+pub struct AckTimeoutTracker<C: Clock = RealClock> {
+    window: Duration,
+    max_pending: usize,
+    clock: C,
+    expected: Arc<Mutex<PendingAcks>>,
+    expired: Arc<AtomicU64>,
+}
+
+impl AckTimeoutTracker {
+    pub fn new(window: Duration) -> Self {
+        Self::with_capacity(window, DEFAULT_MAX_PENDING_ACKS)
+    }
+
+    /// Same as [`AckTimeoutTracker::new`], but keeps at most `max_pending` expectations.
+    pub fn with_capacity(window: Duration, max_pending: usize) -> Self {
+        Self::with_clock(window, max_pending, RealClock)
+    }
+}
+
+impl<C: Clock> AckTimeoutTracker<C> {
+    /// Same as [`AckTimeoutTracker::with_capacity`], but the expectations are timed using the given `clock`.
+    pub fn with_clock(window: Duration, max_pending: usize, clock: C) -> Self {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        lazy_static::initialize(&METRIC_EXPIRED_PENDING_ACKS);
+
+        Self {
+            window,
+            max_pending: max_pending.max(1),
+            clock,
+            expected: Arc::new(Mutex::new(PendingAcks::default())),
+            expired: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Expectation window of this tracker.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    fn record_expired(&self, count: usize) {
+        self.expired.fetch_add(count as u64, Ordering::Relaxed);
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_EXPIRED_PENDING_ACKS.increment_by(count as u64);
+    }
+
+    /// Records that an acknowledgement solving the `challenge` is expected from the `peer`.
+    pub fn expect(&self, peer: &PeerId, challenge: HalfKeyChallenge) {
+        let mut expected = self.expected.lock().unwrap_or_else(|e| e.into_inner());
+        if expected.expected.len() >= self.max_pending && expected.evict_oldest() {
+            self.record_expired(1);
+            debug!(%peer, max_pending = self.max_pending, "Too many pending acknowledgements, evicted the oldest one");
+        }
+
+        // The fulfilled expectations behind a pending one are kept in the order, until there are too many
+        if expected.order.len() >= self.max_pending {
+            expected.compact_order();
+        }
+
+        expected.insert(peer, challenge, self.clock.now());
+    }
+
+    /// Records an acknowledgement solving the `challenge` received from the `peer`, fulfilling its expectation.
+    ///
+    /// Returns `false` if the acknowledgement is not expected from the `peer`,
+    /// e.g. because the expectation has already expired.
+    pub fn acknowledged(&self, peer: &PeerId, challenge: &HalfKeyChallenge) -> bool {
+        self.expected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .fulfill(peer, challenge)
+    }
+
+    /// Number of acknowledgements currently expected from the `peer`.
+    pub fn pending(&self, peer: &PeerId) -> usize {
+        self.expected
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .per_peer
+            .get(peer)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Number of acknowledgements currently expected from all the peers.
+    pub fn total_pending(&self) -> usize {
+        self.expected.lock().unwrap_or_else(|e| e.into_inner()).expected.len()
+    }
+
+    /// Number of expectations dropped so far for being older than the window or over the capacity.
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    /// Removes all the expectations older than the window and reports them per peer.
+    pub fn expire(&self) -> Vec<AckTimeoutEvent> {
+        let now = self.clock.now();
+        let mut missing_per_peer = HashMap::<PeerId, usize>::new();
+
+        let mut expected = self.expected.lock().unwrap_or_else(|e| e.into_inner());
+        // The expectations are created in the order they are sent, so the expired ones are at the front
+        while let Some(pending) =
+            expected.pop_oldest_if(|pending| now.saturating_duration_since(pending.sent) >= self.window)
+        {
+            *missing_per_peer.entry(pending.peer).or_default() += 1;
+        }
+        drop(expected);
+
+        let missing = missing_per_peer.values().sum::<usize>();
+        if missing > 0 {
+            self.record_expired(missing);
+        }
+
+        missing_per_peer
+            .into_iter()
+            .map(|(peer, missing)| AckTimeoutEvent { peer, missing })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use hopr_async_runtime::clock::MockClock;
+    use hopr_db_api::protocol::TransportPacketWithChainData;
+    use hopr_network_types::prelude::ResolvedTransportRouting;
+    use hopr_primitive_types::prelude::Balance;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Counts the handled acknowledgements, optionally failing to handle them.
+    ///
+    /// Acknowledgements already handled once are reported as unexpected, if `single_use` is set.
+    /// Acknowledgements are reported as acknowledging relayed packets, if `relaying` is set.
+    #[derive(Clone, Default)]
+    struct CountingDb {
+        handled: Arc<AtomicUsize>,
+        failing: Arc<AtomicBool>,
+        single_use: Option<Arc<Mutex<std::collections::HashSet<HalfKeyChallenge>>>>,
+        relaying: bool,
+        transient_failures: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl HoprDbProtocolOperations for CountingDb {
+        async fn handle_acknowledgement(&self, ack: Acknowledgement) -> hopr_db_api::errors::Result<AckResult> {
+            self.handled.fetch_add(1, Ordering::SeqCst);
+            if self
+                .transient_failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                Err(DbError::Transient("database is locked".into()))
+            } else if self.failing.load(Ordering::SeqCst) {
+                Err(DbError::General("failing".into()))
+            } else if self.single_use.as_ref().is_some_and(|used| {
+                !used
+                    .lock()
+                    .unwrap()
+                    .insert(ack.ack_challenge().expect("ack must be validated"))
+            }) {
+                Err(DbError::UnexpectedAcknowledgement("already acknowledged".into()))
+            } else if self.relaying {
+                Ok(AckResult::RelayerLosing(Hash::default()))
+            } else {
+                Ok(AckResult::Sender(ack))
+            }
+        }
+
+        async fn get_network_winning_probability(&self) -> hopr_db_api::errors::Result<f64> {
+            unimplemented!()
+        }
+
+        async fn get_network_ticket_price(&self) -> hopr_db_api::errors::Result<Balance> {
+            unimplemented!()
+        }
+
+        async fn to_send_no_ack(
+            &self,
+            _: Box<[u8]>,
+            _: OffchainPublicKey,
+        ) -> std::result::Result<TransportPacketWithChainData, DbError> {
+            unimplemented!()
+        }
+
+        async fn to_send(
+            &self,
+            _: Box<[u8]>,
+            _: ResolvedTransportRouting,
+            _: f64,
+            _: Balance,
+        ) -> std::result::Result<TransportPacketWithChainData, DbError> {
+            unimplemented!()
+        }
+
+        async fn from_recv(
+            &self,
+            _: Box<[u8]>,
+            _: &OffchainKeypair,
+            _: OffchainPublicKey,
+            _: f64,
+            _: Balance,
+        ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+            unimplemented!()
+        }
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_not_process_duplicate_acks() -> anyhow::Result<()> {
+        let db = CountingDb::default();
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default());
+
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let ack = Acknowledgement::random(&peer_key);
+
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Duplicate));
+        assert!(matches!(
+            processor.recv(&peer, Acknowledgement::random(&peer_key)).await?,
+            AckResult::Sender(_)
+        ));
+        assert_eq!(2, db.handled.load(Ordering::SeqCst), "duplicate must not reach the db");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_process_ack_again_after_failure_or_with_detection_disabled() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let ack = Acknowledgement::random(&peer_key);
+
+        let db = CountingDb::default();
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default());
+        db.failing.store(true, Ordering::SeqCst);
+        assert!(processor.recv(&peer, ack).await.is_err());
+        db.failing.store(false, Ordering::SeqCst);
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+
+        let processor = AcknowledgementProcessor::new(
+            CountingDb::default(),
+            AckProtocolConfig {
+                duplicate_window: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_reject_acks_of_expired_expectations_as_stale() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+
+        let db = CountingDb::default();
+        let clock = MockClock::default();
+        let tracker = AckTimeoutTracker::with_clock(Duration::from_millis(50), DEFAULT_MAX_PENDING_ACKS, clock.clone());
+        let processor = AcknowledgementProcessor::with_clock(db.clone(), AckProtocolConfig::default())
+            .with_timeout_tracker(tracker.clone());
+
+        let (first, second) = (Acknowledgement::random(&peer_key), Acknowledgement::random(&peer_key));
+        tracker.expect(&peer, first.ack_challenge()?);
+        tracker.expect(&peer, second.ack_challenge()?);
+        assert!(matches!(processor.recv(&peer, first).await?, AckResult::Sender(_)));
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(vec![AckTimeoutEvent { peer, missing: 1 }], tracker.expire());
+
+        assert!(matches!(
+            processor.recv(&peer, second).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+        assert_eq!(1, processor.stale_acks());
+        assert_eq!(
+            1,
+            db.handled.load(Ordering::SeqCst),
+            "expired ack must not reach the db"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_reject_acks_of_challenges_not_expected_from_the_peer() -> anyhow::Result<()> {
+        let (peer_key, other_key) = (OffchainKeypair::random(), OffchainKeypair::random());
+        let (peer, other): (PeerId, PeerId) = (peer_key.public().into(), other_key.public().into());
+
+        let db = CountingDb::default();
+        let tracker = AckTimeoutTracker::new(Duration::from_secs(30));
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default())
+            .with_timeout_tracker(tracker.clone());
+
+        let expected = Acknowledgement::random(&peer_key);
+        tracker.expect(&peer, expected.ack_challenge()?);
+        tracker.expect(&other, Acknowledgement::random(&other_key).ack_challenge()?);
+
+        // Neither an unknown challenge of the peer nor another peer's challenge fulfill the expectation
+        assert!(matches!(
+            processor.recv(&peer, Acknowledgement::random(&peer_key)).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+        assert!(matches!(
+            processor.recv(&other, Acknowledgement::random(&other_key)).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+        assert_eq!((1, 1), (tracker.pending(&peer), tracker.pending(&other)));
+
+        assert!(matches!(processor.recv(&peer, expected).await?, AckResult::Sender(_)));
+        assert_eq!((0, 1), (tracker.pending(&peer), tracker.pending(&other)));
+        assert_eq!(
+            1,
+            db.handled.load(Ordering::SeqCst),
+            "unexpected acks must not reach the db"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_not_retry_handling_of_the_acknowledgement() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+
+        let db = CountingDb::default();
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default());
+
+        db.transient_failures.store(1, Ordering::SeqCst);
+        let ack = Acknowledgement::random(&peer_key);
+        assert!(processor.recv(&peer, ack).await.is_err());
+        assert_eq!(
+            1,
+            db.handled.load(Ordering::SeqCst),
+            "handling of the acknowledgement must not be retried"
+        );
+
+        // The failed acknowledgement can be processed again when re-sent
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert_eq!(2, db.handled.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_reject_replayed_acks_outside_the_duplicate_window() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let ack = Acknowledgement::random(&peer_key);
+
+        let db = CountingDb {
+            single_use: Some(Default::default()),
+            ..Default::default()
+        };
+        let processor = AcknowledgementProcessor::new(
+            db.clone(),
+            AckProtocolConfig {
+                duplicate_window: Duration::ZERO,
+                ..Default::default()
+            },
+        );
+
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        for _ in 0..2 {
+            assert!(matches!(
+                processor.recv(&peer, ack).await,
+                Err(ProtocolError::StaleAcknowledgement)
+            ));
+        }
+        assert_eq!(2, processor.stale_acks(), "each replay must be counted");
+
+        assert!(matches!(
+            processor.recv(&peer, Acknowledgement::random(&peer_key)).await?,
+            AckResult::Sender(_)
+        ));
+        assert_eq!(2, processor.clone().stale_acks(), "clones must share the counter");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_measure_the_latency_of_the_acknowledged_forwarded_packets() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let (forwarded_ack, sent_ack) = (Acknowledgement::random(&peer_key), Acknowledgement::random(&peer_key));
+
+        let latencies = AckLatencyTracker::new(Duration::from_secs(60));
+        let processor = AcknowledgementProcessor::new(
+            CountingDb {
+                relaying: true,
+                ..Default::default()
+            },
+            AckProtocolConfig::default(),
+        )
+        .with_latency_tracker(latencies.clone());
+
+        let (forwarded, sent) = (forwarded_ack.ack_challenge()?, sent_ack.ack_challenge()?);
+        latencies.forwarded(forwarded).await;
+        latencies.forwarded(sent).await;
+
+        assert!(matches!(
+            processor.recv(&peer, forwarded_ack).await?,
+            AckResult::RelayerLosing(_)
+        ));
+        assert_eq!(
+            None,
+            latencies.acknowledged(&forwarded).await,
+            "the acknowledged packet must not be tracked anymore"
+        );
+
+        let processor = AcknowledgementProcessor::new(CountingDb::default(), AckProtocolConfig::default())
+            .with_latency_tracker(latencies.clone());
+        assert!(matches!(processor.recv(&peer, sent_ack).await?, AckResult::Sender(_)));
+        assert!(
+            latencies.acknowledged(&sent).await.is_some(),
+            "only acknowledgements of the relayed packets must be correlated"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_latency_tracker_should_track_a_bounded_number_of_packets() {
+        let peer_key = OffchainKeypair::random();
+        let latencies = AckLatencyTracker::with_capacity(Duration::from_secs(60), 10);
+
+        let challenges = (0..100)
+            .map(|_| Acknowledgement::random(&peer_key).ack_challenge())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .expect("random acks must be valid");
+        for challenge in &challenges {
+            latencies.forwarded(*challenge).await;
+        }
+        latencies.forwarded.run_pending_tasks().await;
+
+        assert!(latencies.forwarded.entry_count() <= 10);
+
+        let clock = MockClock::default();
+        let latencies = AckLatencyTracker::with_clock(Duration::from_millis(10), 10, clock.clone());
+        latencies.forwarded(challenges[0]).await;
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(
+            None,
+            latencies.acknowledged(&challenges[0]).await,
+            "packets must not be tracked past the window"
+        );
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_reject_acks_of_challenges_not_issued_or_already_solved() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let (ack, unknown_ack) = (Acknowledgement::random(&peer_key), Acknowledgement::random(&peer_key));
+
+        let db = CountingDb::default();
+        let freshness = AckFreshnessTracker::new(Duration::from_secs(30));
+        let processor = AcknowledgementProcessor::new(
+            db.clone(),
+            AckProtocolConfig {
+                duplicate_window: Duration::ZERO,
+                ..Default::default()
+            },
+        )
+        .with_freshness_tracker(freshness.clone());
+
+        freshness.issued(ack.ack_challenge()?).await;
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert!(matches!(
+            processor.recv(&peer, ack).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+        assert!(matches!(
+            processor.recv(&peer, unknown_ack).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+
+        assert_eq!(2, processor.stale_acks());
+        assert_eq!(1, db.handled.load(Ordering::SeqCst), "stale ack must not reach the db");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_reject_acks_of_challenges_issued_before_the_window() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let (late_ack, ack) = (Acknowledgement::random(&peer_key), Acknowledgement::random(&peer_key));
+
+        let clock = MockClock::default();
+        let freshness = AckFreshnessTracker::with_clock(
+            Duration::from_millis(50),
+            DEFAULT_MAX_PENDING_ACKS as u64,
+            clock.clone(),
+        );
+        let processor = AcknowledgementProcessor::with_clock(CountingDb::default(), AckProtocolConfig::default())
+            .with_freshness_tracker(freshness.clone());
+
+        freshness.issued(late_ack.ack_challenge()?).await;
+        clock.advance(Duration::from_millis(60));
+        freshness.issued(ack.ack_challenge()?).await;
+
+        assert!(matches!(
+            processor.recv(&peer, late_ack).await,
+            Err(ProtocolError::StaleAcknowledgement)
+        ));
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert_eq!(1, processor.stale_acks());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_keep_the_challenge_fresh_when_the_ack_fails_to_be_processed() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let ack = Acknowledgement::random(&peer_key);
+
+        let db = CountingDb::default();
+        let freshness = AckFreshnessTracker::new(Duration::from_secs(30));
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default())
+            .with_freshness_tracker(freshness.clone());
+
+        freshness.issued(ack.ack_challenge()?).await;
+        db.failing.store(true, Ordering::SeqCst);
+        assert!(processor.recv(&peer, ack).await.is_err());
+
+        db.failing.store(false, Ordering::SeqCst);
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert_eq!(0, processor.stale_acks());
+
+        Ok(())
+    }
+
+    fn malformed_ack() -> anyhow::Result<Acknowledgement> {
+        use hopr_primitive_types::prelude::BytesRepresentable;
+        Ok(Acknowledgement::try_from(&[1u8; Acknowledgement::SIZE][..])?)
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_drop_and_count_malformed_acks() -> anyhow::Result<()> {
+        let peer: PeerId = OffchainKeypair::random().public().into();
+        let db = CountingDb::default();
+        let (ban_tx, mut ban_rx) = futures::channel::mpsc::unbounded();
+        let processor = AcknowledgementProcessor::new(db.clone(), AckProtocolConfig::default()).with_ban_events(ban_tx);
+
+        for _ in 0..5 {
+            assert!(matches!(
+                processor.recv(&peer, malformed_ack()?).await,
+                Err(ProtocolError::MalformedAcknowledgement(_))
+            ));
+        }
+
+        assert_eq!(5, processor.malformed_acks());
+        assert_eq!(
+            0,
+            db.handled.load(Ordering::SeqCst),
+            "malformed ack must not reach the db"
+        );
+        assert!(ban_rx.try_next().is_err(), "no peer must be banned by default");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_not_fulfill_expectations_with_malformed_acks() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+
+        let tracker = AckTimeoutTracker::new(Duration::from_secs(30));
+        let processor = AcknowledgementProcessor::new(CountingDb::default(), AckProtocolConfig::default())
+            .with_timeout_tracker(tracker.clone());
+
+        let ack = Acknowledgement::random(&peer_key);
+        tracker.expect(&peer, ack.ack_challenge()?);
+        for _ in 0..3 {
+            assert!(matches!(
+                processor.recv(&peer, malformed_ack()?).await,
+                Err(ProtocolError::MalformedAcknowledgement(_))
+            ));
+        }
+        assert_eq!(
+            1,
+            tracker.pending(&peer),
+            "malformed ack must not fulfill the expectation"
+        );
+
+        assert!(matches!(processor.recv(&peer, ack).await?, AckResult::Sender(_)));
+        assert_eq!(0, tracker.pending(&peer));
+        assert_eq!(0, processor.stale_acks());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_processor_should_ban_peer_after_threshold_of_malformed_acks() -> anyhow::Result<()> {
+        let peer_key = OffchainKeypair::random();
+        let peer: PeerId = peer_key.public().into();
+        let other_peer: PeerId = OffchainKeypair::random().public().into();
+
+        let (ban_tx, mut ban_rx) = futures::channel::mpsc::unbounded();
+        let processor = AcknowledgementProcessor::new(
+            CountingDb::default(),
+            AckProtocolConfig {
+                malformed_ack_policy: MalformedAckPolicy::Ban { threshold: 3 },
+                ..Default::default()
+            },
+        )
+        .with_ban_events(ban_tx);
+
+        for _ in 0..2 {
+            assert!(processor.recv(&peer, malformed_ack()?).await.is_err());
+            assert!(processor.recv(&other_peer, malformed_ack()?).await.is_err());
+        }
+        assert!(matches!(
+            processor.recv(&peer, Acknowledgement::random(&peer_key)).await?,
+            AckResult::Sender(_)
+        ));
+        assert!(ban_rx.try_next().is_err(), "no peer must be banned below the threshold");
+
+        assert!(processor.recv(&peer, malformed_ack()?).await.is_err());
+        assert!(matches!(ban_rx.try_next(), Ok(Some(PeerDiscovery::Ban(banned))) if banned == peer));
+        assert!(ban_rx.try_next().is_err(), "the peer must be banned only once");
+        assert_eq!(5, processor.malformed_acks());
+
+        Ok(())
+    }
+
+    fn random_challenge() -> HalfKeyChallenge {
+        HalfKey::random().to_challenge()
+    }
+
+    #[async_std::test]
+    async fn ack_timeout_tracker_should_report_missing_acks_after_the_window() {
+        let clock = MockClock::default();
+        let tracker = AckTimeoutTracker::with_clock(Duration::from_millis(50), DEFAULT_MAX_PENDING_ACKS, clock.clone());
+        let (stalled, responsive) = (PeerId::random(), PeerId::random());
+
+        for _ in 0..3 {
+            tracker.expect(&stalled, random_challenge());
+            let challenge = random_challenge();
+            tracker.expect(&responsive, challenge);
+            assert!(tracker.acknowledged(&responsive, &challenge));
+        }
+
+        assert!(tracker.expire().is_empty(), "nothing must expire within the window");
+
+        clock.advance(Duration::from_millis(60));
+        tracker.expect(&stalled, random_challenge());
+
+        assert_eq!(
+            vec![AckTimeoutEvent {
+                peer: stalled,
+                missing: 3
+            }],
+            tracker.expire()
+        );
+        assert_eq!(1, tracker.pending(&stalled), "recent expectation must be retained");
+        assert_eq!(0, tracker.pending(&responsive));
+    }
+
+    #[test]
+    fn ack_timeout_tracker_should_ignore_unexpected_acks() {
+        let tracker = AckTimeoutTracker::new(Duration::from_secs(1));
+        let (peer, other) = (PeerId::random(), PeerId::random());
+        let challenge = random_challenge();
+
+        assert!(!tracker.acknowledged(&peer, &challenge));
+        tracker.expect(&peer, challenge);
+        assert!(
+            !tracker.acknowledged(&peer, &random_challenge()),
+            "ack of another challenge must not match"
+        );
+        assert!(
+            !tracker.acknowledged(&other, &challenge),
+            "ack from another peer must not match"
+        );
+        assert_eq!(1, tracker.pending(&peer));
+    }
+
+    #[test]
+    fn ack_timeout_tracker_should_match_acks_by_challenge_regardless_of_the_order() {
+        let tracker = AckTimeoutTracker::new(Duration::from_secs(60));
+        let peer = PeerId::random();
+        let challenges = (0..3).map(|_| random_challenge()).collect::<Vec<_>>();
+
+        challenges
+            .iter()
+            .for_each(|challenge| tracker.expect(&peer, *challenge));
+        assert!(tracker.acknowledged(&peer, &challenges[2]));
+        assert!(tracker.acknowledged(&peer, &challenges[0]));
+        assert!(!tracker.acknowledged(&peer, &challenges[2]), "ack must match only once");
+        assert_eq!(1, tracker.pending(&peer));
+        assert!(tracker.acknowledged(&peer, &challenges[1]));
+        assert_eq!(0, tracker.total_pending());
+    }
+
+    #[test]
+    fn ack_timeout_tracker_should_evict_the_oldest_expectations_over_the_capacity() {
+        let tracker = AckTimeoutTracker::with_capacity(Duration::from_secs(60), 3);
+        let (a, b, c, d) = (PeerId::random(), PeerId::random(), PeerId::random(), PeerId::random());
+        let (a1, b1, a2) = (random_challenge(), random_challenge(), random_challenge());
+
+        tracker.expect(&a, a1);
+        tracker.expect(&b, b1);
+        tracker.expect(&a, a2);
+        assert_eq!(3, tracker.total_pending());
+
+        // Evicts the first expectation of `a`
+        tracker.expect(&c, random_challenge());
+        assert_eq!(
+            (1, 1, 1),
+            (tracker.pending(&a), tracker.pending(&b), tracker.pending(&c))
+        );
+        assert_eq!(1, tracker.expired());
+        assert!(
+            !tracker.acknowledged(&a, &a1),
+            "ack of an evicted expectation must not match"
+        );
+
+        // Evicts the expectation of `b`
+        tracker.expect(&d, random_challenge());
+        assert_eq!(0, tracker.pending(&b));
+        assert!(
+            !tracker.acknowledged(&b, &b1),
+            "ack of an evicted expectation must not match"
+        );
+
+        // The fulfilled expectation of `a` is skipped, so the one of `c` is evicted
+        assert!(tracker.acknowledged(&a, &a2));
+        tracker.expect(&a, random_challenge());
+        tracker.expect(&b, random_challenge());
+        assert_eq!(
+            (1, 0, 1, 1),
+            (
+                tracker.pending(&a),
+                tracker.pending(&c),
+                tracker.pending(&d),
+                tracker.pending(&b)
+            )
+        );
+        assert_eq!(3, tracker.total_pending());
+        assert_eq!(3, tracker.expired());
+    }
+
+    #[async_std::test]
+    async fn ack_timeout_tracker_should_count_expectations_removed_by_the_sweep() {
+        let clock = MockClock::default();
+        let tracker = AckTimeoutTracker::with_clock(Duration::from_millis(50), 10, clock.clone());
+        let peer = PeerId::random();
+
+        let expired = (0..5).map(|_| random_challenge()).collect::<Vec<_>>();
+        expired.iter().for_each(|challenge| tracker.expect(&peer, *challenge));
+        clock.advance(Duration::from_millis(60));
+        let recent = random_challenge();
+        tracker.expect(&peer, recent);
+
+        assert_eq!(vec![AckTimeoutEvent { peer, missing: 5 }], tracker.expire());
+        assert_eq!(5, tracker.expired());
+        assert_eq!(1, tracker.total_pending());
+
+        // No late acknowledgement of the expired expectations matches
+        assert!(!tracker.acknowledged(&peer, &expired[0]));
+        assert!(tracker.acknowledged(&peer, &recent));
+        assert_eq!(0, tracker.total_pending());
+    }
+
+    #[test]
+    fn ack_timeout_tracker_should_bound_the_order_by_the_capacity() {
+        let tracker = AckTimeoutTracker::with_capacity(Duration::from_secs(60), 3);
+        let (stalled, responsive) = (PeerId::random(), PeerId::random());
+
+        // The pending expectation at the front keeps the fulfilled ones behind it in the order
+        let stalled_challenge = random_challenge();
+        tracker.expect(&stalled, stalled_challenge);
+        for _ in 0..100 {
+            let challenge = random_challenge();
+            tracker.expect(&responsive, challenge);
+            assert!(tracker.acknowledged(&responsive, &challenge));
+        }
+
+        assert_eq!(1, tracker.total_pending());
+        assert_eq!(0, tracker.expired(), "fulfilled expectations must not count as evicted");
+        assert!(tracker.expected.lock().unwrap().order.len() <= 3);
+
+        // Once the front is fulfilled, the order is trimmed right away
+        assert!(tracker.acknowledged(&stalled, &stalled_challenge));
+        assert!(tracker.expected.lock().unwrap().order.is_empty());
     }
 }
@@ -0,0 +1,240 @@
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::{SinkExt, StreamExt};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use hopr_async_runtime::prelude::spawn;
+use hopr_crypto_types::prelude::*;
+use hopr_internal_types::prelude::*;
+use hopr_transport_identity::PeerId;
+
+use crate::errors::{ProtocolError, Result};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::SimpleGauge;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_ACK_SIGNING_POOL_DEPTH: SimpleGauge = SimpleGauge::new(
+        "hopr_ack_signing_pool_depth",
+        "Number of outgoing acknowledgements waiting to be signed"
+    )
+    .unwrap();
+}
+
+/// Maximum number of acknowledgements queued for a single signing worker.
+pub const ACK_SIGNING_WORKER_QUEUE_SIZE: usize = 256;
+
+/// Default number of acknowledgement signing workers, one per available CPU.
+pub fn default_ack_signing_workers() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Bounded queue of the acknowledgements waiting to be signed by a single worker.
+type WorkerQueue = async_lock::Mutex<Sender<(PeerId, HalfKey)>>;
+
+/// Signs the outgoing acknowledgements in a bounded pool of workers.
+///
+/// Each worker takes the acknowledgements from its bounded queue and signs them one by one
+/// on the CPU thread pool, so the signing never blocks the async executor.
+/// Acknowledgements for the same peer are always handled by the same worker, so they are signed
+/// and emitted in the order they were submitted. Once the queue of a worker is full,
+/// [`AckSigner::sign`] waits for it to drain, which propagates the backpressure to the caller.
+#[derive(Debug, Clone)]
+pub struct AckSigner {
+    workers: Arc<[WorkerQueue]>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl AckSigner {
+    /// Starts `workers` signing workers using the given `keypair`.
+    ///
+    /// Returns the signer and the stream of the signed acknowledgements.
+    pub fn new(keypair: OffchainKeypair, workers: usize) -> (Self, Receiver<(PeerId, Acknowledgement)>) {
+        Self::with_queue_size(keypair, workers, ACK_SIGNING_WORKER_QUEUE_SIZE)
+    }
+
+    /// Same as [`AckSigner::new`], but with a custom queue size of each worker.
+    pub fn with_queue_size(
+        keypair: OffchainKeypair,
+        workers: usize,
+        queue_size: usize,
+    ) -> (Self, Receiver<(PeerId, Acknowledgement)>) {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        lazy_static::initialize(&METRIC_ACK_SIGNING_POOL_DEPTH);
+
+        let workers = workers.max(1);
+        let depth = Arc::new(AtomicUsize::new(0));
+        let keypair = Arc::new(keypair);
+        let (signed_tx, signed_rx) = channel::<(PeerId, Acknowledgement)>(workers * queue_size);
+
+        let senders = (0..workers)
+            .map(|_| {
+                let (tx, mut rx) = channel::<(PeerId, HalfKey)>(queue_size);
+                let keypair = keypair.clone();
+                let depth = depth.clone();
+                let mut signed_tx = signed_tx.clone();
+
+                // The worker terminates once all the signers or the receiver of the signed acknowledgements are dropped
+                spawn(async move {
+                    while let Some((peer, ack_key)) = rx.next().await {
+                        let keypair = keypair.clone();
+                        let ack =
+                            hopr_parallelize::cpu::spawn_blocking(move || Acknowledgement::new(ack_key, &keypair))
+                                .await;
+
+                        depth.fetch_sub(1, Ordering::Relaxed);
+                        #[cfg(all(feature = "prometheus", not(test)))]
+                        METRIC_ACK_SIGNING_POOL_DEPTH.decrement(1.0);
+
+                        if signed_tx.send((peer, ack)).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                async_lock::Mutex::new(tx)
+            })
+            .collect();
+
+        (
+            Self {
+                workers: senders,
+                depth,
+            },
+            signed_rx,
+        )
+    }
+
+    /// Submits the `ack_key` to be signed and sent to the `peer`.
+    ///
+    /// Waits while the worker assigned to the `peer` is saturated.
+    pub async fn sign(&self, peer: PeerId, ack_key: HalfKey) -> Result<()> {
+        let mut pending = PendingAck::new(&self.depth);
+
+        self.workers[self.worker_index(&peer)]
+            .lock()
+            .await
+            .send((peer, ack_key))
+            .await
+            .map_err(|e| ProtocolError::TransportError(format!("acknowledgement signing pool is closed: {e}")))?;
+
+        pending.submitted = true;
+        Ok(())
+    }
+
+    /// Number of acknowledgements submitted, but not signed yet.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    fn worker_index(&self, peer: &PeerId) -> usize {
+        let mut hasher = DefaultHasher::new();
+        peer.hash(&mut hasher);
+        (hasher.finish() % self.workers.len() as u64) as usize
+    }
+}
+
+/// Counts an acknowledgement into the pool depth, unless its submission fails or is cancelled.
+struct PendingAck<'a> {
+    depth: &'a AtomicUsize,
+    submitted: bool,
+}
+
+impl<'a> PendingAck<'a> {
+    fn new(depth: &'a AtomicUsize) -> Self {
+        depth.fetch_add(1, Ordering::Relaxed);
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_ACK_SIGNING_POOL_DEPTH.increment(1.0);
+
+        Self {
+            depth,
+            submitted: false,
+        }
+    }
+}
+
+impl Drop for PendingAck<'_> {
+    fn drop(&mut self) {
+        if !self.submitted {
+            self.depth.fetch_sub(1, Ordering::Relaxed);
+            #[cfg(all(feature = "prometheus", not(test)))]
+            METRIC_ACK_SIGNING_POOL_DEPTH.decrement(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::future::timeout;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[async_std::test]
+    async fn ack_signer_should_preserve_the_order_of_acks_per_peer() -> anyhow::Result<()> {
+        const PEERS: usize = 10;
+        const ACKS_PER_PEER: usize = 100;
+
+        let keypair = OffchainKeypair::random();
+        let (signer, signed) = AckSigner::new(keypair.clone(), 4);
+
+        let peers = (0..PEERS)
+            .map(|_| PeerId::from(*OffchainKeypair::random().public()))
+            .collect::<Vec<_>>();
+
+        let mut expected: HashMap<PeerId, Vec<HalfKey>> = HashMap::new();
+        let submissions = peers.iter().map(|peer| {
+            let signer = signer.clone();
+            let keys = (0..ACKS_PER_PEER).map(|_| HalfKey::random()).collect::<Vec<_>>();
+            expected.insert(*peer, keys.clone());
+
+            async move {
+                for key in keys {
+                    signer.sign(*peer, key).await?;
+                }
+                Ok::<_, ProtocolError>(())
+            }
+        });
+
+        let collected = signed.take(PEERS * ACKS_PER_PEER).collect::<Vec<_>>();
+        let (submitted, collected) = futures::join!(futures::future::try_join_all(submissions), collected);
+        submitted?;
+
+        let mut actual: HashMap<PeerId, Vec<HalfKey>> = HashMap::new();
+        for (peer, ack) in collected {
+            let ack = ack.validate(keypair.public())?;
+            actual.entry(peer).or_default().push(ack.ack_key_share()?);
+        }
+
+        assert_eq!(
+            expected, actual,
+            "acks of each peer must be emitted in the submission order"
+        );
+        assert_eq!(0, signer.depth());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ack_signer_should_apply_backpressure_when_saturated() -> anyhow::Result<()> {
+        let (signer, signed) = AckSigner::with_queue_size(OffchainKeypair::random(), 1, 1);
+        let peer = PeerId::from(*OffchainKeypair::random().public());
+
+        // The signed acks are not consumed, so the pool must eventually saturate
+        let mut submitted = 0;
+        while timeout(Duration::from_millis(100), signer.sign(peer, HalfKey::random()))
+            .await
+            .is_ok()
+        {
+            submitted += 1;
+            assert!(submitted < 100, "signer must block once the pool is saturated");
+        }
+        assert!(signer.depth() > 0, "blocked acks must be counted in the pool depth");
+
+        drop(signed);
+        Ok(())
+    }
+}
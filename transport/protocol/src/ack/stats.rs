@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+
+use hopr_db_api::protocol::AckResult;
+use hopr_internal_types::prelude::*;
+use hopr_primitive_types::prelude::*;
+
+/// Counters of the tickets received in a single channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChannelTicketStats {
+    /// Number of acknowledged winning tickets.
+    pub winning_count: u64,
+    /// Number of acknowledged losing tickets.
+    pub losing_count: u64,
+    /// Number of tickets rejected during validation of the incoming packets.
+    pub rejected_count: u64,
+    /// Total value of the winning tickets.
+    pub winning_value: Balance,
+}
+
+impl Default for ChannelTicketStats {
+    fn default() -> Self {
+        Self {
+            winning_count: 0,
+            losing_count: 0,
+            rejected_count: 0,
+            winning_value: BalanceType::HOPR.zero(),
+        }
+    }
+}
+
+/// Default maximum number of channels the [`TicketStats`] keep the counters for.
+pub const DEFAULT_MAX_TRACKED_CHANNELS: u64 = 10_000;
+
+/// In-memory registry of the [`ChannelTicketStats`] of the channels the tickets were received in.
+///
+/// The clones of the registry share the same counters. The counters are kept until the channel
+/// is explicitly [forgotten](TicketStats::forget), e.g. once it is closed, or until it is evicted
+/// as the least recently updated one once more channels are tracked than the capacity allows.
+/// The channel IDs of the rejected tickets come from the peers, so the registry must stay bounded.
+#[derive(Debug, Clone)]
+pub struct TicketStats {
+    channels: moka::sync::Cache<ChannelId, Arc<Mutex<ChannelTicketStats>>>,
+}
+
+impl Default for TicketStats {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_MAX_TRACKED_CHANNELS)
+    }
+}
+
+impl TicketStats {
+    /// Creates the registry keeping the counters of at most `max_channels` channels.
+    pub fn with_capacity(max_channels: u64) -> Self {
+        Self {
+            channels: moka::sync::Cache::builder()
+                .max_capacity(max_channels)
+                .eviction_policy(moka::policy::EvictionPolicy::lru())
+                .build(),
+        }
+    }
+
+    /// Updates the counters with the outcome of a received acknowledgement.
+    pub fn record(&self, result: &AckResult) {
+        match result {
+            AckResult::Sender(_) | AckResult::Duplicate => {}
+            AckResult::RelayerWinning(ack_ticket) => {
+                let ticket = ack_ticket.ticket.verified_ticket();
+                self.update(ticket.channel_id, |stats| {
+                    stats.winning_count += 1;
+                    stats.winning_value = stats.winning_value + ticket.amount;
+                });
+            }
+            AckResult::RelayerLosing(channel_id) => self.update(*channel_id, |stats| stats.losing_count += 1),
+        }
+    }
+
+    /// Counts a ticket in the given channel that failed to validate.
+    pub fn rejected(&self, channel_id: ChannelId) {
+        self.update(channel_id, |stats| stats.rejected_count += 1);
+    }
+
+    fn update<F: FnOnce(&mut ChannelTicketStats)>(&self, channel_id: ChannelId, f: F) {
+        let stats = self.channels.get_with(channel_id, Default::default);
+        f(&mut stats.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Counters of the given channel, all zero if no tickets were received in it.
+    pub fn stats_for(&self, channel_id: &ChannelId) -> ChannelTicketStats {
+        self.channels
+            .get(channel_id)
+            .map(|stats| *stats.lock().unwrap_or_else(|e| e.into_inner()))
+            .unwrap_or_default()
+    }
+
+    /// Counters of all the tracked channels tickets were received in.
+    pub fn all(&self) -> Vec<(ChannelId, ChannelTicketStats)> {
+        self.channels
+            .iter()
+            .map(|(channel_id, stats)| (*channel_id, *stats.lock().unwrap_or_else(|e| e.into_inner())))
+            .collect()
+    }
+
+    /// Drops the counters of the given channel.
+    pub fn forget(&self, channel_id: &ChannelId) {
+        self.channels.invalidate(channel_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_crypto_random::Randomizable;
+    use hopr_crypto_types::prelude::*;
+
+    fn winning_ticket(issuer: &ChainKeypair, recipient: &ChainKeypair, amount: u64) -> anyhow::Result<AckResult> {
+        let hk1 = HalfKey::random();
+        let hk2 = HalfKey::random();
+        let challenge = Challenge::from(CurvePoint::combine(&[
+            &hk1.to_challenge().try_into()?,
+            &hk2.to_challenge().try_into()?,
+        ]));
+
+        Ok(AckResult::RelayerWinning(
+            TicketBuilder::default()
+                .addresses(issuer, recipient)
+                .amount(amount)
+                .index(0)
+                .index_offset(1)
+                .win_prob(1.0)
+                .channel_epoch(1)
+                .challenge(challenge.to_ethereum_challenge())
+                .build_signed(issuer, &Hash::default())?
+                .into_acknowledged(Response::from_half_keys(&hk1, &hk2)?),
+        ))
+    }
+
+    #[test]
+    fn ticket_stats_should_aggregate_ack_results_per_channel() -> anyhow::Result<()> {
+        let (issuer, me) = (ChainKeypair::random(), ChainKeypair::random());
+        let channel_id = generate_channel_id(&issuer.public().to_address(), &me.public().to_address());
+        let other_channel_id = Hash::create(&[b"other".as_ref()]);
+
+        let stats = TicketStats::default();
+        stats.record(&winning_ticket(&issuer, &me, 10)?);
+        stats.record(&winning_ticket(&issuer, &me, 20)?);
+        stats.record(&AckResult::RelayerLosing(channel_id));
+        stats.record(&AckResult::RelayerLosing(other_channel_id));
+        stats.rejected(other_channel_id);
+        stats.rejected(other_channel_id);
+
+        assert_eq!(
+            ChannelTicketStats {
+                winning_count: 2,
+                losing_count: 1,
+                rejected_count: 0,
+                winning_value: BalanceType::HOPR.balance(30),
+            },
+            stats.stats_for(&channel_id)
+        );
+        assert_eq!(
+            ChannelTicketStats {
+                losing_count: 1,
+                rejected_count: 2,
+                ..Default::default()
+            },
+            stats.stats_for(&other_channel_id)
+        );
+        assert_eq!(2, stats.all().len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ticket_stats_should_forget_channel() {
+        let channel_id = Hash::create(&[b"channel".as_ref()]);
+
+        let stats = TicketStats::default();
+        stats.clone().record(&AckResult::RelayerLosing(channel_id));
+        assert_eq!(
+            1,
+            stats.stats_for(&channel_id).losing_count,
+            "clones must share the counters"
+        );
+
+        stats.forget(&channel_id);
+        assert_eq!(ChannelTicketStats::default(), stats.stats_for(&channel_id));
+        assert!(stats.all().is_empty());
+    }
+
+    #[test]
+    fn ticket_stats_should_keep_at_most_the_given_number_of_channels() {
+        let stats = TicketStats::with_capacity(10);
+        for i in 0..100_u32 {
+            stats.rejected(Hash::create(&[&i.to_be_bytes()]));
+        }
+        stats.channels.run_pending_tasks();
+
+        assert!(
+            stats.all().len() <= 10,
+            "rejected tickets of arbitrary channels must not grow the registry beyond its capacity"
+        );
+    }
+}
@@ -1,14 +1,174 @@
-use std::sync::Arc;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_lock::RwLock;
+use hopr_crypto_types::types::{PacketTag, PACKET_TAG_LENGTH};
 use hopr_internal_types::protocol::TagBloomFilter;
 use hopr_platform::file::native::{read_file, write};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
+
+use crate::errors::ProtocolError;
+
+/// Default maximum time a newly observed tag can stay in the write-ahead log without being synced to disk.
+pub const DEFAULT_TAG_WAL_MAX_BATCH_LATENCY: Duration = Duration::from_millis(50);
+
+/// Write-ahead log file of packet tags observed since the last full save of the tag Bloom filter.
+#[derive(Debug)]
+struct TagWal {
+    path: String,
+    file: File,
+    written: u64,
+}
+
+impl TagWal {
+    fn open(path: &str) -> std::io::Result<Self> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path: path.into(),
+            file,
+            written,
+        })
+    }
+
+    /// Reads all complete tags stored in the log. A trailing partially written tag is ignored.
+    fn read_tags(&mut self) -> std::io::Result<Vec<PacketTag>> {
+        let mut data = Vec::with_capacity(self.written as usize);
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.read_to_end(&mut data)?;
+
+        Ok(data
+            .chunks_exact(PACKET_TAG_LENGTH)
+            .map(|chunk| chunk.try_into().expect("chunk has the exact tag length"))
+            .collect())
+    }
+
+    /// Appends the `tags` and syncs them to disk.
+    fn append(&mut self, tags: &[PacketTag]) -> std::io::Result<()> {
+        if let Err(error) = self.file.write_all(&tags.concat()).and_then(|_| self.file.sync_data()) {
+            // A partially written batch is dropped, so that the tags stay aligned when it is written again
+            let _ = self.file.set_len(self.written);
+            return Err(error);
+        }
+
+        self.written += (tags.len() * PACKET_TAG_LENGTH) as u64;
+        Ok(())
+    }
+
+    /// Drops all the tags written before `offset`, retaining the ones written after it.
+    ///
+    /// The retained tags are written into a temporary file first, which then atomically replaces the log,
+    /// so that no tag is lost if the process crashes in the middle of the truncation.
+    fn truncate_until(&mut self, offset: u64) -> std::io::Result<()> {
+        let mut retained = Vec::with_capacity(self.written.saturating_sub(offset) as usize);
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_to_end(&mut retained)?;
+
+        let tmp_path = format!("{}.tmp", self.path);
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(&retained)?;
+            tmp.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        self.file = OpenOptions::new().read(true).append(true).open(&self.path)?;
+        self.written = retained.len() as u64;
+        Ok(())
+    }
+}
+
+/// Tags not written to the log yet, along with the waiters for their sync.
+#[derive(Debug, Default)]
+struct PendingTags {
+    tags: Vec<PacketTag>,
+    synced: Vec<futures::channel::oneshot::Sender<()>>,
+}
+
+/// Write-ahead log shared by the clones of a [`WrappedTagBloomFilter`].
+///
+/// The newly observed tags are only buffered in memory on the packet processing path.
+/// The blocking file I/O happens in batches on the blocking thread pool, see [`WrappedTagBloomFilter::sync_wal`].
+#[derive(Debug)]
+struct SharedTagWal {
+    pending: Mutex<PendingTags>,
+    written: AtomicU64,
+    file: Mutex<TagWal>,
+}
+
+impl SharedTagWal {
+    fn new(wal: TagWal) -> Self {
+        Self {
+            pending: Mutex::new(PendingTags::default()),
+            written: AtomicU64::new(wal.written),
+            file: Mutex::new(wal),
+        }
+    }
+
+    /// Queues the `tag`, the returned receiver completes once it has been synced to disk.
+    fn push(&self, tag: &PacketTag) -> futures::channel::oneshot::Receiver<()> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.tags.push(*tag);
+        pending.synced.push(tx);
+        rx
+    }
+
+    fn take_pending(&self) -> PendingTags {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Puts back a batch that failed to be written, ahead of the tags queued in the meantime.
+    fn restore(&self, batch: PendingTags) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.tags.splice(0..0, batch.tags);
+        pending.synced.splice(0..0, batch.synced);
+    }
+
+    /// Number of bytes written to the log, which all belong to the tags already set in the filter.
+    fn written(&self) -> u64 {
+        self.written.load(Ordering::Acquire)
+    }
+
+    fn append(&self, tags: &[PacketTag]) -> std::io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let result = file.append(tags);
+        self.written.store(file.written, Ordering::Release);
+        result
+    }
+
+    fn truncate_until(&self, offset: u64) -> std::io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let result = file.truncate_until(offset);
+        self.written.store(file.written, Ordering::Release);
+        result
+    }
+}
+
+/// Runs the blocking file I/O on the blocking thread pool of the runtime.
+pub(crate) async fn run_blocking<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> Option<R> {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    // The task is detached, its result is delivered over the channel
+    drop(hopr_async_runtime::prelude::spawn_blocking(move || {
+        let _ = tx.send(f());
+    }));
+    rx.await.ok()
+}
 
 #[derive(Debug, Clone)]
 pub struct WrappedTagBloomFilter {
     path: String,
+    namespace: Option<String>,
     tbf: Arc<RwLock<TagBloomFilter>>,
+    wal: Option<Arc<SharedTagWal>>,
+    wal_max_batch_latency: Duration,
 }
 
 impl WrappedTagBloomFilter {
@@ -16,23 +176,180 @@ impl WrappedTagBloomFilter {
         .with_little_endian()
         .with_variable_int_encoding();
 
-    pub fn new(path: String) -> Self {
-        let tbf = read_file(&path)
+    /// Magic bytes at the start of a persisted filter prefixed with its namespace.
+    const NAMESPACE_MAGIC: [u8; 4] = *b"HTBN";
+
+    /// Decodes the filter from the compact encoding, or from the legacy `bincode` encoding
+    /// used by the previous versions.
+    fn decode(data: &[u8]) -> Result<TagBloomFilter, String> {
+        if TagBloomFilter::is_compact_encoding(data) {
+            TagBloomFilter::from_compact_bytes(data).map_err(|e| e.to_string())
+        } else {
+            bincode::serde::decode_from_slice(data, Self::TAGBLOOM_BINCODE_CONFIGURATION)
+                .map(|(f, _)| f)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    /// Splits the persisted data into the namespace (if any) and the encoded filter.
+    ///
+    /// The namespace header consists of [`Self::NAMESPACE_MAGIC`], the namespace length (as little-endian `u16`)
+    /// and the UTF-8 namespace itself.
+    fn split_namespace(data: &[u8]) -> Result<(Option<String>, &[u8]), String> {
+        let Some(rest) = data.strip_prefix(&Self::NAMESPACE_MAGIC) else {
+            return Ok((None, data));
+        };
+
+        let (len, rest) = rest
+            .split_first_chunk::<2>()
+            .ok_or_else(|| "truncated namespace header".to_string())?;
+        let len = u16::from_le_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err("truncated namespace".into());
+        }
+
+        let (namespace, filter) = rest.split_at(len);
+        let namespace = String::from_utf8(namespace.to_vec()).map_err(|e| e.to_string())?;
+        Ok((Some(namespace), filter))
+    }
+
+    /// Prefixes the encoded filter with the header of the given namespace.
+    fn with_namespace_header(namespace: &str, filter: Vec<u8>) -> Vec<u8> {
+        // The namespaces are short identifiers, longer ones are truncated
+        let namespace = &namespace.as_bytes()[..namespace.len().min(u16::MAX as usize)];
+
+        let mut ret = Vec::with_capacity(Self::NAMESPACE_MAGIC.len() + 2 + namespace.len() + filter.len());
+        ret.extend_from_slice(&Self::NAMESPACE_MAGIC);
+        ret.extend_from_slice(&(namespace.len() as u16).to_le_bytes());
+        ret.extend_from_slice(namespace);
+        ret.extend(filter);
+        ret
+    }
+
+    /// Loads the filter and its namespace from the given `path`.
+    ///
+    /// If the filter cannot be found or decoded, an empty filter is used instead.
+    fn load(path: &str) -> (Option<String>, TagBloomFilter) {
+        read_file(path)
+            .map_err(|e| e.to_string())
             .and_then(|data| {
-                debug!(path = &path, "Found and loading a tag Bloom filter");
-                bincode::serde::decode_from_slice(&data, Self::TAGBLOOM_BINCODE_CONFIGURATION)
-                    .map(|(f, _)| f)
-                    .map_err(|e| hopr_platform::error::PlatformError::GeneralError(e.to_string()))
+                debug!(path, "Found and loading a tag Bloom filter");
+                let (namespace, filter) = Self::split_namespace(&data)?;
+                Ok((namespace, Self::decode(filter)?))
             })
             .unwrap_or_else(|_| {
-                debug!(path = &path, "No tag Bloom filter found, using empty");
-                TagBloomFilter::default()
-            });
+                debug!(path, "No tag Bloom filter found, using empty");
+                (None, TagBloomFilter::default())
+            })
+    }
+
+    /// Loads the filter from the given `path`.
+    ///
+    /// If the file belongs to a namespace, the namespace is retained when the filter is [saved](WrappedTagBloomFilter::save).
+    pub fn new(path: String) -> Self {
+        let (namespace, tbf) = Self::load(&path);
 
         Self {
             path,
+            namespace,
             tbf: Arc::new(RwLock::new(tbf)),
+            wal: None,
+            wal_max_batch_latency: DEFAULT_TAG_WAL_MAX_BATCH_LATENCY,
+        }
+    }
+
+    /// Creates the filter the same way as [`WrappedTagBloomFilter::new`], but the filter belongs to the given
+    /// `namespace`, which is stored in the persisted file.
+    ///
+    /// This prevents several transport instances in the same process from sharing or overwriting each other's
+    /// filter by accident. Fails if the file at `path` belongs to a different namespace.
+    /// Files without any namespace are loaded and the namespace is stored on the next save.
+    pub fn new_namespaced(path: String, namespace: &str) -> crate::errors::Result<Self> {
+        Self::open_namespaced(path, namespace, false)
+    }
+
+    /// Same as [`WrappedTagBloomFilter::new_namespaced`], but a file belonging to a different namespace
+    /// is loaded as well if `force` is set. The file is then taken over by the given `namespace` on the next save.
+    pub fn open_namespaced(path: String, namespace: &str, force: bool) -> crate::errors::Result<Self> {
+        let mut tbf = Self::new(path);
+
+        match tbf.namespace.as_deref() {
+            Some(found) if found != namespace && !force => {
+                return Err(ProtocolError::BloomNamespaceMismatch {
+                    path: tbf.path,
+                    expected: namespace.into(),
+                    found: found.into(),
+                })
+            }
+            Some(found) if found != namespace => {
+                warn!(
+                    path = &tbf.path,
+                    found, namespace, "Taking over tag Bloom filter of a different namespace"
+                );
+            }
+            _ => {}
         }
+
+        tbf.namespace = Some(namespace.into());
+        Ok(tbf)
+    }
+
+    /// Creates the filter the same way as [`WrappedTagBloomFilter::new`], but additionally
+    /// keeps a write-ahead log of newly observed tags next to the filter file (`<path>.wal`).
+    ///
+    /// See [`WrappedTagBloomFilter::with_wal`].
+    pub fn new_with_wal(path: String, max_batch_latency: Duration) -> Self {
+        Self::new(path).with_wal(max_batch_latency)
+    }
+
+    /// Keeps a write-ahead log of newly observed tags next to the filter file (`<path>.wal`).
+    ///
+    /// Tags found in an existing write-ahead log are replayed into the filter, so that packets
+    /// received after the last successful [save](WrappedTagBloomFilter::save) cannot be replayed
+    /// after a crash. The log is synced to disk at least every `max_batch_latency`
+    /// (see [`WrappedTagBloomFilter::sync_wal`]) and truncated after each full save.
+    pub fn with_wal(mut self, max_batch_latency: Duration) -> Self {
+        let wal_path = self.wal_path();
+        self.wal = match TagWal::open(&wal_path) {
+            Ok(mut wal) => {
+                match wal.read_tags() {
+                    Ok(tags) => {
+                        // The filter is not shared yet, so the lock is never contended
+                        let mut tbf = self.tbf.write_blocking();
+                        tags.iter().for_each(|tag| tbf.set(tag));
+                        debug!(path = &wal_path, count = tags.len(), "Replayed tag write-ahead log");
+                    }
+                    Err(error) => error!(path = &wal_path, %error, "Failed to replay the tag write-ahead log"),
+                }
+                Some(Arc::new(SharedTagWal::new(wal)))
+            }
+            Err(error) => {
+                warn!(path = &wal_path, %error, "Failed to open the tag write-ahead log, running without it");
+                None
+            }
+        };
+        self.wal_max_batch_latency = max_batch_latency;
+        self
+    }
+
+    /// Namespace the filter belongs to, if any.
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Path to the write-ahead log of this filter.
+    pub fn wal_path(&self) -> String {
+        format!("{}.wal", self.path)
+    }
+
+    /// Maximum duration a tag can stay in the write-ahead log without being synced to disk.
+    pub fn wal_max_batch_latency(&self) -> Duration {
+        self.wal_max_batch_latency
+    }
+
+    /// Indicates whether this filter uses a write-ahead log.
+    pub fn has_wal(&self) -> bool {
+        self.wal.is_some()
     }
 
     pub async fn with_write_lock<T>(&self, f: impl FnOnce(&mut TagBloomFilter) -> T) -> T {
@@ -40,16 +357,414 @@ impl WrappedTagBloomFilter {
         f(&mut tbf)
     }
 
+    /// Sets the tag in the filter and queues it for the write-ahead log if it has not been seen before.
+    ///
+    /// Returns whether the tag was already present, and the receiver of the sync of a queued tag.
+    async fn set_and_queue(&self, tag: &PacketTag) -> (bool, Option<futures::channel::oneshot::Receiver<()>>) {
+        let mut tbf = self.tbf.write().await;
+        let was_present = tbf.check_and_set(tag);
+
+        // Queued under the filter lock, so that each tag in the log is also in the filter being saved
+        let synced = match &self.wal {
+            Some(wal) if !was_present => Some(wal.push(tag)),
+            _ => None,
+        };
+
+        (was_present, synced)
+    }
+
+    /// Checks and sets the packet tag in the filter, queuing it for the write-ahead log
+    /// if it has not been seen before.
+    ///
+    /// The queued tag survives a crash only once the log has been [synced](WrappedTagBloomFilter::sync_wal),
+    /// see [`WrappedTagBloomFilter::check_and_set_durably`].
+    /// Returns `true` if the tag was already present.
+    pub async fn check_and_set(&self, tag: &PacketTag) -> bool {
+        self.set_and_queue(tag).await.0
+    }
+
+    /// Same as [`WrappedTagBloomFilter::check_and_set`], but a tag not seen before is returned only once
+    /// it has been synced to the write-ahead log.
+    ///
+    /// A packet processed after this returns cannot be replayed after a crash, at the cost of waiting
+    /// up to the [maximum batch latency](WrappedTagBloomFilter::wal_max_batch_latency) for the next sync.
+    pub async fn check_and_set_durably(&self, tag: &PacketTag) -> bool {
+        let (was_present, synced) = self.set_and_queue(tag).await;
+        if let Some(synced) = synced {
+            // The waiter is dropped without a sync only together with the log itself
+            if synced.await.is_err() {
+                warn!("Tag write-ahead log closed before the tag was synced");
+            }
+        }
+
+        was_present
+    }
+
+    /// Appends the tags observed since the last sync to the write-ahead log and syncs them to disk.
+    ///
+    /// The file I/O runs on the blocking thread pool. The tags failing to be written are kept
+    /// for the next sync.
+    pub async fn sync_wal(&self) {
+        let Some(wal) = self.wal.clone() else {
+            return;
+        };
+
+        let batch = wal.take_pending();
+        if batch.tags.is_empty() {
+            return;
+        }
+
+        let (wal_2, tags) = (wal.clone(), batch.tags.clone());
+        match run_blocking(move || wal_2.append(&tags)).await {
+            Some(Ok(())) => batch.synced.into_iter().for_each(|synced| {
+                let _ = synced.send(());
+            }),
+            Some(Err(error)) => {
+                error!(%error, "Tag write-ahead log sync failed");
+                wal.restore(batch);
+            }
+            None => {
+                error!("Tag write-ahead log sync was interrupted");
+                wal.restore(batch);
+            }
+        }
+    }
+
     pub async fn save(&self) {
-        let bloom = self.tbf.read().await.clone(); // Clone to immediately release the lock
+        let (bloom, wal_offset) = {
+            // Clone to immediately release the lock
+            let tbf = self.tbf.read().await;
+            let wal_offset = self.wal.as_ref().map(|wal| wal.written());
+            (tbf.clone(), wal_offset)
+        };
 
-        if let Err(e) = bincode::serde::encode_to_vec(&bloom, Self::TAGBLOOM_BINCODE_CONFIGURATION)
-            .map_err(|e| hopr_platform::error::PlatformError::GeneralError(e.to_string()))
-            .and_then(|d| write(&self.path, &d))
-        {
+        let data = match &self.namespace {
+            Some(namespace) => Self::with_namespace_header(namespace, bloom.to_compact_bytes()),
+            None => bloom.to_compact_bytes(),
+        };
+
+        if let Err(e) = write(&self.path, data) {
             error!(error = %e, "Tag Bloom filter save failed")
         } else {
-            info!("Tag Bloom filter saved successfully")
+            info!("Tag Bloom filter saved successfully");
+
+            // Tags observed while the filter was being saved must remain in the log
+            if let Some((wal, offset)) = self.wal.clone().zip(wal_offset) {
+                if let Some(Err(error)) = run_blocking(move || wal.truncate_until(offset)).await {
+                    error!(%error, "Tag write-ahead log truncation failed");
+                }
+            }
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_async_runtime::clock::MockClock;
+    use hopr_crypto_random::random_bytes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn tmp_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("tbf").to_str().expect("path must be valid").to_owned()
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_recover_tags_from_wal_after_crash_before_save() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tags = (0..10).map(|_| random_bytes::<PACKET_TAG_LENGTH>()).collect::<Vec<_>>();
+
+        {
+            let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+            for tag in &tags {
+                assert!(!tbf.check_and_set(tag).await, "tag must not be present yet");
+            }
+            tbf.sync_wal().await;
+            // The writer is killed here: the tags have been appended to the log, but no save has been performed
+        }
+
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+        for tag in &tags {
+            assert!(
+                tbf.check_and_set(tag).await,
+                "tag must be recovered from the write-ahead log"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_truncate_wal_after_save() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+        assert!(!tbf.check_and_set(&tag).await);
+        tbf.sync_wal().await;
+        assert_eq!(PACKET_TAG_LENGTH as u64, std::fs::metadata(tbf.wal_path())?.len());
+
+        tbf.save().await;
+        assert_eq!(
+            0,
+            std::fs::metadata(tbf.wal_path())?.len(),
+            "wal must be empty after save"
+        );
+
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+        assert!(
+            tbf.check_and_set(&tag).await,
+            "tag must be loaded from the saved filter"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_return_durably_set_tags_only_after_the_sync() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+
+        let mut set = std::pin::pin!(tbf.check_and_set_durably(&tag));
+        assert!(
+            futures::poll!(set.as_mut()).is_pending(),
+            "tag must not be returned before the sync"
+        );
+
+        tbf.sync_wal().await;
+        assert_eq!(PACKET_TAG_LENGTH as u64, std::fs::metadata(tbf.wal_path())?.len());
+        assert!(!set.await, "tag must not have been present");
+
+        assert!(
+            tbf.check_and_set_durably(&tag).await,
+            "present tag must be returned right away"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_keep_tags_failing_to_be_synced() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+
+        assert!(!tbf.check_and_set(&tag).await);
+        let wal = tbf.wal.clone().expect("wal must be open");
+
+        // The log file is replaced by a read-only one, so that the append fails
+        {
+            let mut file = wal.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.file = File::open(tbf.wal_path())?;
+        }
+        tbf.sync_wal().await;
+        assert_eq!(vec![tag], wal.pending.lock().unwrap_or_else(|e| e.into_inner()).tags);
+
+        {
+            let mut file = wal.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.file = OpenOptions::new().read(true).append(true).open(tbf.wal_path())?;
+        }
+        tbf.sync_wal().await;
+        assert!(wal.pending.lock().unwrap_or_else(|e| e.into_inner()).tags.is_empty());
+        assert_eq!(PACKET_TAG_LENGTH as u64, std::fs::metadata(tbf.wal_path())?.len());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_write_wal_only_in_batches() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+
+        for _ in 0..10 {
+            assert!(!tbf.check_and_set(&random_bytes::<PACKET_TAG_LENGTH>()).await);
+        }
+        assert_eq!(
+            0,
+            std::fs::metadata(tbf.wal_path())?.len(),
+            "tags must not be written on the packet path"
+        );
+
+        tbf.sync_wal().await;
+        assert_eq!(10 * PACKET_TAG_LENGTH as u64, std::fs::metadata(tbf.wal_path())?.len());
+
+        // The log is compacted after the save
+        tbf.save().await;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+        assert!(!tbf.check_and_set(&tag).await);
+        tbf.sync_wal().await;
+        assert_eq!(PACKET_TAG_LENGTH as u64, std::fs::metadata(tbf.wal_path())?.len());
+        assert!(
+            !std::path::Path::new(&format!("{}.tmp", tbf.wal_path())).exists(),
+            "temporary file must be renamed over the log"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_be_persisted_on_each_virtual_tick() -> anyhow::Result<()> {
+        const PERSIST_CYCLE: Duration = Duration::from_secs(90);
+
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+        let clock = MockClock::default();
+        let saves = Arc::new(AtomicUsize::new(0));
+
+        let tbf = WrappedTagBloomFilter::new(tmp_path(&dir));
+        let (tbf_clone, saves_clone) = (tbf.clone(), saves.clone());
+        let persist = async_std::task::spawn(crate::execute_on_tick_with_clock(
+            clock.clone(),
+            PERSIST_CYCLE,
+            move || {
+                let tbf = tbf_clone.clone();
+                let saves = saves_clone.clone();
+                async move {
+                    tbf.save().await;
+                    saves.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+            "persisting the bloom filter to disk".into(),
+        ));
+
+        async_std::future::timeout(Duration::from_secs(5), async {
+            while saves.load(Ordering::SeqCst) < 1 {
+                async_std::task::yield_now().await;
+            }
+        })
+        .await?;
+
+        assert!(!tbf.check_and_set(&tag).await);
+        assert!(
+            !WrappedTagBloomFilter::new(tmp_path(&dir)).check_and_set(&tag).await,
+            "tag must not be persisted before the next tick"
+        );
+
+        async_std::future::timeout(Duration::from_secs(5), async {
+            while saves.load(Ordering::SeqCst) < 2 {
+                clock.advance(PERSIST_CYCLE);
+                async_std::task::yield_now().await;
+            }
+        })
+        .await?;
+        persist.cancel().await;
+
+        assert!(
+            WrappedTagBloomFilter::new(tmp_path(&dir)).check_and_set(&tag).await,
+            "tag must be persisted by the next tick"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_be_saved_compactly_and_load_legacy_format() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tags = (0..100)
+            .map(|_| random_bytes::<PACKET_TAG_LENGTH>())
+            .collect::<Vec<_>>();
+
+        let tbf = WrappedTagBloomFilter::new(tmp_path(&dir));
+        for tag in &tags {
+            assert!(!tbf.check_and_set(tag).await);
+        }
+        tbf.save().await;
+
+        let saved = std::fs::read(tmp_path(&dir))?;
+        assert!(TagBloomFilter::is_compact_encoding(&saved));
+
+        let legacy = tbf
+            .with_write_lock(|tbf| {
+                bincode::serde::encode_to_vec(&*tbf, WrappedTagBloomFilter::TAGBLOOM_BINCODE_CONFIGURATION)
+            })
+            .await?;
+        assert!(
+            saved.len() < legacy.len(),
+            "compact file must be smaller than the legacy one"
+        );
+
+        let tbf = WrappedTagBloomFilter::new(tmp_path(&dir));
+        for tag in &tags {
+            assert!(tbf.check_and_set(tag).await, "tag must be loaded from the compact file");
+        }
+
+        std::fs::write(tmp_path(&dir), &legacy)?;
+        let tbf = WrappedTagBloomFilter::new(tmp_path(&dir));
+        for tag in &tags {
+            assert!(tbf.check_and_set(tag).await, "tag must be loaded from the legacy file");
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_reject_file_of_another_namespace() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+
+        // Files without namespace are adopted by the first namespace
+        let tbf = WrappedTagBloomFilter::new(tmp_path(&dir));
+        assert!(!tbf.check_and_set(&tag).await);
+        tbf.save().await;
+
+        let tbf = WrappedTagBloomFilter::new_namespaced(tmp_path(&dir), "mainnet")?;
+        assert!(
+            tbf.check_and_set(&tag).await,
+            "tag must be loaded from the file without namespace"
+        );
+        tbf.save().await;
+
+        assert!(matches!(
+            WrappedTagBloomFilter::new_namespaced(tmp_path(&dir), "staging"),
+            Err(ProtocolError::BloomNamespaceMismatch { expected, found, .. }) if expected == "staging" && found == "mainnet"
+        ));
+
+        let tbf = WrappedTagBloomFilter::new_namespaced(tmp_path(&dir), "mainnet")?;
+        assert_eq!(Some("mainnet"), tbf.namespace());
+        assert!(
+            tbf.check_and_set(&tag).await,
+            "tag must be loaded in the same namespace"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_of_another_namespace_should_be_taken_over_when_forced() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+
+        let tbf = WrappedTagBloomFilter::new_namespaced(tmp_path(&dir), "mainnet")?;
+        assert!(!tbf.check_and_set(&tag).await);
+        tbf.save().await;
+
+        let tbf = WrappedTagBloomFilter::open_namespaced(tmp_path(&dir), "staging", true)?;
+        assert!(tbf.check_and_set(&tag).await, "tag must be loaded when forced");
+        tbf.save().await;
+
+        assert_eq!(Some("staging"), WrappedTagBloomFilter::new(tmp_path(&dir)).namespace());
+        assert!(WrappedTagBloomFilter::new_namespaced(tmp_path(&dir), "mainnet").is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn tag_bloom_filter_should_ignore_partially_written_wal_entry() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tag = random_bytes::<PACKET_TAG_LENGTH>();
+
+        {
+            let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+            assert!(!tbf.check_and_set(&tag).await);
+            tbf.sync_wal().await;
+            let mut file = OpenOptions::new().append(true).open(tbf.wal_path())?;
+            file.write_all(&tag[..PACKET_TAG_LENGTH / 2])?;
+        }
+
+        let tbf = WrappedTagBloomFilter::new_with_wal(tmp_path(&dir), DEFAULT_TAG_WAL_MAX_BATCH_LATENCY);
+        assert!(tbf.check_and_set(&tag).await);
+
+        Ok(())
+    }
+}
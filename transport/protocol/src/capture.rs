@@ -0,0 +1,593 @@
+//! Capturing of the ingress wire traffic of the `msg`/`ack` pipeline and its deterministic replay.
+//!
+//! When the optional [WireTap] is given to the [`run_msg_ack_protocol`](crate::run_msg_ack_protocol),
+//! every `msg` and `ack` item received from the wire is appended to a capture file before it is processed.
+//! The capture can be later fed into another pipeline instance (e.g. running against a copy of the same DB)
+//! using [replay_capture] to reproduce the processing locally.
+//!
+//! The payloads are encrypted on the wire, so they are captured as they are.
+//!
+//! The capture file starts with the [CAPTURE_FILE_MAGIC] followed by the records of the captured items.
+//! Each record is prefixed by its length (`u32`, little endian) and consists of:
+//! - the receive timestamp in microseconds since the UNIX epoch (`u64`, little endian)
+//! - the [WireDirection] (`u8`)
+//! - the length of the peer ID (`u8`) followed by the peer ID bytes
+//! - the payload bytes
+//!
+//! Once the capture file would exceed its maximum size, it is rotated: the current file is renamed
+//! to `<path>.1`, the previously rotated files are shifted (`<path>.1` to `<path>.2` and so on) and the oldest
+//! ones beyond the maximum number of rotated files are removed.
+//!
+//! The records are written by a background task, so that the file I/O never blocks the pipeline.
+//! The records that do not fit into its queue of [DEFAULT_CAPTURE_QUEUE_SIZE] items are dropped.
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::channel::{mpsc, oneshot};
+use futures::{Sink, SinkExt, StreamExt};
+use tracing::{debug, error, info, trace};
+
+use hopr_async_runtime::prelude::{sleep, spawn};
+use hopr_internal_types::protocol::Acknowledgement;
+use hopr_transport_identity::PeerId;
+
+use crate::errors::{ProtocolError, Result};
+
+/// Magic bytes at the beginning of each capture file.
+pub const CAPTURE_FILE_MAGIC: &[u8; 4] = b"HWTC";
+
+/// Default maximum size of a single capture file in bytes.
+pub const DEFAULT_CAPTURE_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Default number of rotated capture files kept alongside the current one.
+pub const DEFAULT_CAPTURE_MAX_ROTATED_FILES: usize = 4;
+
+/// Default maximum number of the records waiting to be written into the capture file.
+pub const DEFAULT_CAPTURE_QUEUE_SIZE: usize = 4096;
+
+// timestamp + direction + peer ID length
+const RECORD_HEADER_LENGTH: usize = 8 + 1 + 1;
+
+/// Pipeline input a captured wire item was received on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum::Display)]
+#[repr(u8)]
+pub enum WireDirection {
+    /// Packet received over the `msg` protocol.
+    #[strum(to_string = "msg ingress")]
+    MsgIn = 0,
+    /// Acknowledgement received over the `ack` protocol.
+    #[strum(to_string = "ack ingress")]
+    AckIn = 1,
+}
+
+impl TryFrom<u8> for WireDirection {
+    type Error = ProtocolError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::MsgIn),
+            1 => Ok(Self::AckIn),
+            _ => Err(ProtocolError::WireCapture(format!("invalid wire direction {value}"))),
+        }
+    }
+}
+
+/// Single wire item read from a capture file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedWireItem {
+    /// Time the item was received at, since the UNIX epoch.
+    pub timestamp: Duration,
+    /// Pipeline input the item was received on.
+    pub direction: WireDirection,
+    /// Peer the item was received from.
+    pub peer: PeerId,
+    /// Raw payload of the item as received from the wire.
+    pub data: Box<[u8]>,
+}
+
+impl CapturedWireItem {
+    fn encode(&self) -> Vec<u8> {
+        let peer = self.peer.to_bytes();
+        let len = RECORD_HEADER_LENGTH + peer.len() + self.data.len();
+
+        let mut record = Vec::with_capacity(4 + len);
+        record.extend_from_slice(&(len as u32).to_le_bytes());
+        record.extend_from_slice(&(self.timestamp.as_micros() as u64).to_le_bytes());
+        record.push(self.direction as u8);
+        record.push(peer.len() as u8);
+        record.extend_from_slice(&peer);
+        record.extend_from_slice(&self.data);
+        record
+    }
+
+    fn decode(record: &[u8]) -> Result<Self> {
+        if record.len() < RECORD_HEADER_LENGTH {
+            return Err(ProtocolError::WireCapture("record too short".into()));
+        }
+
+        let timestamp = Duration::from_micros(u64::from_le_bytes(record[0..8].try_into().expect("8 bytes")));
+        let direction = WireDirection::try_from(record[8])?;
+        let peer_len = record[9] as usize;
+        let peer = record
+            .get(RECORD_HEADER_LENGTH..RECORD_HEADER_LENGTH + peer_len)
+            .ok_or_else(|| ProtocolError::WireCapture("record too short for the peer id".into()))
+            .and_then(|peer| PeerId::from_bytes(peer).map_err(|e| ProtocolError::WireCapture(e.to_string())))?;
+
+        Ok(Self {
+            timestamp,
+            direction,
+            peer,
+            data: record[RECORD_HEADER_LENGTH + peer_len..].into(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct CaptureFile {
+    path: String,
+    file: File,
+    written: u64,
+    max_file_size: u64,
+    max_rotated_files: usize,
+}
+
+impl CaptureFile {
+    fn open(path: &str) -> std::io::Result<Self> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut written = file.metadata()?.len();
+        if written == 0 {
+            file.write_all(CAPTURE_FILE_MAGIC)?;
+            written = CAPTURE_FILE_MAGIC.len() as u64;
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            file,
+            written,
+            max_file_size: DEFAULT_CAPTURE_MAX_FILE_SIZE,
+            max_rotated_files: DEFAULT_CAPTURE_MAX_ROTATED_FILES,
+        })
+    }
+
+    fn rotated_path(&self, n: usize) -> String {
+        format!("{}.{n}", self.path)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_rotated_files > 0 {
+            for n in (1..self.max_rotated_files).rev() {
+                let from = self.rotated_path(n);
+                if std::path::Path::new(&from).exists() {
+                    std::fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+
+        *self = Self {
+            max_file_size: self.max_file_size,
+            max_rotated_files: self.max_rotated_files,
+            ..Self::open(&self.path)?
+        };
+        debug!(path = self.path, "rotated the wire capture file");
+        Ok(())
+    }
+
+    fn append(&mut self, record: &[u8]) -> std::io::Result<()> {
+        let header_only = self.written <= CAPTURE_FILE_MAGIC.len() as u64;
+        if !header_only && self.written + record.len() as u64 > self.max_file_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(record)?;
+        self.written += record.len() as u64;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum CaptureCommand {
+    Record(Vec<u8>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes the queued records into the `capture` file on the blocking thread pool.
+async fn write_captured_items(
+    capture: Arc<Mutex<CaptureFile>>,
+    queued: Arc<AtomicUsize>,
+    mut commands: mpsc::UnboundedReceiver<CaptureCommand>,
+) {
+    while let Some(command) = commands.next().await {
+        // All the records queued in the meantime are written at once
+        let mut batch = vec![command];
+        while let Ok(command) = commands.try_recv() {
+            batch.push(command);
+        }
+
+        let capture = capture.clone();
+        let queued = queued.clone();
+        crate::bloom::run_blocking(move || {
+            let mut capture = capture.lock().unwrap_or_else(|e| e.into_inner());
+            for command in batch {
+                match command {
+                    CaptureCommand::Record(record) => {
+                        queued.fetch_sub(1, Ordering::Relaxed);
+                        if let Err(error) = capture.append(&record) {
+                            error!(%error, "Failed to capture a wire item");
+                        }
+                    }
+                    CaptureCommand::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        })
+        .await;
+    }
+}
+
+/// Appends the ingress wire items of the pipeline to a capture file with size-based rotation.
+///
+/// The clones of the tap share the same capture file and its writer task, which finishes once all the clones
+/// are dropped.
+#[derive(Debug, Clone)]
+pub struct WireTap {
+    capture: Arc<Mutex<CaptureFile>>,
+    commands: mpsc::UnboundedSender<CaptureCommand>,
+    queued: Arc<AtomicUsize>,
+    max_queued: usize,
+    dropped: Arc<AtomicU64>,
+}
+
+impl WireTap {
+    /// Opens the capture file at the given `path` for appending, creating it if it does not exist.
+    ///
+    /// The file is rotated according to [DEFAULT_CAPTURE_MAX_FILE_SIZE] and [DEFAULT_CAPTURE_MAX_ROTATED_FILES],
+    /// at most [DEFAULT_CAPTURE_QUEUE_SIZE] records wait to be written.
+    pub fn new(path: &str) -> Result<Self> {
+        Self::with_queue_size(path, DEFAULT_CAPTURE_QUEUE_SIZE)
+    }
+
+    /// Same as [WireTap::new], but at most `queue_size` records wait to be written.
+    pub fn with_queue_size(path: &str, queue_size: usize) -> Result<Self> {
+        let capture = Arc::new(Mutex::new(CaptureFile::open(path).map_err(|e| {
+            ProtocolError::WireCapture(format!("cannot open capture file '{path}': {e}"))
+        })?));
+        info!(path, "capturing the ingress wire traffic");
+
+        let (commands, commands_rx) = mpsc::unbounded();
+        let queued = Arc::new(AtomicUsize::new(0));
+        spawn(write_captured_items(capture.clone(), queued.clone(), commands_rx));
+
+        Ok(Self {
+            capture,
+            commands,
+            queued,
+            max_queued: queue_size.max(1),
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Rotates the capture file once it would exceed `max_file_size` bytes,
+    /// keeping at most `max_rotated_files` rotated files.
+    pub fn with_rotation(self, max_file_size: u64, max_rotated_files: usize) -> Self {
+        {
+            let mut capture = self.capture.lock().unwrap_or_else(|e| e.into_inner());
+            capture.max_file_size = max_file_size;
+            capture.max_rotated_files = max_rotated_files;
+        }
+        self
+    }
+
+    /// Path of the current capture file.
+    pub fn path(&self) -> String {
+        self.capture.lock().unwrap_or_else(|e| e.into_inner()).path.clone()
+    }
+
+    /// Number of the records dropped so far, because the writer task could not keep up.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queues the wire item received now from the `peer` for appending to the capture.
+    ///
+    /// The item is dropped if the queue is full, so that the capture never interrupts the pipeline.
+    pub fn record(&self, direction: WireDirection, peer: &PeerId, data: &[u8]) {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        if let Err(error) = self.record_at(timestamp, direction, peer, data) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            trace!(%error, %direction, %peer, "Dropped a captured wire item");
+        }
+    }
+
+    fn record_at(&self, timestamp: Duration, direction: WireDirection, peer: &PeerId, data: &[u8]) -> Result<()> {
+        if self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                (queued < self.max_queued).then_some(queued + 1)
+            })
+            .is_err()
+        {
+            return Err(ProtocolError::WireCapture("capture queue is full".into()));
+        }
+
+        let record = CapturedWireItem {
+            timestamp,
+            direction,
+            peer: *peer,
+            data: data.into(),
+        }
+        .encode();
+
+        self.commands
+            .unbounded_send(CaptureCommand::Record(record))
+            .map_err(|_| ProtocolError::WireCapture("capture writer is closed".into()))
+    }
+
+    /// Waits until all the records queued so far are written into the capture file.
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.commands.unbounded_send(CaptureCommand::Flush(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+/// Reads all the wire items from the capture file at the given `path`.
+///
+/// A trailing partially written record is ignored.
+pub fn read_capture(path: &str) -> Result<Vec<CapturedWireItem>> {
+    let data =
+        std::fs::read(path).map_err(|e| ProtocolError::WireCapture(format!("cannot read capture '{path}': {e}")))?;
+
+    let mut records = data
+        .strip_prefix(CAPTURE_FILE_MAGIC.as_slice())
+        .ok_or_else(|| ProtocolError::WireCapture(format!("'{path}' is not a wire capture file")))?;
+
+    let mut items = Vec::new();
+    while records.len() >= 4 {
+        let len = u32::from_le_bytes(records[0..4].try_into().expect("4 bytes")) as usize;
+        let Some(record) = records.get(4..4 + len) else {
+            break;
+        };
+
+        items.push(CapturedWireItem::decode(record)?);
+        records = &records[4 + len..];
+    }
+
+    if !records.is_empty() {
+        debug!(
+            path,
+            trailing = records.len(),
+            "ignoring a partially written capture record"
+        );
+    }
+
+    Ok(items)
+}
+
+/// Pacing of the items fed into the pipeline by [replay_capture].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ReplayPacing {
+    /// The relative timing of the items is preserved as it was captured.
+    #[default]
+    Recorded,
+    /// The items are fed into the pipeline as fast as it accepts them.
+    AsFastAsPossible,
+}
+
+/// Reads the capture file at the given `path` and feeds the captured items into the `wire_ack` and `wire_msg`
+/// inputs of a pipeline instance (i.e. the senders of the streams given to
+/// the [`run_msg_ack_protocol`](crate::run_msg_ack_protocol)).
+///
+/// Returns the number of replayed items.
+pub async fn replay_capture<A, M>(path: &str, pacing: ReplayPacing, pipeline_inputs: (A, M)) -> Result<usize>
+where
+    A: Sink<(PeerId, Acknowledgement)> + Unpin,
+    M: Sink<(PeerId, Box<[u8]>)> + Unpin,
+{
+    let (mut wire_ack, mut wire_msg) = pipeline_inputs;
+    let items = read_capture(path)?;
+    let count = items.len();
+
+    let start = std::time::Instant::now();
+    let first_timestamp = items.first().map(|item| item.timestamp).unwrap_or_default();
+
+    for item in items {
+        if pacing == ReplayPacing::Recorded {
+            let due = item.timestamp.saturating_sub(first_timestamp);
+            let elapsed = start.elapsed();
+            if due > elapsed {
+                sleep(due - elapsed).await;
+            }
+        }
+
+        match item.direction {
+            WireDirection::MsgIn => wire_msg
+                .send((item.peer, item.data))
+                .await
+                .map_err(|_| ProtocolError::TransportError("msg pipeline input is closed".into()))?,
+            WireDirection::AckIn => wire_ack
+                .send((item.peer, Acknowledgement::try_from(item.data.as_ref())?))
+                .await
+                .map_err(|_| ProtocolError::TransportError("ack pipeline input is closed".into()))?,
+        }
+    }
+
+    info!(path, count, "replayed the captured wire traffic");
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use hopr_crypto_random::Randomizable;
+    use hopr_crypto_types::prelude::*;
+
+    fn capture_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("wire.capture").to_string_lossy().into_owned()
+    }
+
+    #[async_std::test]
+    async fn wire_tap_should_capture_items_in_order() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let peer = PeerId::random();
+
+        let tap = WireTap::new(&capture_path(&dir))?;
+        tap.record_at(Duration::from_micros(10), WireDirection::MsgIn, &peer, &[1, 2, 3])?;
+        tap.record_at(Duration::from_micros(20), WireDirection::AckIn, &peer, &[4, 5])?;
+        tap.flush().await;
+
+        // Re-opening appends to the existing capture
+        let tap = WireTap::new(&capture_path(&dir))?;
+        tap.record_at(Duration::from_micros(30), WireDirection::MsgIn, &peer, &[])?;
+        tap.flush().await;
+
+        assert_eq!(
+            vec![
+                (Duration::from_micros(10), WireDirection::MsgIn, vec![1, 2, 3]),
+                (Duration::from_micros(20), WireDirection::AckIn, vec![4, 5]),
+                (Duration::from_micros(30), WireDirection::MsgIn, vec![]),
+            ],
+            read_capture(&capture_path(&dir))?
+                .into_iter()
+                .map(|item| (item.timestamp, item.direction, item.data.into_vec()))
+                .collect::<Vec<_>>()
+        );
+        assert!(read_capture(&capture_path(&dir))?.iter().all(|item| item.peer == peer));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn read_capture_should_ignore_partially_written_record() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tap = WireTap::new(&capture_path(&dir))?;
+        tap.record_at(Duration::ZERO, WireDirection::MsgIn, &PeerId::random(), &[1; 100])?;
+        tap.flush().await;
+
+        let mut data = std::fs::read(capture_path(&dir))?;
+        let partial = data[CAPTURE_FILE_MAGIC.len()..data.len() - 10].to_vec();
+        data.extend_from_slice(&partial);
+        std::fs::write(capture_path(&dir), &data)?;
+
+        assert_eq!(1, read_capture(&capture_path(&dir))?.len());
+
+        std::fs::write(capture_path(&dir), b"garbage")?;
+        assert!(read_capture(&capture_path(&dir)).is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn wire_tap_should_rotate_capture_files() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let peer = PeerId::random();
+        let record_len = CapturedWireItem {
+            timestamp: Duration::ZERO,
+            direction: WireDirection::MsgIn,
+            peer,
+            data: vec![0; 100].into(),
+        }
+        .encode()
+        .len() as u64;
+
+        // Two records fit into a single file
+        let tap = WireTap::new(&capture_path(&dir))?.with_rotation(CAPTURE_FILE_MAGIC.len() as u64 + 2 * record_len, 2);
+        for i in 0..7_u8 {
+            tap.record_at(Duration::ZERO, WireDirection::MsgIn, &peer, &[i; 100])?;
+        }
+        tap.flush().await;
+
+        let first_bytes = |path: &str| -> anyhow::Result<Vec<u8>> {
+            Ok(read_capture(path)?.into_iter().map(|item| item.data[0]).collect())
+        };
+
+        assert_eq!(vec![6], first_bytes(&capture_path(&dir))?);
+        assert_eq!(vec![4, 5], first_bytes(&format!("{}.1", capture_path(&dir)))?);
+        assert_eq!(vec![2, 3], first_bytes(&format!("{}.2", capture_path(&dir)))?);
+        assert!(
+            !std::path::Path::new(&format!("{}.3", capture_path(&dir))).exists(),
+            "the oldest capture must be removed"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn wire_tap_should_drop_records_not_fitting_into_the_queue() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let peer = PeerId::random();
+        let tap = WireTap::with_queue_size(&capture_path(&dir), 2)?;
+
+        {
+            // Stalls the writer
+            let _capture = tap.capture.lock().unwrap();
+            for i in 0..5_u8 {
+                tap.record(WireDirection::MsgIn, &peer, &[i]);
+            }
+            assert_eq!(3, tap.dropped(), "records over the queue size must be dropped");
+        }
+
+        tap.flush().await;
+        assert_eq!(
+            vec![0, 1],
+            read_capture(&capture_path(&dir))?
+                .into_iter()
+                .map(|item| item.data[0])
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn replay_capture_should_feed_items_into_matching_inputs_preserving_timing() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let peer = PeerId::random();
+        let ack = Acknowledgement::new(HalfKey::random(), &OffchainKeypair::random());
+
+        let tap = WireTap::new(&capture_path(&dir))?;
+        tap.record_at(Duration::from_millis(1000), WireDirection::MsgIn, &peer, &[1])?;
+        tap.record_at(Duration::from_millis(1100), WireDirection::AckIn, &peer, ack.as_ref())?;
+        tap.record_at(Duration::from_millis(1200), WireDirection::MsgIn, &peer, &[2])?;
+        tap.flush().await;
+
+        let (ack_tx, ack_rx) = futures::channel::mpsc::unbounded();
+        let (msg_tx, msg_rx) = futures::channel::mpsc::unbounded();
+
+        let start = std::time::Instant::now();
+        assert_eq!(
+            3,
+            replay_capture(&capture_path(&dir), ReplayPacing::Recorded, (ack_tx, msg_tx)).await?
+        );
+        assert!(
+            start.elapsed() >= Duration::from_millis(200),
+            "relative timing must be preserved"
+        );
+
+        assert_eq!(
+            vec![(peer, ack.as_ref().to_vec())],
+            ack_rx.map(|(p, a)| (p, a.as_ref().to_vec())).collect::<Vec<_>>().await
+        );
+        assert_eq!(
+            vec![
+                (peer, vec![1_u8].into_boxed_slice()),
+                (peer, vec![2_u8].into_boxed_slice())
+            ],
+            msg_rx.collect::<Vec<_>>().await
+        );
+
+        Ok(())
+    }
+}
@@ -1,8 +1,38 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
 use hopr_primitive_types::prelude::Balance;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use validator::Validate;
 
+use crate::ack::config::{AckProtocolConfig, MalformedAckPolicy};
+use crate::heartbeat::config::HeartbeatProtocolConfig;
+use crate::msg::config::{BufferBudget, DropLogSampling, MsgProtocolConfig, PeerMetricLabels};
+use crate::retry::DbRetryConfig;
+use crate::stream::{PrioritySchedulerConfig, SinkFailurePolicy, SourceErrorBackoffConfig};
+use crate::ticket_aggregation::config::{AggregationBusyPolicy, TicketAggregationProtocolConfig};
+use crate::ticket_aggregation::wire::DEFAULT_MAX_AGGREGATION_REQUEST_BYTES;
+
+/// Curated presets of the [`ProtocolConfig`] for the typical kinds of nodes.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Node on constrained hardware relaying little traffic.
+    ///
+    /// Uses a single acknowledgement signing worker, small caches and few metric labels,
+    /// tolerates slower peers and aggregates tickets less often.
+    LowPower,
+    /// General purpose node, matching the defaults with a fixed number of 2 acknowledgement signing workers.
+    Balanced,
+    /// Node on dedicated hardware relaying a lot of traffic.
+    ///
+    /// Uses 8 acknowledgement signing workers and large duplicate acknowledgement caches, retries
+    /// failed wire sends shortly, bounds the time the senders can wait for the pipeline and bans
+    /// peers repeatedly sending malformed acknowledgements.
+    HighThroughputRelay,
+}
+
 /// Configuration of the P2P protocols.
 #[serde_as]
 #[derive(Debug, smart_default::SmartDefault, Serialize, Deserialize, Validate, Copy, Clone, PartialEq)]
@@ -16,9 +46,553 @@ pub struct ProtocolConfig {
     /// Possible override of the network outgoing ticket price.
     pub outgoing_ticket_price: Option<Balance>,
     /// `heartbeat` protocol config
+    #[validate(nested)]
     #[serde(default)]
-    pub heartbeat: crate::heartbeat::config::HeartbeatProtocolConfig,
+    pub heartbeat: HeartbeatProtocolConfig,
     /// `ticket_aggregation` protocol config
+    #[validate(nested)]
     #[serde(default)]
-    pub ticket_aggregation: crate::ticket_aggregation::config::TicketAggregationProtocolConfig,
+    pub ticket_aggregation: TicketAggregationProtocolConfig,
+    /// `msg` protocol config
+    #[validate(nested)]
+    #[serde(default)]
+    pub msg: MsgProtocolConfig,
+    /// `ack` protocol config
+    #[validate(nested)]
+    #[serde(default)]
+    pub ack: AckProtocolConfig,
+}
+
+impl ProtocolConfig {
+    /// Configuration with all the sub-configs filled from the given preset.
+    ///
+    /// The outgoing ticket winning probability and price are left to the network values.
+    pub fn profile(profile: Profile) -> Self {
+        match profile {
+            Profile::LowPower => Self {
+                heartbeat: HeartbeatProtocolConfig {
+                    probe_timeout: Duration::from_secs(10),
+                    responder_timeout: Duration::from_secs(2),
+                    responder_queue_size: 16,
+                },
+                ticket_aggregation: TicketAggregationProtocolConfig {
+                    timeout: Duration::from_secs(30),
+                    min_aggregatable_tickets: 10,
+                    max_request_bytes: DEFAULT_MAX_AGGREGATION_REQUEST_BYTES,
+                    max_concurrent_aggregations: 4,
+                    busy_policy: AggregationBusyPolicy::Reject,
+                },
+                msg: MsgProtocolConfig {
+                    peer_metric_labels: PeerMetricLabels::TopN(10),
+                    sink_failure_policy: SinkFailurePolicy::Log,
+                    send_finalizer_timeout: Some(Duration::from_secs(30)),
+                    max_application_data_bytes: None,
+                    egress_pause_buffer_size: 256,
+                    db_retry: DbRetryConfig::default(),
+                    finalize_after_wire_send: false,
+                    drop_log_sampling: DropLogSampling {
+                        every_nth: 1,
+                        max_per_sec: Some(1),
+                    },
+                    buffer_budget: BufferBudget {
+                        max_bytes: 4 * 1024 * 1024,
+                        relay_percent: 50,
+                    },
+                    max_throttled_per_peer: 16,
+                    source_error_backoff: SourceErrorBackoffConfig {
+                        max_sources: 1_000,
+                        max_delayed_per_source: 4,
+                        ..Default::default()
+                    },
+                    priority_scheduler: PrioritySchedulerConfig {
+                        capacity: 256,
+                        ..Default::default()
+                    },
+                    tag_wal_max_batch_latency: Duration::from_millis(100),
+                },
+                ack: AckProtocolConfig {
+                    sink_failure_policy: SinkFailurePolicy::Log,
+                    expectation_window: Duration::from_secs(60),
+                    max_pending_acks: 10_000,
+                    duplicate_window: Duration::from_secs(60),
+                    duplicate_capacity: 10_000,
+                    signing_workers: 1,
+                    malformed_ack_policy: MalformedAckPolicy::Drop,
+                },
+                ..Default::default()
+            },
+            Profile::Balanced => Self {
+                ack: AckProtocolConfig {
+                    signing_workers: 2,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Profile::HighThroughputRelay => {
+                let retry = SinkFailurePolicy::RetryN {
+                    attempts: 3,
+                    delay: Duration::from_millis(50),
+                };
+
+                Self {
+                    heartbeat: HeartbeatProtocolConfig {
+                        probe_timeout: Duration::from_secs(4),
+                        responder_timeout: Duration::from_secs(1),
+                        responder_queue_size: 16,
+                    },
+                    ticket_aggregation: TicketAggregationProtocolConfig {
+                        timeout: Duration::from_secs(15),
+                        min_aggregatable_tickets: 1,
+                        max_request_bytes: DEFAULT_MAX_AGGREGATION_REQUEST_BYTES,
+                        max_concurrent_aggregations: 64,
+                        busy_policy: AggregationBusyPolicy::default(),
+                    },
+                    msg: MsgProtocolConfig {
+                        peer_metric_labels: PeerMetricLabels::TopN(200),
+                        sink_failure_policy: retry,
+                        send_finalizer_timeout: Some(Duration::from_secs(5)),
+                        max_application_data_bytes: None,
+                        egress_pause_buffer_size: 8192,
+                        db_retry: DbRetryConfig::default(),
+                        finalize_after_wire_send: false,
+                        drop_log_sampling: DropLogSampling {
+                            every_nth: 100,
+                            max_per_sec: Some(10),
+                        },
+                        buffer_budget: BufferBudget {
+                            max_bytes: 64 * 1024 * 1024,
+                            relay_percent: 90,
+                        },
+                        max_throttled_per_peer: 256,
+                        source_error_backoff: SourceErrorBackoffConfig {
+                            max_sources: 100_000,
+                            max_delayed_per_source: 64,
+                            ..Default::default()
+                        },
+                        priority_scheduler: PrioritySchedulerConfig {
+                            capacity: 8192,
+                            ..Default::default()
+                        },
+                        tag_wal_max_batch_latency: Duration::from_millis(20),
+                    },
+                    ack: AckProtocolConfig {
+                        sink_failure_policy: retry,
+                        expectation_window: Duration::from_secs(30),
+                        max_pending_acks: 1_000_000,
+                        duplicate_window: Duration::from_secs(120),
+                        duplicate_capacity: 1_000_000,
+                        signing_workers: 8,
+                        malformed_ack_policy: MalformedAckPolicy::Ban { threshold: 100 },
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    /// Human-readable list of the values of this config deviating from the `other` config.
+    ///
+    /// Each entry has the form `<path>: <other value> -> <value of this config>`.
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut diff = Vec::new();
+
+        push_diff(
+            &mut diff,
+            "outgoing_ticket_winning_prob",
+            &other.outgoing_ticket_winning_prob,
+            &self.outgoing_ticket_winning_prob,
+        );
+        push_diff(
+            &mut diff,
+            "outgoing_ticket_price",
+            &other.outgoing_ticket_price,
+            &self.outgoing_ticket_price,
+        );
+
+        let (this, other_hb) = (&self.heartbeat, &other.heartbeat);
+        push_diff(
+            &mut diff,
+            "heartbeat.probe_timeout",
+            &other_hb.probe_timeout,
+            &this.probe_timeout,
+        );
+        push_diff(
+            &mut diff,
+            "heartbeat.responder_timeout",
+            &other_hb.responder_timeout,
+            &this.responder_timeout,
+        );
+        push_diff(
+            &mut diff,
+            "heartbeat.responder_queue_size",
+            &other_hb.responder_queue_size,
+            &this.responder_queue_size,
+        );
+
+        let (this, other_ta) = (&self.ticket_aggregation, &other.ticket_aggregation);
+        push_diff(
+            &mut diff,
+            "ticket_aggregation.timeout",
+            &other_ta.timeout,
+            &this.timeout,
+        );
+        push_diff(
+            &mut diff,
+            "ticket_aggregation.min_aggregatable_tickets",
+            &other_ta.min_aggregatable_tickets,
+            &this.min_aggregatable_tickets,
+        );
+        push_diff(
+            &mut diff,
+            "ticket_aggregation.max_request_bytes",
+            &other_ta.max_request_bytes,
+            &this.max_request_bytes,
+        );
+        push_diff(
+            &mut diff,
+            "ticket_aggregation.max_concurrent_aggregations",
+            &other_ta.max_concurrent_aggregations,
+            &this.max_concurrent_aggregations,
+        );
+        push_diff(
+            &mut diff,
+            "ticket_aggregation.busy_policy",
+            &other_ta.busy_policy,
+            &this.busy_policy,
+        );
+
+        let (this, other_msg) = (&self.msg, &other.msg);
+        push_diff(
+            &mut diff,
+            "msg.peer_metric_labels",
+            &other_msg.peer_metric_labels,
+            &this.peer_metric_labels,
+        );
+        push_diff(
+            &mut diff,
+            "msg.sink_failure_policy",
+            &other_msg.sink_failure_policy,
+            &this.sink_failure_policy,
+        );
+        push_diff(
+            &mut diff,
+            "msg.send_finalizer_timeout",
+            &other_msg.send_finalizer_timeout,
+            &this.send_finalizer_timeout,
+        );
+        push_diff(
+            &mut diff,
+            "msg.max_application_data_bytes",
+            &other_msg.max_application_data_bytes,
+            &this.max_application_data_bytes,
+        );
+        push_diff(
+            &mut diff,
+            "msg.egress_pause_buffer_size",
+            &other_msg.egress_pause_buffer_size,
+            &this.egress_pause_buffer_size,
+        );
+        push_diff(&mut diff, "msg.db_retry", &other_msg.db_retry, &this.db_retry);
+        push_diff(
+            &mut diff,
+            "msg.finalize_after_wire_send",
+            &other_msg.finalize_after_wire_send,
+            &this.finalize_after_wire_send,
+        );
+        push_diff(
+            &mut diff,
+            "msg.drop_log_sampling",
+            &other_msg.drop_log_sampling,
+            &this.drop_log_sampling,
+        );
+        push_diff(
+            &mut diff,
+            "msg.buffer_budget",
+            &other_msg.buffer_budget,
+            &this.buffer_budget,
+        );
+        push_diff(
+            &mut diff,
+            "msg.max_throttled_per_peer",
+            &other_msg.max_throttled_per_peer,
+            &this.max_throttled_per_peer,
+        );
+        push_diff(
+            &mut diff,
+            "msg.source_error_backoff",
+            &other_msg.source_error_backoff,
+            &this.source_error_backoff,
+        );
+        push_diff(
+            &mut diff,
+            "msg.priority_scheduler",
+            &other_msg.priority_scheduler,
+            &this.priority_scheduler,
+        );
+        push_diff(
+            &mut diff,
+            "msg.tag_wal_max_batch_latency",
+            &other_msg.tag_wal_max_batch_latency,
+            &this.tag_wal_max_batch_latency,
+        );
+
+        let (this, other_ack) = (&self.ack, &other.ack);
+        push_diff(
+            &mut diff,
+            "ack.sink_failure_policy",
+            &other_ack.sink_failure_policy,
+            &this.sink_failure_policy,
+        );
+        push_diff(
+            &mut diff,
+            "ack.expectation_window",
+            &other_ack.expectation_window,
+            &this.expectation_window,
+        );
+        push_diff(
+            &mut diff,
+            "ack.max_pending_acks",
+            &other_ack.max_pending_acks,
+            &this.max_pending_acks,
+        );
+        push_diff(
+            &mut diff,
+            "ack.duplicate_window",
+            &other_ack.duplicate_window,
+            &this.duplicate_window,
+        );
+        push_diff(
+            &mut diff,
+            "ack.duplicate_capacity",
+            &other_ack.duplicate_capacity,
+            &this.duplicate_capacity,
+        );
+        push_diff(
+            &mut diff,
+            "ack.signing_workers",
+            &other_ack.signing_workers,
+            &this.signing_workers,
+        );
+        push_diff(
+            &mut diff,
+            "ack.malformed_ack_policy",
+            &other_ack.malformed_ack_policy,
+            &this.malformed_ack_policy,
+        );
+
+        diff
+    }
+}
+
+fn push_diff<T: PartialEq + Debug>(diff: &mut Vec<String>, path: &str, other: &T, this: &T) {
+    if other != this {
+        diff.push(format!("{path}: {other:?} -> {this:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assert_snapshot(profile: Profile, expected: serde_json::Value) -> anyhow::Result<()> {
+        let cfg = ProtocolConfig::profile(profile);
+        assert_eq!(
+            expected,
+            serde_json::to_value(cfg)?,
+            "the {profile:?} preset has changed, update the snapshot if intended"
+        );
+        assert_eq!(cfg, serde_json::from_value::<ProtocolConfig>(expected)?);
+        Ok(())
+    }
+
+    #[test]
+    fn protocol_config_profiles_should_pass_validation() {
+        for profile in [Profile::LowPower, Profile::Balanced, Profile::HighThroughputRelay] {
+            assert!(
+                ProtocolConfig::profile(profile).validate().is_ok(),
+                "{profile:?} must be valid"
+            );
+        }
+    }
+
+    #[test]
+    fn protocol_config_should_reject_zero_max_application_data_bytes() {
+        let mut cfg = ProtocolConfig::default();
+        cfg.msg.max_application_data_bytes = Some(0);
+        assert!(cfg.validate().is_err());
+
+        cfg.msg.max_application_data_bytes = Some(1024);
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn protocol_config_low_power_profile_snapshot() -> anyhow::Result<()> {
+        assert_snapshot(
+            Profile::LowPower,
+            json!({
+                "outgoing_ticket_winning_prob": null,
+                "outgoing_ticket_price": null,
+                "heartbeat": {"probe_timeout": 10, "responder_timeout": 2, "responder_queue_size": 16},
+                "ticket_aggregation": {
+                    "timeout": 30,
+                    "min_aggregatable_tickets": 10,
+                    "max_request_bytes": 1048576,
+                    "max_concurrent_aggregations": 4,
+                    "busy_policy": "reject"
+                },
+                "msg": {
+                    "peer_metric_labels": {"top_n": 10},
+                    "sink_failure_policy": "log",
+                    "send_finalizer_timeout": 30000,
+                    "max_application_data_bytes": null,
+                    "egress_pause_buffer_size": 256,
+                    "db_retry": {"max_retries": 3, "initial_backoff": 10, "max_backoff": 200},
+                    "finalize_after_wire_send": false,
+                    "drop_log_sampling": {"every_nth": 1, "max_per_sec": 1},
+                    "buffer_budget": {"max_bytes": 4194304, "relay_percent": 50},
+                    "max_throttled_per_peer": 16,
+                    "source_error_backoff": {
+                        "error_threshold": 5,
+                        "initial_backoff": 100,
+                        "max_backoff": 30000,
+                        "max_sources": 1000,
+                        "max_delayed_per_source": 4
+                    },
+                    "priority_scheduler": {"capacity": 256, "max_high_in_row": 8},
+                    "tag_wal_max_batch_latency": 100
+                },
+                "ack": {
+                    "sink_failure_policy": "log",
+                    "expectation_window": 60,
+                    "max_pending_acks": 10000,
+                    "duplicate_window": 60,
+                    "duplicate_capacity": 10000,
+                    "signing_workers": 1,
+                    "malformed_ack_policy": "drop"
+                }
+            }),
+        )
+    }
+
+    #[test]
+    fn protocol_config_balanced_profile_snapshot() -> anyhow::Result<()> {
+        assert_snapshot(
+            Profile::Balanced,
+            json!({
+                "outgoing_ticket_winning_prob": null,
+                "outgoing_ticket_price": null,
+                "heartbeat": {"probe_timeout": 6, "responder_timeout": 1, "responder_queue_size": 16},
+                "ticket_aggregation": {
+                    "timeout": 15,
+                    "min_aggregatable_tickets": 1,
+                    "max_request_bytes": 1048576,
+                    "max_concurrent_aggregations": 16,
+                    "busy_policy": {"queue": {"timeout": 5000}}
+                },
+                "msg": {
+                    "peer_metric_labels": {"top_n": 50},
+                    "sink_failure_policy": "log",
+                    "send_finalizer_timeout": null,
+                    "max_application_data_bytes": null,
+                    "egress_pause_buffer_size": 1024,
+                    "db_retry": {"max_retries": 3, "initial_backoff": 10, "max_backoff": 200},
+                    "finalize_after_wire_send": false,
+                    "drop_log_sampling": {"every_nth": 1, "max_per_sec": 10},
+                    "buffer_budget": {"max_bytes": 16777216, "relay_percent": 75},
+                    "max_throttled_per_peer": 64,
+                    "source_error_backoff": {
+                        "error_threshold": 5,
+                        "initial_backoff": 100,
+                        "max_backoff": 30000,
+                        "max_sources": 10000,
+                        "max_delayed_per_source": 16
+                    },
+                    "priority_scheduler": {"capacity": 1024, "max_high_in_row": 8},
+                    "tag_wal_max_batch_latency": 50
+                },
+                "ack": {
+                    "sink_failure_policy": "log",
+                    "expectation_window": 30,
+                    "max_pending_acks": 100000,
+                    "duplicate_window": 120,
+                    "duplicate_capacity": 100000,
+                    "signing_workers": 2,
+                    "malformed_ack_policy": "drop"
+                }
+            }),
+        )
+    }
+
+    #[test]
+    fn protocol_config_high_throughput_relay_profile_snapshot() -> anyhow::Result<()> {
+        assert_snapshot(
+            Profile::HighThroughputRelay,
+            json!({
+                "outgoing_ticket_winning_prob": null,
+                "outgoing_ticket_price": null,
+                "heartbeat": {"probe_timeout": 4, "responder_timeout": 1, "responder_queue_size": 16},
+                "ticket_aggregation": {
+                    "timeout": 15,
+                    "min_aggregatable_tickets": 1,
+                    "max_request_bytes": 1048576,
+                    "max_concurrent_aggregations": 64,
+                    "busy_policy": {"queue": {"timeout": 5000}}
+                },
+                "msg": {
+                    "peer_metric_labels": {"top_n": 200},
+                    "sink_failure_policy": {"retry_n": {"attempts": 3, "delay": 50}},
+                    "send_finalizer_timeout": 5000,
+                    "max_application_data_bytes": null,
+                    "egress_pause_buffer_size": 8192,
+                    "db_retry": {"max_retries": 3, "initial_backoff": 10, "max_backoff": 200},
+                    "finalize_after_wire_send": false,
+                    "drop_log_sampling": {"every_nth": 100, "max_per_sec": 10},
+                    "buffer_budget": {"max_bytes": 67108864, "relay_percent": 90},
+                    "max_throttled_per_peer": 256,
+                    "source_error_backoff": {
+                        "error_threshold": 5,
+                        "initial_backoff": 100,
+                        "max_backoff": 30000,
+                        "max_sources": 100000,
+                        "max_delayed_per_source": 64
+                    },
+                    "priority_scheduler": {"capacity": 8192, "max_high_in_row": 8},
+                    "tag_wal_max_batch_latency": 20
+                },
+                "ack": {
+                    "sink_failure_policy": {"retry_n": {"attempts": 3, "delay": 50}},
+                    "expectation_window": 30,
+                    "max_pending_acks": 1000000,
+                    "duplicate_window": 120,
+                    "duplicate_capacity": 1000000,
+                    "signing_workers": 8,
+                    "malformed_ack_policy": {"ban": {"threshold": 100}}
+                }
+            }),
+        )
+    }
+
+    #[test]
+    fn protocol_config_diff_should_list_deviating_values() {
+        let balanced = ProtocolConfig::profile(Profile::Balanced);
+        assert!(balanced.diff(&balanced).is_empty());
+
+        let cfg = ProtocolConfig {
+            outgoing_ticket_winning_prob: Some(0.5),
+            ack: AckProtocolConfig {
+                signing_workers: 4,
+                ..balanced.ack
+            },
+            ..balanced
+        };
+
+        assert_eq!(
+            vec![
+                "outgoing_ticket_winning_prob: None -> Some(0.5)".to_string(),
+                "ack.signing_workers: 2 -> 4".to_string(),
+            ],
+            cfg.diff(&balanced)
+        );
+    }
 }
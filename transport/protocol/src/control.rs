@@ -0,0 +1,303 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_lock::{Mutex, RwLock, RwLockWriteGuardArc};
+use futures::{Stream, StreamExt};
+
+use crate::errors::ProtocolError;
+
+/// Gate which can be closed and opened, shared by all its clones.
+#[derive(Debug, Clone, Default)]
+struct Gate {
+    lock: Arc<RwLock<()>>,
+    close_guard: Arc<Mutex<Option<RwLockWriteGuardArc<()>>>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Gate {
+    /// Closes the gate, returns `false` if it has been closed already.
+    async fn close(&self) -> bool {
+        let mut guard = self.close_guard.lock().await;
+        if guard.is_none() {
+            *guard = Some(self.lock.write_arc().await);
+            self.closed.store(true, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Opens the gate, returns `false` if it has not been closed.
+    async fn open(&self) -> bool {
+        if self.close_guard.lock().await.take().is_some() {
+            self.closed.store(false, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the gate is open.
+    async fn passed(&self) {
+        drop(self.lock.read().await);
+    }
+}
+
+/// Controls whether the pipeline processes pull new items from their input streams.
+///
+/// While paused, the pipeline stops pulling from the incoming `wire_msg` and outgoing `api` streams,
+/// so the backpressure builds up in the transport, while all the in-memory state (bloom filter,
+/// acknowledgement tracking, ...) is retained. Items already being processed are finished.
+///
+/// Independently, only the egress of the packets originated by this node can be paused, see
+/// [`PipelineControl::pause_egress`].
+///
+/// The clones of the control share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineControl {
+    pipeline: Gate,
+    egress: Gate,
+}
+
+impl PipelineControl {
+    /// Stops pulling new items from the input streams.
+    ///
+    /// Pausing an already paused pipeline has no effect.
+    pub async fn pause(&self) {
+        if self.pipeline.close().await {
+            tracing::info!("Protocol pipeline paused");
+        }
+    }
+
+    /// Resumes pulling new items from the input streams.
+    ///
+    /// Resuming a pipeline that is not paused has no effect.
+    pub async fn resume(&self) {
+        if self.pipeline.open().await {
+            tracing::info!("Protocol pipeline resumed");
+        }
+    }
+
+    /// Indicates whether the pipeline is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.pipeline.is_closed()
+    }
+
+    /// Stops sending the packets originated by this node, e.g. while the outgoing channels are being funded.
+    ///
+    /// The relayed packets and the acknowledgements keep flowing. The sends made while paused are held
+    /// back in the order they were made, up to the configured
+    /// [`egress_pause_buffer_size`](crate::msg::config::MsgProtocolConfig::egress_pause_buffer_size),
+    /// the sends that do not fit are finalized with [`ProtocolError::EgressPaused`].
+    ///
+    /// Pausing an already paused egress has no effect.
+    pub async fn pause_egress(&self) {
+        if self.egress.close().await {
+            tracing::info!("Protocol egress paused");
+        }
+    }
+
+    /// Resumes sending the packets originated by this node, starting with the ones held back while paused.
+    ///
+    /// Resuming an egress that is not paused has no effect.
+    pub async fn resume_egress(&self) {
+        if self.egress.open().await {
+            tracing::info!("Protocol egress resumed");
+        }
+    }
+
+    /// Indicates whether the egress of the packets originated by this node is currently paused.
+    pub fn is_egress_paused(&self) -> bool {
+        self.egress.is_closed()
+    }
+
+    /// Makes the `stream` wait before pulling each item while the pipeline is paused.
+    pub(crate) fn pausable<S>(&self, stream: S) -> impl Stream<Item = S::Item>
+    where
+        S: Stream + Send + 'static,
+    {
+        futures::stream::unfold((Box::pin(stream), self.clone()), |(mut stream, control)| async move {
+            control.pipeline.passed().await;
+            stream.next().await.map(|item| (item, (stream, control)))
+        })
+    }
+
+    /// Holds back the items of the `stream` while the egress is paused.
+    ///
+    /// Up to `capacity` items are buffered while paused and yielded in order once resumed,
+    /// the items pulled while the buffer is full are passed to the `overflow` function instead,
+    /// along with the [`ProtocolError::EgressPaused`] error.
+    pub(crate) fn egress_gated<S, F>(&self, stream: S, capacity: usize, overflow: F) -> impl Stream<Item = S::Item>
+    where
+        S: Stream + Send + 'static,
+        S::Item: Send,
+        F: Fn(S::Item, ProtocolError) + Send + 'static,
+    {
+        let state = EgressState {
+            stream: Some(Box::pin(stream)),
+            buffer: VecDeque::new(),
+            capacity,
+            overflow,
+            control: self.clone(),
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            state.next().await.map(|item| (item, state))
+        })
+    }
+}
+
+struct EgressState<S: Stream, F> {
+    stream: Option<std::pin::Pin<Box<S>>>,
+    buffer: VecDeque<S::Item>,
+    capacity: usize,
+    overflow: F,
+    control: PipelineControl,
+}
+
+impl<S: Stream, F: Fn(S::Item, ProtocolError)> EgressState<S, F> {
+    fn hold_back(&mut self, item: S::Item) {
+        // An item pulled just as the egress got resumed is queued behind the buffered ones
+        if self.buffer.len() < self.capacity || !self.control.is_egress_paused() {
+            self.buffer.push_back(item);
+        } else {
+            tracing::warn!(
+                capacity = self.capacity,
+                "egress is paused and its buffer is full, dropping send"
+            );
+            (self.overflow)(item, ProtocolError::EgressPaused);
+        }
+    }
+
+    async fn next(&mut self) -> Option<S::Item> {
+        loop {
+            if !self.control.is_egress_paused() {
+                if let Some(item) = self.buffer.pop_front() {
+                    return Some(item);
+                }
+
+                let pulled = self.stream.as_mut()?.next().await;
+                match pulled {
+                    // The egress might have been paused while waiting for the item
+                    Some(item) if self.control.is_egress_paused() => self.hold_back(item),
+                    Some(item) => return Some(item),
+                    None => self.stream = None,
+                }
+            } else if let Some(stream) = self.stream.as_mut() {
+                let pulled = {
+                    let resumed = self.control.egress.passed();
+                    futures::pin_mut!(resumed);
+                    match futures::future::select(stream.next(), resumed).await {
+                        futures::future::Either::Left((pulled, _)) => Some(pulled),
+                        futures::future::Either::Right(_) => None,
+                    }
+                };
+
+                match pulled {
+                    Some(Some(item)) => self.hold_back(item),
+                    Some(None) => self.stream = None,
+                    None => {}
+                }
+            } else {
+                // The input has ended, but the buffered items are still delivered once resumed
+                self.control.egress.passed().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::future::timeout;
+    use std::time::Duration;
+
+    #[async_std::test]
+    async fn pipeline_control_should_stop_pulling_items_while_paused() -> anyhow::Result<()> {
+        let control = PipelineControl::default();
+        let (tx, rx) = futures::channel::mpsc::unbounded::<u32>();
+        let mut stream = Box::pin(control.pausable(rx));
+
+        tx.unbounded_send(1)?;
+        assert_eq!(Some(1), stream.next().await);
+
+        control.pause().await;
+        control.pause().await;
+        assert!(control.is_paused());
+
+        tx.unbounded_send(2)?;
+        assert!(
+            timeout(Duration::from_millis(100), stream.next()).await.is_err(),
+            "no item must be pulled while paused"
+        );
+
+        control.clone().resume().await;
+        assert!(!control.is_paused());
+        assert_eq!(Some(2), stream.next().await);
+
+        drop(tx);
+        assert_eq!(None, stream.next().await);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn egress_gated_stream_should_hold_back_items_while_paused_and_deliver_them_in_order() -> anyhow::Result<()> {
+        let control = PipelineControl::default();
+        let (tx, rx) = futures::channel::mpsc::unbounded::<u32>();
+        let overflown = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let overflown_clone = overflown.clone();
+        let mut stream = Box::pin(control.egress_gated(rx, 3, move |item, error| {
+            assert!(matches!(error, ProtocolError::EgressPaused));
+            overflown_clone.lock().unwrap().push(item)
+        }));
+
+        tx.unbounded_send(1)?;
+        assert_eq!(Some(1), stream.next().await);
+
+        control.pause_egress().await;
+        assert!(control.is_egress_paused());
+        assert!(!control.is_paused(), "only the egress must be paused");
+
+        for i in 2..=6 {
+            tx.unbounded_send(i)?;
+        }
+        assert!(
+            timeout(Duration::from_millis(100), stream.next()).await.is_err(),
+            "no item must be yielded while the egress is paused"
+        );
+        assert_eq!(vec![5, 6], *overflown.lock().unwrap());
+
+        control.clone().resume_egress().await;
+        assert!(!control.is_egress_paused());
+
+        tx.unbounded_send(7)?;
+        drop(tx);
+        assert_eq!(vec![2, 3, 4, 7], stream.collect::<Vec<_>>().await);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn egress_gated_stream_should_deliver_the_buffered_items_after_the_input_has_ended() -> anyhow::Result<()> {
+        let control = PipelineControl::default();
+        let (tx, rx) = futures::channel::mpsc::unbounded::<u32>();
+        let mut stream = Box::pin(control.egress_gated(rx, 10, |_, _| panic!("no item must overflow")));
+
+        control.pause_egress().await;
+        tx.unbounded_send(1)?;
+        tx.unbounded_send(2)?;
+        drop(tx);
+        assert!(timeout(Duration::from_millis(100), stream.next()).await.is_err());
+
+        control.resume_egress().await;
+        assert_eq!(vec![1, 2], stream.collect::<Vec<_>>().await);
+
+        Ok(())
+    }
+}
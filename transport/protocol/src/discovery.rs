@@ -0,0 +1,188 @@
+use futures::{Stream, StreamExt};
+use std::collections::{BTreeSet, HashMap};
+
+use hopr_transport_identity::{Multiaddr, PeerId};
+
+use crate::PeerDiscovery;
+
+/// Transition of a peer's status derived from the [`PeerDiscovery`] events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerStatusChange {
+    /// The peer was not allowed before and is allowed now.
+    BecameAllowed(PeerId),
+    /// The peer was not banned before and is banned now.
+    BecameBanned(PeerId),
+    /// The announced addresses of the peer differ from the previously announced ones.
+    AddressesChanged(PeerId, Vec<Multiaddr>),
+}
+
+#[derive(Debug, Default)]
+struct PeerStatus {
+    allowed: Option<bool>,
+    addresses: BTreeSet<Multiaddr>,
+}
+
+/// Maintains the current status of each peer from the [`PeerDiscovery`] events and turns
+/// the events into [`PeerStatusChange`] transitions.
+///
+/// Events that do not change the status (e.g. duplicates) produce no transition. The allowance
+/// and the addresses are tracked independently, so an announcement arriving before the peer
+/// is allowed is handled the same as one arriving after it. The first allowance or ban of
+/// a previously unseen peer is always reported.
+#[derive(Debug, Default)]
+pub struct PeerDiscoveryState {
+    peers: HashMap<PeerId, PeerStatus>,
+}
+
+impl PeerDiscoveryState {
+    /// Applies the event and returns the transition it caused, if any.
+    pub fn apply(&mut self, event: PeerDiscovery) -> Option<PeerStatusChange> {
+        match event {
+            PeerDiscovery::Allow(peer) => self
+                .set_allowed(peer, true)
+                .then_some(PeerStatusChange::BecameAllowed(peer)),
+            PeerDiscovery::Ban(peer) => self
+                .set_allowed(peer, false)
+                .then_some(PeerStatusChange::BecameBanned(peer)),
+            PeerDiscovery::Announce(peer, addresses) => {
+                let addresses = addresses.into_iter().collect::<BTreeSet<_>>();
+                let status = self.peers.entry(peer).or_default();
+                if status.addresses != addresses {
+                    status.addresses = addresses;
+                    Some(PeerStatusChange::AddressesChanged(
+                        peer,
+                        status.addresses.iter().cloned().collect(),
+                    ))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn set_allowed(&mut self, peer: PeerId, allowed: bool) -> bool {
+        let status = self.peers.entry(peer).or_default();
+        let changed = status.allowed != Some(allowed);
+        status.allowed = Some(allowed);
+        changed
+    }
+
+    /// Indicates whether the peer is currently allowed.
+    pub fn is_allowed(&self, peer: &PeerId) -> bool {
+        self.peers.get(peer).and_then(|status| status.allowed).unwrap_or(false)
+    }
+
+    /// Currently known addresses of the peer.
+    pub fn addresses(&self, peer: &PeerId) -> Vec<Multiaddr> {
+        self.peers
+            .get(peer)
+            .map(|status| status.addresses.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Extension trait turning a stream of [`PeerDiscovery`] events into a stream of [`PeerStatusChange`] transitions.
+pub trait PeerDiscoveryChangesExt: Stream<Item = PeerDiscovery> + Sized {
+    /// Emits only the transitions of the peer status, as tracked by [`PeerDiscoveryState`].
+    fn status_changes(self) -> impl Stream<Item = PeerStatusChange> {
+        self.scan(PeerDiscoveryState::default(), |state, event| {
+            futures::future::ready(Some(state.apply(event)))
+        })
+        .filter_map(futures::future::ready)
+    }
+}
+
+impl<S: Stream<Item = PeerDiscovery>> PeerDiscoveryChangesExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn addr(port: u16) -> Multiaddr {
+        Multiaddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}")).expect("must be a valid multiaddress")
+    }
+
+    #[test]
+    fn peer_discovery_state_should_ignore_duplicate_events() {
+        let mut state = PeerDiscoveryState::default();
+        let peer = PeerId::random();
+
+        assert_eq!(
+            Some(PeerStatusChange::BecameAllowed(peer)),
+            state.apply(PeerDiscovery::Allow(peer))
+        );
+        assert_eq!(None, state.apply(PeerDiscovery::Allow(peer)));
+        assert!(state.is_allowed(&peer));
+
+        assert_eq!(
+            Some(PeerStatusChange::BecameBanned(peer)),
+            state.apply(PeerDiscovery::Ban(peer))
+        );
+        assert_eq!(None, state.apply(PeerDiscovery::Ban(peer)));
+        assert!(!state.is_allowed(&peer));
+    }
+
+    #[test]
+    fn peer_discovery_state_should_report_only_changed_addresses() {
+        let mut state = PeerDiscoveryState::default();
+        let peer = PeerId::random();
+
+        assert_eq!(
+            Some(PeerStatusChange::AddressesChanged(peer, vec![addr(1), addr(2)])),
+            state.apply(PeerDiscovery::Announce(peer, vec![addr(2), addr(1)]))
+        );
+        assert_eq!(
+            None,
+            state.apply(PeerDiscovery::Announce(peer, vec![addr(1), addr(2), addr(1)])),
+            "the order and duplicates of addresses must not matter"
+        );
+        assert_eq!(
+            Some(PeerStatusChange::AddressesChanged(peer, vec![addr(3)])),
+            state.apply(PeerDiscovery::Announce(peer, vec![addr(3)]))
+        );
+        assert_eq!(vec![addr(3)], state.addresses(&peer));
+    }
+
+    #[test]
+    fn peer_discovery_state_should_track_announcements_independently_of_allowance() {
+        let mut state = PeerDiscoveryState::default();
+        let peer = PeerId::random();
+
+        state.apply(PeerDiscovery::Announce(peer, vec![addr(1)]));
+        assert!(!state.is_allowed(&peer), "announcement must not allow the peer");
+
+        assert_eq!(
+            Some(PeerStatusChange::BecameAllowed(peer)),
+            state.apply(PeerDiscovery::Allow(peer))
+        );
+        assert_eq!(vec![addr(1)], state.addresses(&peer));
+    }
+
+    #[async_std::test]
+    async fn status_changes_should_emit_only_transitions() {
+        let (peer_1, peer_2) = (PeerId::random(), PeerId::random());
+
+        let changes = futures::stream::iter(vec![
+            PeerDiscovery::Ban(peer_1),
+            PeerDiscovery::Allow(peer_2),
+            PeerDiscovery::Ban(peer_1),
+            PeerDiscovery::Allow(peer_1),
+            PeerDiscovery::Announce(peer_1, vec![addr(1)]),
+            PeerDiscovery::Allow(peer_2),
+        ])
+        .status_changes()
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(
+            vec![
+                PeerStatusChange::BecameBanned(peer_1),
+                PeerStatusChange::BecameAllowed(peer_2),
+                PeerStatusChange::BecameAllowed(peer_1),
+                PeerStatusChange::AddressesChanged(peer_1, vec![addr(1)]),
+            ],
+            changes
+        );
+    }
+}
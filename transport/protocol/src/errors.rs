@@ -1,5 +1,6 @@
 use hopr_internal_types::errors::CoreTypesError;
 use hopr_primitive_types::errors::GeneralError;
+use hopr_transport_identity::PeerId;
 use thiserror::Error;
 
 /// Errors generated by the crate.
@@ -23,9 +24,18 @@ pub enum ProtocolError {
     #[error("invalidate acknowledgement signature")]
     InvalidSignature,
 
+    #[error("acknowledgement is stale or replayed")]
+    StaleAcknowledgement,
+
+    #[error("malformed acknowledgement: {0}")]
+    MalformedAcknowledgement(String),
+
     #[error("underlying transport error while sending packet: {0}")]
     TransportError(String),
 
+    #[error("packet error: {0}")]
+    PacketError(#[from] hopr_crypto_packet::errors::PacketError),
+
     #[error("db error {0}")]
     DatabaseError(#[from] hopr_db_api::errors::DbError),
 
@@ -35,6 +45,12 @@ pub enum ProtocolError {
     #[error("Ticket aggregation error: {0}")]
     ProtocolTicketAggregation(String),
 
+    #[error(transparent)]
+    Aggregation(#[from] AggregationError),
+
+    #[error("ticket aggregation request has been canceled")]
+    AggregationCanceled,
+
     #[error("General error {0}")]
     GeneralError(#[from] GeneralError),
 
@@ -43,6 +59,42 @@ pub enum ProtocolError {
 
     #[error("Failed on a logical error: {0}")]
     Logic(String),
+
+    #[error("tag bloom filter at '{path}' belongs to namespace '{found}' instead of '{expected}'")]
+    BloomNamespaceMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+
+    #[error("wire capture error: {0}")]
+    WireCapture(String),
+
+    #[error("egress is paused and its buffer is full")]
+    EgressPaused,
+
+    #[error("packet could not be handed over to the first hop {0}")]
+    FirstHopUnreachable(PeerId),
+
+    #[error("invalid packet pricing: {0}")]
+    InvalidPricing(String),
+}
+
+/// Errors of the outgoing ticket aggregation requests, raised before the request is sent, when it is refused
+/// by the counterparty or on its cancellation.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AggregationError {
+    #[error("aggregation request of {bytes} bytes exceeds the maximum of {max} bytes")]
+    RequestTooLarge { bytes: usize, max: usize },
+
+    #[error("too few tickets to aggregate, at least {min} are required")]
+    BelowMinimum { min: u32 },
+
+    #[error("too many ticket aggregations in progress")]
+    Busy,
+
+    #[error("aggregation request has been canceled")]
+    Canceled,
 }
 
 /// Result used by the crate, based on the [ProtocolError] error type.
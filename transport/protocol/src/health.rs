@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use hopr_async_runtime::clock::Clock;
+
+use crate::ProtocolProcesses;
+
+/// Health status of a single protocol process.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProcessStatus {
+    /// Indicates whether the process is still running.
+    pub running: bool,
+    /// Instant the process was started at.
+    pub started: Instant,
+    /// Instant of the last item processed or the last tick of a periodic process, if any.
+    pub last_activity: Option<Instant>,
+    /// Number of items processed or ticks performed by the process.
+    pub processed: u64,
+}
+
+impl ProcessStatus {
+    /// Time elapsed since the last activity of the process, or since its start, if it was not active yet.
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_activity.unwrap_or(self.started))
+    }
+}
+
+/// Status of a single process, updated without locking as the process runs.
+///
+/// The instants are stored as nanoseconds since the `epoch` of the state.
+#[derive(Debug)]
+struct ProcessState {
+    epoch: Instant,
+    running: AtomicBool,
+    started: AtomicU64,
+    /// Zero if the process has not been active yet, otherwise one more than the nanoseconds since the `epoch`.
+    last_activity: AtomicU64,
+    processed: AtomicU64,
+}
+
+impl ProcessState {
+    fn new(now: Instant) -> Self {
+        Self {
+            epoch: now,
+            running: AtomicBool::new(true),
+            started: AtomicU64::new(0),
+            last_activity: AtomicU64::new(0),
+            processed: AtomicU64::new(0),
+        }
+    }
+
+    fn since_epoch(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.epoch).as_nanos() as u64
+    }
+
+    fn start(&self, now: Instant) {
+        self.started.store(self.since_epoch(now), Ordering::Relaxed);
+        self.last_activity.store(0, Ordering::Relaxed);
+        self.processed.store(0, Ordering::Relaxed);
+        self.running.store(true, Ordering::Release);
+    }
+
+    fn record(&self, now: Instant) {
+        self.last_activity
+            .fetch_max(self.since_epoch(now) + 1, Ordering::Relaxed);
+        self.processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn status(&self) -> ProcessStatus {
+        let last_activity = self.last_activity.load(Ordering::Relaxed);
+        ProcessStatus {
+            running: self.running.load(Ordering::Acquire),
+            started: self.epoch + Duration::from_nanos(self.started.load(Ordering::Relaxed)),
+            last_activity: (last_activity > 0).then(|| self.epoch + Duration::from_nanos(last_activity - 1)),
+            processed: self.processed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Registry of the [`ProcessStatus`] of the processes run by the [`run_msg_ack_protocol`](crate::run_msg_ack_protocol).
+///
+/// The processes update their status as they run, so it can be inspected without awaiting their join handles,
+/// e.g. by a supervisor that restarts the pipeline once a process has terminated.
+/// The clones of the registry share the same statuses.
+///
+/// The registry is locked only when a process is started; the activity of a running process
+/// is recorded into its own atomic counters.
+#[derive(Debug, Clone, Default)]
+pub struct ProtocolHealth {
+    processes: Arc<RwLock<HashMap<ProtocolProcesses, Arc<ProcessState>>>>,
+}
+
+impl ProtocolHealth {
+    fn state(&self, process: ProtocolProcesses) -> Option<Arc<ProcessState>> {
+        self.processes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&process)
+            .cloned()
+    }
+
+    /// Status of the given process, if it has been started.
+    pub fn status(&self, process: ProtocolProcesses) -> Option<ProcessStatus> {
+        self.state(process).map(|state| state.status())
+    }
+
+    /// Statuses of all the started processes.
+    pub fn all(&self) -> Vec<(ProtocolProcesses, ProcessStatus)> {
+        self.processes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(process, state)| (*process, state.status()))
+            .collect()
+    }
+
+    /// Indicates whether the given process has been started and is still running.
+    pub fn is_running(&self, process: ProtocolProcesses) -> bool {
+        self.status(process).is_some_and(|status| status.running)
+    }
+
+    /// Processes that have terminated or have not been active for longer than `max_idle`.
+    pub fn unhealthy(&self, now: Instant, max_idle: Duration) -> Vec<ProtocolProcesses> {
+        self.all()
+            .into_iter()
+            .filter(|(_, status)| !status.running || status.idle_for(now) > max_idle)
+            .map(|(process, _)| process)
+            .collect()
+    }
+
+    /// Records an item processed (or a tick performed) by the given process.
+    pub(crate) fn record(&self, process: ProtocolProcesses, now: Instant) {
+        if let Some(state) = self.state(process) {
+            state.record(now);
+        }
+    }
+
+    /// Wraps the action of a periodic process, so that each tick is recorded as its activity.
+    pub(crate) fn on_tick<C, F, Fut>(&self, process: ProtocolProcesses, clock: C, action: F) -> impl Fn() -> Fut
+    where
+        C: Clock,
+        F: Fn() -> Fut,
+    {
+        let health = self.clone();
+        move || {
+            health.record(process, clock.now());
+            action()
+        }
+    }
+
+    /// Creates a callback recording each item of the input stream of the given process as its activity.
+    ///
+    /// The status of the process is looked up only until the process has been started.
+    pub(crate) fn recorder<C: Clock, T>(&self, process: ProtocolProcesses, clock: C) -> impl Fn(&T) {
+        let health = self.clone();
+        let state = OnceLock::new();
+        move |_| {
+            let state = match state.get() {
+                Some(state) => Some(state),
+                None => health.state(process).map(|found| state.get_or_init(|| found)),
+            };
+            if let Some(state) = state {
+                state.record(clock.now());
+            }
+        }
+    }
+
+    /// Wraps the future of the given process, so that it is reported as running until the future
+    /// completes or is dropped (e.g. when it panics or is cancelled).
+    pub(crate) fn monitor<F>(&self, process: ProtocolProcesses, now: Instant, f: F) -> impl Future<Output = ()>
+    where
+        F: Future<Output = ()>,
+    {
+        // A restarted process keeps its state, so that the recorders created before remain valid
+        let state = self
+            .processes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(process)
+            .or_insert_with(|| Arc::new(ProcessState::new(now)))
+            .clone();
+        state.start(now);
+
+        let guard = RunningGuard(state);
+        async move {
+            let _guard = guard;
+            f.await
+        }
+    }
+}
+
+struct RunningGuard(Arc<ProcessState>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_async_runtime::clock::MockClock;
+
+    #[async_std::test]
+    async fn protocol_health_should_track_activity_and_termination() {
+        let health = ProtocolHealth::default();
+        let start = Instant::now();
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+
+        let process = async_std::task::spawn(health.monitor(ProtocolProcesses::MsgIn, start, async move {
+            let _ = rx.await;
+        }));
+        assert!(health.is_running(ProtocolProcesses::MsgIn));
+        assert!(
+            !health.is_running(ProtocolProcesses::MsgOut),
+            "not started process must not run"
+        );
+
+        health.record(ProtocolProcesses::MsgIn, start + Duration::from_secs(1));
+        health.record(ProtocolProcesses::MsgIn, start + Duration::from_secs(2));
+
+        let status = health.status(ProtocolProcesses::MsgIn).expect("status must exist");
+        assert_eq!(2, status.processed);
+        assert_eq!(Some(start + Duration::from_secs(2)), status.last_activity);
+        assert!(health
+            .unhealthy(start + Duration::from_secs(3), Duration::from_secs(5))
+            .is_empty());
+        assert_eq!(
+            vec![ProtocolProcesses::MsgIn],
+            health.unhealthy(start + Duration::from_secs(10), Duration::from_secs(5)),
+            "idle process must be reported"
+        );
+
+        tx.send(()).expect("process must be waiting");
+        process.await;
+        assert!(
+            !health.is_running(ProtocolProcesses::MsgIn),
+            "terminated process must not run"
+        );
+    }
+
+    #[async_std::test]
+    async fn protocol_health_recorder_should_record_once_the_process_is_started() {
+        let health = ProtocolHealth::default();
+        let clock = MockClock::default();
+        let record = health.recorder::<_, ()>(ProtocolProcesses::MsgOut, clock.clone());
+
+        record(&());
+        assert_eq!(None, health.status(ProtocolProcesses::MsgOut));
+
+        let started = clock.now();
+        let process =
+            async_std::task::spawn(health.monitor(ProtocolProcesses::MsgOut, started, futures::future::pending()));
+        clock.advance(Duration::from_secs(1));
+        record(&());
+        record(&());
+
+        let status = health.status(ProtocolProcesses::MsgOut).expect("status must exist");
+        assert!(status.running);
+        assert_eq!(started, status.started);
+        assert_eq!(2, status.processed);
+        assert_eq!(Some(started + Duration::from_secs(1)), status.last_activity);
+
+        process.cancel().await;
+    }
+
+    #[async_std::test]
+    async fn protocol_health_should_report_cancelled_process_as_not_running() {
+        let health = ProtocolHealth::default();
+
+        let process = async_std::task::spawn(health.monitor(
+            ProtocolProcesses::AckIn,
+            Instant::now(),
+            futures::future::pending(),
+        ));
+        assert!(health.is_running(ProtocolProcesses::AckIn));
+
+        process.cancel().await;
+        assert!(!health.is_running(ProtocolProcesses::AckIn));
+    }
+}
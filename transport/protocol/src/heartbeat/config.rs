@@ -2,14 +2,84 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSeconds};
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+/// Lower bound of the round-trip time expected from any peer.
+///
+/// The probes must allow for at least two round trips, one to open the stream and one for the ping itself.
+pub const HEARTBEAT_EXPECTED_RTT_FLOOR: Duration = Duration::from_millis(500);
+
+fn default_responder_timeout() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_responder_queue_size() -> usize {
+    16
+}
+
+fn validate_probe_timeout(value: &Duration) -> Result<(), ValidationError> {
+    if *value >= 2 * HEARTBEAT_EXPECTED_RTT_FLOOR {
+        Ok(())
+    } else {
+        Err(ValidationError::new(
+            "heartbeat probe timeout must be at least twice the expected RTT floor",
+        ))
+    }
+}
 
 /// Configuration for the `heartbeat` protocol.
 #[serde_as]
 #[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
 pub struct HeartbeatProtocolConfig {
-    /// Maximum duration before the request times out
+    /// Maximum duration before an outgoing probe times out
     #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(alias = "timeout")]
+    #[validate(custom(function = "validate_probe_timeout"))]
     #[default(Duration::from_secs(6))]
-    pub timeout: Duration,
+    pub probe_timeout: Duration,
+    /// Maximum duration spent answering an incoming ping
+    #[serde_as(as = "DurationSeconds<u64>")]
+    #[serde(default = "default_responder_timeout")]
+    #[default(default_responder_timeout())]
+    pub responder_timeout: Duration,
+    /// Maximum number of incoming pings waiting to be answered, and of pongs waiting for the wire
+    ///
+    /// Each direction is queued separately. The pings that do not fit are dropped rather than answered late,
+    /// while the pongs wait for the wire and hold back the answering of the next pings.
+    #[serde(default = "default_responder_queue_size")]
+    #[validate(range(min = 1))]
+    #[default(default_responder_queue_size())]
+    pub responder_queue_size: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeat_config_should_reject_probe_timeout_below_rtt_floor() {
+        let cfg = HeartbeatProtocolConfig {
+            probe_timeout: HEARTBEAT_EXPECTED_RTT_FLOOR,
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_err());
+
+        let cfg = HeartbeatProtocolConfig {
+            probe_timeout: 2 * HEARTBEAT_EXPECTED_RTT_FLOOR,
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn heartbeat_config_should_accept_legacy_timeout_and_default_the_responder_timeout() -> anyhow::Result<()> {
+        let cfg: HeartbeatProtocolConfig = serde_json::from_str(r#"{"timeout": 5}"#)?;
+
+        assert_eq!(Duration::from_secs(5), cfg.probe_timeout);
+        assert_eq!(Duration::from_secs(1), cfg.responder_timeout);
+        assert_eq!(16, cfg.responder_queue_size);
+        assert!(cfg.validate().is_ok());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,171 @@
+//! Answering of the incoming heartbeat pings.
+//!
+//! The responder runs as two processes of its own: [`ProtocolProcesses::HeartbeatIn`] generating the pongs
+//! and [`ProtocolProcesses::HeartbeatOut`] passing them to the wire. Both are always spawned on the runtime
+//! executor and never using the [`ProcessSpawners`](crate::spawner::ProcessSpawners) of the `msg`/`ack`
+//! pipeline, so they do not wait behind the packet processing, even if that saturates its own executor.
+//!
+//! Each direction has a queue of its own. The pings wait in the queue in front of the responder, which is
+//! the only place where they are dropped, when the responder falls behind. Once a ping is taken, its pong
+//! is never dropped: a slow wire egress holds back the answering of the next pings through the bounded pong
+//! queue, instead of discarding the pings already answered.
+use futures::{Sink, Stream, StreamExt};
+use std::collections::HashMap;
+use tracing::{error, trace};
+
+use hopr_async_runtime::clock::{Clock, RealClock};
+use hopr_async_runtime::prelude::{spawn, JoinHandle};
+use hopr_transport_identity::PeerId;
+
+use crate::heartbeat::config::HeartbeatProtocolConfig;
+use crate::{health, ProtocolProcesses};
+
+/// Run the processes answering the incoming heartbeat pings.
+///
+/// The pings are taken from the `wire_heartbeat` stream together with the channel to answer them on,
+/// each answered using `respond` and the resulting pong is passed into the `wire_heartbeat` sink
+/// along with the same channel. Pings for which `respond` returns `None` are left unanswered.
+///
+/// Each process reports its status into the optional `health` registry.
+pub fn run_heartbeat_responder<Req, Resp, Ch, F>(
+    cfg: HeartbeatProtocolConfig,
+    wire_heartbeat: (
+        impl Sink<(PeerId, Resp, Ch)> + Send + 'static,
+        impl Stream<Item = (PeerId, Req, Ch)> + Send + 'static,
+    ),
+    respond: F,
+    health: Option<health::ProtocolHealth>,
+) -> HashMap<ProtocolProcesses, JoinHandle<()>>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+    Ch: Send + 'static,
+    F: Fn(&PeerId, Req) -> Option<Resp> + Send + 'static,
+{
+    let health = health.unwrap_or_default();
+    let clock = RealClock;
+
+    // The channel capacity is the buffer plus one slot for the single sender
+    let (pong_tx, pong_rx) =
+        futures::channel::mpsc::channel::<(PeerId, Resp, Ch)>(cfg.responder_queue_size.saturating_sub(1));
+
+    let mut processes = HashMap::new();
+
+    let health_in = health.clone();
+    processes.insert(
+        ProtocolProcesses::HeartbeatIn,
+        spawn(health.monitor(ProtocolProcesses::HeartbeatIn, clock.now(), async move {
+            // Waits for the egress, so that the answered pings are never dropped
+            if let Err(error) = wire_heartbeat
+                .1
+                .inspect(health_in.recorder(ProtocolProcesses::HeartbeatIn, clock))
+                .filter_map(move |(peer, ping, channel)| {
+                    let pong = respond(&peer, ping).map(|pong| (peer, pong, channel));
+                    if pong.is_none() {
+                        trace!(%peer, "Ignoring an invalid heartbeat ping");
+                    }
+                    futures::future::ready(pong)
+                })
+                .map(Ok)
+                .forward(pong_tx)
+                .await
+                .map_err(|_| "failed to pass a pong to the egress")
+            {
+                error!(error, "Heartbeat responder ingress terminated");
+            }
+        })),
+    );
+
+    let health_out = health.clone();
+    processes.insert(
+        ProtocolProcesses::HeartbeatOut,
+        spawn(
+            health.monitor(ProtocolProcesses::HeartbeatOut, clock.now(), async move {
+                if let Err(error) = pong_rx
+                    .inspect(health_out.recorder(ProtocolProcesses::HeartbeatOut, clock))
+                    .map(Ok)
+                    .forward(wire_heartbeat.0)
+                    .await
+                    .map_err(|_| "failed to pass a pong to the wire")
+                {
+                    error!(error, "Heartbeat responder egress terminated");
+                }
+            }),
+        ),
+    );
+
+    processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::prelude::FutureExt;
+    use futures::SinkExt;
+
+    #[async_std::test]
+    async fn heartbeat_responder_should_answer_valid_pings_on_their_channel() -> anyhow::Result<()> {
+        let (mut ping_tx, ping_rx) = futures::channel::mpsc::unbounded::<(PeerId, u32, usize)>();
+        let (pong_tx, pong_rx) = futures::channel::mpsc::unbounded::<(PeerId, u32, usize)>();
+
+        let health = health::ProtocolHealth::default();
+        let _processes = run_heartbeat_responder(
+            HeartbeatProtocolConfig::default(),
+            (pong_tx, ping_rx),
+            |_, ping: u32| ping.checked_add(1),
+            Some(health.clone()),
+        );
+
+        let peer = PeerId::random();
+        ping_tx.send((peer, 1, 10)).await?;
+        ping_tx.send((peer, u32::MAX, 11)).await?;
+        ping_tx.send((peer, 5, 12)).await?;
+
+        let pongs = pong_rx
+            .take(2)
+            .collect::<Vec<_>>()
+            .timeout(std::time::Duration::from_secs(1))
+            .await?;
+
+        assert_eq!(vec![(peer, 2, 10), (peer, 6, 12)], pongs);
+        assert!(health.is_running(ProtocolProcesses::HeartbeatIn));
+        assert!(health.is_running(ProtocolProcesses::HeartbeatOut));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn heartbeat_responder_should_deliver_all_pings_when_the_egress_is_stalled() -> anyhow::Result<()> {
+        let cfg = HeartbeatProtocolConfig::default();
+        let ping_count = 3 * cfg.responder_queue_size;
+
+        // Bounded per direction, the same as between the swarm and the responder
+        let (mut ping_tx, ping_rx) =
+            futures::channel::mpsc::channel::<(PeerId, usize, usize)>(cfg.responder_queue_size);
+        let (pong_tx, pong_rx) = futures::channel::mpsc::channel::<(PeerId, usize, usize)>(cfg.responder_queue_size);
+
+        let _processes = run_heartbeat_responder(cfg, (pong_tx, ping_rx), |_, ping: usize| Some(ping), None);
+
+        let peer = PeerId::random();
+        let pinger = async_std::task::spawn(async move {
+            for i in 0..ping_count {
+                ping_tx.send((peer, i, i)).await?;
+            }
+            anyhow::Ok(())
+        });
+
+        // Nothing is taken from the egress until the pings fill up both directions
+        async_std::task::sleep(std::time::Duration::from_millis(100)).await;
+
+        let pongs = pong_rx
+            .take(ping_count)
+            .collect::<Vec<_>>()
+            .timeout(std::time::Duration::from_secs(1))
+            .await?;
+        pinger.timeout(std::time::Duration::from_secs(1)).await??;
+
+        assert_eq!((0..ping_count).map(|i| (peer, i, i)).collect::<Vec<_>>(), pongs);
+
+        Ok(())
+    }
+}
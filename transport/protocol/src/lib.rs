@@ -11,6 +11,7 @@
 //!
 //! Supported protocol processors:
 //!
+//! - `heartbeat` (responder)
 //! - `ticket_aggregation`
 //!
 //! ### `ticket_aggregation`
@@ -60,26 +61,61 @@ pub mod msg;
 /// `ticket_aggregation` p2p protocol
 pub mod ticket_aggregation;
 
+/// Status transitions derived from the peer discovery events
+pub mod discovery;
+
+/// Loopback probing of multi-hop paths over the `msg` protocol
+pub mod probe;
+
+/// Health of the running protocol processes
+pub mod health;
+
+/// Pausing and resuming of the protocol pipeline
+pub mod control;
+
+/// Spawning of the protocol processes
+pub mod spawner;
+
+/// Optional components of the protocol pipeline
+pub mod options;
+
 /// Stream processing utilities
 pub mod stream;
 
+/// Retrying of the DB operations in the processors
+pub mod retry;
+
+/// Capturing and replaying of the ingress wire traffic for debugging
+pub mod capture;
+
+/// Packet forwarding simulator for capacity planning
+#[cfg(feature = "testing")]
+pub mod simulation;
+
+/// Conformance test vectors of the `msg` and `ack` wire encodings
+#[cfg(any(test, feature = "testing"))]
+pub mod vectors;
+
 pub mod timer;
 use hopr_transport_identity::Multiaddr;
-pub use timer::execute_on_tick;
+pub use timer::{execute_on_tick, execute_on_tick_with_clock};
 
 use futures::{SinkExt, StreamExt};
 use rust_stream_ext_concurrent::then_concurrent::StreamThenConcurrentExt;
 use std::collections::HashMap;
-use tracing::error;
+use std::sync::Arc;
+use tracing::{debug, error, trace, warn};
 
+use hopr_async_runtime::clock::{Clock, RealClock};
 use hopr_async_runtime::prelude::spawn;
+use hopr_crypto_types::prelude::{HalfKey, HalfKeyChallenge, Keypair, Randomizable};
 use hopr_db_api::protocol::HoprDbProtocolOperations;
 use hopr_internal_types::protocol::{Acknowledgement, ApplicationData};
-use hopr_network_types::prelude::ResolvedTransportRouting;
 use hopr_transport_identity::PeerId;
 
 pub use msg::processor::DEFAULT_PRICE_PER_PACKET;
-use msg::processor::{PacketSendFinalizer, PacketUnwrapping, PacketWrapping};
+use msg::processor::{PacketUnwrapping, SendMsgInput};
+use stream::{StreamErrorBackoffExt, StreamPriorityExt};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 use hopr_metrics::metrics::{MultiCounter, SimpleCounter};
@@ -114,6 +150,19 @@ lazy_static::lazy_static! {
     ).unwrap();
     static ref METRIC_REJECTED_TICKETS_COUNT: SimpleCounter =
         SimpleCounter::new("hopr_rejected_tickets_count", "Number of rejected tickets").unwrap();
+    static ref METRIC_ACK_TIMEOUTS: SimpleCounter = SimpleCounter::new(
+        "hopr_ack_timeouts_count",
+        "Number of acknowledgements that did not arrive within the expectation window",
+    ).unwrap();
+    static ref METRIC_OVERSIZE_APP_DATA_COUNT: SimpleCounter = SimpleCounter::new(
+        "hopr_oversize_application_data_count",
+        "Number of received application data payloads dropped for exceeding the maximum size",
+    ).unwrap();
+    static ref METRIC_DROPPED_PACKETS_COUNT: MultiCounter = MultiCounter::new(
+        "hopr_dropped_packets_count",
+        "Number of received packets dropped due to an error, by the reason",
+        &["reason"]
+    ).unwrap();
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::Display)]
@@ -130,6 +179,16 @@ pub enum ProtocolProcesses {
     Mixer,
     #[strum(to_string = "bloom filter persistence (periodic)")]
     BloomPersist,
+    #[strum(to_string = "bloom filter write-ahead log sync (periodic)")]
+    BloomWalSync,
+    #[strum(to_string = "HOPR [ack] - timeout check (periodic)")]
+    AckTimeoutCheck,
+    #[strum(to_string = "HOPR [msg] - re-sending unacknowledged packets (periodic)")]
+    Resend,
+    #[strum(to_string = "HOPR [heartbeat] - ingress")]
+    HeartbeatIn,
+    #[strum(to_string = "HOPR [heartbeat] - egress")]
+    HeartbeatOut,
 }
 /// Processed indexer generated events.
 #[derive(Debug, Clone)]
@@ -143,11 +202,54 @@ pub enum PeerDiscovery {
 ///
 /// The pipeline does not handle the mixing itself, that needs to be injected as a separate process
 /// overlayed on top of the `wire_msg` Stream or Sink.
+///
+/// Packets to be sent are taken from the `api` stream along with their [`stream::Priority`], the received
+/// ones are passed into the `api` sink. The optional components of the pipeline are enabled by the
+/// [`options`](options::ProtocolOptions), all of them are disabled by default.
+///
+/// Along with the handles of the spawned processes, the [`control::PipelineControl`] of the pipeline is returned.
+///
+/// Fails with [`errors::ProtocolError::BloomNamespaceMismatch`] if the persisted tag Bloom filter
+/// belongs to a different packet key.
 #[allow(clippy::too_many_arguments)]
 pub async fn run_msg_ack_protocol<Db>(
     packet_cfg: msg::processor::PacketInteractionConfig,
+    msg_cfg: msg::config::MsgProtocolConfig,
+    ack_cfg: ack::config::AckProtocolConfig,
+    db: Db,
+    wire_ack: (
+        impl futures::Sink<(PeerId, Acknowledgement)> + Send + Sync + 'static,
+        impl futures::Stream<Item = (PeerId, Acknowledgement)> + Send + Sync + 'static,
+    ),
+    wire_msg: (
+        impl futures::Sink<(PeerId, Box<[u8]>)> + Clone + Unpin + Send + Sync + 'static,
+        impl futures::Stream<Item = (PeerId, Box<[u8]>)> + Send + Sync + 'static,
+    ),
+    api: (
+        impl futures::Sink<ApplicationData> + Send + Sync + 'static,
+        impl futures::Stream<Item = SendMsgInput> + Send + Sync + 'static,
+    ),
+    options: options::ProtocolOptions,
+) -> errors::Result<(
+    HashMap<ProtocolProcesses, hopr_async_runtime::prelude::JoinHandle<()>>,
+    control::PipelineControl,
+)>
+where
+    Db: HoprDbProtocolOperations + std::fmt::Debug + Clone + Send + Sync + 'static,
+{
+    run_msg_ack_protocol_with_clock(
+        packet_cfg, msg_cfg, ack_cfg, db, wire_ack, wire_msg, api, options, RealClock,
+    )
+    .await
+}
+
+/// Same as [`run_msg_ack_protocol`], but the periodic processes measure the time using the given `clock`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_msg_ack_protocol_with_clock<Db, C>(
+    packet_cfg: msg::processor::PacketInteractionConfig,
+    msg_cfg: msg::config::MsgProtocolConfig,
+    ack_cfg: ack::config::AckProtocolConfig,
     db: Db,
-    bloom_filter_persistent_path: Option<String>,
     wire_ack: (
         impl futures::Sink<(PeerId, Acknowledgement)> + Send + Sync + 'static,
         impl futures::Stream<Item = (PeerId, Acknowledgement)> + Send + Sync + 'static,
@@ -158,16 +260,40 @@ pub async fn run_msg_ack_protocol<Db>(
     ),
     api: (
         impl futures::Sink<ApplicationData> + Send + Sync + 'static,
-        impl futures::Stream<Item = (ApplicationData, ResolvedTransportRouting, PacketSendFinalizer)>
-            + Send
-            + Sync
-            + 'static,
+        impl futures::Stream<Item = SendMsgInput> + Send + Sync + 'static,
     ),
-) -> HashMap<ProtocolProcesses, hopr_async_runtime::prelude::JoinHandle<()>>
+    options: options::ProtocolOptions,
+    clock: C,
+) -> errors::Result<(
+    HashMap<ProtocolProcesses, hopr_async_runtime::prelude::JoinHandle<()>>,
+    control::PipelineControl,
+)>
 where
     Db: HoprDbProtocolOperations + std::fmt::Debug + Clone + Send + Sync + 'static,
+    C: Clock,
 {
+    let options::ProtocolOptions {
+        bloom_filter_persistent_path,
+        ack_timeout_events,
+        resend_events,
+        ban_events,
+        losing_ticket_events,
+        ticket_stats,
+        traffic,
+        health,
+        buffer_accounting,
+        gate,
+        reachability,
+        wire_tap,
+        spawners,
+    } = options;
+
     let me = packet_cfg.packet_keypair.clone();
+    let health = health.unwrap_or_default();
+    let control = control::PipelineControl::default();
+    let gate = gate.unwrap_or_else(|| Arc::new(msg::gate::AllowAllGate));
+    let throttle = Arc::new(msg::gate::PeerThrottle::new(msg_cfg.max_throttled_per_peer));
+    let reachability = reachability.unwrap_or_else(|| Arc::new(msg::reachability::AssumeReachable));
 
     let mut processes = HashMap::new();
 
@@ -181,45 +307,252 @@ where
         lazy_static::initialize(&METRIC_PACKET_COUNT_PER_PEER);
         lazy_static::initialize(&METRIC_REPLAYED_PACKET_COUNT);
         lazy_static::initialize(&METRIC_REJECTED_TICKETS_COUNT);
+        lazy_static::initialize(&METRIC_ACK_TIMEOUTS);
+        lazy_static::initialize(&METRIC_DROPPED_PACKETS_COUNT);
+        lazy_static::initialize(&METRIC_OVERSIZE_APP_DATA_COUNT);
+        lazy_static::initialize(&retry::METRIC_DB_RETRIES);
+        lazy_static::initialize(&msg::pricing::METRIC_PRICE_EPOCH);
+        lazy_static::initialize(&msg::pricing::METRIC_PACKETS_IN_PRICE_EPOCH);
     }
 
-    let tbf = if let Some(bloom_filter_persistent_path) = bloom_filter_persistent_path {
-        let tbf = bloom::WrappedTagBloomFilter::new(bloom_filter_persistent_path);
+    #[cfg(all(feature = "prometheus", not(test)))]
+    let peer_labeler = msg::peer_labels::PeerMetricLabeler::new(msg_cfg.peer_metric_labels);
+
+    // The filter is namespaced by the packet key, so that the transport instances running
+    // in the same process cannot share or overwrite each other's filter
+    let persistent_tbf = bloom_filter_persistent_path
+        .map(|path| {
+            bloom::WrappedTagBloomFilter::new_namespaced(path, &me.public().to_peerid_str())
+                .map(|tbf| tbf.with_wal(msg_cfg.tag_wal_max_batch_latency))
+                .inspect_err(|error| error!(%error, "Cannot use the persisted tag Bloom filter"))
+        })
+        .transpose()?;
+
+    let tbf = if let Some(tbf) = persistent_tbf {
         let tbf_2 = tbf.clone();
         processes.insert(
             ProtocolProcesses::BloomPersist,
-            spawn(Box::pin(execute_on_tick(
-                std::time::Duration::from_secs(90),
-                move || {
-                    let tbf_clone = tbf_2.clone();
-
-                    async move { tbf_clone.save().await }
-                },
-                "persisting the bloom filter to disk".into(),
-            ))),
+            spawn(health.monitor(
+                ProtocolProcesses::BloomPersist,
+                clock.now(),
+                Box::pin(execute_on_tick_with_clock(
+                    clock.clone(),
+                    std::time::Duration::from_secs(90),
+                    health.on_tick(ProtocolProcesses::BloomPersist, clock.clone(), move || {
+                        let tbf_clone = tbf_2.clone();
+
+                        async move { tbf_clone.save().await }
+                    }),
+                    "persisting the bloom filter to disk".into(),
+                )),
+            )),
+        );
+        let tbf_3 = tbf.clone();
+        processes.insert(
+            ProtocolProcesses::BloomWalSync,
+            spawn(health.monitor(
+                ProtocolProcesses::BloomWalSync,
+                clock.now(),
+                Box::pin(execute_on_tick_with_clock(
+                    clock.clone(),
+                    tbf.wal_max_batch_latency(),
+                    health.on_tick(ProtocolProcesses::BloomWalSync, clock.clone(), move || {
+                        let tbf_clone = tbf_3.clone();
+
+                        async move { tbf_clone.sync_wal().await }
+                    }),
+                    "syncing the bloom filter write-ahead log to disk".into(),
+                )),
+            )),
         );
         tbf
     } else {
         bloom::WrappedTagBloomFilter::new("no_tbf".into())
     };
 
-    let ack_processor_read = ack::processor::AcknowledgementProcessor::new(db.clone());
+    let ack_tracker = ack::processor::AckTimeoutTracker::with_clock(
+        ack_cfg.expectation_window,
+        ack_cfg.max_pending_acks,
+        clock.clone(),
+    );
+    let ack_tracker_check = ack_tracker.clone();
+    processes.insert(
+        ProtocolProcesses::AckTimeoutCheck,
+        spawn(health.monitor(
+            ProtocolProcesses::AckTimeoutCheck,
+            clock.now(),
+            Box::pin(execute_on_tick_with_clock(
+                clock.clone(),
+                ack_tracker.window(),
+                health.on_tick(ProtocolProcesses::AckTimeoutCheck, clock.clone(), move || {
+                    let ack_tracker = ack_tracker_check.clone();
+                    let ack_timeout_events = ack_timeout_events.clone();
+
+                    async move {
+                        for event in ack_tracker.expire() {
+                            warn!(
+                                peer = %event.peer,
+                                missing = event.missing,
+                                "Peer did not acknowledge packets in time"
+                            );
+
+                            #[cfg(all(feature = "prometheus", not(test)))]
+                            METRIC_ACK_TIMEOUTS.increment_by(event.missing as u64);
+
+                            if let Some(tx) = &ack_timeout_events {
+                                if let Err(e) = tx.unbounded_send(event) {
+                                    error!(error = %e, "Failed to report an acknowledgement timeout");
+                                }
+                            }
+                        }
+                    }
+                }),
+                "checking acknowledgement timeouts".into(),
+            )),
+        )),
+    );
+
+    let resend_tracker = packet_cfg.resend_unacked_after.map(|resend_after| {
+        msg::retransmit::ResendTracker::with_clock(
+            resend_after,
+            packet_cfg.max_resends,
+            msg::retransmit::DEFAULT_MAX_PENDING_RESENDS,
+            clock.clone(),
+        )
+    });
+
+    let ack_latencies = ack::processor::AckLatencyTracker::with_clock(
+        ack_cfg.expectation_window,
+        ack::processor::DEFAULT_MAX_TRACKED_ACK_LATENCIES,
+        clock.clone(),
+    );
+    let ack_freshness = ack::processor::AckFreshnessTracker::with_clock(
+        ack_cfg.expectation_window,
+        ack_cfg.max_pending_acks as u64,
+        clock.clone(),
+    );
+    let mut ack_processor_read = ack::processor::AcknowledgementProcessor::with_clock(db.clone(), ack_cfg)
+        .with_latency_tracker(ack_latencies.clone())
+        .with_timeout_tracker(ack_tracker.clone())
+        .with_freshness_tracker(ack_freshness.clone());
+    if let Some(ban_events) = ban_events {
+        ack_processor_read = ack_processor_read.with_ban_events(ban_events);
+    }
     let ack_processor_write = ack_processor_read.clone();
-    let msg_processor_read = msg::processor::PacketProcessor::new(db.clone(), tbf, packet_cfg);
+    let msg_processor_read =
+        msg::processor::PacketProcessor::new(db.clone(), tbf, packet_cfg).with_db_retry(msg_cfg.db_retry);
     let msg_processor_write = msg_processor_read.clone();
 
+    if let Some(resend_tracker) = resend_tracker.clone() {
+        let msg_processor = msg_processor_read.clone();
+        let msg_to_send_tx = wire_msg.0.clone();
+        let ack_tracker = ack_tracker.clone();
+        let ack_freshness = ack_freshness.clone();
+        processes.insert(
+            ProtocolProcesses::Resend,
+            spawn(health.monitor(
+                ProtocolProcesses::Resend,
+                clock.now(),
+                Box::pin(execute_on_tick_with_clock(
+                    clock.clone(),
+                    (resend_tracker.resend_after() / 4).max(std::time::Duration::from_millis(10)),
+                    health.on_tick(ProtocolProcesses::Resend, clock.clone(), move || {
+                        let resend_tracker = resend_tracker.clone();
+                        let msg_processor = msg_processor.clone();
+                        let mut msg_to_send_tx = msg_to_send_tx.clone();
+                        let ack_tracker = ack_tracker.clone();
+                        let ack_freshness = ack_freshness.clone();
+                        let resend_events = resend_events.clone();
+
+                        async move {
+                            let (due, gave_up) = resend_tracker.due();
+
+                            for packet in gave_up {
+                                warn!(
+                                    resends = packet.resends,
+                                    "Packet was not acknowledged, giving up re-sending"
+                                );
+                                if let Some(tx) = &resend_events {
+                                    if let Err(e) = tx.unbounded_send(packet) {
+                                        error!(error = %e, "Failed to report an unacknowledged packet");
+                                    }
+                                }
+                            }
+
+                            for resend in due {
+                                match msg_processor.wrap(resend.data, resend.routing).await {
+                                    Ok(packet) if resend_tracker.resent(resend.id, packet.ack_challenge) => {
+                                        ack_tracker.expect(&packet.next_hop, packet.ack_challenge);
+                                        ack_freshness.issued(packet.ack_challenge).await;
+                                        let _ = stream::send_with_policy(
+                                            &mut msg_to_send_tx,
+                                            (packet.next_hop, packet.data),
+                                            msg_cfg.sink_failure_policy,
+                                            "msg",
+                                        )
+                                        .await;
+                                    }
+                                    Ok(_) => trace!("Packet acknowledged while being re-sent"),
+                                    Err(error) => error!(%error, "Failed to wrap a packet for re-sending"),
+                                }
+                            }
+                        }
+                    }),
+                    "re-sending unacknowledged packets".into(),
+                )),
+            )),
+        );
+    }
+
+    let wire_tap_ack_in = wire_tap.clone();
+    let resend_tracker_in = resend_tracker.clone();
+    let ticket_stats_in = ticket_stats.clone();
+    let losing_ticket_events_in = losing_ticket_events;
+    let (health_ack_in, clock_ack_in) = (health.clone(), clock.clone());
     processes.insert(
         ProtocolProcesses::AckIn,
-        spawn(async move {
+        spawners.spawn_ingress(health.monitor(ProtocolProcesses::AckIn, clock.now(), async move {
             let _neverending = wire_ack
                 .1
+                .inspect(move |(peer, ack)| {
+                    if let Some(wire_tap) = &wire_tap_ack_in {
+                        wire_tap.record(capture::WireDirection::AckIn, peer, ack.as_ref());
+                    }
+                })
+                .inspect(health_ack_in.recorder(ProtocolProcesses::AckIn, clock_ack_in))
                 .for_each_concurrent(None, move |(peer, ack)| {
                     let ack_processor = ack_processor_read.clone();
-
+                    let resend_tracker = resend_tracker_in.clone();
+                    let ticket_stats = ticket_stats_in.clone();
+                    let losing_ticket_events = losing_ticket_events_in.clone();
                     async move {
-                        let _ack_result = ack_processor.recv(&peer, ack).await;
+                        let ack_result = ack_processor.recv(&peer, ack).await;
+                        if let (Some(resend_tracker), Ok(hopr_db_api::prelude::AckResult::Sender(ack))) =
+                            (&resend_tracker, &ack_result)
+                        {
+                            if let Ok(challenge) = ack.ack_challenge() {
+                                resend_tracker.acknowledged(&challenge);
+                            }
+                        }
+
+                        if let (Some(ticket_stats), Ok(ack_result)) = (&ticket_stats, &ack_result) {
+                            ticket_stats.record(ack_result);
+                        }
+
+                        if let (Some(tx), Ok(hopr_db_api::prelude::AckResult::RelayerLosing(channel_id))) =
+                            (&losing_ticket_events, &ack_result)
+                        {
+                            let event = ack::processor::LosingTicketEvent {
+                                peer,
+                                channel_id: *channel_id,
+                            };
+                            if let Err(e) = tx.unbounded_send(event) {
+                                error!(error = %e, "Failed to report a losing ticket");
+                            }
+                        }
+
                         #[cfg(all(feature = "prometheus", not(test)))]
-                        match &_ack_result {
+                        match &ack_result {
                             Ok(hopr_db_api::prelude::AckResult::Sender(_)) => {
                                 METRIC_RECEIVED_ACKS.increment(&["true"]);
                             }
@@ -227,10 +560,13 @@ where
                                 METRIC_RECEIVED_ACKS.increment(&["true"]);
                                 METRIC_TICKETS_COUNT.increment(&["winning"]);
                             }
-                            Ok(hopr_db_api::prelude::AckResult::RelayerLosing) => {
+                            Ok(hopr_db_api::prelude::AckResult::RelayerLosing(_)) => {
                                 METRIC_RECEIVED_ACKS.increment(&["true"]);
                                 METRIC_TICKETS_COUNT.increment(&["losing"]);
                             }
+                            Ok(hopr_db_api::prelude::AckResult::Duplicate) => {
+                                METRIC_RECEIVED_ACKS.increment(&["true"]);
+                            }
                             Err(_) => {
                                 METRIC_RECEIVED_ACKS.increment(&["false"]);
                             }
@@ -238,144 +574,375 @@ where
                     }
                 })
                 .await;
-        }),
+        })),
     );
 
-    let (internal_ack_send, internal_ack_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    // Signing of the outgoing acknowledgements is offloaded from the ingress pipeline to the worker pool
+    let (ack_signer, signed_acks) = ack::signer::AckSigner::new(me, ack_cfg.signing_workers);
 
+    let (health_ack_out, clock_ack_out) = (health.clone(), clock.clone());
     processes.insert(
         ProtocolProcesses::AckOut,
-        spawn(async move {
-            let _neverending = internal_ack_rx
-                .then_concurrent(move |(peer, ack)| {
-                    let ack_processor = ack_processor_write.clone();
+        spawners.spawn_egress(health.monitor(ProtocolProcesses::AckOut, clock.now(), async move {
+            let _terminated = stream::forward_with_policy(
+                signed_acks
+                    .inspect(health_ack_out.recorder(ProtocolProcesses::AckOut, clock_ack_out))
+                    .then(move |(peer, ack)| {
+                        let ack_processor = ack_processor_write.clone();
 
-                    #[cfg(all(feature = "prometheus", not(test)))]
-                    METRIC_SENT_ACKS.increment();
+                        #[cfg(all(feature = "prometheus", not(test)))]
+                        METRIC_SENT_ACKS.increment();
 
-                    async move { (peer, ack_processor.send(&peer, ack).await) }
-                })
-                .map(Ok)
-                .forward(wire_ack.0)
-                .await;
-        }),
+                        async move { (peer, ack_processor.send(&peer, ack).await) }
+                    }),
+                wire_ack.0,
+                ack_cfg.sink_failure_policy,
+                "ack",
+            )
+            .await;
+        })),
     );
 
+    // All the queues of the pipeline share a single budget of the buffered bytes
+    let buffer_accounting =
+        buffer_accounting.unwrap_or_else(|| msg::budget::BufferAccounting::new(msg_cfg.buffer_budget));
+
     let msg_to_send_tx = wire_msg.0.clone();
+    #[cfg(all(feature = "prometheus", not(test)))]
+    let peer_labeler_out = peer_labeler.clone();
+    let buffer_accounting_out = buffer_accounting.clone();
+    let ack_tracker_out = ack_tracker.clone();
+    let ack_freshness_out = ack_freshness.clone();
+    let resend_tracker_out = resend_tracker;
+    let traffic_out = traffic.clone();
+    let (health_msg_out, clock_msg_out) = (health.clone(), clock.clone());
+    let control_msg_out = control.clone();
     processes.insert(
         ProtocolProcesses::MsgOut,
-        spawn(async move {
-            let _neverending = api
-                .1
+        spawners.spawn_egress(health.monitor(ProtocolProcesses::MsgOut, clock.now(), async move {
+            let msg_out = control_msg_out
+                .egress_gated(
+                    control_msg_out.pausable(api.1),
+                    msg_cfg.egress_pause_buffer_size,
+                    |(_, _, finalizer, _): SendMsgInput, error| finalizer.finalize(Err(error)),
+                )
+                .inspect(health_msg_out.recorder(ProtocolProcesses::MsgOut, clock_msg_out))
+                .map(|(data, routing, finalizer, priority)| (priority, (data, routing, finalizer)))
+                .boxed()
+                // High priority packets jump ahead of the normal ones already before they are wrapped
+                .prioritized(msg_cfg.priority_scheduler)
                 .then_concurrent(|(data, routing, finalizer)| {
                     let msg_processor = msg_processor_write.clone();
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    let peer_labeler = peer_labeler_out.clone();
+                    let ack_tracker = ack_tracker_out.clone();
+                    let ack_freshness = ack_freshness_out.clone();
+                    let resend_tracker = resend_tracker_out.clone();
+                    let traffic = traffic_out.clone();
+                    let buffer_accounting = buffer_accounting_out.clone();
+                    let reachability = reachability.clone();
+                    // The packets being wrapped concurrently take their share of the budget as well
+                    let wrapping = buffer_accounting.track(msg::budget::EGRESS_QUEUE, data.plain_text.len());
 
                     async move {
-                        match PacketWrapping::send(&msg_processor, data, routing).await {
-                            Ok(v) => {
+                        // The first hop is checked before the packet is wrapped, unless it is known only afterward
+                        let first_hop = msg::reachability::known_first_hop(&routing);
+                        if let Some(peer) = first_hop {
+                            if !reachability.is_reachable(&peer).await {
+                                debug!(%peer, "Rejecting send to an unreachable first hop");
+                                finalizer.finalize(Err(errors::ProtocolError::FirstHopUnreachable(peer)));
+                                return None;
+                            }
+                        }
+
+                        let resend_input = resend_tracker.as_ref().map(|_| (data.clone(), routing.clone()));
+                        let traffic_input = (data.application_tag, data.plain_text.len());
+
+                        match msg_processor.wrap(data, routing).await {
+                            Ok(packet) => {
+                                if first_hop.is_none() && !reachability.is_reachable(&packet.next_hop).await {
+                                    debug!(peer = %packet.next_hop, "Rejecting send to an unreachable first hop");
+                                    finalizer
+                                        .finalize(Err(errors::ProtocolError::FirstHopUnreachable(packet.next_hop)));
+                                    return None;
+                                }
+
+                                if let (Some(resend_tracker), Some((data, routing))) = (&resend_tracker, resend_input) {
+                                    resend_tracker.track(packet.ack_challenge, data, routing);
+                                }
+                                ack_freshness.issued(packet.ack_challenge).await;
+
+                                if let Some(traffic) = &traffic {
+                                    traffic.record_sent(traffic_input.0, traffic_input.1);
+                                }
+
+                                let receipt = msg::processor::SendReceipt::from(&packet);
+                                let ack_challenge = packet.ack_challenge;
+                                let v = (packet.next_hop, packet.data);
+                                let buffered = buffer_accounting.track(msg::budget::EGRESS_QUEUE, v.1.len());
+                                drop(wrapping);
+                                ack_tracker.expect(&v.0, ack_challenge);
                                 #[cfg(all(feature = "prometheus", not(test)))]
                                 {
-                                    METRIC_PACKET_COUNT_PER_PEER.increment(&["out", &v.0.to_string()]);
+                                    if let Some(peer) = peer_labeler.label(&v.0) {
+                                        METRIC_PACKET_COUNT_PER_PEER.increment(&["out", &peer]);
+                                    }
                                     METRIC_PACKET_COUNT.increment(&["sent"]);
                                 }
-                                finalizer.finalize(Ok(()));
-                                Some(v)
+
+                                // The send is finalized either right away, or once handed over to the wire
+                                let pending = if msg_cfg.finalize_after_wire_send {
+                                    Some((finalizer, receipt))
+                                } else {
+                                    finalizer.finalize_with_receipt(Ok(receipt));
+                                    None
+                                };
+                                Some((v, pending, buffered))
                             }
                             Err(e) => {
-                                finalizer.finalize(Err(e));
+                                finalizer.finalize(Err(e.into()));
                                 None
                             }
                         }
                     }
                 })
-                .filter_map(|v| async move { v })
-                .map(Ok)
-                .forward(msg_to_send_tx)
-                .await;
-        }),
+                .filter_map(|v| async move { v });
+
+            let mut msg_out = std::pin::pin!(msg_out);
+            let mut msg_to_send_tx = msg_to_send_tx;
+            while let Some(((peer, data), pending, _buffered)) = msg_out.next().await {
+                let delivered =
+                    stream::deliver_with_policy(&mut msg_to_send_tx, (peer, data), msg_cfg.sink_failure_policy, "msg")
+                        .await;
+
+                if let Some((finalizer, receipt)) = pending {
+                    match delivered {
+                        Ok(true) => finalizer.finalize_with_receipt(Ok(receipt)),
+                        Ok(false) | Err(_) => finalizer.finalize(Err(errors::ProtocolError::FirstHopUnreachable(peer))),
+                    }
+                }
+
+                if delivered.is_err() {
+                    break;
+                }
+            }
+        })),
     );
 
-    let me = me.clone();
+    let msg_in_backoff =
+        stream::SourceErrorBackoff::<PeerId, _>::with_clock(msg_cfg.source_error_backoff, clock.clone());
+    let drop_log = msg::drop_log::DropLogSampler::with_clock(msg_cfg.drop_log_sampling, clock.clone());
+    let wire_dedup = msg::dedup::WireDuplicateFilter::default();
+    let (health_msg_in, clock_msg_in) = (health.clone(), clock.clone());
+    let control_msg_in = control.clone();
+    // The received packets are handed over through the budgeted queues, so that a stalled sink
+    // neither blocks the processing of the other packets, nor lets them pile up without a limit
+    let (relay_tx, mut relay_rx) = buffer_accounting
+        .channel::<((PeerId, Box<[u8]>), msg::processor::SendAck, HalfKeyChallenge)>(msg::budget::IngressClass::Relay);
+    let ack_signer_relay = ack_signer.clone();
+    let (local_tx, mut local_rx) = buffer_accounting.channel::<ApplicationData>(msg::budget::IngressClass::Local);
     processes.insert(
         ProtocolProcesses::MsgIn,
-        spawn(async move {
-            let _neverending = wire_msg
-                .1
+        spawners.spawn_ingress(health.monitor(ProtocolProcesses::MsgIn, clock.now(), async move {
+            let ingress = control_msg_in
+                .pausable(wire_msg.1)
+                .inspect(move |(peer, data)| {
+                    if let Some(wire_tap) = &wire_tap {
+                        wire_tap.record(capture::WireDirection::MsgIn, peer, data);
+                    }
+                })
+                .inspect(health_msg_in.recorder(ProtocolProcesses::MsgIn, clock_msg_in))
+                .backoff_on_source_errors(msg_in_backoff.clone(), |(peer, _)| *peer)
                 .then_concurrent(move |(peer, data)| {
                     let msg_processor = msg_processor_read.clone();
+                    let gate = gate.clone();
+                    let throttle = throttle.clone();
+                    let wire_dedup = wire_dedup.clone();
 
-                    async move { msg_processor.recv(&peer, data).await.map_err(|e| (peer, e)) }
+                    async move {
+                        // Exact duplicates of the wire items are dropped before the decryption
+                        msg::gate::admit_then(
+                            gate.as_ref(),
+                            &throttle,
+                            &peer,
+                            wire_dedup.recv_unique(&msg_processor, &peer, data),
+                        )
+                        .await
+                        .flatten()
+                        .map(|v| v.map_err(|e| (peer, e)))
+                    }
                 })
-                .filter_map(move |v| {
-                    let mut internal_ack_send = internal_ack_send.clone();
-                    let mut msg_to_send_tx = wire_msg.0.clone();
-                    let me = me.clone();
+                .filter_map(|v| async move { v })
+                .for_each(move |v| {
+                    let ack_signer = ack_signer.clone();
+                    let relay_tx = relay_tx.clone();
+                    let local_tx = local_tx.clone();
+                    let msg_in_backoff = msg_in_backoff.clone();
+                    let ticket_stats = ticket_stats.clone();
+                    let traffic = traffic.clone();
+                    let drop_log = drop_log.clone();
+                    #[cfg(all(feature = "prometheus", not(test)))]
+                    let peer_labeler = peer_labeler.clone();
 
                     async move {
                         match v {
                             Ok(v) => match v {
                                 msg::processor::RecvOperation::Receive { data, ack } => {
+                                    msg_in_backoff.record_success(&ack.peer);
+                                    if let Some(traffic) = &traffic {
+                                        traffic.record_received(data.application_tag, data.plain_text.len());
+                                    }
                                     #[cfg(all(feature = "prometheus", not(test)))]
                                     {
-                                        METRIC_PACKET_COUNT_PER_PEER.increment(&["in", &ack.peer.to_string()]);
+                                        if let Some(peer) = peer_labeler.label(&ack.peer) {
+                                            METRIC_PACKET_COUNT_PER_PEER.increment(&["in", &peer]);
+                                        }
                                         METRIC_PACKET_COUNT.increment(&["received"]);
                                     }
-                                    internal_ack_send.send((ack.peer, ack.ack)).await.unwrap_or_else(|e| {
+                                    ack_signer.sign(ack.peer, ack.ack_key).await.unwrap_or_else(|e| {
                                         error!(error = %e, "Failed to forward an acknowledgement to the transport layer");
                                     });
-                                    Some(data)
+
+                                    if let Some(max) = msg_cfg.max_application_data_bytes.filter(|max| data.plain_text.len() > *max) {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_OVERSIZE_APP_DATA_COUNT.increment();
+
+                                        if let Some(suppressed) = drop_log.sample() {
+                                            error!(
+                                                peer = %ack.peer,
+                                                tag = data.application_tag,
+                                                size = data.plain_text.len(),
+                                                max,
+                                                suppressed,
+                                                "Dropping received application data exceeding the maximum size"
+                                            );
+                                        }
+                                        return;
+                                    }
+
+                                    let (peer, size) = (ack.peer, data.plain_text.len());
+                                    if !local_tx.push(data, size) {
+                                        if let Some(suppressed) = drop_log.sample() {
+                                            warn!(%peer, size, suppressed, "Dropping received application data over the buffer budget");
+                                        }
+                                    }
                                 }
-                                msg::processor::RecvOperation::Forward { msg, ack } => {
+                                msg::processor::RecvOperation::Forward { msg, ack, ack_challenge } => {
+                                    msg_in_backoff.record_success(&ack.peer);
                                     #[cfg(all(feature = "prometheus", not(test)))]
                                     {
-                                        METRIC_PACKET_COUNT_PER_PEER.increment(&["in", &ack.peer.to_string()]);
-                                        METRIC_PACKET_COUNT_PER_PEER.increment(&["out", &msg.peer.to_string()]);
+                                        if let Some(peer) = peer_labeler.label(&ack.peer) {
+                                            METRIC_PACKET_COUNT_PER_PEER.increment(&["in", &peer]);
+                                        }
+                                        if let Some(peer) = peer_labeler.label(&msg.peer) {
+                                            METRIC_PACKET_COUNT_PER_PEER.increment(&["out", &peer]);
+                                        }
                                         METRIC_PACKET_COUNT.increment(&["forwarded"]);
                                     }
 
-                                    msg_to_send_tx.send((msg.peer, msg.data)).await.unwrap_or_else(|_e| {
-                                        error!("Failed to forward a message to the transport layer");
-                                    });
-                                    internal_ack_send.send((ack.peer, ack.ack)).await.unwrap_or_else(|e| {
-                                        error!(error = %e, "Failed to forward an acknowledgement to the transport layer");
-                                    });
-                                    None
+                                    // The packet is acknowledged once it has been handed over to the next hop
+                                    let (previous_hop, next_hop, size) = (ack.peer, msg.peer, msg.data.len());
+                                    if !relay_tx.push(((msg.peer, msg.data), ack, ack_challenge), size) {
+                                        if let Some(suppressed) = drop_log.sample() {
+                                            warn!(peer = %previous_hop, %next_hop, size, suppressed, "Dropping relayed packet over the buffer budget");
+                                        }
+                                        ack_signer.sign(previous_hop, HalfKey::random()).await.unwrap_or_else(|e| {
+                                            error!(error = %e, "Failed to forward an acknowledgement for a dropped packet to the transport layer");
+                                        });
+                                    }
                                 }
                             },
                             Err((peer, e)) => {
+                                if let (Some(ticket_stats), hopr_crypto_packet::errors::PacketError::TicketValidation(error)) =
+                                    (&ticket_stats, &e)
+                                {
+                                    ticket_stats.rejected(error.ticket.channel_id);
+                                }
+
+                                let reason = msg::drop_log::drop_reason(&e);
+
                                 #[cfg(all(feature = "prometheus", not(test)))]
-                                match e {
-                                    hopr_crypto_packet::errors::PacketError::TagReplay => {
-                                        METRIC_REPLAYED_PACKET_COUNT.increment();
-                                    },
-                                    hopr_crypto_packet::errors::PacketError::TicketValidation(_) => {
-                                        METRIC_REJECTED_TICKETS_COUNT.increment();
-                                    },
-                                    _ => {}
+                                {
+                                    METRIC_DROPPED_PACKETS_COUNT.increment(&[reason]);
+                                    match e {
+                                        hopr_crypto_packet::errors::PacketError::TagReplay => {
+                                            METRIC_REPLAYED_PACKET_COUNT.increment();
+                                        },
+                                        hopr_crypto_packet::errors::PacketError::TicketValidation(_) => {
+                                            METRIC_REJECTED_TICKETS_COUNT.increment();
+                                        },
+                                        _ => {}
+                                    }
                                 }
 
-                                error!(peer = %peer, error = %e, "Failed to process the received message");
+                                if let Some(suppressed) = drop_log.sample() {
+                                    error!(peer = %peer, error = %e, reason, suppressed, "Failed to process the received message");
+                                }
+                                if let Some(backoff) = msg_in_backoff.record_error(&peer) {
+                                    warn!(peer = %peer, backoff_in_ms = backoff.as_millis(), "Repeated failures processing messages from peer, backing off");
+                                }
                                 // send random signed acknowledgement to give feedback to the sender
-                                internal_ack_send
-                                    .send((
-                                        peer,
-                                        Acknowledgement::random(&me),
-                                    ))
+                                ack_signer
+                                    .sign(peer, HalfKey::random())
                                     .await
                                     .unwrap_or_else(|e| {
                                         error!(error = %e, "Failed to forward an acknowledgement for a failed packet recv to the transport layer");
                                     });
-
-                                None
                             }
                         }
                     }
-                })
-                .map(Ok)
-                .forward(api.0)
-                .await;
-        }),
+                });
+
+            // The bytes of an item are released only once the item has been accepted by the sink
+            let mut msg_to_send_tx = wire_msg.0;
+            let relay_out = async move {
+                while let Some(((item, ack, ack_challenge), _buffered)) = relay_rx.next().await {
+                    // The acknowledgement of the next hop is expected before it could possibly arrive
+                    let next_hop = item.0;
+                    ack_tracker.expect(&next_hop, ack_challenge);
+                    ack_freshness.issued(ack_challenge).await;
+                    ack_latencies.forwarded(ack_challenge).await;
+
+                    let delivered =
+                        stream::deliver_with_policy(&mut msg_to_send_tx, item, msg_cfg.sink_failure_policy, "msg").await?;
+
+                    // The previous hop is acknowledged only once the packet is on its way
+                    let ack_key = if delivered {
+                        ack.ack_key
+                    } else {
+                        ack_tracker.acknowledged(&next_hop);
+                        HalfKey::random()
+                    };
+                    ack_signer_relay.sign(ack.peer, ack_key).await.unwrap_or_else(|e| {
+                        error!(error = %e, "Failed to forward an acknowledgement to the transport layer");
+                    });
+                }
+                Ok::<_, stream::SinkTerminated>(())
+            };
+            let api_tx = api.0;
+            let local_out = async move {
+                let mut api_tx = std::pin::pin!(api_tx);
+                while let Some((data, _buffered)) = local_rx.next().await {
+                    if api_tx.send(data).await.is_err() {
+                        break;
+                    }
+                }
+            };
+
+            // Terminate right away if the wire sink failure policy requires it, otherwise
+            // once the wire stream is exhausted and both queues are drained
+            let ingress = futures::future::join(ingress, local_out);
+            match futures::future::select(std::pin::pin!(relay_out), std::pin::pin!(ingress)).await {
+                futures::future::Either::Left((Ok(()), ingress)) => {
+                    ingress.await;
+                }
+                futures::future::Either::Right((_, relay_out)) => {
+                    let _ = relay_out.await;
+                }
+                futures::future::Either::Left((Err(stream::SinkTerminated), _)) => {}
+            }
+        })),
     );
 
-    processes
+    Ok((processes, control))
 }
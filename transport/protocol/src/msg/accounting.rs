@@ -0,0 +1,252 @@
+//! Accounting of the application traffic per application tag.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use hopr_internal_types::protocol::Tag;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::MultiCounter;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_TRAFFIC_MESSAGES: MultiCounter = MultiCounter::new(
+        "hopr_app_traffic_messages_count",
+        "Number of application messages sent and received by application tag",
+        &["tag", "direction"]
+    )
+    .unwrap();
+    static ref METRIC_TRAFFIC_BYTES: MultiCounter = MultiCounter::new(
+        "hopr_app_traffic_bytes_count",
+        "Number of application payload bytes sent and received by application tag",
+        &["tag", "direction"]
+    )
+    .unwrap();
+}
+
+/// Label used in the metrics for all tags not labelled individually.
+pub const OTHER_TAGS_LABEL: &str = "other";
+
+/// Number of independently locked shards of the per-tag counters.
+const SHARDS: usize = 16;
+
+/// Traffic of a single application tag.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TagTraffic {
+    /// Number of sent messages.
+    pub sent_messages: u64,
+    /// Number of sent payload bytes.
+    pub sent_bytes: u64,
+    /// Number of received messages.
+    pub received_messages: u64,
+    /// Number of received payload bytes.
+    pub received_bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct TagCounters {
+    sent_messages: AtomicU64,
+    sent_bytes: AtomicU64,
+    received_messages: AtomicU64,
+    received_bytes: AtomicU64,
+}
+
+impl TagCounters {
+    fn add(&self, sent: bool, bytes: u64) {
+        let (messages, total_bytes) = if sent {
+            (&self.sent_messages, &self.sent_bytes)
+        } else {
+            (&self.received_messages, &self.received_bytes)
+        };
+        messages.fetch_add(1, Ordering::Relaxed);
+        total_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> TagTraffic {
+        TagTraffic {
+            sent_messages: self.sent_messages.load(Ordering::Relaxed),
+            sent_bytes: self.sent_bytes.load(Ordering::Relaxed),
+            received_messages: self.received_messages.load(Ordering::Relaxed),
+            received_bytes: self.received_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Accumulates the number of messages and payload bytes sent and received per application tag.
+///
+/// The counters are sharded by the tag, so recording a message only takes a read lock of a single shard
+/// and updates atomic counters, unless the tag is seen for the first time.
+/// The clones of the accounting share the same counters.
+///
+/// If the `prometheus` feature is enabled, the traffic is also counted in the metrics, where only the tags
+/// given in [`TrafficAccounting::with_metric_tags`] are labelled individually and all the others are counted
+/// under [`OTHER_TAGS_LABEL`].
+#[derive(Debug, Clone, Default)]
+pub struct TrafficAccounting {
+    shards: Arc<[RwLock<HashMap<Tag, TagCounters>>; SHARDS]>,
+    #[cfg_attr(not(all(feature = "prometheus", not(test))), allow(dead_code))]
+    metric_tags: Arc<HashSet<Tag>>,
+}
+
+impl TrafficAccounting {
+    /// Labels the given tags individually in the metrics.
+    pub fn with_metric_tags(mut self, tags: impl IntoIterator<Item = Tag>) -> Self {
+        self.metric_tags = Arc::new(tags.into_iter().collect());
+        self
+    }
+
+    /// Records a message with the given `tag` and payload length sent by this node.
+    pub fn record_sent(&self, tag: Tag, bytes: usize) {
+        self.record(tag, bytes, true);
+    }
+
+    /// Records a message with the given `tag` and payload length received by this node.
+    pub fn record_received(&self, tag: Tag, bytes: usize) {
+        self.record(tag, bytes, false);
+    }
+
+    fn record(&self, tag: Tag, bytes: usize, sent: bool) {
+        let shard = &self.shards[tag as usize % SHARDS];
+
+        let counted = shard
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&tag)
+            .map(|counters| counters.add(sent, bytes as u64))
+            .is_some();
+        if !counted {
+            shard
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(tag)
+                .or_default()
+                .add(sent, bytes as u64);
+        }
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        {
+            let label = if self.metric_tags.contains(&tag) {
+                tag.to_string()
+            } else {
+                OTHER_TAGS_LABEL.to_string()
+            };
+            let direction = if sent { "out" } else { "in" };
+            METRIC_TRAFFIC_MESSAGES.increment(&[&label, direction]);
+            METRIC_TRAFFIC_BYTES.increment_by(&[&label, direction], bytes as u64);
+        }
+    }
+
+    /// Traffic of all the tags recorded since the creation or the last [reset](TrafficAccounting::reset).
+    pub fn snapshot(&self) -> HashMap<Tag, TagTraffic> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .iter()
+                    .map(|(tag, counters)| (*tag, counters.load()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Clears all the counters.
+    ///
+    /// The metrics are not affected.
+    pub fn reset(&self) {
+        for shard in self.shards.iter() {
+            shard.write().unwrap_or_else(|e| e.into_inner()).clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traffic_accounting_should_sum_bytes_per_tag() {
+        let accounting = TrafficAccounting::default();
+        let shared = accounting.clone();
+
+        for (tag, len) in [(1, 100), (2, 200), (1, 50), (1024, 10), (2, 300)] {
+            accounting.record_sent(tag, len);
+        }
+        // Tag 17 falls into the same shard as tag 1
+        for (tag, len) in [(17, 40), (1, 25), (1024, 5)] {
+            shared.record_received(tag, len);
+        }
+
+        let snapshot = accounting.snapshot();
+        assert_eq!(4, snapshot.len());
+        assert_eq!(
+            TagTraffic {
+                sent_messages: 2,
+                sent_bytes: 150,
+                received_messages: 1,
+                received_bytes: 25,
+            },
+            snapshot[&1]
+        );
+        assert_eq!(
+            TagTraffic {
+                sent_messages: 2,
+                sent_bytes: 500,
+                ..Default::default()
+            },
+            snapshot[&2]
+        );
+        assert_eq!(
+            TagTraffic {
+                sent_messages: 1,
+                sent_bytes: 10,
+                received_messages: 1,
+                received_bytes: 5,
+            },
+            snapshot[&1024]
+        );
+        assert_eq!(
+            TagTraffic {
+                received_messages: 1,
+                received_bytes: 40,
+                ..Default::default()
+            },
+            snapshot[&17]
+        );
+    }
+
+    #[test]
+    fn traffic_accounting_should_be_cleared_on_reset() {
+        let accounting = TrafficAccounting::default().with_metric_tags([1]);
+        accounting.record_sent(1, 10);
+        accounting.record_received(2, 10);
+
+        accounting.clone().reset();
+        assert!(accounting.snapshot().is_empty());
+
+        accounting.record_sent(1, 20);
+        assert_eq!(20, accounting.snapshot()[&1].sent_bytes);
+    }
+
+    #[test]
+    fn traffic_accounting_should_count_concurrent_updates() {
+        let accounting = TrafficAccounting::default();
+
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let accounting = accounting.clone();
+                s.spawn(move || {
+                    for i in 0..1000 {
+                        accounting.record_sent(i % 3, 1);
+                    }
+                });
+            }
+        });
+
+        let snapshot = accounting.snapshot();
+        assert_eq!(4000, snapshot.values().map(|t| t.sent_bytes).sum::<u64>());
+        assert_eq!(4000, snapshot.values().map(|t| t.sent_messages).sum::<u64>());
+    }
+}
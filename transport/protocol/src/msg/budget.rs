@@ -0,0 +1,389 @@
+//! Accounting of the bytes buffered in the queues of the packet pipeline.
+//!
+//! The received packets are handed over to the wire (relayed packets) or to the application
+//! (packets delivered to this node) through the queues created by [`BufferAccounting::channel`].
+//! All the bytes waiting in these queues, together with the outgoing packets being wrapped or waiting
+//! for the wire and the packets held by the mixer (see [`BufferAccounting::tracked_sink`]),
+//! count towards a single global [`BufferBudget`]. A received packet not fitting into the budget
+//! is dropped, the relayed packets being dropped first.
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures::Sink;
+use hopr_transport_identity::PeerId;
+
+use crate::msg::config::BufferBudget;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::{MultiCounter, MultiGauge};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_BUFFERED_BYTES: MultiGauge = MultiGauge::new(
+        "hopr_pipeline_buffered_bytes",
+        "Number of payload bytes buffered in the queues of the packet pipeline",
+        &["queue"]
+    )
+    .unwrap();
+    static ref METRIC_BUDGET_DROPS: MultiCounter = MultiCounter::new(
+        "hopr_pipeline_budget_dropped_count",
+        "Number of received packets dropped for exceeding the buffer budget of the packet pipeline",
+        &["class"]
+    )
+    .unwrap();
+}
+
+/// Queue of the received packets relayed to the next hop.
+pub const RELAY_QUEUE: &str = "relay";
+/// Queue of the received packets delivered to the application.
+pub const LOCAL_QUEUE: &str = "local";
+/// Queue of the packets originated by this node being wrapped or waiting for the wire.
+pub const EGRESS_QUEUE: &str = "egress";
+/// Queue of the packets held by the mixer before they are sent out.
+pub const MIXER_QUEUE: &str = "mixer";
+
+/// Class of a received packet, determining the order in which the packets are dropped over the budget.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IngressClass {
+    /// Packet relayed to another peer, dropped first.
+    Relay,
+    /// Packet delivered to this node.
+    Local,
+}
+
+impl IngressClass {
+    fn queue(&self) -> &'static str {
+        match self {
+            Self::Relay => RELAY_QUEUE,
+            Self::Local => LOCAL_QUEUE,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AccountingState {
+    buffered: AtomicUsize,
+    dropped_relay: AtomicU64,
+    dropped_local: AtomicU64,
+}
+
+/// Bytes of an item held in one of the pipeline queues, released once dropped.
+#[derive(Debug)]
+pub struct BufferedBytes {
+    state: Arc<AccountingState>,
+    queue: &'static str,
+    bytes: usize,
+}
+
+impl BufferedBytes {
+    fn new(state: Arc<AccountingState>, queue: &'static str, bytes: usize) -> Self {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_BUFFERED_BYTES.increment(&[queue], bytes as f64);
+
+        Self { state, queue, bytes }
+    }
+
+    /// Queue holding the bytes.
+    pub fn queue(&self) -> &'static str {
+        self.queue
+    }
+
+    /// Number of the bytes held.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl Drop for BufferedBytes {
+    fn drop(&mut self) {
+        self.state.buffered.fetch_sub(self.bytes, Ordering::AcqRel);
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_BUFFERED_BYTES.decrement(&[self.queue], self.bytes as f64);
+    }
+}
+
+/// Tracks the bytes buffered in all the pipeline queues against the [`BufferBudget`].
+///
+/// All the clones of the accounting share the same state.
+#[derive(Debug, Clone)]
+pub struct BufferAccounting {
+    budget: BufferBudget,
+    state: Arc<AccountingState>,
+}
+
+impl BufferAccounting {
+    pub fn new(budget: BufferBudget) -> Self {
+        Self {
+            budget,
+            state: Arc::new(AccountingState::default()),
+        }
+    }
+
+    /// Total number of the bytes currently buffered in all the queues.
+    pub fn buffered_bytes(&self) -> usize {
+        self.state.buffered.load(Ordering::Acquire)
+    }
+
+    /// Number of the received packets of the given `class` dropped for exceeding the budget.
+    pub fn dropped(&self, class: IngressClass) -> u64 {
+        match class {
+            IngressClass::Relay => self.state.dropped_relay.load(Ordering::Relaxed),
+            IngressClass::Local => self.state.dropped_local.load(Ordering::Relaxed),
+        }
+    }
+
+    fn limit(&self, class: IngressClass) -> usize {
+        let max = self.budget.max_bytes;
+        match class {
+            IngressClass::Relay => {
+                let percent = usize::from(self.budget.relay_percent.min(100));
+                max / 100 * percent + max % 100 * percent / 100
+            }
+            IngressClass::Local => max,
+        }
+    }
+
+    /// Accounts `bytes` of a received packet of the given `class`, if they fit into the budget.
+    ///
+    /// Otherwise, the packet is counted as dropped and `None` is returned.
+    pub fn admit(&self, class: IngressClass, bytes: usize) -> Option<BufferedBytes> {
+        let limit = self.limit(class);
+        let admitted = self
+            .state
+            .buffered
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |buffered| {
+                buffered.checked_add(bytes).filter(|total| *total <= limit)
+            })
+            .is_ok();
+
+        if admitted {
+            Some(BufferedBytes::new(self.state.clone(), class.queue(), bytes))
+        } else {
+            match class {
+                IngressClass::Relay => self.state.dropped_relay.fetch_add(1, Ordering::Relaxed),
+                IngressClass::Local => self.state.dropped_local.fetch_add(1, Ordering::Relaxed),
+            };
+
+            #[cfg(all(feature = "prometheus", not(test)))]
+            METRIC_BUDGET_DROPS.increment(&[class.queue()]);
+
+            None
+        }
+    }
+
+    /// Accounts `bytes` held in the given `queue` regardless of the budget.
+    ///
+    /// Used for the packets originated by this node, which are never dropped, but still take
+    /// their share of the budget from the received ones.
+    pub fn track(&self, queue: &'static str, bytes: usize) -> BufferedBytes {
+        self.state.buffered.fetch_add(bytes, Ordering::AcqRel);
+        BufferedBytes::new(self.state.clone(), queue, bytes)
+    }
+
+    /// Creates a queue of the received packets of the given `class`.
+    ///
+    /// The receiving end yields each item together with its [`BufferedBytes`], which should be held
+    /// until the item has been handed over further.
+    pub fn channel<T>(&self, class: IngressClass) -> (BudgetedSender<T>, UnboundedReceiver<(T, BufferedBytes)>) {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        (
+            BudgetedSender {
+                accounting: self.clone(),
+                class,
+                tx,
+            },
+            rx,
+        )
+    }
+
+    /// Wraps the `sink` so that the bytes of each packet sent into it are accounted in the given `queue`.
+    ///
+    /// The wrapped sink receives each packet together with its [`BufferedBytes`], which should be held
+    /// until the packet leaves the sink, e.g. until the mixer releases it.
+    pub fn tracked_sink<S>(&self, queue: &'static str, sink: S) -> TrackedSink<S> {
+        TrackedSink {
+            inner: sink,
+            accounting: self.clone(),
+            queue,
+        }
+    }
+}
+
+/// Sink created by [`BufferAccounting::tracked_sink`].
+#[derive(Debug, Clone)]
+pub struct TrackedSink<S> {
+    inner: S,
+    accounting: BufferAccounting,
+    queue: &'static str,
+}
+
+impl<S> Sink<(PeerId, Box<[u8]>)> for TrackedSink<S>
+where
+    S: Sink<((PeerId, Box<[u8]>), BufferedBytes)> + Unpin,
+{
+    type Error = S::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: (PeerId, Box<[u8]>)) -> Result<(), Self::Error> {
+        let buffered = self.accounting.track(self.queue, item.1.len());
+        Pin::new(&mut self.inner).start_send((item, buffered))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+/// Sending end of a queue created by [`BufferAccounting::channel`].
+#[derive(Debug)]
+pub struct BudgetedSender<T> {
+    accounting: BufferAccounting,
+    class: IngressClass,
+    tx: UnboundedSender<(T, BufferedBytes)>,
+}
+
+impl<T> Clone for BudgetedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            accounting: self.accounting.clone(),
+            class: self.class,
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T> BudgetedSender<T> {
+    /// Enqueues the `item` of the given size in bytes without waiting for the receiving end.
+    ///
+    /// Returns `false` if the item has been dropped, because it does not fit into the budget
+    /// or the receiving end is gone.
+    pub fn push(&self, item: T, bytes: usize) -> bool {
+        self.accounting
+            .admit(self.class, bytes)
+            .is_some_and(|buffered| self.tx.unbounded_send((item, buffered)).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use futures::{SinkExt, StreamExt};
+
+    fn budget(max_bytes: usize, relay_percent: u8) -> BufferBudget {
+        BufferBudget {
+            max_bytes,
+            relay_percent,
+        }
+    }
+
+    #[test]
+    fn buffer_accounting_should_drop_relay_traffic_before_local_traffic() {
+        let accounting = BufferAccounting::new(budget(1000, 50));
+
+        let relayed = (0..10)
+            .filter_map(|_| accounting.admit(IngressClass::Relay, 100))
+            .collect::<Vec<_>>();
+        assert_eq!(5, relayed.len(), "relay traffic must be capped by its share");
+        assert_eq!(5, accounting.dropped(IngressClass::Relay));
+
+        let local = (0..10)
+            .filter_map(|_| accounting.admit(IngressClass::Local, 100))
+            .collect::<Vec<_>>();
+        assert_eq!(5, local.len(), "local traffic must use the rest of the budget");
+        assert_eq!(5, accounting.dropped(IngressClass::Local));
+        assert_eq!(1000, accounting.buffered_bytes());
+
+        drop(relayed);
+        assert_eq!(500, accounting.buffered_bytes());
+        drop(local);
+        assert_eq!(0, accounting.buffered_bytes());
+    }
+
+    #[test]
+    fn buffer_accounting_should_count_the_tracked_bytes_towards_the_budget() {
+        let accounting = BufferAccounting::new(budget(1000, 100));
+
+        let egress = accounting.track(EGRESS_QUEUE, 900);
+        assert!(accounting.admit(IngressClass::Local, 200).is_none());
+        assert!(accounting.admit(IngressClass::Local, 100).is_some());
+
+        drop(egress);
+        assert_eq!(0, accounting.buffered_bytes());
+    }
+
+    #[async_std::test]
+    async fn tracked_sink_should_account_the_packets_until_they_leave_the_wrapped_sink() -> anyhow::Result<()> {
+        let accounting = BufferAccounting::new(budget(1000, 50));
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<((PeerId, Box<[u8]>), BufferedBytes)>();
+        let mut sink = accounting.tracked_sink(MIXER_QUEUE, tx);
+
+        sink.send((PeerId::random(), vec![0_u8; 300].into_boxed_slice()))
+            .await?;
+        sink.send((PeerId::random(), vec![0_u8; 200].into_boxed_slice()))
+            .await?;
+        assert_eq!(500, accounting.buffered_bytes());
+        assert!(
+            accounting.admit(IngressClass::Relay, 100).is_none(),
+            "the packets in the sink must count towards the budget"
+        );
+
+        let (_, buffered) = rx.next().await.context("item should be present")?;
+        assert_eq!(MIXER_QUEUE, buffered.queue());
+        assert_eq!(300, buffered.bytes());
+        drop(buffered);
+        assert_eq!(200, accounting.buffered_bytes());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn budgeted_queue_should_cap_the_buffered_bytes_behind_a_stalled_sink() -> anyhow::Result<()> {
+        let accounting = BufferAccounting::new(budget(2000, 50));
+        let (relay_tx, mut relay_rx) = accounting.channel::<Box<[u8]>>(IngressClass::Relay);
+        let (local_tx, _local_rx) = accounting.channel::<Box<[u8]>>(IngressClass::Local);
+
+        // The sink is full and its receiver never makes progress
+        let (mut stalled_tx, _stalled_rx) = futures::channel::mpsc::channel::<Box<[u8]>>(0);
+        stalled_tx.try_send(Box::default())?;
+        let _drain = async_std::task::spawn(async move {
+            while let Some((item, _buffered)) = relay_rx.next().await {
+                if stalled_tx.send(item).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut relayed = 0;
+        for _ in 0..100 {
+            if relay_tx.push(vec![0_u8; 100].into_boxed_slice(), 100) {
+                relayed += 1;
+            }
+            assert!(accounting.buffered_bytes() <= 1000);
+            async_std::task::yield_now().await;
+        }
+        assert_eq!(10, relayed, "only the relay share of the budget must be buffered");
+        assert_eq!(90, accounting.dropped(IngressClass::Relay));
+
+        let local = (0..100)
+            .filter(|_| local_tx.push(vec![0_u8; 100].into_boxed_slice(), 100))
+            .count();
+        assert_eq!(10, local, "local traffic must still fit into the rest of the budget");
+        assert_eq!(90, accounting.dropped(IngressClass::Local));
+        assert_eq!(2000, accounting.buffered_bytes());
+
+        Ok(())
+    }
+}
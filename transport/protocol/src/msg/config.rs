@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
+use validator::{Validate, ValidationError};
+
+use crate::retry::DbRetryConfig;
+use crate::stream::{PrioritySchedulerConfig, SinkFailurePolicy, SourceErrorBackoffConfig};
+
+/// Default number of peers labelled individually in the per-peer packet metrics.
+pub const DEFAULT_PEER_METRIC_LABELS_TOP_N: usize = 50;
+
+fn default_egress_pause_buffer_size() -> usize {
+    1024
+}
+
+fn default_max_throttled_per_peer() -> usize {
+    64
+}
+
+fn default_tag_wal_max_batch_latency() -> Duration {
+    crate::bloom::DEFAULT_TAG_WAL_MAX_BATCH_LATENCY
+}
+
+fn validate_tag_wal_max_batch_latency(value: &Duration) -> Result<(), ValidationError> {
+    if value.is_zero() {
+        Err(ValidationError::new(
+            "tag write-ahead log batch latency must be greater than zero",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn default_drop_log_every_nth() -> u32 {
+    1
+}
+
+fn default_drop_log_max_per_sec() -> Option<u32> {
+    Some(10)
+}
+
+/// Sampling of the log records of the received packets dropped due to an error.
+///
+/// Every dropped packet is still counted in the metrics, only the logging is sampled.
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DropLogSampling {
+    /// Only every n-th dropped packet is logged.
+    ///
+    /// One logs every dropped packet.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_drop_log_every_nth")]
+    #[default(default_drop_log_every_nth())]
+    pub every_nth: u32,
+    /// Maximum number of dropped packets logged per second.
+    ///
+    /// If not set, the number of logged dropped packets is not limited.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_drop_log_max_per_sec")]
+    #[default(default_drop_log_max_per_sec())]
+    pub max_per_sec: Option<u32>,
+}
+
+fn default_buffer_budget_max_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+fn default_buffer_budget_relay_percent() -> u8 {
+    75
+}
+
+/// Global budget of the bytes buffered in the queues of the packet pipeline.
+///
+/// The received packets that do not fit into the budget are dropped, see [`crate::msg::budget`].
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BufferBudget {
+    /// Maximum number of payload bytes buffered in the pipeline queues.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_buffer_budget_max_bytes")]
+    #[default(default_buffer_budget_max_bytes())]
+    pub max_bytes: usize,
+    /// Percentage of `max_bytes` above which the packets relayed to other peers are dropped.
+    ///
+    /// The rest of the budget is reserved for the packets delivered to this node.
+    #[validate(range(min = 1, max = 100))]
+    #[serde(default = "default_buffer_budget_relay_percent")]
+    #[default(default_buffer_budget_relay_percent())]
+    pub relay_percent: u8,
+}
+
+/// Controls how peers are represented in the per-peer packet count metric.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerMetricLabels {
+    /// The per-peer packet count metric is not collected.
+    Off,
+    /// Only the given number of the most active peers is labelled individually,
+    /// all the other peers are aggregated under the `other` label.
+    TopN(usize),
+    /// Every peer is labelled individually.
+    ///
+    /// Use with care, the metric cardinality grows with the number of peers seen.
+    Full,
+}
+
+impl Default for PeerMetricLabels {
+    fn default() -> Self {
+        Self::TopN(DEFAULT_PEER_METRIC_LABELS_TOP_N)
+    }
+}
+
+/// Configuration for the `msg` protocol.
+#[serde_as]
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MsgProtocolConfig {
+    /// Cardinality control of the peer label of the per-peer packet metrics
+    #[serde(default)]
+    pub peer_metric_labels: PeerMetricLabels,
+    /// Behavior when sending a packet to the wire fails
+    #[serde(default)]
+    pub sink_failure_policy: SinkFailurePolicy,
+    /// Maximum time a packet send may take until it is finalized by the pipeline.
+    ///
+    /// If not set, the senders may wait indefinitely when the pipeline is stuck.
+    #[serde_as(as = "Option<DurationMilliSeconds<u64>>")]
+    #[serde(default)]
+    pub send_finalizer_timeout: Option<Duration>,
+    /// Maximum size of the received application data payload delivered to the application.
+    ///
+    /// Larger payloads are dropped. If not set, payloads of any size are delivered.
+    #[validate(range(min = 1))]
+    #[serde(default)]
+    pub max_application_data_bytes: Option<usize>,
+    /// Maximum number of packet sends held back while the egress is paused.
+    ///
+    /// The sends that do not fit are failed instead of waiting for the egress to resume.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_egress_pause_buffer_size")]
+    #[default(default_egress_pause_buffer_size())]
+    pub egress_pause_buffer_size: usize,
+    /// Retrying of the idempotent DB reads failing with a transient error while processing the packets.
+    ///
+    /// The DB operations creating or processing the packets are never retried, since they are not idempotent.
+    #[serde(default)]
+    pub db_retry: DbRetryConfig,
+    /// Finalize the packet sends only once the packet has been handed over to the wire sink.
+    ///
+    /// By default, a send is finalized as soon as the packet is wrapped, so the sender is not notified
+    /// when the packet cannot be delivered to the first hop. If enabled, such a send fails with
+    /// [`ProtocolError::FirstHopUnreachable`](crate::errors::ProtocolError::FirstHopUnreachable),
+    /// the same as a send whose first hop is known to be unreachable already before it is sent
+    /// (see [`crate::msg::reachability`]).
+    #[serde(default)]
+    pub finalize_after_wire_send: bool,
+    /// Sampling of the log records of the received packets dropped due to an error
+    #[validate(nested)]
+    #[serde(default)]
+    pub drop_log_sampling: DropLogSampling,
+    /// Global budget of the bytes buffered in the queues of the packet pipeline
+    #[validate(nested)]
+    #[serde(default)]
+    pub buffer_budget: BufferBudget,
+    /// Maximum number of packets of a throttled peer held back until they are processed.
+    ///
+    /// The packets of the peer that do not fit are dropped, see [`crate::msg::gate::PeerThrottle`].
+    #[validate(range(min = 1))]
+    #[serde(default = "default_max_throttled_per_peer")]
+    #[default(default_max_throttled_per_peer())]
+    pub max_throttled_per_peer: usize,
+    /// Delaying of the packets from the peers whose packets repeatedly fail to be processed.
+    #[validate(nested)]
+    #[serde(default)]
+    pub source_error_backoff: SourceErrorBackoffConfig,
+    /// Scheduling of the outgoing packets by their priority before they are wrapped.
+    ///
+    /// The high priority packets (e.g. control messages) are wrapped and sent ahead of the normal ones.
+    #[validate(nested)]
+    #[serde(default)]
+    pub priority_scheduler: PrioritySchedulerConfig,
+    /// Maximum time the tags of the received packets wait until they are synced to the write-ahead log
+    /// of the packet tag Bloom filter.
+    ///
+    /// A received packet is processed only once its tag has been synced, so this bounds the added
+    /// per-packet latency, while shorter values sync the log more often.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[validate(custom(function = "validate_tag_wal_max_batch_latency"))]
+    #[serde(default = "default_tag_wal_max_batch_latency")]
+    #[default(default_tag_wal_max_batch_latency())]
+    pub tag_wal_max_batch_latency: Duration,
+}
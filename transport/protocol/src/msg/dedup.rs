@@ -0,0 +1,187 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use hopr_crypto_packet::errors::Result;
+use hopr_crypto_types::prelude::Blake3;
+use hopr_transport_identity::PeerId;
+use tracing::debug;
+
+use crate::msg::processor::PacketUnwrapping;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::SimpleCounter;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_DUPLICATE_WIRE_ITEMS: SimpleCounter = SimpleCounter::new(
+        "hopr_duplicate_wire_items",
+        "Number of received wire items dropped as exact duplicates of a recently received one"
+    )
+    .unwrap();
+}
+
+/// Default number of the recently received wire items remembered by the [`WireDuplicateFilter`].
+pub const DEFAULT_WIRE_DUPLICATE_CAPACITY: u64 = 4096;
+
+/// Drops the exact duplicates of the recently received wire items before they are decrypted.
+///
+/// Some transports can redeliver the same wire item, e.g. after a stream reset. Such a duplicate
+/// is recognized by the digest of its sender and raw bytes, which is much cheaper than decrypting the packet
+/// only to have it rejected as a replay. The least recently received items are forgotten first. The duplicates are dropped silently, without penalizing
+/// the peer or sending a negative acknowledgement.
+///
+/// This is only a best-effort pre-filter of the recent items, the replayed packets are still rejected
+/// by the tag Bloom filter once decrypted.
+///
+/// All the clones of the filter share the same state.
+#[derive(Debug, Clone)]
+pub struct WireDuplicateFilter {
+    seen: moka::future::Cache<[u8; 32], ()>,
+    duplicates: Arc<AtomicU64>,
+}
+
+impl Default for WireDuplicateFilter {
+    fn default() -> Self {
+        Self::new(DEFAULT_WIRE_DUPLICATE_CAPACITY)
+    }
+}
+
+impl WireDuplicateFilter {
+    /// Creates the filter remembering up to `capacity` of the recently received wire items.
+    pub fn new(capacity: u64) -> Self {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        lazy_static::initialize(&METRIC_DUPLICATE_WIRE_ITEMS);
+
+        Self {
+            seen: moka::future::Cache::builder()
+                .max_capacity(capacity)
+                .eviction_policy(moka::policy::EvictionPolicy::lru())
+                .build(),
+            duplicates: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Number of the wire items dropped as duplicates.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Records the wire item `data` received from the `peer` as seen and indicates whether it had been
+    /// received from the same peer recently.
+    pub async fn is_duplicate(&self, peer: &PeerId, data: &[u8]) -> bool {
+        let mut hasher = Blake3::new();
+        hasher.update(&peer.to_bytes());
+        hasher.update(data);
+        let digest = *hasher.finalize().as_bytes();
+
+        // Concurrently received duplicates cannot both be considered fresh
+        if self.seen.entry(digest).or_insert(()).await.is_fresh() {
+            return false;
+        }
+
+        self.duplicates.fetch_add(1, Ordering::Relaxed);
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_DUPLICATE_WIRE_ITEMS.increment();
+
+        true
+    }
+
+    /// Unwraps the wire item `data` received from the `peer`, unless it is a duplicate.
+    ///
+    /// Returns `None` if the item was a duplicate, in which case the `unwrapper` is never called.
+    pub async fn recv_unique<U>(&self, unwrapper: &U, peer: &PeerId, data: Box<[u8]>) -> Option<Result<U::Packet>>
+    where
+        U: PacketUnwrapping + Sync,
+    {
+        if self.is_duplicate(peer, &data).await {
+            debug!(%peer, size = data.len(), "Dropping a duplicate wire item");
+            return None;
+        }
+
+        Some(unwrapper.recv(peer, data).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_crypto_types::keypairs::{Keypair, OffchainKeypair};
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Debug, Default)]
+    struct CountingUnwrapper {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl PacketUnwrapping for CountingUnwrapper {
+        type Packet = usize;
+
+        async fn recv(&self, _peer: &PeerId, data: Box<[u8]>) -> Result<Self::Packet> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(data.len())
+        }
+    }
+
+    fn random_peer() -> PeerId {
+        PeerId::from(*OffchainKeypair::random().public())
+    }
+
+    #[async_std::test]
+    async fn wire_duplicate_filter_should_unwrap_the_same_raw_bytes_only_once() -> anyhow::Result<()> {
+        let filter = WireDuplicateFilter::default();
+        let unwrapper = CountingUnwrapper::default();
+        let peer = random_peer();
+        let data: Box<[u8]> = vec![0xaa; 500].into_boxed_slice();
+
+        assert!(matches!(
+            filter.recv_unique(&unwrapper, &peer, data.clone()).await,
+            Some(Ok(500))
+        ));
+        assert!(filter.recv_unique(&unwrapper, &peer, data).await.is_none());
+
+        assert_eq!(
+            1,
+            unwrapper.calls.load(Ordering::SeqCst),
+            "duplicate must not be unwrapped"
+        );
+        assert_eq!(1, filter.duplicates());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn wire_duplicate_filter_should_unwrap_distinct_items() -> anyhow::Result<()> {
+        let filter = WireDuplicateFilter::default();
+        let unwrapper = CountingUnwrapper::default();
+        let peer = random_peer();
+
+        for i in 0..10_u8 {
+            let data = vec![i; 500].into_boxed_slice();
+            assert!(filter.recv_unique(&unwrapper, &peer, data).await.is_some());
+        }
+
+        assert_eq!(10, unwrapper.calls.load(Ordering::SeqCst));
+        assert_eq!(0, filter.duplicates());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn wire_duplicate_filter_should_unwrap_the_same_raw_bytes_from_distinct_peers() -> anyhow::Result<()> {
+        let filter = WireDuplicateFilter::default();
+        let unwrapper = CountingUnwrapper::default();
+        let data: Box<[u8]> = vec![0xaa; 500].into_boxed_slice();
+
+        assert!(filter
+            .recv_unique(&unwrapper, &random_peer(), data.clone())
+            .await
+            .is_some());
+        assert!(filter.recv_unique(&unwrapper, &random_peer(), data).await.is_some());
+
+        assert_eq!(2, unwrapper.calls.load(Ordering::SeqCst));
+        assert_eq!(0, filter.duplicates());
+
+        Ok(())
+    }
+}
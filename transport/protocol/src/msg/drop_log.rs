@@ -0,0 +1,157 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hopr_async_runtime::clock::{Clock, RealClock};
+use hopr_crypto_packet::errors::PacketError;
+
+use crate::msg::config::DropLogSampling;
+
+/// Label of the reason a received packet has been dropped for, used in the drop metrics and log records.
+pub fn drop_reason(error: &PacketError) -> &'static str {
+    match error {
+        PacketError::PacketDecodingError(_) => "decoding",
+        PacketError::PacketConstructionError(_) => "construction",
+        PacketError::TagReplay => "replay",
+        PacketError::ChannelNotFound(_) => "channel_not_found",
+        PacketError::TicketValidation(_) => "ticket_validation",
+        PacketError::AcknowledgementValidation(_) => "ack_validation",
+        PacketError::PoRVerificationError => "por_verification",
+        PacketError::OutOfFunds(_) => "out_of_funds",
+        PacketError::LogicError(_) => "logic",
+        PacketError::Retry => "retry",
+        PacketError::TransportError(_) => "transport",
+        PacketError::PathPositionMismatch => "path_position_mismatch",
+        PacketError::MissingDomainSeparator => "missing_domain_separator",
+        PacketError::CryptographicError(_) => "crypto",
+        PacketError::CoreTypesError(_) => "core_types",
+        PacketError::SphinxError(_) => "sphinx",
+        PacketError::Other(_) => "other",
+    }
+}
+
+#[derive(Debug, Default)]
+struct SamplerState {
+    drops: u64,
+    window_start: Option<Instant>,
+    logged_in_window: u32,
+    suppressed: u64,
+}
+
+/// Decides which of the dropped packets are logged, so that a flood of bad packets does not
+/// also flood the logs.
+///
+/// Only every n-th drop is logged and at most the configured number of drops per second,
+/// see [`DropLogSampling`]. The drops themselves are still counted by the caller.
+///
+/// All the clones of the sampler share the same state.
+#[derive(Debug, Clone)]
+pub struct DropLogSampler<C: Clock = RealClock> {
+    cfg: DropLogSampling,
+    clock: C,
+    state: Arc<Mutex<SamplerState>>,
+}
+
+impl DropLogSampler {
+    pub fn new(cfg: DropLogSampling) -> Self {
+        Self::with_clock(cfg, RealClock)
+    }
+}
+
+impl<C: Clock> DropLogSampler<C> {
+    pub fn with_clock(cfg: DropLogSampling, clock: C) -> Self {
+        Self {
+            cfg,
+            clock,
+            state: Arc::new(Mutex::new(SamplerState::default())),
+        }
+    }
+
+    /// Records a dropped packet.
+    ///
+    /// Returns the number of drops suppressed since the last logged one, if this drop should be logged.
+    pub fn sample(&self) -> Option<u64> {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.drops += 1;
+        let selected = (state.drops - 1) % u64::from(self.cfg.every_nth.max(1)) == 0;
+
+        let within_rate = match self.cfg.max_per_sec {
+            Some(max_per_sec) => {
+                if state
+                    .window_start
+                    .is_none_or(|start| now.saturating_duration_since(start) >= Duration::from_secs(1))
+                {
+                    state.window_start = Some(now);
+                    state.logged_in_window = 0;
+                }
+                state.logged_in_window < max_per_sec
+            }
+            None => true,
+        };
+
+        if selected && within_rate {
+            state.logged_in_window += 1;
+            Some(std::mem::take(&mut state.suppressed))
+        } else {
+            state.suppressed += 1;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_async_runtime::clock::MockClock;
+
+    fn logged(sampler: &DropLogSampler<MockClock>, drops: usize) -> Vec<u64> {
+        (0..drops).filter_map(|_| sampler.sample()).collect()
+    }
+
+    #[test]
+    fn drop_log_sampler_should_log_every_nth_drop() {
+        let sampler = DropLogSampler::with_clock(
+            DropLogSampling {
+                every_nth: 3,
+                max_per_sec: None,
+            },
+            MockClock::default(),
+        );
+
+        assert_eq!(vec![0, 2, 2, 2], logged(&sampler, 10));
+    }
+
+    #[test]
+    fn drop_log_sampler_should_log_at_most_the_given_number_of_drops_per_second() {
+        let clock = MockClock::default();
+        let sampler = DropLogSampler::with_clock(
+            DropLogSampling {
+                every_nth: 1,
+                max_per_sec: Some(2),
+            },
+            clock.clone(),
+        );
+
+        assert_eq!(vec![0, 0], logged(&sampler, 100));
+
+        clock.advance(Duration::from_millis(500));
+        assert!(logged(&sampler, 10).is_empty(), "window must not be renewed yet");
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(vec![108, 0], logged(&sampler, 5));
+    }
+
+    #[test]
+    fn drop_log_sampler_should_log_all_drops_when_not_limited() {
+        let sampler = DropLogSampler::with_clock(
+            DropLogSampling {
+                every_nth: 1,
+                max_per_sec: None,
+            },
+            MockClock::default(),
+        );
+
+        assert_eq!(vec![0; 1000], logged(&sampler, 1000));
+    }
+}
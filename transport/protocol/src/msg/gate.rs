@@ -0,0 +1,422 @@
+//! Admission of the incoming packets based on the peer they were received from.
+//!
+//! The [`PeerGate`] is consulted in the `MsgIn` process before a packet is decrypted,
+//! so packets from unwanted peers do not cost any cryptographic operations.
+//!
+//! The packets of a throttled peer are held back in a bounded per-peer queue of the [`PeerThrottle`]
+//! and released one after another, so a throttled peer cannot make the node buffer an unbounded
+//! number of packets.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use hopr_crypto_types::types::OffchainPublicKey;
+use hopr_db_api::resolver::HoprDbResolverOperations;
+use hopr_transport_identity::PeerId;
+use tracing::{debug, warn};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::MultiCounter;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    static ref METRIC_ADMISSION_COUNT: MultiCounter = MultiCounter::new(
+        "hopr_packet_admission_count",
+        "Number of incoming packets by the admission outcome of the peer they were received from",
+        &["outcome"]
+    )
+    .unwrap();
+}
+
+/// Decision of a [`PeerGate`] about the packets from a peer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// The packet is processed.
+    Allow,
+    /// The packet is dropped without being processed.
+    Deny,
+    /// The packets are processed one after another, each after the given delay.
+    Throttle(Duration),
+}
+
+impl Admission {
+    fn outcome(&self) -> &'static str {
+        match self {
+            Admission::Allow => "allow",
+            Admission::Deny => "deny",
+            Admission::Throttle(_) => "throttle",
+        }
+    }
+}
+
+/// Decides whether the packets received from a peer are processed.
+#[async_trait]
+pub trait PeerGate: Debug + Send + Sync {
+    /// Admission of the packets received from the `peer`.
+    async fn admit(&self, peer: &PeerId) -> Admission;
+}
+
+/// Gate admitting the packets from all peers.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AllowAllGate;
+
+#[async_trait]
+impl PeerGate for AllowAllGate {
+    async fn admit(&self, _peer: &PeerId) -> Admission {
+        Admission::Allow
+    }
+}
+
+/// Gate admitting only the packets from peers which have announced themselves on-chain.
+///
+/// If the announcement cannot be looked up, the packet is admitted.
+#[derive(Debug, Clone)]
+pub struct AnnouncedPeerGate<Db> {
+    db: Db,
+}
+
+impl<Db> AnnouncedPeerGate<Db> {
+    /// Looks up the announcements of the peers in the given `db`.
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl<Db> PeerGate for AnnouncedPeerGate<Db>
+where
+    Db: HoprDbResolverOperations + Debug + Send + Sync,
+{
+    async fn admit(&self, peer: &PeerId) -> Admission {
+        let Ok(packet_key) = OffchainPublicKey::try_from(peer) else {
+            return Admission::Deny;
+        };
+
+        match self.db.is_announced(&packet_key).await {
+            Ok(true) => Admission::Allow,
+            Ok(false) => Admission::Deny,
+            Err(error) => {
+                warn!(%peer, %error, "Failed to look up the peer announcement, admitting the packet");
+                Admission::Allow
+            }
+        }
+    }
+}
+
+/// Decorator remembering the admissions of the inner gate for a limited time,
+/// so that the inner gate is not consulted for each packet.
+#[derive(Debug, Clone)]
+pub struct CachedPeerGate<G> {
+    inner: G,
+    cache: moka::future::Cache<PeerId, Admission>,
+}
+
+impl<G: PeerGate> CachedPeerGate<G> {
+    /// Remembers up to `capacity` admissions of the `inner` gate, each for the `ttl`.
+    pub fn new(inner: G, ttl: Duration, capacity: u64) -> Self {
+        Self {
+            inner,
+            cache: moka::future::Cache::builder()
+                .time_to_live(ttl)
+                .max_capacity(capacity)
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl<G: PeerGate> PeerGate for CachedPeerGate<G> {
+    async fn admit(&self, peer: &PeerId) -> Admission {
+        self.cache.get_with_by_ref(peer, self.inner.admit(peer)).await
+    }
+}
+
+#[derive(Debug)]
+struct ThrottledPeer {
+    held: usize,
+    release_at: Instant,
+}
+
+/// Per-peer queues of the packets held back by the [`Admission::Throttle`].
+///
+/// The throttled packets of a peer are released one after another, each after the throttle delay.
+/// At most `capacity` packets of a peer are held back at a time, the packets over the capacity are dropped.
+#[derive(Debug)]
+pub struct PeerThrottle {
+    capacity: usize,
+    peers: Mutex<HashMap<PeerId, ThrottledPeer>>,
+}
+
+impl PeerThrottle {
+    /// Holds back at most `capacity` packets of each throttled peer.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            peers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of the packets of the `peer` currently held back.
+    pub fn held(&self, peer: &PeerId) -> usize {
+        self.peers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(peer)
+            .map_or(0, |throttled| throttled.held)
+    }
+
+    /// Reserves the release of the next packet of the `peer`, unless its queue is full.
+    fn hold(&self, peer: &PeerId, delay: Duration) -> Option<HeldPacket<'_>> {
+        let now = Instant::now();
+        let mut peers = self.peers.lock().unwrap_or_else(|e| e.into_inner());
+        let throttled = peers.entry(*peer).or_insert(ThrottledPeer {
+            held: 0,
+            release_at: now,
+        });
+        if throttled.held >= self.capacity {
+            return None;
+        }
+
+        throttled.held += 1;
+        throttled.release_at = throttled.release_at.max(now) + delay;
+
+        Some(HeldPacket {
+            throttle: self,
+            peer: *peer,
+            release_at: throttled.release_at,
+        })
+    }
+}
+
+/// Packet held back in the queue of its peer until it is dropped.
+struct HeldPacket<'a> {
+    throttle: &'a PeerThrottle,
+    peer: PeerId,
+    release_at: Instant,
+}
+
+impl Drop for HeldPacket<'_> {
+    fn drop(&mut self) {
+        let mut peers = self.throttle.peers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Entry::Occupied(mut throttled) = peers.entry(self.peer) {
+            throttled.get_mut().held -= 1;
+            if throttled.get().held == 0 {
+                throttled.remove();
+            }
+        }
+    }
+}
+
+/// Runs the `process` only if the packet from the `peer` is admitted by the `gate`.
+///
+/// The packets of a throttled peer are held back by the `throttle` before they are processed.
+/// Returns `None` if the packet was denied or did not fit into the queue of the throttled peer,
+/// in which case the `process` is never polled.
+pub async fn admit_then<F: Future>(
+    gate: &dyn PeerGate,
+    throttle: &PeerThrottle,
+    peer: &PeerId,
+    process: F,
+) -> Option<F::Output> {
+    let admission = gate.admit(peer).await;
+
+    #[cfg(all(feature = "prometheus", not(test)))]
+    METRIC_ADMISSION_COUNT.increment(&[admission.outcome()]);
+
+    match admission {
+        Admission::Allow => Some(process.await),
+        Admission::Deny => {
+            debug!(%peer, outcome = admission.outcome(), "Packet from peer not admitted");
+            None
+        }
+        Admission::Throttle(delay) => {
+            let Some(held) = throttle.hold(peer, delay) else {
+                #[cfg(all(feature = "prometheus", not(test)))]
+                METRIC_ADMISSION_COUNT.increment(&["throttle_overflow"]);
+
+                debug!(%peer, outcome = admission.outcome(), "Packet from throttled peer over the queue capacity");
+                return None;
+            };
+
+            hopr_async_runtime::prelude::sleep(held.release_at.saturating_duration_since(Instant::now())).await;
+            drop(held);
+            Some(process.await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_crypto_types::keypairs::{Keypair, OffchainKeypair};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone)]
+    struct CountingGate {
+        admission: Admission,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PeerGate for CountingGate {
+        async fn admit(&self, _peer: &PeerId) -> Admission {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.admission
+        }
+    }
+
+    fn random_peer() -> PeerId {
+        PeerId::from(*OffchainKeypair::random().public())
+    }
+
+    #[async_std::test]
+    async fn denied_packet_should_not_be_processed() {
+        let gate = CountingGate {
+            admission: Admission::Deny,
+            calls: Default::default(),
+        };
+        let processed = AtomicUsize::new(0);
+
+        let result = admit_then(&gate, &PeerThrottle::new(1), &random_peer(), async {
+            processed.fetch_add(1, Ordering::SeqCst);
+        })
+        .await;
+
+        assert!(result.is_none());
+        assert_eq!(
+            0,
+            processed.load(Ordering::SeqCst),
+            "denied packet must not be processed"
+        );
+    }
+
+    #[async_std::test]
+    async fn admitted_and_throttled_packets_should_be_processed() {
+        let peer = random_peer();
+        let throttle = PeerThrottle::new(1);
+
+        assert_eq!(Some(1), admit_then(&AllowAllGate, &throttle, &peer, async { 1 }).await);
+
+        let throttled = CountingGate {
+            admission: Admission::Throttle(Duration::from_millis(10)),
+            calls: Default::default(),
+        };
+        let start = std::time::Instant::now();
+        assert_eq!(Some(2), admit_then(&throttled, &throttle, &peer, async { 2 }).await);
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(0, throttle.held(&peer), "released packet must leave the queue");
+    }
+
+    #[async_std::test]
+    async fn throttled_packets_should_be_released_one_after_another_and_bounded_per_peer() {
+        let delay = Duration::from_millis(20);
+        let gate = CountingGate {
+            admission: Admission::Throttle(delay),
+            calls: Default::default(),
+        };
+        let throttle = PeerThrottle::new(3);
+        let (peer, other_peer) = (random_peer(), random_peer());
+
+        let senders = [peer, peer, peer, peer, peer, other_peer];
+
+        let start = std::time::Instant::now();
+        let results = futures::future::join_all(
+            senders
+                .iter()
+                .map(|sender| admit_then(&gate, &throttle, sender, async move { start.elapsed() })),
+        )
+        .await;
+
+        let (peer_results, other_results) = results.split_at(5);
+        assert_eq!(
+            3,
+            peer_results.iter().flatten().count(),
+            "packets over the queue capacity must be dropped"
+        );
+        for (n, elapsed) in peer_results.iter().flatten().enumerate() {
+            assert!(
+                *elapsed >= delay * (n as u32 + 1),
+                "packets must be released one after another"
+            );
+        }
+
+        let other_elapsed = other_results[0].expect("packet of the other peer must not be dropped");
+        assert!(
+            other_elapsed < delay * 2,
+            "other peers must not wait for the throttled peer"
+        );
+        assert_eq!(0, throttle.held(&peer));
+    }
+
+    #[async_std::test]
+    async fn cached_gate_should_consult_inner_gate_once_per_peer() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gate = CachedPeerGate::new(
+            CountingGate {
+                admission: Admission::Deny,
+                calls: calls.clone(),
+            },
+            Duration::from_secs(60),
+            100,
+        );
+
+        let (peer_1, peer_2) = (random_peer(), random_peer());
+        for _ in 0..10 {
+            assert_eq!(Admission::Deny, gate.admit(&peer_1).await);
+            assert_eq!(Admission::Deny, gate.admit(&peer_2).await);
+        }
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn announced_peer_gate_should_deny_unannounced_peers() -> anyhow::Result<()> {
+        use hopr_crypto_types::keypairs::ChainKeypair;
+        use hopr_db_sql::{accounts::HoprDbAccountOperations, db::HoprDb};
+        use hopr_internal_types::account::{AccountEntry, AccountType};
+        use std::str::FromStr;
+
+        let db = HoprDb::new_in_memory(ChainKeypair::random()).await?;
+        let (announced, unannounced) = (OffchainKeypair::random(), OffchainKeypair::random());
+        let registered = OffchainKeypair::random();
+        db.insert_account(
+            None,
+            AccountEntry {
+                public_key: *registered.public(),
+                chain_addr: ChainKeypair::random().public().to_address(),
+                entry_type: AccountType::NotAnnounced,
+                published_at: 1,
+            },
+        )
+        .await?;
+        db.insert_account(
+            None,
+            AccountEntry {
+                public_key: *announced.public(),
+                chain_addr: ChainKeypair::random().public().to_address(),
+                entry_type: AccountType::Announced {
+                    multiaddr: hopr_transport_identity::Multiaddr::from_str("/ip4/127.0.0.1/tcp/4444")?,
+                    updated_block: 1,
+                },
+                published_at: 1,
+            },
+        )
+        .await?;
+
+        let gate = AnnouncedPeerGate::new(db);
+        assert_eq!(Admission::Allow, gate.admit(&announced.public().into()).await);
+        assert_eq!(Admission::Deny, gate.admit(&unannounced.public().into()).await);
+        assert_eq!(
+            Admission::Deny,
+            gate.admit(&registered.public().into()).await,
+            "registered account without an announcement must be denied"
+        );
+
+        Ok(())
+    }
+}
@@ -1,6 +1,16 @@
+pub mod accounting;
+pub mod budget;
 mod codec;
+pub mod config;
+pub mod dedup;
+pub mod drop_log;
+pub mod gate;
 pub mod packet;
+pub mod peer_labels;
+pub mod pricing;
 pub mod processor;
+pub mod reachability;
+pub mod retransmit;
 
 pub use codec::v1::MsgCodec;
 pub const CURRENT_HOPR_MSG_PROTOCOL: &str = "/hopr/msg/1.0.0";
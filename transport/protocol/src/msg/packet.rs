@@ -2,7 +2,7 @@ use hopr_db_api::protocol::TransportPacketWithChainData;
 use hopr_transport_identity::PeerId;
 
 use hopr_crypto_types::prelude::*;
-use hopr_internal_types::protocol::Acknowledgement;
+use hopr_primitive_types::prelude::Balance;
 
 use crate::errors::ProtocolError;
 
@@ -21,7 +21,7 @@ pub enum IncomingPacket {
         previous_hop: PeerId,
         next_hop: PeerId,
         data: Box<[u8]>,
-        ack: Acknowledgement,
+        ack_key: HalfKey,
     },
 }
 
@@ -48,13 +48,14 @@ impl TryFrom<TransportPacketWithChainData> for IncomingPacket {
                 previous_hop,
                 next_hop,
                 data,
-                ack,
+                ack_key,
+                ..
             } => Ok(IncomingPacket::Forwarded {
                 packet_tag,
                 previous_hop: previous_hop.into(),
                 next_hop: next_hop.into(),
                 data,
-                ack,
+                ack_key,
             }),
             TransportPacketWithChainData::Outgoing { .. } => Err(ProtocolError::Logic(
                 "Outgoing packet received when processing incoming packets".to_string(),
@@ -68,6 +69,8 @@ pub struct OutgoingPacket {
     pub next_hop: PeerId,
     pub ack_challenge: HalfKeyChallenge,
     pub data: Box<[u8]>,
+    /// Value of the ticket attached to the packet for the next hop.
+    pub ticket_value: Balance,
 }
 
 impl TryFrom<TransportPacketWithChainData> for OutgoingPacket {
@@ -82,10 +85,12 @@ impl TryFrom<TransportPacketWithChainData> for OutgoingPacket {
                 next_hop,
                 ack_challenge,
                 data,
+                ticket_value,
             } => Ok(OutgoingPacket {
                 next_hop: next_hop.into(),
                 ack_challenge,
                 data,
+                ticket_value,
             }),
         }
     }
@@ -106,7 +111,7 @@ pub enum TransportPacket {
         previous_hop: PeerId,
         next_hop: PeerId,
         data: Box<[u8]>,
-        ack: Acknowledgement,
+        ack_key: HalfKey,
     },
     /// Packet that is being sent out by us
     Outgoing {
@@ -137,13 +142,13 @@ impl From<IncomingPacket> for TransportPacket {
                 previous_hop,
                 next_hop,
                 data,
-                ack,
+                ack_key,
             } => TransportPacket::Forwarded {
                 packet_tag,
                 previous_hop,
                 next_hop,
                 data,
-                ack,
+                ack_key,
             },
         }
     }
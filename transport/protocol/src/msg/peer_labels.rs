@@ -0,0 +1,231 @@
+//! Cardinality control of the peer labels used in the per-peer packet metrics.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use hopr_transport_identity::PeerId;
+
+use super::config::PeerMetricLabels;
+
+/// Label used for all peers not labelled individually.
+pub const OTHER_PEERS_LABEL: &str = "other";
+
+/// Space-saving heavy hitters sketch (Metwally et al.) with `O(1)` updates.
+///
+/// The counters are kept in a vector sorted in ascending order and grouped into buckets of equal count,
+/// so that incrementing a counter only swaps it with the last element of its bucket.
+/// Unused slots are kept at the beginning of the vector with the count of 0.
+#[derive(Debug)]
+pub struct SpaceSavingSketch<K> {
+    counters: Vec<(Option<K>, u64)>,
+    positions: HashMap<K, usize>,
+    buckets: HashMap<u64, (usize, usize)>,
+}
+
+impl<K: Eq + Hash + Clone> SpaceSavingSketch<K> {
+    /// Creates a sketch monitoring at most `capacity` (at least 1) items.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            counters: vec![(None, 0); capacity],
+            positions: HashMap::with_capacity(capacity),
+            buckets: HashMap::from([(0, (0, capacity - 1))]),
+        }
+    }
+
+    /// Maximum number of monitored items.
+    pub fn capacity(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Records an occurrence of the `item`, replacing the least frequent monitored item if needed.
+    ///
+    /// Returns the rank of the `item` among the monitored items (0 being the most frequent).
+    pub fn observe(&mut self, item: &K) -> usize {
+        let position = match self.positions.get(item) {
+            Some(position) => *position,
+            None => {
+                // The first counter is always one of the smallest, if not an unused one
+                if let Some(evicted) = self.counters[0].0.replace(item.clone()) {
+                    self.positions.remove(&evicted);
+                }
+                self.positions.insert(item.clone(), 0);
+                0
+            }
+        };
+
+        self.counters.len() - 1 - self.increment(position)
+    }
+
+    /// Estimated count of the `item` (an upper bound), if it is monitored.
+    pub fn estimate(&self, item: &K) -> Option<u64> {
+        self.positions.get(item).map(|position| self.counters[*position].1)
+    }
+
+    fn increment(&mut self, position: usize) -> usize {
+        let count = self.counters[position].1;
+        let (start, end) = self.buckets[&count];
+
+        // Move the counter to the end of its bucket, where it becomes the start of the next bucket
+        if position != end {
+            self.counters.swap(position, end);
+            for moved in [position, end] {
+                if let Some(key) = &self.counters[moved].0 {
+                    self.positions.insert(key.clone(), moved);
+                }
+            }
+        }
+
+        if start == end {
+            self.buckets.remove(&count);
+        } else {
+            self.buckets.insert(count, (start, end - 1));
+        }
+
+        self.counters[end].1 += 1;
+        let next_end = self.buckets.get(&(count + 1)).map_or(end, |(_, next_end)| *next_end);
+        self.buckets.insert(count + 1, (end, next_end));
+
+        end
+    }
+}
+
+#[derive(Debug)]
+struct TopPeers {
+    n: usize,
+    sketch: SpaceSavingSketch<PeerId>,
+    labelled: HashSet<PeerId>,
+}
+
+/// Assigns peer label values for the per-peer packet metrics according to [`PeerMetricLabels`].
+///
+/// In the [`PeerMetricLabels::TopN`] mode, the peer activity is tracked using the [`SpaceSavingSketch`]
+/// and a peer gets its own label once it ranks among the N most active peers. Because metric series cannot be
+/// removed once created, a peer keeps its label afterwards and at most N peers are ever labelled individually,
+/// all others are reported under the [`OTHER_PEERS_LABEL`].
+#[derive(Debug, Clone)]
+pub struct PeerMetricLabeler {
+    top: Option<Arc<Mutex<TopPeers>>>,
+    mode: PeerMetricLabels,
+}
+
+impl PeerMetricLabeler {
+    /// Size of the sketch relative to the number of labelled peers, improving the accuracy of the ranking.
+    const SKETCH_CAPACITY_FACTOR: usize = 4;
+
+    pub fn new(mode: PeerMetricLabels) -> Self {
+        let top = match mode {
+            PeerMetricLabels::TopN(n) => Some(Arc::new(Mutex::new(TopPeers {
+                n,
+                sketch: SpaceSavingSketch::new(n.saturating_mul(Self::SKETCH_CAPACITY_FACTOR)),
+                labelled: HashSet::with_capacity(n),
+            }))),
+            PeerMetricLabels::Off | PeerMetricLabels::Full => None,
+        };
+
+        Self { top, mode }
+    }
+
+    /// Records the activity of the `peer` and returns the label value to use, or `None` if the metric is off.
+    pub fn label(&self, peer: &PeerId) -> Option<String> {
+        match self.mode {
+            PeerMetricLabels::Off => None,
+            PeerMetricLabels::Full => Some(peer.to_string()),
+            PeerMetricLabels::TopN(_) => {
+                let mut top = self.top.as_ref()?.lock().unwrap_or_else(|e| e.into_inner());
+                let rank = top.sketch.observe(peer);
+
+                if top.labelled.contains(peer) || (top.labelled.len() < top.n && rank < top.n) {
+                    top.labelled.insert(*peer);
+                    Some(peer.to_string())
+                } else {
+                    Some(OTHER_PEERS_LABEL.into())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_saving_sketch_should_find_heavy_hitters() {
+        let mut sketch = SpaceSavingSketch::new(10);
+
+        for i in 0..10_000u64 {
+            // Items 0, 1 and 2 are heavy hitters, the rest is noise
+            let item = if i % 2 == 0 { i % 3 } else { 100 + i };
+            sketch.observe(&item);
+        }
+
+        for heavy in 0..3 {
+            assert!(
+                sketch.estimate(&heavy).is_some_and(|count| count >= 1666),
+                "heavy hitter {heavy} must be monitored"
+            );
+        }
+        let ranks = [0, 1, 2].map(|heavy| sketch.observe(&heavy));
+        assert!(
+            ranks.iter().all(|rank| *rank < 3),
+            "heavy hitters must be ranked first: {ranks:?}"
+        );
+    }
+
+    #[test]
+    fn space_saving_sketch_should_keep_counters_sorted() {
+        let mut sketch = SpaceSavingSketch::new(5);
+
+        for item in [1, 2, 2, 3, 3, 3, 4, 5, 6, 3, 2] {
+            sketch.observe(&item);
+        }
+
+        assert!(sketch.counters.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(Some(4), sketch.estimate(&3));
+        assert_eq!(Some(3), sketch.estimate(&2));
+        assert_eq!(sketch.capacity(), sketch.positions.len());
+    }
+
+    #[test]
+    fn peer_metric_labeler_should_keep_label_set_bounded_with_many_peers() {
+        const TOP_N: usize = 50;
+        let labeler = PeerMetricLabeler::new(PeerMetricLabels::TopN(TOP_N));
+
+        let peers = (0..1000).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let mut labels = HashSet::new();
+
+        for round in 0..20 {
+            for (i, peer) in peers.iter().enumerate() {
+                // The first peers are much more active than the rest
+                let activity = if i < TOP_N { 5 } else { (round % 2 == 0) as usize };
+                for _ in 0..activity {
+                    labels.insert(labeler.label(peer).expect("metric must be on"));
+                }
+            }
+        }
+
+        assert!(
+            labels.len() <= TOP_N + 1,
+            "label set must stay bounded: {}",
+            labels.len()
+        );
+        assert!(labels.contains(OTHER_PEERS_LABEL));
+        assert!(
+            peers[..TOP_N].iter().all(|peer| labels.contains(&peer.to_string())),
+            "most active peers must be labelled"
+        );
+    }
+
+    #[test]
+    fn peer_metric_labeler_should_respect_off_and_full_modes() {
+        let peer = PeerId::random();
+
+        assert_eq!(None, PeerMetricLabeler::new(PeerMetricLabels::Off).label(&peer));
+        assert_eq!(
+            Some(peer.to_string()),
+            PeerMetricLabeler::new(PeerMetricLabels::Full).label(&peer)
+        );
+    }
+}
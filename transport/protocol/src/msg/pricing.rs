@@ -0,0 +1,136 @@
+use std::sync::{Arc, RwLock};
+
+use hopr_primitive_types::prelude::Balance;
+
+use crate::errors::{ProtocolError, Result};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::SimpleGauge;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    pub(crate) static ref METRIC_PRICE_EPOCH: SimpleGauge = SimpleGauge::new(
+        "hopr_price_epoch",
+        "Current price epoch of the outgoing packets (0 before the first pricing update)"
+    )
+    .unwrap();
+    pub(crate) static ref METRIC_PACKETS_IN_PRICE_EPOCH: SimpleGauge = SimpleGauge::new(
+        "hopr_packets_in_price_epoch",
+        "Number of outgoing packets wrapped in the current price epoch"
+    )
+    .unwrap();
+}
+
+/// Ticket price and winning probability printed on the tickets of the outgoing packets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacketPricing {
+    ticket_price: Balance,
+    winning_probability: f64,
+}
+
+impl PacketPricing {
+    /// Creates the pricing, rejecting a zero ticket price and a winning probability outside `(0, 1]`.
+    pub fn new(ticket_price: Balance, winning_probability: f64) -> Result<Self> {
+        if ticket_price.is_zero() {
+            return Err(ProtocolError::InvalidPricing("ticket price must be positive".into()));
+        }
+
+        if !(winning_probability > 0.0 && winning_probability <= 1.0) {
+            return Err(ProtocolError::InvalidPricing(format!(
+                "winning probability {winning_probability} must be in (0, 1]"
+            )));
+        }
+
+        Ok(Self {
+            ticket_price,
+            winning_probability,
+        })
+    }
+
+    /// Price of a single ticket.
+    pub fn ticket_price(&self) -> Balance {
+        self.ticket_price
+    }
+
+    /// Winning probability of a single ticket.
+    pub fn winning_probability(&self) -> f64 {
+        self.winning_probability
+    }
+}
+
+/// Handle updating the [`PacketPricing`] of the outgoing packets while the transport is running,
+/// e.g. on the price oracle updates.
+///
+/// Each update starts a new price epoch, numbered from 1. The pricing is read as a whole for each
+/// packet, so that a packet never mixes the price and the winning probability of different epochs.
+/// Before the first update, the packets are priced by the configured or the network values.
+///
+/// All the clones of the updater share the same pricing.
+#[derive(Debug, Clone, Default)]
+pub struct PricingUpdater {
+    current: Arc<RwLock<Option<(u64, PacketPricing)>>>,
+}
+
+impl PricingUpdater {
+    /// Sets the `pricing` of all the following packets and returns its epoch.
+    pub fn update(&self, pricing: PacketPricing) -> u64 {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let epoch = current.map(|(epoch, _)| epoch).unwrap_or_default() + 1;
+        *current = Some((epoch, pricing));
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        {
+            METRIC_PRICE_EPOCH.set(epoch as f64);
+            METRIC_PACKETS_IN_PRICE_EPOCH.set(0.0);
+        }
+
+        tracing::info!(
+            epoch,
+            ticket_price = %pricing.ticket_price,
+            winning_probability = pricing.winning_probability,
+            "outgoing packet pricing updated"
+        );
+        epoch
+    }
+
+    /// Current pricing together with its epoch, if it has been set.
+    pub fn current(&self) -> Option<(u64, PacketPricing)> {
+        *self.current.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_primitive_types::prelude::BalanceType;
+
+    #[test]
+    fn packet_pricing_should_reject_invalid_values() {
+        let price = Balance::new(10_u32, BalanceType::HOPR);
+
+        assert!(PacketPricing::new(price, 0.5).is_ok());
+        assert!(PacketPricing::new(price, 1.0).is_ok());
+        assert!(PacketPricing::new(Balance::zero(BalanceType::HOPR), 0.5).is_err());
+        for win_prob in [0.0, -0.5, 1.5, f64::NAN] {
+            assert!(
+                PacketPricing::new(price, win_prob).is_err(),
+                "{win_prob} must be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn pricing_updater_should_number_the_epochs_across_clones() -> anyhow::Result<()> {
+        let updater = PricingUpdater::default();
+        assert_eq!(None, updater.current());
+
+        let first = PacketPricing::new(Balance::new(10_u32, BalanceType::HOPR), 0.5)?;
+        let second = PacketPricing::new(Balance::new(20_u32, BalanceType::HOPR), 0.25)?;
+
+        assert_eq!(1, updater.update(first));
+        assert_eq!(2, updater.clone().update(second));
+        assert_eq!(Some((2, second)), updater.current());
+
+        Ok(())
+    }
+}
@@ -1,11 +1,14 @@
-use futures::{future::Either, SinkExt};
+use futures::{future::Either, FutureExt, SinkExt};
 use futures::{pin_mut, Sink};
 use hopr_crypto_packet::errors::PacketError;
 use hopr_db_api::protocol::TransportPacketWithChainData;
 use hopr_transport_identity::PeerId;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr, TimestampMilliSeconds};
 use tracing::error;
 
-use hopr_async_runtime::prelude::sleep;
+use hopr_async_runtime::clock::{Clock, RealClock};
+use hopr_async_runtime::prelude::timeout_fut;
 use hopr_crypto_packet::errors::{
     PacketError::{TagReplay, TransportError},
     Result,
@@ -14,10 +17,15 @@ use hopr_crypto_types::prelude::*;
 use hopr_db_api::prelude::HoprDbProtocolOperations;
 use hopr_internal_types::prelude::*;
 use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_platform::time::native::current_time;
 use hopr_primitive_types::prelude::*;
 
 use super::packet::OutgoingPacket;
+use super::pricing::PricingUpdater;
 use crate::bloom;
+use crate::errors::ProtocolError;
+use crate::retry::{retry_db, DbRetryConfig};
+use crate::stream::Priority;
 
 lazy_static::lazy_static! {
     /// Fixed price per packet to 0.01 HOPR
@@ -36,14 +44,23 @@ pub struct SendPkt {
     pub data: Box<[u8]>,
 }
 
+/// Acknowledgement to be sent to the `peer`, signed later by the [`AckSigner`](crate::ack::signer::AckSigner).
 pub struct SendAck {
     pub peer: PeerId,
-    pub ack: Acknowledgement,
+    pub ack_key: HalfKey,
 }
 
 pub enum RecvOperation {
-    Receive { data: ApplicationData, ack: SendAck },
-    Forward { msg: SendPkt, ack: SendAck },
+    Receive {
+        data: ApplicationData,
+        ack: SendAck,
+    },
+    Forward {
+        msg: SendPkt,
+        ack: SendAck,
+        /// Challenge solved by the acknowledgement of the forwarded packet.
+        ack_challenge: HalfKeyChallenge,
+    },
 }
 
 #[async_trait::async_trait]
@@ -62,6 +79,7 @@ where
     db: Db,
     tbf: bloom::WrappedTagBloomFilter,
     cfg: PacketInteractionConfig,
+    db_retry: DbRetryConfig,
 }
 
 #[async_trait::async_trait]
@@ -73,21 +91,7 @@ where
 
     #[tracing::instrument(level = "trace", skip(self, data))]
     async fn send(&self, data: ApplicationData, routing: ResolvedTransportRouting) -> Result<(PeerId, Box<[u8]>)> {
-        let packet = self
-            .db
-            .to_send(
-                data.to_bytes(),
-                routing,
-                self.determine_actual_outgoing_win_prob().await,
-                self.determine_actual_outgoing_ticket_price().await?,
-            )
-            .await
-            .map_err(|e| PacketError::PacketConstructionError(e.to_string()))?;
-
-        let packet: OutgoingPacket = packet
-            .try_into()
-            .map_err(|e: crate::errors::ProtocolError| PacketError::LogicError(e.to_string()))?;
-
+        let packet = self.wrap(data, routing).await?;
         Ok((packet.next_hop, packet.data))
     }
 }
@@ -104,14 +108,17 @@ where
         let previous_hop = OffchainPublicKey::try_from(peer)
             .map_err(|e| PacketError::LogicError(format!("failed to convert '{peer}' into the public key: {e}")))?;
 
+        let (_, outgoing_win_prob, outgoing_ticket_price) = self.determine_outgoing_pricing().await?;
+
+        // Not retried, since the operation is not idempotent
         let packet = self
             .db
             .from_recv(
                 data,
                 &self.cfg.packet_keypair,
                 previous_hop,
-                self.determine_actual_outgoing_win_prob().await,
-                self.determine_actual_outgoing_ticket_price().await?,
+                outgoing_win_prob,
+                outgoing_ticket_price,
             )
             .await
             .map_err(|e| match e {
@@ -147,7 +154,7 @@ where
                         data: app_data,
                         ack: SendAck {
                             peer: previous_hop.into(),
-                            ack: Acknowledgement::new(ack_key, &self.cfg.packet_keypair),
+                            ack_key,
                         },
                     }
                 } else {
@@ -159,7 +166,8 @@ where
                 previous_hop,
                 next_hop,
                 data,
-                ack,
+                ack_key,
+                ack_challenge,
                 ..
             } => RecvOperation::Forward {
                 msg: SendPkt {
@@ -168,8 +176,9 @@ where
                 },
                 ack: SendAck {
                     peer: previous_hop.into(),
-                    ack,
+                    ack_key,
                 },
+                ack_challenge,
             },
             TransportPacketWithChainData::Outgoing { .. } => {
                 return Err(PacketError::LogicError(
@@ -186,39 +195,87 @@ where
 {
     /// Creates a new instance given the DB and configuration.
     pub fn new(db: Db, tbf: bloom::WrappedTagBloomFilter, cfg: PacketInteractionConfig) -> Self {
-        Self { db, tbf, cfg }
+        Self {
+            db,
+            tbf,
+            cfg,
+            db_retry: DbRetryConfig::default(),
+        }
+    }
+
+    /// Sets the retrying of the idempotent DB reads failing with a transient error.
+    pub fn with_db_retry(mut self, db_retry: DbRetryConfig) -> Self {
+        self.db_retry = db_retry;
+        self
+    }
+
+    /// Wraps the data into an outgoing packet, creating a new ticket for it.
+    pub async fn wrap(&self, data: ApplicationData, routing: ResolvedTransportRouting) -> Result<OutgoingPacket> {
+        let (_epoch, outgoing_win_prob, outgoing_ticket_price) = self.determine_outgoing_pricing().await?;
+
+        // Not retried, since the operation is not idempotent
+        let packet = self
+            .db
+            .to_send(data.to_bytes(), routing, outgoing_win_prob, outgoing_ticket_price)
+            .await
+            .map_err(|e| PacketError::PacketConstructionError(e.to_string()))?;
+
+        // The packets priced before an update are not counted towards the new epoch
+        #[cfg(all(feature = "prometheus", not(test)))]
+        if crate::msg::pricing::METRIC_PRICE_EPOCH.get() as u64 == _epoch {
+            crate::msg::pricing::METRIC_PACKETS_IN_PRICE_EPOCH.increment(1.0);
+        }
+
+        packet
+            .try_into()
+            .map_err(|e: crate::errors::ProtocolError| PacketError::LogicError(e.to_string()))
     }
 
     #[tracing::instrument(level = "trace", name = "check_tag_replay", skip(self, tag))]
     /// Check whether the packet is replayed using a packet tag.
     ///
     /// There is a 0.1% chance that the positive result is not a replay because a Bloom filter is used.
+    /// If the filter keeps a write-ahead log, a new tag is returned only once it has been synced to disk,
+    /// so that the packet cannot be replayed after a crash.
     pub async fn is_tag_replay(&self, tag: &PacketTag) -> bool {
-        self.tbf
-            .with_write_lock(|inner: &mut TagBloomFilter| inner.check_and_set(tag))
-            .await
+        self.tbf.check_and_set_durably(tag).await
+    }
+
+    /// Price epoch, winning probability and ticket price of the next packet.
+    ///
+    /// The pricing set via the [`PricingUpdater`] takes precedence over the configured and the network values,
+    /// which are used in the epoch 0 before the first update.
+    async fn determine_outgoing_pricing(&self) -> Result<(u64, f64, Balance)> {
+        if let Some((epoch, pricing)) = self.cfg.pricing.current() {
+            return Ok((epoch, pricing.winning_probability(), pricing.ticket_price()));
+        }
+
+        let outgoing_win_prob = self.determine_actual_outgoing_win_prob().await;
+        let outgoing_ticket_price = self.determine_actual_outgoing_ticket_price().await?;
+        Ok((0, outgoing_win_prob, outgoing_ticket_price))
     }
 
     // NOTE: as opposed to the winning probability, the ticket price does not have
     // a reasonable default and therefore the operation fails
     async fn determine_actual_outgoing_ticket_price(&self) -> Result<Balance> {
         // This operation hits the cache unless the new value is fetched for the first time
-        let network_ticket_price =
-            self.db.get_network_ticket_price().await.map_err(|e| {
-                PacketError::LogicError(format!("failed to determine current network ticket price: {e}"))
-            })?;
+        let network_ticket_price = retry_db(&self.db_retry, "get_network_ticket_price", || {
+            self.db.get_network_ticket_price()
+        })
+        .await
+        .map_err(|e| PacketError::LogicError(format!("failed to determine current network ticket price: {e}")))?;
 
         Ok(self.cfg.outgoing_ticket_price.unwrap_or(network_ticket_price))
     }
 
     async fn determine_actual_outgoing_win_prob(&self) -> f64 {
         // This operation hits the cache unless the new value is fetched for the first time
-        let network_win_prob = self
-            .db
-            .get_network_winning_probability()
-            .await
-            .inspect_err(|error| error!(%error, "failed to determine current network winning probability"))
-            .ok();
+        let network_win_prob = retry_db(&self.db_retry, "get_network_winning_probability", || {
+            self.db.get_network_winning_probability()
+        })
+        .await
+        .inspect_err(|error| error!(%error, "failed to determine current network winning probability"))
+        .ok();
 
         // If no explicit winning probability is configured, use the network value
         // or 1 if the network value was not determined.
@@ -231,6 +288,69 @@ where
     }
 }
 
+/// Blocking entry points for driving single packets through the processor, e.g. from `criterion` benchmarks,
+/// without building the stream pipeline of [`run_msg_ack_protocol`](crate::run_msg_ack_protocol).
+///
+/// The methods block the current thread and must not be called from within an async context.
+#[cfg(feature = "bench")]
+impl<Db> PacketProcessor<Db>
+where
+    Db: HoprDbProtocolOperations + Send + Sync + std::fmt::Debug + Clone,
+{
+    /// Blocking variant of [`PacketWrapping::send`].
+    pub fn send_blocking(
+        &self,
+        data: ApplicationData,
+        routing: ResolvedTransportRouting,
+    ) -> Result<(PeerId, Box<[u8]>)> {
+        futures::executor::block_on(PacketWrapping::send(self, data, routing))
+    }
+
+    /// Blocking variant of [`PacketUnwrapping::recv`].
+    pub fn recv_blocking(&self, peer: &PeerId, data: Box<[u8]>) -> Result<RecvOperation> {
+        futures::executor::block_on(PacketUnwrapping::recv(self, peer, data))
+    }
+}
+
+/// Details of a packet sent out by the pipeline, delivered on its successful finalization.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendReceipt {
+    /// Peer the packet was sent to.
+    #[serde_as(as = "DisplayFromStr")]
+    pub next_hop: PeerId,
+    /// Value of the ticket attached to the packet.
+    #[serde_as(as = "DisplayFromStr")]
+    pub ticket_value: Balance,
+    /// Size of the packet on the wire in bytes.
+    pub packet_size: usize,
+    /// Time when the packet was wrapped.
+    ///
+    /// The packet is handed over to the wire only afterward, even if the send is
+    /// [finalized after the wire send](crate::msg::config::MsgProtocolConfig::finalize_after_wire_send).
+    #[serde_as(as = "TimestampMilliSeconds<i64>")]
+    pub timestamp: std::time::SystemTime,
+}
+
+impl From<&OutgoingPacket> for SendReceipt {
+    fn from(value: &OutgoingPacket) -> Self {
+        Self {
+            next_hop: value.next_hop,
+            ticket_value: value.ticket_value,
+            packet_size: value.data.len(),
+            timestamp: current_time(),
+        }
+    }
+}
+
+type ReceiptResult = std::result::Result<Option<SendReceipt>, ProtocolError>;
+
+#[derive(Debug)]
+enum FinalizerTx {
+    Unit(futures::channel::oneshot::Sender<crate::errors::Result<()>>),
+    Receipt(futures::channel::oneshot::Sender<ReceiptResult>),
+}
+
 /// Packet send finalizer notifying the awaiting future once the send has been acknowledged.
 ///
 /// This is a remnant of the original logic that assumed that the p2p transport is invokable
@@ -239,51 +359,157 @@ where
 /// architectural overhaul of the hopr daemon.
 #[derive(Debug)]
 pub struct PacketSendFinalizer {
-    tx: futures::channel::oneshot::Sender<std::result::Result<(), PacketError>>,
+    tx: FinalizerTx,
 }
 
 impl PacketSendFinalizer {
-    pub fn finalize(self, result: std::result::Result<(), PacketError>) {
-        if self.tx.send(result).is_err() {
+    pub fn finalize(self, result: crate::errors::Result<()>) {
+        self.notify(result.map(|_| None))
+    }
+
+    /// Same as [`PacketSendFinalizer::finalize`], but additionally delivers the [`SendReceipt`]
+    /// of a successfully sent packet, if the awaiter [waits for it](PacketSendAwaiter::wait_for_receipt).
+    pub fn finalize_with_receipt(self, result: crate::errors::Result<SendReceipt>) {
+        self.notify(result.map(Some))
+    }
+
+    fn notify(self, result: ReceiptResult) {
+        let sent = match self.tx {
+            FinalizerTx::Unit(tx) => tx.send(result.map(|_| ())).is_ok(),
+            FinalizerTx::Receipt(tx) => tx.send(result).is_ok(),
+        };
+        if !sent {
             error!("Failed to notify the awaiter about the successful packet transmission")
         }
     }
 }
 
-impl From<futures::channel::oneshot::Sender<std::result::Result<(), PacketError>>> for PacketSendFinalizer {
-    fn from(value: futures::channel::oneshot::Sender<std::result::Result<(), PacketError>>) -> Self {
-        Self { tx: value }
+impl From<futures::channel::oneshot::Sender<crate::errors::Result<()>>> for PacketSendFinalizer {
+    fn from(value: futures::channel::oneshot::Sender<crate::errors::Result<()>>) -> Self {
+        Self {
+            tx: FinalizerTx::Unit(value),
+        }
+    }
+}
+
+impl From<futures::channel::oneshot::Sender<ReceiptResult>> for PacketSendFinalizer {
+    fn from(value: futures::channel::oneshot::Sender<ReceiptResult>) -> Self {
+        Self {
+            tx: FinalizerTx::Receipt(value),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum AwaiterRx {
+    Unit(futures::channel::oneshot::Receiver<crate::errors::Result<()>>),
+    Receipt(futures::channel::oneshot::Receiver<ReceiptResult>),
+}
+
+impl AwaiterRx {
+    fn into_future(
+        self,
+    ) -> futures::future::BoxFuture<'static, std::result::Result<ReceiptResult, futures::channel::oneshot::Canceled>>
+    {
+        match self {
+            AwaiterRx::Unit(rx) => rx.map(|r| r.map(|r| r.map(|_| None))).boxed(),
+            AwaiterRx::Receipt(rx) => rx.boxed(),
+        }
     }
 }
 
 /// Await on future until the confirmation of packet reception is received
 #[derive(Debug)]
 pub struct PacketSendAwaiter {
-    rx: futures::channel::oneshot::Receiver<std::result::Result<(), PacketError>>,
+    rx: AwaiterRx,
+    deadline: Option<std::time::Instant>,
 }
 
-impl From<futures::channel::oneshot::Receiver<std::result::Result<(), PacketError>>> for PacketSendAwaiter {
-    fn from(value: futures::channel::oneshot::Receiver<std::result::Result<(), PacketError>>) -> Self {
-        Self { rx: value }
+impl From<futures::channel::oneshot::Receiver<crate::errors::Result<()>>> for PacketSendAwaiter {
+    fn from(value: futures::channel::oneshot::Receiver<crate::errors::Result<()>>) -> Self {
+        Self {
+            rx: AwaiterRx::Unit(value),
+            deadline: None,
+        }
+    }
+}
+
+impl From<futures::channel::oneshot::Receiver<ReceiptResult>> for PacketSendAwaiter {
+    fn from(value: futures::channel::oneshot::Receiver<ReceiptResult>) -> Self {
+        Self {
+            rx: AwaiterRx::Receipt(value),
+            deadline: None,
+        }
     }
 }
 
 impl PacketSendAwaiter {
+    /// Waits until the packet is sent, at most until the finalizer timeout of the [`MsgSender`] that sent it elapses.
+    ///
+    /// Without a finalizer timeout, waits until the packet is sent or dropped by the pipeline.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn wait(self) -> crate::errors::Result<()> {
+        self.receive().await.map(|_| ())
+    }
+
+    /// Same as [`PacketSendAwaiter::wait`], but returns the [`SendReceipt`] of the sent packet.
+    ///
+    /// Fails if the send was finalized without a receipt.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub async fn consume_and_wait(self, until_timeout: std::time::Duration) -> Result<()> {
-        let timeout = sleep(until_timeout);
-        let rx = self.rx;
+    pub async fn wait_for_receipt(self) -> crate::errors::Result<SendReceipt> {
+        self.receive()
+            .await?
+            .ok_or_else(|| ProtocolError::TransportError("Packet was sent without a receipt".to_owned()))
+    }
+
+    async fn receive(self) -> crate::errors::Result<Option<SendReceipt>> {
+        match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                self.receive_with_clock(&RealClock, remaining).await
+            }
+            None => match self.rx.into_future().await {
+                Ok(Ok(v)) => Ok(v),
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(ProtocolError::TransportError("Canceled".to_owned())),
+            },
+        }
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn consume_and_wait(self, until_timeout: std::time::Duration) -> crate::errors::Result<()> {
+        self.consume_and_wait_with_clock(&RealClock, until_timeout).await
+    }
+
+    /// Same as [`PacketSendAwaiter::consume_and_wait`], but the timeout is measured using the given `clock`.
+    #[tracing::instrument(level = "trace", skip(self, clock))]
+    pub async fn consume_and_wait_with_clock<C: Clock>(
+        self,
+        clock: &C,
+        until_timeout: std::time::Duration,
+    ) -> crate::errors::Result<()> {
+        self.receive_with_clock(clock, until_timeout).await.map(|_| ())
+    }
+
+    async fn receive_with_clock<C: Clock>(
+        self,
+        clock: &C,
+        until_timeout: std::time::Duration,
+    ) -> crate::errors::Result<Option<SendReceipt>> {
+        let timeout = clock.sleep(until_timeout);
+        let rx = self.rx.into_future();
         pin_mut!(rx, timeout);
         match futures::future::select(rx, timeout).await {
             Either::Left((Ok(Ok(v)), _)) => Ok(v),
-            Either::Left((Ok(Err(e)), _)) => Err(TransportError(e.to_string())),
-            Either::Left((Err(_), _)) => Err(TransportError("Canceled".to_owned())),
-            Either::Right(_) => Err(TransportError("Timed out on sending a packet".to_owned())),
+            Either::Left((Ok(Err(e)), _)) => Err(e),
+            Either::Left((Err(_), _)) => Err(ProtocolError::TransportError("Canceled".to_owned())),
+            Either::Right(_) => Err(ProtocolError::Timeout),
         }
     }
 }
 
-pub type SendMsgInput = (ApplicationData, ResolvedTransportRouting, PacketSendFinalizer);
+/// Packet to be sent along with its routing, the finalizer of the send and its [Priority] in the outgoing queue.
+pub type SendMsgInput = (ApplicationData, ResolvedTransportRouting, PacketSendFinalizer, Priority);
 
 #[derive(Debug, Clone)]
 pub struct MsgSender<T>
@@ -291,6 +517,7 @@ where
     T: Sink<SendMsgInput> + Send + Sync + Clone + 'static + std::marker::Unpin,
 {
     tx: T,
+    finalizer_timeout: Option<std::time::Duration>,
 }
 
 impl<T> MsgSender<T>
@@ -298,26 +525,59 @@ where
     T: Sink<SendMsgInput> + Send + Sync + Clone + 'static + std::marker::Unpin,
 {
     pub fn new(tx: T) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            finalizer_timeout: None,
+        }
     }
 
-    /// Pushes a new packet into processing.
-    #[tracing::instrument(level = "trace", skip(self, data))]
+    /// Bounds the time a packet send may take until it is finalized.
+    ///
+    /// The timeout covers both pushing the packet into a possibly full pipeline and
+    /// [waiting](PacketSendAwaiter::wait) for the send to be finalized by it.
+    pub fn with_finalizer_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.finalizer_timeout = Some(timeout);
+        self
+    }
+
+    /// Pushes a new packet into processing with the [normal](Priority::Normal) priority.
     pub async fn send_packet(
         &self,
         data: ApplicationData,
         routing: ResolvedTransportRouting,
     ) -> Result<PacketSendAwaiter> {
-        let (tx, rx) = futures::channel::oneshot::channel::<std::result::Result<(), PacketError>>();
+        self.send_packet_with_priority(data, routing, Priority::Normal).await
+    }
 
-        self.tx
-            .clone()
-            .send((data, routing, tx.into()))
-            .await
-            .map_err(|_| TransportError("Failed to send a message".into()))
-            .map(move |_| {
-                let awaiter: PacketSendAwaiter = rx.into();
-                awaiter
+    /// Pushes a new packet into processing with the given `priority`.
+    ///
+    /// Packets with the [high](Priority::High) priority are sent ahead of the normal ones
+    /// waiting to be sent, see [PriorityScheduler](crate::stream::PriorityScheduler).
+    #[tracing::instrument(level = "trace", skip(self, data))]
+    pub async fn send_packet_with_priority(
+        &self,
+        data: ApplicationData,
+        routing: ResolvedTransportRouting,
+        priority: Priority,
+    ) -> Result<PacketSendAwaiter> {
+        let (tx, rx) = futures::channel::oneshot::channel::<ReceiptResult>();
+        let deadline = self
+            .finalizer_timeout
+            .map(|timeout| std::time::Instant::now() + timeout);
+
+        let mut sink = self.tx.clone();
+        let send = sink.send((data, routing, tx.into(), priority));
+        let sent = match self.finalizer_timeout {
+            Some(timeout) => timeout_fut(timeout, send)
+                .await
+                .map_err(|_| TransportError("Timed out on sending a packet".to_owned()))?,
+            None => send.await,
+        };
+
+        sent.map_err(|_| TransportError("Failed to send a message".into()))
+            .map(move |_| PacketSendAwaiter {
+                rx: AwaiterRx::Receipt(rx),
+                deadline,
             })
     }
 }
@@ -329,6 +589,13 @@ pub struct PacketInteractionConfig {
     pub chain_keypair: ChainKeypair,
     pub outgoing_ticket_win_prob: Option<f64>,
     pub outgoing_ticket_price: Option<Balance>,
+    /// If set, packets whose acknowledgement did not arrive within this duration are re-sent.
+    pub resend_unacked_after: Option<std::time::Duration>,
+    /// Maximum number of re-sends of a single packet before giving up.
+    pub max_resends: u8,
+    /// Pricing of the outgoing packets updated at runtime, superseding the outgoing ticket price
+    /// and winning probability once set.
+    pub pricing: PricingUpdater,
 }
 
 impl PacketInteractionConfig {
@@ -343,6 +610,9 @@ impl PacketInteractionConfig {
             chain_keypair: chain_keypair.clone(),
             outgoing_ticket_win_prob,
             outgoing_ticket_price,
+            resend_unacked_after: None,
+            max_resends: 0,
+            pricing: PricingUpdater::default(),
         }
     }
 }
@@ -354,6 +624,7 @@ mod tests {
     use anyhow::Context;
     use async_std::future::timeout;
     use futures::StreamExt;
+    use hopr_async_runtime::clock::MockClock;
     use hopr_crypto_random::Randomizable;
     use hopr_internal_types::prelude::HoprPseudonym;
     use hopr_path::ValidatedPath;
@@ -361,7 +632,7 @@ mod tests {
 
     #[async_std::test]
     pub async fn packet_send_finalizer_is_triggered() {
-        let (tx, rx) = futures::channel::oneshot::channel::<std::result::Result<(), PacketError>>();
+        let (tx, rx) = futures::channel::oneshot::channel::<crate::errors::Result<()>>();
 
         let finalizer: PacketSendFinalizer = tx.into();
         let awaiter: PacketSendAwaiter = rx.into();
@@ -403,17 +674,277 @@ mod tests {
         assert_eq!(data, expected_data);
         assert!(matches!(path, ResolvedTransportRouting::Forward { forward_path,.. } if forward_path == expected_path));
 
-        async_std::task::spawn(async move {
-            async_std::task::sleep(Duration::from_millis(3)).await;
-            finalizer.finalize(Ok(()))
-        });
+        let clock = MockClock::default();
+        let awaiter = result.context("Awaiter must be present")?;
+        let (result, _) = futures::join!(
+            awaiter.consume_and_wait_with_clock(&clock, Duration::from_millis(10)),
+            async {
+                clock.advance(Duration::from_millis(3));
+                finalizer.finalize(Ok(()))
+            }
+        );
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    pub async fn message_sender_should_time_out_when_the_send_is_not_finalized() -> anyhow::Result<()> {
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let sender = MsgSender::new(tx).with_finalizer_timeout(Duration::from_millis(50));
+
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *OffchainKeypair::random().public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+        let awaiter = sender
+            .send_packet(ApplicationData::from_bytes(&[0x01])?, routing)
+            .await?;
 
-        assert!(result
-            .context("Awaiter must be present")?
-            .consume_and_wait(Duration::from_millis(10))
+        // The pipeline holds on to the finalizer without ever finalizing the send
+        let _stuck = rx.next().await.context("value should be present")?;
+
+        let result = timeout(Duration::from_secs(1), awaiter.wait())
             .await
-            .is_ok());
+            .context("awaiter must not wait beyond the finalizer timeout")?;
+        assert!(matches!(result, Err(ProtocolError::Timeout)));
 
         Ok(())
     }
+
+    #[async_std::test]
+    pub async fn packet_send_awaiter_without_timeout_should_wait_for_the_finalizer() {
+        let (tx, rx) = futures::channel::oneshot::channel::<crate::errors::Result<()>>();
+
+        let finalizer: PacketSendFinalizer = tx.into();
+        let awaiter: PacketSendAwaiter = rx.into();
+
+        let (result, _) = futures::join!(awaiter.wait(), async { finalizer.finalize(Ok(())) });
+        assert!(result.is_ok());
+    }
+
+    /// Wraps all outgoing packets with a ticket of a fixed value,
+    /// capturing the winning probability and the ticket price requested for each packet.
+    #[derive(Debug, Clone)]
+    struct FixedTicketDb {
+        next_hop: OffchainPublicKey,
+        ticket_value: Balance,
+        captured: std::sync::Arc<std::sync::Mutex<Vec<(f64, Balance)>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl HoprDbProtocolOperations for FixedTicketDb {
+        async fn handle_acknowledgement(
+            &self,
+            _: Acknowledgement,
+        ) -> hopr_db_api::errors::Result<hopr_db_api::protocol::AckResult> {
+            unimplemented!()
+        }
+
+        async fn get_network_winning_probability(&self) -> hopr_db_api::errors::Result<f64> {
+            Ok(1.0)
+        }
+
+        async fn get_network_ticket_price(&self) -> hopr_db_api::errors::Result<Balance> {
+            Ok(self.ticket_value)
+        }
+
+        async fn to_send_no_ack(
+            &self,
+            _: Box<[u8]>,
+            _: OffchainPublicKey,
+        ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+            unimplemented!()
+        }
+
+        async fn to_send(
+            &self,
+            data: Box<[u8]>,
+            _: ResolvedTransportRouting,
+            outgoing_ticket_win_prob: f64,
+            outgoing_ticket_price: Balance,
+        ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+            self.captured
+                .lock()
+                .unwrap()
+                .push((outgoing_ticket_win_prob, outgoing_ticket_price));
+            Ok(TransportPacketWithChainData::Outgoing {
+                next_hop: self.next_hop,
+                ack_challenge: HalfKey::random().to_challenge(),
+                data,
+                ticket_value: self.ticket_value,
+            })
+        }
+
+        async fn from_recv(
+            &self,
+            _: Box<[u8]>,
+            _: &OffchainKeypair,
+            _: OffchainPublicKey,
+            _: f64,
+            _: Balance,
+        ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+            unimplemented!()
+        }
+    }
+
+    #[async_std::test]
+    pub async fn message_sender_should_deliver_send_receipt() -> anyhow::Result<()> {
+        let next_hop = OffchainKeypair::random();
+        let ticket_value = Balance::new(42_u32, BalanceType::HOPR);
+        let dir = tempfile::tempdir()?;
+        let processor = PacketProcessor::new(
+            FixedTicketDb {
+                next_hop: *next_hop.public(),
+                ticket_value,
+                captured: Default::default(),
+            },
+            bloom::WrappedTagBloomFilter::new(dir.path().join("tbf").to_string_lossy().into_owned()),
+            PacketInteractionConfig::new(&OffchainKeypair::random(), &ChainKeypair::random(), None, None),
+        );
+
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *next_hop.public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+        let awaiter = MsgSender::new(tx)
+            .send_packet(ApplicationData::from_bytes(&[0x01, 0x02, 0x03])?, routing)
+            .await?;
+
+        let (data, routing, finalizer, priority) = rx.next().await.context("value should be present")?;
+        assert_eq!(Priority::Normal, priority);
+        let packet = processor.wrap(data, routing).await?;
+        let expected_size = packet.data.len();
+        finalizer.finalize_with_receipt(Ok(SendReceipt::from(&packet)));
+
+        let receipt = awaiter.wait_for_receipt().await?;
+        assert_eq!(PeerId::from(next_hop.public()), receipt.next_hop);
+        assert_eq!(ticket_value, receipt.ticket_value);
+        assert_eq!(expected_size, receipt.packet_size);
+        assert!(receipt.timestamp <= current_time());
+
+        let serialized = serde_json::to_string(&receipt)?;
+        assert_eq!(receipt, serde_json::from_str::<SendReceipt>(&serialized)?);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    pub async fn packet_processor_should_price_the_packets_by_the_current_pricing() -> anyhow::Result<()> {
+        let next_hop = OffchainKeypair::random();
+        let network_price = Balance::new(42_u32, BalanceType::HOPR);
+        let db = FixedTicketDb {
+            next_hop: *next_hop.public(),
+            ticket_value: network_price,
+            captured: Default::default(),
+        };
+        let cfg = PacketInteractionConfig::new(&OffchainKeypair::random(), &ChainKeypair::random(), None, None);
+        let pricing = cfg.pricing.clone();
+        let dir = tempfile::tempdir()?;
+        let processor = PacketProcessor::new(
+            db.clone(),
+            bloom::WrappedTagBloomFilter::new(dir.path().join("tbf").to_string_lossy().into_owned()),
+            cfg,
+        );
+
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *next_hop.public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+        let wrap = |count: usize| {
+            let (processor, routing) = (processor.clone(), routing.clone());
+            async move {
+                for _ in 0..count {
+                    processor
+                        .wrap(ApplicationData::from_bytes(&[0x01])?, routing.clone())
+                        .await?;
+                }
+                anyhow::Ok(())
+            }
+        };
+
+        wrap(2).await?;
+        let new_price = Balance::new(100_u32, BalanceType::HOPR);
+        assert_eq!(
+            1,
+            pricing.update(crate::msg::pricing::PacketPricing::new(new_price, 0.5)?)
+        );
+        wrap(2).await?;
+
+        assert_eq!(
+            vec![
+                (1.0, network_price),
+                (1.0, network_price),
+                (0.5, new_price),
+                (0.5, new_price)
+            ],
+            *db.captured.lock().unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    pub async fn packet_send_awaiter_should_wait_for_unit_result_and_fail_without_receipt() -> anyhow::Result<()> {
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let sender = MsgSender::new(tx);
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *OffchainKeypair::random().public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+
+        let awaiter = sender
+            .send_packet(ApplicationData::from_bytes(&[0x01])?, routing.clone())
+            .await?;
+        rx.next().await.context("value should be present")?.2.finalize(Ok(()));
+        assert!(awaiter.wait().await.is_ok());
+
+        let awaiter = sender
+            .send_packet(ApplicationData::from_bytes(&[0x01])?, routing)
+            .await?;
+        rx.next().await.context("value should be present")?.2.finalize(Ok(()));
+        assert!(matches!(
+            awaiter.wait_for_receipt().await,
+            Err(ProtocolError::TransportError(_))
+        ));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    pub async fn message_sender_should_pass_the_priority_of_the_packet() -> anyhow::Result<()> {
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let sender = MsgSender::new(tx);
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *OffchainKeypair::random().public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+
+        let _awaiter = sender
+            .send_packet_with_priority(ApplicationData::from_bytes(&[0x01])?, routing.clone(), Priority::High)
+            .await?;
+        assert_eq!(Priority::High, rx.next().await.context("value should be present")?.3);
+
+        let _awaiter = sender
+            .send_packet(ApplicationData::from_bytes(&[0x02])?, routing)
+            .await?;
+        assert_eq!(Priority::Normal, rx.next().await.context("value should be present")?.3);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    pub async fn packet_send_awaiter_should_time_out_after_the_deadline() {
+        let (_tx, rx) = futures::channel::oneshot::channel::<crate::errors::Result<()>>();
+        let awaiter: PacketSendAwaiter = rx.into();
+
+        let clock = MockClock::default();
+        let (result, _) = futures::join!(
+            awaiter.consume_and_wait_with_clock(&clock, Duration::from_secs(30)),
+            async { clock.advance(Duration::from_secs(31)) }
+        );
+
+        assert!(matches!(result, Err(ProtocolError::Timeout)));
+    }
 }
@@ -0,0 +1,63 @@
+//! Reachability of the first hop of the packets sent by this node.
+//!
+//! The [`PeerReachability`] is consulted in the `MsgOut` process before a packet is handed over
+//! to the wire, so a send to an unreachable first hop is rejected with
+//! [`ProtocolError::FirstHopUnreachable`](crate::errors::ProtocolError::FirstHopUnreachable)
+//! instead of disappearing in the transport.
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_transport_identity::PeerId;
+
+/// Tells whether a peer can currently be reached over the transport.
+#[async_trait]
+pub trait PeerReachability: Debug + Send + Sync {
+    /// Indicates whether the `peer` is currently reachable.
+    async fn is_reachable(&self, peer: &PeerId) -> bool;
+}
+
+/// Considers all the peers reachable.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AssumeReachable;
+
+#[async_trait]
+impl PeerReachability for AssumeReachable {
+    async fn is_reachable(&self, _peer: &PeerId) -> bool {
+        true
+    }
+}
+
+/// First hop of a packet sent using the given `routing`, if it is known before the packet is wrapped.
+///
+/// The first hop of a packet sent over a SURB is known only once the packet has been wrapped.
+pub fn known_first_hop(routing: &ResolvedTransportRouting) -> Option<PeerId> {
+    match routing {
+        ResolvedTransportRouting::Forward { forward_path, .. } => forward_path.first().map(PeerId::from),
+        ResolvedTransportRouting::Return(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_crypto_random::Randomizable;
+    use hopr_crypto_types::prelude::*;
+    use hopr_internal_types::prelude::HoprPseudonym;
+    use hopr_path::ValidatedPath;
+
+    #[test]
+    fn known_first_hop_should_be_the_first_hop_of_the_forward_path() {
+        let first_hop = OffchainKeypair::random();
+        let routing = ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *first_hop.public(),
+            ChainKeypair::random().public().to_address(),
+        ));
+
+        assert_eq!(Some(PeerId::from(first_hop.public())), known_first_hop(&routing));
+        assert_eq!(
+            None,
+            known_first_hop(&ResolvedTransportRouting::Return(HoprPseudonym::random()))
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use hopr_async_runtime::clock::{Clock, RealClock};
+use hopr_crypto_types::types::HalfKeyChallenge;
+use hopr_internal_types::protocol::ApplicationData;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+
+/// Maximum number of sent packets tracked for re-sending at the same time.
+pub const DEFAULT_MAX_PENDING_RESENDS: usize = 10_000;
+
+/// Emitted when a packet has not been acknowledged even after all its re-sends.
+#[derive(Debug, Clone)]
+pub struct UnacknowledgedPacket {
+    /// Data carried by the packet.
+    pub data: ApplicationData,
+    /// Routing the packet was sent over.
+    pub routing: ResolvedTransportRouting,
+    /// Number of re-sends performed before giving up.
+    pub resends: u8,
+}
+
+/// Packet that is due to be re-sent, identified by the send it belongs to.
+#[derive(Debug, Clone)]
+pub struct DueResend {
+    pub id: u64,
+    pub data: ApplicationData,
+    pub routing: ResolvedTransportRouting,
+}
+
+#[derive(Debug)]
+struct PendingSend {
+    data: ApplicationData,
+    routing: ResolvedTransportRouting,
+    resends: u8,
+    deadline: Instant,
+    challenges: Vec<HalfKeyChallenge>,
+}
+
+#[derive(Debug, Default)]
+struct ResendState {
+    next_id: u64,
+    sends: HashMap<u64, PendingSend>,
+    challenges: HashMap<HalfKeyChallenge, u64>,
+}
+
+impl ResendState {
+    fn remove(&mut self, id: u64) -> Option<PendingSend> {
+        let send = self.sends.remove(&id)?;
+        for challenge in send.challenges.iter() {
+            self.challenges.remove(challenge);
+        }
+        Some(send)
+    }
+}
+
+/// Keeps track of the sent packets awaiting an acknowledgement, so that they can be re-sent.
+///
+/// Each re-send wraps the packet anew, therefore a single send can be identified by several
+/// acknowledgement challenges. An acknowledgement of any of them, including the late ones
+/// of the previous re-sends, completes the send.
+#[derive(Debug, Clone)]
+pub struct ResendTracker<C: Clock = RealClock> {
+    resend_after: Duration,
+    max_resends: u8,
+    capacity: usize,
+    clock: C,
+    state: Arc<Mutex<ResendState>>,
+}
+
+impl ResendTracker {
+    pub fn new(resend_after: Duration, max_resends: u8) -> Self {
+        Self::with_clock(resend_after, max_resends, DEFAULT_MAX_PENDING_RESENDS, RealClock)
+    }
+}
+
+impl<C: Clock> ResendTracker<C> {
+    pub fn with_clock(resend_after: Duration, max_resends: u8, capacity: usize, clock: C) -> Self {
+        Self {
+            resend_after,
+            max_resends,
+            capacity,
+            clock,
+            state: Arc::new(Mutex::new(ResendState::default())),
+        }
+    }
+
+    /// Delay after which an unacknowledged packet is re-sent.
+    pub fn resend_after(&self) -> Duration {
+        self.resend_after
+    }
+
+    /// Starts tracking a newly sent packet.
+    ///
+    /// The packet is not tracked if the maximum number of pending sends has been reached.
+    pub fn track(&self, challenge: HalfKeyChallenge, data: ApplicationData, routing: ResolvedTransportRouting) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.sends.len() >= self.capacity {
+            warn!(
+                capacity = self.capacity,
+                "too many packets pending re-send, packet will not be re-sent"
+            );
+            return;
+        }
+
+        let id = state.next_id;
+        state.next_id = state.next_id.wrapping_add(1);
+        state.challenges.insert(challenge, id);
+        state.sends.insert(
+            id,
+            PendingSend {
+                data,
+                routing,
+                resends: 0,
+                deadline: self.clock.now() + self.resend_after,
+                challenges: vec![challenge],
+            },
+        );
+    }
+
+    /// Records the challenge of a re-sent packet belonging to the send with the given `id`.
+    ///
+    /// Returns `false` if the send has been completed in the meantime.
+    pub fn resent(&self, id: u64, challenge: HalfKeyChallenge) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.sends.get_mut(&id) {
+            Some(send) => {
+                send.challenges.push(challenge);
+                state.challenges.insert(challenge, id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Completes the send the acknowledged challenge belongs to, cancelling its pending re-sends.
+    ///
+    /// Returns `true` if the challenge belonged to a tracked send.
+    pub fn acknowledged(&self, challenge: &HalfKeyChallenge) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.challenges.get(challenge).copied() {
+            Some(id) => state.remove(id).is_some(),
+            None => false,
+        }
+    }
+
+    /// Number of sends currently awaiting an acknowledgement.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).sends.len()
+    }
+
+    /// Collects the sends whose acknowledgement did not arrive in time.
+    ///
+    /// Returns the packets that should be re-sent and the packets that exhausted all their re-sends,
+    /// which are no longer tracked.
+    pub fn due(&self) -> (Vec<DueResend>, Vec<UnacknowledgedPacket>) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let expired = state
+            .sends
+            .iter()
+            .filter(|(_, send)| send.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        let mut resend = Vec::new();
+        let mut gave_up = Vec::new();
+        for id in expired {
+            if let Some(send) = state.sends.get_mut(&id).filter(|send| send.resends < self.max_resends) {
+                send.resends += 1;
+                send.deadline = now + self.resend_after;
+                resend.push(DueResend {
+                    id,
+                    data: send.data.clone(),
+                    routing: send.routing.clone(),
+                });
+            } else if let Some(send) = state.remove(id) {
+                gave_up.push(UnacknowledgedPacket {
+                    data: send.data,
+                    routing: send.routing,
+                    resends: send.resends,
+                });
+            }
+        }
+
+        (resend, gave_up)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_async_runtime::clock::MockClock;
+    use hopr_crypto_random::Randomizable;
+    use hopr_crypto_types::keypairs::{ChainKeypair, Keypair, OffchainKeypair};
+    use hopr_crypto_types::types::HalfKey;
+    use hopr_path::ValidatedPath;
+
+    fn challenge() -> HalfKeyChallenge {
+        HalfKey::random().to_challenge()
+    }
+
+    fn routing() -> ResolvedTransportRouting {
+        ResolvedTransportRouting::forward_only(ValidatedPath::direct(
+            *OffchainKeypair::random().public(),
+            ChainKeypair::random().public().to_address(),
+        ))
+    }
+
+    #[test]
+    fn resend_tracker_should_resend_until_max_resends_and_then_give_up() {
+        let clock = MockClock::default();
+        let tracker = ResendTracker::with_clock(Duration::from_secs(1), 2, 10, clock.clone());
+        tracker.track(challenge(), ApplicationData::new(1024, &[1]), routing());
+
+        assert!(tracker.due().0.is_empty(), "nothing must be due before the deadline");
+
+        for attempt in 0..2 {
+            clock.advance(Duration::from_secs(1));
+            let (resend, gave_up) = tracker.due();
+            assert_eq!(1, resend.len(), "attempt {attempt} must be re-sent");
+            assert!(gave_up.is_empty());
+            assert!(tracker.resent(resend[0].id, challenge()));
+        }
+
+        clock.advance(Duration::from_secs(1));
+        let (resend, gave_up) = tracker.due();
+        assert!(resend.is_empty());
+        assert_eq!(1, gave_up.len());
+        assert_eq!(2, gave_up[0].resends);
+        assert_eq!(0, tracker.pending());
+    }
+
+    #[test]
+    fn resend_tracker_should_complete_send_on_late_ack_of_previous_attempt() {
+        let clock = MockClock::default();
+        let tracker = ResendTracker::with_clock(Duration::from_secs(1), 3, 10, clock.clone());
+
+        let original = challenge();
+        tracker.track(original, ApplicationData::new(1024, &[1]), routing());
+
+        clock.advance(Duration::from_secs(1));
+        let (resend, _) = tracker.due();
+        let resent = challenge();
+        assert!(tracker.resent(resend[0].id, resent));
+
+        assert!(tracker.acknowledged(&original), "late ack must complete the send");
+        assert!(!tracker.acknowledged(&resent), "send must be completed only once");
+        assert_eq!(0, tracker.pending());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(tracker.due().0.is_empty(), "completed send must not be re-sent");
+    }
+
+    #[test]
+    fn resend_tracker_should_not_track_beyond_capacity() {
+        let tracker = ResendTracker::with_clock(Duration::from_secs(1), 1, 1, MockClock::default());
+
+        tracker.track(challenge(), ApplicationData::new(1024, &[1]), routing());
+        tracker.track(challenge(), ApplicationData::new(1024, &[2]), routing());
+
+        assert_eq!(1, tracker.pending());
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use futures::channel::mpsc::UnboundedSender;
+
+use crate::ack::processor::{AckTimeoutEvent, LosingTicketEvent};
+use crate::ack::stats::TicketStats;
+use crate::capture::WireTap;
+use crate::health::ProtocolHealth;
+use crate::msg::accounting::TrafficAccounting;
+use crate::msg::budget::BufferAccounting;
+use crate::msg::gate::PeerGate;
+use crate::msg::reachability::PeerReachability;
+use crate::msg::retransmit::UnacknowledgedPacket;
+use crate::spawner::ProcessSpawners;
+use crate::PeerDiscovery;
+
+/// Optional components of the `msg`/`ack` pipeline run by [`run_msg_ack_protocol`](crate::run_msg_ack_protocol).
+///
+/// All the components are disabled by default, each one is enabled by its `with_*` method.
+#[derive(Default)]
+pub struct ProtocolOptions {
+    pub(crate) bloom_filter_persistent_path: Option<String>,
+    pub(crate) ack_timeout_events: Option<UnboundedSender<AckTimeoutEvent>>,
+    pub(crate) resend_events: Option<UnboundedSender<UnacknowledgedPacket>>,
+    pub(crate) ban_events: Option<UnboundedSender<PeerDiscovery>>,
+    pub(crate) losing_ticket_events: Option<UnboundedSender<LosingTicketEvent>>,
+    pub(crate) ticket_stats: Option<TicketStats>,
+    pub(crate) traffic: Option<TrafficAccounting>,
+    pub(crate) health: Option<ProtocolHealth>,
+    pub(crate) buffer_accounting: Option<BufferAccounting>,
+    pub(crate) gate: Option<Arc<dyn PeerGate>>,
+    pub(crate) reachability: Option<Arc<dyn PeerReachability>>,
+    pub(crate) wire_tap: Option<WireTap>,
+    pub(crate) spawners: ProcessSpawners,
+}
+
+impl std::fmt::Debug for ProtocolOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProtocolOptions")
+            .field("bloom_filter_persistent_path", &self.bloom_filter_persistent_path)
+            .field("gate", &self.gate.as_ref().map(|_| "custom"))
+            .field("reachability", &self.reachability.as_ref().map(|_| "custom"))
+            .field("wire_tap", &self.wire_tap.is_some())
+            .field("spawners", &self.spawners)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProtocolOptions {
+    /// Persists the packet tag bloom filter at the given path.
+    pub fn with_bloom_filter_persistence(mut self, path: impl Into<String>) -> Self {
+        self.bloom_filter_persistent_path = Some(path.into());
+        self
+    }
+
+    /// Reports the peers not acknowledging the packets within the expectation window into the given channel.
+    pub fn with_ack_timeout_events(mut self, events: UnboundedSender<AckTimeoutEvent>) -> Self {
+        self.ack_timeout_events = Some(events);
+        self
+    }
+
+    /// Reports the packets not acknowledged even after all the re-sends into the given channel,
+    /// if re-sending is enabled by [`resend_unacked_after`](crate::msg::processor::PacketInteractionConfig::resend_unacked_after).
+    pub fn with_resend_events(mut self, events: UnboundedSender<UnacknowledgedPacket>) -> Self {
+        self.resend_events = Some(events);
+        self
+    }
+
+    /// Reports the bans of the peers repeatedly sending malformed acknowledgements into the given channel,
+    /// if enabled by the [`malformed_ack_policy`](crate::ack::config::AckProtocolConfig::malformed_ack_policy).
+    pub fn with_ban_events(mut self, events: UnboundedSender<PeerDiscovery>) -> Self {
+        self.ban_events = Some(events);
+        self
+    }
+
+    /// Reports the relayed packets whose ticket turned out to be losing into the given channel.
+    pub fn with_losing_ticket_events(mut self, events: UnboundedSender<LosingTicketEvent>) -> Self {
+        self.losing_ticket_events = Some(events);
+        self
+    }
+
+    /// Counts the outcomes of the received tickets per channel in the given registry.
+    pub fn with_ticket_stats(mut self, ticket_stats: TicketStats) -> Self {
+        self.ticket_stats = Some(ticket_stats);
+        self
+    }
+
+    /// Counts the messages and payload bytes sent and received per application tag in the given accounting.
+    pub fn with_traffic_accounting(mut self, traffic: TrafficAccounting) -> Self {
+        self.traffic = Some(traffic);
+        self
+    }
+
+    /// Reports the status of each process into the given registry.
+    pub fn with_health(mut self, health: ProtocolHealth) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Accounts the bytes buffered in the pipeline queues in the given accounting, instead of a new one
+    /// created from the [`buffer_budget`](crate::msg::config::MsgProtocolConfig::buffer_budget).
+    ///
+    /// Allows the buffers outside the pipeline, such as the mixer, to take their share of the same budget.
+    pub fn with_buffer_accounting(mut self, buffer_accounting: BufferAccounting) -> Self {
+        self.buffer_accounting = Some(buffer_accounting);
+        self
+    }
+
+    /// Admits the incoming packets by the given peer gate before they are decrypted,
+    /// by default the packets from all peers are admitted.
+    pub fn with_gate(mut self, gate: Arc<dyn PeerGate>) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Rejects the sends whose first hop is not reachable according to the given reachability,
+    /// by default all the first hops are considered reachable.
+    pub fn with_reachability(mut self, reachability: Arc<dyn PeerReachability>) -> Self {
+        self.reachability = Some(reachability);
+        self
+    }
+
+    /// Captures every `msg` and `ack` item received from the wire into the given tap, before it is processed.
+    pub fn with_wire_tap(mut self, wire_tap: WireTap) -> Self {
+        self.wire_tap = Some(wire_tap);
+        self
+    }
+
+    /// Runs the ingress and egress processes using the given spawners,
+    /// by default all the processes share the runtime executor.
+    pub fn with_spawners(mut self, spawners: ProcessSpawners) -> Self {
+        self.spawners = spawners;
+        self
+    }
+}
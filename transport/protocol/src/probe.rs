@@ -0,0 +1,199 @@
+use futures::future::Either;
+use futures::{pin_mut, Sink};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, trace};
+
+use hopr_async_runtime::prelude::sleep;
+use hopr_crypto_types::types::OffchainPublicKey;
+use hopr_internal_types::protocol::{ApplicationData, Tag};
+use hopr_network_types::prelude::ResolvedTransportRouting;
+
+use crate::errors::{ProtocolError, Result};
+use crate::msg::processor::{MsgSender, SendMsgInput};
+
+/// Application tag reserved for the loopback path probes.
+///
+/// The tag lies in the range reserved for the internal subprotocols.
+pub const PROBE_APPLICATION_TAG: Tag = 1;
+
+/// Size of the probe nonce carried in the probe payload.
+const PROBE_NONCE_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Outcome of a single loopback path probe.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// The probe has returned back to us after the given round-trip time.
+    Returned { rtt: Duration },
+    /// The probe has not returned within the given timeout.
+    Timeout,
+}
+
+/// Measures the end-to-end liveness of multi-hop paths.
+///
+/// A small probe packet is sent over a path that ends at this node, using the `msg`
+/// pipeline's API sink. The returned probe is matched by its nonce once it is passed
+/// through [`PathProber::intercept`] from the pipeline's API stream.
+#[derive(Debug, Clone)]
+pub struct PathProber<T>
+where
+    T: Sink<SendMsgInput> + Send + Sync + Clone + 'static + std::marker::Unpin,
+{
+    me: OffchainPublicKey,
+    sender: MsgSender<T>,
+    pending: Arc<Mutex<HashMap<u64, futures::channel::oneshot::Sender<Instant>>>>,
+}
+
+impl<T> PathProber<T>
+where
+    T: Sink<SendMsgInput> + Send + Sync + Clone + 'static + std::marker::Unpin,
+{
+    pub fn new(me: OffchainPublicKey, tx: T) -> Self {
+        Self {
+            me,
+            sender: MsgSender::new(tx),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sends a probe over the given routing and waits at most `timeout` until it returns back.
+    ///
+    /// The forward path of the routing must end at this node.
+    #[tracing::instrument(level = "debug", skip(self, routing))]
+    pub async fn probe(&self, routing: ResolvedTransportRouting, timeout: Duration) -> Result<ProbeResult> {
+        match &routing {
+            ResolvedTransportRouting::Forward { forward_path, .. } if forward_path.last() == Some(&self.me) => {}
+            _ => return Err(ProtocolError::Logic("probe path must lead back to this node".into())),
+        }
+
+        let nonce = u64::from_be_bytes(hopr_crypto_random::random_bytes());
+        let (tx, rx) = futures::channel::oneshot::channel::<Instant>();
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(nonce, tx);
+
+        let result = self.send_and_wait(nonce, routing, rx, timeout).await;
+
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&nonce);
+        result
+    }
+
+    async fn send_and_wait(
+        &self,
+        nonce: u64,
+        routing: ResolvedTransportRouting,
+        rx: futures::channel::oneshot::Receiver<Instant>,
+        timeout: Duration,
+    ) -> Result<ProbeResult> {
+        let started = Instant::now();
+        let data = ApplicationData::new(PROBE_APPLICATION_TAG, &nonce.to_be_bytes());
+
+        self.sender
+            .send_packet(data, routing)
+            .await
+            .map_err(|e| ProtocolError::TransportError(e.to_string()))?
+            .consume_and_wait(timeout)
+            .await?;
+
+        let timeout = sleep(timeout.saturating_sub(started.elapsed()));
+        pin_mut!(rx, timeout);
+        match futures::future::select(rx, timeout).await {
+            Either::Left((Ok(returned), _)) => {
+                let rtt = returned.saturating_duration_since(started);
+                debug!(nonce, rtt_in_ms = rtt.as_millis(), "probe returned");
+                Ok(ProbeResult::Returned { rtt })
+            }
+            Either::Left((Err(_), _)) => Err(ProtocolError::TransportError("Canceled".into())),
+            Either::Right(_) => {
+                debug!(nonce, "probe timed out");
+                Ok(ProbeResult::Timeout)
+            }
+        }
+    }
+
+    /// Consumes the returned probes from the data received over the `msg` pipeline.
+    ///
+    /// Returns the data back if it is not a probe, so that it can be passed on
+    /// to the rest of the application.
+    pub fn intercept(&self, data: ApplicationData) -> Option<ApplicationData> {
+        if data.application_tag != PROBE_APPLICATION_TAG {
+            return Some(data);
+        }
+
+        let returned = Instant::now();
+        match <[u8; PROBE_NONCE_SIZE]>::try_from(data.plain_text.as_ref()) {
+            Ok(nonce) => {
+                let nonce = u64::from_be_bytes(nonce);
+                match self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&nonce) {
+                    Some(tx) => {
+                        let _ = tx.send(returned);
+                    }
+                    None => trace!(nonce, "received an unknown or expired probe"),
+                }
+            }
+            Err(_) => trace!(len = data.plain_text.len(), "received a malformed probe"),
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use hopr_crypto_types::keypairs::{Keypair, OffchainKeypair};
+    use hopr_primitive_types::prelude::Address;
+
+    fn loop_routing(me: &OffchainKeypair) -> ResolvedTransportRouting {
+        ResolvedTransportRouting::forward_only(hopr_path::ValidatedPath::direct(*me.public(), Address::default()))
+    }
+
+    #[async_std::test]
+    async fn probe_should_reject_path_not_leading_back() -> anyhow::Result<()> {
+        let (tx, _rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let prober = PathProber::new(*OffchainKeypair::random().public(), tx);
+
+        let res = prober
+            .probe(loop_routing(&OffchainKeypair::random()), Duration::from_millis(10))
+            .await;
+        assert!(matches!(res, Err(ProtocolError::Logic(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn intercept_should_pass_through_non_probe_data() {
+        let (tx, _rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let prober = PathProber::new(*OffchainKeypair::random().public(), tx);
+
+        let data = ApplicationData::new(PROBE_APPLICATION_TAG + 1, &[1, 2, 3]);
+        assert_eq!(Some(data.clone()), prober.intercept(data));
+        assert_eq!(
+            None,
+            prober.intercept(ApplicationData::new(PROBE_APPLICATION_TAG, &[1, 2, 3]))
+        );
+    }
+
+    #[async_std::test]
+    async fn probe_should_be_matched_by_nonce() -> anyhow::Result<()> {
+        let me = OffchainKeypair::random();
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let prober = PathProber::new(*me.public(), tx);
+
+        let prober_clone = prober.clone();
+        let loopback = async_std::task::spawn(async move {
+            let (data, _, finalizer, _) = rx.next().await.expect("probe must be sent");
+            finalizer.finalize(Ok(()));
+            async_std::task::sleep(Duration::from_millis(20)).await;
+            assert!(prober_clone.intercept(data).is_none());
+        });
+
+        let res = prober.probe(loop_routing(&me), Duration::from_secs(1)).await?;
+        loopback.await;
+
+        assert!(matches!(res, ProbeResult::Returned { rtt } if rtt >= Duration::from_millis(20)));
+        assert!(prober.pending.lock().unwrap().is_empty());
+
+        Ok(())
+    }
+}
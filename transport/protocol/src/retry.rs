@@ -0,0 +1,177 @@
+use std::future::Future;
+use std::time::Duration;
+
+use hopr_async_runtime::retry::{retry_with_hooks, RetryAction, RetryError, RetryHooks, RetryPolicy};
+use hopr_db_api::errors::DbError;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
+
+#[cfg(all(feature = "prometheus", not(test)))]
+use hopr_metrics::metrics::MultiCounter;
+
+#[cfg(all(feature = "prometheus", not(test)))]
+lazy_static::lazy_static! {
+    pub(crate) static ref METRIC_DB_RETRIES: MultiCounter = MultiCounter::new(
+        "hopr_protocol_db_retries",
+        "Number of retries of the idempotent DB reads of the packet processing failing with a transient error",
+        &["operation"]
+    )
+    .unwrap();
+}
+
+fn default_db_max_retries() -> u32 {
+    3
+}
+
+fn default_db_initial_backoff() -> Duration {
+    Duration::from_millis(10)
+}
+
+fn default_db_max_backoff() -> Duration {
+    Duration::from_millis(200)
+}
+
+/// Retrying of the idempotent DB reads of the packet processing.
+///
+/// Only the [transient](DbError::is_transient) DB errors are retried, all the other errors
+/// are returned right away. Operations modifying the DB must not be retried, since a transient error
+/// does not tell whether their side effects have already taken place.
+#[serde_as]
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DbRetryConfig {
+    /// Maximum number of retries of a single DB operation.
+    ///
+    /// Zero disables the retries.
+    #[serde(default = "default_db_max_retries")]
+    #[default(default_db_max_retries())]
+    pub max_retries: u32,
+    /// Backoff before the first retry, which is doubled for each following retry.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(default = "default_db_initial_backoff")]
+    #[default(default_db_initial_backoff())]
+    pub initial_backoff: Duration,
+    /// Maximum backoff between two retries.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(default = "default_db_max_backoff")]
+    #[default(default_db_max_backoff())]
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy<DbError> for DbRetryConfig {
+    fn is_retryable_error(&self, err: &DbError, retry_number: u32, _retry_queue_size: u32) -> RetryAction {
+        if !err.is_transient() || retry_number > self.max_retries {
+            return RetryAction::NoRetry;
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(retry_number.saturating_sub(1)));
+        RetryAction::RetryAfter(backoff.min(self.max_backoff))
+    }
+}
+
+struct DbRetryHooks {
+    operation: &'static str,
+}
+
+impl RetryHooks<DbError> for DbRetryHooks {
+    fn on_retry(&self, err: &DbError, failures: u32, backoff: Duration) {
+        tracing::debug!(operation = self.operation, error = %err, failures, ?backoff, "retrying a DB operation");
+
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_DB_RETRIES.increment(&[self.operation]);
+    }
+}
+
+/// Performs the DB `operation` until it succeeds, fails with a permanent error or the `cfg` does not allow
+/// any more retries.
+pub(crate) async fn retry_db<F, Fut, T>(cfg: &DbRetryConfig, operation: &'static str, f: F) -> Result<T, DbError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DbError>>,
+{
+    retry_with_hooks(f, cfg, &DbRetryHooks { operation })
+        .await
+        .map(|(value, _)| value)
+        .map_err(RetryError::into_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn cfg() -> DbRetryConfig {
+        DbRetryConfig {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        }
+    }
+
+    async fn fail_times(attempts: &AtomicU32, times: u32, error: fn() -> DbError) -> Result<u32, DbError> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempt <= times {
+            Err(error())
+        } else {
+            Ok(attempt)
+        }
+    }
+
+    #[test]
+    fn db_retry_config_should_back_off_exponentially_up_to_the_maximum() {
+        let cfg = DbRetryConfig {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(30),
+        };
+        let transient = DbError::Transient("locked".into());
+
+        let backoffs = (1..=6)
+            .map(|retry| match cfg.is_retryable_error(&transient, retry, 0) {
+                RetryAction::RetryAfter(backoff) => Some(backoff.as_millis()),
+                RetryAction::NoRetry => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(10), Some(20), Some(30), Some(30), Some(30), None], backoffs);
+
+        assert!(matches!(
+            cfg.is_retryable_error(&DbError::General("corrupted".into()), 1, 0),
+            RetryAction::NoRetry
+        ));
+    }
+
+    #[async_std::test]
+    async fn retry_db_should_retry_transient_errors_only() {
+        let attempts = AtomicU32::new(0);
+        let result = retry_db(&cfg(), "test", || {
+            fail_times(&attempts, 2, || DbError::Transient("locked".into()))
+        })
+        .await;
+        assert_eq!(3, result.expect("must succeed after the transient errors"));
+
+        let attempts = AtomicU32::new(0);
+        let result = retry_db(&cfg(), "test", || {
+            fail_times(&attempts, 10, || DbError::Transient("locked".into()))
+        })
+        .await;
+        assert!(matches!(result, Err(DbError::Transient(_))));
+        assert_eq!(
+            4,
+            attempts.load(Ordering::SeqCst),
+            "must give up after the maximum number of retries"
+        );
+
+        let attempts = AtomicU32::new(0);
+        let result = retry_db(&cfg(), "test", || {
+            fail_times(&attempts, 10, || DbError::LogicalError("permanent".into()))
+        })
+        .await;
+        assert!(matches!(result, Err(DbError::LogicalError(_))));
+        assert_eq!(
+            1,
+            attempts.load(Ordering::SeqCst),
+            "permanent errors must not be retried"
+        );
+    }
+}
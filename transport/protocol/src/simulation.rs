@@ -0,0 +1,443 @@
+//! Minimal packet forwarding simulator used for capacity planning.
+//!
+//! The simulator runs the complete `msg`/`ack` pipeline of a single relay node backed by an
+//! in-memory database and feeds it with packets pre-wrapped by a simulated sender. The packets
+//! are delivered and collected over loopback wires, so the measured values reflect only the
+//! processing capacity of the node itself.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMicroSeconds};
+use tracing::debug;
+
+use hopr_async_runtime::prelude::{cancel_join_handle, timeout_fut};
+use hopr_crypto_packet::prelude::HoprPacket;
+use hopr_crypto_random::Randomizable;
+use hopr_crypto_types::prelude::*;
+use hopr_db_api::info::DomainSeparator;
+use hopr_db_sql::{
+    accounts::HoprDbAccountOperations, channels::HoprDbChannelOperations, db::HoprDb, info::HoprDbInfoOperations,
+};
+use hopr_internal_types::prelude::*;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_path::{channel_graph::ChannelGraph, ChainPath, ValidatedPath};
+use hopr_primitive_types::prelude::*;
+use hopr_transport_identity::{Multiaddr, PeerId};
+
+use crate::config::ProtocolConfig;
+use crate::errors::{ProtocolError, Result};
+use crate::msg::processor::{MsgSender, PacketInteractionConfig, SendMsgInput};
+
+/// Application tag of the simulated packets, lying outside the reserved tag ranges.
+const SIMULATION_APPLICATION_TAG: Tag = 1024;
+
+/// Ticket price used when the configuration does not override it.
+const SIMULATION_TICKET_PRICE: u64 = 100;
+
+/// Maximum time to wait for the simulated packets to pass through a pipeline.
+const SIMULATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Global allocator counting the number of allocations made by the process.
+///
+/// The allocation counts are reported by [`simulate_forwarding`] only if this allocator
+/// is installed as the `#[global_allocator]` of the running binary.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Latency percentiles of a single pipeline stage.
+#[serde_as]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    #[serde_as(as = "DurationMicroSeconds<u64>")]
+    pub p50: Duration,
+    #[serde_as(as = "DurationMicroSeconds<u64>")]
+    pub p99: Duration,
+}
+
+impl LatencySummary {
+    /// Computes the summary from the latencies of the individual packets.
+    fn from_latencies(mut latencies: Vec<Duration>) -> Self {
+        latencies.sort_unstable();
+        Self {
+            p50: percentile(&latencies, 50),
+            p99: percentile(&latencies, 99),
+        }
+    }
+}
+
+/// Nearest-rank percentile of the sorted values.
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Results of a single forwarding simulation run.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForwardingReport {
+    /// Number of packets fed into the relay.
+    pub packets: usize,
+    /// Size of the application payload carried by each packet.
+    pub payload_size: usize,
+    /// Number of packets forwarded by the relay within the simulation timeout.
+    pub forwarded: usize,
+    /// Number of acknowledgements sent by the relay within the simulation timeout.
+    pub acknowledged: usize,
+    /// Time from feeding the first packet until the last packet was forwarded.
+    #[serde_as(as = "DurationMicroSeconds<u64>")]
+    pub elapsed: Duration,
+    /// Number of packets forwarded per second.
+    pub packets_per_second: f64,
+    /// Latency between a packet entering the relay and leaving it towards the next hop.
+    pub forward_latency: LatencySummary,
+    /// Latency between a packet entering the relay and the acknowledgement leaving towards the previous hop.
+    pub ack_latency: LatencySummary,
+    /// Number of allocations made during the run, if [`CountingAllocator`] is installed.
+    pub allocations: Option<u64>,
+}
+
+type SimulatedNode = (OffchainKeypair, ChainKeypair, HoprDb);
+
+async fn create_simulated_nodes(ticket_price: Balance, packet_count: usize) -> Result<Vec<SimulatedNode>> {
+    let keys = (0..3)
+        .map(|_| (OffchainKeypair::random(), ChainKeypair::random()))
+        .collect::<Vec<_>>();
+
+    let channel_balance = Balance::new(
+        ticket_price.amount() * U256::from(10 * (packet_count as u64 + 1)),
+        BalanceType::HOPR,
+    );
+    let channels = keys
+        .windows(2)
+        .map(|pair| {
+            ChannelEntry::new(
+                pair[0].1.public().to_address(),
+                pair[1].1.public().to_address(),
+                channel_balance,
+                U256::zero(),
+                ChannelStatus::Open,
+                U256::zero(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut nodes = Vec::with_capacity(keys.len());
+    for (packet_key, chain_key) in keys.iter().cloned() {
+        let db = HoprDb::new_in_memory(chain_key.clone())
+            .await
+            .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+
+        db.set_domain_separator(None, DomainSeparator::Channel, Hash::default())
+            .await
+            .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+        db.update_ticket_price(None, ticket_price)
+            .await
+            .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+
+        for (announced_packet_key, announced_chain_key) in keys.iter() {
+            db.insert_account(
+                None,
+                AccountEntry {
+                    public_key: *announced_packet_key.public(),
+                    chain_addr: announced_chain_key.public().to_address(),
+                    entry_type: AccountType::Announced {
+                        multiaddr: Multiaddr::from_str("/ip4/127.0.0.1/tcp/4444")
+                            .map_err(|e| ProtocolError::Logic(e.to_string()))?,
+                        updated_block: 1,
+                    },
+                    published_at: 1,
+                },
+            )
+            .await
+            .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+        }
+
+        for channel in channels.iter() {
+            db.upsert_channel(None, *channel)
+                .await
+                .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+        }
+
+        nodes.push((packet_key, chain_key, db));
+    }
+
+    Ok(nodes)
+}
+
+/// Channels connecting a simulated node to the wire and to the application.
+struct SimulatedPipeline {
+    ack_out: futures::channel::mpsc::UnboundedReceiver<(PeerId, Acknowledgement)>,
+    msg_in: futures::channel::mpsc::UnboundedSender<(PeerId, Box<[u8]>)>,
+    msg_out: futures::channel::mpsc::UnboundedReceiver<(PeerId, Box<[u8]>)>,
+    api_send: futures::channel::mpsc::UnboundedSender<SendMsgInput>,
+    processes: Vec<hopr_async_runtime::prelude::JoinHandle<()>>,
+    // Kept alive, so that the pipeline does not observe closed inputs
+    _ack_in: futures::channel::mpsc::UnboundedSender<(PeerId, Acknowledgement)>,
+    _api_recv: futures::channel::mpsc::UnboundedReceiver<ApplicationData>,
+}
+
+impl SimulatedPipeline {
+    async fn start(node: &SimulatedNode, config: &ProtocolConfig, ticket_price: Balance) -> Result<Self> {
+        let (ack_out_tx, ack_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+        let (ack_in_tx, ack_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+        let (msg_out_tx, msg_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+        let (msg_in_tx, msg_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+        let (api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+        let (api_recv_tx, api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
+
+        let packet_cfg = PacketInteractionConfig::new(
+            &node.0,
+            &node.1,
+            Some(config.outgoing_ticket_winning_prob.unwrap_or(1.0)),
+            Some(ticket_price),
+        );
+
+        let (processes, _control) = crate::run_msg_ack_protocol(
+            packet_cfg,
+            config.msg,
+            config.ack,
+            node.2.clone(),
+            (ack_out_tx, ack_in_rx),
+            (msg_out_tx, msg_in_rx),
+            (api_recv_tx, api_send_rx),
+            Default::default(),
+        )
+        .await?;
+
+        Ok(Self {
+            ack_out: ack_out_rx,
+            msg_in: msg_in_tx,
+            msg_out: msg_out_rx,
+            api_send: api_send_tx,
+            processes: processes.into_values().collect(),
+            _ack_in: ack_in_tx,
+            _api_recv: api_recv_rx,
+        })
+    }
+
+    async fn stop(self) {
+        for process in self.processes {
+            cancel_join_handle(process).await;
+        }
+    }
+}
+
+/// Wraps the packets at the simulated sender, so that they can be fed into the relay.
+async fn wrap_packets(
+    nodes: &[SimulatedNode],
+    config: &ProtocolConfig,
+    ticket_price: Balance,
+    num_packets: usize,
+    payload_size: usize,
+) -> Result<Vec<Box<[u8]>>> {
+    let mut cg = ChannelGraph::new(nodes[0].1.public().to_address(), Default::default());
+    for pair in nodes.windows(2) {
+        cg.update_channel(ChannelEntry::new(
+            pair[0].1.public().to_address(),
+            pair[1].1.public().to_address(),
+            Balance::new(1000_u32, BalanceType::HOPR),
+            U256::zero(),
+            ChannelStatus::Open,
+            U256::zero(),
+        ));
+    }
+
+    let path = ValidatedPath::new(
+        ChainPath::new(nodes[1..].iter().map(|node| node.1.public().to_address()))
+            .map_err(|e| ProtocolError::Logic(e.to_string()))?,
+        &cg,
+        &nodes[0].2,
+    )
+    .await
+    .map_err(|e| ProtocolError::Logic(e.to_string()))?;
+
+    let mut sender = SimulatedPipeline::start(&nodes[0], config, ticket_price).await;
+    let msg_sender = MsgSender::new(sender.api_send.clone());
+    let payload = vec![0xaa_u8; payload_size];
+    for _ in 0..num_packets {
+        let routing = ResolvedTransportRouting::Forward {
+            pseudonym: HoprPseudonym::random(),
+            forward_path: path.clone(),
+            return_paths: vec![],
+        };
+        // The send confirmation is not needed, the wrapped packet is collected from the wire
+        let _ = msg_sender
+            .send_packet(ApplicationData::new(SIMULATION_APPLICATION_TAG, &payload), routing)
+            .await?;
+    }
+
+    let wrapped = timeout_fut(
+        SIMULATION_TIMEOUT,
+        (&mut sender.msg_out)
+            .take(num_packets)
+            .map(|(_, data)| data)
+            .collect::<Vec<_>>(),
+    )
+    .await
+    .map_err(|_| ProtocolError::Timeout)?;
+
+    sender.stop().await;
+
+    if wrapped.len() != num_packets {
+        return Err(ProtocolError::Logic(format!(
+            "only {} out of {num_packets} packets could be wrapped",
+            wrapped.len()
+        )));
+    }
+
+    Ok(wrapped)
+}
+
+/// Collects the egress timestamps of at most `count` items leaving the pipeline within the simulation timeout.
+async fn collect_egress<S: futures::Stream + Unpin>(stream: S, count: usize) -> Vec<Instant> {
+    stream
+        .take_until(hopr_async_runtime::prelude::sleep(SIMULATION_TIMEOUT))
+        .take(count)
+        .map(|_| Instant::now())
+        .collect()
+        .await
+}
+
+/// Matches the ingress and egress timestamps in the FIFO order and returns the per-packet latencies.
+fn fifo_latencies(ingress: &[Instant], egress: &[Instant]) -> Vec<Duration> {
+    ingress
+        .iter()
+        .zip(egress.iter())
+        .map(|(ingress, egress)| egress.saturating_duration_since(*ingress))
+        .collect()
+}
+
+/// Simulates a relay node forwarding `num_packets` packets carrying `payload_size` bytes of data each.
+///
+/// The relay runs the `msg`/`ack` pipeline created by [`crate::run_msg_ack_protocol`] with the given
+/// `config` over an in-memory database. All the packets are pre-wrapped by a simulated sender
+/// and fed into the relay at once, hence the reported latencies include the queueing within the
+/// pipeline. As the forwarded packets cannot be correlated with the incoming ones, the per-stage
+/// latencies are computed by matching the ingress and egress in the FIFO order.
+pub async fn simulate_forwarding(
+    config: &ProtocolConfig,
+    num_packets: usize,
+    payload_size: usize,
+) -> Result<ForwardingReport> {
+    if payload_size + std::mem::size_of::<Tag>() > HoprPacket::PAYLOAD_SIZE {
+        return Err(ProtocolError::Logic(format!(
+            "payload size {payload_size} exceeds the packet capacity"
+        )));
+    }
+
+    let ticket_price = config
+        .outgoing_ticket_price
+        .unwrap_or(Balance::new(SIMULATION_TICKET_PRICE, BalanceType::HOPR));
+
+    let nodes = create_simulated_nodes(ticket_price, num_packets).await?;
+    let wrapped = wrap_packets(&nodes, config, ticket_price, num_packets, payload_size).await?;
+    debug!(num_packets, payload_size, "simulated packets wrapped");
+
+    let mut relay = SimulatedPipeline::start(&nodes[1], config, ticket_price).await?;
+    let previous_hop: PeerId = nodes[0].0.public().into();
+
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+
+    let mut ingress = Vec::with_capacity(num_packets);
+    for data in wrapped {
+        ingress.push(Instant::now());
+        relay
+            .msg_in
+            .send((previous_hop, data))
+            .await
+            .map_err(|e| ProtocolError::TransportError(e.to_string()))?;
+    }
+
+    let (forwarded, acknowledged) = futures::join!(
+        collect_egress(&mut relay.msg_out, num_packets),
+        collect_egress(&mut relay.ack_out, num_packets)
+    );
+
+    let allocations_after = ALLOCATIONS.load(Ordering::Relaxed);
+    relay.stop().await;
+
+    let elapsed = match (ingress.first(), forwarded.last()) {
+        (Some(first), Some(last)) => last.saturating_duration_since(*first),
+        _ => Duration::ZERO,
+    };
+
+    Ok(ForwardingReport {
+        packets: num_packets,
+        payload_size,
+        forwarded: forwarded.len(),
+        acknowledged: acknowledged.len(),
+        elapsed,
+        packets_per_second: if elapsed.is_zero() {
+            0.0
+        } else {
+            forwarded.len() as f64 / elapsed.as_secs_f64()
+        },
+        forward_latency: LatencySummary::from_latencies(fifo_latencies(&ingress, &forwarded)),
+        ack_latency: LatencySummary::from_latencies(fifo_latencies(&ingress, &acknowledged)),
+        allocations: (allocations_after > allocations_before).then(|| allocations_after - allocations_before),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_should_use_nearest_rank() {
+        let sorted = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+
+        assert_eq!(Duration::from_millis(50), percentile(&sorted, 50));
+        assert_eq!(Duration::from_millis(99), percentile(&sorted, 99));
+        assert_eq!(Duration::from_millis(1), percentile(&sorted[..1], 99));
+        assert_eq!(Duration::ZERO, percentile(&[], 50));
+    }
+
+    #[async_std::test]
+    async fn simulate_forwarding_should_forward_and_acknowledge_all_packets() -> anyhow::Result<()> {
+        let report = simulate_forwarding(&ProtocolConfig::default(), 10, 100).await?;
+
+        assert_eq!(10, report.forwarded);
+        assert_eq!(10, report.acknowledged);
+        assert!(report.packets_per_second > 0.0);
+        assert!(report.forward_latency.p50 <= report.forward_latency.p99);
+        assert!(report.allocations.is_none(), "counting allocator is not installed");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn simulate_forwarding_should_reject_oversized_payload() {
+        let res = simulate_forwarding(&ProtocolConfig::default(), 1, HoprPacket::PAYLOAD_SIZE).await;
+        assert!(matches!(res, Err(ProtocolError::Logic(_))));
+    }
+}
@@ -0,0 +1,98 @@
+use futures::future::BoxFuture;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+
+use hopr_async_runtime::prelude::{spawn, JoinHandle};
+
+/// Spawns the long-running processes of the protocol pipeline.
+///
+/// Any `Fn(BoxFuture<'static, ()>) -> JoinHandle<()>` closure is a spawner, which allows running
+/// the processes on a dedicated executor, e.g. `move |f: BoxFuture<'static, ()>| runtime_handle.spawn(f)`
+/// with `tokio`.
+pub trait ProcessSpawner: Send + Sync {
+    fn spawn(&self, process: BoxFuture<'static, ()>) -> JoinHandle<()>;
+}
+
+impl<F> ProcessSpawner for F
+where
+    F: Fn(BoxFuture<'static, ()>) -> JoinHandle<()> + Send + Sync,
+{
+    fn spawn(&self, process: BoxFuture<'static, ()>) -> JoinHandle<()> {
+        self(process)
+    }
+}
+
+/// Spawners of the ingress and egress process groups of the `msg`/`ack` pipeline.
+///
+/// The ingress group consists of the processes handling the packets and acknowledgements received from the wire,
+/// the egress group of those sending them out. Groups without a spawner use the shared [`spawn`] of the runtime.
+#[derive(Clone, Default)]
+pub struct ProcessSpawners {
+    ingress: Option<Arc<dyn ProcessSpawner>>,
+    egress: Option<Arc<dyn ProcessSpawner>>,
+}
+
+impl Debug for ProcessSpawners {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessSpawners")
+            .field("ingress", &self.ingress.as_ref().map(|_| "custom"))
+            .field("egress", &self.egress.as_ref().map(|_| "custom"))
+            .finish()
+    }
+}
+
+impl ProcessSpawners {
+    /// Runs the ingress processes using the given spawner.
+    pub fn with_ingress(mut self, spawner: impl ProcessSpawner + 'static) -> Self {
+        self.ingress = Some(Arc::new(spawner));
+        self
+    }
+
+    /// Runs the egress processes using the given spawner.
+    pub fn with_egress(mut self, spawner: impl ProcessSpawner + 'static) -> Self {
+        self.egress = Some(Arc::new(spawner));
+        self
+    }
+
+    pub(crate) fn spawn_ingress<F: Future<Output = ()> + Send + 'static>(&self, process: F) -> JoinHandle<()> {
+        Self::spawn_with(self.ingress.as_deref(), process)
+    }
+
+    pub(crate) fn spawn_egress<F: Future<Output = ()> + Send + 'static>(&self, process: F) -> JoinHandle<()> {
+        Self::spawn_with(self.egress.as_deref(), process)
+    }
+
+    fn spawn_with<F: Future<Output = ()> + Send + 'static>(
+        spawner: Option<&dyn ProcessSpawner>,
+        process: F,
+    ) -> JoinHandle<()> {
+        match spawner {
+            Some(spawner) => spawner.spawn(Box::pin(process)),
+            None => spawn(process),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[async_std::test]
+    async fn process_spawners_should_use_the_spawner_of_the_group() {
+        let ingress_count = Arc::new(AtomicUsize::new(0));
+        let ingress_count_clone = ingress_count.clone();
+
+        let spawners = ProcessSpawners::default().with_ingress(move |process: BoxFuture<'static, ()>| {
+            ingress_count_clone.fetch_add(1, Ordering::SeqCst);
+            spawn(process)
+        });
+
+        spawners.spawn_ingress(async {}).await;
+        spawners.spawn_egress(async {}).await;
+        spawners.spawn_ingress(async {}).await;
+
+        assert_eq!(2, ingress_count.load(Ordering::SeqCst));
+    }
+}
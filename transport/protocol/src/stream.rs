@@ -1,12 +1,29 @@
 //! Infrastructure supporting converting a collection of [`libp2p::PeerId`] split [`libp2p_stream`] managed
 //! individual peer-to-peer [`libp2p::swarm::Stream`]s.
+//!
+//! Also contains generic stream combinators used by the protocol pipelines.
 
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::FutureExt;
 use futures::{AsyncRead, AsyncReadExt, AsyncWrite, SinkExt as _, Stream, StreamExt};
+use hopr_async_runtime::clock::{Clock, RealClock};
+use hopr_internal_types::protocol::{ApplicationData, Tag};
 use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationMilliSeconds};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use tokio_util::{
     codec::{Decoder, Encoder, FramedRead, FramedWrite},
     compat::{FuturesAsyncReadCompatExt, FuturesAsyncWriteCompatExt},
 };
+use validator::Validate;
 
 #[async_trait::async_trait]
 pub trait BidirectionalStreamControl: std::fmt::Debug {
@@ -141,11 +158,827 @@ where
     Ok((tx_out, rx_in))
 }
 
+fn default_backoff_error_threshold() -> u32 {
+    5
+}
+
+fn default_initial_backoff() -> Duration {
+    Duration::from_millis(100)
+}
+
+fn default_max_backoff() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_backoff_max_sources() -> u64 {
+    10_000
+}
+
+fn default_max_delayed_per_source() -> usize {
+    16
+}
+
+/// Configuration of the [`SourceErrorBackoff`].
+#[serde_as]
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SourceErrorBackoffConfig {
+    /// Number of consecutive errors from a single source tolerated before backing off.
+    #[serde(default = "default_backoff_error_threshold")]
+    #[default(default_backoff_error_threshold())]
+    pub error_threshold: u32,
+    /// Duration of the first backoff period.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(default = "default_initial_backoff")]
+    #[default(default_initial_backoff())]
+    pub initial_backoff: Duration,
+    /// Upper bound of the backoff period.
+    #[serde_as(as = "DurationMilliSeconds<u64>")]
+    #[serde(default = "default_max_backoff")]
+    #[default(default_max_backoff())]
+    pub max_backoff: Duration,
+    /// Maximum number of failing sources tracked at a time.
+    ///
+    /// The least recently failing sources are forgotten once more sources are failing.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_backoff_max_sources")]
+    #[default(default_backoff_max_sources())]
+    pub max_sources: u64,
+    /// Maximum number of items of a single backing off source delayed at a time.
+    ///
+    /// The items of the source that do not fit are discarded.
+    #[serde(default = "default_max_delayed_per_source")]
+    #[default(default_max_delayed_per_source())]
+    pub max_delayed_per_source: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SourceErrorState {
+    consecutive_errors: u32,
+    blocked_until: Option<Instant>,
+}
+
+/// Circuit-breaker-like tracker of processing errors per stream item source.
+///
+/// Once a source produces more than [`SourceErrorBackoffConfig::error_threshold`] consecutive errors,
+/// items coming from it are delayed for a backoff period, which doubles with every further error
+/// (up to [`SourceErrorBackoffConfig::max_backoff`]). A single success resets the source.
+/// At most [`SourceErrorBackoffConfig::max_sources`] sources are tracked, the least recently
+/// failing ones are evicted first.
+///
+/// The tracker is cheaply cloneable, so that the errors can be recorded downstream of the
+/// [`StreamErrorBackoffExt::backoff_on_source_errors`] combinator which consults it.
+#[derive(Debug, Clone)]
+pub struct SourceErrorBackoff<K: Eq + Hash + Send + Sync + 'static, C: Clock = RealClock> {
+    cfg: SourceErrorBackoffConfig,
+    clock: C,
+    sources: moka::sync::Cache<K, Arc<Mutex<SourceErrorState>>>,
+}
+
+impl<K: Eq + Hash + Clone + Send + Sync + 'static> SourceErrorBackoff<K> {
+    pub fn new(cfg: SourceErrorBackoffConfig) -> Self {
+        Self::with_clock(cfg, RealClock)
+    }
+}
+
+impl<K: Eq + Hash + Clone + Send + Sync + 'static, C: Clock> SourceErrorBackoff<K, C> {
+    /// Same as [`SourceErrorBackoff::new`], but the backoff periods are measured using the given `clock`.
+    pub fn with_clock(cfg: SourceErrorBackoffConfig, clock: C) -> Self {
+        Self {
+            cfg,
+            clock,
+            sources: moka::sync::Cache::builder()
+                .max_capacity(cfg.max_sources)
+                .eviction_policy(moka::policy::EvictionPolicy::lru())
+                .build(),
+        }
+    }
+
+    /// Records a successfully processed item from the `source`, resetting its backoff.
+    pub fn record_success(&self, source: &K) {
+        self.sources.invalidate(source);
+    }
+
+    /// Records a processing error of an item from the `source`.
+    ///
+    /// Returns the backoff period the source has been put into, if any.
+    pub fn record_error(&self, source: &K) -> Option<Duration> {
+        let state = self.sources.get_with_by_ref(source, Default::default);
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.consecutive_errors = state.consecutive_errors.saturating_add(1);
+        if state.consecutive_errors <= self.cfg.error_threshold {
+            return None;
+        }
+
+        let exponent = (state.consecutive_errors - self.cfg.error_threshold - 1).min(31);
+        let backoff = self
+            .cfg
+            .initial_backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.cfg.max_backoff);
+        state.blocked_until = Some(self.clock.now() + backoff);
+
+        Some(backoff)
+    }
+
+    /// Remaining backoff period of the `source`, if it is currently backing off.
+    pub fn remaining_backoff(&self, source: &K) -> Option<Duration> {
+        self.sources
+            .get(source)?
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .blocked_until
+            .map(|until| until.saturating_duration_since(self.clock.now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Indicates whether items from the `source` should currently be delayed.
+    pub fn is_backing_off(&self, source: &K) -> bool {
+        self.remaining_backoff(source).is_some()
+    }
+}
+
+/// Stream adapter delaying the items of the sources backing off according to a [`SourceErrorBackoff`],
+/// see [`StreamErrorBackoffExt::backoff_on_source_errors`].
+pub struct SourceBackoffDelay<S: Stream, K: Eq + Hash + Send + Sync + 'static, F, C: Clock = RealClock> {
+    inner: Pin<Box<S>>,
+    inner_done: bool,
+    backoff: SourceErrorBackoff<K, C>,
+    source: F,
+    delayed: FuturesUnordered<BoxFuture<'static, (K, S::Item)>>,
+    delayed_per_source: HashMap<K, usize>,
+}
+
+// No field is ever structurally pinned.
+impl<S: Stream, K: Eq + Hash + Send + Sync + 'static, F, C: Clock> Unpin for SourceBackoffDelay<S, K, F, C> {}
+
+impl<S, K, F, C> SourceBackoffDelay<S, K, F, C>
+where
+    S: Stream,
+    S::Item: Send + 'static,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    F: Fn(&S::Item) -> K,
+    C: Clock,
+{
+    /// Delays the `item` until the backoff of its `source` expires, unless too many of its items are delayed.
+    fn delay(&mut self, source: K, item: S::Item, delay: Duration) {
+        let delayed = self.delayed_per_source.get(&source).copied().unwrap_or(0);
+        if delayed >= self.backoff.cfg.max_delayed_per_source {
+            tracing::trace!("Discarding stream item from a source in error backoff with too many delayed items");
+            return;
+        }
+
+        self.delayed_per_source.insert(source.clone(), delayed + 1);
+        self.delayed
+            .push(self.backoff.clock.sleep(delay).map(move |_| (source, item)).boxed());
+    }
+
+    fn release(&mut self, source: &K) {
+        if let std::collections::hash_map::Entry::Occupied(mut delayed) = self.delayed_per_source.entry(source.clone())
+        {
+            *delayed.get_mut() -= 1;
+            if *delayed.get() == 0 {
+                delayed.remove();
+            }
+        }
+    }
+
+    fn poll_delayed(&mut self, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        match self.delayed.poll_next_unpin(cx) {
+            Poll::Ready(Some((source, item))) => {
+                self.release(&source);
+                Poll::Ready(Some(item))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S, K, F, C> Stream for SourceBackoffDelay<S, K, F, C>
+where
+    S: Stream,
+    S::Item: Send + 'static,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    F: Fn(&S::Item) -> K,
+    C: Clock,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // The expired delays are yielded first, so that they are not starved by a busy inner stream
+        if let Poll::Ready(Some(item)) = this.poll_delayed(cx) {
+            return Poll::Ready(Some(item));
+        }
+
+        while !this.inner_done {
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let source = (this.source)(&item);
+                    match this.backoff.remaining_backoff(&source) {
+                        Some(delay) => this.delay(source, item, delay),
+                        None => return Poll::Ready(Some(item)),
+                    }
+                }
+                Poll::Ready(None) => this.inner_done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        // Polls also the newly delayed items, so that their timers are started
+        match this.poll_delayed(cx) {
+            Poll::Ready(None) if this.inner_done => Poll::Ready(None),
+            Poll::Ready(None) => Poll::Pending,
+            poll => poll,
+        }
+    }
+}
+
+/// Extension trait adding the error backoff combinator to any [`Stream`].
+pub trait StreamErrorBackoffExt: Stream + Sized {
+    /// Delays items whose source (extracted by `source`) is currently backing off according to
+    /// the given [`SourceErrorBackoff`] until the backoff expires, without holding back the items
+    /// of the other sources.
+    ///
+    /// At most [`SourceErrorBackoffConfig::max_delayed_per_source`] items of a source are delayed
+    /// at a time, its items over this limit are discarded. The delays are measured by the clock of the `backoff`.
+    fn backoff_on_source_errors<K, F, C>(
+        self,
+        backoff: SourceErrorBackoff<K, C>,
+        source: F,
+    ) -> SourceBackoffDelay<Self, K, F, C>
+    where
+        Self::Item: Send + 'static,
+        K: Eq + Hash + Clone + Send + Sync + 'static,
+        F: Fn(&Self::Item) -> K,
+        C: Clock,
+    {
+        SourceBackoffDelay {
+            inner: Box::pin(self),
+            inner_done: false,
+            backoff,
+            source,
+            delayed: FuturesUnordered::new(),
+            delayed_per_source: HashMap::new(),
+        }
+    }
+}
+
+impl<S: Stream> StreamErrorBackoffExt for S {}
+
+/// Behavior of a protocol process when sending to its wire sink fails.
+#[serde_as]
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SinkFailurePolicy {
+    /// The failure is logged and the item is dropped.
+    #[default]
+    Log,
+    /// The send is retried up to `attempts` times with the given `delay` (in milliseconds)
+    /// between the attempts, before the item is dropped.
+    RetryN {
+        attempts: u32,
+        #[serde_as(as = "DurationMilliSeconds<u64>")]
+        delay: Duration,
+    },
+    /// The process owning the sink terminates on the first failure.
+    Terminate,
+}
+
+/// Indicates that the process owning the sink must terminate, according to its [`SinkFailurePolicy`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SinkTerminated;
+
+/// Sends the `item` into the `sink`, handling a failure according to the `policy`.
+///
+/// Returns an error only if the process owning the sink must terminate.
+pub async fn send_with_policy<S, T>(
+    sink: &mut S,
+    item: T,
+    policy: SinkFailurePolicy,
+    wire: &str,
+) -> std::result::Result<(), SinkTerminated>
+where
+    S: futures::Sink<T> + Unpin,
+{
+    deliver_with_policy(sink, item, policy, wire).await.map(|_| ())
+}
+
+/// Same as [`send_with_policy`], but indicates whether the `item` has been delivered into the `sink`
+/// or dropped after the failures allowed by the `policy`.
+///
+/// The `item` is handed over only to a ready sink, so it is kept for the next attempt while
+/// the sink fails to get ready. Once handed over, a failed item is dropped without retrying.
+pub async fn deliver_with_policy<S, T>(
+    sink: &mut S,
+    item: T,
+    policy: SinkFailurePolicy,
+    wire: &str,
+) -> std::result::Result<bool, SinkTerminated>
+where
+    S: futures::Sink<T> + Unpin,
+{
+    let attempts = match policy {
+        SinkFailurePolicy::RetryN { attempts, .. } => attempts,
+        SinkFailurePolicy::Log | SinkFailurePolicy::Terminate => 0,
+    };
+
+    for attempt in 0..=attempts {
+        if futures::future::poll_fn(|cx| sink.poll_ready_unpin(cx)).await.is_ok() {
+            if sink.start_send_unpin(item).is_ok() && sink.flush().await.is_ok() {
+                return Ok(true);
+            }
+
+            return if policy == SinkFailurePolicy::Terminate {
+                tracing::error!(wire, "Failed to send to the wire sink, terminating");
+                Err(SinkTerminated)
+            } else {
+                tracing::error!(wire, attempt, "Wire sink failed to accept the item, dropping the item");
+                Ok(false)
+            };
+        }
+
+        match policy {
+            SinkFailurePolicy::Log => tracing::error!(wire, "Failed to send to the wire sink, dropping the item"),
+            SinkFailurePolicy::RetryN { delay, .. } if attempt < attempts => {
+                tracing::warn!(wire, attempt, "Failed to send to the wire sink, retrying");
+                hopr_async_runtime::prelude::sleep(delay).await;
+            }
+            SinkFailurePolicy::RetryN { .. } => {
+                tracing::error!(
+                    wire,
+                    attempts,
+                    "Failed to send to the wire sink after retries, dropping the item"
+                )
+            }
+            SinkFailurePolicy::Terminate => {
+                tracing::error!(wire, "Failed to send to the wire sink, terminating");
+                return Err(SinkTerminated);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Forwards all items of the `stream` into the `sink`, handling send failures according to the `policy`.
+///
+/// Completes once the `stream` is exhausted or the `policy` requires termination.
+pub async fn forward_with_policy<St, Si>(
+    stream: St,
+    sink: Si,
+    policy: SinkFailurePolicy,
+    wire: &str,
+) -> std::result::Result<(), SinkTerminated>
+where
+    St: Stream,
+    Si: futures::Sink<St::Item>,
+{
+    let mut stream = std::pin::pin!(stream);
+    let mut sink = std::pin::pin!(sink);
+
+    while let Some(item) = stream.next().await {
+        send_with_policy(&mut sink, item, policy, wire).await?;
+    }
+
+    Ok(())
+}
+
+/// Configuration of the [`OrderedDelivery`] stream adapter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderedDeliveryConfig {
+    /// Maximum number of sequence numbers (starting from the next expected one)
+    /// that can be buffered for a single key.
+    ///
+    /// An item beyond the window forces the missing items at the start of the window to be skipped.
+    pub window: usize,
+    /// Maximum time to wait for a missing item before it is skipped.
+    pub gap_timeout: Duration,
+}
+
+impl Default for OrderedDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            window: 128,
+            gap_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Event produced by the [`OrderedDelivery`] stream adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderedDeliveryEvent<K, T> {
+    /// Next item in sequence for its key.
+    Delivered(T),
+    /// The items with the given sequence numbers were skipped for the given key,
+    /// either because they did not arrive in time or because they did not fit the window.
+    Gap { key: K, missing: Range<u64> },
+}
+
+#[derive(Debug)]
+struct OrderedDeliveryState<T> {
+    next: u64,
+    buffer: BTreeMap<u64, T>,
+    waiting_since: Option<Instant>,
+}
+
+impl<T> Default for OrderedDeliveryState<T> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            buffer: BTreeMap::new(),
+            waiting_since: None,
+        }
+    }
+}
+
+/// Stream adapter reordering items of the underlying stream according to their sequence numbers.
+///
+/// The items are grouped by a key (e.g. the application tag of [`ApplicationData`]) and each group
+/// is delivered in sequence starting from the sequence number 0. Duplicates and items arriving
+/// after their sequence number has already been delivered or skipped are discarded.
+///
+/// Out-of-order items are buffered up to [`OrderedDeliveryConfig::window`] per key. Missing items are
+/// reported as [`OrderedDeliveryEvent::Gap`] once they are not received within
+/// [`OrderedDeliveryConfig::gap_timeout`], or when the window overflows. All the buffered items are
+/// flushed when the underlying stream terminates.
+pub struct OrderedDelivery<S: Stream, K, FK, FS, C: Clock = RealClock> {
+    inner: S,
+    inner_done: bool,
+    key: FK,
+    sequence: FS,
+    cfg: OrderedDeliveryConfig,
+    states: HashMap<K, OrderedDeliveryState<S::Item>>,
+    ready: VecDeque<OrderedDeliveryEvent<K, S::Item>>,
+    timer: Option<(Instant, BoxFuture<'static, ()>)>,
+    clock: C,
+}
+
+// The inner stream is required to be `Unpin` and no field is ever structurally pinned.
+impl<S: Stream, K, FK, FS, C: Clock> Unpin for OrderedDelivery<S, K, FK, FS, C> {}
+
+impl<S, K, FK, FS> OrderedDelivery<S, K, FK, FS>
+where
+    S: Stream + Unpin,
+    K: Eq + Hash + Clone,
+    FK: Fn(&S::Item) -> K,
+    FS: Fn(&S::Item) -> u64,
+{
+    /// Creates the adapter using `key` to group the items and `sequence` to extract their sequence numbers.
+    pub fn new(inner: S, key: FK, sequence: FS, cfg: OrderedDeliveryConfig) -> Self {
+        Self::with_clock(inner, key, sequence, cfg, RealClock)
+    }
+}
+
+impl<S, K, FK, FS, C> OrderedDelivery<S, K, FK, FS, C>
+where
+    S: Stream + Unpin,
+    K: Eq + Hash + Clone,
+    FK: Fn(&S::Item) -> K,
+    FS: Fn(&S::Item) -> u64,
+    C: Clock,
+{
+    /// Same as [`OrderedDelivery::new`], but the gap timeouts are measured using the given `clock`.
+    pub fn with_clock(inner: S, key: FK, sequence: FS, cfg: OrderedDeliveryConfig, clock: C) -> Self {
+        Self {
+            inner,
+            inner_done: false,
+            key,
+            sequence,
+            cfg: OrderedDeliveryConfig {
+                window: cfg.window.max(1),
+                ..cfg
+            },
+            states: HashMap::new(),
+            ready: VecDeque::new(),
+            timer: None,
+            clock,
+        }
+    }
+
+    fn flush_contiguous(
+        state: &mut OrderedDeliveryState<S::Item>,
+        ready: &mut VecDeque<OrderedDeliveryEvent<K, S::Item>>,
+        mut progressed: bool,
+        now: Instant,
+    ) {
+        while let Some(item) = state.buffer.remove(&state.next) {
+            ready.push_back(OrderedDeliveryEvent::Delivered(item));
+            state.next += 1;
+            progressed = true;
+        }
+
+        state.waiting_since = match state.waiting_since {
+            _ if state.buffer.is_empty() => None,
+            Some(since) if !progressed => Some(since),
+            _ => Some(now),
+        };
+    }
+
+    fn skip_to(
+        key: &K,
+        state: &mut OrderedDeliveryState<S::Item>,
+        ready: &mut VecDeque<OrderedDeliveryEvent<K, S::Item>>,
+        new_next: u64,
+        now: Instant,
+    ) {
+        if new_next <= state.next {
+            return;
+        }
+
+        let retained = state.buffer.split_off(&new_next);
+        for (seq, item) in std::mem::replace(&mut state.buffer, retained) {
+            if seq > state.next {
+                ready.push_back(OrderedDeliveryEvent::Gap {
+                    key: key.clone(),
+                    missing: state.next..seq,
+                });
+            }
+            ready.push_back(OrderedDeliveryEvent::Delivered(item));
+            state.next = seq + 1;
+        }
+
+        if new_next > state.next {
+            ready.push_back(OrderedDeliveryEvent::Gap {
+                key: key.clone(),
+                missing: state.next..new_next,
+            });
+            state.next = new_next;
+        }
+
+        Self::flush_contiguous(state, ready, true, now);
+    }
+
+    fn process_item(&mut self, item: S::Item) {
+        let key = (self.key)(&item);
+        let seq = (self.sequence)(&item);
+        let now = self.clock.now();
+        let state = self.states.entry(key.clone()).or_default();
+
+        if seq < state.next || state.buffer.contains_key(&seq) {
+            tracing::trace!(seq, next = state.next, "Discarding a late or duplicate item");
+            return;
+        }
+
+        let window = self.cfg.window as u64;
+        if seq >= state.next.saturating_add(window) {
+            Self::skip_to(&key, state, &mut self.ready, seq + 1 - window, now);
+        }
+
+        state.buffer.insert(seq, item);
+        Self::flush_contiguous(state, &mut self.ready, false, now);
+    }
+
+    fn process_expired(&mut self, now: Instant) -> bool {
+        let mut expired = false;
+        for (key, state) in self.states.iter_mut() {
+            if state
+                .waiting_since
+                .is_some_and(|since| since + self.cfg.gap_timeout <= now)
+            {
+                if let Some(first) = state.buffer.keys().next().copied() {
+                    Self::skip_to(key, state, &mut self.ready, first, now);
+                    expired = true;
+                }
+            }
+        }
+        expired
+    }
+
+    fn flush_all(&mut self) {
+        let now = self.clock.now();
+        for (key, state) in self.states.iter_mut() {
+            if let Some(last) = state.buffer.keys().next_back().copied() {
+                Self::skip_to(key, state, &mut self.ready, last + 1, now);
+            }
+        }
+    }
+}
+
+impl<S, FS> OrderedDelivery<S, Tag, fn(&ApplicationData) -> Tag, FS>
+where
+    S: Stream<Item = ApplicationData> + Unpin,
+    FS: Fn(&ApplicationData) -> u64,
+{
+    /// Creates the adapter ordering [`ApplicationData`] separately for each application tag.
+    pub fn by_application_tag(inner: S, sequence: FS, cfg: OrderedDeliveryConfig) -> Self {
+        Self::new(inner, |data| data.application_tag, sequence, cfg)
+    }
+}
+
+impl<S, K, FK, FS, C> Stream for OrderedDelivery<S, K, FK, FS, C>
+where
+    S: Stream + Unpin,
+    K: Eq + Hash + Clone,
+    FK: Fn(&S::Item) -> K,
+    FS: Fn(&S::Item) -> u64,
+    C: Clock,
+{
+    type Item = OrderedDeliveryEvent<K, S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(event) = this.ready.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+
+            if this.inner_done {
+                return Poll::Ready(None);
+            }
+
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.process_item(item);
+                    continue;
+                }
+                Poll::Ready(None) => {
+                    this.inner_done = true;
+                    this.timer = None;
+                    this.flush_all();
+                    continue;
+                }
+                Poll::Pending => {}
+            }
+
+            let now = this.clock.now();
+            if this.process_expired(now) {
+                continue;
+            }
+
+            let deadline = this
+                .states
+                .values()
+                .filter_map(|state| state.waiting_since)
+                .min()
+                .map(|since| since + this.cfg.gap_timeout);
+
+            match deadline {
+                Some(deadline) => {
+                    if this.timer.as_ref().is_none_or(|(at, _)| *at != deadline) {
+                        let sleep = this.clock.sleep(deadline.saturating_duration_since(now));
+                        this.timer = Some((deadline, sleep));
+                    }
+
+                    if let Some((_, timer)) = this.timer.as_mut() {
+                        if timer.poll_unpin(cx).is_ready() {
+                            this.timer = None;
+                            continue;
+                        }
+                    }
+                    return Poll::Pending;
+                }
+                None => {
+                    this.timer = None;
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+/// Priority of an item scheduled by the [`PriorityScheduler`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Priority {
+    /// Latency-sensitive items (e.g. control messages) jumping the queue ahead of the normal ones.
+    High,
+    /// Bulk items.
+    #[default]
+    Normal,
+}
+
+fn default_priority_scheduler_capacity() -> usize {
+    1024
+}
+
+fn default_max_high_in_row() -> usize {
+    8
+}
+
+/// Configuration of the [`PriorityScheduler`] stream adapter.
+#[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PrioritySchedulerConfig {
+    /// Maximum number of items buffered ahead of the consumer of the scheduler.
+    ///
+    /// Once reached, the underlying stream is not polled until the consumer catches up.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_priority_scheduler_capacity")]
+    #[default(default_priority_scheduler_capacity())]
+    pub capacity: usize,
+    /// Maximum number of [`Priority::High`] items yielded in a row while [`Priority::Normal`] items are waiting.
+    ///
+    /// After that many, one waiting normal priority item is yielded before the high priority ones continue.
+    #[serde(default = "default_max_high_in_row")]
+    #[default(default_max_high_in_row())]
+    pub max_high_in_row: usize,
+}
+
+/// Stream adapter yielding the items of the underlying stream of `(Priority, item)` pairs by their priority.
+///
+/// The items are pulled from the underlying stream as long as they are available (up to
+/// [`PrioritySchedulerConfig::capacity`]) into one queue per priority. Whenever the consumer asks for an item,
+/// the oldest [`Priority::High`] item is yielded first, so the high priority items jump ahead of all the
+/// [`Priority::Normal`] items buffered while the consumer was busy. Items of the same priority keep their order.
+///
+/// To avoid starving the normal priority items under a constant flow of high priority ones, at most
+/// [`PrioritySchedulerConfig::max_high_in_row`] high priority items are yielded in a row while a normal
+/// priority item is waiting. The normal priority items are therefore guaranteed at least
+/// `1 / (max_high_in_row + 1)` of the throughput of the consumer.
+pub struct PriorityScheduler<S, T> {
+    inner: S,
+    inner_done: bool,
+    high: VecDeque<T>,
+    normal: VecDeque<T>,
+    high_in_row: usize,
+    cfg: PrioritySchedulerConfig,
+}
+
+// The inner stream is required to be `Unpin` and no field is ever structurally pinned.
+impl<S, T> Unpin for PriorityScheduler<S, T> {}
+
+impl<S, T> PriorityScheduler<S, T>
+where
+    S: Stream<Item = (Priority, T)> + Unpin,
+{
+    pub fn new(inner: S, cfg: PrioritySchedulerConfig) -> Self {
+        Self {
+            inner,
+            inner_done: false,
+            high: VecDeque::new(),
+            normal: VecDeque::new(),
+            high_in_row: 0,
+            cfg: PrioritySchedulerConfig {
+                capacity: cfg.capacity.max(1),
+                ..cfg
+            },
+        }
+    }
+
+    fn buffered_len(&self) -> usize {
+        self.high.len() + self.normal.len()
+    }
+
+    fn next_item(&mut self) -> Option<T> {
+        let starving = !self.normal.is_empty() && self.high_in_row >= self.cfg.max_high_in_row;
+        if !starving {
+            if let Some(item) = self.high.pop_front() {
+                self.high_in_row += 1;
+                return Some(item);
+            }
+        }
+
+        self.high_in_row = 0;
+        self.normal.pop_front()
+    }
+}
+
+impl<S, T> Stream for PriorityScheduler<S, T>
+where
+    S: Stream<Item = (Priority, T)> + Unpin,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while !this.inner_done && this.buffered_len() < this.cfg.capacity {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some((Priority::High, item))) => this.high.push_back(item),
+                Poll::Ready(Some((Priority::Normal, item))) => this.normal.push_back(item),
+                Poll::Ready(None) => this.inner_done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        match this.next_item() {
+            Some(item) => Poll::Ready(Some(item)),
+            None if this.inner_done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Extension trait adding the [`PriorityScheduler`] to any [`Stream`] of `(Priority, item)` pairs.
+pub trait StreamPriorityExt<T>: Stream<Item = (Priority, T)> + Sized + Unpin {
+    /// Yields the items by their [`Priority`], see [`PriorityScheduler`].
+    fn prioritized(self, cfg: PrioritySchedulerConfig) -> PriorityScheduler<Self, T> {
+        PriorityScheduler::new(self, cfg)
+    }
+}
+
+impl<S: Stream<Item = (Priority, T)> + Unpin, T> StreamPriorityExt<T> for S {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Context;
     use futures::SinkExt;
+    use hopr_async_runtime::clock::MockClock;
 
     struct AsyncBinaryStreamChannel {
         read: async_channel_io::ChannelReader,
@@ -223,4 +1056,485 @@ mod tests {
 
         Ok(())
     }
+
+    fn test_backoff_config() -> SourceErrorBackoffConfig {
+        SourceErrorBackoffConfig {
+            error_threshold: 2,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_millis(150),
+            max_sources: 2,
+            max_delayed_per_source: 2,
+        }
+    }
+
+    #[test]
+    fn source_error_backoff_should_increase_exponentially_up_to_the_maximum() {
+        let backoff = SourceErrorBackoff::new(test_backoff_config());
+
+        assert_eq!(None, backoff.record_error(&1));
+        assert_eq!(None, backoff.record_error(&1));
+        assert!(!backoff.is_backing_off(&1));
+
+        assert_eq!(Some(Duration::from_millis(50)), backoff.record_error(&1));
+        assert_eq!(Some(Duration::from_millis(100)), backoff.record_error(&1));
+        assert_eq!(Some(Duration::from_millis(150)), backoff.record_error(&1));
+        assert!(backoff.is_backing_off(&1));
+        assert!(!backoff.is_backing_off(&2), "other sources must not be affected");
+
+        backoff.record_success(&1);
+        assert!(!backoff.is_backing_off(&1));
+        assert_eq!(None, backoff.record_error(&1));
+    }
+
+    #[test]
+    fn source_error_backoff_should_track_a_bounded_number_of_sources() {
+        let backoff = SourceErrorBackoff::new(test_backoff_config());
+
+        for source in 0..100 {
+            backoff.record_error(&source);
+        }
+        backoff.sources.run_pending_tasks();
+
+        assert!(backoff.sources.entry_count() <= test_backoff_config().max_sources);
+    }
+
+    #[async_std::test]
+    async fn backoff_on_source_errors_should_delay_items_from_failing_source_until_backoff_expires(
+    ) -> anyhow::Result<()> {
+        let backoff = SourceErrorBackoff::new(test_backoff_config());
+        for _ in 0..3 {
+            backoff.record_error(&"bad");
+        }
+
+        let start = Instant::now();
+        let mut items = futures::stream::iter(vec![("bad", 1), ("good", 2), ("bad", 3), ("bad", 4)])
+            .backoff_on_source_errors(backoff.clone(), |(source, _)| *source);
+
+        assert_eq!(
+            Some(("good", 2)),
+            items.next().await,
+            "other sources must not be held back"
+        );
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        let mut delayed = items.collect::<Vec<_>>().await;
+        delayed.sort();
+        assert_eq!(
+            vec![("bad", 1), ("bad", 3)],
+            delayed,
+            "items over the delay capacity must be discarded"
+        );
+        assert!(start.elapsed() >= Duration::from_millis(45), "items must be delayed");
+
+        let start = Instant::now();
+        let items = futures::stream::iter(vec![("bad", 5)])
+            .backoff_on_source_errors(backoff, |(source, _)| *source)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(vec![("bad", 5)], items);
+        assert!(
+            start.elapsed() < Duration::from_millis(45),
+            "expired backoff must not delay"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn backoff_on_source_errors_should_measure_the_delay_using_the_given_clock() -> anyhow::Result<()> {
+        let clock = MockClock::default();
+        let backoff = SourceErrorBackoff::with_clock(test_backoff_config(), clock.clone());
+        for _ in 0..3 {
+            backoff.record_error(&"bad");
+        }
+
+        let mut items = futures::stream::iter(vec![("bad", 1), ("good", 2)])
+            .backoff_on_source_errors(backoff.clone(), |(source, _)| *source);
+
+        assert_eq!(Some(("good", 2)), items.next().await);
+        assert!(
+            futures::poll!(items.next()).is_pending(),
+            "item must be delayed until the clock advances"
+        );
+
+        clock.advance(Duration::from_millis(50));
+        assert!(!backoff.is_backing_off(&"bad"), "backoff must expire with the clock");
+        assert_eq!(vec![("bad", 1)], items.collect::<Vec<_>>().await);
+
+        Ok(())
+    }
+
+    fn sequenced_data(tag: Tag, seq: u64) -> ApplicationData {
+        ApplicationData::new(tag, &seq.to_be_bytes())
+    }
+
+    fn sequence_of(data: &ApplicationData) -> u64 {
+        u64::from_be_bytes(data.plain_text[..8].try_into().expect("must contain a sequence number"))
+    }
+
+    fn delivered(tag: Tag, seq: u64) -> OrderedDeliveryEvent<Tag, ApplicationData> {
+        OrderedDeliveryEvent::Delivered(sequenced_data(tag, seq))
+    }
+
+    #[async_std::test]
+    async fn ordered_delivery_should_pass_through_items_in_order() -> anyhow::Result<()> {
+        let input = futures::stream::iter(vec![sequenced_data(1, 0), sequenced_data(2, 0), sequenced_data(1, 1)]);
+
+        let events = OrderedDelivery::by_application_tag(input, sequence_of, OrderedDeliveryConfig::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(vec![delivered(1, 0), delivered(2, 0), delivered(1, 1)], events);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ordered_delivery_should_reorder_and_deduplicate_items_within_window() -> anyhow::Result<()> {
+        let input = futures::stream::iter(
+            [2, 0, 3, 0, 1, 2]
+                .into_iter()
+                .map(|seq| sequenced_data(1, seq))
+                .collect::<Vec<_>>(),
+        );
+
+        let events = OrderedDelivery::by_application_tag(input, sequence_of, OrderedDeliveryConfig::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            vec![delivered(1, 0), delivered(1, 1), delivered(1, 2), delivered(1, 3)],
+            events
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ordered_delivery_should_skip_missing_items_on_window_overflow() -> anyhow::Result<()> {
+        let (mut tx, rx) = futures::channel::mpsc::unbounded();
+        let cfg = OrderedDeliveryConfig {
+            window: 3,
+            gap_timeout: Duration::from_secs(60),
+        };
+        let mut ordered = OrderedDelivery::by_application_tag(rx, sequence_of, cfg);
+
+        for seq in [0, 2, 3, 5] {
+            tx.send(sequenced_data(1, seq)).await?;
+        }
+
+        assert_eq!(Some(delivered(1, 0)), ordered.next().await);
+        assert_eq!(
+            Some(OrderedDeliveryEvent::Gap { key: 1, missing: 1..2 }),
+            ordered.next().await
+        );
+        assert_eq!(Some(delivered(1, 2)), ordered.next().await);
+        assert_eq!(Some(delivered(1, 3)), ordered.next().await);
+
+        // 5 still fits the window starting at 4
+        assert!(async_std::future::timeout(Duration::from_millis(20), ordered.next())
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ordered_delivery_should_emit_gap_after_timeout() -> anyhow::Result<()> {
+        let (mut tx, rx) = futures::channel::mpsc::unbounded();
+        let cfg = OrderedDeliveryConfig {
+            window: 10,
+            gap_timeout: Duration::from_millis(50),
+        };
+        let mut ordered = OrderedDelivery::by_application_tag(rx, sequence_of, cfg);
+
+        tx.send(sequenced_data(1, 0)).await?;
+        tx.send(sequenced_data(1, 2)).await?;
+        tx.send(sequenced_data(1, 3)).await?;
+
+        assert_eq!(Some(delivered(1, 0)), ordered.next().await);
+
+        let start = Instant::now();
+        assert_eq!(
+            Some(OrderedDeliveryEvent::Gap { key: 1, missing: 1..2 }),
+            ordered.next().await
+        );
+        assert!(
+            start.elapsed() >= Duration::from_millis(40),
+            "gap must not be reported before the timeout"
+        );
+        assert_eq!(Some(delivered(1, 2)), ordered.next().await);
+        assert_eq!(Some(delivered(1, 3)), ordered.next().await);
+
+        tx.send(sequenced_data(1, 1)).await?;
+        drop(tx);
+        assert_eq!(None, ordered.next().await, "late item must be discarded");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn ordered_delivery_should_measure_the_gap_timeout_using_the_given_clock() -> anyhow::Result<()> {
+        let (mut tx, rx) = futures::channel::mpsc::unbounded();
+        let cfg = OrderedDeliveryConfig {
+            window: 10,
+            gap_timeout: Duration::from_secs(60),
+        };
+        let clock = MockClock::default();
+        let mut ordered = OrderedDelivery::with_clock(
+            rx,
+            |data: &ApplicationData| data.application_tag,
+            sequence_of,
+            cfg,
+            clock.clone(),
+        );
+
+        tx.send(sequenced_data(1, 0)).await?;
+        tx.send(sequenced_data(1, 2)).await?;
+
+        assert_eq!(Some(delivered(1, 0)), ordered.next().await);
+        assert!(
+            futures::poll!(ordered.next()).is_pending(),
+            "gap must not be reported before the timeout"
+        );
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(
+            Some(OrderedDeliveryEvent::Gap { key: 1, missing: 1..2 }),
+            ordered.next().await
+        );
+        assert_eq!(Some(delivered(1, 2)), ordered.next().await);
+
+        Ok(())
+    }
+
+    /// Sink that permanently fails to accept any item, counting the attempts.
+    #[derive(Clone, Default)]
+    struct FailingSink {
+        attempts: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl<T> futures::Sink<T> for FailingSink {
+        type Error = ();
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Poll::Ready(Err(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, _item: T) -> Result<(), Self::Error> {
+            unreachable!("the item must not be handed over to a sink that is not ready")
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn random_acks(count: usize) -> Vec<(PeerId, hopr_internal_types::protocol::Acknowledgement)> {
+        use hopr_crypto_types::keypairs::{Keypair, OffchainKeypair};
+        let kp = OffchainKeypair::random();
+        (0..count)
+            .map(|_| {
+                (
+                    PeerId::random(),
+                    hopr_internal_types::protocol::Acknowledgement::random(&kp),
+                )
+            })
+            .collect()
+    }
+
+    #[async_std::test]
+    async fn forward_with_log_policy_should_drop_items_on_permanently_failing_ack_sink() {
+        let sink = FailingSink::default();
+
+        let res = forward_with_policy(
+            futures::stream::iter(random_acks(3)),
+            sink.clone(),
+            SinkFailurePolicy::Log,
+            "ack",
+        )
+        .await;
+
+        assert_eq!(Ok(()), res, "process must keep running until the stream ends");
+        assert_eq!(3, sink.attempts.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[async_std::test]
+    async fn forward_with_retry_policy_should_retry_before_giving_up_on_permanently_failing_ack_sink() {
+        let sink = FailingSink::default();
+        let policy = SinkFailurePolicy::RetryN {
+            attempts: 2,
+            delay: Duration::from_millis(10),
+        };
+
+        let start = Instant::now();
+        let res = forward_with_policy(futures::stream::iter(random_acks(3)), sink.clone(), policy, "ack").await;
+
+        assert_eq!(Ok(()), res, "process must keep running until the stream ends");
+        assert_eq!(9, sink.attempts.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(start.elapsed() >= Duration::from_millis(60), "retries must be delayed");
+    }
+
+    #[async_std::test]
+    async fn forward_with_terminate_policy_should_exit_on_permanently_failing_ack_sink() -> anyhow::Result<()> {
+        let sink = FailingSink::default();
+
+        // The stream never ends, so only the termination can complete the process
+        let process = hopr_async_runtime::prelude::spawn(forward_with_policy(
+            futures::stream::iter(random_acks(3)).chain(futures::stream::pending()),
+            sink.clone(),
+            SinkFailurePolicy::Terminate,
+            "ack",
+        ));
+
+        let res = async_std::future::timeout(Duration::from_secs(1), process)
+            .await
+            .context("process must terminate")?;
+
+        assert_eq!(Err(SinkTerminated), res);
+        assert_eq!(1, sink.attempts.load(std::sync::atomic::Ordering::SeqCst));
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deliver_with_policy_should_indicate_whether_the_item_was_delivered() {
+        let sink = FailingSink::default();
+        let policy = SinkFailurePolicy::RetryN {
+            attempts: 1,
+            delay: Duration::from_millis(1),
+        };
+        let item = random_acks(1).remove(0);
+
+        assert_eq!(
+            Ok(false),
+            deliver_with_policy(&mut sink.clone(), item.clone(), policy, "ack").await
+        );
+        assert_eq!(2, sink.attempts.load(std::sync::atomic::Ordering::SeqCst));
+
+        let (mut tx, mut rx) = futures::channel::mpsc::unbounded();
+        assert_eq!(
+            Ok(true),
+            deliver_with_policy(&mut tx, item.clone(), policy, "ack").await
+        );
+        assert_eq!(Some(item), rx.next().await);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct NonCloneItem(u32);
+
+    /// Sink failing to get ready the given number of times before accepting the items.
+    #[derive(Default)]
+    struct FlakySink {
+        not_ready: usize,
+        accepted: Vec<NonCloneItem>,
+    }
+
+    impl futures::Sink<NonCloneItem> for FlakySink {
+        type Error = ();
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            let this = self.get_mut();
+            if this.not_ready > 0 {
+                this.not_ready -= 1;
+                Poll::Ready(Err(()))
+            } else {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: NonCloneItem) -> Result<(), Self::Error> {
+            self.get_mut().accepted.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[async_std::test]
+    async fn deliver_with_policy_should_retry_with_the_same_item_until_the_sink_is_ready() {
+        let mut sink = FlakySink {
+            not_ready: 2,
+            ..Default::default()
+        };
+        let policy = SinkFailurePolicy::RetryN {
+            attempts: 2,
+            delay: Duration::from_millis(1),
+        };
+
+        assert_eq!(
+            Ok(true),
+            deliver_with_policy(&mut sink, NonCloneItem(1), policy, "msg").await
+        );
+        assert_eq!(vec![NonCloneItem(1)], sink.accepted);
+    }
+
+    #[async_std::test]
+    async fn priority_scheduler_should_yield_high_priority_items_first() {
+        let items = vec![
+            (Priority::Normal, 1),
+            (Priority::Normal, 2),
+            (Priority::High, 3),
+            (Priority::Normal, 4),
+            (Priority::High, 5),
+        ];
+
+        let scheduled = futures::stream::iter(items)
+            .prioritized(PrioritySchedulerConfig::default())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(vec![3, 5, 1, 2, 4], scheduled);
+    }
+
+    #[async_std::test]
+    async fn priority_scheduler_should_not_starve_normal_priority_items() {
+        let items = (0..2)
+            .map(|i| (Priority::Normal, 100 + i))
+            .chain((0..7).map(|i| (Priority::High, i)));
+
+        let scheduled = futures::stream::iter(items)
+            .prioritized(PrioritySchedulerConfig {
+                max_high_in_row: 3,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(vec![0, 1, 2, 100, 3, 4, 5, 101, 6], scheduled);
+    }
+
+    #[async_std::test]
+    async fn priority_scheduler_should_only_reorder_buffered_items() -> anyhow::Result<()> {
+        let (mut tx, rx) = futures::channel::mpsc::unbounded();
+        let mut scheduled = rx.prioritized(PrioritySchedulerConfig {
+            capacity: 2,
+            ..Default::default()
+        });
+
+        tx.send((Priority::Normal, 1)).await?;
+        assert_eq!(
+            Some(1),
+            scheduled.next().await,
+            "a lone item must be yielded right away"
+        );
+
+        for item in [(Priority::Normal, 2), (Priority::Normal, 3), (Priority::High, 4)] {
+            tx.send(item).await?;
+        }
+        tx.close_channel();
+
+        // The high priority item is beyond the capacity when the first item is yielded
+        assert_eq!(vec![2, 4, 3], scheduled.collect::<Vec<_>>().await);
+
+        Ok(())
+    }
 }
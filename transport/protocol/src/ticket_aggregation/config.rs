@@ -1,9 +1,46 @@
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DurationSeconds};
+use serde_with::{serde_as, DurationMilliSeconds, DurationSeconds};
 use validator::Validate;
 
+use crate::ticket_aggregation::wire::DEFAULT_MAX_AGGREGATION_REQUEST_BYTES;
+
+fn default_min_aggregatable_tickets() -> u32 {
+    1
+}
+
+fn default_max_request_bytes() -> usize {
+    DEFAULT_MAX_AGGREGATION_REQUEST_BYTES
+}
+
+fn default_max_concurrent_aggregations() -> usize {
+    16
+}
+
+/// Handling of the aggregations exceeding the [maximum number of concurrent aggregations](TicketAggregationProtocolConfig::max_concurrent_aggregations).
+#[serde_as]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationBusyPolicy {
+    /// The aggregation waits for the others to finish for at most the given `timeout` (in milliseconds),
+    /// before it is refused as busy.
+    Queue {
+        #[serde_as(as = "DurationMilliSeconds<u64>")]
+        timeout: Duration,
+    },
+    /// The aggregation is refused as busy right away.
+    Reject,
+}
+
+impl Default for AggregationBusyPolicy {
+    fn default() -> Self {
+        Self::Queue {
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
 /// Configuration for the `ticket_aggregation` protocol.
 #[serde_as]
 #[derive(Debug, Copy, Clone, smart_default::SmartDefault, Validate, Serialize, Deserialize, Eq, PartialEq)]
@@ -12,4 +49,35 @@ pub struct TicketAggregationProtocolConfig {
     #[serde_as(as = "DurationSeconds<u64>")]
     #[default(Duration::from_secs(15))]
     pub timeout: Duration,
+    /// Minimum number of tickets in a single aggregation request.
+    ///
+    /// Requests with fewer tickets are not sent by this node when it is the requester,
+    /// and are refused with a [below minimum error](super::processor::below_minimum_error)
+    /// when it is the responder. The default of 1 imposes no minimum.
+    #[serde(default = "default_min_aggregatable_tickets")]
+    #[default(default_min_aggregatable_tickets())]
+    pub min_aggregatable_tickets: u32,
+    /// Maximum size of an aggregation request sent by this node in bytes.
+    ///
+    /// Requests [estimated](super::wire::estimated_wire_size) to be larger are not sent,
+    /// and the aggregation fails with [RequestTooLarge](crate::errors::AggregationError::RequestTooLarge).
+    /// The default matches the maximum request size of the transport.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_max_request_bytes")]
+    #[default(default_max_request_bytes())]
+    pub max_request_bytes: usize,
+    /// Maximum number of aggregations performed at the same time with all the peers,
+    /// counting both the requests sent by this node and the requests it responds to.
+    ///
+    /// A sent request counts until its aggregated ticket is received or it is given up on.
+    #[validate(range(min = 1))]
+    #[serde(default = "default_max_concurrent_aggregations")]
+    #[default(default_max_concurrent_aggregations())]
+    pub max_concurrent_aggregations: usize,
+    /// Handling of the aggregations over the maximum number of concurrent aggregations.
+    ///
+    /// The sent requests refused as busy fail with [Busy](crate::errors::AggregationError::Busy),
+    /// the received ones are refused with a [busy error](super::processor::BUSY_ERROR).
+    #[serde(default)]
+    pub busy_policy: AggregationBusyPolicy,
 }
@@ -1,2 +1,4 @@
 pub mod config;
 pub mod processor;
+pub mod selection;
+pub mod wire;
@@ -1,3 +1,4 @@
+use async_lock::{Semaphore, SemaphoreGuardArc};
 use futures::channel::mpsc;
 use futures::channel::mpsc::UnboundedSender;
 use futures::stream::{Stream, StreamExt};
@@ -8,25 +9,31 @@ use futures::{
 };
 use libp2p::request_response::{OutboundRequestId, ResponseChannel};
 use rust_stream_ext_concurrent::then_concurrent::StreamThenConcurrentExt;
-use std::{pin::Pin, task::Poll};
-use tracing::{error, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::{ops::Bound, pin::Pin, sync::Arc, task::Poll};
+use tracing::{debug, error, info, warn};
 
 use hopr_async_runtime::prelude::{sleep, spawn};
 use hopr_crypto_types::prelude::*;
 use hopr_db_api::{
     errors::DbError,
-    tickets::{AggregationPrerequisites, HoprDbTicketOperations},
+    tickets::{AggregationPrerequisites, HoprDbTicketOperations, TicketSelector},
 };
 use hopr_internal_types::prelude::*;
 use hopr_transport_identity::PeerId;
 
 use crate::errors::{
-    ProtocolError::{Retry, TransportError},
+    AggregationError,
+    ProtocolError::{Aggregation, AggregationCanceled, Retry, Timeout, TransportError},
     Result,
 };
+use crate::ticket_aggregation::config::{AggregationBusyPolicy, TicketAggregationProtocolConfig};
+use crate::ticket_aggregation::selection::{AllTickets, TicketSelection};
+use crate::ticket_aggregation::wire::estimated_request_size;
 
 #[cfg(all(feature = "prometheus", not(test)))]
-use hopr_metrics::metrics::SimpleCounter;
+use hopr_metrics::metrics::{MultiCounter, SimpleCounter};
 
 #[cfg(all(feature = "prometheus", not(test)))]
 lazy_static::lazy_static! {
@@ -40,12 +47,50 @@ lazy_static::lazy_static! {
         "Number of performed ticket aggregations"
     )
     .unwrap();
+    static ref METRIC_AGGREGATION_RESULT_COUNT: MultiCounter = MultiCounter::new(
+        "hopr_ticket_aggregation_count",
+        "Number of ticket aggregation outcomes by the role of this node",
+        &["role", "result"]
+    )
+    .unwrap();
 }
 
 // Default sizes of the acknowledgement queues
 pub const TICKET_AGGREGATION_TX_QUEUE_SIZE: usize = 2048;
 pub const TICKET_AGGREGATION_RX_QUEUE_SIZE: usize = 2048;
 
+/// Prefix of the error sent by the responder when the aggregation request contains
+/// fewer tickets than its configured minimum.
+pub const BELOW_MINIMUM_ERROR: &str = "below minimum";
+
+/// Error sent by the responder when it is performing too many aggregations to respond to the request,
+/// see [max_concurrent_aggregations](TicketAggregationProtocolConfig::max_concurrent_aggregations).
+pub const BUSY_ERROR: &str = "busy";
+
+/// Error sent by the responder when the aggregation request has been canceled by the requester.
+///
+/// The requester cancels its in-progress request with a separate notice,
+/// see [receive_aggregation_cancel](TicketAggregationActions::receive_aggregation_cancel).
+pub const CANCELED_ERROR: &str = "canceled";
+
+/// Creates the error sent by the responder when the aggregation request contains fewer than `min_tickets` tickets.
+///
+/// The minimum is carried in the error, so that it can be extracted by [parse_below_minimum_error].
+pub fn below_minimum_error(min_tickets: u32) -> String {
+    format!("{BELOW_MINIMUM_ERROR}: {min_tickets}")
+}
+
+/// Extracts the minimum ticket count of the responder from the error created by [below_minimum_error].
+///
+/// Returns `None` if the error has been caused by something else.
+pub fn parse_below_minimum_error(error: &str) -> Option<u32> {
+    error
+        .strip_prefix(BELOW_MINIMUM_ERROR)?
+        .strip_prefix(": ")?
+        .parse()
+        .ok()
+}
+
 /// The input to the processor background pipeline
 #[allow(clippy::type_complexity)] // TODO: The type needs to be significantly refactored to easily move around
 #[allow(clippy::large_enum_variant)] // TODO: refactor the large types used in the enum
@@ -54,6 +99,8 @@ pub enum TicketAggregationToProcess<T, U> {
     ToReceive(PeerId, std::result::Result<Ticket, String>, U),
     ToProcess(PeerId, Vec<TransferableWinningTicket>, T),
     ToSend(Hash, AggregationPrerequisites, TicketAggregationFinalizer),
+    ToRollback(Hash),
+    ToCancel(PeerId),
 }
 
 /// Emitted by the processor background pipeline once processed
@@ -63,6 +110,11 @@ pub enum TicketAggregationProcessed<T, U> {
     Receive(PeerId, AcknowledgedTicket, U),
     Reply(PeerId, std::result::Result<Ticket, String>, T),
     Send(PeerId, Vec<TransferableWinningTicket>, TicketAggregationFinalizer),
+    /// The counterparty refused to aggregate the tickets of the request sent to it.
+    ///
+    /// The aggregation should be [rolled back](TicketAggregationActions::rollback_refused) and the request
+    /// [failed](TicketAggregationFinalizer::fail) with the given error.
+    Refused(PeerId, AggregationError, U),
 }
 
 #[async_trait::async_trait]
@@ -124,22 +176,34 @@ where
     async fn aggregate_tickets(&self, channel: &Hash, prerequisites: AggregationPrerequisites) -> Result<()> {
         let awaiter = self.writer.clone().aggregate_tickets(channel, prerequisites)?;
 
-        if let Err(e) = awaiter.consume_and_wait(self.agg_timeout).await {
-            warn!(%channel, error = %e, "Error during ticket aggregation, performing a rollback");
-            self.db.rollback_aggregation_in_channel(*channel).await?;
-        }
+        match awaiter.consume_and_wait(self.agg_timeout).await {
+            // The request was not sent or was refused, and the aggregation has been rolled back already
+            Err(e @ Aggregation(_)) => Err(e),
+            Err(e) => {
+                #[cfg(all(feature = "prometheus", not(test)))]
+                if matches!(e, Timeout) {
+                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "timeout"]);
+                }
 
-        Ok(())
+                warn!(%channel, error = %e, "Error during ticket aggregation, performing a rollback");
+                self.db.rollback_aggregation_in_channel(*channel).await?;
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
     }
 }
 
+/// Outcome of an outgoing aggregation request signalled by the [TicketAggregationFinalizer].
+pub type TicketAggregationOutcome = std::result::Result<(), AggregationError>;
+
 #[derive(Debug)]
 pub struct TicketAggregationAwaiter {
-    rx: mpsc::UnboundedReceiver<()>,
+    rx: mpsc::UnboundedReceiver<TicketAggregationOutcome>,
 }
 
-impl From<mpsc::UnboundedReceiver<()>> for TicketAggregationAwaiter {
-    fn from(value: mpsc::UnboundedReceiver<()>) -> Self {
+impl From<mpsc::UnboundedReceiver<TicketAggregationOutcome>> for TicketAggregationAwaiter {
+    fn from(value: mpsc::UnboundedReceiver<TicketAggregationOutcome>) -> Self {
         Self { rx: value }
     }
 }
@@ -151,26 +215,182 @@ impl TicketAggregationAwaiter {
 
         pin_mut!(resolve, timeout);
         match futures::future::select(resolve, timeout).await {
-            Either::Left((result, _)) => result.ok_or(TransportError("Canceled".to_owned())),
-            Either::Right(_) => Err(TransportError("Timed out on sending a packet".to_owned())),
+            Either::Left((Some(Err(AggregationError::Canceled)), _)) => Err(AggregationCanceled),
+            Either::Left((Some(outcome), _)) => outcome.map_err(Aggregation),
+            Either::Left((None, _)) => Err(TransportError("Canceled".to_owned())),
+            Either::Right(_) => Err(Timeout),
         }
     }
 }
 
+/// Global limit of the ticket aggregations performed at the same time by this node in both roles.
+#[derive(Debug, Clone)]
+struct AggregationLimit {
+    permits: Arc<Semaphore>,
+    busy_policy: AggregationBusyPolicy,
+}
+
+impl AggregationLimit {
+    fn new(max_concurrent: usize, busy_policy: AggregationBusyPolicy) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            busy_policy,
+        }
+    }
+
+    /// Acquires a permit to perform an aggregation, returns `None` if the aggregation is refused as busy.
+    async fn acquire(&self) -> Option<SemaphoreGuardArc> {
+        if let Some(permit) = self.permits.try_acquire_arc() {
+            return Some(permit);
+        }
+
+        match self.busy_policy {
+            AggregationBusyPolicy::Reject => None,
+            AggregationBusyPolicy::Queue { timeout } => {
+                let permit = self.permits.acquire_arc();
+                let timeout = sleep(timeout);
+                pin_mut!(permit, timeout);
+                match futures::future::select(permit, timeout).await {
+                    Either::Left((permit, _)) => Some(permit),
+                    Either::Right(_) => None,
+                }
+            }
+        }
+    }
+}
+
+const REQUEST_PENDING: u8 = 0;
+const REQUEST_RESOLVED: u8 = 1;
+const REQUEST_CANCELED: u8 = 2;
+
+type CancelHook = Box<dyn FnOnce() + Send>;
+
+/// State of an outgoing aggregation request shared by its finalizer and cancel handle.
+#[derive(Default)]
+struct RequestState {
+    state: AtomicU8,
+    on_cancel: std::sync::Mutex<Option<CancelHook>>,
+}
+
+impl RequestState {
+    fn lock_hook(&self) -> std::sync::MutexGuard<'_, Option<CancelHook>> {
+        self.on_cancel.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_canceled(&self) -> bool {
+        self.state.load(Ordering::Acquire) == REQUEST_CANCELED
+    }
+
+    /// Marks the request as resolved, returns `false` if it has been resolved or canceled already.
+    fn resolve(&self) -> bool {
+        self.state
+            .compare_exchange(REQUEST_PENDING, REQUEST_RESOLVED, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Marks the request as canceled and runs the cancel hook,
+    /// returns `false` if it has been resolved or canceled already.
+    fn cancel(&self) -> bool {
+        let hook = {
+            let mut hook = self.lock_hook();
+            if self
+                .state
+                .compare_exchange(REQUEST_PENDING, REQUEST_CANCELED, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+            {
+                return false;
+            }
+            hook.take()
+        };
+
+        if let Some(hook) = hook {
+            hook();
+        }
+        true
+    }
+}
+
+impl std::fmt::Debug for RequestState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestState")
+            .field("state", &self.state.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TicketAggregationFinalizer {
-    tx: Option<UnboundedSender<()>>,
+    tx: Option<UnboundedSender<TicketAggregationOutcome>>,
+    /// Permit of the aggregation held until the finalizer is dropped.
+    permit: Option<Arc<SemaphoreGuardArc>>,
+    channel: Option<Hash>,
+    state: Arc<RequestState>,
 }
 
 impl TicketAggregationFinalizer {
-    pub fn new(tx: UnboundedSender<()>) -> Self {
-        Self { tx: Some(tx) }
+    pub fn new(tx: UnboundedSender<TicketAggregationOutcome>) -> Self {
+        Self {
+            tx: Some(tx),
+            permit: None,
+            channel: None,
+            state: Arc::new(RequestState::default()),
+        }
+    }
+
+    /// Channel whose tickets are aggregated by the request, known once the request is being prepared.
+    pub fn channel(&self) -> Option<Hash> {
+        self.channel
+    }
+
+    /// Same as [TicketAggregationFinalizer::new], but the request can be abandoned using
+    /// the returned [TicketAggregationCancelHandle].
+    pub fn new_cancellable(tx: UnboundedSender<TicketAggregationOutcome>) -> (Self, TicketAggregationCancelHandle) {
+        let finalizer = Self::new(tx.clone());
+        let handle = TicketAggregationCancelHandle {
+            tx,
+            state: finalizer.state.clone(),
+        };
+        (finalizer, handle)
     }
 
-    pub fn finalize(mut self) {
+    /// Indicates whether the request has been canceled via its [TicketAggregationCancelHandle].
+    ///
+    /// A canceled request should not be sent and its response should be discarded.
+    pub fn is_canceled(&self) -> bool {
+        self.state.is_canceled()
+    }
+
+    /// Sets the `hook` invoked once the request is canceled after it has been sent.
+    ///
+    /// The transport uses it to roll back the aggregation and notify the counterparty. The `hook` is
+    /// invoked right away if the request has been canceled already, and never if it gets resolved first.
+    pub fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) {
+        let mut on_cancel = self.state.lock_hook();
+        if self.state.is_canceled() {
+            drop(on_cancel);
+            hook();
+        } else {
+            *on_cancel = Some(Box::new(hook));
+        }
+    }
+
+    pub fn finalize(self) {
+        if self.state.resolve() {
+            self.notify(Ok(()))
+        }
+    }
+
+    /// Notifies the awaiter that the aggregation request was not sent or was refused due to the given `error`.
+    pub fn fail(self, error: AggregationError) {
+        if self.state.resolve() {
+            self.notify(Err(error))
+        }
+    }
+
+    fn notify(mut self, outcome: TicketAggregationOutcome) {
         if let Some(sender) = self.tx.take() {
-            if sender.unbounded_send(()).is_err() {
-                error!("Failed to notify the awaiter about the successful ticket aggregation")
+            if sender.unbounded_send(outcome).is_err() {
+                error!("Failed to notify the awaiter about the outcome of the ticket aggregation")
             }
         } else {
             error!("Sender for packet send signalization is already spent")
@@ -178,6 +398,33 @@ impl TicketAggregationFinalizer {
     }
 }
 
+/// Cancels an outgoing aggregation request when invoked or dropped.
+///
+/// The awaiter of the request stops waiting right away with [ProtocolError::AggregationCanceled](crate::errors::ProtocolError::AggregationCanceled),
+/// while the processor and the transport abort the request at its next step: a request not prepared yet
+/// is not prepared at all, a prepared one is rolled back and not sent. A request already sent is rolled back,
+/// its response is discarded and the counterparty is notified to abort the aggregation, see [CANCELED_ERROR].
+///
+/// Canceling a request which has already been resolved has no effect.
+#[derive(Debug)]
+pub struct TicketAggregationCancelHandle {
+    tx: UnboundedSender<TicketAggregationOutcome>,
+    state: Arc<RequestState>,
+}
+
+impl TicketAggregationCancelHandle {
+    /// Cancels the request, same as dropping the handle.
+    pub fn cancel(self) {}
+}
+
+impl Drop for TicketAggregationCancelHandle {
+    fn drop(&mut self) {
+        if self.state.cancel() {
+            let _ = self.tx.unbounded_send(Err(AggregationError::Canceled));
+        }
+    }
+}
+
 /// External API for feeding Ticket Aggregation actions into the Ticket Aggregation
 /// processor processing the elements independently in the background.
 #[derive(Debug)]
@@ -223,7 +470,7 @@ impl<T, U> TicketAggregationActions<T, U> {
         channel: &Hash,
         prerequisites: AggregationPrerequisites,
     ) -> Result<TicketAggregationAwaiter> {
-        let (tx, rx) = mpsc::unbounded::<()>();
+        let (tx, rx) = mpsc::unbounded::<TicketAggregationOutcome>();
 
         self.process(TicketAggregationToProcess::ToSend(
             *channel,
@@ -234,6 +481,40 @@ impl<T, U> TicketAggregationActions<T, U> {
         Ok(rx.into())
     }
 
+    /// Same as [TicketAggregationActions::aggregate_tickets], but returns also the handle to cancel the request.
+    pub fn aggregate_tickets_cancellable(
+        &mut self,
+        channel: &Hash,
+        prerequisites: AggregationPrerequisites,
+    ) -> Result<(TicketAggregationAwaiter, TicketAggregationCancelHandle)> {
+        let (tx, rx) = mpsc::unbounded::<TicketAggregationOutcome>();
+        let (finalizer, cancel_handle) = TicketAggregationFinalizer::new_cancellable(tx);
+
+        self.process(TicketAggregationToProcess::ToSend(*channel, prerequisites, finalizer))?;
+
+        Ok((rx.into(), cancel_handle))
+    }
+
+    /// Aborts the aggregation requested by the `source`, after it canceled its request.
+    ///
+    /// The aborted request is replied with [CANCELED_ERROR] instead of the aggregated ticket.
+    pub fn receive_aggregation_cancel(&mut self, source: PeerId) -> Result<()> {
+        self.process(TicketAggregationToProcess::ToCancel(source))
+    }
+
+    /// Rolls back the aggregation in the `channel` after its request has been canceled.
+    pub fn rollback_canceled(&mut self, channel: &Hash) -> Result<()> {
+        #[cfg(all(feature = "prometheus", not(test)))]
+        METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "canceled"]);
+
+        self.process(TicketAggregationToProcess::ToRollback(*channel))
+    }
+
+    /// Rolls back the aggregation in the `channel` after its request has been refused by the counterparty.
+    pub fn rollback_refused(&mut self, channel: &Hash) -> Result<()> {
+        self.process(TicketAggregationToProcess::ToRollback(*channel))
+    }
+
     fn process(&mut self, event: TicketAggregationToProcess<T, U>) -> Result<()> {
         self.queue.try_send(event).map_err(|e| {
             if e.is_full() {
@@ -247,6 +528,122 @@ impl<T, U> TicketAggregationActions<T, U> {
     }
 }
 
+/// Aggregations performed by this node as the responder, which can be aborted by the requesting peer.
+///
+/// The abort is checked before and after the tickets are aggregated in the DB, the DB aggregation itself
+/// is not interrupted. The ticket aggregated for an aborted request is not sent to the requester.
+#[derive(Debug, Clone, Default)]
+struct InProgressAggregations {
+    aborts: Arc<std::sync::Mutex<HashMap<PeerId, Arc<AtomicBool>>>>,
+}
+
+impl InProgressAggregations {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PeerId, Arc<AtomicBool>>> {
+        self.aborts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Registers a new aggregation requested by the `peer`, replacing its previous one.
+    fn start(&self, peer: PeerId) -> InProgressAggregation {
+        let aborted = Arc::new(AtomicBool::new(false));
+        self.lock().insert(peer, aborted.clone());
+        InProgressAggregation {
+            peer,
+            aborted,
+            registry: self.clone(),
+        }
+    }
+
+    /// Aborts the aggregation requested by the `peer`, returns `false` if there is none in progress.
+    fn abort(&self, peer: &PeerId) -> bool {
+        self.lock()
+            .remove(peer)
+            .inspect(|aborted| aborted.store(true, Ordering::Release))
+            .is_some()
+    }
+}
+
+/// Aggregation registered in the [InProgressAggregations], unregistered when dropped.
+struct InProgressAggregation {
+    peer: PeerId,
+    aborted: Arc<AtomicBool>,
+    registry: InProgressAggregations,
+}
+
+impl InProgressAggregation {
+    fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for InProgressAggregation {
+    fn drop(&mut self) {
+        let mut aborts = self.registry.lock();
+        if aborts.get(&self.peer).is_some_and(|a| Arc::ptr_eq(a, &self.aborted)) {
+            aborts.remove(&self.peer);
+        }
+    }
+}
+
+/// Narrows the `tickets` prepared for aggregation down to the window chosen by the `selection`.
+///
+/// The prepared tickets outside the selected window are released from the aggregation.
+async fn select_tickets_to_aggregate<Db>(
+    db: &Db,
+    selection: &dyn TicketSelection,
+    mut tickets: Vec<TransferableWinningTicket>,
+) -> hopr_db_api::errors::Result<Vec<TransferableWinningTicket>>
+where
+    Db: HoprDbTicketOperations,
+{
+    let selected = selection.select(&tickets);
+    let selected = selected.start.min(tickets.len())..selected.end.min(tickets.len());
+    if selected.is_empty() {
+        // The caller rolls back the entire aggregation
+        return Ok(Vec::new());
+    }
+    if selected.len() == tickets.len() {
+        return Ok(tickets);
+    }
+
+    let (first, last) = (&tickets[selected.start].ticket, &tickets[selected.end - 1].ticket);
+    let (first_index, last_index) = (first.index, last.index);
+    let selector = TicketSelector::new(first.channel_id, first.channel_epoch)
+        .with_state(AcknowledgedTicketStatus::BeingAggregated);
+
+    let released = db
+        .update_ticket_states(
+            selector.clone().with_index_range(..first_index),
+            AcknowledgedTicketStatus::Untouched,
+        )
+        .await?
+        + db.update_ticket_states(
+            selector.with_index_range((Bound::Excluded(last_index), Bound::Unbounded)),
+            AcknowledgedTicketStatus::Untouched,
+        )
+        .await?;
+
+    debug!(
+        selected = selected.len(),
+        released, "tickets outside of the selection were released from the aggregation"
+    );
+    Ok(tickets.drain(selected).collect())
+}
+
+async fn send_processed<T, U>(
+    processed_tx: &mut Sender<TicketAggregationProcessed<T, U>>,
+    event: TicketAggregationProcessed<T, U>,
+) {
+    match poll_fn(|cx| Pin::new(&mut *processed_tx).poll_ready(cx)).await {
+        Ok(_) => match processed_tx.start_send(event) {
+            Ok(_) => {}
+            Err(e) => error!(error = %e, "Failed to pass a processed ack message"),
+        },
+        Err(e) => {
+            warn!(error = %e, "The receiver for processed ack no longer exists");
+        }
+    };
+}
+
 type AckEventQueue<T, U> = (
     Sender<TicketAggregationToProcess<T, U>>,
     Receiver<TicketAggregationProcessed<T, U>>,
@@ -267,7 +664,23 @@ where
     U: Send,
 {
     /// Creates a new instance given the DB to process the ticket aggregation requests.
-    pub fn new<Db>(db: Db, chain_key: &ChainKeypair) -> Self
+    ///
+    /// All the tickets prepared for aggregation are sent in each aggregation request.
+    pub fn new<Db>(db: Db, chain_key: &ChainKeypair, cfg: TicketAggregationProtocolConfig) -> Self
+    where
+        Db: HoprDbTicketOperations + Send + Sync + Clone + std::fmt::Debug + 'static,
+    {
+        Self::new_with_selection(db, chain_key, cfg, Arc::new(AllTickets))
+    }
+
+    /// Same as [TicketAggregationInteraction::new], but the tickets sent in each aggregation request
+    /// are chosen by the given [TicketSelection].
+    pub fn new_with_selection<Db>(
+        db: Db,
+        chain_key: &ChainKeypair,
+        cfg: TicketAggregationProtocolConfig,
+        selection: Arc<dyn TicketSelection>,
+    ) -> Self
     where
         Db: HoprDbTicketOperations + Send + Sync + Clone + std::fmt::Debug + 'static,
     {
@@ -279,10 +692,17 @@ where
         );
 
         let chain_key = chain_key.clone();
+        let min_tickets = cfg.min_aggregatable_tickets;
+        let max_request_bytes = cfg.max_request_bytes;
+        let limit = AggregationLimit::new(cfg.max_concurrent_aggregations, cfg.busy_policy);
+        let in_progress = InProgressAggregations::default();
 
         let mut processing_stream = processing_in_rx.then_concurrent(move |event| {
             let chain_key = chain_key.clone();
             let db = db.clone();
+            let selection = selection.clone();
+            let limit = limit.clone();
+            let in_progress = in_progress.clone();
             let mut processed_tx = processing_out_tx.clone();
 
             async move {
@@ -291,25 +711,87 @@ where
                         let opk: std::result::Result<OffchainPublicKey, hopr_primitive_types::errors::GeneralError> =
                             destination.try_into();
                         match opk {
+                            Ok(_) if acked_tickets.len() < min_tickets as usize => {
+                                #[cfg(all(feature = "prometheus", not(test)))]
+                                METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "below_minimum"]);
+
+                                info!(%destination, count = acked_tickets.len(), min_tickets, "Refusing to aggregate too few tickets");
+                                Some(TicketAggregationProcessed::Reply(
+                                    destination,
+                                    Err(below_minimum_error(min_tickets)),
+                                    response,
+                                ))
+                            }
                             Ok(opk) => {
                                 let count = acked_tickets.len();
+                                let aggregation = in_progress.start(destination);
+                                let permit = limit.acquire().await;
+                                if permit.is_none() {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "busy"]);
+
+                                    info!(%destination, count, "Refusing to aggregate tickets, too many aggregations in progress");
+                                    return send_processed(
+                                        &mut processed_tx,
+                                        TicketAggregationProcessed::Reply(destination, Err(BUSY_ERROR.into()), response),
+                                    )
+                                    .await;
+                                }
+
+                                if aggregation.is_aborted() {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "canceled"]);
+
+                                    info!(%destination, count, "Not aggregating tickets of the canceled request");
+                                    return send_processed(
+                                        &mut processed_tx,
+                                        TicketAggregationProcessed::Reply(destination, Err(CANCELED_ERROR.into()), response),
+                                    )
+                                    .await;
+                                }
+
                                 match db.aggregate_tickets(opk, acked_tickets, &chain_key).await {
-                                    Ok(ticket) => Some(TicketAggregationProcessed::Reply(
-                                        destination,
-                                        Ok(ticket.leak()),
-                                        response,
-                                    )),
+                                    Ok(_) if aggregation.is_aborted() => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "canceled"]);
+
+                                        info!(%destination, count, "Not sending the aggregated ticket of the canceled request");
+                                        Some(TicketAggregationProcessed::Reply(
+                                            destination,
+                                            Err(CANCELED_ERROR.into()),
+                                            response,
+                                        ))
+                                    }
+                                    Ok(ticket) => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "success"]);
+
+                                        Some(TicketAggregationProcessed::Reply(
+                                            destination,
+                                            Ok(ticket.leak()),
+                                            response,
+                                        ))
+                                    },
                                     Err(DbError::TicketAggregationError(e)) => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "error"]);
+
                                         // forward error to counterparty
                                         Some(TicketAggregationProcessed::Reply(destination, Err(e), response))
                                     }
                                     Err(e) => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "error"]);
+
                                         error!(error = %e, %destination, count, "Dropping tickets aggregation request due to an error");
                                         None
                                     }
                                 }
                             },
                             Err(e) => {
+                                #[cfg(all(feature = "prometheus", not(test)))]
+                                METRIC_AGGREGATION_RESULT_COUNT.increment(&["responder", "error"]);
+
                                 error!(
                                     %destination, error = %e,
                                     "Failed to aggregate tickets due to destination deserialization error from an offchain public key"
@@ -323,31 +805,150 @@ where
                             Ok(ticket) => match db.process_received_aggregated_ticket(ticket.clone(), &chain_key).await
                             {
                                 Ok(acked_ticket) => {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "success"]);
+
                                     Some(TicketAggregationProcessed::Receive(destination, acked_ticket, request))
                                 }
                                 Err(e) => {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "error"]);
+
                                     error!(error = %e, counterparty = %destination, "Error while handling aggregated ticket");
                                     None
                                 }
                             },
-                            Err(e) => {
-                                warn!(error = %e, counterparty = %destination, "Counterparty refused to aggregate tickets");
-                                None
+                            Err(e) if e == BUSY_ERROR => {
+                                #[cfg(all(feature = "prometheus", not(test)))]
+                                METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "busy"]);
+
+                                // The aggregation is rolled back by the transport, so the tickets are kept
+                                info!(counterparty = %destination, "Counterparty is too busy to aggregate tickets");
+                                Some(TicketAggregationProcessed::Refused(
+                                    destination,
+                                    AggregationError::Busy,
+                                    request,
+                                ))
                             }
+                            Err(e) => match parse_below_minimum_error(&e) {
+                                Some(min_tickets) => {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "below_minimum"]);
+
+                                    // The aggregation is rolled back by the transport, so the tickets are kept
+                                    info!(counterparty = %destination, min_tickets, "Counterparty requires more tickets to aggregate");
+                                    Some(TicketAggregationProcessed::Refused(
+                                        destination,
+                                        AggregationError::BelowMinimum { min: min_tickets },
+                                        request,
+                                    ))
+                                }
+                                None => {
+                                    #[cfg(all(feature = "prometheus", not(test)))]
+                                    METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "error"]);
+
+                                    warn!(error = %e, counterparty = %destination, "Counterparty refused to aggregate tickets");
+                                    None
+                                }
+                            },
                         }
                     }
-                    TicketAggregationToProcess::ToSend(channel, prerequsites, finalizer) => {
+                    TicketAggregationToProcess::ToCancel(source) => {
+                        let aborted = in_progress.abort(&source);
+                        debug!(%source, aborted, "Counterparty canceled its aggregation request");
+                        None
+                    }
+                    TicketAggregationToProcess::ToRollback(channel) => {
+                        debug!(%channel, "Rolling back the canceled or refused aggregation request");
+                        if let Err(e) = db.rollback_aggregation_in_channel(channel).await {
+                            error!(%channel, error = %e, "Failed to roll back the canceled or refused aggregation");
+                        }
+                        None
+                    }
+                    TicketAggregationToProcess::ToSend(channel, prerequsites, mut finalizer) => {
+                        finalizer.channel = Some(channel);
+
+                        // The permit is held by the finalizer until the aggregated ticket is received
+                        match limit.acquire().await {
+                            Some(permit) => finalizer.permit = Some(Arc::new(permit)),
+                            None => {
+                                #[cfg(all(feature = "prometheus", not(test)))]
+                                METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "busy"]);
+
+                                warn!(%channel, "Not sending aggregation request, too many aggregations in progress");
+                                finalizer.fail(AggregationError::Busy);
+                                return;
+                            }
+                        }
+
+                        if finalizer.is_canceled() {
+                            #[cfg(all(feature = "prometheus", not(test)))]
+                            METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "canceled"]);
+
+                            debug!(%channel, "Not preparing canceled aggregation request");
+                            return;
+                        }
+
                         match db.prepare_aggregation_in_channel(&channel, prerequsites).await {
                             Ok(Some((source, tickets, _))) if !tickets.is_empty() => {
-                                #[cfg(all(feature = "prometheus", not(test)))]
-                                {
-                                    METRIC_AGGREGATED_TICKETS.increment_by(tickets.len() as u64);
-                                    METRIC_AGGREGATION_COUNT.increment();
-                                }
+                                match select_tickets_to_aggregate(&db, selection.as_ref(), tickets).await {
+                                    Ok(tickets) if tickets.len() < (min_tickets as usize).max(1) => {
+                                        info!(%channel, count = tickets.len(), min_tickets, "Not sending too few tickets for aggregation");
+                                        if let Err(e) = db.rollback_aggregation_in_channel(channel).await {
+                                            error!(%channel, error = %e, "Failed to roll back the declined aggregation");
+                                        }
+                                        finalizer.fail(AggregationError::BelowMinimum {
+                                            min: min_tickets.max(1),
+                                        });
+                                        None
+                                    }
+                                    Ok(tickets) if estimated_request_size(tickets.len()) > max_request_bytes => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "too_large"]);
+
+                                        let bytes = estimated_request_size(tickets.len());
+                                        warn!(%channel, count = tickets.len(), bytes, max_request_bytes, "Not sending too large aggregation request");
+                                        if let Err(e) = db.rollback_aggregation_in_channel(channel).await {
+                                            error!(%channel, error = %e, "Failed to roll back the declined aggregation");
+                                        }
+                                        finalizer.fail(AggregationError::RequestTooLarge {
+                                            bytes,
+                                            max: max_request_bytes,
+                                        });
+                                        None
+                                    }
+                                    Ok(_) if finalizer.is_canceled() => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "canceled"]);
+
+                                        debug!(%channel, "Rolling back the aggregation request canceled while being prepared");
+                                        if let Err(e) = db.rollback_aggregation_in_channel(channel).await {
+                                            error!(%channel, error = %e, "Failed to roll back the canceled aggregation");
+                                        }
+                                        None
+                                    }
+                                    Ok(tickets) => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        {
+                                            METRIC_AGGREGATED_TICKETS.increment_by(tickets.len() as u64);
+                                            METRIC_AGGREGATION_COUNT.increment();
+                                        }
+
+                                        Some(TicketAggregationProcessed::Send(source.into(), tickets, finalizer))
+                                    }
+                                    Err(e) => {
+                                        #[cfg(all(feature = "prometheus", not(test)))]
+                                        METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "error"]);
 
-                                Some(TicketAggregationProcessed::Send(source.into(), tickets, finalizer))
+                                        error!(%channel, error = %e, "Failed to release the tickets not selected for aggregation");
+                                        None
+                                    }
+                                }
                             }
                             Err(e) => {
+                                #[cfg(all(feature = "prometheus", not(test)))]
+                                METRIC_AGGREGATION_RESULT_COUNT.increment(&["requester", "error"]);
+
                                 error!(error = %e, "An error occured when preparing the channel aggregation");
                                 None
                             }
@@ -360,15 +961,7 @@ where
                 };
 
                 if let Some(event) = processed {
-                    match poll_fn(|cx| Pin::new(&mut processed_tx).poll_ready(cx)).await {
-                        Ok(_) => match processed_tx.start_send(event) {
-                            Ok(_) => {}
-                            Err(e) => error!(error = %e, "Failed to pass a processed ack message"),
-                        },
-                        Err(e) => {
-                            warn!(error = %e, "The receiver for processed ack no longer exists");
-                        }
-                    };
+                    send_processed(&mut processed_tx, event).await;
                 }
             }
         });
@@ -407,6 +1000,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::TicketAggregationProcessed;
+    use crate::errors::{AggregationError, ProtocolError};
+    use crate::ticket_aggregation::config::{AggregationBusyPolicy, TicketAggregationProtocolConfig};
+    use crate::ticket_aggregation::selection::LowestIndexFirst;
+    use crate::ticket_aggregation::wire::estimated_request_size;
     use async_std::prelude::FutureExt;
     use futures::pin_mut;
     use futures::stream::StreamExt;
@@ -425,6 +1022,8 @@ mod tests {
     use hopr_primitive_types::prelude::*;
     use lazy_static::lazy_static;
     use std::ops::{Add, Mul};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
 
     lazy_static! {
@@ -558,8 +1157,10 @@ mod tests {
         let (bob_notify_tx, bob_notify_rx) = futures::channel::mpsc::unbounded();
         db_bob.start_ticket_processing(bob_notify_tx.into())?;
 
-        let mut alice = super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0]);
-        let mut bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1]);
+        let mut alice =
+            super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0], Default::default());
+        let mut bob =
+            super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], Default::default());
 
         let awaiter = bob
             .writer()
@@ -680,8 +1281,10 @@ mod tests {
             db_bob.upsert_ticket(None, ticket).await?;
         }
 
-        let mut alice = super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0]);
-        let mut bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1]);
+        let mut alice =
+            super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0], Default::default());
+        let mut bob =
+            super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], Default::default());
 
         let awaiter = bob
             .writer()
@@ -752,4 +1355,636 @@ mod tests {
 
         Ok(awaiter.consume_and_wait(Duration::from_millis(2000)).await?)
     }
+
+    #[test]
+    fn test_below_minimum_error_should_carry_the_minimum() {
+        assert_eq!(
+            Some(10),
+            super::parse_below_minimum_error(&super::below_minimum_error(10))
+        );
+        assert_eq!(None, super::parse_below_minimum_error("below minimum"));
+        assert_eq!(None, super::parse_below_minimum_error("invalid ticket"));
+    }
+
+    #[async_std::test]
+    async fn test_ticket_aggregation_should_respect_minimum_ticket_count() -> anyhow::Result<()> {
+        let db_alice = HoprDb::new_in_memory(PEERS_CHAIN[0].clone()).await?;
+        let db_bob = HoprDb::new_in_memory(PEERS_CHAIN[1].clone()).await?;
+        init_db(db_alice.clone()).await?;
+        init_db(db_bob.clone()).await?;
+
+        const NUM_TICKETS: u64 = 5;
+
+        let mut agg_balance = Balance::zero(BalanceType::HOPR);
+        let mut tickets = vec![];
+        for i in 1..=NUM_TICKETS {
+            let ack_ticket = mock_acknowledged_ticket(&PEERS_CHAIN[0], &PEERS_CHAIN[1], i)?;
+            agg_balance = agg_balance.add(&ack_ticket.verified_ticket().amount);
+            tickets.push(ack_ticket)
+        }
+
+        let channel_alice_bob = ChannelEntry::new(
+            (&PEERS_CHAIN[0]).into(),
+            (&PEERS_CHAIN[1]).into(),
+            agg_balance.mul(10),
+            1_u32.into(),
+            ChannelStatus::Open,
+            1u32.into(),
+        );
+
+        db_alice.upsert_channel(None, channel_alice_bob).await?;
+        db_bob.upsert_channel(None, channel_alice_bob).await?;
+
+        for ticket in tickets.into_iter() {
+            db_bob.upsert_ticket(None, ticket).await?;
+        }
+
+        let bob_packet_key = PEERS[1].public().into();
+
+        // Bob as the requester declines to send fewer tickets than his minimum
+        let bob_cfg = TicketAggregationProtocolConfig {
+            min_aggregatable_tickets: NUM_TICKETS as u32 + 1,
+            ..Default::default()
+        };
+        let mut bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], bob_cfg);
+
+        let res = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?
+            .consume_and_wait(Duration::from_millis(2000))
+            .await;
+        assert!(
+            matches!(
+                res,
+                Err(ProtocolError::Aggregation(AggregationError::BelowMinimum { min })) if min == NUM_TICKETS as u32 + 1
+            ),
+            "declined request must fail as below the minimum: {res:?}"
+        );
+
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert_eq!(NUM_TICKETS as usize, stored_acked_tickets.len());
+        assert!(
+            stored_acked_tickets
+                .iter()
+                .all(|t| t.status == AcknowledgedTicketStatus::Untouched),
+            "declined tickets must be left untouched"
+        );
+
+        // Alice as the responder refuses to aggregate fewer tickets than her minimum
+        let alice_cfg = TicketAggregationProtocolConfig {
+            min_aggregatable_tickets: 10,
+            ..Default::default()
+        };
+        let mut alice =
+            super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0], alice_cfg);
+
+        alice.writer().receive_aggregation_request(
+            bob_packet_key,
+            stored_acked_tickets
+                .into_iter()
+                .map(|t| t.into_transferable(&PEERS_CHAIN[1], &Hash::default()))
+                .collect::<Result<Vec<_>, _>>()?,
+            (),
+        )?;
+
+        let refusal = match alice.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Reply(_, Err(e), ()))) => {
+                assert_eq!(Some(10), super::parse_below_minimum_error(&e));
+                e
+            }
+            _ => panic!("alice should have refused to aggregate the tickets"),
+        };
+
+        // Bob as the requester fails his request with the minimum of Alice
+        bob.writer()
+            .receive_ticket(PEERS[0].public().into(), Err(refusal), ())?;
+        match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Refused(_, AggregationError::BelowMinimum { min: 10 }, ()))) => {}
+            _ => panic!("bob should have been refused for too few tickets"),
+        };
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_ticket_aggregation_should_not_send_too_large_requests() -> anyhow::Result<()> {
+        let db_bob = HoprDb::new_in_memory(PEERS_CHAIN[1].clone()).await?;
+        init_db(db_bob.clone()).await?;
+
+        const NUM_TICKETS: u64 = 3;
+
+        let mut agg_balance = Balance::zero(BalanceType::HOPR);
+        let mut tickets = vec![];
+        for i in 1..=NUM_TICKETS {
+            let ack_ticket = mock_acknowledged_ticket(&PEERS_CHAIN[0], &PEERS_CHAIN[1], i)?;
+            agg_balance = agg_balance.add(&ack_ticket.verified_ticket().amount);
+            tickets.push(ack_ticket)
+        }
+
+        let channel_alice_bob = ChannelEntry::new(
+            (&PEERS_CHAIN[0]).into(),
+            (&PEERS_CHAIN[1]).into(),
+            agg_balance.mul(10),
+            1_u32.into(),
+            ChannelStatus::Open,
+            1u32.into(),
+        );
+
+        db_bob.upsert_channel(None, channel_alice_bob).await?;
+        for ticket in tickets.into_iter() {
+            db_bob.upsert_ticket(None, ticket).await?;
+        }
+
+        // Just below the size of the request
+        let bob_cfg = TicketAggregationProtocolConfig {
+            max_request_bytes: estimated_request_size(NUM_TICKETS as usize) - 1,
+            ..Default::default()
+        };
+        let bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], bob_cfg);
+
+        let res = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?
+            .consume_and_wait(Duration::from_millis(2000))
+            .await;
+        match res {
+            Err(ProtocolError::Aggregation(AggregationError::RequestTooLarge { bytes, max })) => {
+                assert_eq!(estimated_request_size(NUM_TICKETS as usize), bytes);
+                assert_eq!(bob_cfg.max_request_bytes, max);
+            }
+            res => panic!("the request must be refused as too large: {res:?}"),
+        }
+
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert_eq!(NUM_TICKETS as usize, stored_acked_tickets.len());
+        assert!(
+            stored_acked_tickets
+                .iter()
+                .all(|t| t.status == AcknowledgedTicketStatus::Untouched),
+            "tickets of the refused request must be left untouched"
+        );
+
+        // Exactly the size of the request
+        let bob_cfg = TicketAggregationProtocolConfig {
+            max_request_bytes: estimated_request_size(NUM_TICKETS as usize),
+            ..Default::default()
+        };
+        let mut bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], bob_cfg);
+
+        let _awaiter = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?;
+
+        match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Send(_, acked_tickets, _))) => {
+                assert_eq!(NUM_TICKETS as usize, acked_tickets.len());
+            }
+            _ => panic!("the request within the limit must be sent"),
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_cancel_handle_should_resolve_the_awaiter_once() -> anyhow::Result<()> {
+        // Invoked cancellation wins over the later outcome
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        assert!(!finalizer.is_canceled());
+
+        cancel_handle.cancel();
+        assert!(finalizer.is_canceled());
+        finalizer.clone().finalize();
+
+        let awaiter: super::TicketAggregationAwaiter = rx.into();
+        assert!(matches!(
+            awaiter.consume_and_wait(Duration::from_millis(100)).await,
+            Err(ProtocolError::AggregationCanceled)
+        ));
+
+        // Dropping the handle cancels as well
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        drop(cancel_handle);
+        assert!(finalizer.is_canceled());
+
+        let awaiter: super::TicketAggregationAwaiter = rx.into();
+        assert!(matches!(
+            awaiter.consume_and_wait(Duration::from_millis(100)).await,
+            Err(ProtocolError::AggregationCanceled)
+        ));
+
+        // Dropping the handle after the resolution has no effect
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        finalizer.finalize();
+        drop(cancel_handle);
+
+        let awaiter: super::TicketAggregationAwaiter = rx.into();
+        Ok(awaiter.consume_and_wait(Duration::from_millis(100)).await?)
+    }
+
+    #[async_std::test]
+    async fn test_canceled_ticket_aggregation_should_not_be_sent() -> anyhow::Result<()> {
+        let db_bob = HoprDb::new_in_memory(PEERS_CHAIN[1].clone()).await?;
+        init_db(db_bob.clone()).await?;
+
+        const NUM_TICKETS: u64 = 3;
+
+        let mut agg_balance = Balance::zero(BalanceType::HOPR);
+        let mut tickets = vec![];
+        for i in 1..=NUM_TICKETS {
+            let ack_ticket = mock_acknowledged_ticket(&PEERS_CHAIN[0], &PEERS_CHAIN[1], i)?;
+            agg_balance = agg_balance.add(&ack_ticket.verified_ticket().amount);
+            tickets.push(ack_ticket)
+        }
+
+        let channel_alice_bob = ChannelEntry::new(
+            (&PEERS_CHAIN[0]).into(),
+            (&PEERS_CHAIN[1]).into(),
+            agg_balance.mul(10),
+            1_u32.into(),
+            ChannelStatus::Open,
+            1u32.into(),
+        );
+
+        db_bob.upsert_channel(None, channel_alice_bob).await?;
+        for ticket in tickets.into_iter() {
+            db_bob.upsert_ticket(None, ticket).await?;
+        }
+
+        let mut bob =
+            super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], Default::default());
+
+        // Canceled before the processing
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        cancel_handle.cancel();
+        bob.writer().queue.try_send(super::TicketAggregationToProcess::ToSend(
+            channel_alice_bob.get_id(),
+            Default::default(),
+            finalizer,
+        ))?;
+
+        let awaiter: super::TicketAggregationAwaiter = rx.into();
+        assert!(matches!(
+            awaiter.consume_and_wait(Duration::from_millis(100)).await,
+            Err(ProtocolError::AggregationCanceled)
+        ));
+        assert!(
+            bob.next().timeout(Duration::from_millis(500)).await.is_err(),
+            "canceled request must not be sent"
+        );
+
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert!(
+            stored_acked_tickets
+                .iter()
+                .all(|t| t.status == AcknowledgedTicketStatus::Untouched),
+            "tickets of the canceled request must be left untouched"
+        );
+
+        // Canceled after the request has been prepared
+        let (awaiter, cancel_handle) = bob
+            .writer()
+            .aggregate_tickets_cancellable(&channel_alice_bob.get_id(), Default::default())?;
+
+        match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Send(_, _, finalizer))) => {
+                assert!(!finalizer.is_canceled());
+
+                // Same as the transport does once the request has been sent
+                let (hook_tx, hook_rx) = futures::channel::oneshot::channel();
+                let mut writer = bob.writer();
+                let channel = channel_alice_bob.get_id();
+                finalizer.on_cancel(move || {
+                    writer.rollback_canceled(&channel).expect("rollback must be enqueued");
+                    let _ = hook_tx.send(());
+                });
+
+                drop(cancel_handle);
+                assert!(finalizer.is_canceled(), "the transport must see the cancellation");
+                hook_rx.await?;
+
+                // Resolving the canceled request has no effect
+                finalizer.finalize();
+            }
+            _ => panic!("the request must be sent before its cancellation"),
+        }
+
+        assert!(matches!(
+            awaiter.consume_and_wait(Duration::from_millis(100)).await,
+            Err(ProtocolError::AggregationCanceled)
+        ));
+
+        // The rollback emits nothing
+        assert!(bob.next().timeout(Duration::from_millis(500)).await.is_err());
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert!(
+            stored_acked_tickets
+                .iter()
+                .all(|t| t.status == AcknowledgedTicketStatus::Untouched),
+            "tickets of the request canceled after being sent must be rolled back"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_hook_should_run_once_and_only_on_cancellation() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook = |count: Arc<AtomicUsize>| {
+            move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+
+        // Hook set before the cancellation
+        let (tx, _rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        finalizer.on_cancel(hook(count.clone()));
+        assert_eq!(0, count.load(Ordering::SeqCst));
+        cancel_handle.cancel();
+        assert_eq!(1, count.load(Ordering::SeqCst));
+
+        // Hook set after the cancellation runs right away
+        finalizer.on_cancel(hook(count.clone()));
+        assert_eq!(2, count.load(Ordering::SeqCst));
+
+        // Hook of the resolved request never runs
+        let (tx, _rx) = futures::channel::mpsc::unbounded();
+        let (finalizer, cancel_handle) = super::TicketAggregationFinalizer::new_cancellable(tx);
+        finalizer.on_cancel(hook(count.clone()));
+        finalizer.finalize();
+        drop(cancel_handle);
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_in_progress_aggregations_should_abort_only_the_latest_request_of_the_peer() {
+        let in_progress = super::InProgressAggregations::default();
+        let peer = PEERS[1].public().into();
+
+        let previous = in_progress.start(peer);
+        let latest = in_progress.start(peer);
+        drop(previous);
+
+        assert!(in_progress.abort(&peer), "the latest request must still be registered");
+        assert!(latest.is_aborted());
+        assert!(!in_progress.abort(&peer), "the request can be aborted only once");
+
+        let next = in_progress.start(peer);
+        drop(next);
+        assert!(!in_progress.abort(&peer), "finished request must be unregistered");
+    }
+
+    #[async_std::test]
+    async fn test_ticket_aggregation_cancel_notice_should_not_be_replied() -> anyhow::Result<()> {
+        let db_alice = HoprDb::new_in_memory(PEERS_CHAIN[0].clone()).await?;
+        init_db(db_alice.clone()).await?;
+
+        let mut alice =
+            super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0], Default::default());
+
+        alice.writer().receive_aggregation_cancel(PEERS[1].public().into())?;
+
+        assert!(
+            alice.next().timeout(Duration::from_millis(200)).await.is_err(),
+            "the cancel notice must not produce a reply"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_aggregation_limit_should_reject_or_queue_over_the_maximum() {
+        let limit = super::AggregationLimit::new(2, AggregationBusyPolicy::Reject);
+        let first = limit.acquire().await.expect("first permit must be acquired");
+        let _second = limit.clone().acquire().await.expect("second permit must be acquired");
+        assert!(
+            limit.acquire().await.is_none(),
+            "permit over the maximum must be refused"
+        );
+        drop(first);
+        assert!(
+            limit.acquire().await.is_some(),
+            "released permit must be acquired again"
+        );
+
+        let limit = super::AggregationLimit::new(
+            1,
+            AggregationBusyPolicy::Queue {
+                timeout: Duration::from_millis(100),
+            },
+        );
+        let permit = limit.acquire().await.expect("first permit must be acquired");
+        assert!(
+            limit.acquire().await.is_none(),
+            "permit must be refused once the queue timeout elapses"
+        );
+
+        let release = async move {
+            async_std::task::sleep(Duration::from_millis(20)).await;
+            drop(permit);
+        };
+        let (queued, _) = futures::join!(limit.acquire(), release);
+        assert!(queued.is_some(), "queued permit must be acquired once released");
+    }
+
+    #[async_std::test]
+    async fn test_ticket_aggregation_should_respect_the_global_limit_in_both_roles() -> anyhow::Result<()> {
+        let db_bob = HoprDb::new_in_memory(PEERS_CHAIN[1].clone()).await?;
+        init_db(db_bob.clone()).await?;
+
+        const NUM_TICKETS: u64 = 3;
+
+        let mut agg_balance = Balance::zero(BalanceType::HOPR);
+        let mut tickets = vec![];
+        for i in 1..=NUM_TICKETS {
+            let ack_ticket = mock_acknowledged_ticket(&PEERS_CHAIN[0], &PEERS_CHAIN[1], i)?;
+            agg_balance = agg_balance.add(&ack_ticket.verified_ticket().amount);
+            tickets.push(ack_ticket)
+        }
+
+        let channel_alice_bob = ChannelEntry::new(
+            (&PEERS_CHAIN[0]).into(),
+            (&PEERS_CHAIN[1]).into(),
+            agg_balance.mul(10),
+            1_u32.into(),
+            ChannelStatus::Open,
+            1u32.into(),
+        );
+
+        db_bob.upsert_channel(None, channel_alice_bob).await?;
+        for ticket in tickets.into_iter() {
+            db_bob.upsert_ticket(None, ticket).await?;
+        }
+
+        let bob_cfg = TicketAggregationProtocolConfig {
+            max_concurrent_aggregations: 1,
+            busy_policy: AggregationBusyPolicy::Reject,
+            ..Default::default()
+        };
+        let mut bob = super::TicketAggregationInteraction::<(), ()>::new(db_bob.clone(), &PEERS_CHAIN[1], bob_cfg);
+
+        // The request in flight holds the only permit until it is finalized
+        let _awaiter = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?;
+        let (acked_tickets, _finalizer) = match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Send(_, acked_tickets, finalizer))) => (acked_tickets, finalizer),
+            _ => panic!("the first request must be sent"),
+        };
+
+        let res = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?
+            .consume_and_wait(Duration::from_millis(2000))
+            .await;
+        assert!(
+            matches!(res, Err(ProtocolError::Aggregation(AggregationError::Busy))),
+            "another request must be refused as busy: {res:?}"
+        );
+
+        bob.writer()
+            .receive_aggregation_request(PEERS[0].public().into(), acked_tickets, ())?;
+        let refusal = match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Reply(_, Err(e), ()))) => {
+                assert_eq!(super::BUSY_ERROR, e);
+                e
+            }
+            _ => panic!("the received request must be refused as busy"),
+        };
+
+        // The busy reply of the counterparty fails the request the same as the local limit
+        bob.writer()
+            .receive_ticket(PEERS[0].public().into(), Err(refusal), ())?;
+        match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Refused(_, AggregationError::Busy, ()))) => {}
+            _ => panic!("the request refused by the counterparty must fail as busy"),
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_ticket_aggregation_should_send_only_selected_tickets() -> anyhow::Result<()> {
+        let db_alice = HoprDb::new_in_memory(PEERS_CHAIN[0].clone()).await?;
+        let db_bob = HoprDb::new_in_memory(PEERS_CHAIN[1].clone()).await?;
+        init_db(db_alice.clone()).await?;
+        init_db(db_bob.clone()).await?;
+
+        const NUM_TICKETS: u64 = 30;
+        const MAX_SELECTED: usize = 10;
+
+        let mut tickets = vec![];
+        let mut total_balance = Balance::zero(BalanceType::HOPR);
+        let mut selected_balance = Balance::zero(BalanceType::HOPR);
+        for i in 1..=NUM_TICKETS {
+            let ack_ticket = mock_acknowledged_ticket(&PEERS_CHAIN[0], &PEERS_CHAIN[1], i)?;
+            total_balance = total_balance.add(&ack_ticket.verified_ticket().amount);
+            if i <= MAX_SELECTED as u64 {
+                selected_balance = selected_balance.add(&ack_ticket.verified_ticket().amount);
+            }
+            tickets.push(ack_ticket)
+        }
+
+        let channel_alice_bob = ChannelEntry::new(
+            (&PEERS_CHAIN[0]).into(),
+            (&PEERS_CHAIN[1]).into(),
+            total_balance.mul(10),
+            1_u32.into(),
+            ChannelStatus::Open,
+            1u32.into(),
+        );
+
+        db_alice.upsert_channel(None, channel_alice_bob).await?;
+        db_bob.upsert_channel(None, channel_alice_bob).await?;
+
+        for ticket in tickets.into_iter() {
+            db_bob.upsert_ticket(None, ticket).await?;
+        }
+
+        let (bob_notify_tx, bob_notify_rx) = futures::channel::mpsc::unbounded();
+        db_bob.start_ticket_processing(bob_notify_tx.into())?;
+
+        let mut alice =
+            super::TicketAggregationInteraction::<(), ()>::new(db_alice.clone(), &PEERS_CHAIN[0], Default::default());
+        let mut bob = super::TicketAggregationInteraction::<(), ()>::new_with_selection(
+            db_bob.clone(),
+            &PEERS_CHAIN[1],
+            Default::default(),
+            Arc::new(LowestIndexFirst {
+                max_tickets: MAX_SELECTED,
+            }),
+        );
+
+        let _awaiter = bob
+            .writer()
+            .aggregate_tickets(&channel_alice_bob.get_id(), Default::default())?;
+
+        let finalizer = match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Send(_, acked_tickets, request_finalizer))) => {
+                assert_eq!(
+                    (1..=MAX_SELECTED as u64).collect::<Vec<_>>(),
+                    acked_tickets.iter().map(|t| t.ticket.index).collect::<Vec<_>>(),
+                    "only the tickets with the lowest indices must be sent"
+                );
+                alice
+                    .writer()
+                    .receive_aggregation_request(PEERS[1].public().into(), acked_tickets, ())?;
+                request_finalizer
+            }
+            _ => panic!("unexpected action happened while sending agg request by Bob"),
+        };
+
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert_eq!(
+            MAX_SELECTED,
+            stored_acked_tickets
+                .iter()
+                .filter(|t| t.status == AcknowledgedTicketStatus::BeingAggregated)
+                .count(),
+            "tickets not selected must be released from the aggregation"
+        );
+
+        match alice.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Reply(_, aggregated_ticket, ()))) => {
+                bob.writer()
+                    .receive_ticket(PEERS[0].public().into(), aggregated_ticket, ())?
+            }
+            _ => panic!("unexpected action happened while awaiting agg request at Alice"),
+        };
+
+        match bob.next().timeout(Duration::from_secs(5)).await {
+            Ok(Some(TicketAggregationProcessed::Receive(_, _, ()))) => finalizer.finalize(),
+            _ => panic!("unexpected action happened while awaiting agg response at Bob"),
+        }
+
+        pin_mut!(bob_notify_rx);
+        bob_notify_rx
+            .next()
+            .await
+            .expect("bob should have received the ticket notification");
+
+        let stored_acked_tickets = db_bob.get_tickets((&channel_alice_bob).into()).await?;
+        assert_eq!(
+            NUM_TICKETS as usize - MAX_SELECTED + 1,
+            stored_acked_tickets.len(),
+            "there should be 1 aggregated ticket and all the tickets not selected"
+        );
+        let aggregated = stored_acked_tickets
+            .iter()
+            .find(|t| t.verified_ticket().is_aggregated())
+            .expect("aggregated ticket must be stored");
+        assert_eq!(selected_balance, aggregated.verified_ticket().amount);
+        assert!(
+            stored_acked_tickets
+                .iter()
+                .all(|t| t.status == AcknowledgedTicketStatus::Untouched),
+            "all the remaining tickets must be untouched"
+        );
+
+        Ok(())
+    }
 }
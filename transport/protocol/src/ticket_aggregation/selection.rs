@@ -0,0 +1,148 @@
+//! Strategies choosing which of the prepared tickets are sent in a single aggregation request.
+//!
+//! When the node requests an aggregation in a channel, all the tickets eligible for aggregation
+//! are prepared first (sorted by their indices in ascending order). The [TicketSelection] then
+//! decides which of them are actually sent to the counterparty. The tickets not selected are released
+//! from the aggregation and remain available for a future aggregation or redemption.
+//!
+//! The selection is always a contiguous window of the prepared tickets, because an aggregated ticket
+//! covers the entire index range of the tickets it replaces.
+use std::fmt::Debug;
+use std::ops::Range;
+
+use hopr_internal_types::prelude::*;
+use hopr_primitive_types::prelude::*;
+
+/// Chooses the tickets sent in a single aggregation request.
+pub trait TicketSelection: Debug + Send + Sync {
+    /// Selects the window of the given `tickets` to be aggregated.
+    ///
+    /// The `tickets` are sorted by their indices in ascending order. An empty range means that
+    /// no aggregation request is sent. Ranges exceeding the number of `tickets` are truncated.
+    fn select(&self, tickets: &[TransferableWinningTicket]) -> Range<usize>;
+}
+
+/// Selects all the prepared tickets.
+///
+/// This is the default selection.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct AllTickets;
+
+impl TicketSelection for AllTickets {
+    fn select(&self, tickets: &[TransferableWinningTicket]) -> Range<usize> {
+        0..tickets.len()
+    }
+}
+
+/// Selects at most `max_tickets` tickets with the lowest indices.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LowestIndexFirst {
+    pub max_tickets: usize,
+}
+
+impl TicketSelection for LowestIndexFirst {
+    fn select(&self, tickets: &[TransferableWinningTicket]) -> Range<usize> {
+        0..tickets.len().min(self.max_tickets)
+    }
+}
+
+/// Selects the window of at most `max_tickets` consecutive tickets with the highest total value.
+///
+/// If multiple windows have the same value, the one with the lowest indices is selected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HighestValueWindow {
+    pub max_tickets: usize,
+}
+
+impl TicketSelection for HighestValueWindow {
+    fn select(&self, tickets: &[TransferableWinningTicket]) -> Range<usize> {
+        let window = tickets.len().min(self.max_tickets);
+        if window == 0 {
+            return 0..0;
+        }
+
+        let amounts = tickets.iter().map(|t| t.ticket.amount.amount()).collect::<Vec<_>>();
+
+        let mut value = amounts[..window].iter().fold(U256::zero(), |acc, a| acc + *a);
+        let (mut best_start, mut best_value) = (0, value);
+        for start in 1..=amounts.len() - window {
+            value = value - amounts[start - 1] + amounts[start + window - 1];
+            if value > best_value {
+                best_start = start;
+                best_value = value;
+            }
+        }
+
+        best_start..best_start + window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hopr_crypto_random::Randomizable;
+    use hopr_crypto_types::prelude::*;
+
+    fn mock_tickets(amounts: &[u64]) -> anyhow::Result<Vec<TransferableWinningTicket>> {
+        let issuer = ChainKeypair::random();
+        let recipient = ChainKeypair::random();
+
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(index, amount)| -> anyhow::Result<TransferableWinningTicket> {
+                let index = index as u64 + 1;
+                let response = Response::try_from(Hash::create(&[index.to_be_bytes().as_ref()]).as_ref())?;
+                Ok(TicketBuilder::default()
+                    .addresses(&issuer, &recipient)
+                    .amount(*amount)
+                    .index(index)
+                    .index_offset(1)
+                    .win_prob(1.0)
+                    .channel_epoch(1)
+                    .challenge(response.to_challenge().into())
+                    .build_signed(&issuer, &Hash::default())?
+                    .into_acknowledged(response)
+                    .into_transferable(&recipient, &Hash::default())?)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn all_tickets_should_select_everything() -> anyhow::Result<()> {
+        let tickets = mock_tickets(&[1, 2, 3])?;
+
+        assert_eq!(0..3, AllTickets.select(&tickets));
+        assert_eq!(0..0, AllTickets.select(&[]));
+        Ok(())
+    }
+
+    #[test]
+    fn lowest_index_first_should_cap_the_number_of_tickets() -> anyhow::Result<()> {
+        let tickets = mock_tickets(&[1, 2, 3])?;
+
+        assert_eq!(0..2, LowestIndexFirst { max_tickets: 2 }.select(&tickets));
+        assert_eq!(0..3, LowestIndexFirst { max_tickets: 10 }.select(&tickets));
+        assert_eq!(0..0, LowestIndexFirst { max_tickets: 0 }.select(&tickets));
+        Ok(())
+    }
+
+    #[test]
+    fn highest_value_window_should_select_the_most_valuable_consecutive_tickets() -> anyhow::Result<()> {
+        let tickets = mock_tickets(&[1, 5, 1, 7, 8, 1, 2])?;
+
+        assert_eq!(3..5, HighestValueWindow { max_tickets: 2 }.select(&tickets));
+        assert_eq!(2..5, HighestValueWindow { max_tickets: 3 }.select(&tickets));
+        assert_eq!(0..7, HighestValueWindow { max_tickets: 10 }.select(&tickets));
+        assert_eq!(0..0, HighestValueWindow { max_tickets: 0 }.select(&tickets));
+        Ok(())
+    }
+
+    #[test]
+    fn highest_value_window_should_prefer_lowest_indices_on_tie() -> anyhow::Result<()> {
+        let tickets = mock_tickets(&[3, 3, 3, 3])?;
+
+        assert_eq!(0..2, HighestValueWindow { max_tickets: 2 }.select(&tickets));
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+//! Estimation of the size of the ticket aggregation requests on the wire.
+//!
+//! The aggregation request is a CBOR-encoded list of [TransferableWinningTicket]s, which must fit
+//! into the maximum request size of the request-response transport. The estimate is an upper bound
+//! computed from the largest possible encoding of each ticket field, so that it can be checked
+//! before the tickets are serialized.
+use hopr_internal_types::prelude::*;
+
+/// Default maximum size of a ticket aggregation request in bytes.
+///
+/// Equal to the maximum request size of the CBOR request-response codec.
+pub const DEFAULT_MAX_AGGREGATION_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// Size of a CBOR header carrying the given length.
+const fn header(len: usize) -> usize {
+    if len < 24 {
+        1
+    } else if len <= u8::MAX as usize {
+        2
+    } else if len <= u16::MAX as usize {
+        3
+    } else if len <= u32::MAX as usize {
+        5
+    } else {
+        9
+    }
+}
+
+/// Largest encoding of an unsigned integer.
+const UINT: usize = 9;
+
+/// Encoding of a map key.
+const fn key(name: &str) -> usize {
+    header(name.len()) + name.len()
+}
+
+/// Encoding of a byte string (`serde_bytes` or `serialize_bytes`).
+const fn bytes(len: usize) -> usize {
+    header(len) + len
+}
+
+/// Largest encoding of a byte array serialized as a tuple of integers.
+const fn byte_array(len: usize) -> usize {
+    header(len) + 2 * len
+}
+
+/// Largest encoding of a [Balance]: the amount as a hex string (`0x` and up to 64 digits)
+/// and the longest name of a [BalanceType].
+const BALANCE: usize = header(2) + bytes(66) + bytes("Native".len());
+
+/// Largest encoding of a [Ticket].
+const TICKET: usize = header(8)
+    + key("channel_id")
+    + bytes(32)
+    + key("amount")
+    + BALANCE
+    + key("index")
+    + UINT
+    + key("index_offset")
+    + UINT
+    + key("encoded_win_prob")
+    + byte_array(7)
+    + key("channel_epoch")
+    + UINT
+    + key("challenge")
+    + byte_array(20)
+    + key("signature")
+    + bytes(64);
+
+/// Upper bound on the encoded size of a single [TransferableWinningTicket].
+pub const MAX_TRANSFERABLE_TICKET_WIRE_SIZE: usize = header(4)
+    + key("ticket")
+    + TICKET
+    + key("response")
+    + bytes(32)
+    + key("vrf_params")
+    + bytes(97)
+    + key("signer")
+    + byte_array(20);
+
+/// Upper bound on the size of an aggregation request carrying `ticket_count` tickets.
+pub fn estimated_request_size(ticket_count: usize) -> usize {
+    header(ticket_count) + ticket_count * MAX_TRANSFERABLE_TICKET_WIRE_SIZE
+}
+
+/// Upper bound on the size of the aggregation request carrying the given `tickets`.
+///
+/// The estimate never falls below the actual size of the serialized request.
+pub fn estimated_wire_size(tickets: &[AcknowledgedTicket]) -> usize {
+    estimated_request_size(tickets.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use hopr_crypto_random::{random_float, random_integer};
+    use hopr_crypto_types::prelude::*;
+    use hopr_primitive_types::prelude::*;
+
+    lazy_static::lazy_static! {
+        static ref ISSUER: ChainKeypair = ChainKeypair::from_secret(&hex!(
+            "51d3003d908045a4d76d0bfc0d84f6ff946b5934b7ea6a2958faf02fead4567a"
+        ))
+        .expect("lazy static keypair should be valid");
+        static ref RECIPIENT: ChainKeypair = ChainKeypair::from_secret(&hex!(
+            "e1f89073a01831d0eed9fe2c67e7d65c144b9d9945320f6d325b1cccc2d124e9"
+        ))
+        .expect("lazy static keypair should be valid");
+    }
+
+    fn random_ticket() -> anyhow::Result<AcknowledgedTicket> {
+        let response = Response::try_from(Hash::create(&[random_integer(0, None).to_be_bytes().as_ref()]).as_ref())?;
+
+        Ok(TicketBuilder::default()
+            .addresses(&*ISSUER, &*RECIPIENT)
+            .amount(U256::from(random_integer(1, None)) * U256::from(1_u64 << 28))
+            .index(random_integer(0, Some(1 << 48)))
+            .index_offset(random_integer(1, Some(u32::MAX as u64)) as u32)
+            .win_prob(random_float().max(0.01))
+            .channel_epoch(random_integer(0, Some(1 << 24)) as u32)
+            .challenge(response.to_challenge().into())
+            .build_signed(&ISSUER, &Hash::default())?
+            .into_acknowledged(response))
+    }
+
+    fn serialized_size(tickets: &[AcknowledgedTicket]) -> anyhow::Result<usize> {
+        let transferable = tickets
+            .iter()
+            .cloned()
+            .map(|t| t.into_transferable(&RECIPIENT, &Hash::default()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(serde_cbor::to_vec(&transferable)?.len())
+    }
+
+    #[test]
+    fn estimated_wire_size_should_grow_with_the_ticket_count() {
+        assert_eq!(1, estimated_request_size(0));
+        assert_eq!(
+            estimated_request_size(23) + MAX_TRANSFERABLE_TICKET_WIRE_SIZE + 1,
+            estimated_request_size(24),
+            "the list header grows at 24 items"
+        );
+        assert!(estimated_request_size(1000) < DEFAULT_MAX_AGGREGATION_REQUEST_BYTES);
+    }
+
+    #[test]
+    fn estimated_wire_size_should_never_underestimate_the_serialized_size() -> anyhow::Result<()> {
+        for count in [0, 1, 2, 5, 23, 24, 30] {
+            for _ in 0..5 {
+                let tickets = (0..count)
+                    .map(|_| random_ticket())
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let actual = serialized_size(&tickets)?;
+                let estimate = estimated_wire_size(&tickets);
+
+                assert!(
+                    actual <= estimate,
+                    "estimate {estimate} below the actual size {actual} of {count} tickets"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -4,20 +4,27 @@ use futures::FutureExt;
 use std::time::Duration;
 use tracing::{trace, warn};
 
-use hopr_async_runtime::prelude::sleep;
-use hopr_platform::time::native::current_time;
-use hopr_primitive_types::prelude::AsUnixTimestamp;
+use hopr_async_runtime::clock::{Clock, RealClock};
 
 /// Construct an infinitely running background loop producing ticks with a given period
 /// with the maximum tick duration at most the period.
 pub async fn execute_on_tick<F>(cycle: Duration, action: impl Fn() -> F, operation: String)
 where
     F: std::future::Future<Output = ()> + Send,
+{
+    execute_on_tick_with_clock(RealClock, cycle, action, operation).await
+}
+
+/// Same as [`execute_on_tick`], but measures the time using the given `clock`.
+pub async fn execute_on_tick_with_clock<C, F>(clock: C, cycle: Duration, action: impl Fn() -> F, operation: String)
+where
+    C: Clock,
+    F: std::future::Future<Output = ()> + Send,
 {
     loop {
-        let start = current_time().as_unix_timestamp();
+        let start = clock.now();
 
-        let timeout = sleep(cycle).fuse();
+        let timeout = clock.sleep(cycle).fuse();
         let todo = (action)().fuse();
 
         pin_mut!(timeout, todo);
@@ -27,13 +34,13 @@ where
             Either::Right(_) => {
                 trace!(operation, "Timer tick finished");
 
-                let action_duration = current_time().as_unix_timestamp().saturating_sub(start);
+                let action_duration = clock.now().saturating_duration_since(start);
                 if let Some(remaining) = cycle.checked_sub(action_duration) {
                     trace!(
                         remaining_time_in_ms = remaining.as_millis(),
                         "Universal timer sleeping for",
                     );
-                    sleep(remaining).await
+                    clock.sleep(remaining).await
                 }
             }
         };
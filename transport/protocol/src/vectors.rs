@@ -0,0 +1,456 @@
+//! Conformance test vectors of the `msg` and `ack` wire encodings.
+//!
+//! The vectors are produced from the fixed keypairs and payload of the [VectorFixture] and stored as hex fixtures
+//! (a single `<name>.hex` file per vector) in the [vectors directory](default_vectors_dir) of this crate:
+//! - [WRAPPED_PACKET_VECTOR]: packet wrapped by the sender for a path over a single relay
+//! - [FORWARDED_PACKET_VECTOR]: the same packet forwarded by the relay to the destination
+//! - [VALID_ACK_VECTOR]: acknowledgement of the forwarded packet sent by the destination to the relay
+//! - [RANDOM_ACK_VECTOR]: random acknowledgement, as sent by the destination when it fails to process a packet
+//!
+//! The forwarded packet and the valid acknowledgement are fully determined by the wrapped packet. Wrapping a packet
+//! and creating a random acknowledgement consume randomness, which is drawn from the RNG seeded by the seeds of the
+//! [VectorFixture] (see [`hopr_crypto_random::with_seeded_rng`]), so all the vectors are checked against the golden
+//! encodings.
+//!
+//! Setting the [REGENERATE_VECTORS_ENV] environment variable to `true` overwrites the fixtures with the current
+//! encodings instead of checking them. This must be done only if the wire encoding is changed intentionally.
+use std::path::{Path, PathBuf};
+
+use hex_literal::hex;
+use tokio_util::bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+use tracing::warn;
+
+use hopr_crypto_packet::prelude::*;
+use hopr_crypto_packet::{HoprSphinxHeaderSpec, HoprSphinxSuite, KeyIdMapper};
+use hopr_crypto_types::prelude::*;
+use hopr_internal_types::prelude::*;
+use hopr_path::TransportPath;
+use hopr_primitive_types::prelude::*;
+
+use crate::ack::AckCodec;
+
+/// Errors of producing or checking the test vectors.
+#[derive(thiserror::Error, Debug)]
+pub enum VectorError {
+    #[error("failed to access '{path}': {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("invalid hex in '{path}': {source}")]
+    InvalidHex {
+        path: PathBuf,
+        #[source]
+        source: hex::FromHexError,
+    },
+
+    #[error(
+        "encoding of '{name}' ({actual} bytes) differs from the golden vector ({expected} bytes) at byte {offset}, \
+         set {REGENERATE_VECTORS_ENV}=true to regenerate the vectors if the change is intended"
+    )]
+    Mismatch {
+        name: String,
+        actual: usize,
+        expected: usize,
+        offset: usize,
+    },
+
+    #[error("invalid vector: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Packet(#[from] hopr_crypto_packet::errors::PacketError),
+
+    #[error(transparent)]
+    Core(#[from] CoreTypesError),
+}
+
+/// Result of producing or checking the test vectors.
+pub type Result<T> = std::result::Result<T, VectorError>;
+
+/// Environment variable which, if set to `true`, makes [check_vector] overwrite the fixtures.
+pub const REGENERATE_VECTORS_ENV: &str = "HOPR_REGENERATE_TEST_VECTORS";
+
+/// Name of the vector of a packet wrapped by the sender.
+pub const WRAPPED_PACKET_VECTOR: &str = "msg_wrapped_1hop";
+
+/// Name of the vector of a packet forwarded by the relay.
+pub const FORWARDED_PACKET_VECTOR: &str = "msg_forwarded_at_relay";
+
+/// Name of the vector of a valid acknowledgement.
+pub const VALID_ACK_VECTOR: &str = "ack_valid";
+
+/// Name of the vector of a random acknowledgement.
+pub const RANDOM_ACK_VECTOR: &str = "ack_random";
+
+/// Directory with the golden test vectors of this crate.
+pub fn default_vectors_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("vectors")
+}
+
+/// Indicates whether the fixtures should be regenerated (see [REGENERATE_VECTORS_ENV]).
+pub fn regenerate_vectors() -> bool {
+    std::env::var(REGENERATE_VECTORS_ENV).is_ok_and(|v| v.to_lowercase() == "true")
+}
+
+fn vector_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.hex"))
+}
+
+/// Loads the golden vector of the given `name` from the `dir`.
+pub fn load_vector(dir: &Path, name: &str) -> Result<Box<[u8]>> {
+    let path = vector_path(dir, name);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(source) => return Err(VectorError::Io { path, source }),
+    };
+
+    hex::decode(contents.trim())
+        .map(Vec::into_boxed_slice)
+        .map_err(|source| VectorError::InvalidHex { path, source })
+}
+
+/// Stores the `data` as the golden vector of the given `name` into the `dir`.
+pub fn store_vector(dir: &Path, name: &str, data: &[u8]) -> Result<()> {
+    let path = vector_path(dir, name);
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&path, format!("{}\n", hex::encode(data))))
+        .map_err(|source| VectorError::Io { path, source })
+}
+
+/// Checks the `actual` encoding against the golden vector of the given `name`,
+/// or stores it as the golden vector if the [vectors are regenerated](regenerate_vectors).
+pub fn check_vector(dir: &Path, name: &str, actual: &[u8]) -> Result<()> {
+    if regenerate_vectors() {
+        warn!(name, "regenerating the test vector");
+        return store_vector(dir, name, actual);
+    }
+
+    let expected = load_vector(dir, name)?;
+    if expected.as_ref() != actual {
+        let offset = expected
+            .iter()
+            .zip(actual)
+            .position(|(e, a)| e != a)
+            .unwrap_or(expected.len().min(actual.len()));
+
+        return Err(VectorError::Mismatch {
+            name: name.into(),
+            actual: actual.len(),
+            expected: expected.len(),
+            offset,
+        });
+    }
+
+    Ok(())
+}
+
+/// Packet forwarded by the relay.
+#[derive(Clone)]
+pub struct ForwardedVector {
+    /// Wire encoding of the packet sent to the destination.
+    pub data: Box<[u8]>,
+    /// Challenge solved by the acknowledgement from the destination.
+    pub ack_challenge: HalfKeyChallenge,
+}
+
+/// Packet received by the destination.
+#[derive(Clone)]
+pub struct ReceivedVector {
+    /// Decrypted payload of the packet.
+    pub plain_text: Box<[u8]>,
+    /// Key share to be acknowledged to the relay.
+    pub ack_key: HalfKey,
+}
+
+/// Fixed nodes, payload and RNG seeds producing the test vectors.
+///
+/// The packet is sent by the `sender` via the `relay` to the `destination`.
+pub struct VectorFixture {
+    /// Chain and packet keys of the sender.
+    pub sender: (ChainKeypair, OffchainKeypair),
+    /// Chain and packet keys of the relay.
+    pub relay: (ChainKeypair, OffchainKeypair),
+    /// Chain and packet keys of the destination.
+    pub destination: (ChainKeypair, OffchainKeypair),
+    /// Payload of the packet.
+    pub payload: Box<[u8]>,
+    /// Pseudonym of the sender.
+    pub pseudonym: HoprPseudonym,
+    /// Domain separator the tickets are signed with.
+    pub domain_separator: Hash,
+    /// Price of the ticket issued to the relay.
+    pub ticket_price: U256,
+    /// Seed of the RNG the packet is wrapped with.
+    pub wrap_seed: [u8; 32],
+    /// Seed of the RNG the random acknowledgement is created with.
+    pub random_ack_seed: [u8; 32],
+}
+
+impl Default for VectorFixture {
+    fn default() -> Self {
+        let node = |chain_key: [u8; 32], packet_key: [u8; 32]| {
+            (
+                ChainKeypair::from_secret(&chain_key).expect("fixed chain key must be valid"),
+                OffchainKeypair::from_secret(&packet_key).expect("fixed packet key must be valid"),
+            )
+        };
+
+        Self {
+            sender: node(
+                hex!("a7c486ceccf5ab53bd428888ab1543dc2667abd2d5e80aae918da8d4b503a426"),
+                hex!("5eb212d4d6aa5948c4f71574d45dad43afef6d330edb873fca69d0e1b197e906"),
+            ),
+            relay: node(
+                hex!("9a82976f7182c05126313bead5617c623b93d11f9f9691c87b1a26f869d569ed"),
+                hex!("e995db483ada5174666c46bafbf3628005aca449c94ebdc0c9239c3f65d61ae0"),
+            ),
+            destination: node(
+                hex!("ca4bdfd54a8467b5283a0216288fdca7091122479ccf3cfb147dfa59d13f3486"),
+                hex!("9dec751c00f49e50fceff7114823f726a0425a68a8dc6af0e4287badfea8f4a4"),
+            ),
+            payload: Box::from(b"HOPR protocol conformance test vector".as_slice()),
+            pseudonym: SimplePseudonym([0x5a; SimplePseudonym::SIZE]),
+            domain_separator: Hash::create(&[b"HOPR test vectors"]),
+            ticket_price: U256::from(10_000_000_000_000_000_u128),
+            wrap_seed: hex!("3a0f6c1e9b27d85440c2e6f1a8d35b7e09f4c2a6d1e83b5f7c0a9e4d26b1f853"),
+            random_ack_seed: hex!("c81d4e7a02b95f36e1a07d4c9b3f28e56d0a1c7f4b92e38d5a6f0c1b7e24d9a3"),
+        }
+    }
+}
+
+impl VectorFixture {
+    fn nodes(&self) -> [&(ChainKeypair, OffchainKeypair); 3] {
+        [&self.sender, &self.relay, &self.destination]
+    }
+
+    /// Wraps the payload into a packet sent by the sender via the relay to the destination.
+    pub fn wrap_one_hop_packet(&self) -> Result<Box<[u8]>> {
+        hopr_crypto_random::with_seeded_rng(self.wrap_seed, || self.wrap_one_hop_packet_unseeded())
+    }
+
+    fn wrap_one_hop_packet_unseeded(&self) -> Result<Box<[u8]>> {
+        let ticket = TicketBuilder::default()
+            .direction(
+                &self.sender.0.public().to_address(),
+                &self.relay.0.public().to_address(),
+            )
+            .amount(self.ticket_price)
+            .index(1)
+            .index_offset(1)
+            .win_prob(1.0)
+            .channel_epoch(1);
+
+        let forward_path = TransportPath::new([*self.relay.1.public(), *self.destination.1.public()])
+            .map_err(|e| VectorError::Invalid(e.to_string()))?;
+
+        let (packet, _) = HoprPacket::into_outgoing(
+            &self.payload,
+            &self.pseudonym,
+            PacketRouting::ForwardPath {
+                forward_path,
+                return_paths: vec![],
+            },
+            &self.sender.0,
+            ticket,
+            self,
+            &self.domain_separator,
+        )?;
+
+        let out = packet
+            .try_as_outgoing()
+            .ok_or_else(|| VectorError::Invalid("wrapped packet must be outgoing".into()))?;
+
+        let mut data = Vec::with_capacity(HoprPacket::SIZE);
+        data.extend_from_slice(out.packet.as_ref());
+        data.extend_from_slice(&out.ticket.into_encoded());
+        Ok(data.into_boxed_slice())
+    }
+
+    /// Processes the wrapped packet at the relay, which verifies the ticket from the sender
+    /// and replaces it with its own ticket for the destination.
+    pub fn forward_at_relay(&self, wrapped: &[u8]) -> Result<ForwardedVector> {
+        let packet = HoprPacket::from_incoming(wrapped, &self.relay.1, *self.sender.1.public(), self, |_| None)?;
+
+        let mut fwd = packet
+            .try_as_forwarded()
+            .ok_or_else(|| VectorError::Invalid("packet must be forwarded by the relay".into()))?;
+
+        fwd.outgoing
+            .ticket
+            .clone()
+            .verify(&self.sender.0.public().to_address(), &self.domain_separator)
+            .map_err(|_| VectorError::Invalid("ticket from the sender must be valid".into()))?;
+
+        fwd.outgoing.ticket = TicketBuilder::zero_hop()
+            .direction(
+                &self.relay.0.public().to_address(),
+                &self.destination.0.public().to_address(),
+            )
+            .challenge(fwd.next_challenge)
+            .build_signed(&self.relay.0, &self.domain_separator)?
+            .leak();
+
+        let mut data = Vec::with_capacity(HoprPacket::SIZE);
+        data.extend_from_slice(fwd.outgoing.packet.as_ref());
+        data.extend_from_slice(&fwd.outgoing.ticket.into_encoded());
+        Ok(ForwardedVector {
+            data: data.into_boxed_slice(),
+            ack_challenge: fwd.outgoing.ack_challenge,
+        })
+    }
+
+    /// Processes the forwarded packet at the destination.
+    pub fn receive_at_destination(&self, forwarded: &[u8]) -> Result<ReceivedVector> {
+        let packet = HoprPacket::from_incoming(forwarded, &self.destination.1, *self.relay.1.public(), self, |_| None)?;
+
+        let incoming = packet
+            .try_as_final()
+            .ok_or_else(|| VectorError::Invalid("packet must be final at the destination".into()))?;
+
+        Ok(ReceivedVector {
+            plain_text: incoming.plain_text,
+            ack_key: incoming.ack_key,
+        })
+    }
+
+    /// Wire encoding of the acknowledgement of the given key share sent by the destination.
+    pub fn acknowledgement(&self, ack_key: HalfKey) -> Result<Box<[u8]>> {
+        encode_acknowledgement(Acknowledgement::new(ack_key, &self.destination.1))
+    }
+
+    /// Wire encoding of a random acknowledgement sent by the destination.
+    pub fn random_acknowledgement(&self) -> Result<Box<[u8]>> {
+        let ack =
+            hopr_crypto_random::with_seeded_rng(self.random_ack_seed, || Acknowledgement::random(&self.destination.1));
+        encode_acknowledgement(ack)
+    }
+
+    /// Decodes the acknowledgement sent by the destination and verifies its signature.
+    pub fn decode_acknowledgement(&self, data: &[u8]) -> Result<Acknowledgement> {
+        let ack = AckCodec::new()
+            .decode(&mut BytesMut::from(data))
+            .map_err(|e| VectorError::Invalid(e.to_string()))?
+            .ok_or_else(|| VectorError::Invalid("incomplete acknowledgement".into()))?;
+
+        Ok(ack.validate(self.destination.1.public())?)
+    }
+}
+
+impl KeyIdMapper<HoprSphinxSuite, HoprSphinxHeaderSpec> for VectorFixture {
+    fn map_key_to_id(&self, key: &OffchainPublicKey) -> Option<KeyIdent> {
+        self.nodes()
+            .iter()
+            .position(|(_, packet_key)| packet_key.public() == key)
+            .map(|i| KeyIdent::from(i as u32))
+    }
+
+    fn map_id_to_public(&self, id: &KeyIdent) -> Option<OffchainPublicKey> {
+        self.nodes()
+            .get(u32::from(*id) as usize)
+            .map(|(_, packet_key)| *packet_key.public())
+    }
+}
+
+fn encode_acknowledgement(ack: Acknowledgement) -> Result<Box<[u8]>> {
+    let mut buf = BytesMut::new();
+    AckCodec::new()
+        .encode(ack, &mut buf)
+        .map_err(|e| VectorError::Invalid(e.to_string()))?;
+
+    Ok(buf.to_vec().into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msg_and_ack_vectors_should_match_the_golden_encodings() -> anyhow::Result<()> {
+        let dir = default_vectors_dir();
+        let fixture = VectorFixture::default();
+
+        let wrapped = fixture.wrap_one_hop_packet()?;
+        assert_eq!(HoprPacket::SIZE, wrapped.len());
+        check_vector(&dir, WRAPPED_PACKET_VECTOR, &wrapped)?;
+
+        // Everything else is derived from the golden wrapped packet
+        let forwarded = fixture.forward_at_relay(&load_vector(&dir, WRAPPED_PACKET_VECTOR)?)?;
+        check_vector(&dir, FORWARDED_PACKET_VECTOR, &forwarded.data)?;
+
+        let received = fixture.receive_at_destination(&load_vector(&dir, FORWARDED_PACKET_VECTOR)?)?;
+        assert_eq!(fixture.payload, received.plain_text);
+
+        check_vector(&dir, VALID_ACK_VECTOR, &fixture.acknowledgement(received.ack_key)?)?;
+
+        let ack = fixture.decode_acknowledgement(&load_vector(&dir, VALID_ACK_VECTOR)?)?;
+        assert_eq!(
+            forwarded.ack_challenge,
+            ack.ack_challenge()?,
+            "valid acknowledgement must solve the challenge of the relay"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn random_ack_vector_should_match_the_golden_encoding() -> anyhow::Result<()> {
+        let dir = default_vectors_dir();
+        let fixture = VectorFixture::default();
+
+        check_vector(&dir, RANDOM_ACK_VECTOR, &fixture.random_acknowledgement()?)?;
+
+        // A random acknowledgement carries a valid signature, but does not solve the challenge of the relay
+        let ack = fixture.decode_acknowledgement(&load_vector(&dir, RANDOM_ACK_VECTOR)?)?;
+        let forwarded = fixture.forward_at_relay(&load_vector(&dir, WRAPPED_PACKET_VECTOR)?)?;
+        assert_ne!(forwarded.ack_challenge, ack.ack_challenge()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vectors_consuming_randomness_should_depend_only_on_the_seeds() -> anyhow::Result<()> {
+        let fixture = VectorFixture::default();
+        assert_eq!(fixture.wrap_one_hop_packet()?, fixture.wrap_one_hop_packet()?);
+        assert_eq!(fixture.random_acknowledgement()?, fixture.random_acknowledgement()?);
+
+        let reseeded = VectorFixture {
+            wrap_seed: [0x01; 32],
+            random_ack_seed: [0x02; 32],
+            ..VectorFixture::default()
+        };
+        assert_ne!(fixture.wrap_one_hop_packet()?, reseeded.wrap_one_hop_packet()?);
+        assert_ne!(fixture.random_acknowledgement()?, reseeded.random_acknowledgement()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_vector_should_fail_on_a_changed_encoding() -> anyhow::Result<()> {
+        if regenerate_vectors() {
+            return Ok(());
+        }
+
+        let dir = tempfile::tempdir()?;
+        store_vector(dir.path(), "test", &[1, 2, 3])?;
+
+        assert!(check_vector(dir.path(), "test", &[1, 2, 3]).is_ok());
+        assert!(matches!(
+            check_vector(dir.path(), "test", &[1, 2, 4]),
+            Err(VectorError::Mismatch { offset: 2, .. })
+        ));
+        assert!(matches!(
+            check_vector(dir.path(), "test", &[1, 2]),
+            Err(VectorError::Mismatch { offset: 2, .. })
+        ));
+        assert!(matches!(
+            check_vector(dir.path(), "missing", &[1, 2, 3]),
+            Err(VectorError::Io { .. })
+        ));
+
+        Ok(())
+    }
+}
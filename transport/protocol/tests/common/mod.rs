@@ -1,4 +1,7 @@
+#![allow(dead_code)]
+
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Context;
 use async_std::prelude::FutureExt;
@@ -24,7 +27,12 @@ use hopr_path::{channel_graph::ChannelGraph, ChainPath, Path, PathAddressResolve
 use hopr_primitive_types::prelude::*;
 use hopr_transport_mixer::config::MixerConfig;
 use hopr_transport_protocol::{
-    msg::processor::{MsgSender, PacketInteractionConfig, PacketSendFinalizer},
+    capture::WireTap,
+    msg::config::MsgProtocolConfig,
+    msg::processor::{MsgSender, PacketInteractionConfig, SendMsgInput},
+    msg::reachability::PeerReachability,
+    msg::retransmit::UnacknowledgedPacket,
+    options::ProtocolOptions,
     DEFAULT_PRICE_PER_PACKET,
 };
 use tracing::debug;
@@ -152,15 +160,84 @@ pub type WireChannels = (
 );
 
 pub type LogicalChannels = (
-    futures::channel::mpsc::UnboundedSender<(ApplicationData, ResolvedTransportRouting, PacketSendFinalizer)>,
+    futures::channel::mpsc::UnboundedSender<SendMsgInput>,
     futures::channel::mpsc::UnboundedReceiver<ApplicationData>,
 );
 
 pub type TicketChannel = futures::channel::mpsc::UnboundedReceiver<AcknowledgedTicket>;
 
+pub type ResendChannel = futures::channel::mpsc::UnboundedReceiver<UnacknowledgedPacket>;
+
 pub async fn peer_setup_for(
     count: usize,
 ) -> anyhow::Result<(Vec<WireChannels>, Vec<LogicalChannels>, Vec<TicketChannel>)> {
+    let (wire_channels, logical_channels, ticket_channels, _) = peer_setup_with_resends(count, None).await?;
+    Ok((wire_channels, logical_channels, ticket_channels))
+}
+
+/// Same as [`peer_setup_for`], but all the peers re-send unacknowledged packets with the given delay
+/// and maximum number of re-sends.
+pub async fn peer_setup_with_resends(
+    count: usize,
+    resend: Option<(std::time::Duration, u8)>,
+) -> anyhow::Result<(
+    Vec<WireChannels>,
+    Vec<LogicalChannels>,
+    Vec<TicketChannel>,
+    Vec<ResendChannel>,
+)> {
+    peer_setup_with_wire_taps(count, resend, vec![]).await
+}
+
+/// Same as [`peer_setup_with_resends`], but the ingress wire traffic of the peers is captured
+/// by the corresponding `wire_taps` (if any).
+pub async fn peer_setup_with_wire_taps(
+    count: usize,
+    resend: Option<(std::time::Duration, u8)>,
+    wire_taps: Vec<Option<WireTap>>,
+) -> anyhow::Result<(
+    Vec<WireChannels>,
+    Vec<LogicalChannels>,
+    Vec<TicketChannel>,
+    Vec<ResendChannel>,
+)> {
+    peer_setup_with_config(count, resend, wire_taps, Default::default(), None).await
+}
+
+/// Same as [`peer_setup_for`], but all the peers run the `msg` protocol with the given `msg_cfg`.
+pub async fn peer_setup_with_msg_config(
+    count: usize,
+    msg_cfg: MsgProtocolConfig,
+) -> anyhow::Result<(Vec<WireChannels>, Vec<LogicalChannels>, Vec<TicketChannel>)> {
+    let (wire_channels, logical_channels, ticket_channels, _) =
+        peer_setup_with_config(count, None, vec![], msg_cfg, None).await?;
+    Ok((wire_channels, logical_channels, ticket_channels))
+}
+
+/// Same as [`peer_setup_with_msg_config`], but all the peers consider the first hops reachable
+/// according to the given `reachability`.
+pub async fn peer_setup_with_reachability(
+    count: usize,
+    msg_cfg: MsgProtocolConfig,
+    reachability: Arc<dyn PeerReachability>,
+) -> anyhow::Result<(Vec<WireChannels>, Vec<LogicalChannels>, Vec<TicketChannel>)> {
+    let (wire_channels, logical_channels, ticket_channels, _) =
+        peer_setup_with_config(count, None, vec![], msg_cfg, Some(reachability)).await?;
+    Ok((wire_channels, logical_channels, ticket_channels))
+}
+
+async fn peer_setup_with_config(
+    count: usize,
+    resend: Option<(std::time::Duration, u8)>,
+    wire_taps: Vec<Option<WireTap>>,
+    msg_cfg: MsgProtocolConfig,
+    reachability: Option<Arc<dyn PeerReachability>>,
+) -> anyhow::Result<(
+    Vec<WireChannels>,
+    Vec<LogicalChannels>,
+    Vec<TicketChannel>,
+    Vec<ResendChannel>,
+)> {
     let peer_count = count;
 
     assert!(peer_count <= PEERS.len());
@@ -187,6 +264,7 @@ pub async fn peer_setup_for(
     let mut wire_channels = Vec::new();
     let mut logical_channels = Vec::new();
     let mut ticket_channels = Vec::new();
+    let mut resend_channels = Vec::new();
 
     for (i, db) in dbs.into_iter().enumerate().collect::<Vec<(usize, HoprDb)>>() {
         let (received_ack_tickets_tx, received_ack_tickets_rx) =
@@ -199,8 +277,7 @@ pub async fn peer_setup_for(
         let (mixer_channel_tx, mixer_channel_rx) =
             hopr_transport_mixer::channel::<(PeerId, Box<[u8]>)>(MixerConfig::default());
 
-        let (api_send_tx, api_send_rx) =
-            futures::channel::mpsc::unbounded::<(ApplicationData, ResolvedTransportRouting, PacketSendFinalizer)>();
+        let (api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
         let (api_recv_tx, api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
 
         let opk: &OffchainKeypair = &PEERS[i];
@@ -210,19 +287,33 @@ pub async fn peer_setup_for(
             chain_keypair: ock.clone(),
             outgoing_ticket_win_prob: Some(1.0),
             outgoing_ticket_price: Some(BalanceType::HOPR.balance(100)),
+            resend_unacked_after: resend.map(|(after, _)| after),
+            max_resends: resend.map(|(_, max)| max).unwrap_or_default(),
+            pricing: Default::default(),
         };
+        let (resend_tx, resend_rx) = futures::channel::mpsc::unbounded::<UnacknowledgedPacket>();
 
         db.start_ticket_processing(Some(received_ack_tickets_tx))?;
 
+        let mut options = ProtocolOptions::default().with_resend_events(resend_tx);
+        if let Some(wire_tap) = wire_taps.get(i).cloned().flatten() {
+            options = options.with_wire_tap(wire_tap);
+        }
+        if let Some(reachability) = reachability.clone() {
+            options = options.with_reachability(reachability);
+        }
+
         hopr_transport_protocol::run_msg_ack_protocol(
             packet_cfg,
+            msg_cfg,
+            Default::default(),
             db,
-            None,
             (wire_ack_recv_tx, wire_ack_send_rx),
             (mixer_channel_tx, wire_msg_send_rx),
             (api_recv_tx, api_send_rx),
+            options,
         )
-        .await;
+        .await?;
 
         wire_channels.push((
             (wire_ack_send_tx, wire_ack_recv_rx),
@@ -230,10 +321,11 @@ pub async fn peer_setup_for(
         ));
 
         logical_channels.push((api_send_tx, api_recv_rx));
-        ticket_channels.push(received_ack_tickets_rx)
+        ticket_channels.push(received_ack_tickets_rx);
+        resend_channels.push(resend_rx);
     }
 
-    Ok((wire_channels, logical_channels, ticket_channels))
+    Ok((wire_channels, logical_channels, ticket_channels, resend_channels))
 }
 
 #[tracing::instrument(level = "debug", skip(components))]
@@ -302,6 +394,10 @@ impl HoprDbResolverOperations for TestResolver {
     ) -> hopr_db_api::errors::Result<Option<Address>> {
         Ok(self.0.iter().find(|(pk, _)| pk.eq(offchain_key)).map(|(_, addr)| *addr))
     }
+
+    async fn is_announced(&self, offchain_key: &OffchainPublicKey) -> hopr_db_api::errors::Result<bool> {
+        Ok(self.0.iter().any(|(pk, _)| pk.eq(offchain_key)))
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,195 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use async_std::prelude::FutureExt;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::{SinkExt, StreamExt};
+use libp2p::PeerId;
+use serial_test::serial;
+
+use hopr_crypto_packet::prelude::HoprPacket;
+use hopr_crypto_random::{random_bytes, Randomizable};
+use hopr_crypto_types::prelude::*;
+use hopr_db_api::protocol::{AckResult, HoprDbProtocolOperations, TransportPacketWithChainData};
+use hopr_db_sql::db::HoprDb;
+use hopr_internal_types::prelude::*;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_primitive_types::prelude::*;
+use hopr_transport_protocol::{
+    heartbeat::{config::HeartbeatProtocolConfig, responder::run_heartbeat_responder},
+    msg::processor::{PacketInteractionConfig, SendMsgInput},
+    options::ProtocolOptions,
+    spawner::ProcessSpawners,
+};
+
+use common::{create_dbs, PEERS, PEERS_CHAIN};
+
+const FLOOD_PACKET_COUNT: usize = 200;
+const SLOW_DB_DELAY: Duration = Duration::from_millis(20);
+
+/// DB blocking the thread of the caller on each received packet, as if it was overloaded.
+#[derive(Debug, Clone)]
+struct SlowDb {
+    inner: HoprDb,
+    delay: Duration,
+}
+
+#[async_trait]
+impl HoprDbProtocolOperations for SlowDb {
+    async fn handle_acknowledgement(&self, ack: Acknowledgement) -> hopr_db_api::errors::Result<AckResult> {
+        self.inner.handle_acknowledgement(ack).await
+    }
+
+    async fn get_network_winning_probability(&self) -> hopr_db_api::errors::Result<f64> {
+        self.inner.get_network_winning_probability().await
+    }
+
+    async fn get_network_ticket_price(&self) -> hopr_db_api::errors::Result<Balance> {
+        self.inner.get_network_ticket_price().await
+    }
+
+    async fn to_send_no_ack(
+        &self,
+        data: Box<[u8]>,
+        destination: OffchainPublicKey,
+    ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+        self.inner.to_send_no_ack(data, destination).await
+    }
+
+    async fn to_send(
+        &self,
+        data: Box<[u8]>,
+        routing: ResolvedTransportRouting,
+        outgoing_ticket_win_prob: f64,
+        outgoing_ticket_price: Balance,
+    ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+        self.inner
+            .to_send(data, routing, outgoing_ticket_win_prob, outgoing_ticket_price)
+            .await
+    }
+
+    async fn from_recv(
+        &self,
+        data: Box<[u8]>,
+        pkt_keypair: &OffchainKeypair,
+        sender: OffchainPublicKey,
+        outgoing_ticket_win_prob: f64,
+        outgoing_ticket_price: Balance,
+    ) -> hopr_db_api::errors::Result<TransportPacketWithChainData> {
+        std::thread::sleep(self.delay);
+        self.inner
+            .from_recv(
+                data,
+                pkt_keypair,
+                sender,
+                outgoing_ticket_win_prob,
+                outgoing_ticket_price,
+            )
+            .await
+    }
+}
+
+#[serial]
+#[async_std::test]
+async fn heartbeat_pongs_should_be_sent_in_time_when_msg_ingress_is_saturated() -> anyhow::Result<()> {
+    let db = create_dbs(1).await?.remove(0);
+
+    let (_wire_ack_in_tx, wire_ack_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (wire_ack_out_tx, _wire_ack_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (mut wire_msg_in_tx, wire_msg_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+    let (wire_msg_out_tx, _wire_msg_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+    let (_api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+    let (api_recv_tx, _api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
+
+    let packet_cfg = PacketInteractionConfig {
+        packet_keypair: PEERS[0].clone(),
+        chain_keypair: PEERS_CHAIN[0].clone(),
+        outgoing_ticket_win_prob: Some(1.0),
+        outgoing_ticket_price: Some(BalanceType::HOPR.balance(100)),
+        resend_unacked_after: None,
+        max_resends: 0,
+        pricing: Default::default(),
+    };
+
+    // The ingress runs on a single dedicated thread, which the slow DB keeps blocked
+    let spawners = ProcessSpawners::default().with_ingress(|process: BoxFuture<'static, ()>| {
+        hopr_async_runtime::prelude::spawn_blocking(move || futures::executor::block_on(process))
+    });
+
+    let (_msg_ack_processes, _control) = hopr_transport_protocol::run_msg_ack_protocol(
+        packet_cfg,
+        Default::default(),
+        Default::default(),
+        SlowDb {
+            inner: db,
+            delay: SLOW_DB_DELAY,
+        },
+        (wire_ack_out_tx, wire_ack_in_rx),
+        (wire_msg_out_tx, wire_msg_in_rx),
+        (api_recv_tx, api_send_rx),
+        ProtocolOptions::default().with_spawners(spawners),
+    )
+    .await?;
+
+    let heartbeat_cfg = HeartbeatProtocolConfig::default();
+    // Bounded per direction, the same as between the swarm and the responder
+    let (mut ping_tx, ping_rx) =
+        futures::channel::mpsc::channel::<(PeerId, u64, Instant)>(heartbeat_cfg.responder_queue_size);
+    let (pong_tx, pong_rx) =
+        futures::channel::mpsc::channel::<(PeerId, u64, Instant)>(heartbeat_cfg.responder_queue_size);
+    let _heartbeat_processes =
+        run_heartbeat_responder(heartbeat_cfg, (pong_tx, ping_rx), |_, ping: u64| Some(ping + 1), None);
+
+    let flooded_at = Instant::now();
+    // Each packet comes from a different peer, so that the ingress does not back off from any of them
+    for _ in 0..FLOOD_PACKET_COUNT {
+        let peer: PeerId = OffchainKeypair::random().public().into();
+        wire_msg_in_tx
+            .send((peer, random_bytes::<{ HoprPacket::SIZE }>().into()))
+            .await?;
+    }
+
+    let ping_count: u64 = 10;
+    let pinger: PeerId = PEERS[1].public().into();
+    let pinging = async_std::task::spawn(async move {
+        let mut delivered = 0;
+        for challenge in 0..ping_count {
+            async_std::task::sleep(Duration::from_millis(50)).await;
+            // The swarm drops the pings that do not fit into the queue, rather than waiting
+            if ping_tx.try_send((pinger, challenge, Instant::now())).is_ok() {
+                delivered += 1;
+            }
+        }
+        delivered
+    });
+
+    let pongs = pong_rx
+        .take(ping_count as usize)
+        .map(|(peer, pong, sent_at)| (peer, pong, sent_at.elapsed()))
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+
+    assert!(
+        flooded_at.elapsed() < SLOW_DB_DELAY * FLOOD_PACKET_COUNT as u32,
+        "the pings must be answered while the ingress is still saturated"
+    );
+    assert_eq!(
+        ping_count,
+        pinging.await,
+        "all the pings must be delivered to the responder"
+    );
+    assert_eq!(ping_count as usize, pongs.len());
+    for (i, (peer, pong, elapsed)) in pongs.into_iter().enumerate() {
+        assert_eq!(pinger, peer);
+        assert_eq!(i as u64 + 1, pong);
+        assert!(
+            elapsed < heartbeat_cfg.responder_timeout,
+            "pong {i} took {elapsed:?}, which is over the responder timeout"
+        );
+    }
+
+    Ok(())
+}
@@ -1,8 +1,31 @@
 mod common;
 
-use common::{random_packets_of_count, send_relay_receive_channel_of_n_peers};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_std::prelude::FutureExt;
+use futures::{SinkExt, StreamExt};
+use hopr_crypto_types::keypairs::Keypair;
+use hopr_internal_types::prelude::*;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_primitive_types::prelude::*;
+use hopr_transport_protocol::errors::ProtocolError;
+use hopr_transport_protocol::msg::{
+    budget::{BufferAccounting, IngressClass},
+    config::{BufferBudget, MsgProtocolConfig},
+    processor::{MsgSender, PacketInteractionConfig, SendMsgInput},
+    reachability::PeerReachability,
+};
+use hopr_transport_protocol::options::ProtocolOptions;
+use libp2p::PeerId;
 use serial_test::serial;
 
+use common::{
+    create_dbs, create_minimal_topology, peer_setup_for, peer_setup_with_msg_config, peer_setup_with_reachability,
+    random_packets_of_count, resolve_mock_path, send_relay_receive_channel_of_n_peers, PEERS, PEERS_CHAIN,
+};
+
 #[serial]
 #[async_std::test]
 // #[tracing_test::traced_test]
@@ -20,3 +43,276 @@ async fn test_packet_relayer_workflow_5_peers() -> anyhow::Result<()> {
 
     send_relay_receive_channel_of_n_peers(5, packets).await
 }
+
+#[serial]
+#[async_std::test]
+async fn test_sender_should_be_notified_when_the_first_hop_is_unreachable() -> anyhow::Result<()> {
+    let msg_cfg = MsgProtocolConfig {
+        finalize_after_wire_send: true,
+        ..Default::default()
+    };
+    let (mut wire_apis, apis, _) = peer_setup_with_msg_config(3, msg_cfg).await?;
+
+    let routing = ResolvedTransportRouting::forward_only(
+        resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            vec![*PEERS[1].public()],
+            vec![PEERS_CHAIN[1].public().to_address()],
+        )
+        .await?,
+    );
+    let sender = MsgSender::new(apis[0].0.clone());
+
+    // The wire from the first node into the ingress of the second one
+    let (_ack_wire, (_msg_in, mut msg_out)) = wire_apis.remove(0);
+
+    // While the ingress is open, the send is finalized once the packet is on the wire
+    sender
+        .send_packet(random_packets_of_count(1).remove(0), routing.clone())
+        .await?
+        .consume_and_wait(Duration::from_secs(5))
+        .await?;
+
+    let (next_hop, _) = msg_out
+        .next()
+        .timeout(Duration::from_secs(5))
+        .await?
+        .context("the packet must be handed over to the wire")?;
+    assert_eq!(PeerId::from(PEERS[1].public()), next_hop);
+
+    // Once the ingress is closed, the sender receives the failure
+    drop(msg_out);
+
+    let res = sender
+        .send_packet(random_packets_of_count(1).remove(0), routing)
+        .await?
+        .consume_and_wait(Duration::from_secs(5))
+        .await;
+
+    assert!(
+        matches!(res, Err(ProtocolError::FirstHopUnreachable(peer)) if peer == next_hop),
+        "unexpected result: {res:?}"
+    );
+
+    Ok(())
+}
+
+/// Considers only the given peers unreachable.
+#[derive(Debug)]
+struct UnreachablePeers(Vec<PeerId>);
+
+#[async_trait::async_trait]
+impl PeerReachability for UnreachablePeers {
+    async fn is_reachable(&self, peer: &PeerId) -> bool {
+        !self.0.contains(peer)
+    }
+}
+
+#[serial]
+#[async_std::test]
+async fn test_sender_should_be_rejected_before_sending_when_the_first_hop_is_not_reachable() -> anyhow::Result<()> {
+    let first_hop = PeerId::from(PEERS[1].public());
+    let (mut wire_apis, apis, _) = peer_setup_with_reachability(
+        3,
+        MsgProtocolConfig::default(),
+        Arc::new(UnreachablePeers(vec![first_hop])),
+    )
+    .await?;
+
+    let routing = ResolvedTransportRouting::forward_only(
+        resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            vec![*PEERS[1].public()],
+            vec![PEERS_CHAIN[1].public().to_address()],
+        )
+        .await?,
+    );
+    let res = MsgSender::new(apis[0].0.clone())
+        .send_packet(random_packets_of_count(1).remove(0), routing)
+        .await?
+        .consume_and_wait(Duration::from_secs(5))
+        .await;
+
+    assert!(
+        matches!(res, Err(ProtocolError::FirstHopUnreachable(peer)) if peer == first_hop),
+        "unexpected result: {res:?}"
+    );
+
+    let (_, (_, mut msg_out)) = wire_apis.remove(0);
+    assert!(
+        msg_out.next().timeout(Duration::from_millis(200)).await.is_err(),
+        "the packet must not be sent to an unreachable first hop"
+    );
+
+    Ok(())
+}
+
+#[serial]
+#[async_std::test]
+async fn test_relayer_should_cap_the_buffered_bytes_and_acknowledge_only_the_packets_handed_over_to_a_stalled_wire(
+) -> anyhow::Result<()> {
+    const PACKET_COUNT: usize = 6;
+    const BUFFERED_PACKETS: usize = 2;
+
+    // The packets of the sender are taken off its wire and fed into a separate relayer
+    let (mut wire_apis, apis, _) = peer_setup_for(3).await?;
+    let routing = ResolvedTransportRouting::forward_only(
+        resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            PEERS[1..3].iter().map(|p| *p.public()).collect(),
+            PEERS_CHAIN[1..3].iter().map(|k| k.public().to_address()).collect(),
+        )
+        .await?,
+    );
+    let sender = MsgSender::new(apis[0].0.clone());
+    for data in random_packets_of_count(PACKET_COUNT) {
+        sender
+            .send_packet(data, routing.clone())
+            .await?
+            .consume_and_wait(Duration::from_secs(5))
+            .await?;
+    }
+    let (_, (_, sender_wire)) = wire_apis.remove(0);
+    let packets = sender_wire
+        .take(PACKET_COUNT)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    let packet_size = packets.first().context("packet should be present")?.1.len();
+
+    let mut dbs = create_dbs(3).await?;
+    create_minimal_topology(&mut dbs).await?;
+
+    let (_wire_ack_in_tx, wire_ack_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (wire_ack_out_tx, mut wire_ack_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (mut wire_msg_in_tx, wire_msg_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+    let (_api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+    let (api_recv_tx, _api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
+
+    // The wire sink is full and does not make any progress until it is drained
+    let (mut wire_msg_out_tx, mut wire_msg_out_rx) = futures::channel::mpsc::channel::<(PeerId, Box<[u8]>)>(0);
+    wire_msg_out_tx.try_send((PeerId::random(), Box::default()))?;
+
+    let accounting = BufferAccounting::new(BufferBudget {
+        max_bytes: 2 * BUFFERED_PACKETS * packet_size,
+        relay_percent: 50,
+    });
+    let packet_cfg = PacketInteractionConfig {
+        packet_keypair: PEERS[1].clone(),
+        chain_keypair: PEERS_CHAIN[1].clone(),
+        outgoing_ticket_win_prob: Some(1.0),
+        outgoing_ticket_price: Some(BalanceType::HOPR.balance(100)),
+        resend_unacked_after: None,
+        max_resends: 0,
+        pricing: Default::default(),
+    };
+    let (_processes, _control) = hopr_transport_protocol::run_msg_ack_protocol(
+        packet_cfg,
+        Default::default(),
+        Default::default(),
+        dbs.remove(1),
+        (wire_ack_out_tx, wire_ack_in_rx),
+        (wire_msg_out_tx, wire_msg_in_rx),
+        (api_recv_tx, api_send_rx),
+        ProtocolOptions::default().with_buffer_accounting(accounting.clone()),
+    )
+    .await?;
+
+    for (_, data) in packets {
+        wire_msg_in_tx.send((PEERS[0].public().into(), data)).await?;
+    }
+
+    // Only the packets dropped over the budget are acknowledged right away
+    let dropped_acks = wire_ack_out_rx
+        .by_ref()
+        .take(PACKET_COUNT - BUFFERED_PACKETS)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    assert_eq!(PACKET_COUNT - BUFFERED_PACKETS, dropped_acks.len());
+    assert!(
+        wire_ack_out_rx
+            .next()
+            .timeout(Duration::from_millis(200))
+            .await
+            .is_err(),
+        "the packets waiting for the wire must not be acknowledged"
+    );
+    assert_eq!(BUFFERED_PACKETS * packet_size, accounting.buffered_bytes());
+    assert_eq!(
+        (PACKET_COUNT - BUFFERED_PACKETS) as u64,
+        accounting.dropped(IngressClass::Relay)
+    );
+
+    // Once the wire makes progress, the buffered packets are relayed and acknowledged
+    wire_msg_out_rx
+        .next()
+        .await
+        .context("stalling item should be present")?;
+    let relayed = wire_msg_out_rx
+        .by_ref()
+        .take(BUFFERED_PACKETS)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    assert!(relayed
+        .iter()
+        .all(|(next_hop, _)| *next_hop == PeerId::from(PEERS[2].public())));
+
+    let relayed_acks = wire_ack_out_rx
+        .take(BUFFERED_PACKETS)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    assert_eq!(BUFFERED_PACKETS, relayed_acks.len());
+
+    Ok(())
+}
+
+#[serial]
+#[async_std::test]
+async fn test_protocol_should_not_start_with_the_bloom_filter_of_another_packet_key() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tbf").to_string_lossy().to_string();
+
+    // The filter persisted by another transport instance
+    hopr_transport_protocol::bloom::WrappedTagBloomFilter::new_namespaced(
+        path.clone(),
+        &PEERS[0].public().to_peerid_str(),
+    )?
+    .save()
+    .await;
+
+    let mut dbs = create_dbs(2).await?;
+    let (wire_ack_out_tx, _wire_ack_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (_wire_ack_in_tx, wire_ack_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Acknowledgement)>();
+    let (wire_msg_out_tx, _wire_msg_out_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+    let (_wire_msg_in_tx, wire_msg_in_rx) = futures::channel::mpsc::unbounded::<(PeerId, Box<[u8]>)>();
+    let (_api_send_tx, api_send_rx) = futures::channel::mpsc::unbounded::<SendMsgInput>();
+    let (api_recv_tx, _api_recv_rx) = futures::channel::mpsc::unbounded::<ApplicationData>();
+    let packet_cfg = PacketInteractionConfig {
+        packet_keypair: PEERS[1].clone(),
+        chain_keypair: PEERS_CHAIN[1].clone(),
+        outgoing_ticket_win_prob: Some(1.0),
+        outgoing_ticket_price: Some(BalanceType::HOPR.balance(100)),
+        resend_unacked_after: None,
+        max_resends: 0,
+        pricing: Default::default(),
+    };
+
+    let started = hopr_transport_protocol::run_msg_ack_protocol(
+        packet_cfg,
+        Default::default(),
+        Default::default(),
+        dbs.remove(1),
+        (wire_ack_out_tx, wire_ack_in_rx),
+        (wire_msg_out_tx, wire_msg_in_rx),
+        (api_recv_tx, api_send_rx),
+        ProtocolOptions::default().with_bloom_filter_persistence(path),
+    )
+    .await;
+
+    assert!(matches!(started, Err(ProtocolError::BloomNamespaceMismatch { .. })));
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use hopr_crypto_types::keypairs::Keypair;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_transport_protocol::probe::{PathProber, ProbeResult};
+use serial_test::serial;
+
+use common::{peer_setup_for, resolve_mock_path, WireChannels, PEERS, PEERS_CHAIN};
+
+const WIRE_DELAY: Duration = Duration::from_millis(50);
+
+/// Connects the first two nodes directly over the emulated wire.
+///
+/// The messages of the second node towards the first one are only delivered when `loop_back` is set.
+fn connect_two_nodes(mut wire: Vec<WireChannels>, loop_back: bool) {
+    let ((mut ack_in_1, mut ack_out_1), (mut msg_in_1, mut msg_out_1)) = wire.remove(1);
+    let ((mut ack_in_0, mut ack_out_0), (mut msg_in_0, mut msg_out_0)) = wire.remove(0);
+
+    async_std::task::spawn(async move {
+        while let Some((_, data)) = msg_out_0.next().await {
+            async_std::task::sleep(WIRE_DELAY).await;
+            let _ = msg_in_1.send((PEERS[0].public().into(), data)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, data)) = msg_out_1.next().await {
+            if loop_back {
+                async_std::task::sleep(WIRE_DELAY).await;
+                let _ = msg_in_0.send((PEERS[1].public().into(), data)).await;
+            }
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, ack)) = ack_out_0.next().await {
+            let _ = ack_in_1.send((PEERS[0].public().into(), ack)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, ack)) = ack_out_1.next().await {
+            let _ = ack_in_0.send((PEERS[1].public().into(), ack)).await;
+        }
+    });
+}
+
+async fn probe_one_hop_loop(loop_back: bool, timeout: Duration) -> anyhow::Result<ProbeResult> {
+    let (wire_apis, mut apis, _ticket_channels) = peer_setup_for(3).await?;
+    connect_two_nodes(wire_apis, loop_back);
+
+    let (api_send, api_recv) = apis.remove(0);
+    let prober = PathProber::new(*PEERS[0].public(), api_send);
+
+    let prober_clone = prober.clone();
+    async_std::task::spawn(async move {
+        api_recv
+            .filter_map(|data| futures::future::ready(prober_clone.intercept(data)))
+            .for_each(|data| async move { panic!("unexpected non-probe data received: {data}") })
+            .await;
+    });
+
+    let loop_path = resolve_mock_path(
+        PEERS_CHAIN[0].public().to_address(),
+        vec![*PEERS[1].public(), *PEERS[0].public()],
+        vec![
+            PEERS_CHAIN[1].public().to_address(),
+            PEERS_CHAIN[0].public().to_address(),
+        ],
+    )
+    .await?;
+
+    Ok(prober
+        .probe(ResolvedTransportRouting::forward_only(loop_path), timeout)
+        .await?)
+}
+
+#[serial]
+#[async_std::test]
+async fn test_probe_should_measure_rtt_of_1_hop_loop() -> anyhow::Result<()> {
+    let timeout = Duration::from_secs(5);
+
+    match probe_one_hop_loop(true, timeout).await? {
+        ProbeResult::Returned { rtt } => {
+            assert!(rtt >= 2 * WIRE_DELAY, "rtt must include the delay on both hops");
+            assert!(rtt < timeout, "rtt must be within the timeout");
+        }
+        ProbeResult::Timeout => panic!("probe must return back"),
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[async_std::test]
+async fn test_probe_should_time_out_when_loop_is_broken() -> anyhow::Result<()> {
+    assert_eq!(
+        ProbeResult::Timeout,
+        probe_one_hop_loop(false, Duration::from_millis(500)).await?
+    );
+
+    Ok(())
+}
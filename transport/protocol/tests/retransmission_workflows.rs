@@ -0,0 +1,117 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use hopr_crypto_types::keypairs::Keypair;
+use hopr_internal_types::protocol::ApplicationData;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_transport_protocol::msg::processor::MsgSender;
+use serial_test::serial;
+
+use common::{peer_setup_with_resends, resolve_mock_path, WireChannels, PEERS, PEERS_CHAIN};
+
+const RESEND_AFTER: Duration = Duration::from_millis(300);
+
+/// Connects the first two nodes directly over the emulated wire, dropping the given number of the first
+/// acknowledgements sent from the second node to the first one.
+///
+/// Returns the counter of the packets sent by the first node.
+fn connect_two_nodes(mut wire: Vec<WireChannels>, dropped_acks: usize) -> Arc<AtomicUsize> {
+    let ((mut ack_in_1, mut ack_out_1), (mut msg_in_1, _)) = wire.remove(1);
+    let ((mut ack_in_0, mut ack_out_0), (_, mut msg_out_0)) = wire.remove(0);
+
+    let sent = Arc::new(AtomicUsize::new(0));
+    let sent_clone = sent.clone();
+
+    async_std::task::spawn(async move {
+        while let Some((_, data)) = msg_out_0.next().await {
+            sent_clone.fetch_add(1, Ordering::SeqCst);
+            let _ = msg_in_1.send((PEERS[0].public().into(), data)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, ack)) = ack_out_0.next().await {
+            let _ = ack_in_1.send((PEERS[0].public().into(), ack)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        let mut ack_count = 0;
+        while let Some((_, ack)) = ack_out_1.next().await {
+            ack_count += 1;
+            if ack_count > dropped_acks {
+                let _ = ack_in_0.send((PEERS[1].public().into(), ack)).await;
+            }
+        }
+    });
+
+    sent
+}
+
+async fn direct_routing() -> anyhow::Result<ResolvedTransportRouting> {
+    Ok(ResolvedTransportRouting::forward_only(
+        resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            vec![*PEERS[1].public()],
+            vec![PEERS_CHAIN[1].public().to_address()],
+        )
+        .await?,
+    ))
+}
+
+#[serial]
+#[async_std::test]
+async fn test_packet_should_be_resent_when_acknowledgement_is_lost() -> anyhow::Result<()> {
+    let (wire_apis, mut apis, _ticket_channels, mut resend_channels) =
+        peer_setup_with_resends(3, Some((RESEND_AFTER, 3))).await?;
+    let sent = connect_two_nodes(wire_apis, 1);
+
+    let (_, mut api_recv_1) = apis.remove(1);
+    let data = ApplicationData::new(1024, &[1, 2, 3]);
+
+    MsgSender::new(apis[0].0.clone())
+        .send_packet(data.clone(), direct_routing().await?)
+        .await?
+        .consume_and_wait(Duration::from_secs(1))
+        .await?;
+
+    async_std::task::sleep(5 * RESEND_AFTER).await;
+
+    assert_eq!(2, sent.load(Ordering::SeqCst), "packet must be re-sent exactly once");
+    assert_eq!(Some(data.clone()), api_recv_1.next().await);
+    assert_eq!(Some(data), api_recv_1.next().await, "re-sent packet must be delivered");
+    assert!(
+        resend_channels[0].try_next().is_err(),
+        "acknowledged packet must not be given up"
+    );
+
+    Ok(())
+}
+
+#[serial]
+#[async_std::test]
+async fn test_packet_should_be_given_up_after_max_resends() -> anyhow::Result<()> {
+    let max_resends = 2;
+    let (wire_apis, apis, _ticket_channels, mut resend_channels) =
+        peer_setup_with_resends(3, Some((RESEND_AFTER, max_resends))).await?;
+    let sent = connect_two_nodes(wire_apis, usize::MAX);
+
+    let data = ApplicationData::new(1024, &[1, 2, 3]);
+    MsgSender::new(apis[0].0.clone())
+        .send_packet(data.clone(), direct_routing().await?)
+        .await?
+        .consume_and_wait(Duration::from_secs(1))
+        .await?;
+
+    let unacknowledged = async_std::future::timeout(10 * RESEND_AFTER, resend_channels.remove(0).next())
+        .await?
+        .expect("give-up event must be emitted");
+
+    assert_eq!(data, unacknowledged.data);
+    assert_eq!(max_resends, unacknowledged.resends);
+    assert_eq!(1 + max_resends as usize, sent.load(Ordering::SeqCst));
+
+    Ok(())
+}
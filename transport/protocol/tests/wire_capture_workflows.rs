@@ -0,0 +1,143 @@
+mod common;
+
+use std::time::Duration;
+
+use async_std::prelude::FutureExt;
+use futures::{SinkExt, StreamExt};
+use hopr_crypto_types::keypairs::Keypair;
+use hopr_internal_types::protocol::ApplicationData;
+use hopr_network_types::prelude::ResolvedTransportRouting;
+use hopr_transport_protocol::capture::{read_capture, replay_capture, ReplayPacing, WireDirection, WireTap};
+use hopr_transport_protocol::msg::processor::MsgSender;
+use libp2p::PeerId;
+use serial_test::serial;
+
+use common::{
+    peer_setup_with_resends, peer_setup_with_wire_taps, random_packets_of_count, resolve_mock_path, WireChannels,
+    PEERS, PEERS_CHAIN,
+};
+
+const PACKET_COUNT: usize = 3;
+
+/// Connects the first two nodes directly over the emulated wire.
+fn connect_two_nodes(mut wire: Vec<WireChannels>) {
+    let ((mut ack_in_1, mut ack_out_1), (mut msg_in_1, _)) = wire.remove(1);
+    let ((mut ack_in_0, mut ack_out_0), (_, mut msg_out_0)) = wire.remove(0);
+
+    async_std::task::spawn(async move {
+        while let Some((_, data)) = msg_out_0.next().await {
+            let _ = msg_in_1.send((PEERS[0].public().into(), data)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, ack)) = ack_out_0.next().await {
+            let _ = ack_in_1.send((PEERS[0].public().into(), ack)).await;
+        }
+    });
+    async_std::task::spawn(async move {
+        while let Some((_, ack)) = ack_out_1.next().await {
+            let _ = ack_in_0.send((PEERS[1].public().into(), ack)).await;
+        }
+    });
+}
+
+async fn direct_routing() -> anyhow::Result<ResolvedTransportRouting> {
+    Ok(ResolvedTransportRouting::forward_only(
+        resolve_mock_path(
+            PEERS_CHAIN[0].public().to_address(),
+            vec![*PEERS[1].public()],
+            vec![PEERS_CHAIN[1].public().to_address()],
+        )
+        .await?,
+    ))
+}
+
+fn sorted(mut packets: Vec<ApplicationData>) -> Vec<ApplicationData> {
+    packets.sort_by(|a, b| a.plain_text.cmp(&b.plain_text));
+    packets
+}
+
+#[serial]
+#[async_std::test]
+async fn test_captured_wire_traffic_should_be_replayed_into_another_pipeline() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let sender_capture = dir.path().join("sender.capture").to_string_lossy().into_owned();
+    let recipient_capture = dir.path().join("recipient.capture").to_string_lossy().into_owned();
+    let recipient_tap = WireTap::new(&recipient_capture)?;
+
+    let (wire_apis, mut apis, _ticket_channels, _resend_channels) = peer_setup_with_wire_taps(
+        3,
+        None,
+        vec![Some(WireTap::new(&sender_capture)?), Some(recipient_tap.clone())],
+    )
+    .await?;
+    connect_two_nodes(wire_apis);
+
+    let packets = random_packets_of_count(PACKET_COUNT);
+    for data in packets.iter().cloned() {
+        MsgSender::new(apis[0].0.clone())
+            .send_packet(data, direct_routing().await?)
+            .await?
+            .consume_and_wait(Duration::from_secs(1))
+            .await?;
+    }
+
+    let received = apis[1]
+        .1
+        .by_ref()
+        .take(PACKET_COUNT)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    assert_eq!(sorted(packets.clone()), sorted(received));
+
+    // The acknowledgements are delivered to the sender asynchronously
+    let captured_acks = async {
+        loop {
+            let items = read_capture(&sender_capture)?;
+            if items.len() >= PACKET_COUNT {
+                break Ok::<_, anyhow::Error>(items);
+            }
+            async_std::task::sleep(Duration::from_millis(10)).await;
+        }
+    }
+    .timeout(Duration::from_secs(5))
+    .await??;
+    let recipient: PeerId = PEERS[1].public().into();
+    assert_eq!(PACKET_COUNT, captured_acks.len());
+    assert!(captured_acks
+        .iter()
+        .all(|item| item.direction == WireDirection::AckIn && item.peer == recipient));
+
+    recipient_tap.flush().await;
+    let captured_msgs = read_capture(&recipient_capture)?;
+    assert_eq!(PACKET_COUNT, captured_msgs.len());
+    let sender: PeerId = PEERS[0].public().into();
+    assert!(captured_msgs
+        .iter()
+        .all(|item| item.direction == WireDirection::MsgIn && item.peer == sender));
+
+    // Replay the capture of the recipient into a new pipeline with the same keys and DB contents
+    let (mut wire_apis, mut apis, _ticket_channels, _resend_channels) = peer_setup_with_resends(3, None).await?;
+    let ((ack_in, _ack_out), (msg_in, _msg_out)) = wire_apis.remove(1);
+
+    assert_eq!(
+        PACKET_COUNT,
+        replay_capture(&recipient_capture, ReplayPacing::AsFastAsPossible, (ack_in, msg_in)).await?
+    );
+
+    let replayed = apis
+        .remove(1)
+        .1
+        .take(PACKET_COUNT)
+        .collect::<Vec<_>>()
+        .timeout(Duration::from_secs(5))
+        .await?;
+    assert_eq!(
+        sorted(packets),
+        sorted(replayed),
+        "replayed packets must be delivered again"
+    );
+
+    Ok(())
+}